@@ -1,6 +1,6 @@
 //! Curves that are commonly useful when defining interesting game mechanics.
 
-use bevy::math::Vec2;
+use crate::bevy::math::Vec2;
 
 /// A type which maps from an input value to an output value that lies on a curve.
 pub trait Mapping {
@@ -184,7 +184,7 @@ pub struct Sigmoid {
     horizontal_scale: f32,
     /// Defines the asymptotic minimum (output value attained at `-infinity`) produced by this sigmoid.
     vertical_offset: f32,
-    /// Defines which input value produces an output of `0.5 * (`asymptotic_max` + `asymptotic_min`)
+    /// Defines which input value produces an output of `0.5 * (asymptotic_max + asymptotic_min)`
     /// where `asymptotic_max` is the output value produced at `+infinity` and `asymptotic_min`
     /// is the output value produced at `-infinity`.
     horizontal_offset: f32,
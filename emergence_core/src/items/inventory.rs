@@ -2,12 +2,14 @@
 
 use std::fmt::Display;
 
-use crate::asset_management::manifest::{Id, Item, ItemManifest};
+use crate::bevy::utils::{HashMap, HashSet};
+
+use crate::manifest::{Id, Item, ItemManifest};
 
 use super::{
     errors::{
-        AddManyItemsError, AddOneItemError, ItemTransferError, RemoveManyItemsError,
-        RemoveOneItemError,
+        AddManyItemsError, AddOneItemError, FilteredItemError, ItemTransferError,
+        RemoveManyItemsError, RemoveOneItemError,
     },
     slot::ItemSlot,
     ItemCount,
@@ -15,7 +17,7 @@ use super::{
 
 /// An inventory to store multiple types of items.
 #[derive(Debug, Default, Clone)]
-pub(crate) struct Inventory {
+pub struct Inventory {
     /// The item slots that are currently active.
     ///
     /// `slots.len() <= max_slot_count` is guaranteed.
@@ -23,11 +25,40 @@ pub(crate) struct Inventory {
 
     /// The maximum number of item slots this inventory can hold.
     max_slot_count: usize,
+
+    /// Which items may be stored in this inventory.
+    filter: ItemFilter,
+
+    /// Stack sizes that override the default [`ItemData::stack_size`](super::ItemData::stack_size) of an item.
+    stack_limits: HashMap<Id<Item>, usize>,
+}
+
+/// Which items an [`Inventory`] is willing to hold.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ItemFilter {
+    /// Any item may be stored.
+    #[default]
+    AllowAll,
+    /// Only the listed items may be stored.
+    Allow(HashSet<Id<Item>>),
+    /// Every item except the listed ones may be stored.
+    Deny(HashSet<Id<Item>>),
+}
+
+impl ItemFilter {
+    /// Can `item_id` pass through this filter?
+    pub fn accepts(&self, item_id: Id<Item>) -> bool {
+        match self {
+            ItemFilter::AllowAll => true,
+            ItemFilter::Allow(allowed) => allowed.contains(&item_id),
+            ItemFilter::Deny(denied) => !denied.contains(&item_id),
+        }
+    }
 }
 
 /// The fullness of an inventory
 #[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
-pub(crate) enum InventoryState {
+pub enum InventoryState {
     /// Fully empty.
     Empty,
     /// Neither empty nor full.
@@ -56,34 +87,85 @@ impl InventoryState {
 #[allow(dead_code)]
 impl Inventory {
     /// Create an empty inventory with the given amount of slots.
-    pub(crate) fn new(max_slot_count: usize) -> Self {
+    pub fn new(max_slot_count: usize) -> Self {
         Self {
             slots: Vec::new(),
             max_slot_count,
+            ..Default::default()
         }
     }
 
     // FIXME: this doesn't properly respect max stack size
     /// Creates an inventory from the provided [`ItemCount`].
-    pub(crate) fn new_from_item(item_count: ItemCount) -> Self {
+    pub fn new_from_item(item_count: ItemCount) -> Self {
         Self {
             slots: vec![ItemSlot::new(item_count.item_id, item_count.count)],
             max_slot_count: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Creates an inventory with a slot for each of the provided [`ItemCount`]s, each sized to fit exactly that many items.
+    pub fn new_from_items(item_counts: &[ItemCount]) -> Self {
+        Self {
+            slots: item_counts
+                .iter()
+                .map(|item_count| ItemSlot::new(item_count.item_id, item_count.count))
+                .collect(),
+            max_slot_count: item_counts.len(),
+            ..Default::default()
+        }
+    }
+
+    /// Restricts which items can be stored in this inventory.
+    ///
+    /// Items that are already stored are kept, even if they no longer pass the filter.
+    pub fn with_filter(mut self, filter: ItemFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Lets `item_id` pass through the [`ItemFilter`] of this inventory, if it could not already.
+    pub fn allow_item(&mut self, item_id: Id<Item>) {
+        match &mut self.filter {
+            ItemFilter::AllowAll => (),
+            ItemFilter::Allow(allowed) => {
+                allowed.insert(item_id);
+            }
+            ItemFilter::Deny(denied) => {
+                denied.remove(&item_id);
+            }
+        }
+    }
+
+    /// Limits the number of `item_id` that fit in a single slot, overriding its usual stack size.
+    ///
+    /// Only slots created after this is set are affected.
+    pub fn with_stack_limit(mut self, item_id: Id<Item>, stack_limit: usize) -> Self {
+        self.stack_limits.insert(item_id, stack_limit);
+        self
+    }
+
+    /// The number of `item_id` that fit in a single slot of this inventory.
+    pub fn stack_size(&self, item_id: Id<Item>, item_manifest: &ItemManifest) -> usize {
+        match self.stack_limits.get(&item_id) {
+            Some(&stack_limit) => stack_limit,
+            None => item_manifest.get(item_id).stack_size(),
         }
     }
 
     /// Returns an iterator over the items in the inventory and their count.
-    pub(crate) fn iter(&self) -> impl Iterator<Item = &ItemSlot> {
+    pub fn iter(&self) -> impl Iterator<Item = &ItemSlot> {
         self.slots.iter()
     }
 
     /// Returns a mutable iterator over the contained item slots.
-    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut ItemSlot> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ItemSlot> {
         self.slots.iter_mut()
     }
 
     /// How full is this inventory?
-    pub(crate) fn state(&self) -> InventoryState {
+    pub fn state(&self) -> InventoryState {
         let mut inventory_state: Option<InventoryState> = None;
 
         for item_slot in self.iter() {
@@ -103,7 +185,7 @@ impl Inventory {
     }
 
     /// Determine how many items of the given type are in the inventory.
-    pub(crate) fn item_count(&self, item_id: Id<Item>) -> usize {
+    pub fn item_count(&self, item_id: Id<Item>) -> usize {
         self.slots
             .iter()
             .filter_map(|slot| {
@@ -117,27 +199,27 @@ impl Inventory {
     }
 
     /// Determine if the inventory holds enough of the given item.
-    pub(crate) fn has_count_of_item(&self, item_count: &ItemCount) -> bool {
+    pub fn has_count_of_item(&self, item_count: &ItemCount) -> bool {
         self.item_count(item_count.item_id()) >= item_count.count()
     }
 
     /// Returns `true` if there are no items in the inventory.
-    pub(crate) fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.slots.iter().all(|slot| slot.is_empty())
     }
 
     /// Returns `true` if all slots are filled to their capacity.
-    pub(crate) fn is_full(&self) -> bool {
+    pub fn is_full(&self) -> bool {
         self.slots.len() == self.max_slot_count && self.slots.iter().all(|slot| slot.is_full())
     }
 
     /// The number of slots that don't have an item in them.
-    pub(crate) fn free_slot_count(&self) -> usize {
+    pub fn free_slot_count(&self) -> usize {
         self.max_slot_count - self.slots.len()
     }
 
     /// The remaining space for the item in the slots that it already occupies.
-    pub(crate) fn remaining_reserved_space_for_item(&self, item_id: Id<Item>) -> usize {
+    pub fn remaining_reserved_space_for_item(&self, item_id: Id<Item>) -> usize {
         self.slots
             .iter()
             .filter_map(|slot| {
@@ -151,22 +233,26 @@ impl Inventory {
     }
 
     /// The number of items of the given type that can still fit in the inventory.
-    pub(crate) fn remaining_space_for_item(
+    pub fn remaining_space_for_item(
         &self,
         item_id: Id<Item>,
         item_manifest: &ItemManifest,
     ) -> usize {
+        if !self.filter.accepts(item_id) {
+            return 0;
+        }
+
         // We can fill up the remaining space in the slots for this item...
         self.remaining_reserved_space_for_item(item_id)
             // ...and use up the remaining free slots
-            + self.free_slot_count() * item_manifest.get(item_id).stack_size()
+            + self.free_slot_count() * self.stack_size(item_id, item_manifest)
     }
 
     /// Clears any inventory stacks with 0 items in them.
     ///
     /// This is the standard behavior for units and storages, but not for crafting.
     /// In those cases, the slots should persist with 0 items.
-    pub(crate) fn clear_empty_slots(&mut self) {
+    pub fn clear_empty_slots(&mut self) {
         let mut slots_to_clear: Vec<usize> = Vec::with_capacity(self.max_slot_count);
 
         for (i, slot) in self.slots.iter().enumerate() {
@@ -182,19 +268,27 @@ impl Inventory {
 
     /// Adds an empty slot that is reserved for the provided `item_id`.
     ///
-    /// This operation is infallible: if there are not enough slots available, the inventory size will be expanded.
-    pub(crate) fn add_empty_slot(&mut self, item_id: Id<Item>, item_manifest: &ItemManifest) {
-        let n_existing_slots = self.slots.len();
-        let slot_to_use = n_existing_slots + 1;
-        let stack_size = item_manifest.get(item_id).stack_size();
-        let empty_stack = ItemSlot::new(item_id, stack_size);
-
-        if slot_to_use >= self.max_slot_count {
-            self.max_slot_count = slot_to_use;
-            self.slots.push(empty_stack);
-        } else {
-            self.slots[slot_to_use] = empty_stack
+    /// If there are no free slots left, the inventory size will be expanded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without adding a slot, if `item_id` does not pass the [`ItemFilter`] of this inventory.
+    pub fn add_empty_slot(
+        &mut self,
+        item_id: Id<Item>,
+        item_manifest: &ItemManifest,
+    ) -> Result<(), FilteredItemError> {
+        if !self.filter.accepts(item_id) {
+            return Err(FilteredItemError { item_id });
         }
+
+        let stack_size = self.stack_size(item_id, item_manifest);
+        if self.slots.len() == self.max_slot_count {
+            self.max_slot_count += 1;
+        }
+        self.slots.push(ItemSlot::new(item_id, stack_size));
+
+        Ok(())
     }
 
     /// Try to add as many items to the inventory as possible, up to the given count.
@@ -204,14 +298,22 @@ impl Inventory {
     /// - If all items can fit in the inventory, they are all added and `Ok` is returned.
     /// - Otherwise, all items that can fit are added and `Err` is returned.
     ///
+    /// Items that do not pass the [`ItemFilter`] of this inventory are never added.
+    ///
     /// # Warning
     ///
     /// Adding 0 of an item will not create an empty slot. Instead, use [`Inventory::add_empty_slot`].
-    pub(crate) fn try_add_item(
+    pub fn try_add_item(
         &mut self,
         item_count: &ItemCount,
         item_manifest: &ItemManifest,
     ) -> Result<(), AddOneItemError> {
+        if !self.filter.accepts(item_count.item_id()) {
+            return Err(AddOneItemError {
+                excess_count: item_count.clone(),
+            });
+        }
+
         let mut items_to_add = item_count.count();
 
         // Fill up the slots of this item
@@ -233,7 +335,7 @@ impl Inventory {
         while items_to_add > 0 && self.slots.len() < self.max_slot_count {
             let mut new_slot = ItemSlot::new(
                 item_count.item_id(),
-                item_manifest.get(item_count.item_id()).stack_size,
+                self.stack_size(item_count.item_id(), item_manifest),
             );
 
             match new_slot.add_until_full(items_to_add) {
@@ -300,7 +402,11 @@ impl Inventory {
         let excess_counts: Vec<ItemCount> = item_counts
             .iter()
             .filter_map(|item_count| {
-                let stack_size = item_manifest.get(item_count.item_id()).stack_size;
+                if !self.filter.accepts(item_count.item_id()) {
+                    return Some(item_count.clone());
+                }
+
+                let stack_size = self.stack_size(item_count.item_id(), item_manifest);
 
                 let remaining_reserved_space =
                     self.remaining_reserved_space_for_item(item_count.item_id());
@@ -453,7 +559,7 @@ impl Inventory {
     /// Transfers item of the type given by `item_count` from the inventory of `self` to `other`.
     ///
     /// As many items will be transferred as possible.
-    /// If all items are transferred, [`Ok(())`] will be returned.
+    /// If all items are transferred, `Ok(())` will be returned.
     /// Otherwise, an [`ItemTransferError`] will be returned that contains the number of items that could not be transferred and why.
     pub fn transfer_item(
         &mut self,
@@ -494,6 +600,40 @@ impl Inventory {
             })
         }
     }
+
+    /// Transfers as many items of every type as possible from `self` to `other`.
+    ///
+    /// If all items are transferred, `Ok(())` will be returned.
+    /// Otherwise, an [`AddManyItemsError`] will be returned that contains the items that were left behind.
+    pub fn transfer_to(
+        &mut self,
+        other: &mut Inventory,
+        item_manifest: &ItemManifest,
+    ) -> Result<(), AddManyItemsError> {
+        let mut item_ids: Vec<Id<Item>> = self.slots.iter().map(|slot| slot.item_id()).collect();
+        item_ids.sort();
+        item_ids.dedup();
+
+        let mut excess_counts: Vec<ItemCount> = Vec::new();
+        for item_id in item_ids {
+            let item_count = ItemCount::new(item_id, self.item_count(item_id));
+
+            if let Err(ItemTransferError {
+                items_remaining, ..
+            }) = self.transfer_item(&item_count, other, item_manifest)
+            {
+                if items_remaining.count() > 0 {
+                    excess_counts.push(items_remaining);
+                }
+            }
+        }
+
+        if excess_counts.is_empty() {
+            Ok(())
+        } else {
+            Err(AddManyItemsError { excess_counts })
+        }
+    }
 }
 
 impl Display for Inventory {
@@ -513,7 +653,7 @@ impl Display for Inventory {
 
 #[cfg(test)]
 mod tests {
-    use bevy::utils::HashMap;
+    use crate::bevy::utils::HashMap;
 
     use super::*;
     use crate::items::ItemData;
@@ -522,7 +662,13 @@ mod tests {
     fn item_manifest() -> ItemManifest {
         let mut item_manifest = HashMap::new();
         item_manifest.insert(Id::acacia_leaf(), ItemData::acacia_leaf());
-        item_manifest.insert(Id::test(), ItemData { stack_size: 10 });
+        item_manifest.insert(
+            Id::test(),
+            ItemData {
+                stack_size: 10,
+                spoilage: None,
+            },
+        );
 
         ItemManifest::new(item_manifest)
     }
@@ -531,6 +677,7 @@ mod tests {
         Inventory {
             max_slot_count: 1,
             slots: vec![ItemSlot::new_with_count(Id::test(), 10, 10)],
+            ..Default::default()
         }
     }
 
@@ -538,6 +685,7 @@ mod tests {
         Inventory {
             max_slot_count: 1,
             slots: vec![ItemSlot::new_with_count(Id::test(), 10, 7)],
+            ..Default::default()
         }
     }
 
@@ -545,6 +693,7 @@ mod tests {
         Inventory {
             max_slot_count: 1,
             slots: vec![],
+            ..Default::default()
         }
     }
 
@@ -564,6 +713,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert_eq!(inventory.item_count(Id::acacia_leaf()), 15);
@@ -578,6 +728,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert!(inventory.has_count_of_item(&ItemCount::new(Id::acacia_leaf(), 15)));
@@ -592,6 +743,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert!(!inventory.has_count_of_item(&ItemCount::new(Id::acacia_leaf(), 16)));
@@ -613,6 +765,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert!(!inventory.is_empty());
@@ -628,6 +781,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 10),
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 10),
             ],
+            ..Default::default()
         };
 
         assert!(inventory.is_full());
@@ -642,6 +796,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert!(!inventory.is_full());
@@ -656,6 +811,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert_eq!(inventory.free_slot_count(), 1);
@@ -670,6 +826,7 @@ mod tests {
                 ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                 ItemSlot::new_with_count(Id::test(), 10, 3),
             ],
+            ..Default::default()
         };
 
         assert_eq!(
@@ -693,6 +850,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -713,6 +871,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -743,6 +902,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -765,6 +925,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -794,6 +955,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -819,6 +981,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -852,6 +1015,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -871,6 +1035,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -896,6 +1061,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -915,6 +1081,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -940,6 +1107,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -962,6 +1130,7 @@ mod tests {
                         ItemSlot::new_with_count(Id::acacia_leaf(), 10, 5),
                         ItemSlot::new_with_count(Id::test(), 10, 3),
                     ],
+                    ..Default::default()
                 };
 
                 assert_eq!(
@@ -1062,5 +1231,124 @@ mod tests {
                 })
             );
         }
+
+        #[test]
+        fn transfer_to_moves_every_item() {
+            let mut source = Inventory::new(2);
+            source
+                .try_add_items(
+                    &[
+                        ItemCount::new(Id::test(), 3),
+                        ItemCount::new(Id::acacia_leaf(), 2),
+                    ],
+                    &item_manifest(),
+                )
+                .unwrap();
+            let mut destination = Inventory::new(2);
+
+            let result = source.transfer_to(&mut destination, &item_manifest());
+            assert_eq!(result, Ok(()));
+            assert!(source.is_empty());
+            assert_eq!(destination.item_count(Id::test()), 3);
+            assert_eq!(destination.item_count(Id::acacia_leaf()), 2);
+        }
+
+        #[test]
+        fn transfer_to_respects_filters() {
+            let mut source = Inventory::new(2);
+            source
+                .try_add_items(
+                    &[
+                        ItemCount::new(Id::test(), 3),
+                        ItemCount::new(Id::acacia_leaf(), 2),
+                    ],
+                    &item_manifest(),
+                )
+                .unwrap();
+            let mut destination = Inventory::new(2)
+                .with_filter(ItemFilter::Deny(HashSet::from_iter([Id::acacia_leaf()])));
+
+            let result = source.transfer_to(&mut destination, &item_manifest());
+            assert_eq!(
+                result,
+                Err(AddManyItemsError {
+                    excess_counts: vec![ItemCount::new(Id::acacia_leaf(), 2)]
+                })
+            );
+            assert_eq!(destination.item_count(Id::test()), 3);
+        }
+    }
+
+    mod filters {
+        use super::*;
+
+        #[test]
+        fn allow_filters_reject_unlisted_items() {
+            let mut inventory =
+                Inventory::new(2).with_filter(ItemFilter::Allow(HashSet::from_iter([Id::test()])));
+
+            assert_eq!(
+                inventory.try_add_item(&ItemCount::new(Id::acacia_leaf(), 1), &item_manifest()),
+                Err(AddOneItemError {
+                    excess_count: ItemCount::new(Id::acacia_leaf(), 1)
+                })
+            );
+            assert_eq!(
+                inventory.remaining_space_for_item(Id::acacia_leaf(), &item_manifest()),
+                0
+            );
+            assert_eq!(
+                inventory.try_add_item(&ItemCount::new(Id::test(), 1), &item_manifest()),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn empty_slots_are_only_added_for_allowed_items() {
+            let mut inventory =
+                Inventory::new(1).with_filter(ItemFilter::Deny(HashSet::from_iter([Id::test()])));
+
+            assert_eq!(
+                inventory.add_empty_slot(Id::test(), &item_manifest()),
+                Err(FilteredItemError {
+                    item_id: Id::test()
+                })
+            );
+            assert_eq!(inventory.free_slot_count(), 1);
+        }
+
+        #[test]
+        fn empty_slots_fill_free_slots_before_expanding() {
+            let mut inventory = Inventory::new(2);
+
+            inventory
+                .add_empty_slot(Id::test(), &item_manifest())
+                .unwrap();
+            inventory
+                .add_empty_slot(Id::acacia_leaf(), &item_manifest())
+                .unwrap();
+            assert_eq!(inventory.free_slot_count(), 0);
+
+            inventory
+                .add_empty_slot(Id::test(), &item_manifest())
+                .unwrap();
+            assert_eq!(inventory.iter().count(), 3);
+            assert_eq!(inventory.free_slot_count(), 0);
+            assert!(inventory.is_empty());
+        }
+
+        #[test]
+        fn stack_limits_override_the_manifest() {
+            let mut inventory = Inventory::new(2).with_stack_limit(Id::test(), 4);
+
+            assert_eq!(inventory.stack_size(Id::test(), &item_manifest()), 4);
+            assert_eq!(
+                inventory.try_add_item(&ItemCount::new(Id::test(), 10), &item_manifest()),
+                Err(AddOneItemError {
+                    excess_count: ItemCount::new(Id::test(), 2)
+                })
+            );
+            assert_eq!(inventory.item_count(Id::test()), 8);
+        }
     }
 }
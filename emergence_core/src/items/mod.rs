@@ -4,12 +4,15 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use crate::asset_management::manifest::{Id, Item};
+use crate::manifest::{Id, Item};
 
-pub(crate) mod errors;
-pub(crate) mod inventory;
-pub(crate) mod recipe;
-pub(crate) mod slot;
+use self::spoilage::Spoilage;
+
+pub mod errors;
+pub mod inventory;
+pub mod recipe;
+pub mod slot;
+pub mod spoilage;
 
 // TODO: these should be loaded from file
 impl Id<Item> {
@@ -28,6 +31,21 @@ impl Id<Item> {
         Self::from_string_id("ant_egg")
     }
 
+    /// The item ID of fertilizer.
+    pub fn fertilizer() -> Self {
+        Self::from_string_id("fertilizer")
+    }
+
+    /// The item ID of water.
+    pub fn water() -> Self {
+        Self::from_string_id("water")
+    }
+
+    /// The item ID of the body of a unit that died of old age.
+    pub fn corpse() -> Self {
+        Self::from_string_id("corpse")
+    }
+
     /// An item ID solely used for testing.
     #[cfg(test)]
     pub fn test() -> Self {
@@ -40,36 +58,87 @@ impl Id<Item> {
 pub struct ItemData {
     /// The number of items that can fit in a single item slot.
     stack_size: usize,
+    /// How quickly this item rots, if it is organic.
+    spoilage: Option<Spoilage>,
 }
 
 impl ItemData {
+    /// Creates a new kind of item.
+    pub fn new(stack_size: usize, spoilage: Option<Spoilage>) -> Self {
+        ItemData {
+            stack_size,
+            spoilage,
+        }
+    }
+
     /// The number of items that can fit in a single item slot.
     pub fn stack_size(&self) -> usize {
         self.stack_size
     }
 
+    /// How quickly this item rots, if it ever does.
+    pub fn spoilage(&self) -> Option<&Spoilage> {
+        self.spoilage.as_ref()
+    }
+
     // TODO: Remove this once we can load item data from asset files
     /// A leaf from an acacia plant.
     pub fn acacia_leaf() -> Self {
-        Self { stack_size: 10 }
+        Self {
+            stack_size: 10,
+            spoilage: Some(Spoilage::new(3., Id::fertilizer())),
+        }
     }
 
     // TODO: Remove this once we can load item data from asset files
     /// A piece of a leuco mushroom.
     pub fn leuco_chunk() -> Self {
-        Self { stack_size: 5 }
+        Self {
+            stack_size: 5,
+            spoilage: Some(Spoilage::new(2., Id::fertilizer())),
+        }
     }
 
     // TODO: Remove this once we can load item data from asset files
     /// An egg that will hatch into a grown ant.
     pub fn ant_egg() -> Self {
-        Self { stack_size: 5 }
+        Self {
+            stack_size: 5,
+            spoilage: None,
+        }
+    }
+
+    // TODO: Remove this once we can load item data from asset files
+    /// Rich compost, made from rotting plant matter.
+    pub fn fertilizer() -> Self {
+        Self {
+            stack_size: 10,
+            spoilage: None,
+        }
+    }
+
+    // TODO: Remove this once we can load item data from asset files
+    /// A measure of fresh water.
+    pub fn water() -> Self {
+        Self {
+            stack_size: 20,
+            spoilage: None,
+        }
+    }
+
+    // TODO: Remove this once we can load item data from asset files
+    /// The body of a unit that died of old age.
+    pub fn corpse() -> Self {
+        Self {
+            stack_size: 1,
+            spoilage: Some(Spoilage::new(5., Id::fertilizer())),
+        }
     }
 }
 
 /// A specific amount of a given item.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct ItemCount {
+pub struct ItemCount {
     /// The unique identifier of the item being counted.
     item_id: Id<Item>,
 
@@ -79,12 +148,12 @@ pub(crate) struct ItemCount {
 
 impl ItemCount {
     /// Create a new item count with the given number of items.
-    pub(crate) fn new(item_id: Id<Item>, count: usize) -> Self {
+    pub fn new(item_id: Id<Item>, count: usize) -> Self {
         Self { item_id, count }
     }
 
     /// A single one of the given item.
-    pub(crate) fn one(item_id: Id<Item>) -> Self {
+    pub fn one(item_id: Id<Item>) -> Self {
         Self { item_id, count: 1 }
     }
 
@@ -3,12 +3,15 @@
 use std::{fmt::Display, time::Duration};
 
 use crate::{
-    asset_management::manifest::{Id, ItemManifest, Recipe},
+    manifest::{Id, ItemManifest, Recipe, Structure},
     organisms::energy::Energy,
     structures::crafting::{InputInventory, OutputInventory},
 };
 
-use super::{inventory::Inventory, ItemCount};
+use super::{
+    inventory::{Inventory, ItemFilter},
+    ItemCount,
+};
 
 // TODO: these should be read from disc
 impl Id<Recipe> {
@@ -31,11 +34,26 @@ impl Id<Recipe> {
     pub fn hatch_ants() -> Self {
         Self::from_string_id("hatch_ants")
     }
+
+    /// The ID of the recipe to turn plant litter into fertilizer.
+    pub fn composting() -> Self {
+        Self::from_string_id("composting")
+    }
+
+    /// The ID of the recipe to turn the remains of dead units into fertilizer.
+    pub fn corpse_composting() -> Self {
+        Self::from_string_id("corpse_composting")
+    }
+
+    /// The ID of the recipe to collect rainwater.
+    pub fn water_collection() -> Self {
+        Self::from_string_id("water_collection")
+    }
 }
 
 /// A recipe to turn a set of items into different items.
 #[derive(Debug, Clone)]
-pub(crate) struct RecipeData {
+pub struct RecipeData {
     /// The inputs needed to craft the recipe.
     inputs: Vec<ItemCount>,
 
@@ -52,16 +70,20 @@ pub(crate) struct RecipeData {
     ///
     /// This is only relevant to living structures.
     energy: Option<Energy>,
+
+    /// The structure that this recipe must be crafted at.
+    required_structure: Id<Structure>,
 }
 
 impl RecipeData {
-    /// Create a new recipe with the given inputs, outputs and craft time.
-    pub(crate) fn new(
+    /// Create a new recipe with the given inputs, outputs and craft time, made at `required_structure`.
+    pub fn new(
         inputs: Vec<ItemCount>,
         outputs: Vec<ItemCount>,
         craft_time: Duration,
         work_required: bool,
         energy: Option<Energy>,
+        required_structure: Id<Structure>,
     ) -> Self {
         Self {
             inputs,
@@ -69,96 +91,163 @@ impl RecipeData {
             craft_time,
             work_required,
             energy,
+            required_structure,
         }
     }
 
     /// The inputs needed to craft the recipe.
-    pub(crate) fn inputs(&self) -> &Vec<ItemCount> {
+    pub fn inputs(&self) -> &Vec<ItemCount> {
         &self.inputs
     }
 
     /// The outputs generated by crafting.
-    pub(crate) fn outputs(&self) -> &Vec<ItemCount> {
+    pub fn outputs(&self) -> &Vec<ItemCount> {
         &self.outputs
     }
 
     /// The time needed to craft the recipe.
-    pub(crate) fn craft_time(&self) -> Duration {
+    pub fn craft_time(&self) -> Duration {
         self.craft_time
     }
 
     /// Is work from units needed to advance this recipe?
-    pub(crate) fn work_required(&self) -> bool {
+    pub fn work_required(&self) -> bool {
         self.work_required
     }
 
     /// An inventory with empty slots for all of the inputs of this recipe.
-    pub(crate) fn input_inventory(&self, item_manifest: &ItemManifest) -> InputInventory {
-        let mut inventory = Inventory::new(self.inputs.len());
+    pub fn input_inventory(&self, item_manifest: &ItemManifest) -> InputInventory {
+        let mut inventory = Inventory::new(self.inputs.len()).with_filter(ItemFilter::Allow(
+            self.inputs
+                .iter()
+                .map(|item_count| item_count.item_id)
+                .collect(),
+        ));
         for item_count in &self.inputs {
-            inventory.add_empty_slot(item_count.item_id, item_manifest);
+            // The filter allows every input, so this cannot fail
+            inventory
+                .add_empty_slot(item_count.item_id, item_manifest)
+                .unwrap();
         }
         InputInventory { inventory }
     }
 
     /// An inventory with empty slots for all of the outputs of this recipe.
-    pub(crate) fn output_inventory(&self, item_manifest: &ItemManifest) -> OutputInventory {
-        let mut inventory = Inventory::new(self.outputs.len());
+    pub fn output_inventory(&self, item_manifest: &ItemManifest) -> OutputInventory {
+        let mut inventory = Inventory::new(self.outputs.len()).with_filter(ItemFilter::Allow(
+            self.outputs
+                .iter()
+                .map(|item_count| item_count.item_id)
+                .collect(),
+        ));
         for item_count in &self.outputs {
-            inventory.add_empty_slot(item_count.item_id, item_manifest);
+            // The filter allows every output, so this cannot fail
+            inventory
+                .add_empty_slot(item_count.item_id, item_manifest)
+                .unwrap();
         }
         OutputInventory { inventory }
     }
 
     /// The amount of energy produced by crafting the recipe, if any.
-    pub(crate) fn energy(&self) -> &Option<Energy> {
+    pub fn energy(&self) -> &Option<Energy> {
         &self.energy
     }
+
+    /// The structure that this recipe must be crafted at.
+    pub fn required_structure(&self) -> Id<Structure> {
+        self.required_structure
+    }
+
+    /// Can this recipe be crafted by a structure of type `structure_id`?
+    pub fn can_be_crafted_at(&self, structure_id: Id<Structure>) -> bool {
+        self.required_structure == structure_id
+    }
 }
 
 // TODO: Remove this once we load recipes from asset files
 impl RecipeData {
     /// An acacia plant producing leaves.
-    pub(crate) fn acacia_leaf_production() -> Self {
+    pub fn acacia_leaf_production() -> Self {
         RecipeData::new(
             Vec::new(),
             vec![ItemCount::one(Id::acacia_leaf())],
             Duration::from_secs(3),
             false,
             Some(Energy(20.)),
+            Id::from_string_id("acacia"),
         )
     }
 
     /// A leuco mushroom processing acacia leaves
-    pub(crate) fn leuco_chunk_production() -> Self {
+    pub fn leuco_chunk_production() -> Self {
         RecipeData::new(
             vec![ItemCount::one(Id::acacia_leaf())],
             vec![ItemCount::one(Id::leuco_chunk())],
             Duration::from_secs(2),
             false,
             Some(Energy(40.)),
+            Id::from_string_id("leuco"),
         )
     }
 
     /// An ant hive producing eggs.
-    pub(crate) fn ant_egg_production() -> Self {
+    pub fn ant_egg_production() -> Self {
         RecipeData::new(
             vec![ItemCount::one(Id::leuco_chunk())],
             vec![ItemCount::one(Id::ant_egg())],
             Duration::from_secs(5),
             false,
             None,
+            Id::from_string_id("ant_hive"),
         )
     }
 
-    /// An ant hive producing eggs.
-    pub(crate) fn hatch_ants() -> Self {
+    /// A hatchery turning eggs into ants.
+    pub fn hatch_ants() -> Self {
         RecipeData::new(
             vec![ItemCount::one(Id::ant_egg())],
             vec![],
             Duration::from_secs(10),
             true,
             None,
+            Id::from_string_id("hatchery"),
+        )
+    }
+
+    /// A composter slowly rotting leaves into fertilizer.
+    pub fn composting() -> Self {
+        RecipeData::new(
+            vec![ItemCount::new(Id::acacia_leaf(), 2)],
+            vec![ItemCount::one(Id::fertilizer())],
+            Duration::from_secs(10),
+            false,
+            None,
+            Id::from_string_id("composter"),
+        )
+    }
+
+    /// A composter breaking down the remains of dead units.
+    pub fn corpse_composting() -> Self {
+        RecipeData::new(
+            vec![ItemCount::one(Id::corpse())],
+            vec![ItemCount::new(Id::fertilizer(), 2)],
+            Duration::from_secs(10),
+            false,
+            None,
+            Id::from_string_id("composter"),
+        )
+    }
+
+    /// A cistern collecting rainwater.
+    pub fn water_collection() -> Self {
+        RecipeData::new(
+            Vec::new(),
+            vec![ItemCount::one(Id::water())],
+            Duration::from_secs(5),
+            false,
+            None,
+            Id::from_string_id("cistern"),
         )
     }
 }
@@ -0,0 +1,421 @@
+//! Organic items rot over time, unless they are stored somewhere that preserves them.
+//!
+//! Rather than giving every item its own timer, each inventory remembers when each batch of items arrived,
+//! and a single [`SpoilageTracker`] queues the moments at which those batches will spoil.
+//! Only inventories with a batch that is due are ever inspected.
+//!
+//! Items are only tracked while they are stored: items carried by units start fresh wherever they are dropped off.
+//!
+//! Rotten items are only kept in slots that were set aside for them, such as the fertilizer slot of a composter,
+//! where they can be collected and put to use.
+//! Everywhere else, they decay into the soil beneath the inventory and enrich it.
+
+use crate::bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use crate::{
+    manifest::{Id, Item, ItemManifest},
+    simulation::{geometry::TilePos, time::DAY_LENGTH_IN_SECONDS},
+    structures::crafting::{InputInventory, OutputInventory},
+    terrain::Decompose,
+};
+
+use super::{inventory::Inventory, ItemCount};
+
+/// The fertility returned to the soil by each rotten item that had no slot set aside for it.
+const ROTTEN_ITEM_NUTRIENTS: f32 = 0.02;
+
+/// Rots stored items that have been kept for too long.
+pub struct SpoilagePlugin;
+
+impl Plugin for SpoilagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpoilageTracker>()
+            .add_system(track_freshness)
+            .add_system(spoil_items.after(track_freshness));
+    }
+}
+
+/// How quickly an organic item rots, and what it rots into.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spoilage {
+    /// How long the item keeps before it rots
+    pub shelf_life: Duration,
+    /// The item that this item becomes once it has rotted
+    pub rots_into: Id<Item>,
+}
+
+impl Spoilage {
+    /// Creates a new [`Spoilage`], for an item that keeps for `shelf_life_in_days` in-game days.
+    pub fn new(shelf_life_in_days: f32, rots_into: Id<Item>) -> Self {
+        Spoilage {
+            shelf_life: Duration::from_secs_f32(shelf_life_in_days * DAY_LENGTH_IN_SECONDS),
+            rots_into,
+        }
+    }
+}
+
+/// A marker component for structures whose stored items never spoil.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Preserving;
+
+/// A number of identical items that arrived in an inventory at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Batch {
+    /// The number of items in this batch that have not yet been used or spoiled
+    count: usize,
+    /// The moment at which the items in this batch spoil
+    expires_at: Duration,
+}
+
+/// The ages of the spoilable items stored in a single entity's inventories.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Freshness {
+    /// The batches of each item, from oldest to newest
+    batches: HashMap<Id<Item>, VecDeque<Batch>>,
+}
+
+impl Freshness {
+    /// The number of `item_id` whose age is being tracked.
+    fn n_tracked(&self, item_id: Id<Item>) -> usize {
+        self.batches
+            .get(&item_id)
+            .map_or(0, |batches| batches.iter().map(|batch| batch.count).sum())
+    }
+
+    /// Brings the tracked number of `item_id` in line with the `current_count` stored.
+    ///
+    /// New items are added as a fresh batch, while missing items are assumed to have been taken from the oldest batches.
+    /// Returns the moment at which the new batch will spoil, if one was added.
+    fn reconcile(
+        &mut self,
+        item_id: Id<Item>,
+        current_count: usize,
+        now: Duration,
+        shelf_life: Duration,
+    ) -> Option<Duration> {
+        let n_tracked = self.n_tracked(item_id);
+
+        if current_count > n_tracked {
+            let expires_at = now + shelf_life;
+            self.batches.entry(item_id).or_default().push_back(Batch {
+                count: current_count - n_tracked,
+                expires_at,
+            });
+            return Some(expires_at);
+        }
+
+        let mut n_missing = n_tracked - current_count;
+        if let Some(batches) = self.batches.get_mut(&item_id) {
+            while n_missing > 0 {
+                match batches.front_mut() {
+                    Some(oldest) if oldest.count > n_missing => {
+                        oldest.count -= n_missing;
+                        n_missing = 0;
+                    }
+                    Some(oldest) => {
+                        n_missing -= oldest.count;
+                        batches.pop_front();
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Removes every batch that has spoiled by `now`, returning the number of each item that spoiled.
+    fn take_expired(&mut self, now: Duration) -> Vec<ItemCount> {
+        let mut expired = Vec::new();
+
+        for (&item_id, batches) in self.batches.iter_mut() {
+            let mut count = 0;
+            while let Some(oldest) = batches.front() {
+                if oldest.expires_at > now {
+                    break;
+                }
+                count += oldest.count;
+                batches.pop_front();
+            }
+
+            if count > 0 {
+                expired.push(ItemCount::new(item_id, count));
+            }
+        }
+
+        self.batches.retain(|_, batches| !batches.is_empty());
+        expired
+    }
+}
+
+/// Tracks the age of every stored item that can spoil.
+#[derive(Resource, Debug, Default)]
+pub struct SpoilageTracker {
+    /// The freshness of the items stored by each entity
+    freshness: HashMap<Entity, Freshness>,
+    /// When each batch will spoil, soonest first
+    ///
+    /// An entity may appear here several times, once for each batch it has received.
+    expiry_queue: BinaryHeap<Reverse<(Duration, Entity)>>,
+}
+
+/// Starts tracking the age of newly stored items, and stops tracking items that were taken away.
+fn track_freshness(
+    inventory_query: Query<
+        (Entity, Option<&InputInventory>, Option<&OutputInventory>),
+        (
+            Or<(Changed<InputInventory>, Changed<OutputInventory>)>,
+            Without<Preserving>,
+        ),
+    >,
+    item_manifest: Res<ItemManifest>,
+    time: Res<Time>,
+    mut spoilage_tracker: ResMut<SpoilageTracker>,
+) {
+    let now = time.elapsed();
+    let spoilage_tracker = &mut *spoilage_tracker;
+
+    for (entity, maybe_input, maybe_output) in inventory_query.iter() {
+        let slots = maybe_input
+            .into_iter()
+            .flat_map(|input| input.iter())
+            .chain(maybe_output.into_iter().flat_map(|output| output.iter()));
+
+        let freshness = spoilage_tracker.freshness.entry(entity).or_default();
+
+        let mut item_ids: Vec<Id<Item>> = slots
+            .map(|slot| slot.item_id())
+            .chain(freshness.batches.keys().copied())
+            .collect();
+        item_ids.sort();
+        item_ids.dedup();
+
+        for item_id in item_ids {
+            let shelf_life = match item_manifest.get(item_id).spoilage() {
+                Some(spoilage) => spoilage.shelf_life,
+                None => continue,
+            };
+
+            let current_count = maybe_input.map_or(0, |input| input.item_count(item_id))
+                + maybe_output.map_or(0, |output| output.item_count(item_id));
+
+            if let Some(expires_at) = freshness.reconcile(item_id, current_count, now, shelf_life) {
+                spoilage_tracker
+                    .expiry_queue
+                    .push(Reverse((expires_at, entity)));
+            }
+        }
+
+        // Don't keep empty records around for inventories that never store anything perishable
+        if freshness.batches.is_empty() {
+            spoilage_tracker.freshness.remove(&entity);
+        }
+    }
+}
+
+/// Stores as many as possible of `n_rotten` items of `rots_into` in the slots of `inventory` that were set aside for them.
+///
+/// Returns the number of items that were kept.
+fn keep_rotten(
+    inventory: &mut Inventory,
+    rots_into: Id<Item>,
+    n_rotten: usize,
+    item_manifest: &ItemManifest,
+) -> usize {
+    let n_to_keep = inventory
+        .remaining_reserved_space_for_item(rots_into)
+        .min(n_rotten);
+    if n_to_keep == 0 {
+        return 0;
+    }
+
+    match inventory.try_add_item(&ItemCount::new(rots_into, n_to_keep), item_manifest) {
+        Ok(()) => n_to_keep,
+        Err(error) => n_to_keep - error.excess_count.count(),
+    }
+}
+
+/// Turns items that have been stored for too long into whatever they rot into.
+///
+/// Rotten items are kept in the same inventory if it has a slot set aside for them, and otherwise decay into the soil.
+fn spoil_items(
+    mut inventory_query: Query<(
+        Option<&mut InputInventory>,
+        Option<&mut OutputInventory>,
+        &TilePos,
+    )>,
+    item_manifest: Res<ItemManifest>,
+    time: Res<Time>,
+    mut spoilage_tracker: ResMut<SpoilageTracker>,
+    mut decompose_events: EventWriter<Decompose>,
+) {
+    let now = time.elapsed();
+    let spoilage_tracker = &mut *spoilage_tracker;
+
+    while let Some(&Reverse((expires_at, entity))) = spoilage_tracker.expiry_queue.peek() {
+        if expires_at > now {
+            break;
+        }
+        spoilage_tracker.expiry_queue.pop();
+
+        let (mut maybe_input, mut maybe_output, &tile_pos) = match inventory_query.get_mut(entity) {
+            Ok(query_item) => query_item,
+            // The entity storing these items no longer exists
+            Err(_) => {
+                spoilage_tracker.freshness.remove(&entity);
+                continue;
+            }
+        };
+
+        // Batches that were used up before they spoiled leave stale entries in the queue, which find nothing here
+        let expired = match spoilage_tracker.freshness.get_mut(&entity) {
+            Some(freshness) => freshness.take_expired(now),
+            None => continue,
+        };
+
+        for item_count in expired {
+            let rots_into = match item_manifest.get(item_count.item_id()).spoilage() {
+                Some(spoilage) => spoilage.rots_into,
+                None => continue,
+            };
+
+            // Take the rotten items from whichever inventories hold them
+            let mut n_missing = item_count.count();
+            if let Some(output) = maybe_output.as_mut() {
+                n_missing = match output.try_remove_item(&item_count) {
+                    Ok(()) => 0,
+                    Err(error) => error.missing_count.count(),
+                };
+            }
+            if let Some(input) = maybe_input.as_mut() {
+                if n_missing > 0 {
+                    n_missing = match input
+                        .try_remove_item(&ItemCount::new(item_count.item_id(), n_missing))
+                    {
+                        Ok(()) => 0,
+                        Err(error) => error.missing_count.count(),
+                    };
+                }
+            }
+
+            let mut n_rotten = item_count.count() - n_missing;
+            if let Some(output) = maybe_output.as_mut() {
+                n_rotten -= keep_rotten(&mut output.inventory, rots_into, n_rotten, &item_manifest);
+            }
+            if let Some(input) = maybe_input.as_mut() {
+                n_rotten -= keep_rotten(&mut input.inventory, rots_into, n_rotten, &item_manifest);
+            }
+
+            if n_rotten > 0 {
+                decompose_events.send(Decompose {
+                    tile_pos,
+                    nutrients: n_rotten as f32 * ROTTEN_ITEM_NUTRIENTS,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ItemData;
+
+    /// How long the test items keep.
+    const SHELF_LIFE: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn new_items_are_tracked_in_batches() {
+        let mut freshness = Freshness::default();
+
+        assert_eq!(
+            freshness.reconcile(Id::acacia_leaf(), 3, Duration::ZERO, SHELF_LIFE),
+            Some(SHELF_LIFE)
+        );
+        assert_eq!(
+            freshness.reconcile(Id::acacia_leaf(), 5, Duration::from_secs(4), SHELF_LIFE),
+            Some(Duration::from_secs(14))
+        );
+        assert_eq!(freshness.n_tracked(Id::acacia_leaf()), 5);
+    }
+
+    #[test]
+    fn the_oldest_items_are_used_first() {
+        let mut freshness = Freshness::default();
+        freshness.reconcile(Id::acacia_leaf(), 3, Duration::ZERO, SHELF_LIFE);
+        freshness.reconcile(Id::acacia_leaf(), 5, Duration::from_secs(4), SHELF_LIFE);
+
+        // Using four items empties the first batch, and takes one from the second
+        assert_eq!(
+            freshness.reconcile(Id::acacia_leaf(), 1, Duration::from_secs(5), SHELF_LIFE),
+            None
+        );
+
+        assert!(freshness.take_expired(Duration::from_secs(10)).is_empty());
+        assert_eq!(
+            freshness.take_expired(Duration::from_secs(14)),
+            vec![ItemCount::new(Id::acacia_leaf(), 1)]
+        );
+        assert_eq!(freshness.n_tracked(Id::acacia_leaf()), 0);
+    }
+
+    #[test]
+    fn only_expired_batches_spoil() {
+        let mut freshness = Freshness::default();
+        freshness.reconcile(Id::acacia_leaf(), 2, Duration::ZERO, SHELF_LIFE);
+        freshness.reconcile(Id::leuco_chunk(), 1, Duration::from_secs(5), SHELF_LIFE);
+
+        assert_eq!(
+            freshness.take_expired(Duration::from_secs(12)),
+            vec![ItemCount::new(Id::acacia_leaf(), 2)]
+        );
+        assert_eq!(freshness.n_tracked(Id::leuco_chunk()), 1);
+    }
+
+    /// An item manifest containing the items used in these tests.
+    fn item_manifest() -> ItemManifest {
+        let mut map = HashMap::new();
+        map.insert(Id::acacia_leaf(), ItemData::acacia_leaf());
+        map.insert(Id::fertilizer(), ItemData::fertilizer());
+        ItemManifest::new(map)
+    }
+
+    #[test]
+    fn rotten_items_are_kept_in_slots_set_aside_for_them() {
+        let item_manifest = item_manifest();
+        let mut composter_output = Inventory::new(1);
+        composter_output
+            .add_empty_slot(Id::fertilizer(), &item_manifest)
+            .unwrap();
+
+        assert_eq!(
+            keep_rotten(&mut composter_output, Id::fertilizer(), 3, &item_manifest),
+            3
+        );
+        assert_eq!(composter_output.item_count(Id::fertilizer()), 3);
+    }
+
+    #[test]
+    fn rotten_items_without_a_slot_are_not_kept() {
+        let item_manifest = item_manifest();
+        // An open inventory, such as the remains left by a dead unit, with room for anything
+        let mut remains = Inventory::new(2);
+        remains
+            .try_add_item(&ItemCount::one(Id::acacia_leaf()), &item_manifest)
+            .unwrap();
+
+        assert_eq!(
+            keep_rotten(&mut remains, Id::fertilizer(), 3, &item_manifest),
+            0
+        );
+        assert_eq!(remains.item_count(Id::fertilizer()), 0);
+    }
+}
@@ -0,0 +1,48 @@
+//! The Emergence simulation: terrain, organisms, items, signals and the rules that tie them together.
+//!
+//! This crate runs headlessly, and only depends on the parts of Bevy that drive the ECS.
+//! Rendering, input, audio and asset loading are added on top of it by `emergence_lib`.
+#![deny(missing_docs)]
+#![deny(clippy::missing_docs_in_private_items)]
+#![forbid(unsafe_code)]
+#![warn(clippy::doc_markdown)]
+// Often exceeded by queries
+#![allow(clippy::type_complexity)]
+
+pub mod curves;
+pub mod enum_iter;
+pub mod items;
+pub mod manifest;
+pub mod organisms;
+pub mod signals;
+pub mod simulation;
+pub mod structures;
+pub mod terrain;
+pub mod units;
+
+/// The parts of Bevy that the simulation is built on.
+///
+/// Only the ECS and its supporting crates are pulled in:
+/// the simulation must not depend on rendering, windowing or input.
+pub mod bevy {
+    pub use bevy_app as app;
+    pub use bevy_ecs as ecs;
+    pub use bevy_hierarchy as hierarchy;
+    pub use bevy_log as log;
+    pub use bevy_math as math;
+    pub use bevy_tasks as tasks;
+    pub use bevy_time as time;
+    pub use bevy_utils as utils;
+
+    /// Everything that is commonly needed to write simulation systems.
+    pub mod prelude {
+        pub use bevy_app::prelude::*;
+        pub use bevy_derive::{Deref, DerefMut};
+        pub use bevy_ecs::prelude::*;
+        pub use bevy_hierarchy::prelude::*;
+        pub use bevy_log::prelude::*;
+        pub use bevy_math::prelude::*;
+        pub use bevy_time::prelude::*;
+        pub use bevy_utils::prelude::*;
+    }
+}
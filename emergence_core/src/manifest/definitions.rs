@@ -0,0 +1,898 @@
+//! Definitions of items, recipes, structures and units, read from RON files in `assets/definitions/`,
+//! and of the scripted events of each scenario, read from `assets/scenarios/`.
+//!
+//! Each game object is named by a human-readable string identifier, which is interned into an [`Id`] as it is read;
+//! two identifiers that hash to the same [`Id`] are rejected.
+//! The model, footprint, construction cost, passability and growth requirements of each structure are read from its definition,
+//! but what a structure does (crafting, traps, automation and so on) is still built in, in [`built_in_structures`].
+//!
+//! With the `mods` feature, the definitions of each mod are read after the game's own.
+//!
+//! When a definition file is missing, the definitions built into the game are used instead,
+//! so tests and tools that run without the game's assets behave as before.
+//! A definition file that exists but is broken is a bug in the game's content, and stops the game.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Deserializer};
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    items::{inventory::Inventory, recipe::RecipeData, spoilage::Spoilage, ItemCount, ItemData},
+    organisms::{energy::Energy, growth::GrowthRequirements},
+    signals::{SignalCategory, SignalSensitivity},
+    simulation::{
+        geometry::Crossing,
+        scenario::{EventTrigger, IntroWaypoint, Scenario, ScheduledEvent, SpawnWaves},
+    },
+    structures::{built_in_structures, crafting::InputInventory, StructureData},
+    terrain::Terrain,
+    units::{
+        animation::{AnimationState, UnitAnimations},
+        varieties::UnitVariety,
+    },
+};
+
+use super::{
+    Id, IdCollision, Item, ItemManifest, RecipeManifest, Structure, StructureManifest, Unit,
+};
+
+/// The folder inside the asset directory that contains the definition files.
+const DEFINITIONS_FOLDER: &str = "assets/definitions";
+
+/// The folder inside the asset directory that contains the scenario files.
+const SCENARIOS_FOLDER: &str = "assets/scenarios";
+
+/// The definition of a single item, as written in `items.ron`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ItemDefinition {
+    /// The string identifier of the item
+    id: String,
+    /// The number of items that can fit in a single item slot
+    stack_size: usize,
+    /// How quickly this item rots, if it is organic
+    #[serde(default)]
+    spoilage: Option<SpoilageDefinition>,
+}
+
+/// How quickly an item rots, as written in `items.ron`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct SpoilageDefinition {
+    /// The number of in-game days that the item keeps for
+    shelf_life_in_days: f32,
+    /// The string identifier of the item that this item rots into
+    rots_into: String,
+}
+
+/// The definition of a single recipe, as written in `recipes.ron`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RecipeDefinition {
+    /// The string identifier of the recipe
+    id: String,
+    /// The string identifier and number of each item consumed
+    #[serde(default)]
+    inputs: Vec<(String, usize)>,
+    /// The string identifier and number of each item produced
+    #[serde(default)]
+    outputs: Vec<(String, usize)>,
+    /// The time needed to craft the recipe
+    craft_time_in_seconds: f32,
+    /// Is work by units needed to advance this recipe?
+    #[serde(default)]
+    work_required: bool,
+    /// The energy gained by a living structure when the recipe is completed
+    #[serde(default)]
+    energy: Option<f32>,
+    /// The string identifier of the structure that crafts this recipe
+    structure: String,
+}
+
+/// The definition of a single structure, as written in `structures.ron`.
+///
+/// Optional fields that are left out keep the value built into the game, if the structure is built in.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct StructureDefinition {
+    /// The string identifier of the structure
+    id: String,
+    /// The path to the model of the structure, relative to the asset directory
+    model: String,
+    /// The terrain types that the structure can be built on
+    allowed_terrain: Vec<Terrain>,
+    /// The string identifier and number of each item needed to build the structure
+    #[serde(default, deserialize_with = "present")]
+    construction_materials: Option<Vec<(String, usize)>>,
+    /// The work by units needed to build the structure
+    #[serde(default, deserialize_with = "present")]
+    build_time_in_seconds: Option<f32>,
+    /// How units walk across the structure, if they can
+    #[serde(default)]
+    crossing: Option<Crossing>,
+    /// The conditions the structure needs to grow, if it is a living organism
+    #[serde(default)]
+    growth_requirements: Option<GrowthRequirements>,
+}
+
+/// The definition of a single variety of unit, as written in `units.ron`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct UnitDefinition {
+    /// The string identifier of the unit
+    id: String,
+    /// How quickly this unit walks, relative to a standard unit
+    walking_speed: f32,
+    /// The number of items this unit can carry at once
+    carry_capacity: usize,
+    /// The weight given to each category of signal, for those that are not weighed normally
+    #[serde(default)]
+    goal_weights: Vec<(SignalCategory, f32)>,
+    /// The path to the model of the unit, relative to the asset directory
+    model: String,
+    /// The index of the animation clip in the model played in each state, for those states that have one
+    #[serde(default)]
+    animations: Vec<(AnimationState, usize)>,
+}
+
+/// The definition of a scenario, as written in its file in `assets/scenarios/`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ScenarioDefinition {
+    /// The events scripted to occur
+    #[serde(default)]
+    events: Vec<ScheduledEventDefinition>,
+    /// The waypoints of the camera path played when the scenario begins, if any
+    #[serde(default)]
+    intro_pan: Vec<IntroWaypointDefinition>,
+}
+
+/// The definition of a single scripted event of a scenario.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ScheduledEventDefinition {
+    /// The name of the event, shown to the player when it occurs
+    name: String,
+    /// When this event occurs
+    trigger: EventTrigger,
+    /// The string identifier of the unit that arrives
+    unit: String,
+    /// The number of units spawned in each wave
+    units_per_wave: usize,
+    /// The total number of waves
+    n_waves: usize,
+    /// The number of seconds between each wave
+    seconds_between_waves: f32,
+}
+
+/// The definition of a single waypoint of a scenario's intro pan.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct IntroWaypointDefinition {
+    /// The point on the ground that the camera looks at
+    #[serde(default)]
+    translation: (f32, f32, f32),
+    /// The distance from the camera to the point it is looking at
+    distance: f32,
+    /// How long the camera takes to travel to this waypoint from the previous one, in seconds
+    #[serde(default)]
+    seconds_from_previous: f32,
+}
+
+/// Something went wrong when reading a definition file.
+#[derive(Debug)]
+pub enum DefinitionError {
+    /// The definition file does not exist.
+    Missing(PathBuf),
+    /// The definition file could not be read.
+    Unreadable(PathBuf, std::io::Error),
+    /// The definition file is not valid RON.
+    Invalid(PathBuf, ron::error::SpannedError),
+    /// A definition refers to an item that was never defined.
+    UnknownItem {
+        /// The definition containing the reference
+        referenced_by: String,
+        /// The string identifier of the missing item
+        item: String,
+    },
+    /// Two different string identifiers hash to the same [`Id`].
+    Collision(IdCollision),
+    /// Growth requirements were given for a structure that is not a living organism.
+    NotAnOrganism(String),
+}
+
+impl From<IdCollision> for DefinitionError {
+    fn from(collision: IdCollision) -> Self {
+        DefinitionError::Collision(collision)
+    }
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefinitionError::Missing(path) => write!(f, "{} does not exist", path.display()),
+            DefinitionError::Unreadable(path, error) => {
+                write!(f, "could not read {}: {error}", path.display())
+            }
+            DefinitionError::Invalid(path, error) => {
+                write!(f, "{} is not valid: {error}", path.display())
+            }
+            DefinitionError::UnknownItem {
+                referenced_by,
+                item,
+            } => write!(f, "{referenced_by} refers to the unknown item {item}"),
+            DefinitionError::Collision(collision) => write!(f, "{collision}"),
+            DefinitionError::NotAnOrganism(structure) => write!(
+                f,
+                "{structure} has growth requirements, but is not a living organism"
+            ),
+        }
+    }
+}
+
+/// The directory that the asset folders are found in.
+///
+/// This is found the same way as Bevy's asset directory,
+/// without depending on the asset server, which the headless simulation does not have.
+fn asset_root() -> PathBuf {
+    if let Ok(asset_root) = env::var("BEVY_ASSET_ROOT") {
+        PathBuf::from(asset_root)
+    } else if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        PathBuf::from(manifest_dir)
+    } else {
+        env::current_exe()
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default()
+    }
+}
+
+/// The directory containing the definition files used by the game.
+pub fn definitions_directory() -> PathBuf {
+    asset_root().join(DEFINITIONS_FOLDER)
+}
+
+/// The directory containing the scenario files used by the game.
+pub fn scenarios_directory() -> PathBuf {
+    asset_root().join(SCENARIOS_FOLDER)
+}
+
+/// Reads and parses the definition file `file_name` from `directory`.
+pub(super) fn read_definitions<T: for<'de> Deserialize<'de>>(
+    directory: &Path,
+    file_name: &str,
+) -> Result<T, DefinitionError> {
+    let path = directory.join(file_name);
+
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Err(DefinitionError::Missing(path))
+        }
+        Err(error) => return Err(DefinitionError::Unreadable(path, error)),
+    };
+
+    ron::from_str(&text).map_err(|error| DefinitionError::Invalid(path, error))
+}
+
+/// Reads the definitions in `file_name` from `directory`, followed by those of any mods.
+fn read_definitions_and_mods<T: for<'de> Deserialize<'de>>(
+    directory: &Path,
+    file_name: &str,
+) -> Result<Vec<T>, DefinitionError> {
+    let definitions = read_definitions(directory, file_name)?;
+
+    #[cfg(feature = "mods")]
+    let definitions = super::mods::with_mod_definitions(definitions, directory, file_name)?;
+
+    Ok(definitions)
+}
+
+/// Uses the definitions that were loaded, or the `built_in` definitions if they could not be.
+///
+/// A missing file is expected when running without the game's assets.
+///
+/// # Panics
+///
+/// Panics if the definitions exist but could not be loaded, rather than silently hiding the mistake.
+pub fn loaded_or_built_in<T>(
+    loaded: Result<T, DefinitionError>,
+    built_in: impl FnOnce() -> T,
+) -> T {
+    match loaded {
+        Ok(definitions) => definitions,
+        Err(DefinitionError::Missing(path)) => {
+            info!(
+                "{} does not exist, so the built-in definitions will be used",
+                path.display()
+            );
+            built_in()
+        }
+        Err(error) => panic!("The definition files are broken: {error}"),
+    }
+}
+
+/// Looks up the [`Id`] of an item referred to by `referenced_by`, checking that it has been defined.
+fn known_item(
+    item: &str,
+    referenced_by: &str,
+    item_manifest: &ItemManifest,
+) -> Result<Id<Item>, DefinitionError> {
+    let item_id = Id::from_name(item)?;
+
+    if item_manifest
+        .variants()
+        .into_iter()
+        .any(|known| known == item_id)
+    {
+        Ok(item_id)
+    } else {
+        Err(DefinitionError::UnknownItem {
+            referenced_by: referenced_by.to_string(),
+            item: item.to_string(),
+        })
+    }
+}
+
+/// Looks up the [`Id`] of each item in `items`, pairing it with its count.
+fn item_counts(
+    items: &[(String, usize)],
+    referenced_by: &str,
+    item_manifest: &ItemManifest,
+) -> Result<Vec<ItemCount>, DefinitionError> {
+    items
+        .iter()
+        .map(|(item, count)| {
+            known_item(item, referenced_by, item_manifest)
+                .map(|item_id| ItemCount::new(item_id, *count))
+        })
+        .collect()
+}
+
+/// Reads an optional field that is written without `Some`, so that leaving it out can be told apart from an empty value.
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Builds the [`ItemManifest`] from the item definitions.
+fn item_manifest(definitions: Vec<ItemDefinition>) -> Result<ItemManifest, DefinitionError> {
+    let known_items: Vec<String> = definitions.iter().map(|item| item.id.clone()).collect();
+    let mut map = HashMap::new();
+
+    for definition in definitions {
+        let spoilage = match definition.spoilage {
+            Some(spoilage) => {
+                if !known_items.contains(&spoilage.rots_into) {
+                    return Err(DefinitionError::UnknownItem {
+                        referenced_by: definition.id,
+                        item: spoilage.rots_into,
+                    });
+                }
+
+                Some(Spoilage::new(
+                    spoilage.shelf_life_in_days,
+                    Id::from_name(&spoilage.rots_into)?,
+                ))
+            }
+            None => None,
+        };
+
+        map.insert(
+            Id::from_name(&definition.id)?,
+            ItemData::new(definition.stack_size, spoilage),
+        );
+    }
+
+    Ok(ItemManifest::new(map))
+}
+
+/// Builds the [`RecipeManifest`] from the recipe definitions, checking that every item used is in the `item_manifest`.
+fn recipe_manifest(
+    definitions: Vec<RecipeDefinition>,
+    item_manifest: &ItemManifest,
+) -> Result<RecipeManifest, DefinitionError> {
+    let mut map = HashMap::new();
+
+    for definition in definitions {
+        let recipe = RecipeData::new(
+            item_counts(&definition.inputs, &definition.id, item_manifest)?,
+            item_counts(&definition.outputs, &definition.id, item_manifest)?,
+            Duration::from_secs_f32(definition.craft_time_in_seconds),
+            definition.work_required,
+            definition.energy.map(Energy),
+            Id::from_name(&definition.structure)?,
+        );
+
+        map.insert(Id::from_name(&definition.id)?, recipe);
+    }
+
+    Ok(RecipeManifest::new(map))
+}
+
+/// Loads the [`ItemManifest`] from `items.ron` in `directory`.
+pub fn load_items(directory: &Path) -> Result<ItemManifest, DefinitionError> {
+    item_manifest(read_definitions_and_mods(directory, "items.ron")?)
+}
+
+/// Loads the [`RecipeManifest`] from `recipes.ron` in `directory`.
+pub fn load_recipes(
+    directory: &Path,
+    item_manifest: &ItemManifest,
+) -> Result<RecipeManifest, DefinitionError> {
+    recipe_manifest(
+        read_definitions_and_mods(directory, "recipes.ron")?,
+        item_manifest,
+    )
+}
+
+/// Loads the path to the model of each structure from `structures.ron` in `directory`.
+pub fn load_structure_models(
+    directory: &Path,
+) -> Result<HashMap<Id<Structure>, String>, DefinitionError> {
+    let definitions: Vec<StructureDefinition> =
+        read_definitions_and_mods(directory, "structures.ron")?;
+
+    definitions
+        .into_iter()
+        .map(|definition| -> Result<_, DefinitionError> {
+            Ok((Id::from_name(&definition.id)?, definition.model))
+        })
+        .collect()
+}
+
+/// Builds the [`StructureManifest`] from the structure definitions, checking that every item used is in the `item_manifest`.
+///
+/// Each definition overrides the data of the matching `built_in` structure;
+/// structures that are not built in have no special behavior.
+fn structure_manifest(
+    definitions: Vec<StructureDefinition>,
+    item_manifest: &ItemManifest,
+    mut built_in: HashMap<Id<Structure>, StructureData>,
+) -> Result<StructureManifest, DefinitionError> {
+    for definition in definitions {
+        let structure_id = Id::from_name(&definition.id)?;
+        let allowed_terrain_types = definition.allowed_terrain.into_iter().collect();
+
+        let mut data = match built_in.remove(&structure_id) {
+            Some(mut data) => {
+                data.allowed_terrain_types = allowed_terrain_types;
+                data
+            }
+            None => StructureData::plain(allowed_terrain_types),
+        };
+
+        if let Some(construction_materials) = &definition.construction_materials {
+            data = data.with_construction_materials(InputInventory {
+                inventory: Inventory::new_from_items(&item_counts(
+                    construction_materials,
+                    &definition.id,
+                    item_manifest,
+                )?),
+            });
+        }
+
+        if let Some(build_time_in_seconds) = definition.build_time_in_seconds {
+            data = data.with_build_duration(Duration::from_secs_f32(build_time_in_seconds));
+        }
+
+        if let Some(crossing) = definition.crossing {
+            data = data.with_crossing(crossing);
+        }
+
+        if let Some(growth_requirements) = definition.growth_requirements {
+            if data.growth_requirements().is_none() {
+                return Err(DefinitionError::NotAnOrganism(definition.id));
+            }
+            data = data.with_growth_requirements(growth_requirements);
+        }
+
+        built_in.insert(structure_id, data);
+    }
+
+    Ok(StructureManifest::new(built_in))
+}
+
+/// Loads the [`StructureManifest`] from `structures.ron` in `directory`, checking that every item used is in the `item_manifest`.
+pub fn load_structures(
+    directory: &Path,
+    item_manifest: &ItemManifest,
+) -> Result<StructureManifest, DefinitionError> {
+    structure_manifest(
+        read_definitions_and_mods(directory, "structures.ron")?,
+        item_manifest,
+        built_in_structures(),
+    )
+}
+
+/// Builds the variety of each unit from the unit definitions.
+fn unit_varieties(
+    definitions: Vec<UnitDefinition>,
+) -> Result<HashMap<Id<Unit>, UnitVariety>, DefinitionError> {
+    definitions
+        .into_iter()
+        .map(|definition| -> Result<_, DefinitionError> {
+            let goal_weights = definition.goal_weights.into_iter().fold(
+                SignalSensitivity::default(),
+                |goal_weights, (category, weight)| goal_weights.with_multiplier(category, weight),
+            );
+
+            let animations = definition
+                .animations
+                .into_iter()
+                .fold(UnitAnimations::default(), |animations, (state, index)| {
+                    animations.with_clip(state, index)
+                });
+
+            let variety = UnitVariety {
+                walking_speed: definition.walking_speed,
+                carry_capacity: definition.carry_capacity,
+                goal_weights,
+                model: definition.model,
+                animations,
+            };
+
+            Ok((Id::from_name(&definition.id)?, variety))
+        })
+        .collect()
+}
+
+/// Loads the variety of each unit from `units.ron` in `directory`.
+pub fn load_unit_varieties(
+    directory: &Path,
+) -> Result<HashMap<Id<Unit>, UnitVariety>, DefinitionError> {
+    unit_varieties(read_definitions_and_mods(directory, "units.ron")?)
+}
+
+/// Builds a [`Scenario`] from its definition.
+fn scenario(definition: ScenarioDefinition) -> Result<Scenario, DefinitionError> {
+    let events = definition
+        .events
+        .into_iter()
+        .map(|event| -> Result<_, DefinitionError> {
+            Ok(ScheduledEvent {
+                name: event.name,
+                trigger: event.trigger,
+                spawn_waves: SpawnWaves {
+                    unit_id: Id::from_name(&event.unit)?,
+                    units_per_wave: event.units_per_wave,
+                    n_waves: event.n_waves,
+                    seconds_between_waves: event.seconds_between_waves,
+                },
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let intro_pan = definition
+        .intro_pan
+        .into_iter()
+        .map(|waypoint| {
+            let (x, y, z) = waypoint.translation;
+            IntroWaypoint {
+                translation: Vec3::new(x, y, z),
+                distance: waypoint.distance,
+                seconds_from_previous: waypoint.seconds_from_previous,
+            }
+        })
+        .collect();
+
+    Ok(Scenario::new(events).with_intro_pan(intro_pan))
+}
+
+/// Loads the scenario called `name` from `<name>.ron` in `directory`.
+pub fn load_scenario(directory: &Path, name: &str) -> Result<Scenario, DefinitionError> {
+    scenario(read_definitions(directory, &format!("{name}.ron"))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy::utils::HashSet;
+    use crate::{
+        simulation::scenario::DEFAULT_SCENARIO, units::varieties::built_in_unit_varieties,
+    };
+
+    /// A small set of items, written as they would be in `items.ron`.
+    const ITEMS: &str = r#"[
+        (id: "acacia_leaf", stack_size: 10, spoilage: Some((shelf_life_in_days: 3.0, rots_into: "fertilizer"))),
+        (id: "fertilizer", stack_size: 10),
+    ]"#;
+
+    #[test]
+    fn items_can_be_defined() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+
+        assert_eq!(
+            *item_manifest.get(Id::acacia_leaf()),
+            ItemData::acacia_leaf()
+        );
+        assert_eq!(*item_manifest.get(Id::fertilizer()), ItemData::fertilizer());
+    }
+
+    #[test]
+    fn recipes_can_be_defined() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let recipes = r#"[(
+            id: "composting",
+            inputs: [("acacia_leaf", 2)],
+            outputs: [("fertilizer", 1)],
+            craft_time_in_seconds: 10.0,
+            structure: "composter",
+        )]"#;
+
+        let recipe_manifest =
+            recipe_manifest(ron::from_str(recipes).unwrap(), &item_manifest).unwrap();
+        let recipe = recipe_manifest.get(Id::composting());
+
+        assert_eq!(recipe.inputs(), RecipeData::composting().inputs());
+        assert_eq!(recipe.outputs(), RecipeData::composting().outputs());
+        assert_eq!(recipe.craft_time(), RecipeData::composting().craft_time());
+        assert!(recipe.can_be_crafted_at(Id::from_string_id("composter")));
+    }
+
+    #[test]
+    fn unknown_items_are_rejected() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let recipes = r#"[(
+            id: "alchemy",
+            inputs: [("lead", 1)],
+            outputs: [("fertilizer", 1)],
+            craft_time_in_seconds: 1.0,
+            structure: "composter",
+        )]"#;
+
+        assert!(matches!(
+            recipe_manifest(ron::from_str(recipes).unwrap(), &item_manifest),
+            Err(DefinitionError::UnknownItem { .. })
+        ));
+    }
+
+    #[test]
+    fn colliding_ids_are_rejected() {
+        // These two string identifiers hash to the same value
+        let items = r#"[
+            (id: "iyzovjuj", stack_size: 1),
+            (id: "jsvixmcr", stack_size: 1),
+        ]"#;
+
+        assert!(matches!(
+            item_manifest(ron::from_str(items).unwrap()),
+            Err(DefinitionError::Collision(..))
+        ));
+    }
+
+    /// The item and number of each of the construction materials of `data`.
+    fn materials(data: &StructureData) -> Vec<(Id<Item>, usize)> {
+        data.construction_materials()
+            .iter()
+            .map(|slot| (slot.item_id(), slot.max_item_count()))
+            .collect()
+    }
+
+    #[test]
+    fn structures_can_be_defined() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let structures = r#"[(
+            id: "acacia",
+            model: "structures/acacia.gltf#Scene0",
+            allowed_terrain: [Plain],
+            construction_materials: [("acacia_leaf", 3)],
+            build_time_in_seconds: 2.0,
+            growth_requirements: Some((min_moisture: 0.5)),
+        )]"#;
+
+        let structure_manifest = structure_manifest(
+            ron::from_str(structures).unwrap(),
+            &item_manifest,
+            built_in_structures(),
+        )
+        .unwrap();
+        let acacia = structure_manifest.get(Id::from_string_id("acacia"));
+
+        assert_eq!(
+            acacia.allowed_terrain_types,
+            HashSet::from_iter([Terrain::Plain])
+        );
+        assert_eq!(materials(acacia), vec![(Id::acacia_leaf(), 3)]);
+        assert_eq!(acacia.build_duration(), Duration::from_secs(2));
+        assert_eq!(
+            acacia.growth_requirements(),
+            Some(&GrowthRequirements {
+                min_moisture: 0.5,
+                ..Default::default()
+            })
+        );
+        // Behavior that cannot be defined in the file is kept
+        assert!(acacia.tags().contains(&"crafting"));
+    }
+
+    #[test]
+    fn unknown_structures_are_plain() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let structures = r#"[(
+            id: "boardwalk",
+            model: "structures/hatchery.gltf#Scene0",
+            allowed_terrain: [Muddy, Water],
+            crossing: Some((walking_speed: 2.0, climbs_cliffs: false)),
+        )]"#;
+
+        let structure_manifest = structure_manifest(
+            ron::from_str(structures).unwrap(),
+            &item_manifest,
+            HashMap::new(),
+        )
+        .unwrap();
+        let boardwalk = structure_manifest.get(Id::from_string_id("boardwalk"));
+
+        assert_eq!(boardwalk.tags(), vec!["crossing"]);
+        assert!(boardwalk.construction_materials().is_empty());
+        assert_eq!(boardwalk.build_duration(), Duration::ZERO);
+        assert_eq!(
+            boardwalk.crossing(),
+            Some(Crossing {
+                walking_speed: 2.,
+                climbs_cliffs: false,
+            })
+        );
+    }
+
+    #[test]
+    fn left_out_fields_keep_the_built_in_values() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let structures = r#"[(
+            id: "bridge",
+            model: "structures/hatchery.gltf#Scene0",
+            allowed_terrain: [Water],
+        )]"#;
+
+        let structure_manifest = structure_manifest(
+            ron::from_str(structures).unwrap(),
+            &item_manifest,
+            built_in_structures(),
+        )
+        .unwrap();
+        let bridge = structure_manifest.get(Id::from_string_id("bridge"));
+        let built_in = &built_in_structures()[&Id::from_string_id("bridge")];
+
+        assert_eq!(materials(bridge), materials(built_in));
+        assert_eq!(bridge.build_duration(), built_in.build_duration());
+        assert_eq!(bridge.crossing(), built_in.crossing());
+        assert!(bridge.crossing().is_some());
+    }
+
+    #[test]
+    fn growth_requirements_of_non_organisms_are_rejected() {
+        let item_manifest = item_manifest(ron::from_str(ITEMS).unwrap()).unwrap();
+        let structures = r#"[(
+            id: "ant_hive",
+            model: "structures/ant_hive.gltf#Scene0",
+            allowed_terrain: [Plain],
+            growth_requirements: Some((min_light: 0.5)),
+        )]"#;
+
+        assert!(matches!(
+            structure_manifest(
+                ron::from_str(structures).unwrap(),
+                &item_manifest,
+                built_in_structures(),
+            ),
+            Err(DefinitionError::NotAnOrganism(..))
+        ));
+    }
+
+    #[test]
+    fn missing_definitions_fall_back_to_the_built_in_ones() {
+        let missing: Result<Vec<ItemDefinition>, _> =
+            read_definitions(Path::new("does/not/exist"), "items.ron");
+
+        assert_eq!(loaded_or_built_in(missing, Vec::new), Vec::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn broken_definitions_are_not_hidden() {
+        let broken = ron::from_str::<Vec<ItemDefinition>>("[(id: ")
+            .map_err(|error| DefinitionError::Invalid(PathBuf::from("items.ron"), error));
+
+        loaded_or_built_in(broken, Vec::new);
+    }
+
+    #[test]
+    fn unit_varieties_can_be_defined() {
+        let units = r#"[(
+            id: "ant",
+            walking_speed: 2.0,
+            carry_capacity: 4,
+            goal_weights: [(Work, 0.0)],
+            model: "units/ant.gltf#Scene0",
+            animations: [(Idle, 0), (Walking, 2)],
+        )]"#;
+
+        let unit_varieties = unit_varieties(ron::from_str(units).unwrap()).unwrap();
+        let ant = &unit_varieties[&Id::ant()];
+        assert_eq!(ant.walking_speed, 2.);
+        assert_eq!(ant.carry_capacity, 4);
+        assert_eq!(ant.goal_weights.multiplier(SignalCategory::Work), 0.);
+        assert_eq!(ant.goal_weights.multiplier(SignalCategory::Push), 1.);
+        assert_eq!(ant.animations.clip(AnimationState::Walking), Some(2));
+        assert_eq!(ant.animations.clip(AnimationState::Working), Some(0));
+    }
+
+    #[test]
+    fn the_shipped_definitions_match_the_built_in_ones() {
+        let directory =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../emergence_game/assets/definitions");
+
+        let item_manifest = load_items(&directory).unwrap();
+        for item_id in item_manifest.variants() {
+            let built_in = match item_id.name() {
+                Some("acacia_leaf") => ItemData::acacia_leaf(),
+                Some("leuco_chunk") => ItemData::leuco_chunk(),
+                Some("ant_egg") => ItemData::ant_egg(),
+                Some("fertilizer") => ItemData::fertilizer(),
+                Some("water") => ItemData::water(),
+                Some("corpse") => ItemData::corpse(),
+                _ => continue,
+            };
+            assert_eq!(*item_manifest.get(item_id), built_in);
+        }
+
+        let recipe_manifest = load_recipes(&directory, &item_manifest).unwrap();
+        let hatch_ants = recipe_manifest.get(Id::hatch_ants());
+        assert!(hatch_ants.work_required());
+        assert!(hatch_ants.can_be_crafted_at(Id::from_string_id("hatchery")));
+
+        let structure_models = load_structure_models(&directory).unwrap();
+        assert!(structure_models.contains_key(&Id::from_string_id("ant_hive")));
+
+        let structure_manifest = load_structures(&directory, &item_manifest).unwrap();
+        for (structure_id, built_in) in built_in_structures() {
+            let loaded = structure_manifest.get(structure_id);
+            assert_eq!(loaded.allowed_terrain_types, built_in.allowed_terrain_types);
+            assert_eq!(materials(loaded), materials(&built_in));
+            assert_eq!(loaded.build_duration(), built_in.build_duration());
+            assert_eq!(loaded.crossing(), built_in.crossing());
+            assert_eq!(loaded.growth_requirements(), built_in.growth_requirements());
+        }
+
+        let unit_varieties = load_unit_varieties(&directory).unwrap();
+        assert_eq!(unit_varieties, built_in_unit_varieties());
+    }
+
+    #[test]
+    fn scenarios_can_be_defined() {
+        let definition = r#"(
+            events: [(
+                name: "A trickle of beetles",
+                trigger: EveryNDays(first_day: 5, period: 10),
+                unit: "beetle",
+                units_per_wave: 2,
+                n_waves: 1,
+                seconds_between_waves: 5.0,
+            )],
+        )"#;
+
+        let scenario = scenario(ron::from_str(definition).unwrap()).unwrap();
+        assert_eq!(scenario.events.len(), 1);
+        assert_eq!(
+            scenario.events[0].trigger,
+            EventTrigger::EveryNDays {
+                first_day: 5,
+                period: 10
+            }
+        );
+        assert_eq!(scenario.events[0].spawn_waves.unit_id, Id::beetle());
+        assert!(scenario.intro_pan.is_empty());
+    }
+
+    #[test]
+    fn the_shipped_default_scenario_matches_the_built_in_one() {
+        let directory =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../emergence_game/assets/scenarios");
+        let scenario = load_scenario(&directory, DEFAULT_SCENARIO).unwrap();
+        let built_in = Scenario::built_in();
+
+        assert_eq!(scenario.events, built_in.events);
+        assert_eq!(scenario.intro_pan, built_in.intro_pan);
+    }
+}
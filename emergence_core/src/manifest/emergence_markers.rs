@@ -12,19 +12,24 @@ use super::Manifest;
 pub struct Recipe;
 
 /// Stores the read-only definitions for all recipes.
-pub(crate) type RecipeManifest = Manifest<Recipe, RecipeData>;
+pub type RecipeManifest = Manifest<Recipe, RecipeData>;
 
 /// The marker type for [`Id<Unit>`](super::Id).
 pub struct Unit;
 /// Stores the read-only definitions for all units.
-pub(crate) type UnitManifest = Manifest<Unit, UnitData>;
+pub type UnitManifest = Manifest<Unit, UnitData>;
 
 /// The marker type for [`Id<Structure>`](super::Id).
 pub struct Structure;
 /// Stores the read-only definitions for all structures.
-pub(crate) type StructureManifest = Manifest<Structure, StructureData>;
+pub type StructureManifest = Manifest<Structure, StructureData>;
 
 /// The marker type for [`Id<Item>`](super::Id).
 pub struct Item;
 /// Stores the read-only definitions for all items.
-pub(crate) type ItemManifest = Manifest<Item, ItemData>;
+pub type ItemManifest = Manifest<Item, ItemData>;
+
+/// The marker type for [`Id<SignalKind>`](super::Id).
+///
+/// These identify the custom signals registered in the [`SignalKindRegistry`](crate::signals::SignalKindRegistry).
+pub struct SignalKind;
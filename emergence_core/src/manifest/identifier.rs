@@ -0,0 +1,346 @@
+//! Code for a generic identifier type
+
+use crate::bevy::{
+    prelude::{warn, Component},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    sync::RwLock,
+};
+
+/// The unique identifier of type `T`.
+///
+/// This is tiny [`Copy`] type, used to quickly and uniquely identify game objects.
+/// Unlike enum variants, these can be read from disk and constructred at runtime.
+///
+/// It can be stored as a component to identify the variety of game object used.
+#[derive(Component, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Id<T> {
+    /// The unique identifier.
+    ///
+    /// This is usually the hash of a string identifier used in the manifest files.
+    /// The number value is used to handle the data more efficiently in the game.
+    value: u64,
+
+    /// Marker to make the compiler happy
+    #[serde(skip)]
+    _phantom: PhantomData<T>,
+}
+
+/// A constant used in the hashing algorithm of the IDs.
+///
+/// This should be a positive prime number, roughly equal to the number of characters in the input alphabet.
+const HASH_P: u64 = 53;
+
+/// A constant used in the hashing algorithm of the IDs.
+///
+/// This should be a large prime number as it is used for modulo operations.
+/// Larger numbers have a lower chance of a hash collision.
+const HASH_M: u64 = 1_000_000_009;
+
+/// Interns the human-readable string identifiers that IDs are created from.
+///
+/// Each distinct string identifier is assigned a dense index, in the order that they are first seen.
+/// These are shared between all types of ID, as the string identifiers are globally unique in practice.
+struct Interner {
+    /// The string identifier of each interned ID, in the order they were interned
+    names: Vec<&'static str>,
+    /// The position in `names` of each interned ID, keyed by ID value
+    indices: BTreeMap<u64, usize>,
+}
+
+/// The global [`Interner`] used by all IDs.
+static INTERNER: RwLock<Interner> = RwLock::new(Interner {
+    names: Vec::new(),
+    indices: BTreeMap::new(),
+});
+
+/// The dense index and string identifier of an interned ID.
+#[derive(Debug, Clone, Copy)]
+struct Interned {
+    /// The position of the string identifier in the [`Interner`]
+    index: usize,
+    /// The string identifier
+    name: &'static str,
+}
+
+thread_local! {
+    /// The IDs that this thread has already interned or looked up, keyed by ID value.
+    ///
+    /// IDs are frequently recreated and displayed in hot systems, so this avoids locking the shared [`INTERNER`] each time.
+    static KNOWN_HERE: RefCell<HashMap<u64, Interned>> = RefCell::new(HashMap::default());
+}
+
+/// Two different string identifiers hash to the same [`Id`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdCollision {
+    /// The string identifier that was interned first
+    pub interned: &'static str,
+    /// The string identifier that collides with it
+    pub name: String,
+}
+
+impl Display for IdCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the string identifiers {} and {} hash to the same ID",
+            self.interned, self.name
+        )
+    }
+}
+
+/// Records that `name` is the string identifier of the ID with the given `value`.
+///
+/// `to_static` is only called if no string identifier has been interned for this value yet.
+fn intern(
+    value: u64,
+    name: &str,
+    to_static: impl FnOnce() -> &'static str,
+) -> Result<Interned, IdCollision> {
+    let interned = match KNOWN_HERE.with(|known| known.borrow().get(&value).copied()) {
+        Some(interned) => interned,
+        None => {
+            let mut interner = INTERNER.write().unwrap();
+            let index = match interner.indices.get(&value) {
+                Some(&index) => index,
+                None => {
+                    let index = interner.names.len();
+                    interner.names.push(to_static());
+                    interner.indices.insert(value, index);
+                    index
+                }
+            };
+
+            let interned = Interned {
+                index,
+                name: interner.names[index],
+            };
+            KNOWN_HERE.with(|known| known.borrow_mut().insert(value, interned));
+            interned
+        }
+    };
+
+    if interned.name == name {
+        Ok(interned)
+    } else {
+        Err(IdCollision {
+            interned: interned.name,
+            name: name.to_string(),
+        })
+    }
+}
+
+/// The interned string identifier of the ID with the given `value`, if any.
+fn lookup(value: u64) -> Option<Interned> {
+    KNOWN_HERE.with(|known| {
+        if let Some(&interned) = known.borrow().get(&value) {
+            return Some(interned);
+        }
+
+        let interner = INTERNER.read().unwrap();
+        let &index = interner.indices.get(&value)?;
+        let interned = Interned {
+            index,
+            name: interner.names[index],
+        };
+        known.borrow_mut().insert(value, interned);
+        Some(interned)
+    })
+}
+
+impl<T> Id<T> {
+    /// Create a new identifier from the given unique number.
+    pub const fn new(value: u64) -> Self {
+        Self {
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The unique number that this ID wraps.
+    ///
+    /// This is stable between runs, so it is suitable for saving to disk.
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Creates a new ID from human-readable string identifier.
+    ///
+    /// This ID is created as a hash of the string.
+    pub fn from_string_id(str: &'static str) -> Self {
+        let value = hash_string_id(str);
+
+        let first_time_here = KNOWN_HERE.with(|known| !known.borrow().contains_key(&value));
+        if first_time_here {
+            // The built-in string identifiers are also read from the definition files,
+            // so any collision between them is reported as an error when the definitions are loaded
+            if let Err(collision) = intern(value, str, || str) {
+                warn!("{collision}");
+            }
+        }
+
+        Self::new(value)
+    }
+
+    /// Creates a new ID from a string identifier that is only known at runtime, such as one read from a file.
+    ///
+    /// Each new string identifier is leaked, so that it can be interned for the rest of the program.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a different string identifier already hashes to the same ID.
+    pub fn from_name(name: &str) -> Result<Self, IdCollision> {
+        let value = hash_string_id(name);
+        intern(value, name, || Box::leak(name.to_string().into_boxed_str()))?;
+
+        Ok(Self::new(value))
+    }
+
+    /// The human-readable string identifier that this ID was created from, if known.
+    pub fn name(&self) -> Option<&'static str> {
+        lookup(self.value).map(|interned| interned.name)
+    }
+
+    /// A small, dense index that uniquely identifies this ID, if it was created from a string identifier.
+    ///
+    /// Indices are assigned in the order that string identifiers are first seen, starting from 0,
+    /// so they can be used to store data about each ID in a [`Vec`] rather than a hash map.
+    /// They are stable for the lifetime of the program, but not between runs: save the ID itself instead.
+    pub fn index(&self) -> Option<usize> {
+        lookup(self.value).map(|interned| interned.index)
+    }
+}
+
+/// Hashes a human-readable string identifier into the value stored in an [`Id`].
+fn hash_string_id(str: &str) -> u64 {
+    // Algorithm adopted from <https://cp-algorithms.com/string/string-hashing.html>
+
+    let mut value = 0;
+    let mut p_pow = 1;
+
+    str.bytes().for_each(|byte| {
+        value = (value + (byte as u64 + 1) * p_pow) % HASH_M;
+        p_pow = (p_pow * HASH_P) % HASH_M;
+    });
+
+    value
+}
+
+impl<T> Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Id").field("value", &self.value).finish()
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+/// IDs are displayed using the string identifier they were created from, if known.
+impl<T> Display for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "#{}", self.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Id;
+
+    struct Marker;
+
+    #[test]
+    fn ids_display_their_names() {
+        let id = Id::<Marker>::from_string_id("test_interned_name");
+        assert_eq!(id.to_string(), "test_interned_name");
+        assert_eq!(Id::<Marker>::new(7).to_string(), "#7");
+    }
+
+    #[test]
+    fn interned_indices_are_stable_and_unique() {
+        let first = Id::<Marker>::from_string_id("test_first_index");
+        let second = Id::<Marker>::from_name("test_second_index").unwrap();
+
+        assert!(first.index().is_some());
+        assert_ne!(first.index(), second.index());
+        assert_eq!(
+            Id::<Marker>::from_string_id("test_first_index").index(),
+            first.index()
+        );
+        assert_eq!(Id::<Marker>::new(7).index(), None);
+    }
+
+    #[test]
+    fn runtime_names_match_static_names() {
+        let name = String::from("test_runtime_name");
+        let from_name = Id::<Marker>::from_name(&name).unwrap();
+
+        assert_eq!(from_name, Id::<Marker>::from_string_id("test_runtime_name"));
+        assert_eq!(from_name.name(), Some("test_runtime_name"));
+    }
+
+    #[test]
+    fn colliding_names_are_rejected() {
+        let first = Id::<Marker>::from_name("krblvxen").unwrap();
+        let collision = Id::<Marker>::from_name("cwjjzmdh").unwrap_err();
+
+        assert_eq!(collision.interned, "krblvxen");
+        assert_eq!(collision.name, "cwjjzmdh");
+        assert_eq!(first.name(), Some("krblvxen"));
+    }
+
+    #[test]
+    fn known_names_are_remembered_by_each_thread() {
+        let id = std::thread::spawn(|| Id::<Marker>::from_string_id("test_other_thread"))
+            .join()
+            .unwrap();
+
+        assert_eq!(id.name(), Some("test_other_thread"));
+        assert_eq!(Id::<Marker>::from_string_id("test_other_thread"), id);
+        assert_eq!(
+            Id::<Marker>::from_string_id("test_other_thread").index(),
+            id.index()
+        );
+    }
+}
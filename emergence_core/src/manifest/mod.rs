@@ -7,17 +7,20 @@
 pub use self::emergence_markers::*;
 pub use self::identifier::*;
 
+pub mod definitions;
 mod emergence_markers;
 mod identifier;
+#[cfg(feature = "mods")]
+mod mods;
 
-use bevy::{prelude::*, utils::HashMap};
+use crate::bevy::{prelude::*, utils::HashMap};
 use std::fmt::Debug;
 
 /// Write-once data definitions.
 ///
 /// These are intended to be created a single time, via [`Manifest::new`].
 #[derive(Debug, Resource)]
-pub(crate) struct Manifest<T, Data>
+pub struct Manifest<T, Data>
 where
     T: 'static,
     Data: Debug,
@@ -47,6 +50,11 @@ where
             .unwrap_or_else(|| panic!("ID {id} not found in manifest"))
     }
 
+    /// Is there a data entry for the given ID?
+    pub fn contains(&self, id: Id<T>) -> bool {
+        self.map.contains_key(&id)
+    }
+
     /// The complete list of loaded options.
     ///
     /// The order is arbitrary.
@@ -0,0 +1,108 @@
+//! Mods add to and replace the game's definitions, without changing its files.
+//!
+//! Each mod is a folder inside `mods/` in the definitions directory, containing any of the same definition files.
+//! Mods are applied in alphabetical order of their folder's name,
+//! so a mod's definition replaces any earlier definition with the same string identifier.
+
+use serde::Deserialize;
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use super::definitions::{read_definitions, DefinitionError};
+
+/// The folder inside the definitions directory that contains each mod.
+const MODS_FOLDER: &str = "mods";
+
+/// The folder of each mod found in `directory`, in the order that they are applied.
+fn mod_directories(directory: &Path) -> Result<Vec<PathBuf>, DefinitionError> {
+    let mods_directory = directory.join(MODS_FOLDER);
+
+    let entries = match fs::read_dir(&mods_directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(DefinitionError::Unreadable(mods_directory, error)),
+    };
+
+    let mut mod_directories = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| DefinitionError::Unreadable(mods_directory.clone(), error))?
+            .path();
+
+        if path.is_dir() {
+            mod_directories.push(path);
+        }
+    }
+
+    mod_directories.sort();
+    Ok(mod_directories)
+}
+
+/// Adds the definitions in `file_name` of each mod in `directory` after the game's own `definitions`.
+///
+/// Mods do not need to provide every definition file.
+pub(super) fn with_mod_definitions<T: for<'de> Deserialize<'de>>(
+    mut definitions: Vec<T>,
+    directory: &Path,
+    file_name: &str,
+) -> Result<Vec<T>, DefinitionError> {
+    for mod_directory in mod_directories(directory)? {
+        match read_definitions::<Vec<T>>(&mod_directory, file_name) {
+            Ok(mod_definitions) => definitions.extend(mod_definitions),
+            Err(DefinitionError::Missing(_)) => (),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(definitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        items::ItemData,
+        manifest::{definitions::load_items, Id},
+    };
+
+    #[test]
+    fn mods_replace_and_add_definitions() {
+        let directory = std::env::temp_dir().join(format!("emergence_mods_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("mods/bigger_leaves")).unwrap();
+        fs::create_dir_all(directory.join("mods/gems")).unwrap();
+
+        fs::write(
+            directory.join("items.ron"),
+            r#"[(id: "acacia_leaf", stack_size: 10), (id: "fertilizer", stack_size: 10)]"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("mods/bigger_leaves/items.ron"),
+            r#"[(id: "acacia_leaf", stack_size: 20)]"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("mods/gems/items.ron"),
+            r#"[(id: "test_gem", stack_size: 1)]"#,
+        )
+        .unwrap();
+
+        let item_manifest = load_items(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(item_manifest.get(Id::acacia_leaf()).stack_size(), 20);
+        assert_eq!(*item_manifest.get(Id::fertilizer()), ItemData::fertilizer());
+        assert!(item_manifest.contains(Id::from_string_id("test_gem")));
+    }
+
+    #[test]
+    fn no_mods_are_needed() {
+        let directory = Path::new("does/not/exist");
+
+        assert!(mod_directories(directory).unwrap().is_empty());
+    }
+}
@@ -1,18 +1,21 @@
 //! Logic and data types for energy.
 
-use bevy::prelude::*;
+use crate::bevy::{prelude::*, utils::Duration};
 use core::fmt::Display;
 use core::ops::{Div, Mul};
 use derive_more::{Add, AddAssign, Sub, SubAssign};
-use leafwing_abilities::{pool::MaxPoolLessThanZero, prelude::Pool};
 
-use crate::asset_management::manifest::{Id, Structure};
-use crate::{simulation::geometry::TilePos, structures::commands::StructureCommandsExt};
+use crate::manifest::{Id, Structure};
+use crate::{
+    simulation::{chronicle::HistoricalEvent, geometry::TilePos},
+    structures::commands::StructureCommandsExt,
+    terrain::Decompose,
+};
 
 /// The amount of energy available to an organism.
 /// If they run out, they die.
-#[derive(Debug, Clone, PartialEq, Component, Resource)]
-pub(crate) struct EnergyPool {
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct EnergyPool {
     /// The current amount of stored energy.
     current: Energy,
     /// The maximum energy that can be stored.
@@ -22,22 +25,22 @@ pub(crate) struct EnergyPool {
     /// The threshold at which no more action is taken to gain energy.
     satiation_threshold: Energy,
     /// The amount of life regenerated per second.
-    pub(crate) regen_per_second: Energy,
+    pub regen_per_second: Energy,
 }
 
 impl EnergyPool {
     /// Is this organism out of energy?
-    pub(crate) fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.current <= Energy(0.)
     }
 
     /// Is this organism close to running out of energy?
-    pub(crate) fn is_hungry(&self) -> bool {
+    pub fn is_hungry(&self) -> bool {
         self.current <= self.warning_threshold
     }
 
     /// Is this organism close to running out of energy?
-    pub(crate) fn is_satiated(&self) -> bool {
+    pub fn is_satiated(&self) -> bool {
         self.current >= self.satiation_threshold
     }
 }
@@ -52,7 +55,7 @@ impl Display for EnergyPool {
 ///
 /// Organisms produce energy by crafting recipes.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Add, Sub, AddAssign, SubAssign)]
-pub(crate) struct Energy(pub f32);
+pub struct Energy(pub f32);
 
 impl Display for Energy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -84,13 +87,12 @@ impl Div<f32> for Energy {
     }
 }
 
-impl Pool for EnergyPool {
-    type Quantity = Energy;
-    const ZERO: Energy = Energy(0.);
-
-    fn new(current: Self::Quantity, max: Self::Quantity, regen_per_second: Self::Quantity) -> Self {
+impl EnergyPool {
+    /// Creates a new pool with the provided `current` and `max` energy.
+    ///
+    /// `regen_per_second` is added to the current energy each second, and is typically negative.
+    pub fn new(current: Energy, max: Energy, regen_per_second: Energy) -> Self {
         // TODO: don't hard code this.
-        // Blocked on: https://github.com/Leafwing-Studios/leafwing_abilities/issues/18
         let warning_threshold = 0.25 * max;
         let satiation_threshold = 0.75 * max;
 
@@ -103,46 +105,65 @@ impl Pool for EnergyPool {
         }
     }
 
-    fn current(&self) -> Self::Quantity {
+    /// Creates a new pool that starts out full.
+    pub fn new_full(max: Energy, regen_per_second: Energy) -> Self {
+        EnergyPool::new(max, max, regen_per_second)
+    }
+
+    /// The current amount of stored energy.
+    pub fn current(&self) -> Energy {
         self.current
     }
 
-    fn set_current(&mut self, new_quantity: Self::Quantity) -> Self::Quantity {
+    /// Sets the current amount of energy, clamped between zero and the maximum.
+    ///
+    /// Returns the value that was actually set.
+    pub fn set_current(&mut self, new_quantity: Energy) -> Energy {
         let actual_value = Energy(new_quantity.0.clamp(0., self.max.0));
         self.current = actual_value;
         self.current
     }
 
-    fn max(&self) -> Self::Quantity {
+    /// The maximum energy that can be stored.
+    pub fn max(&self) -> Energy {
         self.max
     }
 
-    fn set_max(&mut self, new_max: Self::Quantity) -> Result<(), MaxPoolLessThanZero> {
-        if new_max < Self::ZERO {
-            Err(MaxPoolLessThanZero)
-        } else {
-            self.max = new_max;
-            self.set_current(self.current);
-            Ok(())
-        }
+    /// Applies [`EnergyPool::regen_per_second`] over the `delta_time` that has passed.
+    pub fn regenerate(&mut self, delta_time: Duration) {
+        self.set_current(self.current + self.regen_per_second * delta_time.as_secs_f32());
     }
+}
 
-    fn regen_per_second(&self) -> Self::Quantity {
-        self.regen_per_second
-    }
+/// Regenerates (or drains) the [`EnergyPool`] of every organism as time passes.
+pub(super) fn regenerate_energy(time: Res<Time>, mut energy_query: Query<&mut EnergyPool>) {
+    let delta_time = time.delta();
 
-    fn set_regen_per_second(&mut self, new_regen_per_second: Self::Quantity) {
-        self.regen_per_second = new_regen_per_second;
+    for mut energy_pool in energy_query.iter_mut() {
+        energy_pool.regenerate(delta_time);
     }
 }
 
-/// Despawns organisms when they run out of energy
+/// The fertility returned to the soil when an organism dies and decays.
+const DECOMPOSITION_NUTRIENTS: f32 = 0.2;
+
+/// Despawns organisms when they run out of energy, returning their nutrients to the soil.
 pub(super) fn kill_organisms_when_out_of_energy(
     organism_query: Query<(Entity, &EnergyPool, &TilePos, Option<&Id<Structure>>)>,
+    mut decompose_events: EventWriter<Decompose>,
+    mut historical_events: EventWriter<HistoricalEvent>,
     mut commands: Commands,
 ) {
     for (entity, energy_pool, tile_pos, maybe_structure) in organism_query.iter() {
         if energy_pool.is_empty() {
+            decompose_events.send(Decompose {
+                tile_pos: *tile_pos,
+                nutrients: DECOMPOSITION_NUTRIENTS,
+            });
+            historical_events.send(HistoricalEvent::Starved {
+                tile_pos: *tile_pos,
+            });
+
             match maybe_structure {
                 Some(_) => commands.despawn_structure(*tile_pos),
                 None => commands.entity(entity).despawn_recursive(),
@@ -0,0 +1,312 @@
+//! Records who hatched from where, so the history of each family in the colony can be traced.
+//!
+//! Every unit, and every structure that hatches units, is given a [`Lineage`] when it first appears.
+//! Units hatched by a structure remember it as their parent, one generation further down the [`FamilyTree`].
+//! Old records of organisms that have died are pruned, so long games don't grow the tree forever.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use std::collections::BTreeMap;
+
+use crate::{
+    manifest::{Id, Unit},
+    simulation::time::InGameTime,
+};
+
+use super::individuals::Individual;
+
+/// The maximum number of records kept in the [`FamilyTree`].
+const MAX_RECORDS: usize = 1000;
+
+/// Records the birth and death of every member of the colony.
+pub(super) struct GenealogyPlugin;
+
+impl Plugin for GenealogyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FamilyTree>()
+            .add_system(record_founders)
+            .add_system(record_names.after(record_founders))
+            .add_system(
+                record_deaths
+                    .after(record_names)
+                    .in_base_set(CoreSet::PostUpdate),
+            );
+    }
+}
+
+/// A unique identifier for a member of the [`FamilyTree`].
+///
+/// Unlike [`Entity`]s, these are never reused, so records remain correct after their organism has died.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineageId(u64);
+
+/// The place of an organism in the [`FamilyTree`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lineage {
+    /// The identifier of this organism's record
+    pub id: LineageId,
+}
+
+/// Everything that is remembered about a single member of the [`FamilyTree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageRecord {
+    /// The name of the organism, once it has one
+    pub name: Option<String>,
+    /// The organism that this one hatched from, if it was born rather than founded
+    pub parent: Option<LineageId>,
+    /// The number of ancestors of this organism: founders are generation 0
+    pub generation: u32,
+    /// The day on which this organism appeared
+    pub born_on: u32,
+    /// The day on which this organism died, if it has
+    pub died_on: Option<u32>,
+}
+
+impl LineageRecord {
+    /// The name of the organism, or a placeholder if it never had one.
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("Unnamed")
+    }
+}
+
+/// The parentage of every member of the colony, living or recently dead.
+#[derive(Resource, Debug, Default)]
+pub struct FamilyTree {
+    /// The records of each organism, in the order they appeared
+    records: BTreeMap<LineageId, LineageRecord>,
+    /// The record of each living organism
+    living: HashMap<Entity, LineageId>,
+    /// The identifier that will be given to the next record
+    next_id: u64,
+}
+
+impl FamilyTree {
+    /// Adds a record for an organism that appeared without a parent.
+    pub fn record_founder(&mut self, entity: Entity, today: u32) -> Lineage {
+        self.record(entity, None, 0, today)
+    }
+
+    /// Adds a record for an organism that hatched from `parent`.
+    pub fn record_birth(&mut self, entity: Entity, parent: Lineage, today: u32) -> Lineage {
+        let generation = self
+            .records
+            .get(&parent.id)
+            .map_or(1, |record| record.generation + 1);
+
+        self.record(entity, Some(parent.id), generation, today)
+    }
+
+    /// Adds a new record to the tree.
+    fn record(
+        &mut self,
+        entity: Entity,
+        parent: Option<LineageId>,
+        generation: u32,
+        today: u32,
+    ) -> Lineage {
+        let id = LineageId(self.next_id);
+        self.next_id += 1;
+
+        self.records.insert(
+            id,
+            LineageRecord {
+                name: None,
+                parent,
+                generation,
+                born_on: today,
+                died_on: None,
+            },
+        );
+        self.living.insert(entity, id);
+        self.prune(MAX_RECORDS);
+
+        Lineage { id }
+    }
+
+    /// Marks the record of `entity` as dead.
+    fn record_death(&mut self, entity: Entity, today: u32) {
+        if let Some(id) = self.living.remove(&entity) {
+            if let Some(record) = self.records.get_mut(&id) {
+                record.died_on = Some(today);
+            }
+        }
+    }
+
+    /// Removes the oldest records of dead organisms until at most `max_records` remain.
+    ///
+    /// Living organisms are never forgotten, even if their ancestors are.
+    fn prune(&mut self, max_records: usize) {
+        let n_excess = self.records.len().saturating_sub(max_records);
+        if n_excess == 0 {
+            return;
+        }
+
+        let forgotten: Vec<LineageId> = self
+            .records
+            .iter()
+            .filter(|(_, record)| record.died_on.is_some())
+            .map(|(&id, _)| id)
+            .take(n_excess)
+            .collect();
+
+        for id in forgotten {
+            self.records.remove(&id);
+        }
+    }
+
+    /// The record with the provided `id`, if it has not been pruned.
+    pub fn get(&self, id: LineageId) -> Option<&LineageRecord> {
+        self.records.get(&id)
+    }
+
+    /// The record of the living organism `entity`, if it is part of the tree.
+    pub fn living(&self, entity: Entity) -> Option<LineageId> {
+        self.living.get(&entity).copied()
+    }
+
+    /// The ancestors of `id` that are still remembered, from its parent back to its founder.
+    pub fn ancestors(&self, id: LineageId) -> Vec<LineageId> {
+        let mut ancestors = Vec::new();
+        let mut current = self.records.get(&id).and_then(|record| record.parent);
+
+        while let Some(parent) = current {
+            match self.records.get(&parent) {
+                Some(record) => {
+                    ancestors.push(parent);
+                    current = record.parent;
+                }
+                // The rest of the family has been forgotten
+                None => break,
+            }
+        }
+
+        ancestors
+    }
+
+    /// The remembered children of `id`, from oldest to youngest.
+    pub fn children(&self, id: LineageId) -> Vec<LineageId> {
+        self.records
+            .iter()
+            .filter(|(_, record)| record.parent == Some(id))
+            .map(|(&child, _)| child)
+            .collect()
+    }
+
+    /// The number of living organisms in each generation, starting from the founders.
+    pub fn living_per_generation(&self) -> Vec<usize> {
+        let mut counts = Vec::new();
+
+        for record in self
+            .records
+            .values()
+            .filter(|record| record.died_on.is_none())
+        {
+            let generation = record.generation as usize;
+            if counts.len() <= generation {
+                counts.resize(generation + 1, 0);
+            }
+            counts[generation] += 1;
+        }
+
+        counts
+    }
+
+    /// The total number of records in the tree.
+    pub fn n_records(&self) -> usize {
+        self.records.len()
+    }
+}
+
+/// Units that were not hatched, such as those present at the start of the game, found their own lineage.
+fn record_founders(
+    unit_query: Query<Entity, (With<Id<Unit>>, Without<Lineage>)>,
+    in_game_time: Res<InGameTime>,
+    mut family_tree: ResMut<FamilyTree>,
+    mut commands: Commands,
+) {
+    let today = in_game_time.current_day();
+
+    for entity in unit_query.iter() {
+        let lineage = family_tree.record_founder(entity, today);
+        commands.entity(entity).insert(lineage);
+    }
+}
+
+/// Copies the names of organisms into the tree, so they are remembered after death.
+fn record_names(
+    named_query: Query<(&Lineage, &Individual), Changed<Individual>>,
+    mut family_tree: ResMut<FamilyTree>,
+) {
+    for (lineage, individual) in named_query.iter() {
+        if let Some(record) = family_tree.records.get_mut(&lineage.id) {
+            record.name = Some(individual.name.clone());
+        }
+    }
+}
+
+/// Marks the records of despawned organisms as dead.
+fn record_deaths(
+    mut removed_lineages: RemovedComponents<Lineage>,
+    in_game_time: Res<InGameTime>,
+    mut family_tree: ResMut<FamilyTree>,
+) {
+    let today = in_game_time.current_day();
+
+    for entity in removed_lineages.iter() {
+        family_tree.record_death(entity, today);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generations_increase_with_each_birth() {
+        let mut family_tree = FamilyTree::default();
+
+        let founder = family_tree.record_founder(Entity::from_raw(0), 0);
+        let child = family_tree.record_birth(Entity::from_raw(1), founder, 1);
+        let grandchild = family_tree.record_birth(Entity::from_raw(2), child, 2);
+
+        assert_eq!(family_tree.get(grandchild.id).unwrap().generation, 2);
+        assert_eq!(
+            family_tree.ancestors(grandchild.id),
+            vec![child.id, founder.id]
+        );
+        assert_eq!(family_tree.children(founder.id), vec![child.id]);
+        assert_eq!(family_tree.living_per_generation(), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn only_the_dead_are_pruned() {
+        let mut family_tree = FamilyTree::default();
+
+        let founder = family_tree.record_founder(Entity::from_raw(0), 0);
+        let child = family_tree.record_birth(Entity::from_raw(1), founder, 1);
+        let living = family_tree.record_founder(Entity::from_raw(2), 1);
+
+        family_tree.record_death(Entity::from_raw(0), 3);
+        family_tree.record_death(Entity::from_raw(1), 4);
+        family_tree.prune(1);
+
+        assert_eq!(family_tree.n_records(), 1);
+        assert!(family_tree.get(living.id).is_some());
+        assert!(family_tree.get(child.id).is_none());
+    }
+
+    #[test]
+    fn pruned_ancestors_are_forgotten() {
+        let mut family_tree = FamilyTree::default();
+
+        let founder = family_tree.record_founder(Entity::from_raw(0), 0);
+        let child = family_tree.record_birth(Entity::from_raw(1), founder, 1);
+        let grandchild = family_tree.record_birth(Entity::from_raw(2), child, 2);
+
+        family_tree.record_death(Entity::from_raw(0), 3);
+        family_tree.prune(2);
+
+        assert_eq!(family_tree.ancestors(grandchild.id), vec![child.id]);
+        // Generations are remembered even when the ancestors are not
+        assert_eq!(family_tree.get(grandchild.id).unwrap().generation, 2);
+    }
+}
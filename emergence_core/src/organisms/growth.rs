@@ -0,0 +1,193 @@
+//! The environmental conditions that sessile organisms need in order to grow.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use serde::Deserialize;
+
+use crate::{
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        time::TimeOfDay,
+    },
+    structures::crafting::CraftingState,
+    terrain::{Fertility, SoilMoisture},
+};
+
+/// The light, moisture and fertility conditions that an organism needs in order to grow.
+///
+/// Terrain restrictions are handled separately, via `StructureData::allowed_terrain_types`.
+///
+/// Requirements that are left out of a definition file impose no limit.
+#[derive(Component, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GrowthRequirements {
+    /// The minimum light level, from 0 to 1.
+    pub min_light: f32,
+    /// The minimum soil moisture, from 0 to 1.
+    pub min_moisture: f32,
+    /// The maximum soil moisture, from 0 to 1.
+    pub max_moisture: f32,
+    /// The minimum soil fertility, from 0 to 1.
+    pub min_fertility: f32,
+}
+
+impl Default for GrowthRequirements {
+    fn default() -> Self {
+        GrowthRequirements {
+            min_light: 0.,
+            min_moisture: 0.,
+            max_moisture: 1.,
+            min_fertility: 0.,
+        }
+    }
+}
+
+impl GrowthRequirements {
+    /// Checks whether or not an organism can grow with the provided `light`, `moisture` and `fertility` levels.
+    ///
+    /// If it cannot, the first requirement that was not met is returned.
+    pub fn check(
+        &self,
+        light: f32,
+        moisture: f32,
+        fertility: f32,
+    ) -> Result<(), UnmetGrowthRequirement> {
+        if light < self.min_light {
+            Err(UnmetGrowthRequirement::TooDark)
+        } else if moisture < self.min_moisture {
+            Err(UnmetGrowthRequirement::TooDry)
+        } else if moisture > self.max_moisture {
+            Err(UnmetGrowthRequirement::TooWet)
+        } else if fertility < self.min_fertility {
+            Err(UnmetGrowthRequirement::TooBarren)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The fertility at which organisms grow at their normal rate.
+pub const NORMAL_FERTILITY: f32 = 0.5;
+
+/// The multiplier applied to the growth rate of organisms on soil with the provided `fertility`.
+///
+/// Rich soil speeds growth up to 1.5 times the normal rate, while barren soil slows it to half.
+pub fn fertility_growth_multiplier(fertility: f32) -> f32 {
+    1. + (fertility.clamp(0., 1.) - NORMAL_FERTILITY)
+}
+
+/// The fertility drawn from the soil each second by each growing organism.
+const NUTRIENT_CONSUMPTION_PER_SECOND: f32 = 0.0002;
+
+/// A reason why an organism cannot grow at its current location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub enum UnmetGrowthRequirement {
+    /// There is not enough light.
+    TooDark,
+    /// The soil is too dry.
+    TooDry,
+    /// The soil is too wet.
+    TooWet,
+    /// The soil is not fertile enough.
+    TooBarren,
+}
+
+impl Display for UnmetGrowthRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            UnmetGrowthRequirement::TooDark => "Too dark",
+            UnmetGrowthRequirement::TooDry => "Too dry",
+            UnmetGrowthRequirement::TooWet => "Too wet",
+            UnmetGrowthRequirement::TooBarren => "Too barren",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Marks an organism whose [`GrowthRequirements`] are not met, pausing its growth.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct Stunted(pub UnmetGrowthRequirement);
+
+/// Adds or removes [`Stunted`] based on the conditions at each organism's tile.
+pub(super) fn check_growth_conditions(
+    organism_query: Query<(Entity, &TilePos, &GrowthRequirements, Option<&Stunted>)>,
+    terrain_query: Query<(&SoilMoisture, &Fertility)>,
+    map_geometry: Res<MapGeometry>,
+    time_of_day: Res<TimeOfDay>,
+    mut commands: Commands,
+) {
+    for (entity, &tile_pos, growth_requirements, maybe_stunted) in organism_query.iter() {
+        let (moisture, fertility) = match map_geometry.terrain_index.get(&tile_pos) {
+            Some(&terrain_entity) => terrain_query.get(terrain_entity).unwrap(),
+            None => continue,
+        };
+
+        // Organisms that need light can only photosynthesize while the sun is up
+        let light = map_geometry.light_level(tile_pos) * time_of_day.daylight();
+
+        match (
+            growth_requirements.check(light, moisture.0, fertility.0),
+            maybe_stunted,
+        ) {
+            (Ok(()), Some(_)) => {
+                commands.entity(entity).remove::<Stunted>();
+            }
+            (Err(reason), maybe_stunted) => {
+                if maybe_stunted.map(|stunted| stunted.0) != Some(reason) {
+                    commands.entity(entity).insert(Stunted(reason));
+                }
+            }
+            (Ok(()), None) => (),
+        }
+    }
+}
+
+/// Organisms that are actively growing draw nutrients out of the soil beneath them.
+pub(super) fn consume_nutrients(
+    time: Res<Time>,
+    organism_query: Query<(&TilePos, &CraftingState), (With<GrowthRequirements>, Without<Stunted>)>,
+    mut fertility_query: Query<&mut Fertility>,
+    map_geometry: Res<MapGeometry>,
+) {
+    let consumed = NUTRIENT_CONSUMPTION_PER_SECOND * time.delta_seconds();
+
+    for (tile_pos, crafting_state) in organism_query.iter() {
+        if !matches!(crafting_state, CraftingState::InProgress { .. }) {
+            continue;
+        }
+
+        if let Some(&terrain_entity) = map_geometry.terrain_index.get(tile_pos) {
+            if let Ok(mut fertility) = fertility_query.get_mut(terrain_entity) {
+                fertility.deplete(consumed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barren_soil_stunts_growth() {
+        let requirements = GrowthRequirements {
+            min_fertility: 0.3,
+            ..Default::default()
+        };
+
+        assert_eq!(requirements.check(1., 0.5, 0.5), Ok(()));
+        assert_eq!(
+            requirements.check(1., 0.5, 0.1),
+            Err(UnmetGrowthRequirement::TooBarren)
+        );
+    }
+
+    #[test]
+    fn richer_soil_grows_faster() {
+        assert_eq!(fertility_growth_multiplier(NORMAL_FERTILITY), 1.);
+        assert!(fertility_growth_multiplier(1.) > fertility_growth_multiplier(0.5));
+        assert!(fertility_growth_multiplier(0.) > 0.);
+    }
+}
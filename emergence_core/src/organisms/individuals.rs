@@ -0,0 +1,289 @@
+//! Gives each organism a name and a life story, so that players can get attached to them.
+//!
+//! Names are generated when an organism first appears, and can be changed by sending a [`RenameIndividual`] event.
+//! The most remarkable organisms are tracked in the [`NotableIndividuals`] resource.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    simulation::{
+        geometry::TilePos,
+        jitter::{Jitter, JitterStream},
+        time::every_second,
+    },
+    structures::crafting::CraftingState,
+};
+
+use super::Organism;
+
+/// Names, ages and tracks the achievements of individual organisms.
+pub(super) struct IndividualsPlugin;
+
+impl Plugin for IndividualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NotableIndividuals>()
+            .add_event::<RenameIndividual>()
+            .add_system(name_new_organisms)
+            .add_system(rename_individuals.after(name_new_organisms))
+            .add_system(count_production)
+            .add_system(
+                update_individuals
+                    .run_if(every_second())
+                    .after(rename_individuals)
+                    .after(count_production),
+            );
+    }
+}
+
+/// The identity and life story of a single organism.
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Individual {
+    /// The name of this organism
+    pub name: String,
+    /// How long this organism has been alive, in seconds
+    pub age: f32,
+    /// The number of recipes this organism has completed
+    pub produced: u32,
+}
+
+impl Individual {
+    /// A newborn individual called `name`.
+    pub fn new(name: String) -> Self {
+        Individual {
+            name,
+            age: 0.,
+            produced: 0,
+        }
+    }
+}
+
+impl Display for Individual {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = &self.name;
+        let age = self.age;
+        let produced = self.produced;
+
+        write!(f, "{name} (age {age:.0} s, produced {produced})")
+    }
+}
+
+/// The syllables that generated names are built from.
+const SYLLABLES: [&str; 24] = [
+    "ka", "ri", "to", "mu", "ne", "sa", "lo", "vi", "da", "pe", "zu", "ha", "mi", "ro", "te", "fa",
+    "gu", "li", "no", "bi", "sho", "wen", "tal", "dor",
+];
+
+/// Generates a random, pronounceable name of two or three syllables.
+pub fn generate_name(rng: &mut impl Rng) -> String {
+    let n_syllables = rng.gen_range(2..=3);
+    let name: String = (0..n_syllables)
+        .map(|_| *SYLLABLES.choose(rng).unwrap())
+        .collect();
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Asks for an organism to be given a new name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameIndividual {
+    /// The organism to rename
+    pub entity: Entity,
+    /// Its new name
+    pub name: String,
+}
+
+/// An organism that stands out from the crowd.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotableIndividual {
+    /// The organism itself
+    pub entity: Entity,
+    /// Where the organism can be found
+    pub tile_pos: TilePos,
+    /// Who the organism is, and what it has done
+    pub individual: Individual,
+}
+
+impl Display for NotableIndividual {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.individual, self.tile_pos)
+    }
+}
+
+/// The most remarkable organisms currently alive.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct NotableIndividuals {
+    /// The organism that has been alive the longest
+    pub oldest: Option<NotableIndividual>,
+    /// The organism that has completed the most recipes
+    pub most_prolific: Option<NotableIndividual>,
+}
+
+impl NotableIndividuals {
+    /// Picks out the notable individuals from every living organism.
+    ///
+    /// Ties are broken in favor of the earliest organism in `individuals`, so the result doesn't flicker.
+    pub fn find<'a>(
+        individuals: impl IntoIterator<Item = (Entity, TilePos, &'a Individual)>,
+    ) -> Self {
+        let mut notables = NotableIndividuals::default();
+
+        for (entity, tile_pos, individual) in individuals {
+            let notable = || NotableIndividual {
+                entity,
+                tile_pos,
+                individual: individual.clone(),
+            };
+
+            if notables
+                .oldest
+                .as_ref()
+                .is_none_or(|oldest| individual.age > oldest.individual.age)
+            {
+                notables.oldest = Some(notable());
+            }
+
+            // Organisms that have never produced anything are not prolific
+            if individual.produced > 0
+                && notables
+                    .most_prolific
+                    .as_ref()
+                    .is_none_or(|prolific| individual.produced > prolific.individual.produced)
+            {
+                notables.most_prolific = Some(notable());
+            }
+        }
+
+        notables
+    }
+}
+
+impl Display for NotableIndividuals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |notable: &Option<NotableIndividual>| match notable {
+            Some(notable) => notable.to_string(),
+            None => "nobody yet".to_string(),
+        };
+
+        writeln!(f, "Oldest: {}", describe(&self.oldest))?;
+        write!(f, "Most prolific: {}", describe(&self.most_prolific))
+    }
+}
+
+/// Gives every organism that doesn't have a name yet a randomly generated one.
+///
+/// Organisms restored from a save already have their [`Individual`], and so keep their names.
+fn name_new_organisms(
+    organism_query: Query<Entity, (With<Organism>, Without<Individual>)>,
+    jitter: Jitter,
+    mut commands: Commands,
+) {
+    for entity in organism_query.iter() {
+        let rng = &mut jitter.rng(entity, JitterStream::Naming);
+        commands
+            .entity(entity)
+            .insert(Individual::new(generate_name(rng)));
+    }
+}
+
+/// Renames organisms in response to [`RenameIndividual`] events.
+fn rename_individuals(
+    mut rename_events: EventReader<RenameIndividual>,
+    mut individual_query: Query<&mut Individual>,
+) {
+    for event in rename_events.iter() {
+        let name = event.name.trim();
+        // Blank names would make the organism impossible to tell apart
+        if name.is_empty() {
+            continue;
+        }
+
+        if let Ok(mut individual) = individual_query.get_mut(event.entity) {
+            individual.name = name.to_string();
+        }
+    }
+}
+
+/// Counts the recipes completed by each organism.
+fn count_production(mut individual_query: Query<(&CraftingState, &mut Individual)>) {
+    for (crafting_state, mut individual) in individual_query.iter_mut() {
+        // Recipes are only complete for a single frame, before their outputs are collected
+        if matches!(crafting_state, CraftingState::RecipeComplete) {
+            individual.produced += 1;
+        }
+    }
+}
+
+/// Ages every organism, and finds the most notable ones, once a second.
+fn update_individuals(
+    mut individual_query: Query<(Entity, &TilePos, &mut Individual)>,
+    mut notable_individuals: ResMut<NotableIndividuals>,
+) {
+    // Ages are counted in seconds, and this system runs once a second
+    for (.., mut individual) in individual_query.iter_mut() {
+        individual.age += 1.;
+    }
+
+    let new_notables = NotableIndividuals::find(
+        individual_query
+            .iter()
+            .map(|(entity, &tile_pos, individual)| (entity, tile_pos, individual)),
+    );
+
+    notable_individuals.set_if_neq(new_notables);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn generated_names_are_capitalized() {
+        let rng = &mut StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let name = generate_name(rng);
+            assert!(name.len() >= 4);
+            assert!(name.chars().next().unwrap().is_uppercase());
+            assert!(name.chars().skip(1).all(|c| c.is_lowercase()));
+        }
+    }
+
+    #[test]
+    fn notable_individuals_are_found() {
+        let young_and_busy = Individual {
+            name: "Kari".to_string(),
+            age: 10.,
+            produced: 5,
+        };
+        let old_and_idle = Individual {
+            name: "Todor".to_string(),
+            age: 100.,
+            produced: 0,
+        };
+
+        let notables = NotableIndividuals::find([
+            (Entity::from_raw(0), TilePos::new(1, 0), &young_and_busy),
+            (Entity::from_raw(1), TilePos::new(0, 1), &old_and_idle),
+        ]);
+
+        assert_eq!(notables.oldest.unwrap().individual, old_and_idle);
+        assert_eq!(notables.most_prolific.unwrap().individual, young_and_busy);
+    }
+
+    #[test]
+    fn idle_organisms_are_not_prolific() {
+        let idle = Individual::new("Mune".to_string());
+        let notables = NotableIndividuals::find([(Entity::from_raw(0), TilePos::ORIGIN, &idle)]);
+
+        assert!(notables.oldest.is_some());
+        assert!(notables.most_prolific.is_none());
+    }
+}
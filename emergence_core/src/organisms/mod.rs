@@ -0,0 +1,70 @@
+//! Models organisms, which have two primary types: units (organisms that can move around freely)
+//! and structures (organisms that are fixed in place).
+use crate::bevy::prelude::*;
+
+use crate::{simulation::freezing::ColdTolerance, structures::farming::CropSchedule};
+
+use self::{
+    energy::{kill_organisms_when_out_of_energy, regenerate_energy, EnergyPool},
+    growth::{check_growth_conditions, consume_nutrients, GrowthRequirements},
+};
+
+pub mod energy;
+pub mod genealogy;
+pub mod growth;
+pub mod individuals;
+pub mod population;
+
+/// All of the standard components of an [`Organism`]
+#[derive(Bundle)]
+pub struct OrganismBundle {
+    /// The marker component for orgamisms
+    organism: Organism,
+    /// The energy available to this organism
+    energy_pool: EnergyPool,
+    /// The lowest temperature this organism can endure
+    cold_tolerance: ColdTolerance,
+}
+
+impl OrganismBundle {
+    /// Create a new [`OrganismBundle`]
+    pub fn new(energy_pool: EnergyPool, cold_tolerance: ColdTolerance) -> OrganismBundle {
+        OrganismBundle {
+            organism: Organism,
+            energy_pool,
+            cold_tolerance,
+        }
+    }
+}
+
+/// Information about a variety of organism.
+#[derive(Debug, Clone)]
+pub struct OrganismVariety {
+    /// Controls the maximum energy, and the rate at which it drains.
+    pub energy_pool: EnergyPool,
+    /// The light, moisture and fertility needed for this organism to grow.
+    pub growth_requirements: GrowthRequirements,
+    /// The lowest temperature this organism can endure without being harmed.
+    pub cold_tolerance: ColdTolerance,
+    /// When this organism is sown, tended and harvested, if it can be farmed.
+    pub crop_schedule: Option<CropSchedule>,
+}
+
+/// A living part of the game ecosystem.
+#[derive(Component, Default)]
+pub struct Organism;
+
+/// Controls the behavior of living organisms
+pub struct OrganismPlugin;
+
+impl Plugin for OrganismPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(individuals::IndividualsPlugin)
+            .add_plugin(genealogy::GenealogyPlugin)
+            .add_plugin(population::PopulationPlugin)
+            .add_system(regenerate_energy)
+            .add_system(kill_organisms_when_out_of_energy)
+            .add_system(check_growth_conditions)
+            .add_system(consume_nutrients.after(check_growth_conditions));
+    }
+}
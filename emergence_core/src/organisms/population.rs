@@ -0,0 +1,140 @@
+//! Keeps count of how many organisms of each kind are alive.
+//!
+//! The [`PopulationCensus`] is refreshed periodically, and is shown to the player in the population panel.
+//! Research environments count organisms on demand with [`PopulationCensus::count`] instead, so their observations are never stale.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use std::collections::BTreeMap;
+
+use crate::{
+    manifest::{Id, Structure, Unit},
+    simulation::time::every_second,
+};
+
+use super::Organism;
+
+/// Counts the living organisms of each kind.
+pub(super) struct PopulationPlugin;
+
+impl Plugin for PopulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PopulationCensus>()
+            .add_system(take_census.run_if(every_second()));
+    }
+}
+
+/// The number of living organisms of each kind.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct PopulationCensus {
+    /// The number of units of each kind
+    units: HashMap<Id<Unit>, usize>,
+    /// The number of living structures of each kind
+    structures: HashMap<Id<Structure>, usize>,
+}
+
+impl PopulationCensus {
+    /// Counts the `units` and living `structures` given.
+    pub fn count(
+        units: impl IntoIterator<Item = Id<Unit>>,
+        structures: impl IntoIterator<Item = Id<Structure>>,
+    ) -> Self {
+        let mut census = PopulationCensus::default();
+
+        for unit_id in units {
+            *census.units.entry(unit_id).or_default() += 1;
+        }
+
+        for structure_id in structures {
+            *census.structures.entry(structure_id).or_default() += 1;
+        }
+
+        census
+    }
+
+    /// The total number of living organisms, of all kinds.
+    pub fn total(&self) -> usize {
+        self.units.values().sum::<usize>() + self.structures.values().sum::<usize>()
+    }
+
+    /// The number of living organisms of each kind, labelled `unit:<id>` or `structure:<id>`.
+    pub fn by_kind(&self) -> BTreeMap<String, usize> {
+        let units = self
+            .units
+            .iter()
+            .map(|(unit_id, &count)| (format!("unit:{unit_id}"), count));
+        let structures = self
+            .structures
+            .iter()
+            .map(|(structure_id, &count)| (format!("structure:{structure_id}"), count));
+
+        units.chain(structures).collect()
+    }
+
+    /// The name and number of each kind of living organism, from most to least numerous.
+    ///
+    /// Kinds with the same number of organisms are sorted by name.
+    pub fn most_numerous(&self) -> Vec<(String, usize)> {
+        let units = self
+            .units
+            .iter()
+            .map(|(unit_id, &count)| (unit_id.to_string(), count));
+        let structures = self
+            .structures
+            .iter()
+            .map(|(structure_id, &count)| (structure_id.to_string(), count));
+
+        let mut counts: Vec<(String, usize)> = units.chain(structures).collect();
+        counts.sort_by(|(a_name, a_count), (b_name, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+        });
+        counts
+    }
+}
+
+/// Counts every living organism, by kind, once a second.
+fn take_census(
+    unit_query: Query<&Id<Unit>>,
+    structure_query: Query<&Id<Structure>, With<Organism>>,
+    mut census: ResMut<PopulationCensus>,
+) {
+    let new_census =
+        PopulationCensus::count(unit_query.iter().copied(), structure_query.iter().copied());
+
+    census.set_if_neq(new_census);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn organisms_are_counted_by_kind() {
+        let census = PopulationCensus::count(
+            [Id::ant(), Id::soldier_ant(), Id::ant()],
+            [Id::from_string_id("acacia")],
+        );
+
+        assert_eq!(census.total(), 4);
+        assert_eq!(census.by_kind()["unit:ant"], 2);
+        assert_eq!(census.by_kind()["unit:soldier_ant"], 1);
+        assert!(!census.by_kind().contains_key("unit:locust"));
+        assert_eq!(census.by_kind()["structure:acacia"], 1);
+    }
+
+    #[test]
+    fn the_most_numerous_organisms_are_listed_first() {
+        let census = PopulationCensus::count(
+            [Id::soldier_ant(), Id::ant(), Id::ant()],
+            [Id::from_string_id("acacia")],
+        );
+
+        assert_eq!(
+            census.most_numerous(),
+            vec![
+                ("ant".to_string(), 2),
+                ("acacia".to_string(), 1),
+                ("soldier_ant".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,2609 @@
+//! Signals are used for pathfinding and decision-making.
+//!
+//! By collecting information about the local environment into a slowly updated, tile-centric data structure,
+//! we can scale path-finding and decisionmaking in a clear and comprehensible way.
+
+use crate::bevy::{
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    tasks::{ComputeTaskPool, TaskPool},
+    utils::{HashMap, HashSet, StableHashMap},
+};
+use core::fmt::Display;
+use core::ops::{Add, Deref, DerefMut, Mul, Sub};
+use core::time::Duration;
+use hexx::{shapes::hexagon, Hex};
+use itertools::Itertools;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::{Id, Item, SignalKind, Structure};
+use crate::simulation::geometry::{MapGeometry, TilePos};
+use crate::simulation::jitter::{Jitter, JitterStream};
+use crate::simulation::time::Season;
+use crate::simulation::wind::Wind;
+use crate::units::goals::Goal;
+
+/// The fraction of signals in each cell that will move to each of 6 neighbors each tick.
+///
+/// Higher values will result in more spread out signals.
+///
+/// If no neighbor exists, total diffusion will be reduced correspondingly.
+/// As a result, this value *must* be below 1/6,
+/// and probably should be below 1/7 to avoid weirdness.
+pub const DIFFUSION_FRACTION: f32 = 0.1;
+
+/// The fraction of signal that will decay at each step.
+///
+/// Higher values lead to faster decay and improved signal responsiveness.
+/// This must always be between 0 and 1.
+pub const DEGRADATION_FRACTION: f32 = 0.01;
+
+/// The resources and systems need to work with signals
+///
+/// Insert a [`SignalConfig`] before adding this plugin to customize how each signal spreads and fades,
+/// and a [`StepSelection`] to customize how units follow them.
+pub struct SignalsPlugin;
+
+impl Plugin for SignalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_schedule(SignalSchedule, Schedule::new())
+            .init_resource::<Signals>()
+            .init_resource::<SignalKindRegistry>()
+            .init_resource::<SignalConfig>()
+            .init_resource::<SignalTickRate>()
+            .init_resource::<SignalClock>()
+            .init_resource::<StepSelection>()
+            .add_system(run_signal_ticks.in_base_set(CoreSet::FixedUpdate))
+            .add_systems(
+                (
+                    emit_signals,
+                    diffuse_signals,
+                    degrade_signals,
+                    cache_signal_gradients,
+                )
+                    .chain()
+                    .in_schedule(SignalSchedule),
+            );
+    }
+}
+
+/// The schedule that emits, diffuses and degrades signals, run once for each signal tick.
+///
+/// Systems that emit signals continuously should be added to this schedule, before `emit_signals`,
+/// so their emissions don't depend on the frame rate.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignalSchedule;
+
+/// How many times per second of in-game time signals are emitted, diffused and degraded.
+///
+/// Signals are updated on their own fixed timestep, so they spread at the same speed no matter how fast the game is rendering.
+/// When the game is sped up, more ticks are run each frame; when it is paused, none are.
+/// Changing the tick rate only affects the [`SignalSchedule`]: [`CoreSchedule::FixedUpdate`] keeps its own timestep.
+/// Insert this resource before adding the simulation plugins to change the starting rate.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct SignalTickRate {
+    /// The number of signal ticks per second.
+    ///
+    /// This must be positive.
+    ticks_per_second: f32,
+}
+
+impl SignalTickRate {
+    /// Creates a new tick rate, which updates signals `ticks_per_second` times each second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ticks_per_second` is not positive.
+    pub fn new(ticks_per_second: f32) -> Self {
+        assert!(
+            ticks_per_second > 0.,
+            "Signal tick rate must be positive, but was {ticks_per_second}"
+        );
+
+        SignalTickRate { ticks_per_second }
+    }
+
+    /// The number of signal ticks per second.
+    pub fn ticks_per_second(&self) -> f32 {
+        self.ticks_per_second
+    }
+
+    /// The amount of in-game time between each signal tick.
+    pub fn period(&self) -> Duration {
+        Duration::from_secs_f64(1. / self.ticks_per_second as f64)
+    }
+}
+
+impl Default for SignalTickRate {
+    fn default() -> Self {
+        SignalTickRate::new(60.)
+    }
+}
+
+/// How units choose which neighboring tile to step to when following a signal upstream.
+///
+/// Insert this resource before adding the simulation plugins to change how units move.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub enum StepSelection {
+    /// Always step to the neighbor with the strongest signal.
+    ///
+    /// Units following the same signal walk in tidy single-file lines.
+    #[default]
+    Greedy,
+    /// Step to each neighbor with a probability proportional to `exp((strength / strongest - 1) / temperature)`.
+    ///
+    /// Strengths are compared relative to the strongest neighbor, so the temperature doesn't depend on how strong the signal is.
+    /// Low temperatures behave almost greedily, while high temperatures spread units across every neighbor with some signal.
+    /// Temperatures of 0 or less behave exactly like [`StepSelection::Greedy`].
+    Softmax {
+        /// How freely units stray from the strongest neighbor
+        temperature: f32,
+    },
+    /// Step to each neighbor with a probability proportional to its signal strength.
+    Roulette,
+}
+
+impl StepSelection {
+    /// Chooses the next step for a unit standing on `tile_pos`, given the perceived signal `scores` of it and its neighbors.
+    ///
+    /// Returns [`None`] if the unit is already on the tile with the strongest signal.
+    fn choose(
+        &self,
+        tile_pos: TilePos,
+        scores: HashMap<TilePos, SignalStrength>,
+        rng: &mut impl Rng,
+    ) -> Option<TilePos> {
+        let current_strength = scores
+            .get(&tile_pos)
+            .copied()
+            .unwrap_or(SignalStrength::ZERO);
+        let strongest = scores
+            .values()
+            .fold(SignalStrength::ZERO, |strongest, &strength| {
+                if strength > strongest {
+                    strength
+                } else {
+                    strongest
+                }
+            });
+
+        // Units that have reached the peak stay put, just like greedy units
+        if strongest <= SignalStrength::ZERO || current_strength >= strongest {
+            return None;
+        }
+
+        let mut candidates: Vec<(TilePos, SignalStrength)> = scores
+            .into_iter()
+            .filter(|&(neighbor, strength)| neighbor != tile_pos && strength > SignalStrength::ZERO)
+            .collect();
+        // Hash maps are iterated in an arbitrary order, which would break determinism
+        candidates.sort_by_key(|(neighbor, _)| (neighbor.x, neighbor.y));
+
+        let weight = |strength: SignalStrength| match *self {
+            StepSelection::Softmax { temperature } if temperature > 0. => {
+                ((strength.value() / strongest.value() - 1.) / temperature).exp()
+            }
+            StepSelection::Roulette => strength.value(),
+            // Greedy, or too cold to ever stray
+            _ => {
+                if strength == strongest {
+                    1.
+                } else {
+                    0.
+                }
+            }
+        };
+
+        let weighted_index =
+            WeightedIndex::new(candidates.iter().map(|&(_, strength)| weight(strength))).ok()?;
+        Some(candidates[weighted_index.sample(rng)].0)
+    }
+}
+
+/// Tracks how much in-game time has passed since the last signal tick.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+struct SignalClock {
+    /// Time that has passed but not yet been used up by a signal tick
+    accumulated: Duration,
+}
+
+impl SignalClock {
+    /// Advances the clock by `delta`, returning the number of signal ticks of length `period` that are now due.
+    fn advance(&mut self, delta: Duration, period: Duration) -> u32 {
+        self.accumulated += delta;
+
+        let mut n_ticks = 0;
+        while self.accumulated >= period {
+            self.accumulated -= period;
+            n_ticks += 1;
+        }
+
+        n_ticks
+    }
+}
+
+/// Runs the [`SignalSchedule`] once for every signal tick that is due, according to the [`SignalTickRate`].
+fn run_signal_ticks(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    let period = world.resource::<SignalTickRate>().period();
+    let n_ticks = world.resource_mut::<SignalClock>().advance(delta, period);
+
+    for _ in 0..n_ticks {
+        world.run_schedule(SignalSchedule);
+    }
+}
+
+/// The central resource that tracks all signals.
+#[derive(Resource, Debug, Default)]
+pub struct Signals {
+    /// The spatialized map for each signal
+    ///
+    /// Uses a fixed hasher, so that signals are always visited (and their floating point strengths summed) in the same order.
+    maps: StableHashMap<SignalType, DoubleBufferedSignalMap>,
+    /// The best next step towards the source of each signal, as of the end of the last signal tick
+    gradients: GradientCache,
+}
+
+impl Signals {
+    /// Returns the signal strength of `signal_type` at the given `tile_pos`.
+    ///
+    /// Missing values will be filled with [`SignalStrength::ZERO`].
+    pub fn get(&self, signal_type: SignalType, tile_pos: TilePos) -> SignalStrength {
+        match self.maps.get(&signal_type) {
+            Some(map) => map.get(tile_pos),
+            None => SignalStrength::ZERO,
+        }
+    }
+
+    /// Adds `signal_strength` of `signal_type` at `tile_pos`.
+    pub fn add_signal(
+        &mut self,
+        signal_type: SignalType,
+        tile_pos: TilePos,
+        signal_strength: SignalStrength,
+    ) {
+        self.gradients.is_fresh = false;
+
+        match self.maps.get_mut(&signal_type) {
+            Some(map) => map.add_signal(tile_pos, signal_strength),
+            None => {
+                let mut new_map = DoubleBufferedSignalMap::default();
+                new_map.add_signal(tile_pos, signal_strength);
+                self.maps.insert(signal_type, new_map);
+            }
+        }
+    }
+
+    /// Multiplies the strength of every signal at `tile_pos` by `factor`.
+    #[cfg(feature = "weather")]
+    pub fn scale_at(&mut self, tile_pos: TilePos, factor: f32) {
+        self.gradients.is_fresh = false;
+
+        for signal_map in self.maps.values_mut() {
+            let existing = signal_map.get(tile_pos);
+            // Don't touch tiles without this signal, to avoid allocating empty chunks
+            if existing > SignalStrength::ZERO {
+                signal_map.set(tile_pos, existing * factor);
+            }
+        }
+    }
+
+    /// Returns the complete set of signals at the given `tile_pos`.
+    ///
+    /// This is useful for decision-making.
+    pub fn all_signals_at_position(&self, tile_pos: TilePos) -> LocalSignals {
+        let mut all_signals = StableHashMap::default();
+        for &signal_type in self.maps.keys() {
+            let strength = self.get(signal_type, tile_pos);
+            all_signals.insert(signal_type, strength);
+        }
+
+        LocalSignals { map: all_signals }
+    }
+
+    /// Returns the strength of `signal_type` sensed from `tile_pos` by an organism that can sense signals up to `radius` tiles away.
+    ///
+    /// The signal on each tile is weighted by `1 / (1 + distance)`, so nearby signals count for more than distant ones.
+    /// With a `radius` of 0, this is the same as [`Signals::get`].
+    pub fn sensed_strength(
+        &self,
+        signal_type: SignalType,
+        tile_pos: TilePos,
+        radius: u32,
+        map_geometry: &MapGeometry,
+    ) -> SignalStrength {
+        let signal_map = match self.maps.get(&signal_type) {
+            Some(signal_map) => signal_map,
+            None => return SignalStrength::ZERO,
+        };
+
+        hexagon(tile_pos.hex, radius)
+            .map(|hex| TilePos { hex })
+            .filter(|&sensed_tile| map_geometry.is_valid(sensed_tile))
+            .fold(SignalStrength::ZERO, |total, sensed_tile| {
+                let distance = sensed_tile.hex.distance_to(tile_pos.hex) as u32;
+                total + signal_map.get(sensed_tile) * sensing_weight(distance)
+            })
+    }
+
+    /// Returns the complete set of signals sensed from `tile_pos` by an organism that can sense signals up to `radius` tiles away.
+    ///
+    /// See [`Signals::sensed_strength`] for how signals from different distances are combined.
+    pub fn signals_in_radius(
+        &self,
+        tile_pos: TilePos,
+        radius: u32,
+        map_geometry: &MapGeometry,
+    ) -> LocalSignals {
+        let mut all_signals = StableHashMap::default();
+        for &signal_type in self.maps.keys() {
+            let strength = self.sensed_strength(signal_type, tile_pos, radius, map_geometry);
+            all_signals.insert(signal_type, strength);
+        }
+
+        LocalSignals { map: all_signals }
+    }
+
+    /// Iterates over the strength of every signal on every tile where it is present.
+    pub fn iter_strengths(
+        &self,
+    ) -> impl Iterator<Item = (SignalType, TilePos, SignalStrength)> + '_ {
+        self.maps.iter().flat_map(|(&signal_type, signal_map)| {
+            signal_map
+                .iter()
+                .map(move |(tile_pos, strength)| (signal_type, tile_pos, strength))
+        })
+    }
+
+    /// Iterates over every tile where `signal_type` is present, along with its strength there.
+    ///
+    /// Tiles without any of this signal are skipped, and tiles are visited in no particular order.
+    pub fn tiles_with(
+        &self,
+        signal_type: SignalType,
+    ) -> impl Iterator<Item = (TilePos, SignalStrength)> + '_ {
+        self.maps
+            .get(&signal_type)
+            .into_iter()
+            .flat_map(|signal_map| signal_map.iter())
+    }
+
+    /// Returns every type of signal that has been emitted, in sorted order.
+    pub fn signal_types(&self) -> Vec<SignalType> {
+        self.maps.keys().copied().sorted().collect()
+    }
+
+    /// Returns the strongest signal of type `signal_type` on any tile.
+    pub fn max_strength(&self, signal_type: SignalType) -> SignalStrength {
+        match self.maps.get(&signal_type) {
+            Some(map) => map.iter().fold(SignalStrength::ZERO, |max, (_, strength)| {
+                if strength > max {
+                    strength
+                } else {
+                    max
+                }
+            }),
+            None => SignalStrength::ZERO,
+        }
+    }
+
+    /// Returns the total strength of each type of signal, summed across all tiles.
+    pub fn total_strengths(&self) -> impl Iterator<Item = (SignalType, SignalStrength)> + '_ {
+        self.maps.iter().map(|(&signal_type, signal_map)| {
+            let total = signal_map
+                .iter()
+                .fold(SignalStrength::ZERO, |total, (_, strength)| {
+                    total + strength
+                });
+            (signal_type, total)
+        })
+    }
+
+    /// Returns the adjacent, empty tile position that contains the highest sum signal strength that can be used to meet the provided `goal`.
+    ///
+    /// Each signal is weighted by the `goal_weights` of the unit following it,
+    /// so units of different varieties can favor different signals when several lead to the same goal.
+    /// If no suitable tile exists, [`None`] will be returned instead.
+    ///
+    /// Once per signal tick, the answer for every tile is cached by [`Signals::cache_gradients`],
+    /// so this is a single lookup unless signals have been added since then,
+    /// or the `goal_weights` favor some of the signals followed by the `goal` over others.
+    pub fn upstream(
+        &self,
+        tile_pos: TilePos,
+        goal: &Goal,
+        goal_weights: &SignalSensitivity,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        // Weighting every signal equally does not change which tile is best, so the cached answer can be used
+        if !self.gradients.is_fresh || !goal_weights.weighs_evenly(GradientCache::categories(goal))
+        {
+            return self.compute_upstream(tile_pos, goal, goal_weights, map_geometry);
+        }
+
+        let key = GradientCache::key(goal)?;
+        self.gradients
+            .best_steps
+            .get(&key)
+            .and_then(|best_steps| best_steps.get(&tile_pos))
+            .copied()
+    }
+
+    /// Chooses an adjacent, empty tile position to step to in order to meet the provided `goal`,
+    /// according to the `step_selection`.
+    ///
+    /// With [`StepSelection::Greedy`] this is the same as [`Signals::upstream`], and the `rng` is left untouched.
+    /// Otherwise, the `rng` should be seeded for the unit taking the step, so that its path can be replayed.
+    pub fn choose_upstream(
+        &self,
+        tile_pos: TilePos,
+        goal: &Goal,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        if step_selection == StepSelection::Greedy {
+            return self.upstream(tile_pos, goal, goal_weights, map_geometry);
+        }
+
+        let scores = self.upstream_scores(tile_pos, goal, goal_weights, map_geometry)?;
+        step_selection.choose(tile_pos, scores, rng)
+    }
+
+    /// Computes [`Signals::upstream`] from scratch, without using the cache.
+    fn compute_upstream(
+        &self,
+        tile_pos: TilePos,
+        goal: &Goal,
+        goal_weights: &SignalSensitivity,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        let mut best_choice: Option<TilePos> = None;
+        let mut best_score = SignalStrength::ZERO;
+
+        let neighboring_signals =
+            self.upstream_scores(tile_pos, goal, goal_weights, map_geometry)?;
+
+        for (possible_tile, current_score) in neighboring_signals {
+            // Hash maps are iterated in an arbitrary order, so ties are broken by position instead
+            let is_better = match best_choice {
+                Some(best_tile) if current_score == best_score => {
+                    (possible_tile.x, possible_tile.y) < (best_tile.x, best_tile.y)
+                }
+                _ => current_score > best_score,
+            };
+
+            if is_better {
+                best_score = current_score;
+                best_choice = Some(possible_tile);
+            }
+        }
+
+        if let Some(best_tile_pos) = best_choice {
+            if best_tile_pos == tile_pos {
+                None
+            } else {
+                best_choice
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The perceived strength of the signals followed to meet `goal`, on `tile_pos` and each of its neighbors that can be walked to.
+    ///
+    /// Returns [`None`] for goals that are not met by following signals upstream.
+    fn upstream_scores(
+        &self,
+        tile_pos: TilePos,
+        goal: &Goal,
+        goal_weights: &SignalSensitivity,
+        map_geometry: &MapGeometry,
+    ) -> Option<HashMap<TilePos, SignalStrength>> {
+        let neighboring_signals = match goal {
+            Goal::Wander | Goal::Guard(_) | Goal::Patrol(_) | Goal::Avoid => return None,
+            Goal::Pickup(item_id) | Goal::Eat(item_id) => {
+                let push_signals = self.neighboring_signals(
+                    SignalType::Push(*item_id),
+                    tile_pos,
+                    goal_weights,
+                    map_geometry,
+                );
+                let contains_signals = self.neighboring_signals(
+                    SignalType::Contains(*item_id),
+                    tile_pos,
+                    goal_weights,
+                    map_geometry,
+                );
+                let mut total_signals = push_signals;
+
+                for (tile_pos, signal_strength) in contains_signals {
+                    if let Some(existing_signal_strength) = total_signals.get_mut(&tile_pos) {
+                        *existing_signal_strength = *existing_signal_strength + signal_strength;
+                    } else {
+                        total_signals.insert(tile_pos, signal_strength);
+                    }
+                }
+
+                total_signals
+            }
+            Goal::DropOff(item_id) => self.neighboring_signals(
+                SignalType::Pull(*item_id),
+                tile_pos,
+                goal_weights,
+                map_geometry,
+            ),
+            Goal::Work(structure_id) => self.neighboring_signals(
+                SignalType::Work(*structure_id),
+                tile_pos,
+                goal_weights,
+                map_geometry,
+            ),
+            Goal::Demolish(structure_id) => self.neighboring_signals(
+                SignalType::Demolish(*structure_id),
+                tile_pos,
+                goal_weights,
+                map_geometry,
+            ),
+            Goal::Fight => {
+                self.neighboring_signals(SignalType::Alarm, tile_pos, goal_weights, map_geometry)
+            }
+        };
+
+        Some(neighboring_signals)
+    }
+
+    /// Returns the adjacent, empty tile position with the lowest signal strength that can be used to meet the provided `goal`.
+    ///
+    /// This is the mirror image of [`Signals::upstream`], used by goals that are met by moving away from a signal's source.
+    /// If no neighboring tile has a weaker signal than the current tile, [`None`] will be returned instead.
+    pub fn downstream(
+        &self,
+        tile_pos: TilePos,
+        goal: &Goal,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        let signal_type = match goal {
+            Goal::Avoid => SignalType::Danger,
+            _ => return None,
+        };
+
+        let current_strength = self.get(signal_type, tile_pos);
+
+        tile_pos
+            .reachable_neighbors(map_geometry)
+            .into_iter()
+            .map(|neighbor| (neighbor, self.get(signal_type, neighbor)))
+            .filter(|&(_, strength)| strength < current_strength)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(neighbor, _)| neighbor)
+    }
+
+    /// Returns the signal strength of the type `signal_type` in `tile_pos` and each of its neighbors that can be walked to,
+    /// as perceived by a unit with the provided `goal_weights`.
+    fn neighboring_signals(
+        &self,
+        signal_type: SignalType,
+        tile_pos: TilePos,
+        goal_weights: &SignalSensitivity,
+        map_geometry: &MapGeometry,
+    ) -> HashMap<TilePos, SignalStrength> {
+        let mut signal_strength_map = HashMap::with_capacity(7);
+
+        signal_strength_map.insert(
+            tile_pos,
+            goal_weights.perceive(signal_type, self.get(signal_type, tile_pos)),
+        );
+        for neighbor in tile_pos.reachable_neighbors(map_geometry) {
+            signal_strength_map.insert(
+                neighbor,
+                goal_weights.perceive(signal_type, self.get(signal_type, neighbor)),
+            );
+        }
+
+        signal_strength_map
+    }
+
+    /// Precomputes [`Signals::upstream`] for every tile and goal, so units can look up their next step cheaply.
+    ///
+    /// Only tiles that have a signal, or are next to one, can have a next step.
+    /// The cache is invalidated whenever signals change, and is not updated when structures are built or removed.
+    pub fn cache_gradients(&mut self, map_geometry: &MapGeometry) {
+        let mut candidates: HashMap<SignalType, (Goal, HashSet<TilePos>)> = HashMap::new();
+
+        for (&signal_type, signal_map) in self.maps.iter() {
+            let goal = match GradientCache::goal(signal_type) {
+                Some(goal) => goal,
+                None => continue,
+            };
+            let key = match GradientCache::key(&goal) {
+                Some(key) => key,
+                None => continue,
+            };
+
+            let (_goal, tiles) = candidates
+                .entry(key)
+                .or_insert_with(|| (goal, HashSet::new()));
+
+            for (tile_pos, _strength) in signal_map.iter() {
+                tiles.insert(tile_pos);
+                tiles.extend(tile_pos.all_neighbors(map_geometry));
+            }
+        }
+
+        let best_steps = candidates
+            .into_iter()
+            .map(|(key, (goal, tiles))| {
+                let steps = tiles
+                    .into_iter()
+                    .filter_map(|tile_pos| {
+                        self.compute_upstream(
+                            tile_pos,
+                            &goal,
+                            &SignalSensitivity::default(),
+                            map_geometry,
+                        )
+                        .map(|best_step| (tile_pos, best_step))
+                    })
+                    .collect();
+
+                (key, steps)
+            })
+            .collect();
+
+        self.gradients = GradientCache {
+            best_steps,
+            is_fresh: true,
+        };
+    }
+
+    /// Degrades signals at the rate set in the `config` for each signal type.
+    pub fn degrade(&mut self, config: &SignalConfig) {
+        self.degrade_scaled(config, 1.);
+    }
+
+    /// Degrades signals at `multiplier` times the rate set in the `config` for each signal type.
+    ///
+    /// The scaled rate is capped at 1, at which point signals vanish completely.
+    pub fn degrade_scaled(&mut self, config: &SignalConfig, multiplier: f32) {
+        self.gradients.is_fresh = false;
+
+        /// The value below which decayed signals are eliminated completely
+        ///
+        /// Increasing this value will:
+        ///  - increase computational costs
+        ///  - increase the range at which tasks can be detected
+        ///  - increase the amount of time units will wait around for more production
+        const EPSILON_STRENGTH: SignalStrength = SignalStrength(1e-8);
+
+        for (&signal_type, signal_map) in self.maps.iter_mut() {
+            let degradation_fraction =
+                (config.rates(signal_type).degradation_fraction * multiplier).clamp(0., 1.);
+            signal_map.scale(1. - degradation_fraction, EPSILON_STRENGTH);
+        }
+    }
+
+    /// Diffuses signals from one cell into the next, and carries some of them along with the `wind`.
+    ///
+    /// The work is split across the [`ComputeTaskPool`], which is created if it does not exist yet.
+    pub fn diffuse(&mut self, map_geometry: &MapGeometry, config: &SignalConfig, wind: &Wind) {
+        let task_pool = ComputeTaskPool::init(TaskPool::default);
+        self.diffuse_with(map_geometry, config, wind, task_pool);
+    }
+
+    /// Diffuses signals from one cell into the next, using the provided `task_pool`.
+    ///
+    /// Each chunk of each signal map is diffused in its own task, reading only from the front buffer.
+    /// The results are then merged into the back buffer, which is swapped to the front once every signal has been diffused.
+    ///
+    /// After spreading to its neighbors, a fraction of whatever signal remains on each tile is advected to its downwind neighbor.
+    ///
+    /// Signal is only ever moved between tiles, so the total strength of each signal is conserved.
+    pub fn diffuse_with(
+        &mut self,
+        map_geometry: &MapGeometry,
+        config: &SignalConfig,
+        wind: &Wind,
+        task_pool: &TaskPool,
+    ) {
+        self.gradients.is_fresh = false;
+
+        let chunk_results = task_pool.scope(|scope| {
+            for (&signal_type, buffers) in self.maps.iter() {
+                let diffusion_fraction = config.rates(signal_type).diffusion_fraction;
+
+                for (&chunk_pos, chunk) in buffers.front.chunks.iter() {
+                    scope.spawn(async move {
+                        let mut partial_map = SignalMap::default();
+
+                        for (occupied_tile, original_strength) in chunk.iter(chunk_pos) {
+                            let amount_to_send_to_each_neighbor =
+                                original_strength * diffusion_fraction;
+                            let mut retained_strength = original_strength;
+
+                            for neighboring_tile in occupied_tile.all_neighbors(map_geometry) {
+                                let transmission = map_geometry
+                                    .signal_transmission(occupied_tile, neighboring_tile);
+
+                                if transmission > 0. {
+                                    let amount_to_send =
+                                        amount_to_send_to_each_neighbor * transmission;
+                                    retained_strength = retained_strength - amount_to_send;
+                                    partial_map.add_signal(neighboring_tile, amount_to_send);
+                                }
+                            }
+
+                            if !wind.is_calm() {
+                                let downwind_tile = occupied_tile.neighbor(wind.direction);
+                                let transmission =
+                                    map_geometry.signal_transmission(occupied_tile, downwind_tile);
+
+                                if transmission > 0. {
+                                    let amount_to_advect = retained_strength
+                                        * (wind.advection_fraction * transmission);
+                                    retained_strength = retained_strength - amount_to_advect;
+                                    partial_map.add_signal(downwind_tile, amount_to_advect);
+                                }
+                            }
+
+                            partial_map.add_signal(occupied_tile, retained_strength);
+                        }
+
+                        (signal_type, partial_map)
+                    });
+                }
+            }
+        });
+
+        // Every tile's new strength is built up in the back buffer, so no tile can observe a half-diffused neighbor
+        for (signal_type, partial_map) in chunk_results {
+            self.maps
+                .get_mut(&signal_type)
+                .unwrap()
+                .back
+                .merge(partial_map);
+        }
+
+        for buffers in self.maps.values_mut() {
+            buffers.swap();
+        }
+    }
+}
+
+/// How much a signal `distance` tiles away counts towards the strength sensed by an organism.
+fn sensing_weight(distance: u32) -> f32 {
+    1. / (1 + distance) as f32
+}
+
+/// The result of [`Signals::upstream`] for every tile near a signal, rebuilt once per signal tick.
+#[derive(Debug, Default)]
+struct GradientCache {
+    /// The best next step from each tile, keyed by the main signal type that each goal follows
+    best_steps: HashMap<SignalType, HashMap<TilePos, TilePos>>,
+    /// Is the cache up to date with the current signals?
+    is_fresh: bool,
+}
+
+impl GradientCache {
+    /// The goal whose path is guided by `signal_type`, if any.
+    fn goal(signal_type: SignalType) -> Option<Goal> {
+        match signal_type {
+            SignalType::Push(item_id) | SignalType::Contains(item_id) => {
+                Some(Goal::Pickup(item_id))
+            }
+            SignalType::Pull(item_id) => Some(Goal::DropOff(item_id)),
+            SignalType::Work(structure_id) => Some(Goal::Work(structure_id)),
+            SignalType::Demolish(structure_id) => Some(Goal::Demolish(structure_id)),
+            SignalType::Alarm => Some(Goal::Fight),
+            SignalType::Danger | SignalType::Custom(_) => None,
+        }
+    }
+
+    /// The key under which the next steps for `goal` are cached.
+    ///
+    /// Goals that follow the same signals share a key.
+    fn key(goal: &Goal) -> Option<SignalType> {
+        match goal {
+            Goal::Pickup(item_id) | Goal::Eat(item_id) => Some(SignalType::Push(*item_id)),
+            Goal::DropOff(item_id) => Some(SignalType::Pull(*item_id)),
+            Goal::Work(structure_id) => Some(SignalType::Work(*structure_id)),
+            Goal::Demolish(structure_id) => Some(SignalType::Demolish(*structure_id)),
+            Goal::Fight => Some(SignalType::Alarm),
+            Goal::Wander | Goal::Guard(_) | Goal::Patrol(_) | Goal::Avoid => None,
+        }
+    }
+
+    /// The categories of signal that are followed to meet `goal`.
+    fn categories(goal: &Goal) -> &'static [SignalCategory] {
+        match goal {
+            Goal::Pickup(_) | Goal::Eat(_) => &[SignalCategory::Push, SignalCategory::Contains],
+            Goal::DropOff(_) => &[SignalCategory::Pull],
+            Goal::Work(_) => &[SignalCategory::Work],
+            Goal::Demolish(_) => &[SignalCategory::Demolish],
+            Goal::Fight => &[SignalCategory::Alarm],
+            Goal::Wander | Goal::Guard(_) | Goal::Patrol(_) | Goal::Avoid => &[],
+        }
+    }
+}
+
+/// All of the signals on a single tile.
+#[derive(Debug)]
+pub struct LocalSignals {
+    /// Internal data storage
+    ///
+    /// Units draw random noise for each signal in turn, so this must be iterated in the same order every run.
+    map: StableHashMap<SignalType, SignalStrength>,
+}
+
+impl LocalSignals {
+    /// Returns the strength of `signal_type` on this tile.
+    ///
+    /// Missing values will be filled with [`SignalStrength::ZERO`].
+    pub fn get(&self, signal_type: SignalType) -> SignalStrength {
+        self.map.get(&signal_type).copied().unwrap_or_default()
+    }
+
+    /// Iterates over every type of signal on this tile, along with its strength.
+    pub fn iter(&self) -> impl Iterator<Item = (SignalType, SignalStrength)> + '_ {
+        self.map
+            .iter()
+            .map(|(&signal_type, &signal_strength)| (signal_type, signal_strength))
+    }
+
+    /// Returns the set of signals that might be used to pick a goal
+    pub fn goal_relevant_signals(
+        &self,
+    ) -> impl Iterator<Item = (&SignalType, &SignalStrength)> + Clone {
+        self.map.iter().filter(|(signal_type, _signal_strength)| {
+            // Alarms and danger are responded to separately, and custom signals are interpreted by the systems that registered them
+            !matches!(
+                **signal_type,
+                SignalType::Contains(_)
+                    | SignalType::Alarm
+                    | SignalType::Danger
+                    | SignalType::Custom(_)
+            )
+        })
+    }
+}
+
+/// How quickly a type of signal spreads out and fades away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalRates {
+    /// The fraction of the signal on each tile that moves to each of its 6 neighbors each tick.
+    ///
+    /// This *must* be below 1/6: see [`DIFFUSION_FRACTION`].
+    pub diffusion_fraction: f32,
+    /// The fraction of the signal that decays each tick.
+    ///
+    /// This must be between 0 and 1.
+    pub degradation_fraction: f32,
+}
+
+impl SignalRates {
+    /// Creates a new set of rates, panicking if they would make signals unstable.
+    pub fn new(diffusion_fraction: f32, degradation_fraction: f32) -> Self {
+        assert!(
+            (0. ..1. / 6.).contains(&diffusion_fraction),
+            "Diffusion fraction must be between 0 and 1/6, but was {diffusion_fraction}"
+        );
+        assert!(
+            (0. ..=1.).contains(&degradation_fraction),
+            "Degradation fraction must be between 0 and 1, but was {degradation_fraction}"
+        );
+
+        SignalRates {
+            diffusion_fraction,
+            degradation_fraction,
+        }
+    }
+}
+
+impl Default for SignalRates {
+    fn default() -> Self {
+        SignalRates {
+            diffusion_fraction: DIFFUSION_FRACTION,
+            degradation_fraction: DEGRADATION_FRACTION,
+        }
+    }
+}
+
+/// Controls how quickly each type of signal spreads out and fades away.
+///
+/// Rates set for a specific [`SignalType`] take priority over those set for its [`SignalCategory`],
+/// which in turn take priority over the default rates.
+///
+/// Insert this resource before adding the signals plugin to customize it.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SignalConfig {
+    /// The rates used for signals without a more specific override
+    default_rates: SignalRates,
+    /// Rates for every signal of a category
+    category_rates: HashMap<SignalCategory, SignalRates>,
+    /// Rates for individual signal types
+    type_rates: HashMap<SignalType, SignalRates>,
+}
+
+impl SignalConfig {
+    /// Creates a config where every signal uses the same `rates`.
+    pub fn uniform(rates: SignalRates) -> Self {
+        SignalConfig {
+            default_rates: rates,
+            category_rates: HashMap::default(),
+            type_rates: HashMap::default(),
+        }
+    }
+
+    /// Sets the rates used by signals without a more specific override.
+    pub fn with_default_rates(mut self, rates: SignalRates) -> Self {
+        self.default_rates = rates;
+        self
+    }
+
+    /// Sets the rates used by every signal in the `category`.
+    pub fn with_category_rates(mut self, category: SignalCategory, rates: SignalRates) -> Self {
+        self.category_rates.insert(category, rates);
+        self
+    }
+
+    /// Sets the rates used by the `signal_type`.
+    pub fn with_type_rates(mut self, signal_type: SignalType, rates: SignalRates) -> Self {
+        self.type_rates.insert(signal_type, rates);
+        self
+    }
+
+    /// The rates used by the `signal_type`.
+    pub fn rates(&self, signal_type: SignalType) -> SignalRates {
+        match self.type_rates.get(&signal_type) {
+            Some(&rates) => rates,
+            None => self
+                .category_rates
+                .get(&signal_type.category())
+                .copied()
+                .unwrap_or(self.default_rates),
+        }
+    }
+}
+
+impl Default for SignalConfig {
+    fn default() -> Self {
+        SignalConfig::uniform(SignalRates::default())
+            // Items waiting to be hauled away should stop calling for help soon after they are collected
+            .with_category_rates(
+                SignalCategory::Push,
+                SignalRates::new(DIFFUSION_FRACTION, 0.03),
+            )
+            // Work sites are few and far between, and should be found from a long way off
+            .with_category_rates(
+                SignalCategory::Work,
+                SignalRates::new(DIFFUSION_FRACTION, 0.005),
+            )
+    }
+}
+
+/// Signal strengths are displayed with the formatter's precision, or 3 decimal places by default.
+impl Display for LocalSignals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        let mut string = String::default();
+
+        for signal_type in self.map.keys().sorted() {
+            let signal_strength = self.map.get(signal_type).unwrap().0;
+
+            let substring = format!("{signal_type}: {signal_strength:.precision$}\n");
+
+            string += &substring;
+        }
+
+        write!(f, "{string}")
+    }
+}
+
+/// A [`SignalMap`] that is diffused by writing its next state into a separate buffer.
+///
+/// All reads and writes outside of diffusion go to the front buffer, which this dereferences to.
+/// The back buffer is always empty between diffusion steps.
+#[derive(Debug, Default)]
+struct DoubleBufferedSignalMap {
+    /// The current state of the signal
+    front: SignalMap,
+    /// The next state of the signal, while it is being computed
+    back: SignalMap,
+}
+
+impl DoubleBufferedSignalMap {
+    /// Makes the back buffer current, and empties the old front buffer to be reused.
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        self.back.chunks.clear();
+    }
+}
+
+impl Deref for DoubleBufferedSignalMap {
+    type Target = SignalMap;
+
+    fn deref(&self) -> &Self::Target {
+        &self.front
+    }
+}
+
+impl DerefMut for DoubleBufferedSignalMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.front
+    }
+}
+
+/// The number of tiles along each axis of a [`SignalChunk`].
+const CHUNK_SIZE: i32 = 16;
+
+/// The number of tiles stored in each [`SignalChunk`].
+const TILES_PER_CHUNK: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// A dense block of signal strengths, covering a [`CHUNK_SIZE`] by [`CHUNK_SIZE`] parallelogram of tiles in axial coordinates.
+#[derive(Debug, Clone)]
+struct SignalChunk {
+    /// The strength of the signal on each tile of the chunk, indexed by [`SignalMap::local_index`]
+    strengths: [SignalStrength; TILES_PER_CHUNK],
+    /// The number of tiles in this chunk with a non-zero signal
+    n_occupied: usize,
+}
+
+impl Default for SignalChunk {
+    fn default() -> Self {
+        SignalChunk {
+            strengths: [SignalStrength::ZERO; TILES_PER_CHUNK],
+            n_occupied: 0,
+        }
+    }
+}
+
+impl SignalChunk {
+    /// Iterates over every tile in this chunk with a non-zero signal, given that the chunk is stored at `chunk_pos`.
+    fn iter(&self, chunk_pos: Hex) -> impl Iterator<Item = (TilePos, SignalStrength)> + '_ {
+        self.strengths
+            .iter()
+            .enumerate()
+            .filter(|(_, &strength)| strength > SignalStrength::ZERO)
+            .map(move |(index, &strength)| (SignalMap::tile_pos(chunk_pos, index), strength))
+    }
+
+    /// Sets the strength at `index`, keeping track of how many tiles are occupied.
+    fn set(&mut self, index: usize, signal_strength: SignalStrength) {
+        let was_occupied = self.strengths[index] > SignalStrength::ZERO;
+        let is_occupied = signal_strength > SignalStrength::ZERO;
+
+        match (was_occupied, is_occupied) {
+            (false, true) => self.n_occupied += 1,
+            (true, false) => self.n_occupied -= 1,
+            _ => (),
+        }
+
+        self.strengths[index] = signal_strength;
+    }
+}
+
+/// Stores the [`SignalStrength`] of the given [`SignalType`] at each [`TilePos`].
+///
+/// Signals are stored sparsely, in fixed-size chunks that are only allocated once they contain a signal,
+/// and freed once all of their signals have faded away.
+/// Within each chunk, strengths are stored densely, so iterating over a signal visits contiguous memory.
+#[derive(Debug, Default)]
+struct SignalMap {
+    /// The allocated chunks, keyed by chunk coordinate
+    ///
+    /// Like [`Signals`], this uses a fixed hasher so that diffusion adds up contributions in the same order every run.
+    chunks: StableHashMap<Hex, Box<SignalChunk>>,
+}
+
+impl SignalMap {
+    /// The coordinate of the chunk that contains `tile_pos`.
+    fn chunk_pos(tile_pos: TilePos) -> Hex {
+        Hex::new(
+            tile_pos.x.div_euclid(CHUNK_SIZE),
+            tile_pos.y.div_euclid(CHUNK_SIZE),
+        )
+    }
+
+    /// The index of `tile_pos` within its chunk.
+    fn local_index(tile_pos: TilePos) -> usize {
+        let local_x = tile_pos.x.rem_euclid(CHUNK_SIZE);
+        let local_y = tile_pos.y.rem_euclid(CHUNK_SIZE);
+
+        (local_x + local_y * CHUNK_SIZE) as usize
+    }
+
+    /// The tile position stored at `index` within the chunk at `chunk_pos`.
+    fn tile_pos(chunk_pos: Hex, index: usize) -> TilePos {
+        let index = index as i32;
+
+        TilePos::new(
+            chunk_pos.x * CHUNK_SIZE + index % CHUNK_SIZE,
+            chunk_pos.y * CHUNK_SIZE + index / CHUNK_SIZE,
+        )
+    }
+
+    /// Returns the signal strenth at the given [`TilePos`].
+    ///
+    /// Missing values will be filled with [`SignalStrength::ZERO`].
+    fn get(&self, tile_pos: TilePos) -> SignalStrength {
+        match self.chunks.get(&SignalMap::chunk_pos(tile_pos)) {
+            Some(chunk) => chunk.strengths[SignalMap::local_index(tile_pos)],
+            None => SignalStrength::ZERO,
+        }
+    }
+
+    /// Sets the signal at `tile_pos` to exactly `signal_strength`.
+    fn set(&mut self, tile_pos: TilePos, signal_strength: SignalStrength) {
+        let chunk_pos = SignalMap::chunk_pos(tile_pos);
+        let index = SignalMap::local_index(tile_pos);
+
+        match self.chunks.get_mut(&chunk_pos) {
+            Some(chunk) => {
+                chunk.set(index, signal_strength);
+                if chunk.n_occupied == 0 {
+                    self.chunks.remove(&chunk_pos);
+                }
+            }
+            None => {
+                // Don't allocate a chunk just to store nothing
+                if signal_strength > SignalStrength::ZERO {
+                    let mut chunk = Box::<SignalChunk>::default();
+                    chunk.set(index, signal_strength);
+                    self.chunks.insert(chunk_pos, chunk);
+                }
+            }
+        }
+    }
+
+    /// Adds the `signal_strength` to the signal at `tile_pos`.
+    fn add_signal(&mut self, tile_pos: TilePos, signal_strength: SignalStrength) {
+        let existing = self.get(tile_pos);
+        self.set(tile_pos, existing + signal_strength);
+    }
+
+    /// Iterates over every tile with a non-zero signal, chunk by chunk.
+    fn iter(&self) -> impl Iterator<Item = (TilePos, SignalStrength)> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|(&chunk_pos, chunk)| chunk.iter(chunk_pos))
+    }
+
+    /// Adds every signal in `other` to this map.
+    fn merge(&mut self, other: SignalMap) {
+        for (tile_pos, signal_strength) in other.iter() {
+            self.add_signal(tile_pos, signal_strength);
+        }
+    }
+
+    /// Multiplies every signal by `factor`, removing any that end up at or below `epsilon`.
+    fn scale(&mut self, factor: f32, epsilon: SignalStrength) {
+        for chunk in self.chunks.values_mut() {
+            for index in 0..TILES_PER_CHUNK {
+                let new_strength = chunk.strengths[index] * factor;
+
+                if new_strength > epsilon {
+                    chunk.strengths[index] = new_strength;
+                } else {
+                    chunk.set(index, SignalStrength::ZERO);
+                }
+            }
+        }
+
+        self.chunks.retain(|_, chunk| chunk.n_occupied > 0);
+    }
+}
+
+/// The variety of signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SignalType {
+    /// Take this item away from here.
+    Push(Id<Item>),
+    /// Bring me an item of this type.
+    Pull(Id<Item>),
+    /// Has an item of this type, in case you were looking.
+    Contains(Id<Item>),
+    /// Perform work at this type of structure.
+    #[allow(dead_code)]
+    Work(Id<Structure>),
+    /// Destroy a structure of this type
+    Demolish(Id<Structure>),
+    /// Danger! Raised by intruders and violent deaths, this causes members of the colony to guard their nest.
+    Alarm,
+    /// Stay away! Given off by hostile creatures and hazards, this causes members of the colony to flee down its gradient.
+    Danger,
+    /// A signal defined outside of this module, registered in the [`SignalKindRegistry`].
+    ///
+    /// These diffuse and decay like every other signal, but are never used to pick goals.
+    Custom(Id<SignalKind>),
+}
+
+impl Display for SignalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            SignalType::Push(item_id) => format!("Push({item_id})"),
+            SignalType::Pull(item_id) => format!("Pull({item_id})"),
+            SignalType::Contains(item_id) => format!("Contains({item_id})"),
+            SignalType::Work(structure_id) => format!("Work({structure_id})"),
+            SignalType::Demolish(structure_id) => format!("Demolish({structure_id})"),
+            SignalType::Alarm => "Alarm".to_string(),
+            SignalType::Danger => "Danger".to_string(),
+            SignalType::Custom(signal_kind) => format!("Custom({signal_kind})"),
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+impl SignalType {
+    /// The broad category that this signal belongs to.
+    pub fn category(&self) -> SignalCategory {
+        match self {
+            SignalType::Push(_) => SignalCategory::Push,
+            SignalType::Pull(_) => SignalCategory::Pull,
+            SignalType::Contains(_) => SignalCategory::Contains,
+            SignalType::Work(_) => SignalCategory::Work,
+            SignalType::Demolish(_) => SignalCategory::Demolish,
+            SignalType::Alarm => SignalCategory::Alarm,
+            SignalType::Danger => SignalCategory::Danger,
+            SignalType::Custom(_) => SignalCategory::Custom,
+        }
+    }
+}
+
+/// The broad categories of [`SignalType`], ignoring which item or structure they refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum SignalCategory {
+    /// [`SignalType::Push`]
+    Push,
+    /// [`SignalType::Pull`]
+    Pull,
+    /// [`SignalType::Contains`]
+    Contains,
+    /// [`SignalType::Work`]
+    Work,
+    /// [`SignalType::Demolish`]
+    Demolish,
+    /// [`SignalType::Alarm`]
+    Alarm,
+    /// [`SignalType::Danger`]
+    Danger,
+    /// [`SignalType::Custom`]
+    Custom,
+}
+
+/// How strongly a species perceives each [`SignalCategory`].
+///
+/// Each category has a multiplier that is applied to the strength of signals when they are sampled.
+/// Categories default to a multiplier of 1, while a multiplier of 0 makes the species completely blind to them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignalSensitivity {
+    /// The multipliers that differ from the default
+    multipliers: HashMap<SignalCategory, f32>,
+}
+
+impl SignalSensitivity {
+    /// Sets the multiplier applied to signals of the provided `category`.
+    pub fn with_multiplier(mut self, category: SignalCategory, multiplier: f32) -> Self {
+        self.multipliers.insert(category, multiplier.max(0.));
+        self
+    }
+
+    /// Makes this species completely unable to sense signals of the provided `category`.
+    pub fn blind_to(self, category: SignalCategory) -> Self {
+        self.with_multiplier(category, 0.)
+    }
+
+    /// The multiplier applied to signals of the provided `category`.
+    pub fn multiplier(&self, category: SignalCategory) -> f32 {
+        self.multipliers.get(&category).copied().unwrap_or(1.)
+    }
+
+    /// The strength of a signal of type `signal_type`, as perceived by this species.
+    pub fn perceive(
+        &self,
+        signal_type: SignalType,
+        signal_strength: SignalStrength,
+    ) -> SignalStrength {
+        signal_strength * self.multiplier(signal_type.category())
+    }
+
+    /// Are signals of every one of the `categories` perceived, and perceived equally strongly?
+    fn weighs_evenly(&self, categories: &[SignalCategory]) -> bool {
+        let mut multipliers = categories.iter().map(|&category| self.multiplier(category));
+
+        match multipliers.next() {
+            Some(first) => first > 0. && multipliers.all(|multiplier| multiplier == first),
+            None => true,
+        }
+    }
+}
+
+/// Tracks the kinds of [`SignalType::Custom`] signals, which can be defined without modifying this module.
+///
+/// Mods and new subsystems should register each of their signal kinds once, when they are initialized,
+/// and then use the returned ID to emit and read the signal.
+#[derive(Resource, Debug, Default)]
+pub struct SignalKindRegistry {
+    /// The name of each registered signal kind
+    names: HashMap<Id<SignalKind>, &'static str>,
+}
+
+impl SignalKindRegistry {
+    /// Registers a new kind of signal with the unique `name`, returning its ID.
+    ///
+    /// Registering the same name again returns the same ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` has the same ID as a different, previously registered name.
+    pub fn register(&mut self, name: &'static str) -> Id<SignalKind> {
+        let signal_kind = Id::from_string_id(name);
+
+        if let Some(&existing_name) = self.names.get(&signal_kind) {
+            assert_eq!(
+                existing_name, name,
+                "The signal kinds {existing_name} and {name} have the same ID"
+            );
+        }
+
+        self.names.insert(signal_kind, name);
+        signal_kind
+    }
+
+    /// Returns the ID of the previously registered signal kind with this `name`, if any.
+    pub fn get(&self, name: &str) -> Option<Id<SignalKind>> {
+        self.names
+            .iter()
+            .find(|(_, &registered_name)| registered_name == name)
+            .map(|(&signal_kind, _)| signal_kind)
+    }
+
+    /// Iterates over all registered signal kinds and their names.
+    pub fn iter(&self) -> impl Iterator<Item = (Id<SignalKind>, &'static str)> + '_ {
+        self.names
+            .iter()
+            .map(|(&signal_kind, &name)| (signal_kind, name))
+    }
+}
+
+/// How strong a signal is.
+///
+/// This has a minimum value of 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct SignalStrength(f32);
+
+impl SignalStrength {
+    /// No signal is present.
+    pub const ZERO: SignalStrength = SignalStrength(0.);
+
+    /// Creates a new [`SignalStrength`], ensuring that it has a minimum value of 0.
+    pub fn new(value: f32) -> Self {
+        SignalStrength(value.max(0.))
+    }
+
+    /// The underlying value
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl Add<SignalStrength> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn add(self, rhs: SignalStrength) -> Self::Output {
+        SignalStrength(self.0 + rhs.0)
+    }
+}
+
+impl Sub<SignalStrength> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn sub(self, rhs: SignalStrength) -> Self::Output {
+        SignalStrength((self.0 - rhs.0).max(0.))
+    }
+}
+
+impl Mul<f32> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        SignalStrength(self.0 * rhs)
+    }
+}
+
+/// The component that causes a game object to emit a signal.
+///
+/// This can change over time, and multiple signals may be emitted at once.
+#[derive(Default, Component, Debug, Clone)]
+pub struct Emitter {
+    /// The list of signals to emit at a provided
+    pub signals: Vec<(SignalType, SignalStrength)>,
+    /// The tiles that these signals are spread over
+    pub area: EmissionArea,
+}
+
+/// The tiles over which an [`Emitter`] spreads its signals.
+///
+/// The strength of each signal is shared evenly between the tiles of the area that are on the map,
+/// so larger emitters do not emit more in total.
+/// Emitters with [`TrailSmearing`] spread their signals along their trail instead.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub enum EmissionArea {
+    /// Only the tile that the emitter stands on.
+    #[default]
+    Tile,
+    /// Every tile within this distance of the emitter.
+    Radius(u32),
+}
+
+impl EmissionArea {
+    /// The tiles on the map covered by this area, when the emitter stands at `center`.
+    pub fn tiles(&self, center: TilePos, map_geometry: &MapGeometry) -> Vec<TilePos> {
+        let tiles: Vec<TilePos> = match self {
+            EmissionArea::Tile => vec![center],
+            EmissionArea::Radius(radius) => hexagon(center.hex, *radius)
+                .map(|hex| TilePos { hex })
+                .collect(),
+        };
+
+        tiles
+            .into_iter()
+            .filter(|&tile_pos| map_geometry.is_valid(tile_pos))
+            .collect()
+    }
+}
+
+/// Scales the strength of the signals emitted by an [`Emitter`], based on the state of the entity emitting them.
+///
+/// The [`Emitter`] decides *which* signals are emitted, while this decides *how strongly*.
+/// Each signal type has its own multiplier, which defaults to 1.
+/// These are kept up to date by the systems that own the relevant state,
+/// such as structures scaling their [`SignalType::Pull`] by how empty their inputs are.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct SignalModulator {
+    /// The multiplier for each signal type that is modulated
+    multipliers: HashMap<SignalType, f32>,
+}
+
+impl SignalModulator {
+    /// Sets the multiplier applied to signals of `signal_type`.
+    pub fn set(&mut self, signal_type: SignalType, multiplier: f32) {
+        self.multipliers.insert(signal_type, multiplier);
+    }
+
+    /// The strength at which a signal of `signal_type` and base `signal_strength` should be emitted.
+    pub fn modulate(
+        &self,
+        signal_type: SignalType,
+        signal_strength: SignalStrength,
+    ) -> SignalStrength {
+        match self.multipliers.get(&signal_type) {
+            Some(&multiplier) => signal_strength * multiplier,
+            None => signal_strength,
+        }
+    }
+}
+
+/// Smears the emission of a moving [`Emitter`] along every tile it has passed through since it last emitted.
+///
+/// Without this, emitters that move more than one tile per tick (such as fast units at high simulation speeds)
+/// leave gaps in their trails.
+/// Each signal is split evenly between the traversed tiles, so the total strength emitted per tick is unchanged.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrailSmearing {
+    /// Where the emitter was the last time that it emitted signals
+    last_tile_pos: Option<TilePos>,
+}
+
+impl TrailSmearing {
+    /// The tiles traversed when moving in a straight line from `start` to `end`.
+    ///
+    /// This excludes `start`, which was emitted to last tick, unless the emitter has not moved.
+    fn traversed_tiles(start: TilePos, end: TilePos) -> Vec<TilePos> {
+        if start == end {
+            return vec![end];
+        }
+
+        start
+            .line_to(end.hex)
+            .skip(1)
+            .map(|hex| TilePos { hex })
+            .collect()
+    }
+}
+
+/// The fraction by which the strength of each emitted signal randomly varies from tick to tick.
+///
+/// This noise stops identical emitters from producing perfectly symmetric signal fields,
+/// which units would otherwise follow in lockstep.
+/// As the noise is symmetric, the average emission is unchanged.
+const EMISSION_JITTER: f32 = 0.1;
+
+/// Emits signals from [`Emitter`] sources.
+///
+/// Other systems that emit signals continuously should run in the [`SignalSchedule`] before this system,
+/// so their emissions don't depend on the frame rate.
+pub fn emit_signals(
+    mut signals: ResMut<Signals>,
+    emitter_query: Query<
+        (Entity, &TilePos, &Emitter, Option<&SignalModulator>),
+        Without<TrailSmearing>,
+    >,
+    mut trail_query: Query<(
+        Entity,
+        &TilePos,
+        &Emitter,
+        Option<&SignalModulator>,
+        &mut TrailSmearing,
+    )>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+) {
+    /// The strength that each signal should actually be emitted at, after modulation.
+    fn modulated_signals<'a>(
+        emitter: &'a Emitter,
+        maybe_modulator: Option<&'a SignalModulator>,
+    ) -> impl Iterator<Item = (SignalType, SignalStrength)> + 'a {
+        emitter.signals.iter().map(
+            move |&(signal_type, signal_strength)| match maybe_modulator {
+                Some(modulator) => (
+                    signal_type,
+                    modulator.modulate(signal_type, signal_strength),
+                ),
+                None => (signal_type, signal_strength),
+            },
+        )
+    }
+
+    for (entity, &tile_pos, emitter, maybe_modulator) in emitter_query.iter() {
+        let noise = jitter.factor(entity, JitterStream::Emission, EMISSION_JITTER);
+
+        // Most emitters cover a single tile, so skip working out the area for them
+        if emitter.area == EmissionArea::Tile {
+            for (signal_type, signal_strength) in modulated_signals(emitter, maybe_modulator) {
+                signals.add_signal(signal_type, tile_pos, signal_strength * noise);
+            }
+            continue;
+        }
+
+        // The area is found once per emitter, rather than once per signal
+        let covered_tiles = emitter.area.tiles(tile_pos, &map_geometry);
+        if covered_tiles.is_empty() {
+            continue;
+        }
+        let share = noise / covered_tiles.len() as f32;
+
+        for (signal_type, signal_strength) in modulated_signals(emitter, maybe_modulator) {
+            for &covered_tile in &covered_tiles {
+                signals.add_signal(signal_type, covered_tile, signal_strength * share);
+            }
+        }
+    }
+
+    for (entity, &tile_pos, emitter, maybe_modulator, trail_smearing) in trail_query.iter_mut() {
+        let traversed_tiles = match trail_smearing.last_tile_pos {
+            Some(last_tile_pos) => TrailSmearing::traversed_tiles(last_tile_pos, tile_pos),
+            None => vec![tile_pos],
+        };
+        let share = jitter.factor(entity, JitterStream::Emission, EMISSION_JITTER)
+            / traversed_tiles.len() as f32;
+
+        for (signal_type, signal_strength) in modulated_signals(emitter, maybe_modulator) {
+            for &traversed_tile in &traversed_tiles {
+                signals.add_signal(signal_type, traversed_tile, signal_strength * share);
+            }
+        }
+
+        trail_smearing
+            .map_unchanged(|trail_smearing| &mut trail_smearing.last_tile_pos)
+            .set_if_neq(Some(tile_pos));
+    }
+}
+
+/// Spreads signals between tiles.
+fn diffuse_signals(
+    mut signals: ResMut<Signals>,
+    map_geometry: Res<MapGeometry>,
+    config: Res<SignalConfig>,
+    wind: Res<Wind>,
+) {
+    let map_geometry = &*map_geometry;
+    signals.diffuse(map_geometry, &config, &wind);
+}
+
+/// Degrades signals, allowing them to approach an asymptotically constant level.
+///
+/// Signals degrade faster in hot seasons, and linger in cold ones.
+fn degrade_signals(mut signals: ResMut<Signals>, config: Res<SignalConfig>, season: Res<Season>) {
+    signals.degrade_scaled(&config, season.signal_decay_multiplier());
+}
+
+/// Caches the best next step towards each signal, once the signals have settled for this tick.
+fn cache_signal_gradients(mut signals: ResMut<Signals>, map_geometry: Res<MapGeometry>) {
+    signals.cache_gradients(&map_geometry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::generation::GenerationConfig;
+    use crate::simulation::geometry::Crossing;
+    use crate::simulation::time::SimulationTick;
+    use crate::structures::walls::Wall;
+
+    const TEST_ITEM: Id<Item> = Id::new(12345);
+    const TEST_STRUCTURE: Id<Structure> = Id::new(67890);
+
+    #[test]
+    fn neighboring_signals_checks_origin_tile() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        signals.add_signal(
+            SignalType::Contains(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+
+        let neighboring_signals = signals.neighboring_signals(
+            SignalType::Contains(TEST_ITEM),
+            TilePos::ORIGIN,
+            &SignalSensitivity::default(),
+            &map_geometry,
+        );
+
+        assert_eq!(neighboring_signals.len(), 7);
+
+        assert_eq!(
+            neighboring_signals.get(&TilePos::ORIGIN),
+            Some(&SignalStrength(1.))
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_with_no_signals() {
+        let signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::Pickup(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::Work(TEST_STRUCTURE),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::Wander,
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_at_trivial_peak() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_at_peak() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        signals.add_signal(
+            SignalType::Push(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            signals.add_signal(SignalType::Push(TEST_ITEM), neighbor, SignalStrength(0.5));
+        }
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::Pickup(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+    }
+
+    #[test]
+    // The logic for Goal::DropOff is significantly more complex and worth testing seperately
+    fn upstream_returns_none_at_peak_dropoff() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            signals.add_signal(SignalType::Pull(TEST_ITEM), neighbor, SignalStrength(0.5));
+        }
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_some_at_trivial_valley() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            signals.add_signal(SignalType::Pull(TEST_ITEM), neighbor, SignalStrength(0.5));
+        }
+
+        assert!(signals
+            .upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn upstream_returns_some_at_valley() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(0.5),
+        );
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            signals.add_signal(SignalType::Pull(TEST_ITEM), neighbor, SignalStrength(1.));
+        }
+
+        assert!(signals
+            .upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn upstream_follows_the_most_heavily_weighted_signals() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+        let pushed = TilePos::ORIGIN.neighbor(hexx::Direction::Top);
+        let stored = TilePos::ORIGIN.neighbor(hexx::Direction::Bottom);
+
+        signals.add_signal(SignalType::Push(TEST_ITEM), pushed, SignalStrength(1.));
+        signals.add_signal(SignalType::Contains(TEST_ITEM), stored, SignalStrength(2.));
+        signals.cache_gradients(&map_geometry);
+
+        let goal = Goal::Pickup(TEST_ITEM);
+        let even = SignalSensitivity::default();
+        let favors_push = SignalSensitivity::default().with_multiplier(SignalCategory::Push, 3.);
+        let ignores_contains = SignalSensitivity::default().blind_to(SignalCategory::Contains);
+
+        assert_eq!(
+            signals.upstream(TilePos::ORIGIN, &goal, &even, &map_geometry),
+            Some(stored)
+        );
+        assert_eq!(
+            signals.upstream(TilePos::ORIGIN, &goal, &favors_push, &map_geometry),
+            Some(pushed)
+        );
+        assert_eq!(
+            signals.upstream(TilePos::ORIGIN, &goal, &ignores_contains, &map_geometry),
+            Some(pushed)
+        );
+    }
+
+    /// Chooses the next step towards a pair of equally strong signals to the east and west of the origin, once per seed.
+    fn choose_steps(
+        step_selection: StepSelection,
+        seeds: core::ops::Range<u64>,
+    ) -> Vec<Option<TilePos>> {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(2);
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::new(1, 0),
+            SignalStrength(1.),
+        );
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::new(-1, 0),
+            SignalStrength(0.9),
+        );
+
+        seeds
+            .map(|seed| {
+                signals.choose_upstream(
+                    TilePos::ORIGIN,
+                    &Goal::DropOff(TEST_ITEM),
+                    &SignalSensitivity::default(),
+                    step_selection,
+                    &mut SmallRng::seed_from_u64(seed),
+                    &map_geometry,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn greedy_steps_always_follow_the_strongest_signal() {
+        let steps = choose_steps(StepSelection::Greedy, 0..20);
+        assert!(steps.iter().all(|&step| step == Some(TilePos::new(1, 0))));
+
+        let frozen_steps = choose_steps(StepSelection::Softmax { temperature: 0. }, 0..20);
+        assert_eq!(frozen_steps, steps);
+    }
+
+    #[test]
+    fn probabilistic_steps_are_spread_out_but_reproducible() {
+        for step_selection in [
+            StepSelection::Softmax { temperature: 0.5 },
+            StepSelection::Roulette,
+        ] {
+            let steps = choose_steps(step_selection, 0..100);
+
+            assert!(steps.contains(&Some(TilePos::new(1, 0))));
+            assert!(steps.contains(&Some(TilePos::new(-1, 0))));
+            // Neighbors without any signal are never chosen
+            assert!(steps
+                .iter()
+                .all(|step| matches!(step, Some(tile_pos) if tile_pos.y == 0)));
+            assert_eq!(choose_steps(step_selection, 0..100), steps);
+        }
+    }
+
+    #[test]
+    fn probabilistic_steps_stop_at_the_peak() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::new(1, 0),
+            SignalStrength(0.5),
+        );
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(
+            signals.choose_upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                StepSelection::Roulette,
+                &mut rng,
+                &map_geometry,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn cached_gradients_match_uncached_gradients() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(3);
+
+        signals.add_signal(
+            SignalType::Push(TEST_ITEM),
+            TilePos::new(2, 0),
+            SignalStrength(1.),
+        );
+        signals.add_signal(
+            SignalType::Contains(TEST_ITEM),
+            TilePos::new(-2, 1),
+            SignalStrength(3.),
+        );
+        signals.add_signal(SignalType::Alarm, TilePos::new(0, -2), SignalStrength(1.));
+        signals.diffuse(&map_geometry, &SignalConfig::default(), &Wind::CALM);
+
+        let goals = [
+            Goal::Pickup(TEST_ITEM),
+            Goal::Eat(TEST_ITEM),
+            Goal::DropOff(TEST_ITEM),
+            Goal::Fight,
+            Goal::Wander,
+        ];
+        let tiles: Vec<TilePos> = hexx::shapes::hexagon(Hex::ZERO, 3)
+            .map(|hex| TilePos { hex })
+            .collect();
+
+        let uncached: Vec<Option<TilePos>> = goals
+            .iter()
+            .flat_map(|goal| {
+                tiles.iter().map(|&tile_pos| {
+                    signals.upstream(tile_pos, goal, &SignalSensitivity::default(), &map_geometry)
+                })
+            })
+            .collect();
+
+        signals.cache_gradients(&map_geometry);
+        assert!(signals.gradients.is_fresh);
+
+        let cached: Vec<Option<TilePos>> = goals
+            .iter()
+            .flat_map(|goal| {
+                tiles.iter().map(|&tile_pos| {
+                    signals.upstream(tile_pos, goal, &SignalSensitivity::default(), &map_geometry)
+                })
+            })
+            .collect();
+
+        assert_eq!(cached, uncached);
+        assert!(cached.iter().any(|step| step.is_some()));
+    }
+
+    #[test]
+    fn adding_signals_invalidates_cached_gradients() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+        signals.cache_gradients(&map_geometry);
+
+        signals.add_signal(
+            SignalType::Pull(TEST_ITEM),
+            TilePos::new(1, 0),
+            SignalStrength(1.),
+        );
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            Some(TilePos::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn sensing_radius_weights_signals_by_distance() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(3);
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(1.));
+        signals.add_signal(SignalType::Alarm, TilePos::new(2, 0), SignalStrength(3.));
+
+        assert_eq!(
+            signals.sensed_strength(SignalType::Alarm, TilePos::ORIGIN, 0, &map_geometry),
+            signals.get(SignalType::Alarm, TilePos::ORIGIN)
+        );
+        assert_eq!(
+            signals.sensed_strength(SignalType::Alarm, TilePos::ORIGIN, 1, &map_geometry),
+            SignalStrength(1.)
+        );
+        // The distant signal is two tiles away, so only a third of it is sensed
+        assert_eq!(
+            signals.sensed_strength(SignalType::Alarm, TilePos::ORIGIN, 2, &map_geometry),
+            SignalStrength(2.)
+        );
+
+        let local_signals = signals.signals_in_radius(TilePos::new(3, 0), 1, &map_geometry);
+        assert_eq!(local_signals.get(SignalType::Alarm), SignalStrength(1.5));
+    }
+
+    #[test]
+    fn custom_signals_are_registered_by_name() {
+        let mut registry = SignalKindRegistry::default();
+        let scent = registry.register("test_scent");
+
+        assert_eq!(registry.register("test_scent"), scent);
+        assert_eq!(registry.get("test_scent"), Some(scent));
+        assert_eq!(registry.get("missing_scent"), None);
+        assert_ne!(registry.register("test_smoke"), scent);
+        assert_eq!(SignalType::Custom(scent).to_string(), "Custom(test_scent)");
+    }
+
+    #[test]
+    fn custom_signals_diffuse_but_are_not_goal_relevant() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+        let scent = SignalKindRegistry::default().register("test_scent");
+
+        signals.add_signal(
+            SignalType::Custom(scent),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+        signals.diffuse(&map_geometry, &SignalConfig::default(), &Wind::CALM);
+
+        let neighbor = TilePos::ORIGIN.neighbor(hexx::Direction::Top);
+        assert!(signals.get(SignalType::Custom(scent), neighbor) > SignalStrength::ZERO);
+        assert_eq!(
+            signals
+                .all_signals_at_position(TilePos::ORIGIN)
+                .goal_relevant_signals()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn sensitivity_scales_and_masks_signals() {
+        let sensitivity = SignalSensitivity::default()
+            .with_multiplier(SignalCategory::Pull, 2.)
+            .blind_to(SignalCategory::Work);
+
+        assert_eq!(
+            sensitivity.perceive(SignalType::Push(TEST_ITEM), SignalStrength(1.)),
+            SignalStrength(1.)
+        );
+        assert_eq!(
+            sensitivity.perceive(SignalType::Pull(TEST_ITEM), SignalStrength(1.)),
+            SignalStrength(2.)
+        );
+        assert_eq!(
+            sensitivity.perceive(SignalType::Work(TEST_STRUCTURE), SignalStrength(1.)),
+            SignalStrength::ZERO
+        );
+    }
+
+    #[test]
+    fn signal_maps_span_chunk_boundaries() {
+        let mut signal_map = SignalMap::default();
+        let tiles = [
+            TilePos::ORIGIN,
+            TilePos::new(-1, 0),
+            TilePos::new(0, -1),
+            TilePos::new(CHUNK_SIZE - 1, CHUNK_SIZE),
+            TilePos::new(-CHUNK_SIZE, 3 * CHUNK_SIZE + 2),
+        ];
+
+        for (i, &tile_pos) in tiles.iter().enumerate() {
+            signal_map.add_signal(tile_pos, SignalStrength(i as f32 + 1.));
+        }
+
+        for (i, &tile_pos) in tiles.iter().enumerate() {
+            assert_eq!(signal_map.get(tile_pos), SignalStrength(i as f32 + 1.));
+        }
+        assert_eq!(signal_map.get(TilePos::new(1, 0)), SignalStrength::ZERO);
+
+        let mut iterated: Vec<TilePos> = signal_map.iter().map(|(tile_pos, _)| tile_pos).collect();
+        iterated.sort_by_key(|tile_pos| (tile_pos.x, tile_pos.y));
+        let mut expected = tiles.to_vec();
+        expected.sort_by_key(|tile_pos| (tile_pos.x, tile_pos.y));
+        assert_eq!(iterated, expected);
+    }
+
+    #[test]
+    fn empty_chunks_are_freed() {
+        let mut signal_map = SignalMap::default();
+        let tile_pos = TilePos::new(-20, 7);
+
+        signal_map.add_signal(tile_pos, SignalStrength(1.));
+        assert_eq!(signal_map.chunks.len(), 1);
+
+        signal_map.set(tile_pos, SignalStrength::ZERO);
+        assert_eq!(signal_map.get(tile_pos), SignalStrength::ZERO);
+        assert!(signal_map.chunks.is_empty());
+
+        signal_map.add_signal(tile_pos, SignalStrength(1.));
+        signal_map.scale(0.5, SignalStrength(0.6));
+        assert!(signal_map.chunks.is_empty());
+    }
+
+    #[test]
+    fn downstream_moves_away_from_danger() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(2);
+
+        let hazard = TilePos::ORIGIN.neighbor(hexx::Direction::Top);
+        let refuge = TilePos::ORIGIN.neighbor(hexx::Direction::Bottom);
+
+        signals.add_signal(SignalType::Danger, hazard, SignalStrength(10.));
+        signals.add_signal(SignalType::Danger, TilePos::ORIGIN, SignalStrength(5.));
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            if neighbor != hazard && neighbor != refuge {
+                signals.add_signal(SignalType::Danger, neighbor, SignalStrength(2.));
+            }
+        }
+
+        assert_eq!(
+            signals.downstream(TilePos::ORIGIN, &Goal::Avoid, &map_geometry),
+            Some(refuge)
+        );
+        // Danger is never followed upstream
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::Avoid,
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn downstream_returns_none_at_safest_tile() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(1);
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            signals.add_signal(SignalType::Danger, neighbor, SignalStrength(1.));
+        }
+
+        assert_eq!(
+            signals.downstream(TilePos::ORIGIN, &Goal::Avoid, &map_geometry),
+            None
+        );
+        assert_eq!(
+            signals.downstream(TilePos::ORIGIN, &Goal::Wander, &map_geometry),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_does_not_climb_cliffs() {
+        let mut signals = Signals::default();
+        let mut map_geometry = MapGeometry::new(1);
+
+        let cliff = TilePos::ORIGIN.neighbor(hexx::Direction::Top);
+        map_geometry.height_index.insert(TilePos::ORIGIN, 1.);
+        map_geometry.height_index.insert(cliff, 3.);
+
+        signals.add_signal(SignalType::Pull(TEST_ITEM), cliff, SignalStrength(1.));
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+
+        map_geometry.crossing_index.insert(
+            cliff,
+            Crossing {
+                walking_speed: 1.,
+                climbs_cliffs: true,
+            },
+        );
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::ORIGIN,
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            Some(cliff)
+        );
+    }
+
+    /// Sums the strength of `signal_type` across every tile.
+    fn total_strength(signals: &Signals, signal_type: SignalType) -> f32 {
+        signals
+            .total_strengths()
+            .find(|(total_type, _)| *total_type == signal_type)
+            .map(|(_, total)| total.value())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn diffusion_splits_signal_between_neighbors() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(2);
+        let rates = SignalRates::new(0.1, 0.);
+        let config = SignalConfig::uniform(rates);
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(1.));
+        signals.diffuse(&map_geometry, &config, &Wind::CALM);
+
+        let retained = signals.get(SignalType::Alarm, TilePos::ORIGIN).value();
+        assert!((retained - 0.4).abs() < 1e-6);
+
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            let received = signals.get(SignalType::Alarm, neighbor).value();
+            assert!((received - 0.1).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn diffusion_conserves_signal_strength() {
+        let mut signals = Signals::default();
+        let mut map_geometry = MapGeometry::new(1);
+        let config = SignalConfig::uniform(SignalRates::new(0.15, 0.));
+
+        // Signal at the edge of the map and next to a wall can't flow freely in every direction
+        let edge = TilePos::new(1, 0);
+        map_geometry.wall_index.insert(
+            TilePos::new(0, 1),
+            Wall {
+                signal_opacity: 0.5,
+                supports_roof: false,
+            },
+        );
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(3.));
+        signals.add_signal(SignalType::Alarm, edge, SignalStrength(2.));
+        signals.add_signal(SignalType::Pull(TEST_ITEM), edge, SignalStrength(1.));
+
+        for _ in 0..10 {
+            signals.diffuse(&map_geometry, &config, &Wind::CALM);
+
+            assert!((total_strength(&signals, SignalType::Alarm) - 5.).abs() < 1e-4);
+            assert!((total_strength(&signals, SignalType::Pull(TEST_ITEM)) - 1.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn signal_strength_only_decays_across_ticks() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(10);
+        let rates = SignalRates::new(0.1, 0.05);
+        let config = SignalConfig::uniform(rates);
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(10.));
+        signals.add_signal(SignalType::Alarm, TilePos::new(3, -2), SignalStrength(5.));
+
+        let mut expected = 15.;
+        for _ in 0..20 {
+            signals.diffuse(&map_geometry, &config, &Wind::CALM);
+            signals.degrade(&config);
+            expected *= 1. - rates.degradation_fraction;
+
+            let total = total_strength(&signals, SignalType::Alarm);
+            assert!((total - expected).abs() < expected * 1e-4);
+        }
+    }
+
+    #[test]
+    fn scaled_degradation_changes_the_decay_rate() {
+        let config = SignalConfig::uniform(SignalRates::new(0., 0.1));
+        let mut slow = Signals::default();
+        let mut fast = Signals::default();
+        slow.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(10.));
+        fast.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(10.));
+
+        slow.degrade_scaled(&config, 0.5);
+        fast.degrade_scaled(&config, 20.);
+
+        assert!((slow.get(SignalType::Alarm, TilePos::ORIGIN).value() - 9.5).abs() < 1e-6);
+        // The degradation rate is capped, so the signal vanishes rather than becoming negative
+        assert_eq!(
+            fast.get(SignalType::Alarm, TilePos::ORIGIN),
+            SignalStrength::ZERO
+        );
+    }
+
+    #[test]
+    fn wind_carries_signal_downwind() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(3);
+        let config = SignalConfig::uniform(SignalRates::new(0.1, 0.));
+        let wind = Wind::new(hexx::Direction::Top, 0.5);
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(1.));
+        signals.diffuse(&map_geometry, &config, &wind);
+
+        let downwind = TilePos::ORIGIN.neighbor(hexx::Direction::Top);
+        let upwind = TilePos::ORIGIN.neighbor(hexx::Direction::Bottom);
+
+        // Half of the 0.4 left behind after diffusion is blown downwind
+        let retained = signals.get(SignalType::Alarm, TilePos::ORIGIN).value();
+        assert!((retained - 0.2).abs() < 1e-6);
+        let received = signals.get(SignalType::Alarm, downwind).value();
+        assert!((received - 0.3).abs() < 1e-6);
+        let received = signals.get(SignalType::Alarm, upwind).value();
+        assert!((received - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn wind_conserves_signal_strength() {
+        let mut signals = Signals::default();
+        let map_geometry = MapGeometry::new(2);
+        let config = SignalConfig::uniform(SignalRates::new(0.15, 0.));
+        let wind = Wind::new(hexx::Direction::TopRight, 0.8);
+
+        signals.add_signal(SignalType::Alarm, TilePos::ORIGIN, SignalStrength(4.));
+
+        // The signal piles up against the edge of the map, rather than being blown off of it
+        for _ in 0..20 {
+            signals.diffuse(&map_geometry, &config, &wind);
+            assert!((total_strength(&signals, SignalType::Alarm) - 4.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn wind_cannot_carry_more_than_all_signal() {
+        Wind::new(hexx::Direction::Top, 1.5);
+    }
+
+    #[test]
+    fn parallel_diffusion_matches_serial_diffusion() {
+        use crate::bevy::tasks::TaskPoolBuilder;
+
+        let map_geometry = MapGeometry::new(40);
+        let mut serial_signals = Signals::default();
+        let mut parallel_signals = Signals::default();
+
+        // Spread the sources across several chunks, including along chunk boundaries
+        for x in -2..=2 {
+            for y in -2..=2 {
+                let tile_pos = TilePos::new(x * CHUNK_SIZE / 2, y * CHUNK_SIZE / 2);
+                for signals in [&mut serial_signals, &mut parallel_signals] {
+                    signals.add_signal(SignalType::Pull(TEST_ITEM), tile_pos, SignalStrength(1.));
+                    signals.add_signal(SignalType::Alarm, tile_pos, SignalStrength(2.));
+                }
+            }
+        }
+
+        let serial_pool = TaskPoolBuilder::new().num_threads(1).build();
+        let parallel_pool = TaskPoolBuilder::new().num_threads(4).build();
+
+        let config = SignalConfig::default();
+
+        for _ in 0..3 {
+            serial_signals.diffuse_with(&map_geometry, &config, &Wind::CALM, &serial_pool);
+            parallel_signals.diffuse_with(&map_geometry, &config, &Wind::CALM, &parallel_pool);
+        }
+
+        let serial: Vec<_> = serial_signals
+            .iter_strengths()
+            .sorted_by_key(|(signal_type, tile_pos, _)| (*signal_type, tile_pos.x, tile_pos.y))
+            .collect();
+        let parallel: Vec<_> = parallel_signals
+            .iter_strengths()
+            .sorted_by_key(|(signal_type, tile_pos, _)| (*signal_type, tile_pos.x, tile_pos.y))
+            .collect();
+
+        assert_eq!(serial.len(), parallel.len());
+        for (
+            (serial_type, serial_pos, serial_strength),
+            (parallel_type, parallel_pos, parallel_strength),
+        ) in serial.iter().zip(parallel.iter())
+        {
+            assert_eq!((serial_type, serial_pos), (parallel_type, parallel_pos));
+            assert!((serial_strength.value() - parallel_strength.value()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn signal_rates_are_overridden_by_category_then_type() {
+        let slow = SignalRates::new(0.05, 0.001);
+        let fast = SignalRates::new(0.15, 0.5);
+        let config = SignalConfig::uniform(SignalRates::default())
+            .with_category_rates(SignalCategory::Pull, slow)
+            .with_type_rates(SignalType::Pull(TEST_ITEM), fast);
+
+        assert_eq!(config.rates(SignalType::Alarm), SignalRates::default());
+        assert_eq!(config.rates(SignalType::Pull(Id::new(1))), slow);
+        assert_eq!(config.rates(SignalType::Pull(TEST_ITEM)), fast);
+    }
+
+    #[test]
+    fn push_signals_fade_faster_than_work_signals() {
+        let mut signals = Signals::default();
+        let config = SignalConfig::default();
+
+        signals.add_signal(
+            SignalType::Push(TEST_ITEM),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+        signals.add_signal(
+            SignalType::Work(TEST_STRUCTURE),
+            TilePos::ORIGIN,
+            SignalStrength(1.),
+        );
+
+        for _ in 0..10 {
+            signals.degrade(&config);
+        }
+
+        assert!(
+            signals.get(SignalType::Push(TEST_ITEM), TilePos::ORIGIN)
+                < signals.get(SignalType::Work(TEST_STRUCTURE), TilePos::ORIGIN)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn unstable_diffusion_rates_are_rejected() {
+        SignalRates::new(0.2, 0.01);
+    }
+
+    #[test]
+    fn trails_are_smeared_across_traversed_tiles() {
+        let start = TilePos::ORIGIN;
+        let end = TilePos::new(3, 0);
+
+        let traversed = TrailSmearing::traversed_tiles(start, end);
+        assert_eq!(
+            traversed,
+            vec![TilePos::new(1, 0), TilePos::new(2, 0), TilePos::new(3, 0)]
+        );
+
+        assert_eq!(TrailSmearing::traversed_tiles(end, end), vec![end]);
+    }
+
+    #[test]
+    fn modulators_scale_only_their_signal_types() {
+        let mut modulator = SignalModulator::default();
+        modulator.set(SignalType::Pull(TEST_ITEM), 0.25);
+
+        assert_eq!(
+            modulator.modulate(SignalType::Pull(TEST_ITEM), SignalStrength(8.)),
+            SignalStrength(2.)
+        );
+        assert_eq!(
+            modulator.modulate(SignalType::Push(TEST_ITEM), SignalStrength(8.)),
+            SignalStrength(8.)
+        );
+    }
+
+    #[test]
+    fn smeared_emission_is_shared_evenly() {
+        let mut app = App::new();
+        app.init_resource::<Signals>()
+            .init_resource::<SimulationTick>()
+            .init_resource::<GenerationConfig>()
+            .insert_resource(MapGeometry::new(10))
+            .add_system(emit_signals);
+
+        let emitter = Emitter {
+            signals: vec![(SignalType::Push(TEST_ITEM), SignalStrength(3.))],
+            ..default()
+        };
+        let entity = app
+            .world
+            .spawn((TilePos::ORIGIN, emitter, TrailSmearing::default()))
+            .id();
+        app.update();
+
+        *app.world.get_mut::<TilePos>(entity).unwrap() = TilePos::new(3, 0);
+        app.update();
+
+        // Emission strength is jittered from tick to tick, but shared evenly between the traversed tiles
+        let signals = app.world.resource::<Signals>();
+        let first_share = signals.get(SignalType::Push(TEST_ITEM), TilePos::new(1, 0));
+        for x in 2..=3 {
+            let strength = signals.get(SignalType::Push(TEST_ITEM), TilePos::new(x, 0));
+            assert!((strength.value() - first_share.value()).abs() < 1e-6);
+        }
+
+        let total = first_share.value() * 3.;
+        assert!((total - 3.).abs() <= 3. * EMISSION_JITTER + 1e-6);
+    }
+
+    #[test]
+    fn area_emission_is_shared_evenly() {
+        let mut app = App::new();
+        app.init_resource::<Signals>()
+            .init_resource::<SimulationTick>()
+            .init_resource::<GenerationConfig>()
+            .insert_resource(MapGeometry::new(10))
+            .add_system(emit_signals);
+
+        let emitter = Emitter {
+            signals: vec![(SignalType::Push(TEST_ITEM), SignalStrength(7.))],
+            area: EmissionArea::Radius(1),
+        };
+        app.world.spawn((TilePos::ORIGIN, emitter));
+        app.update();
+
+        let signals = app.world.resource::<Signals>();
+        let center = signals.get(SignalType::Push(TEST_ITEM), TilePos::ORIGIN);
+        for neighbor in hexagon(Hex::ZERO, 1) {
+            let strength = signals.get(SignalType::Push(TEST_ITEM), TilePos { hex: neighbor });
+            assert!((strength.value() - center.value()).abs() < 1e-6);
+        }
+        assert_eq!(
+            signals.get(SignalType::Push(TEST_ITEM), TilePos::new(2, 0)),
+            SignalStrength::ZERO
+        );
+
+        let total = center.value() * 7.;
+        assert!((total - 7.).abs() <= 7. * EMISSION_JITTER + 1e-6);
+    }
+
+    #[test]
+    fn emission_areas_stay_on_the_map() {
+        let map_geometry = MapGeometry::new(1);
+
+        assert_eq!(
+            EmissionArea::Tile.tiles(TilePos::ORIGIN, &map_geometry),
+            vec![TilePos::ORIGIN]
+        );
+        assert_eq!(
+            EmissionArea::Radius(1)
+                .tiles(TilePos::new(1, 0), &map_geometry)
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn signal_tick_rate_sets_the_period() {
+        assert_eq!(SignalTickRate::new(20.).period(), Duration::from_millis(50));
+    }
+
+    #[test]
+    #[should_panic]
+    fn signal_tick_rate_must_be_positive() {
+        SignalTickRate::new(0.);
+    }
+
+    #[test]
+    fn signal_clock_ticks_once_per_period() {
+        let mut clock = SignalClock::default();
+        let period = SignalTickRate::new(20.).period();
+
+        assert_eq!(clock.advance(Duration::from_millis(30), period), 0);
+        // The leftover time carries over to the next frame
+        assert_eq!(clock.advance(Duration::from_millis(30), period), 1);
+        assert_eq!(clock.advance(Duration::from_millis(140), period), 3);
+        assert_eq!(clock.advance(Duration::ZERO, period), 0);
+    }
+}
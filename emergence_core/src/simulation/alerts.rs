@@ -0,0 +1,74 @@
+//! Alerts notify the player of important events in the world.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use std::collections::VecDeque;
+
+use super::{geometry::TilePos, time::InGameTime};
+
+/// An important event that the player should be told about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// The message shown to the player
+    pub message: String,
+    /// Where the event is happening, if it has a location
+    pub tile_pos: Option<TilePos>,
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = &self.message;
+
+        match self.tile_pos {
+            Some(tile_pos) => write!(f, "{message} at {tile_pos}"),
+            None => write!(f, "{message}"),
+        }
+    }
+}
+
+/// The most recent alerts, and the day on which they were raised.
+#[derive(Resource, Debug, Default)]
+pub struct AlertLog {
+    /// The stored alerts, from oldest to newest
+    alerts: VecDeque<(u32, Alert)>,
+}
+
+impl AlertLog {
+    /// The maximum number of alerts that are remembered.
+    const MAX_ALERTS: usize = 10;
+
+    /// Iterates over the stored alerts from newest to oldest, along with the day that they were raised.
+    pub fn recent(&self) -> impl Iterator<Item = &(u32, Alert)> {
+        self.alerts.iter().rev()
+    }
+}
+
+/// Records each [`Alert`] event in the [`AlertLog`].
+fn record_alerts(
+    mut alert_events: EventReader<Alert>,
+    in_game_time: Res<InGameTime>,
+    mut alert_log: ResMut<AlertLog>,
+) {
+    for alert in alert_events.iter() {
+        info!("Alert: {alert}");
+
+        alert_log
+            .alerts
+            .push_back((in_game_time.current_day(), alert.clone()));
+
+        if alert_log.alerts.len() > AlertLog::MAX_ALERTS {
+            alert_log.alerts.pop_front();
+        }
+    }
+}
+
+/// Collects alerts raised by the simulation.
+pub(super) struct AlertsPlugin;
+
+impl Plugin for AlertsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Alert>()
+            .init_resource::<AlertLog>()
+            .add_system(record_alerts.in_base_set(CoreSet::PostUpdate));
+    }
+}
@@ -0,0 +1,324 @@
+//! Diagnoses why work is not getting done: which units are idle, and which jobs are still waiting for a worker.
+//!
+//! There is no explicit job queue: units follow signals to their work.
+//! Instead, a job is any structure that needs attention, and it is claimed once a unit is acting on it.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use core::fmt::Display;
+use std::collections::VecDeque;
+
+use crate::{
+    manifest::{Id, Structure, Unit, UnitManifest},
+    structures::{
+        construction::{Ghost, MarkedForDemolition},
+        crafting::CraftingState,
+    },
+    units::{actions::CurrentAction, goals::Goal},
+};
+
+use super::{
+    geometry::{MapGeometry, TilePos},
+    time::every_second,
+};
+
+/// Keeps the [`JobBacklog`] up to date.
+pub(super) struct BacklogPlugin;
+
+impl Plugin for BacklogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JobBacklog>()
+            .add_system(update_job_backlog.run_if(every_second()));
+    }
+}
+
+/// A kind of work that the colony could be doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum JobKind {
+    /// Bringing materials to a ghost, or working on it, so that it can be built
+    Build(Id<Structure>),
+    /// Working at a structure whose recipe cannot progress without a worker
+    Work(Id<Structure>),
+    /// Tearing down a structure that has been marked for demolition
+    Demolish(Id<Structure>),
+}
+
+impl Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            JobKind::Build(structure_id) => format!("Build {structure_id}"),
+            JobKind::Work(structure_id) => format!("Work at {structure_id}"),
+            JobKind::Demolish(structure_id) => format!("Demolish {structure_id}"),
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The unclaimed jobs of a single [`JobKind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobSummary {
+    /// The kind of job
+    pub kind: JobKind,
+    /// The number of jobs of this kind that no unit is doing
+    pub unclaimed: usize,
+    /// How many of the unclaimed jobs no member of the colony can walk to
+    pub unreachable: usize,
+    /// Where one of these jobs can be found, preferring unreachable ones
+    pub example: TilePos,
+}
+
+impl Display for JobSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = self.kind;
+        let unclaimed = self.unclaimed;
+
+        match self.unreachable {
+            0 => write!(f, "{kind}: {unclaimed}"),
+            unreachable => write!(f, "{kind}: {unclaimed} ({unreachable} unreachable)"),
+        }
+    }
+}
+
+/// A summary of the colony's idle units and unclaimed jobs.
+///
+/// This is the first place to look when nothing seems to be getting done.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct JobBacklog {
+    /// The number of members of the colony that have nothing to do
+    idle_units: usize,
+    /// Where one of the idle units is standing
+    idle_example: Option<TilePos>,
+    /// The unclaimed jobs of each kind, from most to least numerous
+    jobs: Vec<JobSummary>,
+}
+
+impl JobBacklog {
+    /// The number of members of the colony that have nothing to do.
+    pub fn idle_units(&self) -> usize {
+        self.idle_units
+    }
+
+    /// Where one of the idle units is standing, if there are any.
+    pub fn idle_example(&self) -> Option<TilePos> {
+        self.idle_example
+    }
+
+    /// The unclaimed jobs of each kind, from most to least numerous.
+    pub fn jobs(&self) -> &[JobSummary] {
+        &self.jobs
+    }
+}
+
+/// Every tile that a unit starting from one of the `starts` could walk to.
+fn reachable_tiles(
+    starts: impl IntoIterator<Item = TilePos>,
+    map_geometry: &MapGeometry,
+) -> HashSet<TilePos> {
+    let mut reachable = HashSet::new();
+    let mut frontier = VecDeque::new();
+
+    for start in starts {
+        if reachable.insert(start) {
+            frontier.push_back(start);
+        }
+    }
+
+    while let Some(tile_pos) = frontier.pop_front() {
+        for neighbor in tile_pos.reachable_neighbors(map_geometry) {
+            if reachable.insert(neighbor) {
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Can a unit get close enough to the job at `tile_pos` to do it?
+///
+/// Structures usually block movement, so standing on any neighboring tile is enough.
+fn job_is_reachable(
+    tile_pos: TilePos,
+    reachable: &HashSet<TilePos>,
+    map_geometry: &MapGeometry,
+) -> bool {
+    reachable.contains(&tile_pos)
+        || tile_pos
+            .all_neighbors(map_geometry)
+            .into_iter()
+            .any(|neighbor| reachable.contains(&neighbor))
+}
+
+/// Groups unclaimed jobs by kind, given the kind, location and reachability of each job.
+fn summarize_jobs(jobs: impl IntoIterator<Item = (JobKind, TilePos, bool)>) -> Vec<JobSummary> {
+    let mut summaries: Vec<JobSummary> = Vec::new();
+
+    for (kind, tile_pos, reachable) in jobs {
+        let summary = match summaries.iter_mut().find(|summary| summary.kind == kind) {
+            Some(summary) => summary,
+            None => {
+                summaries.push(JobSummary {
+                    kind,
+                    unclaimed: 0,
+                    unreachable: 0,
+                    example: tile_pos,
+                });
+                summaries.last_mut().unwrap()
+            }
+        };
+
+        summary.unclaimed += 1;
+        if !reachable {
+            // Unreachable jobs are the most surprising, so they make the best examples
+            if summary.unreachable == 0 {
+                summary.example = tile_pos;
+            }
+            summary.unreachable += 1;
+        }
+    }
+
+    summaries.sort_by(|a, b| b.unclaimed.cmp(&a.unclaimed).then(a.kind.cmp(&b.kind)));
+    summaries
+}
+
+/// Recounts the idle units and unclaimed jobs, once a second.
+#[allow(clippy::too_many_arguments)]
+fn update_job_backlog(
+    unit_query: Query<(&TilePos, &Id<Unit>, &Goal, &CurrentAction)>,
+    ghost_query: Query<(Entity, &TilePos, &Id<Structure>), With<Ghost>>,
+    workplace_query: Query<
+        (Entity, &TilePos, &Id<Structure>, &CraftingState),
+        (Without<Ghost>, Without<MarkedForDemolition>),
+    >,
+    demolition_query: Query<(Entity, &TilePos, &Id<Structure>), With<MarkedForDemolition>>,
+    unit_manifest: Res<UnitManifest>,
+    map_geometry: Res<MapGeometry>,
+    mut backlog: ResMut<JobBacklog>,
+) {
+    let mut colony_positions = Vec::new();
+    let mut claimed = HashSet::new();
+    let mut idle_units = 0;
+    let mut idle_example = None;
+
+    for (&tile_pos, &unit_id, goal, current_action) in unit_query.iter() {
+        // Intruders are not expected to help out
+        if unit_manifest.get(unit_id).nest().is_none() {
+            continue;
+        }
+
+        colony_positions.push(tile_pos);
+        if let Some(target) = current_action.target_entity() {
+            claimed.insert(target);
+        }
+
+        if *goal == Goal::Wander {
+            idle_units += 1;
+            idle_example.get_or_insert(tile_pos);
+        }
+    }
+
+    let builds = ghost_query
+        .iter()
+        .map(|(entity, &tile_pos, &structure_id)| (entity, JobKind::Build(structure_id), tile_pos));
+    let work = workplace_query
+        .iter()
+        .filter(|(.., crafting_state)| {
+            matches!(
+                crafting_state,
+                CraftingState::InProgress {
+                    work_required: true,
+                    worker_present: false,
+                    ..
+                }
+            )
+        })
+        .map(|(entity, &tile_pos, &structure_id, _)| {
+            (entity, JobKind::Work(structure_id), tile_pos)
+        });
+    let demolitions = demolition_query
+        .iter()
+        .map(|(entity, &tile_pos, &structure_id)| {
+            (entity, JobKind::Demolish(structure_id), tile_pos)
+        });
+
+    let reachable = reachable_tiles(colony_positions, &map_geometry);
+    let jobs = builds
+        .chain(work)
+        .chain(demolitions)
+        .filter(|(entity, ..)| !claimed.contains(entity))
+        .map(|(_, kind, tile_pos)| {
+            (
+                kind,
+                tile_pos,
+                job_is_reachable(tile_pos, &reachable, &map_geometry),
+            )
+        });
+
+    let new_backlog = JobBacklog {
+        idle_units,
+        idle_example,
+        jobs: summarize_jobs(jobs),
+    };
+
+    backlog.set_if_neq(new_backlog);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_STRUCTURE: Id<Structure> = Id::new(12345);
+    const OTHER_STRUCTURE: Id<Structure> = Id::new(67890);
+
+    #[test]
+    fn cliffs_split_the_map_into_unreachable_regions() {
+        let mut map_geometry = MapGeometry::new(3);
+        // A ring of cliffs around the origin
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            map_geometry.set_height(neighbor, 5.);
+        }
+
+        let reachable = reachable_tiles([TilePos::new(3, 0)], &map_geometry);
+
+        assert!(!reachable.contains(&TilePos::ORIGIN));
+        assert!(reachable.contains(&TilePos::new(-3, 0)));
+        // Jobs on the cliffs themselves can still be done from below
+        assert!(job_is_reachable(
+            TilePos::new(1, 0),
+            &reachable,
+            &map_geometry
+        ));
+        assert!(!job_is_reachable(
+            TilePos::ORIGIN,
+            &reachable,
+            &map_geometry
+        ));
+    }
+
+    #[test]
+    fn jobs_are_grouped_by_kind() {
+        let summaries = summarize_jobs([
+            (JobKind::Work(OTHER_STRUCTURE), TilePos::new(0, 1), true),
+            (JobKind::Build(TEST_STRUCTURE), TilePos::new(1, 0), true),
+            (JobKind::Build(TEST_STRUCTURE), TilePos::new(2, 0), false),
+        ]);
+
+        assert_eq!(
+            summaries,
+            vec![
+                JobSummary {
+                    kind: JobKind::Build(TEST_STRUCTURE),
+                    unclaimed: 2,
+                    unreachable: 1,
+                    example: TilePos::new(2, 0),
+                },
+                JobSummary {
+                    kind: JobKind::Work(OTHER_STRUCTURE),
+                    unclaimed: 1,
+                    unreachable: 0,
+                    example: TilePos::new(0, 1),
+                },
+            ]
+        );
+    }
+}
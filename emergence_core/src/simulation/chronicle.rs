@@ -0,0 +1,476 @@
+//! Distills the history of the world into a chronicle, one season at a time.
+//!
+//! Noteworthy happenings are sent as [`HistoricalEvent`]s, and collected over the course of each season.
+//! When the season ends, similar events are grouped together and written up as short narrative [`ChronicleEntry`]s,
+//! like "The great fire of year 2 destroyed 7 structures in the east".
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use hexx::HexLayout;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    manifest::{Id, Structure},
+    units::alarm::ViolentDeath,
+};
+
+use super::{
+    director::Hazard,
+    geometry::{MapGeometry, TilePos},
+    time::{InGameTime, Season, SeasonChanged, DAYS_PER_SEASON},
+};
+
+/// The number of structures that a single disaster must destroy in one season to be remembered as great.
+const GREAT_DISASTER_THRESHOLD: usize = 5;
+
+/// Events whose average position is closer than this to the center of the map happened "in the heart of the land".
+const HEARTLAND_RADIUS: f32 = 10.;
+
+/// Collects [`HistoricalEvent`]s and writes them into the [`Chronicle`].
+pub(super) struct ChroniclePlugin;
+
+impl Plugin for ChroniclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HistoricalEvent>()
+            .init_resource::<Chronicle>()
+            .add_system(record_historical_events.in_base_set(CoreSet::PostUpdate))
+            .add_system(
+                write_chronicle
+                    .after(record_historical_events)
+                    .in_base_set(CoreSet::PostUpdate),
+            );
+    }
+}
+
+/// A disaster that can destroy structures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disaster {
+    /// The structure burned down
+    Fire,
+    /// The structure was torn apart by a storm
+    Storm,
+}
+
+impl Display for Disaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Disaster::Fire => "fire",
+            Disaster::Storm => "storm",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Something that happened which is worth remembering.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoricalEvent {
+    /// A structure was destroyed by a disaster
+    Destroyed {
+        /// The type of structure that was destroyed
+        structure_id: Id<Structure>,
+        /// What destroyed it
+        disaster: Disaster,
+        /// Where the structure stood
+        tile_pos: TilePos,
+    },
+    /// A hazard sent by the director struck
+    HazardStruck {
+        /// The type of hazard
+        hazard: Hazard,
+        /// Where the hazard struck, if it was localized
+        tile_pos: Option<TilePos>,
+    },
+    /// Visitors from a scenario event arrived at the edge of the map
+    Arrived {
+        /// The name of the scenario event
+        name: String,
+        /// Where the visitors entered the map
+        tile_pos: TilePos,
+    },
+    /// An organism ran out of energy
+    Starved {
+        /// Where the organism died
+        tile_pos: TilePos,
+    },
+    /// A unit died of old age
+    DiedOfOldAge {
+        /// Where the unit died
+        tile_pos: TilePos,
+    },
+    /// A unit was killed by something other than starvation
+    KilledInBattle {
+        /// Where the unit fell
+        tile_pos: TilePos,
+    },
+}
+
+/// A single narrative entry in the [`Chronicle`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChronicleEntry {
+    /// The year in which this happened, starting from 1
+    pub year: u32,
+    /// The season in which this happened
+    pub season: Season,
+    /// What happened
+    pub text: String,
+}
+
+impl Display for ChronicleEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of year {}: {}", self.season, self.year, self.text)
+    }
+}
+
+/// The written history of the world.
+#[derive(Resource, Debug, Default)]
+pub struct Chronicle {
+    /// Every entry written so far, from oldest to newest
+    entries: Vec<ChronicleEntry>,
+    /// The events of the current season, which have not yet been written up
+    pending: Vec<HistoricalEvent>,
+}
+
+impl Chronicle {
+    /// Every entry written so far, from oldest to newest.
+    pub fn entries(&self) -> &[ChronicleEntry] {
+        &self.entries
+    }
+
+    /// The entries of each season, from oldest to newest.
+    ///
+    /// Seasons in which nothing was written are skipped.
+    pub fn seasons(&self) -> Vec<&[ChronicleEntry]> {
+        let mut seasons = Vec::new();
+        let mut start = 0;
+
+        for end in 1..=self.entries.len() {
+            let season_ends = match self.entries.get(end) {
+                Some(next) => {
+                    let current = &self.entries[start];
+                    (next.year, next.season) != (current.year, current.season)
+                }
+                None => true,
+            };
+
+            if season_ends {
+                seasons.push(&self.entries[start..end]);
+                start = end;
+            }
+        }
+
+        seasons
+    }
+
+    /// Replaces the whole chronicle with previously written `entries`, e.g. when a saved game is loaded.
+    ///
+    /// Events from the current season are forgotten.
+    pub fn restore(&mut self, entries: Vec<ChronicleEntry>) {
+        self.entries = entries;
+        self.pending.clear();
+    }
+}
+
+/// The year that `day` falls in, starting from 1.
+fn year_of_day(day: u32) -> u32 {
+    day / (DAYS_PER_SEASON * 4) + 1
+}
+
+/// Describes where on the map a group of events happened, e.g. "the east".
+fn describe_region(tile_positions: &[TilePos], layout: &HexLayout) -> &'static str {
+    if tile_positions.is_empty() {
+        return "the wilds";
+    }
+
+    let total: Vec2 = tile_positions
+        .iter()
+        .map(|tile_pos| layout.hex_to_world_pos(tile_pos.hex))
+        .sum();
+    let average = total / tile_positions.len() as f32;
+
+    if average.length() < HEARTLAND_RADIUS {
+        "the heart of the land"
+    } else if average.x.abs() >= average.y.abs() {
+        if average.x > 0. {
+            "the east"
+        } else {
+            "the west"
+        }
+    } else if average.y > 0. {
+        "the south"
+    } else {
+        "the north"
+    }
+}
+
+/// Writes up the `events` of a season as narrative sentences.
+///
+/// Similar events are grouped together, so that a season full of small fires reads as a single disaster.
+fn narrate_season(events: &[HistoricalEvent], year: u32, layout: &HexLayout) -> Vec<String> {
+    let mut sentences = Vec::new();
+
+    for disaster in [Disaster::Fire, Disaster::Storm] {
+        let mut tile_positions = Vec::new();
+        let mut structure_counts: Vec<(Id<Structure>, usize)> = Vec::new();
+
+        for event in events {
+            if let HistoricalEvent::Destroyed {
+                structure_id,
+                disaster: event_disaster,
+                tile_pos,
+            } = event
+            {
+                if *event_disaster != disaster {
+                    continue;
+                }
+
+                tile_positions.push(*tile_pos);
+                match structure_counts
+                    .iter_mut()
+                    .find(|(id, _)| id == structure_id)
+                {
+                    Some((_, count)) => *count += 1,
+                    None => structure_counts.push((*structure_id, 1)),
+                }
+            }
+        }
+
+        let n_destroyed = tile_positions.len();
+        if n_destroyed == 0 {
+            continue;
+        }
+
+        let region = describe_region(&tile_positions, layout);
+        // Ties go to the structure that was destroyed first
+        let mut most_common = structure_counts[0];
+        for candidate in structure_counts {
+            if candidate.1 > most_common.1 {
+                most_common = candidate;
+            }
+        }
+        let most_common = most_common.0;
+
+        sentences.push(if n_destroyed >= GREAT_DISASTER_THRESHOLD {
+            format!("The great {disaster} of year {year} destroyed {n_destroyed} structures in {region}, most of them {most_common}")
+        } else if n_destroyed == 1 {
+            format!("A {disaster} destroyed a {most_common} in {region}")
+        } else {
+            format!("A {disaster} destroyed {n_destroyed} structures in {region}")
+        });
+    }
+
+    for hazard in [Hazard::PestWave, Hazard::Blight, Hazard::Storm] {
+        let tile_positions: Vec<Option<TilePos>> = events
+            .iter()
+            .filter_map(|event| match event {
+                HistoricalEvent::HazardStruck {
+                    hazard: event_hazard,
+                    tile_pos,
+                } if *event_hazard == hazard => Some(*tile_pos),
+                _ => None,
+            })
+            .collect();
+
+        match tile_positions.len() {
+            0 => (),
+            1 => match tile_positions[0] {
+                Some(tile_pos) => sentences.push(format!(
+                    "{hazard} struck {}",
+                    describe_region(&[tile_pos], layout)
+                )),
+                None => sentences.push(format!("{hazard} swept over the land")),
+            },
+            n => sentences.push(format!("{hazard} struck {n} times")),
+        }
+    }
+
+    for event in events {
+        if let HistoricalEvent::Arrived { name, tile_pos } = event {
+            sentences.push(format!(
+                "{name} arrived from {}",
+                describe_region(&[*tile_pos], layout)
+            ));
+        }
+    }
+
+    let n_starved = events
+        .iter()
+        .filter(|event| matches!(event, HistoricalEvent::Starved { .. }))
+        .count();
+    match n_starved {
+        0 => (),
+        1 => sentences.push("An organism starved".to_string()),
+        n => sentences.push(format!("{n} organisms starved")),
+    }
+
+    let n_died_of_old_age = events
+        .iter()
+        .filter(|event| matches!(event, HistoricalEvent::DiedOfOldAge { .. }))
+        .count();
+    match n_died_of_old_age {
+        0 => (),
+        1 => sentences.push("A creature died of old age".to_string()),
+        n => sentences.push(format!("{n} creatures died of old age")),
+    }
+
+    let fallen: Vec<TilePos> = events
+        .iter()
+        .filter_map(|event| match event {
+            HistoricalEvent::KilledInBattle { tile_pos } => Some(*tile_pos),
+            _ => None,
+        })
+        .collect();
+    match fallen.len() {
+        0 => (),
+        1 => sentences.push(format!(
+            "A creature fell in battle in {}",
+            describe_region(&fallen, layout)
+        )),
+        n => sentences.push(format!(
+            "{n} creatures fell in battle in {}",
+            describe_region(&fallen, layout)
+        )),
+    }
+
+    if sentences.is_empty() {
+        sentences.push("The season passed quietly".to_string());
+    }
+
+    sentences
+}
+
+/// Collects the events of the current season.
+fn record_historical_events(
+    mut historical_events: EventReader<HistoricalEvent>,
+    mut violent_deaths: EventReader<ViolentDeath>,
+    mut chronicle: ResMut<Chronicle>,
+) {
+    for event in historical_events.iter() {
+        chronicle.pending.push(event.clone());
+    }
+
+    for violent_death in violent_deaths.iter() {
+        chronicle.pending.push(HistoricalEvent::KilledInBattle {
+            tile_pos: violent_death.tile_pos,
+        });
+    }
+}
+
+/// Writes up the season that just ended whenever the [`Season`] changes.
+fn write_chronicle(
+    mut season_changed: EventReader<SeasonChanged>,
+    in_game_time: Res<InGameTime>,
+    map_geometry: Res<MapGeometry>,
+    mut chronicle: ResMut<Chronicle>,
+) {
+    for event in season_changed.iter() {
+        // The season that just ended finished on the previous day
+        let year = year_of_day(in_game_time.current_day().saturating_sub(1));
+        let events = std::mem::take(&mut chronicle.pending);
+
+        for text in narrate_season(&events, year, &map_geometry.layout) {
+            chronicle.entries.push(ChronicleEntry {
+                year,
+                season: event.previous,
+                text,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_STRUCTURE: Id<Structure> = Id::new(12345);
+
+    fn burned(x: i32, y: i32) -> HistoricalEvent {
+        HistoricalEvent::Destroyed {
+            structure_id: TEST_STRUCTURE,
+            disaster: Disaster::Fire,
+            tile_pos: TilePos::new(x, y),
+        }
+    }
+
+    #[test]
+    fn entries_are_grouped_by_season() {
+        let entry = |year, season| ChronicleEntry {
+            year,
+            season,
+            text: "Something happened".to_string(),
+        };
+
+        let mut chronicle = Chronicle::default();
+        assert!(chronicle.seasons().is_empty());
+
+        chronicle.restore(vec![
+            entry(1, Season::Spring),
+            entry(1, Season::Spring),
+            entry(1, Season::Summer),
+            entry(2, Season::Spring),
+        ]);
+        let lengths: Vec<usize> = chronicle
+            .seasons()
+            .iter()
+            .map(|season| season.len())
+            .collect();
+        assert_eq!(lengths, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn years_are_counted_from_one() {
+        assert_eq!(year_of_day(0), 1);
+        assert_eq!(year_of_day(DAYS_PER_SEASON * 4 - 1), 1);
+        assert_eq!(year_of_day(DAYS_PER_SEASON * 4), 2);
+    }
+
+    #[test]
+    fn quiet_seasons_are_still_recorded() {
+        let sentences = narrate_season(&[], 1, &HexLayout::default());
+        assert_eq!(sentences, vec!["The season passed quietly".to_string()]);
+    }
+
+    #[test]
+    fn many_fires_become_one_great_fire() {
+        let events: Vec<HistoricalEvent> = (0..6).map(|i| burned(40, i)).collect();
+        let sentences = narrate_season(&events, 2, &HexLayout::default());
+
+        assert_eq!(sentences.len(), 1);
+        assert!(sentences[0].starts_with("The great fire of year 2 destroyed 6 structures"));
+    }
+
+    #[test]
+    fn events_near_the_center_happen_in_the_heartland() {
+        let layout = HexLayout::default();
+
+        assert_eq!(
+            describe_region(&[TilePos::ORIGIN], &layout),
+            "the heart of the land"
+        );
+        assert_ne!(
+            describe_region(&[TilePos::new(40, 0)], &layout),
+            describe_region(&[TilePos::new(-40, 0)], &layout)
+        );
+    }
+
+    #[test]
+    fn different_kinds_of_events_are_narrated_separately() {
+        let events = [
+            burned(0, 0),
+            HistoricalEvent::Starved {
+                tile_pos: TilePos::ORIGIN,
+            },
+            HistoricalEvent::Starved {
+                tile_pos: TilePos::ORIGIN,
+            },
+            HistoricalEvent::HazardStruck {
+                hazard: Hazard::Storm,
+                tile_pos: None,
+            },
+        ];
+        let sentences = narrate_season(&events, 1, &HexLayout::default());
+
+        assert_eq!(sentences.len(), 3);
+        assert!(sentences.contains(&"2 organisms starved".to_string()));
+    }
+}
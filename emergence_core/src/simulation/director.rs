@@ -0,0 +1,421 @@
+//! The director watches how well the colony is doing, and sends hazards to challenge it.
+//!
+//! Hazards are scaled to the prosperity of the colony, announced ahead of time,
+//! and separated by a cooldown so that the player has time to recover.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use hexx::shapes::hexagon;
+use rand::seq::IteratorRandom;
+
+use crate::{
+    manifest::{Id, Structure, Unit, UnitManifest},
+    organisms::{
+        energy::{Energy, EnergyPool},
+        Organism,
+    },
+    structures::crafting::OutputInventory,
+};
+
+use super::{
+    alerts::Alert,
+    chronicle::HistoricalEvent,
+    geometry::{MapGeometry, TilePos},
+    jitter::{Jitter, JitterStream},
+    scenario::{random_edge_tile, ActiveWaves, SpawnWaves},
+    time::InGameTime,
+    weather::{CurrentWeather, Weather},
+};
+
+/// Controls how (and whether) the director sends hazards.
+///
+/// Insert this resource before adding the [`SimulationPlugin`](super::SimulationPlugin) to customize it for a game.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct DirectorConfig {
+    /// Should hazards be sent at all?
+    pub enabled: bool,
+    /// Scales the severity of all hazards.
+    pub difficulty: f32,
+    /// The minimum number of days between hazards.
+    pub cooldown_days: u32,
+    /// How many seconds of warning are given before a hazard strikes.
+    pub warning_seconds: f32,
+}
+
+impl Default for DirectorConfig {
+    fn default() -> Self {
+        DirectorConfig {
+            enabled: true,
+            difficulty: 1.0,
+            cooldown_days: 5,
+            warning_seconds: 20.,
+        }
+    }
+}
+
+/// A summary of how well the colony is doing.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct Prosperity {
+    /// The number of living units that belong to the colony
+    pub n_units: usize,
+    /// The number of structures of any kind
+    pub n_structures: usize,
+    /// The total number of items stored in structures
+    pub n_stored_items: usize,
+}
+
+impl Prosperity {
+    /// A single number summarizing the colony's prosperity.
+    pub fn score(&self) -> f32 {
+        self.n_units as f32 + 0.5 * self.n_structures as f32 + 0.1 * self.n_stored_items as f32
+    }
+
+    /// How severe a hazard sent at the given `difficulty` should be, or `None` if the colony is struggling too much to be challenged.
+    fn hazard_severity(&self, difficulty: f32) -> Option<f32> {
+        let severity = self.score() * difficulty * 0.2;
+        (severity >= MIN_SEVERITY).then_some(severity)
+    }
+}
+
+/// A challenge sent by the director.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hazard {
+    /// A swarm of pests arrives from the edge of the map.
+    PestWave,
+    /// A disease that drains the energy of organisms in an area.
+    Blight,
+    /// A storm sweeps across the whole map.
+    Storm,
+}
+
+impl Display for Hazard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Hazard::PestWave => "A swarm of pests",
+            Hazard::Blight => "A blight",
+            Hazard::Storm => "A storm",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// A hazard that has been announced, but has not yet struck.
+#[derive(Debug, Clone)]
+struct PendingHazard {
+    /// The type of hazard
+    hazard: Hazard,
+    /// How strong the hazard will be
+    severity: f32,
+    /// Where the hazard will strike, if it is localized
+    tile_pos: Option<TilePos>,
+    /// Counts down until the hazard strikes
+    timer: Timer,
+}
+
+/// The internal state of the director.
+#[derive(Resource, Debug, Default)]
+struct Director {
+    /// The earliest day on which the next hazard can be announced
+    next_hazard_day: u32,
+    /// The hazard that is about to strike, if any
+    pending: Option<PendingHazard>,
+}
+
+/// Hazards less severe than this are not worth sending.
+const MIN_SEVERITY: f32 = 1.;
+
+/// The number of days before the first hazard can occur, giving new colonies time to settle in.
+const GRACE_PERIOD_DAYS: u32 = 10;
+
+/// How many tiles away from its center a blight spreads.
+const BLIGHT_RADIUS: u32 = 2;
+
+/// The energy drained from each organism in a blight, per point of severity.
+const BLIGHT_ENERGY_PER_SEVERITY: f32 = 5.;
+
+/// The maximum number of pests sent in a single wave.
+const MAX_PESTS_PER_WAVE: usize = 20;
+
+/// Measures the [`Prosperity`] of the colony.
+fn measure_prosperity(
+    unit_query: Query<&Id<Unit>>,
+    unit_manifest: Res<UnitManifest>,
+    structure_query: Query<(), With<Id<Structure>>>,
+    storage_query: Query<&OutputInventory>,
+    mut prosperity: ResMut<Prosperity>,
+) {
+    prosperity.set_if_neq(Prosperity {
+        // Intruders are not part of the colony's success
+        n_units: unit_query
+            .iter()
+            .filter(|&&unit_id| unit_manifest.get(unit_id).nest().is_some())
+            .count(),
+        n_structures: structure_query.iter().len(),
+        n_stored_items: storage_query
+            .iter()
+            .map(|output| output.iter().map(|slot| slot.count()).sum::<usize>())
+            .sum(),
+    });
+}
+
+/// Announces new hazards, scaled to the colony's [`Prosperity`].
+#[allow(clippy::too_many_arguments)]
+fn announce_hazards(
+    config: Res<DirectorConfig>,
+    prosperity: Res<Prosperity>,
+    in_game_time: Res<InGameTime>,
+    mut director: ResMut<Director>,
+    organism_query: Query<&TilePos, (With<Organism>, With<Id<Structure>>)>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+    mut alerts: EventWriter<Alert>,
+) {
+    if !config.enabled || director.pending.is_some() {
+        return;
+    }
+
+    let today = in_game_time.current_day();
+    if today < GRACE_PERIOD_DAYS.max(director.next_hazard_day) {
+        return;
+    }
+
+    let severity = match prosperity.hazard_severity(config.difficulty) {
+        Some(severity) => severity,
+        // The colony is struggling enough already: check again tomorrow
+        None => {
+            director.next_hazard_day = today + 1;
+            return;
+        }
+    };
+
+    let rng = &mut jitter.world_rng(JitterStream::Director);
+    let hazard = [Hazard::PestWave, Hazard::Blight, Hazard::Storm]
+        .into_iter()
+        .choose(rng)
+        .unwrap();
+
+    // The outer option is None if no valid location could be found for the hazard
+    let maybe_target = match hazard {
+        Hazard::PestWave => random_edge_tile(&map_geometry, rng).map(Some),
+        Hazard::Blight => organism_query.iter().copied().choose(rng).map(Some),
+        // Storms strike the whole map
+        Hazard::Storm => Some(None),
+    };
+
+    match maybe_target {
+        Some(tile_pos) => {
+            let warning_seconds = config.warning_seconds;
+            alerts.send(Alert {
+                message: format!("{hazard} will strike in {warning_seconds:.0} seconds"),
+                tile_pos,
+            });
+
+            director.pending = Some(PendingHazard {
+                hazard,
+                severity,
+                tile_pos,
+                timer: Timer::from_seconds(warning_seconds, TimerMode::Once),
+            });
+        }
+        None => director.next_hazard_day = today + 1,
+    }
+}
+
+/// Unleashes announced hazards once their warning period is over.
+#[allow(clippy::too_many_arguments)]
+fn strike_hazards(
+    time: Res<Time>,
+    config: Res<DirectorConfig>,
+    in_game_time: Res<InGameTime>,
+    mut director: ResMut<Director>,
+    mut organism_query: Query<(&TilePos, &mut EnergyPool), (With<Organism>, With<Id<Structure>>)>,
+    mut current_weather: ResMut<CurrentWeather>,
+    mut alerts: EventWriter<Alert>,
+    mut historical_events: EventWriter<HistoricalEvent>,
+    mut commands: Commands,
+) {
+    let pending = match &mut director.pending {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    pending.timer.tick(time.delta());
+    if !pending.timer.finished() {
+        return;
+    }
+
+    match (pending.hazard, pending.tile_pos) {
+        (Hazard::PestWave, Some(entry_point)) => {
+            let units_per_wave = (pending.severity.round() as usize).clamp(1, MAX_PESTS_PER_WAVE);
+
+            commands.spawn(ActiveWaves::new(
+                SpawnWaves {
+                    unit_id: Id::locust(),
+                    units_per_wave,
+                    n_waves: 2,
+                    seconds_between_waves: 10.,
+                },
+                entry_point,
+            ));
+        }
+        (Hazard::Blight, Some(center)) => {
+            let damage = Energy(pending.severity * BLIGHT_ENERGY_PER_SEVERITY);
+            let blighted_area: Vec<TilePos> = hexagon(center.hex, BLIGHT_RADIUS)
+                .map(|hex| TilePos { hex })
+                .collect();
+
+            for (tile_pos, mut energy_pool) in organism_query.iter_mut() {
+                if blighted_area.contains(tile_pos) {
+                    let proposed = energy_pool.current() - damage;
+                    energy_pool.set_current(proposed);
+                }
+            }
+        }
+        (Hazard::Storm, _) => current_weather.set(Weather::Storm),
+        // Localized hazards are always given a location when they are announced
+        (_, None) => (),
+    }
+
+    alerts.send(Alert {
+        message: format!("{} has struck", pending.hazard),
+        tile_pos: pending.tile_pos,
+    });
+    historical_events.send(HistoricalEvent::HazardStruck {
+        hazard: pending.hazard,
+        tile_pos: pending.tile_pos,
+    });
+
+    director.pending = None;
+    director.next_hazard_day = in_game_time.current_day() + config.cooldown_days;
+}
+
+/// Sends hazards that scale with the colony's success.
+pub(super) struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DirectorConfig>()
+            .init_resource::<Prosperity>()
+            .init_resource::<Director>()
+            .add_system(measure_prosperity)
+            .add_system(announce_hazards.after(measure_prosperity))
+            .add_system(strike_hazards.after(announce_hazards));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{generation::GenerationConfig, time::SimulationTick};
+
+    /// A colony whose [`Prosperity::score`] is `score`.
+    fn colony_with_score(score: usize) -> Prosperity {
+        Prosperity {
+            n_units: score,
+            ..default()
+        }
+    }
+
+    /// An app that announces hazards to a colony with the given `prosperity`.
+    fn director_app(prosperity: Prosperity) -> App {
+        let mut app = App::new();
+        app.init_resource::<DirectorConfig>()
+            .init_resource::<Director>()
+            .init_resource::<InGameTime>()
+            .init_resource::<SimulationTick>()
+            .init_resource::<GenerationConfig>()
+            .insert_resource(prosperity)
+            .insert_resource(MapGeometry::new(5))
+            .add_event::<Alert>()
+            .add_system(announce_hazards);
+
+        // Blights need an organism to strike
+        app.world.spawn((
+            TilePos::ORIGIN,
+            Id::<Structure>::from_string_id("acacia"),
+            Organism,
+        ));
+
+        app
+    }
+
+    /// Advances the in-game clock to the start of `day`, and lets the director act.
+    fn update_on_day(app: &mut App, day: u32) {
+        app.world
+            .resource_mut::<InGameTime>()
+            .set_elapsed_days(day as f32);
+        app.update();
+    }
+
+    #[test]
+    fn prosperity_weighs_units_above_structures_and_items() {
+        let prosperity = Prosperity {
+            n_units: 2,
+            n_structures: 4,
+            n_stored_items: 10,
+        };
+
+        assert_eq!(prosperity.score(), 2. + 2. + 1.);
+        assert_eq!(Prosperity::default().score(), 0.);
+    }
+
+    #[test]
+    fn struggling_colonies_are_not_challenged() {
+        // A score of 5 is just enough at the default difficulty
+        assert_eq!(colony_with_score(4).hazard_severity(1.), None);
+        assert!(colony_with_score(5).hazard_severity(1.).is_some());
+
+        // Higher difficulties challenge smaller colonies, with more severe hazards
+        let severity = colony_with_score(4).hazard_severity(2.).unwrap();
+        assert!(severity > colony_with_score(5).hazard_severity(1.).unwrap());
+    }
+
+    #[test]
+    fn intruders_do_not_count_towards_prosperity() {
+        let mut app = App::new();
+        app.init_resource::<Prosperity>()
+            .init_resource::<UnitManifest>()
+            .add_system(measure_prosperity);
+        app.world.spawn(Id::ant());
+        app.world.spawn(Id::soldier_ant());
+        app.world.spawn(Id::locust());
+        app.update();
+
+        assert_eq!(app.world.resource::<Prosperity>().n_units, 2);
+    }
+
+    #[test]
+    fn hazards_wait_for_the_grace_period() {
+        let mut app = director_app(colony_with_score(100));
+
+        update_on_day(&mut app, GRACE_PERIOD_DAYS - 1);
+        assert!(app.world.resource::<Director>().pending.is_none());
+
+        update_on_day(&mut app, GRACE_PERIOD_DAYS);
+        assert!(app.world.resource::<Director>().pending.is_some());
+        assert_eq!(app.world.resource::<Events<Alert>>().len(), 1);
+    }
+
+    #[test]
+    fn hazards_wait_for_the_cooldown() {
+        let mut app = director_app(colony_with_score(100));
+        app.world.resource_mut::<Director>().next_hazard_day = GRACE_PERIOD_DAYS + 5;
+
+        update_on_day(&mut app, GRACE_PERIOD_DAYS + 4);
+        assert!(app.world.resource::<Director>().pending.is_none());
+
+        update_on_day(&mut app, GRACE_PERIOD_DAYS + 5);
+        assert!(app.world.resource::<Director>().pending.is_some());
+    }
+
+    #[test]
+    fn struggling_colonies_are_checked_again_tomorrow() {
+        let mut app = director_app(colony_with_score(1));
+
+        update_on_day(&mut app, GRACE_PERIOD_DAYS);
+        let director = app.world.resource::<Director>();
+        assert!(director.pending.is_none());
+        assert_eq!(director.next_hazard_day, GRACE_PERIOD_DAYS + 1);
+    }
+}
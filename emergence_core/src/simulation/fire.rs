@@ -0,0 +1,258 @@
+//! Fires burn structures, draining the energy of living ones and destroying the rest.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use rand::Rng;
+
+use crate::{
+    manifest::{Id, Structure},
+    organisms::energy::{Energy, EnergyPool},
+    structures::commands::StructureCommandsExt,
+};
+
+use super::{
+    alerts::Alert,
+    chronicle::{Disaster, HistoricalEvent},
+    geometry::{MapGeometry, TilePos},
+    jitter::{Jitter, JitterStream},
+    weather::LocalWeather,
+};
+
+/// A structure that is currently burning.
+#[derive(Component, Debug, Clone)]
+pub struct OnFire {
+    /// Counts down until the fire burns out
+    timer: Timer,
+}
+
+impl Default for OnFire {
+    fn default() -> Self {
+        OnFire {
+            timer: Timer::from_seconds(OnFire::BURN_DURATION_IN_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl OnFire {
+    /// How long a fire burns before going out.
+    const BURN_DURATION_IN_SECONDS: f32 = 15.;
+}
+
+/// The energy drained from burning organisms per second.
+const FIRE_DAMAGE_PER_SECOND: Energy = Energy(5.);
+
+/// The chance per second that a fire spreads to each neighboring structure.
+const FIRE_SPREAD_CHANCE_PER_SECOND: f64 = 0.05;
+
+/// Burns structures that are on fire, spreading the flames to their neighbors.
+#[allow(clippy::too_many_arguments)]
+fn burn(
+    time: Res<Time>,
+    local_weather: LocalWeather,
+    mut burning_query: Query<(
+        Entity,
+        &TilePos,
+        &Id<Structure>,
+        &mut OnFire,
+        Option<&mut EnergyPool>,
+    )>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+    mut alerts: EventWriter<Alert>,
+    mut historical_events: EventWriter<HistoricalEvent>,
+    mut commands: Commands,
+) {
+    let delta = time.delta_seconds();
+
+    let burning_tiles: HashSet<TilePos> = burning_query
+        .iter()
+        .map(|(_, &tile_pos, ..)| tile_pos)
+        .collect();
+
+    for (entity, &tile_pos, structure_id, mut on_fire, maybe_energy_pool) in
+        burning_query.iter_mut()
+    {
+        // Wet weather helps put fires out
+        let burn_rate = local_weather.at(tile_pos).burn_rate();
+        let spread_chance =
+            (FIRE_SPREAD_CHANCE_PER_SECOND * delta as f64 / burn_rate as f64).min(1.);
+
+        on_fire.timer.tick(time.delta().mul_f32(burn_rate));
+
+        match maybe_energy_pool {
+            // Living structures are damaged, but survive if they have enough energy
+            Some(mut energy_pool) => {
+                let proposed = energy_pool.current() - FIRE_DAMAGE_PER_SECOND * delta;
+                energy_pool.set_current(proposed);
+
+                if on_fire.timer.finished() {
+                    commands.entity(entity).remove::<OnFire>();
+                }
+            }
+            // Everything else is consumed by the fire
+            None => {
+                if on_fire.timer.finished() {
+                    commands.despawn_structure(tile_pos);
+                    alerts.send(Alert {
+                        message: format!("A {structure_id} burned down"),
+                        tile_pos: Some(tile_pos),
+                    });
+                    historical_events.send(HistoricalEvent::Destroyed {
+                        structure_id: *structure_id,
+                        disaster: Disaster::Fire,
+                        tile_pos,
+                    });
+                }
+            }
+        }
+
+        let rng = &mut jitter.rng(entity, JitterStream::Fire);
+        for neighbor in tile_pos.all_neighbors(&map_geometry) {
+            if burning_tiles.contains(&neighbor) {
+                continue;
+            }
+
+            if let Some(&neighbor_entity) = map_geometry.structure_index.get(&neighbor) {
+                if rng.gen_bool(spread_chance) {
+                    commands.entity(neighbor_entity).insert(OnFire::default());
+                }
+            }
+        }
+    }
+}
+
+/// Burning structures and the spread of fire.
+pub(super) struct FirePlugin;
+
+impl Plugin for FirePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(burn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::utils::{Duration, Instant};
+
+    use super::*;
+    use crate::simulation::{
+        generation::GenerationConfig,
+        time::SimulationTick,
+        weather::{CurrentWeather, Weather, WeatherFronts},
+    };
+
+    /// An app that burns structures, where every frame lasts for `frame_seconds`.
+    fn fire_app(frame_seconds: f32) -> App {
+        let mut app = App::new();
+
+        // The clock is never advanced, so every frame lasts for the same time
+        let mut time = Time::default();
+        let start = Instant::now();
+        time.update_with_instant(start);
+        time.update_with_instant(start + Duration::from_secs_f32(frame_seconds));
+
+        app.insert_resource(time)
+            .insert_resource(MapGeometry::new(5))
+            .init_resource::<CurrentWeather>()
+            .init_resource::<WeatherFronts>()
+            .init_resource::<SimulationTick>()
+            .init_resource::<GenerationConfig>()
+            .add_event::<Alert>()
+            .add_event::<HistoricalEvent>()
+            .add_system(burn);
+
+        app
+    }
+
+    /// Spawns a structure at `tile_pos`, which is living if it has an `energy_pool`.
+    fn spawn_structure(
+        app: &mut App,
+        tile_pos: TilePos,
+        maybe_energy_pool: Option<EnergyPool>,
+    ) -> Entity {
+        let mut structure = app
+            .world
+            .spawn((tile_pos, Id::<Structure>::from_string_id("acacia")));
+        if let Some(energy_pool) = maybe_energy_pool {
+            structure.insert(energy_pool);
+        }
+        let structure_entity = structure.id();
+
+        app.world
+            .resource_mut::<MapGeometry>()
+            .structure_index
+            .insert(tile_pos, structure_entity);
+
+        structure_entity
+    }
+
+    /// A full pool of energy, large enough to survive a fire.
+    fn energy_pool() -> EnergyPool {
+        EnergyPool::new_full(Energy(500.), Energy(0.))
+    }
+
+    #[test]
+    fn fires_spread_to_neighboring_structures() {
+        // Long enough that the fire is certain to spread
+        let mut app = fire_app(20.);
+        let burning = spawn_structure(&mut app, TilePos::ORIGIN, Some(energy_pool()));
+        app.world.entity_mut(burning).insert(OnFire::default());
+        let neighbor = spawn_structure(&mut app, TilePos::new(1, 0), Some(energy_pool()));
+        let distant = spawn_structure(&mut app, TilePos::new(2, 0), Some(energy_pool()));
+        app.update();
+
+        assert!(app.world.get::<OnFire>(neighbor).is_some());
+        assert!(app.world.get::<OnFire>(distant).is_none());
+    }
+
+    #[test]
+    fn living_structures_are_damaged_until_the_fire_goes_out() {
+        let mut app = fire_app(5.);
+        let burning = spawn_structure(&mut app, TilePos::ORIGIN, Some(energy_pool()));
+        app.world.entity_mut(burning).insert(OnFire::default());
+
+        app.update();
+        let energy = app.world.get::<EnergyPool>(burning).unwrap().current();
+        assert_eq!(energy, Energy(500.) - FIRE_DAMAGE_PER_SECOND * 5.);
+
+        for _ in 0..2 {
+            app.update();
+        }
+        assert!(app.world.get::<OnFire>(burning).is_none());
+        assert!(app.world.get_entity(burning).is_some());
+    }
+
+    #[test]
+    fn other_structures_burn_down() {
+        let mut app = fire_app(5.);
+        let burning = spawn_structure(&mut app, TilePos::ORIGIN, None);
+        app.world.entity_mut(burning).insert(OnFire::default());
+
+        for _ in 0..2 {
+            app.update();
+        }
+        assert!(app.world.get_entity(burning).is_some());
+
+        app.update();
+        assert!(app.world.get_entity(burning).is_none());
+        assert!(app
+            .world
+            .resource::<MapGeometry>()
+            .structure_index
+            .is_empty());
+        assert_eq!(app.world.resource::<Events<Alert>>().len(), 1);
+        assert_eq!(app.world.resource::<Events<HistoricalEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn wet_weather_puts_fires_out_sooner() {
+        let mut app = fire_app(10.);
+        app.world
+            .resource_mut::<CurrentWeather>()
+            .set(Weather::Rain);
+        let burning = spawn_structure(&mut app, TilePos::ORIGIN, Some(energy_pool()));
+        app.world.entity_mut(burning).insert(OnFire::default());
+        app.update();
+
+        assert!(app.world.get::<OnFire>(burning).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+//! Guides any number of units towards a shared destination.
+//!
+//! Rather than searching for a separate [`Path`](super::pathfinding::Path) from each unit,
+//! a [`FlowField`] is computed once for the destination, integrating the cost of walking there from every tile on the map.
+//! Each unit then simply steps onto the neighboring tile that the field points it towards.
+//!
+//! Flow fields are cached in [`FlowFields`], and thrown away whenever the walkable parts of the map change.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use core::cmp::Ordering;
+use std::{
+    collections::{hash_map::DefaultHasher, BinaryHeap, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use crate::terrain::Terrain;
+
+use super::geometry::{MapGeometry, TilePos};
+
+/// The maximum number of flow fields kept in the cache.
+///
+/// Only a few destinations are shared by many units at once, so this can be small.
+const MAX_FLOW_FIELDS: usize = 16;
+
+/// Caches flow fields, and discards them when they go stale.
+pub(super) struct FlowFieldPlugin;
+
+impl Plugin for FlowFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowFields>()
+            .add_system(invalidate_flow_fields.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+/// The cheapest way to walk to a single destination, from every tile that can reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowField {
+    /// The tile that this field leads to
+    destination: TilePos,
+    /// The tile to step onto next from each tile, on the way to the destination
+    next_tiles: HashMap<TilePos, TilePos>,
+    /// The total cost of walking from each tile to the destination
+    costs: HashMap<TilePos, f32>,
+}
+
+/// A tile whose cost to the destination is known, ordered so that the [`BinaryHeap`] returns the cheapest tile first.
+#[derive(Debug, Clone, Copy)]
+struct Integrated {
+    /// The cost of walking from this tile to the destination
+    cost: f32,
+    /// The tile whose cost is known
+    tile_pos: TilePos,
+}
+
+impl PartialEq for Integrated {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Integrated {}
+
+impl PartialOrd for Integrated {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Integrated {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the lowest cost is popped first
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl FlowField {
+    /// Integrates the cost of walking to `destination` from every tile on the map.
+    ///
+    /// `step_cost` returns the cost of stepping from the first tile to the adjacent second tile.
+    /// The destination itself does not need to be passable: units can be led up to a structure, such as their nest.
+    pub fn compute(
+        map_geometry: &MapGeometry,
+        destination: TilePos,
+        step_cost: impl Fn(TilePos, TilePos) -> f32,
+    ) -> Self {
+        let mut next_tiles = HashMap::new();
+        let mut costs = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+
+        costs.insert(destination, 0.);
+        frontier.push(Integrated {
+            cost: 0.,
+            tile_pos: destination,
+        });
+
+        while let Some(Integrated { cost, tile_pos }) = frontier.pop() {
+            // Stale entries are left in the heap when a cheaper route is found
+            if cost > costs[&tile_pos] {
+                continue;
+            }
+
+            // Search backwards: find the tiles that a unit could step from to reach this one
+            for neighbor in tile_pos.all_neighbors(map_geometry) {
+                // Units can't stand on impassable tiles, even if they could step out of them
+                let can_enter = map_geometry.is_passable(neighbor)
+                    && (tile_pos == destination || map_geometry.can_step(neighbor, tile_pos));
+                if !can_enter {
+                    continue;
+                }
+
+                let new_cost = cost + step_cost(neighbor, tile_pos);
+                let is_cheaper = match costs.get(&neighbor) {
+                    Some(&existing_cost) => new_cost < existing_cost,
+                    None => true,
+                };
+
+                if is_cheaper {
+                    costs.insert(neighbor, new_cost);
+                    next_tiles.insert(neighbor, tile_pos);
+                    frontier.push(Integrated {
+                        cost: new_cost,
+                        tile_pos: neighbor,
+                    });
+                }
+            }
+        }
+
+        FlowField {
+            destination,
+            next_tiles,
+            costs,
+        }
+    }
+
+    /// The tile that this field leads to.
+    pub fn destination(&self) -> TilePos {
+        self.destination
+    }
+
+    /// The tile that a unit standing on `tile_pos` should step onto next.
+    ///
+    /// Returns `None` at the destination, or if the destination cannot be reached from `tile_pos`.
+    pub fn next_step(&self, tile_pos: TilePos) -> Option<TilePos> {
+        self.next_tiles.get(&tile_pos).copied()
+    }
+
+    /// The cost of walking from `tile_pos` to the destination, or `None` if it cannot be reached.
+    pub fn cost_from(&self, tile_pos: TilePos) -> Option<f32> {
+        self.costs.get(&tile_pos).copied()
+    }
+}
+
+/// The flow fields computed so far, stored by their destination.
+#[derive(Resource, Debug, Default)]
+pub struct FlowFields {
+    /// The cached flow field for each destination
+    fields: HashMap<TilePos, FlowField>,
+    /// The destinations in the cache, from the least to the most recently computed
+    computed_order: VecDeque<TilePos>,
+    /// A summary of everything that affects where units can walk, as of the last check
+    passability: u64,
+}
+
+impl FlowFields {
+    /// The cached flow field leading to `destination`, if any.
+    pub fn get(&self, destination: TilePos) -> Option<&FlowField> {
+        self.fields.get(&destination)
+    }
+
+    /// The flow field leading to `destination`, computing it if it is not already cached.
+    ///
+    /// See [`FlowField::compute`] for the meaning of `step_cost`.
+    pub fn get_or_compute(
+        &mut self,
+        map_geometry: &MapGeometry,
+        destination: TilePos,
+        step_cost: impl Fn(TilePos, TilePos) -> f32,
+    ) -> &FlowField {
+        if !self.fields.contains_key(&destination) {
+            if self.computed_order.len() >= MAX_FLOW_FIELDS {
+                if let Some(oldest) = self.computed_order.pop_front() {
+                    self.fields.remove(&oldest);
+                }
+            }
+
+            let flow_field = FlowField::compute(map_geometry, destination, step_cost);
+            self.fields.insert(destination, flow_field);
+            self.computed_order.push_back(destination);
+        }
+
+        &self.fields[&destination]
+    }
+
+    /// Discards every cached flow field.
+    pub fn clear(&mut self) {
+        self.fields.clear();
+        self.computed_order.clear();
+    }
+
+    /// The number of flow fields currently cached.
+    pub fn n_cached(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+/// Hashes a single `value`, for use in [`passability_fingerprint`].
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A summary of everything in the [`MapGeometry`] that affects where units can walk, and how quickly.
+///
+/// The hashes of each tile are summed, so the iteration order of the indexes does not matter.
+fn passability_fingerprint(map_geometry: &MapGeometry) -> u64 {
+    let structures = map_geometry
+        .structure_index
+        .keys()
+        .map(|tile_pos| hash_of((0u8, tile_pos)));
+    let crossings = map_geometry
+        .crossing_index
+        .iter()
+        .map(|(tile_pos, crossing)| {
+            hash_of((
+                1u8,
+                tile_pos,
+                crossing.climbs_cliffs,
+                crossing.walking_speed.to_bits(),
+            ))
+        });
+    let open_water = map_geometry
+        .open_water
+        .iter()
+        .map(|tile_pos| hash_of((2u8, tile_pos)));
+    let heights = map_geometry
+        .height_index
+        .iter()
+        .map(|(tile_pos, height)| hash_of((3u8, tile_pos, height.to_bits())));
+
+    structures
+        .chain(crossings)
+        .chain(open_water)
+        .chain(heights)
+        .fold(0, u64::wrapping_add)
+}
+
+/// Discards the cached flow fields whenever the walkable parts of the map change.
+fn invalidate_flow_fields(
+    map_geometry: Res<MapGeometry>,
+    changed_terrain_query: Query<(), Changed<Terrain>>,
+    mut flow_fields: ResMut<FlowFields>,
+) {
+    let terrain_changed = !changed_terrain_query.is_empty();
+
+    // The map geometry also changes for reasons that don't affect walking, like placing previews
+    if !map_geometry.is_changed() && !terrain_changed {
+        return;
+    }
+
+    let passability = passability_fingerprint(&map_geometry);
+
+    // Changed terrain can alter walking costs without changing which tiles can be walked on
+    if passability != flow_fields.passability || (terrain_changed && flow_fields.n_cached() > 0) {
+        flow_fields.passability = passability;
+        flow_fields.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::Hex;
+
+    use super::*;
+
+    #[test]
+    fn flow_fields_lead_to_the_destination() {
+        let map_geometry = MapGeometry::new(5);
+        let destination = TilePos::new(2, 0);
+        let flow_field = FlowField::compute(&map_geometry, destination, |_, _| 1.);
+
+        let mut tile_pos = TilePos::new(-3, 0);
+        let mut n_steps = 0;
+        while let Some(next_tile) = flow_field.next_step(tile_pos) {
+            assert_eq!(next_tile.hex.distance_to(tile_pos.hex), 1);
+            tile_pos = next_tile;
+            n_steps += 1;
+        }
+
+        assert_eq!(tile_pos, destination);
+        assert_eq!(n_steps, 5);
+        assert_eq!(flow_field.cost_from(TilePos::new(-3, 0)), Some(5.));
+    }
+
+    #[test]
+    fn impassable_destinations_can_be_approached() {
+        let mut map_geometry = MapGeometry::new(5);
+        let nest = TilePos::new(2, 0);
+        map_geometry
+            .structure_index
+            .insert(nest, Entity::from_raw(0));
+
+        let flow_field = FlowField::compute(&map_geometry, nest, |_, _| 1.);
+
+        assert_eq!(flow_field.next_step(TilePos::new(1, 0)), Some(nest));
+        assert_eq!(flow_field.cost_from(TilePos::ORIGIN), Some(2.));
+    }
+
+    #[test]
+    fn flow_fields_go_around_obstacles() {
+        let mut map_geometry = MapGeometry::new(5);
+        let blocked = TilePos::new(1, 0);
+        map_geometry
+            .structure_index
+            .insert(blocked, Entity::from_raw(0));
+
+        let flow_field = FlowField::compute(&map_geometry, TilePos::new(2, 0), |_, _| 1.);
+
+        assert_ne!(flow_field.next_step(TilePos::ORIGIN), Some(blocked));
+        assert_eq!(flow_field.cost_from(TilePos::ORIGIN), Some(3.));
+        assert_eq!(flow_field.cost_from(blocked), None);
+    }
+
+    #[test]
+    fn passability_changes_are_detected() {
+        let mut map_geometry = MapGeometry::new(5);
+        let original = passability_fingerprint(&map_geometry);
+
+        map_geometry
+            .structure_index
+            .insert(TilePos::new(1, 0), Entity::from_raw(0));
+        assert_ne!(passability_fingerprint(&map_geometry), original);
+
+        map_geometry.structure_index.clear();
+        assert_eq!(passability_fingerprint(&map_geometry), original);
+
+        // Previews don't block units, so the cached fields can be kept
+        map_geometry
+            .preview_index
+            .insert(TilePos::new(1, 0), Entity::from_raw(1));
+        assert_eq!(passability_fingerprint(&map_geometry), original);
+    }
+
+    #[test]
+    fn the_oldest_flow_fields_are_forgotten() {
+        let map_geometry = MapGeometry::new(5);
+        let mut flow_fields = FlowFields::default();
+
+        let destinations: Vec<TilePos> = hexx::shapes::hexagon(Hex::ZERO, 5)
+            .map(|hex| TilePos { hex })
+            .take(MAX_FLOW_FIELDS + 1)
+            .collect();
+        for &destination in &destinations {
+            flow_fields.get_or_compute(&map_geometry, destination, |_, _| 1.);
+        }
+
+        assert_eq!(flow_fields.n_cached(), MAX_FLOW_FIELDS);
+        assert!(flow_fields.get(destinations[0]).is_none());
+    }
+}
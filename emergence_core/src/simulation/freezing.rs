@@ -0,0 +1,205 @@
+//! Freezing temperatures turn water to ice, freeze stored water and harm organisms that are not adapted to the cold.
+
+use crate::bevy::prelude::*;
+
+use crate::{
+    organisms::energy::{Energy, EnergyPool},
+    structures::{irrigation::Cistern, walls::Enclosures},
+    terrain::Terrain,
+};
+
+use super::{
+    geometry::{MapGeometry, TilePos},
+    temperature::Temperature,
+};
+
+/// The temperature at which water freezes, in degrees Celsius.
+pub const FREEZING_POINT: f32 = 0.;
+
+/// Marks water tiles that have frozen into walkable ice, and cisterns whose water has frozen solid.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frozen;
+
+/// The lowest temperature that an organism can endure without being harmed, in degrees Celsius.
+///
+/// Organisms that are adapted to the cold have lower tolerances.
+#[derive(Component, Debug, Clone, Copy, PartialEq, PartialOrd, Deref)]
+pub struct ColdTolerance(pub f32);
+
+/// The energy drained per second from organisms, for each degree below their [`ColdTolerance`].
+const COLD_DAMAGE_PER_DEGREE_PER_SECOND: f32 = 0.2;
+
+/// Freezes and thaws water tiles, changing whether they can be walked across.
+fn freeze_water(
+    terrain_query: Query<(Entity, &TilePos, &Terrain, &Temperature, Option<&Frozen>)>,
+    mut map_geometry: ResMut<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, &tile_pos, terrain, temperature, maybe_frozen) in terrain_query.iter() {
+        if *terrain != Terrain::Water {
+            continue;
+        }
+
+        match (temperature.0 < FREEZING_POINT, maybe_frozen.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(Frozen);
+                map_geometry.open_water.remove(&tile_pos);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Frozen>();
+                map_geometry.open_water.insert(tile_pos);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Freezes and thaws the water stored in cisterns, based on the temperature of the tile they are on.
+fn freeze_cisterns(
+    cistern_query: Query<(Entity, &TilePos, Option<&Frozen>), With<Cistern>>,
+    temperature_query: Query<&Temperature>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, tile_pos, maybe_frozen) in cistern_query.iter() {
+        let temperature = match map_geometry.terrain_index.get(tile_pos) {
+            Some(&terrain_entity) => temperature_query.get(terrain_entity).unwrap().0,
+            None => continue,
+        };
+
+        match (temperature < FREEZING_POINT, maybe_frozen.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(Frozen);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Frozen>();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Organisms on exposed tiles colder than they can tolerate lose energy.
+fn cold_damage(
+    time: Res<Time>,
+    mut organism_query: Query<(&TilePos, &ColdTolerance, &mut EnergyPool)>,
+    temperature_query: Query<&Temperature>,
+    enclosures: Res<Enclosures>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (tile_pos, cold_tolerance, mut energy_pool) in organism_query.iter_mut() {
+        if enclosures.is_sheltered(*tile_pos) {
+            continue;
+        }
+
+        let temperature = match map_geometry.terrain_index.get(tile_pos) {
+            Some(&terrain_entity) => temperature_query.get(terrain_entity).unwrap().0,
+            None => continue,
+        };
+
+        let degrees_too_cold = cold_tolerance.0 - temperature;
+        if degrees_too_cold > 0. {
+            let damage =
+                Energy(degrees_too_cold * COLD_DAMAGE_PER_DEGREE_PER_SECOND * time.delta_seconds());
+            let proposed = energy_pool.current() - damage;
+            energy_pool.set_current(proposed);
+        }
+    }
+}
+
+/// The effects of freezing temperatures.
+pub(super) struct FreezingPlugin;
+
+impl Plugin for FreezingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(freeze_water)
+            .add_system(freeze_cisterns)
+            .add_system(cold_damage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app that freezes and thaws a single water tile at the origin, starting at `temperature`.
+    fn water_app(temperature: f32) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_system(freeze_water).add_system(freeze_cisterns);
+
+        let terrain_entity = app
+            .world
+            .spawn((TilePos::ORIGIN, Terrain::Water, Temperature(temperature)))
+            .id();
+        let mut map_geometry = MapGeometry::new(1);
+        map_geometry
+            .terrain_index
+            .insert(TilePos::ORIGIN, terrain_entity);
+        map_geometry.open_water.insert(TilePos::ORIGIN);
+        app.insert_resource(map_geometry);
+
+        (app, terrain_entity)
+    }
+
+    /// Is the water at the origin frozen, both as a component and in the [`MapGeometry`]?
+    fn is_frozen(app: &App, terrain_entity: Entity) -> bool {
+        let frozen = app.world.get::<Frozen>(terrain_entity).is_some();
+        let open = app
+            .world
+            .resource::<MapGeometry>()
+            .open_water
+            .contains(&TilePos::ORIGIN);
+        assert_ne!(frozen, open);
+
+        frozen
+    }
+
+    #[test]
+    fn water_freezes_below_the_freezing_point() {
+        let (mut app, terrain_entity) = water_app(FREEZING_POINT - 0.1);
+        app.update();
+
+        assert!(is_frozen(&app, terrain_entity));
+    }
+
+    #[test]
+    fn water_does_not_freeze_at_the_freezing_point() {
+        let (mut app, terrain_entity) = water_app(FREEZING_POINT);
+        app.update();
+
+        assert!(!is_frozen(&app, terrain_entity));
+    }
+
+    #[test]
+    fn ice_thaws_once_it_warms_up() {
+        let (mut app, terrain_entity) = water_app(-5.);
+        app.update();
+        assert!(is_frozen(&app, terrain_entity));
+
+        app.world.get_mut::<Temperature>(terrain_entity).unwrap().0 = FREEZING_POINT;
+        app.update();
+        assert!(!is_frozen(&app, terrain_entity));
+    }
+
+    #[test]
+    fn dry_land_never_freezes() {
+        let (mut app, terrain_entity) = water_app(-5.);
+        app.world.entity_mut(terrain_entity).insert(Terrain::Plain);
+        app.update();
+
+        assert!(app.world.get::<Frozen>(terrain_entity).is_none());
+    }
+
+    #[test]
+    fn cisterns_freeze_and_thaw_with_their_tile() {
+        let (mut app, terrain_entity) = water_app(-5.);
+        let cistern = app.world.spawn((TilePos::ORIGIN, Cistern::default())).id();
+
+        app.update();
+        assert!(app.world.get::<Frozen>(cistern).is_some());
+
+        app.world.get_mut::<Temperature>(terrain_entity).unwrap().0 = 5.;
+        app.update();
+        assert!(app.world.get::<Frozen>(cistern).is_none());
+    }
+}
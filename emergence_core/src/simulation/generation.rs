@@ -0,0 +1,538 @@
+//! Generating starting terrain and organisms
+//!
+//! Generation is fully determined by [`GenerationConfig`]: the same seed always produces the same map.
+use crate::bevy::app::{App, Plugin};
+use crate::bevy::ecs::prelude::*;
+use crate::bevy::log::info;
+use crate::bevy::math::vec2;
+use crate::bevy::prelude::{CoreSchedule, IntoSystemAppConfigs};
+use crate::bevy::utils::HashMap;
+use crate::enum_iter::IterableEnum;
+use crate::manifest::{Id, Structure, StructureManifest, Unit, UnitManifest};
+use crate::simulation::geometry::{ChunkPos, Facing, TilePos};
+use crate::structures::{commands::StructureCommandsExt, ClipboardData};
+use crate::terrain::{Terrain, TerrainBundle};
+use crate::units::UnitBundle;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::geometry::MapGeometry;
+use super::noise::fbm_simplex_2d_seeded;
+
+/// Controls world generation strategy
+#[derive(Resource, Debug, Clone)]
+pub struct GenerationConfig {
+    /// Radius of the map.
+    map_radius: u32,
+    /// Initial number of ants.
+    n_ant: usize,
+    /// Initial number of plants.
+    n_plant: usize,
+    /// Initial number of fungi.
+    n_fungi: usize,
+    /// Initial number of ant hives.
+    n_hive: usize,
+    /// Relative probability of generating tiles of each terrain type.
+    terrain_weights: HashMap<Terrain, f32>,
+    /// The seed that determines every random choice made during generation.
+    seed: u64,
+    /// The number of chunks around the [`GenerationFocus`] to generate, or `None` to generate the whole map at startup.
+    streaming_distance: Option<u32>,
+    /// Units that are always spawned at a fixed position, in addition to the randomly placed ones.
+    placed_units: Vec<(Id<Unit>, TilePos)>,
+    /// Structures that are always spawned at a fixed position, in addition to the randomly placed ones.
+    placed_structures: Vec<(Id<Structure>, TilePos)>,
+}
+
+impl GenerationConfig {
+    /// The number of tiles from the center of the map to the edge
+    const MAP_RADIUS: u32 = 20;
+
+    /// The number of ants in the default generation config
+    const N_ANT: usize = 5;
+    /// The number of plants in the default generation config
+    const N_PLANT: usize = 12;
+    /// The number of fungi in the default generation config
+    const N_FUNGI: usize = 2;
+    /// The number of ant hives in the default generation config
+    const N_HIVE: usize = 1;
+
+    /// The choice weight for plain terrain in default generation config
+    const TERRAIN_WEIGHT_PLAIN: f32 = 1.0;
+    /// The choice weight for high terrain in default generation config
+    const TERRAIN_WEIGHT_HIGH: f32 = 0.3;
+    /// The choice weight for impassable terrain in default generation config
+    const TERRAIN_WEIGHT_ROCKY: f32 = 0.2;
+    /// The choice weight for water in default generation config
+    const TERRAIN_WEIGHT_WATER: f32 = 0.1;
+
+    /// The seed used by the default generation config
+    const SEED: u64 = 2378;
+
+    /// Sets the seed used for world generation.
+    ///
+    /// Maps generated with the same seed and settings are identical.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The seed used for world generation.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Replaces the seed, e.g. when a saved game is loaded.
+    ///
+    /// The seed also drives the randomness of the running simulation, and of any chunks that are yet to be generated.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Sets the radius of the map, in tiles.
+    pub fn with_map_radius(mut self, map_radius: u32) -> Self {
+        self.map_radius = map_radius;
+        self
+    }
+
+    /// Generates the map lazily, a chunk at a time, as the `GenerationFocus` comes within `distance` chunks of it.
+    ///
+    /// This allows maps far larger than could be generated all at once.
+    /// Chunks are never unloaded once generated, as the simulation runs across the whole map.
+    pub fn with_streaming(mut self, distance: u32) -> Self {
+        self.streaming_distance = Some(distance);
+        self
+    }
+
+    /// Covers the whole map in a single type of terrain.
+    pub fn with_terrain_fill(mut self, terrain: Terrain) -> Self {
+        self.terrain_weights = Terrain::variants()
+            .map(|variant| (variant, if variant == terrain { 1. } else { 0. }))
+            .collect();
+        self
+    }
+
+    /// Disables the randomly placed starting organisms.
+    ///
+    /// Units and structures added with [`GenerationConfig::with_unit`] and [`GenerationConfig::with_structure`] are still spawned.
+    pub fn without_random_organisms(mut self) -> Self {
+        self.n_ant = 0;
+        self.n_plant = 0;
+        self.n_fungi = 0;
+        self.n_hive = 0;
+        self
+    }
+
+    /// Spawns a unit of type `unit_id` at `tile_pos` when the world is generated.
+    pub fn with_unit(mut self, unit_id: Id<Unit>, tile_pos: TilePos) -> Self {
+        self.placed_units.push((unit_id, tile_pos));
+        self
+    }
+
+    /// Spawns a structure of type `structure_id` at `tile_pos` when the world is generated.
+    pub fn with_structure(mut self, structure_id: Id<Structure>, tile_pos: TilePos) -> Self {
+        self.placed_structures.push((structure_id, tile_pos));
+        self
+    }
+
+    /// The random number generator used for each stage of generation.
+    ///
+    /// Each stage gets its own generator, so changing how many random numbers one stage uses does not reshuffle the others.
+    fn rng(&self, stage: GenerationStage) -> SmallRng {
+        SmallRng::seed_from_u64(self.seed ^ stage as u64)
+    }
+
+    /// The random number generator used to generate the tile at `tile_pos`.
+    fn tile_rng(&self, tile_pos: TilePos) -> SmallRng {
+        /// Spreads the bits of the tile coordinates across the whole seed
+        const TILE_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+        let tile_bits = ((tile_pos.x as u32 as u64) << 32) | tile_pos.y as u32 as u64;
+        SmallRng::seed_from_u64(
+            self.seed ^ GenerationStage::Terrain as u64 ^ tile_bits.wrapping_mul(TILE_MULTIPLIER),
+        )
+    }
+
+    /// The seed passed to the noise function that shapes the terrain height.
+    ///
+    /// The noise function works in floating point, so only the low bits of the seed are used.
+    fn noise_seed(&self) -> f32 {
+        (self.seed % 65_536) as f32
+    }
+}
+
+/// The stages of world generation that draw random numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GenerationStage {
+    /// Choosing the type of each tile
+    ///
+    /// Each tile is further seeded by its position, so chunks can be generated in any order.
+    Terrain = 0x5EED_0001,
+    /// Placing the starting organisms
+    Organisms = 0x5EED_0002,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> GenerationConfig {
+        let mut terrain_weights: HashMap<Terrain, f32> = HashMap::new();
+        terrain_weights.insert(Terrain::Plain, GenerationConfig::TERRAIN_WEIGHT_PLAIN);
+        terrain_weights.insert(Terrain::Muddy, GenerationConfig::TERRAIN_WEIGHT_HIGH);
+        terrain_weights.insert(Terrain::Rocky, GenerationConfig::TERRAIN_WEIGHT_ROCKY);
+        terrain_weights.insert(Terrain::Water, GenerationConfig::TERRAIN_WEIGHT_WATER);
+
+        GenerationConfig {
+            map_radius: GenerationConfig::MAP_RADIUS,
+            n_ant: GenerationConfig::N_ANT,
+            n_plant: GenerationConfig::N_PLANT,
+            n_fungi: GenerationConfig::N_FUNGI,
+            n_hive: GenerationConfig::N_HIVE,
+            terrain_weights,
+            seed: GenerationConfig::SEED,
+            streaming_distance: None,
+            placed_units: Vec::new(),
+            placed_structures: Vec::new(),
+        }
+    }
+}
+
+/// The tile around which chunks of the map are generated, when streaming.
+///
+/// This is kept up to date with the camera by the interaction plugins, but can be set directly when running headlessly.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct GenerationFocus {
+    /// The tile to generate chunks around
+    pub tile_pos: TilePos,
+}
+
+/// Generate the world.
+pub(super) struct GenerationPlugin {
+    /// Configuration settings for world generation
+    pub(super) config: GenerationConfig,
+}
+
+impl Plugin for GenerationPlugin {
+    fn build(&self, app: &mut App) {
+        info!("Building Generation plugin...");
+        app.insert_resource(self.config.clone())
+            .insert_resource(MapGeometry::new(self.config.map_radius))
+            .init_resource::<GenerationFocus>()
+            .add_systems(
+                (generate_terrain, apply_system_buffers, generate_organisms)
+                    .chain()
+                    .in_schedule(CoreSchedule::Startup),
+            )
+            .add_system(stream_chunks);
+    }
+}
+
+/// The minimum height of any tile.
+///
+/// This should always be a multiple of 1.0;
+const MIN_HEIGHT: f32 = 1.0;
+/// Scale the pos to make it work better with the noise function
+const FREQUENCY_SCALE: f32 = 0.07;
+/// Scale the output of the noise function so you can more easily use the number for a height
+const AMPLITUDE_SCALE: f32 = 2.0;
+/// How many times will the fbm be sampled?
+const OCTAVES: usize = 4;
+/// Smoothing factor
+const LACUNARITY: f32 = 2.3;
+/// Scale the output of the fbm function
+const GAIN: f32 = 0.5;
+
+/// Chooses the type and height of the tile at `tile_pos`, according to `config`.
+///
+/// Each tile is generated independently of every other, so the map is identical no matter which order chunks are generated in.
+fn generate_tile(config: &GenerationConfig, tile_pos: TilePos) -> (Terrain, f32) {
+    let mut rng = config.tile_rng(tile_pos);
+
+    let terrain_variants = Terrain::variants().collect::<Vec<Terrain>>();
+    let terrain_weights = &config.terrain_weights;
+    let &terrain_type = terrain_variants
+        .choose_weighted(&mut rng, |terrain_type| {
+            terrain_weights.get(terrain_type).unwrap()
+        })
+        .unwrap();
+
+    let pos = vec2(tile_pos.x as f32, tile_pos.y as f32);
+    let hex_height = MIN_HEIGHT
+        + (fbm_simplex_2d_seeded(
+            pos * FREQUENCY_SCALE,
+            OCTAVES,
+            LACUNARITY,
+            GAIN,
+            config.noise_seed(),
+        ) * AMPLITUDE_SCALE)
+            .abs()
+            // Height is stepped, and should always be a multiple of 1.0
+            .round();
+
+    (terrain_type, hex_height)
+}
+
+/// Generates the terrain for every tile of `chunk_pos` that lies within the map.
+///
+/// Chunks that have already been generated are skipped.
+fn generate_chunk(
+    chunk_pos: ChunkPos,
+    commands: &mut Commands,
+    config: &GenerationConfig,
+    map_geometry: &mut MapGeometry,
+) {
+    if !map_geometry.generated_chunks.insert(chunk_pos) {
+        return;
+    }
+
+    for tile_pos in chunk_pos.tiles() {
+        if !map_geometry.is_valid(tile_pos) {
+            continue;
+        }
+
+        let (terrain_type, hex_height) = generate_tile(config, tile_pos);
+
+        // Store the height, so it can be used below
+        map_geometry.height_index.insert(tile_pos, hex_height);
+
+        if terrain_type == Terrain::Water {
+            map_geometry.open_water.insert(tile_pos);
+        }
+
+        // Spawn the terrain entity
+        let terrain_entity = commands
+            .spawn(TerrainBundle::new(terrain_type, tile_pos))
+            .id();
+
+        // Update the index of what terrain is where
+        map_geometry.terrain_index.insert(tile_pos, terrain_entity);
+    }
+}
+
+/// Creates the world according to [`GenerationConfig`].
+///
+/// When streaming, only the chunks around the [`GenerationFocus`] are created now, and the rest are left to [`stream_chunks`].
+pub fn generate_terrain(
+    mut commands: Commands,
+    config: Res<GenerationConfig>,
+    focus: Res<GenerationFocus>,
+    mut map_geometry: ResMut<MapGeometry>,
+) {
+    info!("Generating terrain with seed {}...", config.seed);
+
+    let chunks: Vec<ChunkPos> = match config.streaming_distance {
+        Some(distance) => ChunkPos::containing(focus.tile_pos)
+            .within(distance)
+            .filter(|&chunk_pos| map_geometry.contains_chunk(chunk_pos))
+            .collect(),
+        None => map_geometry.chunks().collect(),
+    };
+
+    for chunk_pos in chunks {
+        generate_chunk(chunk_pos, &mut commands, &config, &mut map_geometry);
+    }
+}
+
+/// Generates the chunks that come within [`GenerationConfig::with_streaming`] chunks of the [`GenerationFocus`].
+///
+/// Only a few chunks are generated each frame, to avoid stutters when the focus moves quickly.
+fn stream_chunks(
+    mut commands: Commands,
+    config: Res<GenerationConfig>,
+    focus: Res<GenerationFocus>,
+    mut map_geometry: ResMut<MapGeometry>,
+) {
+    /// The most chunks that are generated in a single frame.
+    const MAX_CHUNKS_PER_FRAME: usize = 2;
+
+    let distance = match config.streaming_distance {
+        Some(distance) => distance,
+        None => return,
+    };
+
+    let missing_chunks: Vec<ChunkPos> = ChunkPos::containing(focus.tile_pos)
+        .within(distance)
+        .filter(|&chunk_pos| {
+            !map_geometry.generated_chunks.contains(&chunk_pos)
+                && map_geometry.contains_chunk(chunk_pos)
+        })
+        .take(MAX_CHUNKS_PER_FRAME)
+        .collect();
+
+    for chunk_pos in missing_chunks {
+        generate_chunk(chunk_pos, &mut commands, &config, &mut map_geometry);
+    }
+}
+
+/// Create starting organisms according to [`GenerationConfig`], and randomly place them on
+/// passable tiles.
+fn generate_organisms(
+    mut commands: Commands,
+    config: Res<GenerationConfig>,
+    tile_query: Query<&TilePos, With<Terrain>>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+) {
+    info!("Generating organisms...");
+    let n_ant = config.n_ant;
+    let n_plant = config.n_plant;
+    let n_fungi = config.n_fungi;
+    let n_hive = config.n_hive;
+
+    let n_entities = n_ant + n_plant + n_fungi + n_hive;
+
+    let rng = &mut config.rng(GenerationStage::Organisms);
+    let mut entity_positions: Vec<TilePos> = {
+        let mut possible_positions: Vec<TilePos> = tile_query
+            .iter()
+            .copied()
+            .filter(|tile_pos| map_geometry.is_passable(*tile_pos))
+            .filter(|tile_pos| {
+                !config
+                    .placed_structures
+                    .iter()
+                    .any(|(_, placed_pos)| placed_pos == tile_pos)
+            })
+            .collect();
+        assert!(n_entities <= possible_positions.len());
+        // Query order is not guaranteed, so sort the candidates to keep placement reproducible
+        possible_positions.sort_by_key(|tile_pos| (tile_pos.x, tile_pos.y));
+
+        possible_positions
+            .choose_multiple(rng, n_entities)
+            .cloned()
+            .collect()
+    };
+
+    // Ant
+    let ant_positions = entity_positions.split_off(entity_positions.len() - n_ant);
+    for ant_position in ant_positions {
+        commands.spawn(UnitBundle::new(
+            Id::ant(),
+            ant_position,
+            unit_manifest.get(Id::from_string_id("ant")).clone(),
+        ));
+    }
+
+    // Plant
+    let plant_positions = entity_positions.split_off(entity_positions.len() - n_plant);
+    for position in plant_positions {
+        let structure_id = Id::from_string_id("acacia");
+
+        let item = ClipboardData {
+            structure_id,
+            facing: Facing::default(),
+            active_recipe: structure_manifest
+                .get(structure_id)
+                .starting_recipe()
+                .clone(),
+        };
+
+        commands.spawn_randomized_structure(position, item, rng);
+    }
+
+    // Fungi
+    let fungus_positions = entity_positions.split_off(entity_positions.len() - n_fungi);
+    for position in fungus_positions {
+        let structure_id = Id::from_string_id("leuco");
+
+        let item = ClipboardData {
+            structure_id,
+            facing: Facing::default(),
+            active_recipe: structure_manifest
+                .get(structure_id)
+                .starting_recipe()
+                .clone(),
+        };
+
+        commands.spawn_randomized_structure(position, item, rng);
+    }
+
+    // Hives
+    let hive_positions = entity_positions.split_off(entity_positions.len() - n_hive);
+    for position in hive_positions {
+        let structure_id = Id::from_string_id("ant_hive");
+
+        let item = ClipboardData {
+            structure_id,
+            facing: Facing::default(),
+            active_recipe: structure_manifest
+                .get(structure_id)
+                .starting_recipe()
+                .clone(),
+        };
+
+        commands.spawn_randomized_structure(position, item, rng);
+    }
+
+    // Fixed placements
+    for &(unit_id, tile_pos) in &config.placed_units {
+        commands.spawn(UnitBundle::new(
+            unit_id,
+            tile_pos,
+            unit_manifest.get(unit_id).clone(),
+        ));
+    }
+
+    for &(structure_id, tile_pos) in &config.placed_structures {
+        let item = ClipboardData {
+            structure_id,
+            facing: Facing::default(),
+            active_recipe: structure_manifest
+                .get(structure_id)
+                .starting_recipe()
+                .clone(),
+        };
+
+        commands.spawn_structure(tile_pos, item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexx::{shapes::hexagon, Hex};
+
+    /// Generates every tile within `radius` of the origin.
+    fn generate_tiles(config: &GenerationConfig, radius: u32) -> Vec<(Terrain, f32)> {
+        hexagon(Hex::ZERO, radius)
+            .map(|hex| generate_tile(config, TilePos { hex }))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_generates_same_tiles() {
+        let config = GenerationConfig::default().with_seed(42);
+
+        assert_eq!(generate_tiles(&config, 10), generate_tiles(&config, 10));
+    }
+
+    #[test]
+    fn different_seeds_generate_different_tiles() {
+        let first = GenerationConfig::default().with_seed(1);
+        let second = GenerationConfig::default().with_seed(2);
+
+        assert_ne!(generate_tiles(&first, 10), generate_tiles(&second, 10));
+    }
+
+    #[test]
+    fn terrain_fill_covers_every_tile() {
+        let config = GenerationConfig::default().with_terrain_fill(Terrain::Muddy);
+
+        assert!(generate_tiles(&config, 10)
+            .iter()
+            .all(|&(terrain, _height)| terrain == Terrain::Muddy));
+    }
+
+    #[test]
+    fn neighboring_tiles_are_generated_independently() {
+        let config = GenerationConfig::default();
+        let n_water = hexagon(Hex::ZERO, 20)
+            .filter(|&hex| generate_tile(&config, TilePos { hex }).0 == Terrain::Water)
+            .count();
+
+        // Roughly matches the default weight of water, rather than every tile getting the same result
+        assert!(n_water > 0);
+        assert!(n_water < 400);
+    }
+}
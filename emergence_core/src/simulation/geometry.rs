@@ -0,0 +1,663 @@
+//! Manages the game world's grid and data tied to that grid
+
+use crate::bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use core::fmt::Display;
+use derive_more::{Add, AddAssign, Display, Sub, SubAssign};
+use hexx::{shapes::hexagon, Direction, Hex, HexLayout};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::structures::{express::ExpressLink, walls::Wall};
+
+/// A hex-based coordinate, that represents exactly one tile.
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Deref,
+    DerefMut,
+    Default,
+    Add,
+    Sub,
+    AddAssign,
+    SubAssign,
+    Serialize,
+    Deserialize,
+)]
+#[serde(transparent)]
+pub struct TilePos {
+    /// The underlying hex coordinate
+    pub hex: Hex,
+}
+
+impl Display for TilePos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubic = self.to_cubic_array();
+
+        let x = cubic[0];
+        let y = cubic[1];
+        let z = cubic[2];
+
+        write!(f, "({x}, {y}, {z})")
+    }
+}
+
+impl From<(i32, i32)> for TilePos {
+    /// Creates a [`TilePos`] from axial coordinates, as in [`TilePos::new`].
+    fn from((x, y): (i32, i32)) -> Self {
+        TilePos::new(x, y)
+    }
+}
+
+impl TilePos {
+    /// The position of the central tile
+    pub const ORIGIN: TilePos = TilePos {
+        hex: Hex { x: 0, y: 0 },
+    };
+
+    /// Generates a new [`TilePos`] from axial coordinates.
+    #[inline]
+    pub fn new(x: i32, y: i32) -> Self {
+        TilePos { hex: Hex { x, y } }
+    }
+
+    /// Generates a random [`TilePos`], sampled uniformly from the valid positions in `map_geometry`
+    #[inline]
+    pub fn random(map_geometry: &MapGeometry, rng: &mut impl Rng) -> TilePos {
+        let range = -(map_geometry.radius as i32)..(map_geometry.radius as i32);
+
+        // Just use rejection sampling: easy to get right
+        let mut chosen_tile: Option<TilePos> = None;
+        while chosen_tile.is_none() {
+            let x = rng.gen_range(range.clone());
+            let y = rng.gen_range(range.clone());
+
+            let proposed_tile = TilePos::new(x, y);
+
+            if map_geometry.is_valid(proposed_tile) {
+                chosen_tile = Some(proposed_tile)
+            }
+        }
+
+        chosen_tile.unwrap()
+    }
+
+    /// Returns the world position (in `Transform` units) associated with this tile.
+    ///
+    /// The `y` value returned corresponds to the top of the tile column at this location.
+    #[must_use]
+    pub fn into_world_pos(self, map_geometry: &MapGeometry) -> Vec3 {
+        let xz = map_geometry.layout.hex_to_world_pos(self.hex);
+        let y = *map_geometry.height_index.get(&self).unwrap();
+
+        Vec3 {
+            x: xz.x,
+            y,
+            z: xz.y,
+        }
+    }
+
+    /// Returns the nearest tile position to the provided `world_pos`
+    ///
+    /// `world_pos` generally corresponds to the `translation` of a `Transform`.
+    #[must_use]
+    pub fn from_world_pos(world_pos: Vec3, map_geometry: &MapGeometry) -> Self {
+        TilePos {
+            hex: map_geometry.layout.world_pos_to_hex(Vec2 {
+                x: world_pos.x,
+                y: world_pos.z,
+            }),
+        }
+    }
+
+    /// Returns the [`TilePos`] in the provided `direction` from `self`.
+    pub fn neighbor(&self, direction: Direction) -> Self {
+        TilePos {
+            hex: self.hex.neighbor(direction),
+        }
+    }
+
+    /// All adjacent tiles that are on the map.
+    pub fn all_neighbors(&self, map_geometry: &MapGeometry) -> impl IntoIterator<Item = TilePos> {
+        // PERF: this can be done without any allocations
+        let all_hexes = self.hex.all_neighbors();
+        let mut neighbors = Vec::new();
+
+        for &hex in all_hexes.iter() {
+            let tile_pos = TilePos { hex };
+            if map_geometry.is_valid(tile_pos) {
+                neighbors.push(tile_pos);
+            }
+        }
+
+        neighbors
+    }
+
+    /// All adjacent tiles that a unit standing on this tile could walk to.
+    ///
+    /// See [`MapGeometry::can_step`] for the rules used.
+    pub fn reachable_neighbors(
+        &self,
+        map_geometry: &MapGeometry,
+    ) -> impl IntoIterator<Item = TilePos> {
+        let neighbors = self.all_neighbors(map_geometry);
+        // PERF: this can be done without allocations
+        let reachable_neighbors: Vec<TilePos> = neighbors
+            .into_iter()
+            .filter(|&tile_pos| map_geometry.can_step(*self, tile_pos))
+            .collect();
+
+        reachable_neighbors
+    }
+
+    /// All adjacent tiles that are on the map and free of structures.
+    pub fn empty_neighbors(&self, map_geometry: &MapGeometry) -> impl IntoIterator<Item = TilePos> {
+        let neighbors = self.all_neighbors(map_geometry);
+        // PERF: this can be done without allocations
+        let empty_neighbors: Vec<TilePos> = neighbors
+            .into_iter()
+            .filter(|tile_pos| !map_geometry.structure_index.contains_key(tile_pos))
+            .collect();
+
+        empty_neighbors
+    }
+}
+
+/// A block of tiles that are generated together, so large maps can be created a piece at a time.
+///
+/// Each chunk covers a [`ChunkPos::SIZE`] by [`ChunkPos::SIZE`] parallelogram of tiles in axial coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    /// The coordinate of the chunk, in units of chunks
+    hex: Hex,
+}
+
+impl ChunkPos {
+    /// The number of tiles along each axis of a chunk.
+    pub const SIZE: i32 = 16;
+
+    /// The chunk that contains `tile_pos`.
+    pub fn containing(tile_pos: TilePos) -> Self {
+        ChunkPos {
+            hex: Hex::new(
+                tile_pos.x.div_euclid(ChunkPos::SIZE),
+                tile_pos.y.div_euclid(ChunkPos::SIZE),
+            ),
+        }
+    }
+
+    /// Every tile in this chunk, whether or not it is part of the map.
+    pub fn tiles(&self) -> impl Iterator<Item = TilePos> {
+        let origin = self.hex * ChunkPos::SIZE;
+
+        (0..ChunkPos::SIZE).flat_map(move |y| {
+            (0..ChunkPos::SIZE).map(move |x| TilePos::new(origin.x + x, origin.y + y))
+        })
+    }
+
+    /// Every chunk within `distance` chunks of this one, including itself.
+    pub fn within(&self, distance: u32) -> impl Iterator<Item = ChunkPos> {
+        hexagon(self.hex, distance).map(|hex| ChunkPos { hex })
+    }
+}
+
+/// The largest difference in height that units can climb or descend between adjacent tiles.
+///
+/// Larger changes in height are cliffs, and can only be traversed using a structure with a [`Crossing`].
+pub const MAX_STEP_HEIGHT: f32 = 1.0;
+
+/// The additional fraction of time it takes to walk up each unit of height.
+///
+/// Walking downhill or across flat ground has no penalty.
+pub const UPHILL_WALKING_PENALTY: f32 = 0.5;
+
+/// The fraction of signal strength lost for each unit of height that a signal must diffuse uphill.
+///
+/// Signals pool in valleys and are slow to climb out of them.
+pub const UPHILL_SIGNAL_LOSS: f32 = 0.5;
+
+/// Describes how units move across a structure that they can walk on, such as a bridge or ramp.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Crossing {
+    /// The walking speed multiplier for units on this structure.
+    ///
+    /// This replaces the walking speed of the underlying terrain.
+    pub walking_speed: f32,
+    /// Can units use this structure to climb up or down cliffs?
+    pub climbs_cliffs: bool,
+}
+
+/// The overall size and arrangement of the map.
+#[derive(Debug, Resource)]
+pub struct MapGeometry {
+    /// The size and orientation of the map.
+    pub layout: HexLayout,
+    /// The number of tiles from the center to the edge of the map.
+    ///
+    /// Note that the central tile is not counted.
+    pub radius: u32,
+    /// Which [`Terrain`](crate::terrain::Terrain) entity is stored at each tile position
+    pub terrain_index: HashMap<TilePos, Entity>,
+    /// Which [`Id<Structure>`](crate::manifest::Id) entity is stored at each tile position
+    pub structure_index: HashMap<TilePos, Entity>,
+    /// Which [`Ghost`](crate::structures::construction::Ghost) entity is stored at each tile position
+    pub ghost_index: HashMap<TilePos, Entity>,
+    /// Which [`Preview`](crate::structures::construction::Preview) entity is stored at each tile position
+    pub preview_index: HashMap<TilePos, Entity>,
+    /// Which [`Remains`](crate::units::lifecycle::Remains) entity is stored at each tile position
+    pub remains_index: HashMap<TilePos, Entity>,
+    /// The height of the terrain at each tile position
+    pub height_index: HashMap<TilePos, f32>,
+    /// Which tiles contain structures that units can walk across, and how
+    ///
+    /// This must be kept in sync with the `structure_index`.
+    pub crossing_index: HashMap<TilePos, Crossing>,
+    /// Which tiles contain walls, and how they affect signals
+    ///
+    /// This must be kept in sync with the `structure_index`.
+    pub wall_index: HashMap<TilePos, Wall>,
+    /// Which tiles are linked to distant tiles by express routes, such as tunnels
+    ///
+    /// This must be kept in sync with the `structure_index`.
+    pub express_index: HashMap<TilePos, ExpressLink>,
+    /// Which tiles are covered by unfrozen water, and so cannot be walked across
+    pub open_water: HashSet<TilePos>,
+    /// Which chunks of the map have had their terrain generated
+    pub generated_chunks: HashSet<ChunkPos>,
+}
+
+impl MapGeometry {
+    /// Creates a new [`MapGeometry`] of the provided raidus.
+    ///
+    /// All indexes will be empty.
+    pub fn new(radius: u32) -> Self {
+        MapGeometry {
+            layout: HexLayout::default(),
+            radius,
+            terrain_index: HashMap::default(),
+            structure_index: HashMap::default(),
+            ghost_index: HashMap::default(),
+            preview_index: HashMap::default(),
+            remains_index: HashMap::default(),
+            height_index: HashMap::default(),
+            crossing_index: HashMap::default(),
+            wall_index: HashMap::default(),
+            express_index: HashMap::default(),
+            open_water: HashSet::default(),
+            generated_chunks: HashSet::default(),
+        }
+    }
+
+    /// Does any tile of `chunk_pos` lie within the map?
+    pub fn contains_chunk(&self, chunk_pos: ChunkPos) -> bool {
+        chunk_pos.tiles().any(|tile_pos| self.is_valid(tile_pos))
+    }
+
+    /// Every chunk that overlaps the map.
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        let radius = self.radius as i32;
+        let min = (-radius).div_euclid(ChunkPos::SIZE);
+        let max = radius.div_euclid(ChunkPos::SIZE);
+
+        (min..=max)
+            .flat_map(move |y| {
+                (min..=max).map(move |x| ChunkPos {
+                    hex: Hex::new(x, y),
+                })
+            })
+            .filter(|&chunk_pos| self.contains_chunk(chunk_pos))
+    }
+
+    /// The distance in world units from the center of the map to the center of its furthest tiles.
+    pub fn world_radius(&self) -> f32 {
+        self.layout
+            .hex_to_world_pos(Hex::new(self.radius as i32, 0))
+            .length()
+    }
+
+    /// Is the provided `tile_pos` in the map?
+    pub fn is_valid(&self, tile_pos: TilePos) -> bool {
+        let distance = Hex::ZERO.distance_to(tile_pos.hex);
+        distance <= self.radius as i32
+    }
+
+    /// Is the provided `tile_pos` passable?
+    ///
+    /// Tiles that are not part of the map will return `false`.
+    /// Tiles with structures or open water are only passable if they contain a `Crossing`.
+    pub fn is_passable(&self, tile_pos: TilePos) -> bool {
+        self.is_valid(tile_pos)
+            && (self.crossing_index.contains_key(&tile_pos)
+                || (!self.structure_index.contains_key(&tile_pos)
+                    && !self.open_water.contains(&tile_pos)))
+    }
+
+    /// Can a unit walk directly from `origin` to the adjacent tile `target`?
+    ///
+    /// The `target` must be passable, and any change in height must be no larger than [`MAX_STEP_HEIGHT`],
+    /// unless either tile contains a [`Crossing`] that climbs cliffs.
+    pub fn can_step(&self, origin: TilePos, target: TilePos) -> bool {
+        if !self.is_passable(target) {
+            return false;
+        }
+
+        if self.height_difference(origin, target).abs() <= MAX_STEP_HEIGHT {
+            return true;
+        }
+
+        [origin, target].iter().any(|tile_pos| {
+            self.crossing_index
+                .get(tile_pos)
+                .map(|crossing| crossing.climbs_cliffs)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Sets the height of the terrain at `tile_pos`.
+    ///
+    /// Only the index is updated: any terrain entity at this position is left where it is.
+    pub fn set_height(&mut self, tile_pos: TilePos, height: f32) {
+        self.height_index.insert(tile_pos, height);
+    }
+
+    /// How much higher `target` is than `origin`.
+    ///
+    /// This is negative when `target` is lower than `origin`.
+    pub fn height_difference(&self, origin: TilePos, target: TilePos) -> f32 {
+        let origin_height = self.height_index.get(&origin).copied().unwrap_or_default();
+        let target_height = self.height_index.get(&target).copied().unwrap_or_default();
+
+        target_height - origin_height
+    }
+
+    /// The factor by which walking from `origin` to the adjacent tile `target` is slowed by the slope between them.
+    ///
+    /// This is always at least 1: only climbing is penalized, based on `UPHILL_WALKING_PENALTY`.
+    pub fn slope_cost(&self, origin: TilePos, target: TilePos) -> f32 {
+        let rise = self.height_difference(origin, target).max(0.);
+
+        1. + rise * UPHILL_WALKING_PENALTY
+    }
+
+    /// The fraction of signal strength that survives diffusing from `origin` to the adjacent tile `target`.
+    ///
+    /// Signals flow freely between tiles that units can walk between, but lose some strength when climbing, based on [`UPHILL_SIGNAL_LOSS`].
+    /// They also seep through walls based on their [`Wall::signal_opacity`].
+    pub fn signal_transmission(&self, origin: TilePos, target: TilePos) -> f32 {
+        if self.can_step(origin, target) {
+            let rise = self.height_difference(origin, target).max(0.);
+            (1. - rise * UPHILL_SIGNAL_LOSS).max(0.)
+        } else if let Some(wall) = self.wall_index.get(&target) {
+            1. - wall.signal_opacity
+        } else {
+            0.
+        }
+    }
+
+    /// Returns the average height of tiles around `tile_pos` within `radius`
+    pub fn average_height(&self, tile_pos: TilePos, radius: u32) -> f32 {
+        let hex_iter = hexagon(tile_pos.hex, radius);
+        let heights = hex_iter.map(|hex| *self.height_index.get(&TilePos { hex }).unwrap_or(&0.));
+        let n = Hex::range_count(radius);
+        heights.sum::<f32>() / n as f32
+    }
+
+    /// Returns the fraction of light that reaches the tile at `tile_pos`, from 0 to 1.
+    ///
+    /// Tiles that sit below their neighbors are shaded by them.
+    pub fn light_level(&self, tile_pos: TilePos) -> f32 {
+        /// The fraction of light blocked by each unit of height that a neighbor rises above this tile
+        const SHADING_PER_HEIGHT: f32 = 0.1;
+
+        let height = match self.height_index.get(&tile_pos) {
+            Some(&height) => height,
+            None => return 0.,
+        };
+
+        let shading: f32 = tile_pos
+            .all_neighbors(self)
+            .into_iter()
+            .map(|neighbor| {
+                let neighbor_height = *self.height_index.get(&neighbor).unwrap_or(&height);
+                (neighbor_height - height).max(0.) * SHADING_PER_HEIGHT
+            })
+            .sum();
+
+        (1. - shading).clamp(0., 1.)
+    }
+
+    /// Gets the ghost or structure [`Entity`] at the provided `tile_pos`, if any.
+    ///
+    /// Ghosts will take priority over structures.
+    pub fn get_ghost_or_structure(&self, tile_pos: TilePos) -> Option<Entity> {
+        if let Some(&ghost_entity) = self.ghost_index.get(&tile_pos) {
+            Some(ghost_entity)
+        } else if let Some(&structure_entity) = self.structure_index.get(&tile_pos) {
+            Some(structure_entity)
+        } else {
+            None
+        }
+    }
+}
+
+/// The hex direction that this entity is facing.
+///
+/// Stored as a component on each entity with a grid-aligned rotation.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct Facing {
+    /// The desired direction.
+    ///
+    /// Defaults to [`Direction::Top`].
+    pub direction: Direction,
+}
+
+impl Facing {
+    /// Rotates this facing one 60 degree step clockwise.
+    pub fn rotate_left(&mut self) {
+        self.direction = self.direction.left();
+    }
+
+    /// Rotates this facing one 60 degree step counterclockwise.
+    pub fn rotate_right(&mut self) {
+        self.direction = self.direction.right();
+    }
+}
+
+impl Default for Facing {
+    fn default() -> Self {
+        Facing {
+            direction: Direction::Top,
+        }
+    }
+}
+
+impl Display for Facing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self.direction {
+            Direction::TopRight => "Top-right",
+            Direction::Top => "Top",
+            Direction::TopLeft => "Top-left",
+            Direction::BottomLeft => "Bottom-left",
+            Direction::Bottom => "Bottom",
+            Direction::BottomRight => "Bottom-right",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The direction of a [`Facing`] rotation
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display)]
+pub enum RotationDirection {
+    /// Counterclockwise
+    Left,
+    /// Clockwise
+    Right,
+}
+
+impl RotationDirection {
+    /// Picks a direction to rotate in at random
+    pub fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen::<bool>() {
+            true => RotationDirection::Left,
+            false => RotationDirection::Right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Generates tile positions in a region much larger than any real map.
+    fn tile_pos() -> impl Strategy<Value = TilePos> {
+        (-100..100i32, -100..100i32).prop_map(|(x, y)| TilePos::new(x, y))
+    }
+
+    /// Generates any of the six hex directions.
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop::sample::select(Direction::ALL_DIRECTIONS.to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn distance_is_symmetric(a in tile_pos(), b in tile_pos()) {
+            prop_assert_eq!(a.unsigned_distance_to(b.hex), b.unsigned_distance_to(a.hex));
+        }
+
+        #[test]
+        fn distance_is_zero_only_to_self(a in tile_pos(), b in tile_pos()) {
+            prop_assert_eq!(a.unsigned_distance_to(b.hex) == 0, a == b);
+        }
+
+        #[test]
+        fn distance_obeys_triangle_inequality(a in tile_pos(), b in tile_pos(), c in tile_pos()) {
+            prop_assert!(
+                a.unsigned_distance_to(c.hex)
+                    <= a.unsigned_distance_to(b.hex) + b.unsigned_distance_to(c.hex)
+            );
+        }
+
+        #[test]
+        fn neighbors_are_reciprocal(a in tile_pos(), direction in direction()) {
+            let neighbor = a.neighbor(direction);
+
+            prop_assert_eq!(a.unsigned_distance_to(neighbor.hex), 1);
+            prop_assert!(neighbor.hex.all_neighbors().contains(&a.hex));
+            prop_assert_eq!(a.direction_to(neighbor.hex), direction);
+        }
+
+        #[test]
+        fn neighbors_on_the_map_are_on_the_map(a in tile_pos(), radius in 0..50u32) {
+            let map_geometry = MapGeometry::new(radius);
+
+            for neighbor in a.all_neighbors(&map_geometry) {
+                prop_assert!(map_geometry.is_valid(neighbor));
+            }
+        }
+
+        #[test]
+        fn rings_have_six_tiles_per_step(a in tile_pos(), radius in 1..20u32) {
+            let ring = a.ring(radius);
+
+            prop_assert_eq!(ring.len(), 6 * radius as usize);
+        }
+
+        #[test]
+        fn hexagons_match_range_count(a in tile_pos(), radius in 0..20u32) {
+            let tile_count = hexagon(a.hex, radius).count();
+
+            prop_assert_eq!(tile_count, Hex::range_count(radius));
+        }
+
+        #[test]
+        fn lines_connect_their_endpoints(a in tile_pos(), b in tile_pos()) {
+            let line: Vec<Hex> = a.line_to(b.hex).collect();
+
+            prop_assert_eq!(line.first(), Some(&a.hex));
+            prop_assert_eq!(line.last(), Some(&b.hex));
+            prop_assert_eq!(line.len() as u32, a.unsigned_distance_to(b.hex) + 1);
+
+            for pair in line.windows(2) {
+                prop_assert_eq!(pair[0].unsigned_distance_to(pair[1]), 1);
+            }
+        }
+
+        #[test]
+        fn six_rotations_are_the_identity(direction in direction()) {
+            let mut facing = Facing { direction };
+            for _ in 0..6 {
+                facing.rotate_left();
+            }
+            prop_assert_eq!(facing.direction, direction);
+
+            for _ in 0..6 {
+                facing.rotate_right();
+            }
+            prop_assert_eq!(facing.direction, direction);
+        }
+
+        #[test]
+        fn opposite_rotations_cancel(direction in direction()) {
+            let mut facing = Facing { direction };
+            facing.rotate_left();
+            facing.rotate_right();
+            prop_assert_eq!(facing.direction, direction);
+        }
+
+        #[test]
+        fn world_positions_round_trip(a in tile_pos()) {
+            let mut map_geometry = MapGeometry::new(200);
+            map_geometry.height_index.insert(a, 0.);
+
+            let world_pos = a.into_world_pos(&map_geometry);
+            prop_assert_eq!(TilePos::from_world_pos(world_pos, &map_geometry), a);
+        }
+
+        #[test]
+        fn tiles_belong_to_their_chunk(a in tile_pos()) {
+            let chunk_pos = ChunkPos::containing(a);
+            prop_assert!(chunk_pos.tiles().any(|tile_pos| tile_pos == a));
+        }
+
+        #[test]
+        fn map_chunks_cover_the_map((radius, x, y) in (0..50i32).prop_flat_map(|radius| (Just(radius), -radius..=radius, -radius..=radius))) {
+            let map_geometry = MapGeometry::new(radius as u32);
+            let a = TilePos::new(x, y);
+            prop_assume!(map_geometry.is_valid(a));
+
+            let chunk_pos = ChunkPos::containing(a);
+            prop_assert!(map_geometry.chunks().any(|candidate| candidate == chunk_pos));
+        }
+
+        #[test]
+        fn only_climbing_slows_walking(a in tile_pos(), direction in direction(), rise in 0. ..MAX_STEP_HEIGHT) {
+            let b = a.neighbor(direction);
+            let mut map_geometry = MapGeometry::new(200);
+            map_geometry.height_index.insert(a, 0.);
+            map_geometry.height_index.insert(b, rise);
+
+            prop_assert!(map_geometry.slope_cost(a, b) >= 1.);
+            prop_assert_eq!(map_geometry.slope_cost(b, a), 1.);
+        }
+
+        #[test]
+        fn signals_climb_less_than_they_descend(a in tile_pos(), direction in direction(), rise in 0. ..MAX_STEP_HEIGHT) {
+            let b = a.neighbor(direction);
+            let mut map_geometry = MapGeometry::new(200);
+            map_geometry.height_index.insert(a, 0.);
+            map_geometry.height_index.insert(b, rise);
+
+            prop_assert!(map_geometry.signal_transmission(a, b) <= map_geometry.signal_transmission(b, a));
+            prop_assert_eq!(map_geometry.signal_transmission(b, a), 1.);
+        }
+    }
+}
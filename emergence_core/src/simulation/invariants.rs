@@ -0,0 +1,337 @@
+//! Runtime checks that the simulation is internally consistent, used for soak testing.
+//!
+//! These checks are too expensive to run during normal play,
+//! so [`InvariantsPlugin`] must be added explicitly.
+//! When a check fails, the state of the world is dumped to disk and the app panics.
+
+use crate::bevy::{app::AppExit, prelude::*};
+use core::fmt::Display;
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    manifest::{Id, Item, Structure, Unit},
+    signals::{SignalType, Signals},
+    structures::{
+        construction::{Ghost, Preview},
+        crafting::{InputInventory, OutputInventory},
+    },
+};
+
+use super::{
+    geometry::{MapGeometry, TilePos},
+    snapshot::SnapshotQuery,
+    time::SimulationTick,
+};
+
+/// Controls the thresholds used when checking invariants.
+///
+/// Insert this resource before adding the [`InvariantsPlugin`] to customize it.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct InvariantConfig {
+    /// The largest total strength that any one type of signal may reach across the whole map.
+    pub max_signal_total: f32,
+    /// The folder that the state of the world is dumped to when an invariant is violated.
+    pub dump_folder: PathBuf,
+}
+
+impl Default for InvariantConfig {
+    fn default() -> Self {
+        InvariantConfig {
+            max_signal_total: 1e6,
+            dump_folder: PathBuf::from("soak_failures"),
+        }
+    }
+}
+
+/// Runs the app for a fixed length of simulated time, then exits.
+///
+/// Insert this resource alongside the [`InvariantsPlugin`] to run a soak test.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct SoakTest {
+    /// How much simulated time must pass before the test succeeds.
+    pub duration: Duration,
+}
+
+/// A way in which the simulation has become inconsistent.
+#[derive(Debug, Clone, PartialEq)]
+enum InvariantViolation {
+    /// A signal strength is NaN, infinite or negative.
+    InvalidSignal {
+        /// The type of the signal
+        signal_type: SignalType,
+        /// Where the signal is
+        tile_pos: TilePos,
+        /// The invalid strength
+        strength: f32,
+    },
+    /// The total strength of a type of signal is larger than [`InvariantConfig::max_signal_total`].
+    UnboundedSignal {
+        /// The type of the signal
+        signal_type: SignalType,
+        /// The total strength across the map
+        total: f32,
+    },
+    /// An inventory slot holds more items than it can.
+    OverfullSlot {
+        /// The entity that owns the inventory
+        entity: Entity,
+        /// The item stored in the slot
+        item_id: Id<Item>,
+        /// The number of items in the slot
+        count: usize,
+        /// The maximum number of items that the slot can hold
+        max_item_count: usize,
+    },
+    /// The structure index refers to an entity that is not a structure on that tile.
+    StaleStructureIndex {
+        /// The indexed tile
+        tile_pos: TilePos,
+        /// The entity stored in the index
+        entity: Entity,
+    },
+    /// A structure is missing from the structure index.
+    UnindexedStructure {
+        /// The structure
+        entity: Entity,
+        /// Where the structure is
+        tile_pos: TilePos,
+    },
+    /// A unit is standing outside of the map.
+    UnitOffMap {
+        /// The unit
+        entity: Entity,
+        /// Where the unit is
+        tile_pos: TilePos,
+    },
+}
+
+impl Display for InvariantViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvariantViolation::InvalidSignal {
+                signal_type,
+                tile_pos,
+                strength,
+            } => write!(f, "Signal {signal_type} at {tile_pos} has invalid strength {strength}"),
+            InvariantViolation::UnboundedSignal { signal_type, total } => {
+                write!(f, "Signal {signal_type} has grown to a total of {total}")
+            }
+            InvariantViolation::OverfullSlot {
+                entity,
+                item_id,
+                count,
+                max_item_count,
+            } => write!(
+                f,
+                "{entity:?} holds {count} of {item_id}, but only has room for {max_item_count}"
+            ),
+            InvariantViolation::StaleStructureIndex { tile_pos, entity } => write!(
+                f,
+                "The structure index stores {entity:?} at {tile_pos}, but it is not a structure there"
+            ),
+            InvariantViolation::UnindexedStructure { entity, tile_pos } => {
+                write!(f, "Structure {entity:?} at {tile_pos} is missing from the structure index")
+            }
+            InvariantViolation::UnitOffMap { entity, tile_pos } => {
+                write!(f, "Unit {entity:?} is off the map at {tile_pos}")
+            }
+        }
+    }
+}
+
+/// Checks that every signal strength is a non-negative number, and that no signal has grown without bound.
+fn check_signals(signals: &Signals, max_signal_total: f32) -> Vec<InvariantViolation> {
+    let mut violations = Vec::new();
+
+    for (signal_type, tile_pos, strength) in signals.iter_strengths() {
+        let strength = strength.value();
+        if !strength.is_finite() || strength < 0. {
+            violations.push(InvariantViolation::InvalidSignal {
+                signal_type,
+                tile_pos,
+                strength,
+            });
+        }
+    }
+
+    for (signal_type, total) in signals.total_strengths() {
+        let total = total.value();
+        if total > max_signal_total {
+            violations.push(InvariantViolation::UnboundedSignal { signal_type, total });
+        }
+    }
+
+    violations
+}
+
+/// Checks all invariants, dumping the state of the world and panicking if any are violated.
+#[allow(clippy::too_many_arguments)]
+fn check_invariants(
+    config: Res<InvariantConfig>,
+    simulation_tick: Res<SimulationTick>,
+    signals: Res<Signals>,
+    map_geometry: Res<MapGeometry>,
+    input_inventory_query: Query<(Entity, &InputInventory)>,
+    output_inventory_query: Query<(Entity, &OutputInventory)>,
+    structure_query: Query<
+        (Entity, &TilePos),
+        (With<Id<Structure>>, Without<Ghost>, Without<Preview>),
+    >,
+    unit_query: Query<(Entity, &TilePos), With<Id<Unit>>>,
+    snapshot_query: SnapshotQuery,
+) {
+    let mut violations = check_signals(&signals, config.max_signal_total);
+
+    let inventories = input_inventory_query
+        .iter()
+        .map(|(entity, input)| (entity, &input.inventory))
+        .chain(
+            output_inventory_query
+                .iter()
+                .map(|(entity, output)| (entity, &output.inventory)),
+        );
+    for (entity, inventory) in inventories {
+        for slot in inventory.iter() {
+            if slot.count() > slot.max_item_count() {
+                violations.push(InvariantViolation::OverfullSlot {
+                    entity,
+                    item_id: slot.item_id(),
+                    count: slot.count(),
+                    max_item_count: slot.max_item_count(),
+                });
+            }
+        }
+    }
+
+    for (&tile_pos, &entity) in map_geometry.structure_index.iter() {
+        match structure_query.get(entity) {
+            Ok((_, &structure_pos)) if structure_pos == tile_pos => (),
+            _ => violations.push(InvariantViolation::StaleStructureIndex { tile_pos, entity }),
+        }
+    }
+
+    for (entity, &tile_pos) in structure_query.iter() {
+        if map_geometry.structure_index.get(&tile_pos) != Some(&entity) {
+            violations.push(InvariantViolation::UnindexedStructure { entity, tile_pos });
+        }
+    }
+
+    for (entity, &tile_pos) in unit_query.iter() {
+        if !map_geometry.is_valid(tile_pos) {
+            violations.push(InvariantViolation::UnitOffMap { entity, tile_pos });
+        }
+    }
+
+    if violations.is_empty() {
+        return;
+    }
+
+    let tick = simulation_tick.get();
+    let mut dump = format!(
+        "{} invariant(s) violated on tick {tick}:\n",
+        violations.len()
+    );
+    for violation in &violations {
+        error!("Invariant violated: {violation}");
+        dump += &format!("{violation}\n");
+    }
+    dump += "\n";
+    dump += &snapshot_query.capture(tick).to_string();
+
+    // The app is about to abort, so write the dump immediately rather than on a background thread
+    let path = config
+        .dump_folder
+        .join(format!("invariant_violation_tick_{tick}.txt"));
+    let result =
+        std::fs::create_dir_all(&config.dump_folder).and_then(|_| std::fs::write(&path, dump));
+    match result {
+        Ok(()) => error!("Dumped the state of the world to {}", path.display()),
+        Err(error) => error!("Could not dump the state of the world: {error}"),
+    }
+
+    panic!("{} invariant(s) violated on tick {tick}", violations.len());
+}
+
+/// Exits the app once the [`SoakTest`] has run for long enough.
+fn end_soak_test(
+    soak_test: Res<SoakTest>,
+    time: Res<Time>,
+    simulation_tick: Res<SimulationTick>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if time.elapsed() >= soak_test.duration {
+        info!(
+            "Soak test passed: no invariants were violated in {} ticks",
+            simulation_tick.get()
+        );
+        exit_events.send(AppExit);
+    }
+}
+
+/// Checks that the simulation stays internally consistent, and runs any [`SoakTest`].
+pub struct InvariantsPlugin;
+
+impl Plugin for InvariantsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InvariantConfig>()
+            .add_system(check_invariants.in_base_set(CoreSet::Last))
+            .add_system(
+                end_soak_test
+                    .after(check_invariants)
+                    .in_base_set(CoreSet::Last)
+                    .run_if(resource_exists::<SoakTest>()),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SignalStrength;
+
+    #[test]
+    fn healthy_signals_pass() {
+        let mut signals = Signals::default();
+        signals.add_signal(
+            SignalType::Pull(Id::new(1)),
+            TilePos::ORIGIN,
+            SignalStrength::new(1.),
+        );
+
+        assert!(check_signals(&signals, 10.).is_empty());
+    }
+
+    #[test]
+    fn infinite_signals_are_caught() {
+        let mut signals = Signals::default();
+        signals.add_signal(
+            SignalType::Pull(Id::new(1)),
+            TilePos::ORIGIN,
+            SignalStrength::new(f32::INFINITY),
+        );
+
+        let violations = check_signals(&signals, 10.);
+        assert!(matches!(
+            violations[0],
+            InvariantViolation::InvalidSignal { .. }
+        ));
+    }
+
+    #[test]
+    fn unbounded_signals_are_caught() {
+        let mut signals = Signals::default();
+        signals.add_signal(
+            SignalType::Pull(Id::new(1)),
+            TilePos::ORIGIN,
+            SignalStrength::new(100.),
+        );
+
+        assert_eq!(
+            check_signals(&signals, 10.),
+            vec![InvariantViolation::UnboundedSignal {
+                signal_type: SignalType::Pull(Id::new(1)),
+                total: 100.
+            }]
+        );
+    }
+}
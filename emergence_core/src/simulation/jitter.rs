@@ -0,0 +1,163 @@
+//! Cheap, deterministic randomness that differs between entities.
+//!
+//! Sharing a single random number generator between every entity forces systems to run one after another,
+//! while seeding every entity identically makes them all act in lockstep.
+//! Instead, each entity gets its own short-lived [`SmallRng`], seeded from a hash of the world's seed, the entity,
+//! the current [`SimulationTick`] and the [`JitterStream`] the randomness is being used for.
+//! Random choices made for the world as a whole, like the weather, are seeded the same way, without an entity.
+//!
+//! The same entity on the same tick always gets the same random numbers, which keeps replays reproducible.
+//! Nothing in the simulation should draw on any other source of randomness.
+
+use crate::bevy::{ecs::system::SystemParam, prelude::*};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::{generation::GenerationConfig, time::SimulationTick};
+
+/// What a stream of jitter is being used for.
+///
+/// Each stream is seeded differently, so unrelated random choices made by the same entity on the same tick are independent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JitterStream {
+    /// Choosing which goal to pursue.
+    Goals,
+    /// Choosing which way to turn and which target to head for.
+    Actions,
+    /// Varying the strength of emitted signals.
+    Emission,
+    /// Deciding whether to give birth.
+    Breeding,
+    /// Naming newly born organisms.
+    Naming,
+    /// Changing the weather, and which structures storms destroy.
+    #[cfg(feature = "weather")]
+    Weather,
+    /// Bringing in new weather fronts.
+    #[cfg(feature = "weather")]
+    Fronts,
+    /// Deciding when and where lightning strikes.
+    #[cfg(feature = "weather")]
+    Lightning,
+    /// Spreading fires.
+    Fire,
+    /// Spreading sickness between units.
+    #[cfg(feature = "disease")]
+    Disease,
+    /// Choosing where the director's hazards occur.
+    Director,
+    /// Choosing where scripted scenario events occur.
+    Scenario,
+}
+
+impl JitterStream {
+    /// A constant used to separate the seeds of each stream.
+    const fn salt(&self) -> u64 {
+        match self {
+            JitterStream::Goals => 0x243F_6A88_85A3_08D3,
+            JitterStream::Actions => 0x1319_8A2E_0370_7344,
+            JitterStream::Emission => 0xA409_3822_299F_31D0,
+            JitterStream::Breeding => 0x082E_FA98_EC4E_6C89,
+            JitterStream::Naming => 0x4528_21E6_38D0_1377,
+            #[cfg(feature = "weather")]
+            JitterStream::Weather => 0xBE54_66CF_34E9_0C6C,
+            #[cfg(feature = "weather")]
+            JitterStream::Fronts => 0xD131_0BA6_98DF_B5AC,
+            #[cfg(feature = "weather")]
+            JitterStream::Lightning => 0x2FFD_72DB_D01A_DFB7,
+            JitterStream::Fire => 0xC0AC_29B7_C97C_50DD,
+            #[cfg(feature = "disease")]
+            JitterStream::Disease => 0xB8E1_AFED_6A26_7E96,
+            JitterStream::Director => 0x3F84_D5B5_B547_0917,
+            JitterStream::Scenario => 0x9216_D5D9_8979_FB1B,
+        }
+    }
+}
+
+/// Mixes the bits of `x` thoroughly, using the finalizer of the `SplitMix64` generator.
+const fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Computes the seed used for the randomness of `entity` on `tick` in the world seeded by `world_seed`, for use in `stream`.
+fn jitter_seed(world_seed: u64, entity: Entity, tick: u64, stream: JitterStream) -> u64 {
+    mix(mix(mix(world_seed) ^ entity.to_bits() ^ stream.salt()) ^ tick)
+}
+
+/// Provides per-entity random number generators, which change every tick.
+#[derive(SystemParam)]
+pub struct Jitter<'w> {
+    /// The current tick, which reseeds every generator
+    simulation_tick: Res<'w, SimulationTick>,
+    /// The settings the world was generated with, whose seed underlies every generator
+    generation_config: Res<'w, GenerationConfig>,
+}
+
+impl<'w> Jitter<'w> {
+    /// A random number generator for `entity` to use in `stream` during this tick.
+    pub fn rng(&self, entity: Entity, stream: JitterStream) -> SmallRng {
+        SmallRng::seed_from_u64(jitter_seed(
+            self.generation_config.seed(),
+            entity,
+            self.simulation_tick.get(),
+            stream,
+        ))
+    }
+
+    /// A random number generator for choices made for the world as a whole in `stream` during this tick.
+    pub fn world_rng(&self, stream: JitterStream) -> SmallRng {
+        self.rng(Entity::PLACEHOLDER, stream)
+    }
+
+    /// A random factor between `1 - amplitude` and `1 + amplitude`, for `entity` to use in `stream` during this tick.
+    pub fn factor(&self, entity: Entity, stream: JitterStream, amplitude: f32) -> f32 {
+        1. + self.rng(entity, stream).gen_range(-amplitude..=amplitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_is_deterministic() {
+        let entity = Entity::from_raw(7);
+
+        assert_eq!(
+            jitter_seed(0, entity, 42, JitterStream::Actions),
+            jitter_seed(0, entity, 42, JitterStream::Actions)
+        );
+    }
+
+    #[test]
+    fn jitter_differs_between_worlds_entities_ticks_and_streams() {
+        let entity = Entity::from_raw(7);
+        let seed = jitter_seed(0, entity, 42, JitterStream::Actions);
+
+        assert_ne!(
+            seed,
+            jitter_seed(0, Entity::from_raw(8), 42, JitterStream::Actions)
+        );
+        assert_ne!(seed, jitter_seed(0, entity, 43, JitterStream::Actions));
+        assert_ne!(seed, jitter_seed(0, entity, 42, JitterStream::Emission));
+        assert_ne!(seed, jitter_seed(1, entity, 42, JitterStream::Actions));
+    }
+
+    #[test]
+    fn neighboring_entities_do_not_move_in_lockstep() {
+        let n_turning_left = (0..1000)
+            .filter(|&i| {
+                SmallRng::seed_from_u64(jitter_seed(
+                    0,
+                    Entity::from_raw(i),
+                    0,
+                    JitterStream::Actions,
+                ))
+                .gen::<bool>()
+            })
+            .count();
+
+        assert!((400..600).contains(&n_turning_left));
+    }
+}
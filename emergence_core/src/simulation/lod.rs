@@ -0,0 +1,279 @@
+//! Level of detail for the simulation.
+//!
+//! Expensive, slowly changing systems don't need to run every tick.
+//! Instead, each [`LodGroup`] runs once every few ticks, and is handed all of the time that has passed since it last ran.
+//! Because no time is ever dropped, the results are the same no matter how fast the game is running:
+//! they are simply computed in fewer, larger steps.
+//!
+//! Fast-moving systems, like unit movement and signals, are not part of any group and still run every tick.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+    manifest::{Id, Unit},
+    organisms::Organism,
+    structures::crafting::CraftingState,
+};
+
+use super::{geometry::TilePos, save::SavedLodGroup, time::advance_simulation_tick};
+
+/// Sessile organisms that are further than this many tiles from every unit are simulated at a reduced rate.
+const ACTIVITY_RADIUS: i32 = 12;
+
+/// Coordinates how often each [`LodGroup`] runs.
+pub(super) struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodSchedule>()
+            .add_system(
+                advance_lod_schedule
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(advance_simulation_tick),
+            )
+            .add_system(
+                mark_distant_organisms
+                    .after(crate::structures::crafting::progress_crafting)
+                    .run_if(lod_group_ready(LodGroup::DistantOrganisms)),
+            );
+    }
+}
+
+/// A set of systems that run at the same reduced rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LodGroup {
+    /// Moisture and fertility changes in the soil.
+    Soil,
+    /// Growth of sessile organisms that are far away from any unit.
+    DistantOrganisms,
+}
+
+impl LodGroup {
+    /// All of the groups, in the order they are staggered.
+    const ALL: [LodGroup; 2] = [LodGroup::Soil, LodGroup::DistantOrganisms];
+
+    /// The number of ticks between each run of this group.
+    const fn interval(&self) -> u64 {
+        match self {
+            LodGroup::Soil => 10,
+            LodGroup::DistantOrganisms => 20,
+        }
+    }
+}
+
+/// The progress of a single [`LodGroup`] towards its next run.
+#[derive(Debug, Clone, PartialEq)]
+struct LodGroupState {
+    /// The number of ticks that must pass between each run
+    interval: u64,
+    /// The number of ticks that have passed since the group last ran
+    ticks_since_run: u64,
+    /// The in-game time that has passed since the group last ran
+    accumulated: Duration,
+    /// The time that the group should simulate this tick, if it runs this tick
+    delta: Option<Duration>,
+}
+
+impl LodGroupState {
+    /// Creates a new group state, which first runs after `offset` ticks.
+    fn new(interval: u64, offset: u64) -> Self {
+        LodGroupState {
+            interval,
+            ticks_since_run: interval.saturating_sub(1 + offset % interval.max(1)),
+            accumulated: Duration::ZERO,
+            delta: None,
+        }
+    }
+
+    /// Advances the group by one tick, during which `delta` time passed.
+    fn advance(&mut self, delta: Duration) {
+        self.ticks_since_run += 1;
+        self.accumulated += delta;
+
+        if self.ticks_since_run >= self.interval {
+            self.delta = Some(self.accumulated);
+            self.ticks_since_run = 0;
+            self.accumulated = Duration::ZERO;
+        } else {
+            self.delta = None;
+        }
+    }
+}
+
+/// Tracks when each [`LodGroup`] should run, and how much time it should simulate when it does.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LodSchedule {
+    /// The state of each group
+    groups: HashMap<LodGroup, LodGroupState>,
+}
+
+impl Default for LodSchedule {
+    fn default() -> Self {
+        let groups = LodGroup::ALL
+            .iter()
+            .enumerate()
+            // Stagger the groups, so they don't all land on the same tick
+            .map(|(i, &group)| (group, LodGroupState::new(group.interval(), i as u64)))
+            .collect();
+
+        LodSchedule { groups }
+    }
+}
+
+impl LodSchedule {
+    /// Does `group` run this tick?
+    pub fn is_ready(&self, group: LodGroup) -> bool {
+        self.groups[&group].delta.is_some()
+    }
+
+    /// The amount of in-game time that `group` should simulate this tick.
+    ///
+    /// This is all of the time since the group last ran, or zero if it does not run this tick.
+    pub fn delta(&self, group: LodGroup) -> Duration {
+        self.groups[&group].delta.unwrap_or_default()
+    }
+
+    /// The amount of in-game time that `group` should simulate this tick, in seconds.
+    pub fn delta_seconds(&self, group: LodGroup) -> f32 {
+        self.delta(group).as_secs_f32()
+    }
+
+    /// How far along each group is towards its next run, so that the schedule can be saved.
+    pub fn record(&self) -> Vec<SavedLodGroup> {
+        LodGroup::ALL
+            .iter()
+            .map(|group| {
+                let state = &self.groups[group];
+                SavedLodGroup {
+                    group: *group,
+                    ticks_since_run: state.ticks_since_run,
+                    accumulated: state.accumulated,
+                }
+            })
+            .collect()
+    }
+
+    /// Picks up each group where the `saved_groups` left off, e.g. when a saved game is loaded.
+    ///
+    /// Groups that were not saved keep their current progress.
+    pub fn restore(&mut self, saved_groups: &[SavedLodGroup]) {
+        for saved_group in saved_groups {
+            if let Some(state) = self.groups.get_mut(&saved_group.group) {
+                state.ticks_since_run = saved_group.ticks_since_run;
+                state.accumulated = saved_group.accumulated;
+                // Recomputed when the schedule next advances
+                state.delta = None;
+            }
+        }
+    }
+}
+
+/// Advances every [`LodGroup`] by one tick, unless the simulation is paused.
+fn advance_lod_schedule(time: Res<Time>, mut lod_schedule: ResMut<LodSchedule>) {
+    if time.is_paused() {
+        for state in lod_schedule.groups.values_mut() {
+            state.delta = None;
+        }
+        return;
+    }
+
+    for state in lod_schedule.groups.values_mut() {
+        state.advance(time.delta());
+    }
+}
+
+/// A run condition that only allows systems to run on ticks when `group` is scheduled.
+pub fn lod_group_ready(group: LodGroup) -> impl FnMut(Res<LodSchedule>) -> bool {
+    move |lod_schedule: Res<LodSchedule>| lod_schedule.is_ready(group)
+}
+
+/// Marks sessile organisms that are far from any unit, which are simulated as part of [`LodGroup::DistantOrganisms`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Distant;
+
+/// Adds or removes [`Distant`] based on how close each growing organism is to the nearest unit.
+///
+/// This only runs on the same ticks as [`LodGroup::DistantOrganisms`], after they have been simulated,
+/// so organisms never lose or double-count time as they switch between rates.
+fn mark_distant_organisms(
+    organism_query: Query<
+        (Entity, &TilePos, Option<&Distant>),
+        (With<Organism>, With<CraftingState>),
+    >,
+    unit_query: Query<&TilePos, With<Id<Unit>>>,
+    mut commands: Commands,
+) {
+    let unit_positions: Vec<TilePos> = unit_query.iter().copied().collect();
+
+    for (entity, tile_pos, maybe_distant) in organism_query.iter() {
+        let is_distant = unit_positions
+            .iter()
+            .all(|unit_pos| unit_pos.hex.distance_to(tile_pos.hex) > ACTIVITY_RADIUS);
+
+        match (is_distant, maybe_distant.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(Distant);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Distant>();
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_run_once_per_interval() {
+        let mut state = LodGroupState::new(4, 0);
+        let mut runs = Vec::new();
+
+        for tick in 0..12 {
+            state.advance(Duration::from_millis(10));
+            if state.delta.is_some() {
+                runs.push(tick);
+            }
+        }
+
+        assert_eq!(runs, vec![0, 4, 8]);
+    }
+
+    #[test]
+    fn offset_groups_are_staggered() {
+        let mut state = LodGroupState::new(4, 1);
+        let mut runs = Vec::new();
+
+        for tick in 0..12 {
+            state.advance(Duration::from_millis(10));
+            if state.delta.is_some() {
+                runs.push(tick);
+            }
+        }
+
+        assert_eq!(runs, vec![1, 5, 9]);
+    }
+
+    #[test]
+    fn no_time_is_lost_between_runs() {
+        for interval in [1, 3, 10] {
+            let mut state = LodGroupState::new(interval, 0);
+            let mut simulated = Duration::ZERO;
+
+            for tick in 0..61 {
+                // Uneven frame times, as when the game speeds up and slows down
+                state.advance(Duration::from_millis(5 + tick % 7));
+                simulated += state.delta.unwrap_or_default();
+            }
+
+            let elapsed: Duration = (0..61)
+                .map(|tick| Duration::from_millis(5 + tick % 7))
+                .sum();
+            assert_eq!(simulated + state.accumulated, elapsed);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Code that runs the simulation of the game.
+//!
+//! All plugins in this module should work without rendering.
+
+use crate::bevy::app::{App, Plugin};
+use crate::bevy::log::info;
+use crate::items::spoilage::SpoilagePlugin;
+use crate::organisms::OrganismPlugin;
+use crate::signals::SignalsPlugin;
+use crate::simulation::alerts::AlertsPlugin;
+use crate::simulation::backlog::BacklogPlugin;
+use crate::simulation::chronicle::ChroniclePlugin;
+use crate::simulation::director::DirectorPlugin;
+use crate::simulation::fire::FirePlugin;
+use crate::simulation::flow_fields::FlowFieldPlugin;
+use crate::simulation::freezing::FreezingPlugin;
+use crate::simulation::generation::{GenerationConfig, GenerationPlugin};
+use crate::simulation::lod::LodPlugin;
+use crate::simulation::pathfinding::PathfindingPlugin;
+use crate::simulation::save::SaveLoadPlugin;
+use crate::simulation::scenario::ScenarioPlugin;
+use crate::simulation::snapshot::SnapshotPlugin;
+use crate::simulation::temperature::TemperaturePlugin;
+use crate::simulation::time::InGameTimePlugin;
+use crate::simulation::vision::VisionPlugin;
+use crate::simulation::wind::WindPlugin;
+use crate::structures::StructuresPlugin;
+use crate::terrain::TerrainPlugin;
+use crate::units::UnitsPlugin;
+
+pub mod alerts;
+pub mod backlog;
+pub mod chronicle;
+pub mod director;
+pub mod fire;
+pub mod flow_fields;
+pub mod freezing;
+pub mod generation;
+pub mod geometry;
+pub mod invariants;
+pub mod jitter;
+pub mod lod;
+mod noise;
+pub mod pathfinding;
+pub mod save;
+pub mod scenario;
+pub mod snapshot;
+pub mod temperature;
+pub mod time;
+pub mod vision;
+pub mod weather;
+pub mod wind;
+
+/// All of the code needed to make the simulation run
+pub struct SimulationPlugin {
+    /// Configuration settings for world generation
+    pub gen_config: GenerationConfig,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        info!("Building simulation plugin...");
+        app.add_plugin(GenerationPlugin {
+            config: self.gen_config.clone(),
+        });
+        // Signals come first, as other plugins add systems to the signal schedule
+        app.add_plugin(SignalsPlugin)
+            .add_plugin(StructuresPlugin)
+            .add_plugin(OrganismPlugin)
+            .add_plugin(UnitsPlugin)
+            .add_plugin(TemperaturePlugin)
+            .add_plugin(VisionPlugin)
+            .add_plugin(InGameTimePlugin)
+            .add_plugin(LodPlugin)
+            .add_plugin(PathfindingPlugin)
+            .add_plugin(FlowFieldPlugin)
+            .add_plugin(AlertsPlugin)
+            .add_plugin(BacklogPlugin)
+            .add_plugin(ChroniclePlugin)
+            .add_plugin(ScenarioPlugin)
+            .add_plugin(DirectorPlugin)
+            .add_plugin(WindPlugin)
+            .add_plugin(FirePlugin)
+            .add_plugin(FreezingPlugin)
+            .add_plugin(SpoilagePlugin)
+            .add_plugin(SnapshotPlugin)
+            .add_plugin(SaveLoadPlugin)
+            .add_plugin(TerrainPlugin);
+
+        #[cfg(feature = "weather")]
+        app.add_plugin(weather::WeatherPlugin);
+
+        // Other systems read the weather, so it stays permanently clear when the weather is disabled
+        #[cfg(not(feature = "weather"))]
+        app.init_resource::<weather::CurrentWeather>()
+            .init_resource::<weather::WeatherFronts>();
+    }
+}
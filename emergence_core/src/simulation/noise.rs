@@ -0,0 +1,91 @@
+//! Simplex noise, used to shape the terrain during world generation.
+//!
+//! Ported from [`noisy_bevy`](https://github.com/johanhelsing/noisy_bevy),
+//! Copyright (c) 2022 Johan Helsing, available under the MIT or Apache 2.0 License,
+//! which is in turn ported from <https://github.com/stegu/webgl-noise> (MIT License).
+//!
+//! `noisy_bevy` also registers its shaders with the renderer,
+//! so only the CPU implementation is kept here to keep the simulation free of rendering.
+
+use crate::bevy::math::{vec2, vec3, vec4, Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
+
+/// Permutes each component of `x`, for use in [`simplex_noise_2d_seeded`].
+fn permute_3(x: Vec3) -> Vec3 {
+    (((x * 34.) + 1.) * x) % Vec3::splat(289.)
+}
+
+/// Simplex noise in two dimensions, shifted by `seed`.
+///
+/// The result is roughly in the range -1 to 1.
+pub fn simplex_noise_2d_seeded(v: Vec2, seed: f32) -> f32 {
+    const C: Vec4 = Vec4::new(
+        0.211_324_87,  // (3.0 - sqrt(3.0)) / 6.0
+        0.366_025_42,  // 0.5 * (sqrt(3.0) - 1.0)
+        -0.577_350_26, // -1.0 + 2.0 * C.x
+        0.024_390_243, // 1.0 / 41.0
+    );
+
+    // First corner
+    let mut i = (v + v.dot(C.yy())).floor();
+    let x0 = v - i + i.dot(C.xx());
+
+    // Other corners
+    let i1 = if x0.x > x0.y {
+        vec2(1., 0.)
+    } else {
+        vec2(0., 1.)
+    };
+    let mut x12 = x0.xyxy() + C.xxzz();
+    x12 = vec4(x12.x - i1.x, x12.y - i1.y, x12.z, x12.w);
+
+    // Permutations, avoiding truncation effects
+    i %= Vec2::splat(289.);
+
+    let mut p = permute_3(permute_3(i.y + vec3(0., i1.y, 1.)) + i.x + vec3(0., i1.x, 1.));
+    p = permute_3(p + Vec3::splat(seed));
+
+    let mut m =
+        (0.5 - vec3(x0.dot(x0), x12.xy().dot(x12.xy()), x12.zw().dot(x12.zw()))).max(Vec3::ZERO);
+    m *= m;
+    m *= m;
+
+    // Gradients: 41 points uniformly over a line, mapped onto a diamond.
+    // The ring size 17 * 17 = 289 is close to a multiple of 41 (41 * 7 = 287)
+    let x = 2. * (p * C.www()).fract() - 1.;
+    let h = x.abs() - 0.5;
+    let ox = (x + 0.5).floor();
+    let a0 = x - ox;
+
+    // Normalize the gradients implicitly by scaling m,
+    // approximating m *= inversesqrt(a0 * a0 + h * h)
+    m *= 1.792_842_9 - 0.853_734_73 * (a0 * a0 + h * h);
+
+    // Compute the final noise value at v
+    let gx = a0.x * x0.x + h.x * x0.y;
+    let gyz = a0.yz() * x12.xz() + h.yz() * x12.yw();
+    let g = vec3(gx, gyz.x, gyz.y);
+    130. * m.dot(g)
+}
+
+/// Fractional Brownian motion (fbm), summing `octaves` layers of [`simplex_noise_2d_seeded`].
+///
+/// Each layer is `lacunarity` times finer and `gain` times weaker than the last.
+pub fn fbm_simplex_2d_seeded(
+    pos: Vec2,
+    octaves: usize,
+    lacunarity: f32,
+    gain: f32,
+    seed: f32,
+) -> f32 {
+    let mut sum = 0.;
+    let mut amplitude = 1.;
+    let mut frequency = 1.;
+
+    for _ in 0..octaves {
+        sum += simplex_noise_2d_seeded(pos * frequency, seed) * amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+
+    sum
+}
@@ -0,0 +1,628 @@
+//! Finds the shortest walkable route between two tiles.
+//!
+//! Searches can be run immediately with [`find_path`], or queued with the [`PathfindingService`],
+//! which answers a bounded number of requests each frame.
+//! Queuing keeps frame times steady when many units need new paths at once.
+//!
+//! Answers are cached, so that units heading between the same pair of tiles only pay for one search.
+//! The cache is cleared whenever the [`MapGeometry`] or any [`Terrain`] changes, as paths may no longer be valid.
+
+use crate::bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use core::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::{
+    terrain::Terrain,
+    units::actions::{min_walking_duration, walking_duration},
+};
+
+use super::geometry::{MapGeometry, TilePos};
+
+/// Answers queued path requests.
+pub(super) struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PathfindingService>()
+            .add_event::<PathFound>()
+            .add_system(invalidate_path_cache.before(process_path_requests))
+            .add_system(process_path_requests);
+    }
+}
+
+/// A walkable route between two tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// The tiles visited, including both the start and the goal
+    pub tiles: Vec<TilePos>,
+    /// The total cost of walking along this path
+    pub cost: f32,
+}
+
+impl Path {
+    /// The number of steps needed to walk this path.
+    pub fn steps(&self) -> usize {
+        self.tiles.len().saturating_sub(1)
+    }
+}
+
+/// A tile waiting to be explored, ordered so that the [`BinaryHeap`] returns the most promising tile first.
+#[derive(Debug, Clone, Copy)]
+struct Frontier {
+    /// The cost so far, plus the estimated cost to reach the goal
+    estimate: f32,
+    /// The tile to explore
+    tile_pos: TilePos,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the lowest estimate is popped first
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// A request for the [`PathfindingService`] to find a walkable path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathRequest {
+    /// The entity that the answer is for
+    pub requester: Entity,
+    /// Where the path should start
+    pub start: TilePos,
+    /// Where the path should end
+    pub goal: TilePos,
+    /// Requests with a higher priority are answered first
+    pub priority: u8,
+}
+
+/// A [`PathRequest`] waiting in the queue of the [`PathfindingService`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedRequest {
+    /// The request being made
+    request: PathRequest,
+    /// The order in which this request was made, used to answer older requests first
+    sequence: u64,
+}
+
+impl PartialOrd for QueuedRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedRequest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The highest priority is popped first, and then the oldest request
+        self.request
+            .priority
+            .cmp(&other.request.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// The maximum number of answers stored by the [`PathfindingService`] before its cache is cleared.
+const MAX_CACHED_PATHS: usize = 1024;
+
+/// Queues requests for paths, and answers a few of them each frame with a [`PathFound`] event.
+///
+/// Each requester can have only one request waiting: making a new request replaces the old one.
+/// Requests that can be answered from the cache do not count towards [`PathfindingService::requests_per_frame`].
+#[derive(Resource, Debug)]
+pub struct PathfindingService {
+    /// The requests waiting to be answered, most urgent first
+    ///
+    /// Replaced requests are left in the queue, and skipped when they are reached.
+    queue: BinaryHeap<QueuedRequest>,
+    /// The sequence number of the latest request made by each requester
+    latest: HashMap<Entity, u64>,
+    /// The sequence number given to the next request
+    next_sequence: u64,
+    /// Previous answers, keyed by their start and goal
+    cache: HashMap<(TilePos, TilePos), Option<Path>>,
+    /// The maximum number of requests answered each frame
+    pub requests_per_frame: usize,
+}
+
+impl Default for PathfindingService {
+    fn default() -> Self {
+        PathfindingService {
+            queue: BinaryHeap::new(),
+            latest: HashMap::new(),
+            next_sequence: 0,
+            cache: HashMap::new(),
+            requests_per_frame: 8,
+        }
+    }
+}
+
+impl PathfindingService {
+    /// Adds `request` to the queue, replacing any request already waiting for the same requester.
+    pub fn request(&mut self, request: PathRequest) {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        self.latest.insert(request.requester, sequence);
+        self.queue.push(QueuedRequest { request, sequence });
+    }
+
+    /// Is there a request waiting to be answered for `requester`?
+    pub fn is_pending(&self, requester: Entity) -> bool {
+        self.latest.contains_key(&requester)
+    }
+
+    /// The number of requests waiting to be answered.
+    pub fn n_pending(&self) -> usize {
+        self.latest.len()
+    }
+
+    /// Removes the most urgent request from the queue.
+    fn next_request(&mut self) -> Option<PathRequest> {
+        while let Some(QueuedRequest { request, sequence }) = self.queue.pop() {
+            if self.latest.get(&request.requester) == Some(&sequence) {
+                self.latest.remove(&request.requester);
+                return Some(request);
+            }
+        }
+
+        None
+    }
+
+    /// The answer previously found for a path from `start` to `goal`, if it is still cached.
+    fn cached_path(&self, start: TilePos, goal: TilePos) -> Option<&Option<Path>> {
+        self.cache.get(&(start, goal))
+    }
+
+    /// Stores the answer found for a path from `start` to `goal`.
+    ///
+    /// The whole cache is cleared once it reaches [`MAX_CACHED_PATHS`], so it cannot grow without bound.
+    fn cache_path(&mut self, start: TilePos, goal: TilePos, path: Option<Path>) {
+        if self.cache.len() >= MAX_CACHED_PATHS {
+            self.cache.clear();
+        }
+
+        self.cache.insert((start, goal), path);
+    }
+
+    /// The number of answers currently cached.
+    pub fn n_cached(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Forgets every cached answer, so that new requests are searched for from scratch.
+    pub fn invalidate_cache(&mut self) {
+        self.cache.clear();
+    }
+}
+
+/// The answer to a [`PathRequest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFound {
+    /// The entity that made the request
+    pub requester: Entity,
+    /// Where the path starts
+    pub start: TilePos,
+    /// Where the path ends
+    pub goal: TilePos,
+    /// The cheapest walkable path, or `None` if the goal cannot be reached
+    pub path: Option<Path>,
+}
+
+/// Clears the cache of the [`PathfindingService`] when the map changes, as cached paths may now be blocked or suboptimal.
+fn invalidate_path_cache(
+    mut pathfinding_service: ResMut<PathfindingService>,
+    map_geometry: Res<MapGeometry>,
+    changed_terrain_query: Query<(), Changed<Terrain>>,
+) {
+    // Clearing an empty cache would still mark the service as changed
+    if pathfinding_service.n_cached() == 0 {
+        return;
+    }
+
+    if map_geometry.is_changed() || !changed_terrain_query.is_empty() {
+        pathfinding_service.invalidate_cache();
+    }
+}
+
+/// Answers the most urgent path requests, searching for up to [`PathfindingService::requests_per_frame`] new paths.
+///
+/// Requests with a cached answer are answered immediately, without counting towards this limit.
+fn process_path_requests(
+    mut pathfinding_service: ResMut<PathfindingService>,
+    map_geometry: Res<MapGeometry>,
+    terrain_query: Query<&Terrain>,
+    mut path_events: EventWriter<PathFound>,
+) {
+    // Avoid triggering change detection when there is nothing to do
+    if pathfinding_service.n_pending() == 0 {
+        return;
+    }
+
+    let min_step_cost = min_walking_duration(&terrain_query, &map_geometry);
+    let mut n_searches = 0;
+
+    while n_searches < pathfinding_service.requests_per_frame {
+        let request = match pathfinding_service.next_request() {
+            Some(request) => request,
+            None => break,
+        };
+
+        let path = match pathfinding_service.cached_path(request.start, request.goal) {
+            Some(cached_path) => cached_path.clone(),
+            None => {
+                n_searches += 1;
+
+                let path = find_path(
+                    &map_geometry,
+                    request.start,
+                    request.goal,
+                    min_step_cost,
+                    |tile_pos, target_tile_pos| {
+                        walking_duration(tile_pos, target_tile_pos, &map_geometry, &terrain_query)
+                    },
+                );
+                pathfinding_service.cache_path(request.start, request.goal, path.clone());
+                path
+            }
+        };
+
+        path_events.send(PathFound {
+            requester: request.requester,
+            start: request.start,
+            goal: request.goal,
+            path,
+        });
+    }
+}
+
+/// Finds the cheapest path that a unit could walk from `start` to `goal`, using A* search.
+///
+/// `step_cost` returns the cost of stepping from the first tile to the adjacent second tile,
+/// and `min_step_cost` must be no larger than any value it returns, or the path found may not be the cheapest.
+///
+/// Express routes in the `MapGeometry::express_index` are used whenever they are cheaper than walking,
+/// with their traversal time as their cost.
+/// Consecutive tiles of the path are not adjacent where it takes an express route.
+///
+/// Returns `None` if the `goal` cannot be reached.
+pub fn find_path(
+    map_geometry: &MapGeometry,
+    start: TilePos,
+    goal: TilePos,
+    min_step_cost: f32,
+    step_cost: impl Fn(TilePos, TilePos) -> f32,
+) -> Option<Path> {
+    // Express routes can skip across the map, so the estimate must allow for taking them to stay optimistic
+    let express_links = &map_geometry.express_index;
+    let min_express_cost = express_links
+        .values()
+        .map(|link| link.traversal_time)
+        .fold(f32::INFINITY, f32::min);
+    let min_exit_distance = express_links
+        .values()
+        .map(|link| link.exit.hex.distance_to(goal.hex))
+        .min();
+
+    let heuristic = |tile_pos: TilePos| {
+        let walking_estimate = tile_pos.hex.distance_to(goal.hex) as f32 * min_step_cost;
+
+        match min_exit_distance {
+            Some(min_exit_distance) => {
+                let min_entrance_distance = express_links
+                    .keys()
+                    .map(|entrance| tile_pos.hex.distance_to(entrance.hex))
+                    .min()
+                    .unwrap_or_default();
+                let express_estimate = (min_entrance_distance + min_exit_distance) as f32
+                    * min_step_cost
+                    + min_express_cost;
+
+                walking_estimate.min(express_estimate)
+            }
+            None => walking_estimate,
+        }
+    };
+
+    let mut frontier = BinaryHeap::new();
+    let mut came_from: HashMap<TilePos, TilePos> = HashMap::new();
+    let mut cost_so_far: HashMap<TilePos, f32> = HashMap::new();
+    let mut explored: HashSet<TilePos> = HashSet::new();
+
+    frontier.push(Frontier {
+        estimate: heuristic(start),
+        tile_pos: start,
+    });
+    cost_so_far.insert(start, 0.);
+
+    while let Some(Frontier { tile_pos, .. }) = frontier.pop() {
+        if tile_pos == goal {
+            let mut tiles = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                tiles.push(previous);
+                current = previous;
+            }
+            tiles.reverse();
+
+            return Some(Path {
+                tiles,
+                cost: cost_so_far[&goal],
+            });
+        }
+
+        // Stale entries are left in the heap when a cheaper route is found
+        if !explored.insert(tile_pos) {
+            continue;
+        }
+
+        let steps = tile_pos
+            .reachable_neighbors(map_geometry)
+            .into_iter()
+            .map(|neighbor| (neighbor, step_cost(tile_pos, neighbor)))
+            .chain(
+                express_links
+                    .get(&tile_pos)
+                    .map(|link| (link.exit, link.traversal_time)),
+            );
+
+        for (neighbor, cost) in steps {
+            let new_cost = cost_so_far[&tile_pos] + cost;
+            let is_cheaper = match cost_so_far.get(&neighbor) {
+                Some(&existing_cost) => new_cost < existing_cost,
+                None => true,
+            };
+
+            if is_cheaper {
+                cost_so_far.insert(neighbor, new_cost);
+                came_from.insert(neighbor, tile_pos);
+                frontier.push(Frontier {
+                    estimate: new_cost + heuristic(neighbor),
+                    tile_pos: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::prelude::Entity;
+
+    use super::*;
+    use crate::{simulation::geometry::UPHILL_WALKING_PENALTY, structures::express::ExpressLink};
+
+    #[test]
+    fn path_to_self_is_empty() {
+        let map_geometry = MapGeometry::new(3);
+        let path = find_path(
+            &map_geometry,
+            TilePos::ORIGIN,
+            TilePos::ORIGIN,
+            1.,
+            |_, _| 1.,
+        )
+        .unwrap();
+
+        assert_eq!(path.tiles, vec![TilePos::ORIGIN]);
+        assert_eq!(path.steps(), 0);
+        assert_eq!(path.cost, 0.);
+    }
+
+    #[test]
+    fn open_paths_are_straight() {
+        let map_geometry = MapGeometry::new(5);
+        let goal = TilePos::new(3, 0);
+        let path = find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |_, _| 1.).unwrap();
+
+        assert_eq!(path.steps(), 3);
+        assert_eq!(path.cost, 3.);
+        assert_eq!(path.tiles.first(), Some(&TilePos::ORIGIN));
+        assert_eq!(path.tiles.last(), Some(&goal));
+    }
+
+    #[test]
+    fn paths_go_around_obstacles() {
+        let mut map_geometry = MapGeometry::new(5);
+        let blocked = TilePos::new(1, 0);
+        map_geometry
+            .structure_index
+            .insert(blocked, Entity::from_raw(0));
+
+        let goal = TilePos::new(2, 0);
+        let path = find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |_, _| 1.).unwrap();
+
+        assert!(!path.tiles.contains(&blocked));
+        assert_eq!(path.steps(), 3);
+    }
+
+    #[test]
+    fn paths_avoid_expensive_tiles() {
+        let map_geometry = MapGeometry::new(5);
+        let slow = TilePos::new(1, 0);
+        let goal = TilePos::new(2, 0);
+        let path = find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |tile_pos, _| {
+            if tile_pos == slow {
+                10.
+            } else {
+                1.
+            }
+        })
+        .unwrap();
+
+        assert!(!path.tiles.contains(&slow));
+        assert_eq!(path.cost, 3.);
+    }
+
+    #[test]
+    fn climbing_hills_is_slower() {
+        let mut map_geometry = MapGeometry::new(5);
+        let hill = TilePos::new(1, 0);
+        let goal = TilePos::new(2, 0);
+        map_geometry.height_index.insert(hill, 1.);
+
+        let path = find_path(
+            &map_geometry,
+            TilePos::ORIGIN,
+            goal,
+            1.,
+            |origin, target| map_geometry.slope_cost(origin, target),
+        )
+        .unwrap();
+
+        // Walking down the far side of the hill costs nothing extra
+        assert!(path.tiles.contains(&hill));
+        assert_eq!(path.cost, 2. + UPHILL_WALKING_PENALTY);
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        let mut map_geometry = MapGeometry::new(5);
+        let goal = TilePos::new(2, 0);
+        for neighbor in goal.all_neighbors(&map_geometry) {
+            map_geometry
+                .structure_index
+                .insert(neighbor, Entity::from_raw(0));
+        }
+
+        assert_eq!(
+            find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |_, _| 1.),
+            None
+        );
+    }
+
+    /// Links `entrance` to `exit` with an express route that takes `traversal_time` to travel along.
+    fn link(map_geometry: &mut MapGeometry, entrance: TilePos, exit: TilePos, traversal_time: f32) {
+        map_geometry.express_index.insert(
+            entrance,
+            ExpressLink {
+                exit,
+                traversal_time,
+            },
+        );
+    }
+
+    #[test]
+    fn fast_express_routes_are_taken() {
+        let mut map_geometry = MapGeometry::new(10);
+        let entrance = TilePos::new(1, 0);
+        let exit = TilePos::new(8, 0);
+        link(&mut map_geometry, entrance, exit, 2.);
+
+        let goal = TilePos::new(9, 0);
+        let path = find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |_, _| 1.).unwrap();
+
+        assert_eq!(path.tiles, vec![TilePos::ORIGIN, entrance, exit, goal]);
+        assert_eq!(path.cost, 4.);
+    }
+
+    #[test]
+    fn slow_express_routes_are_ignored() {
+        let mut map_geometry = MapGeometry::new(10);
+        let entrance = TilePos::new(1, 0);
+        let exit = TilePos::new(3, 0);
+        link(&mut map_geometry, entrance, exit, 5.);
+
+        let goal = TilePos::new(4, 0);
+        let path = find_path(&map_geometry, TilePos::ORIGIN, goal, 1., |_, _| 1.).unwrap();
+
+        assert_eq!(path.steps(), 4);
+        assert_eq!(path.cost, 4.);
+    }
+
+    /// A request from `requester` with the provided `priority`.
+    fn request(requester: u32, priority: u8) -> PathRequest {
+        PathRequest {
+            requester: Entity::from_raw(requester),
+            start: TilePos::ORIGIN,
+            goal: TilePos::new(1, 0),
+            priority,
+        }
+    }
+
+    #[test]
+    fn urgent_requests_are_answered_first() {
+        let mut pathfinding_service = PathfindingService::default();
+        pathfinding_service.request(request(0, 0));
+        pathfinding_service.request(request(1, 5));
+        pathfinding_service.request(request(2, 0));
+
+        assert_eq!(pathfinding_service.n_pending(), 3);
+        assert_eq!(pathfinding_service.next_request(), Some(request(1, 5)));
+        // Requests with the same priority are answered in the order they were made
+        assert_eq!(pathfinding_service.next_request(), Some(request(0, 0)));
+        assert_eq!(pathfinding_service.next_request(), Some(request(2, 0)));
+        assert_eq!(pathfinding_service.next_request(), None);
+    }
+
+    #[test]
+    fn new_requests_replace_old_ones() {
+        let mut pathfinding_service = PathfindingService::default();
+        pathfinding_service.request(request(0, 5));
+        pathfinding_service.request(request(0, 0));
+
+        assert_eq!(pathfinding_service.n_pending(), 1);
+        assert!(pathfinding_service.is_pending(Entity::from_raw(0)));
+        assert_eq!(pathfinding_service.next_request(), Some(request(0, 0)));
+        assert!(!pathfinding_service.is_pending(Entity::from_raw(0)));
+        assert_eq!(pathfinding_service.next_request(), None);
+    }
+
+    #[test]
+    fn cached_paths_are_remembered_until_invalidated() {
+        let mut pathfinding_service = PathfindingService::default();
+        let goal = TilePos::new(1, 0);
+        let path = Path {
+            tiles: vec![TilePos::ORIGIN, goal],
+            cost: 1.,
+        };
+
+        assert_eq!(pathfinding_service.cached_path(TilePos::ORIGIN, goal), None);
+        pathfinding_service.cache_path(TilePos::ORIGIN, goal, Some(path.clone()));
+        pathfinding_service.cache_path(goal, TilePos::new(5, 5), None);
+
+        assert_eq!(pathfinding_service.n_cached(), 2);
+        assert_eq!(
+            pathfinding_service.cached_path(TilePos::ORIGIN, goal),
+            Some(&Some(path))
+        );
+        // Unreachable goals are cached too, so they aren't searched for over and over
+        assert_eq!(
+            pathfinding_service.cached_path(goal, TilePos::new(5, 5)),
+            Some(&None)
+        );
+        // Paths are not assumed to be reversible
+        assert_eq!(pathfinding_service.cached_path(goal, TilePos::ORIGIN), None);
+
+        pathfinding_service.invalidate_cache();
+        assert_eq!(pathfinding_service.n_cached(), 0);
+        assert_eq!(pathfinding_service.cached_path(TilePos::ORIGIN, goal), None);
+    }
+
+    #[test]
+    fn path_cache_is_bounded() {
+        let mut pathfinding_service = PathfindingService::default();
+        for x in 0..=MAX_CACHED_PATHS as i32 {
+            pathfinding_service.cache_path(TilePos::ORIGIN, TilePos::new(x, 0), None);
+        }
+
+        assert!(pathfinding_service.n_cached() <= MAX_CACHED_PATHS);
+    }
+}
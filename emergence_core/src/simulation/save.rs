@@ -0,0 +1,1436 @@
+//! Saving the whole simulation to disk, and loading it back again.
+//!
+//! Saves are written as [RON](https://github.com/ron-rs/ron), prefixed with the version of the format they were written in.
+//! Unlike [`Snapshot`](super::snapshot::Snapshot)s, saves contain everything needed to rebuild the world:
+//! the terrain, every structure and the items it stores, every unit and the items it holds, the piles of remains left by the dead,
+//! and the [`Signals`] on every tile.
+//! Organisms keep their `Individual` name and life story, so players can find their favorites again after loading,
+//! and the `Chronicle` of past seasons is kept too.
+//! The calendar and the weather are saved along with the world, as is each structure's crafting progress and the energy and age of each organism.
+//! So are the moisture, fertility and zoning of the soil, the tiles that have been explored, the traces left along hauling corridors,
+//! and how far each level of detail group is towards its next run.
+//!
+//! Random choices are seeded from the world's seed and the current tick, so both are saved.
+//! Choices made for the world as a whole, like the weather, repeat exactly after loading.
+//! Choices made for individual organisms are also seeded from their entity, which is new after loading,
+//! so a loaded world does not make the same choices for them that the original would have.
+//!
+//! The same state can be kept in memory as a [`Checkpoint`], with [`save_snapshot`] and [`restore_snapshot`].
+//!
+//! Ghosts and previews are not saved, and units restart from their default goal and action.
+//! Zoned ghosts are placed again from the saved zoning of their tile.
+//! Some other state is deliberately left out:
+//! - the temperature of each tile, which is recomputed from the season, the weather and any heat sources every tick
+//! - the tiles currently in view, which are recomputed from the fog of war on the next tick
+//! - the family tree, which is tracked by entity: loaded units found new lineages
+//! - the freshness of stored items, which is also tracked by entity: stored items start out fresh after loading
+//!
+//! Identifiers are saved using their raw [`Id::value`], so saves remain readable even if the manifests change order.
+//! Before a save is loaded, every identifier in it is checked against the manifests,
+//! so that a save from a game with different definitions is rejected rather than half loaded.
+
+use crate::bevy::{
+    ecs::system::{Command, SystemParam, SystemState},
+    prelude::*,
+    tasks::IoTaskPool,
+};
+use core::fmt::Display;
+use hexx::Direction;
+use ron::{error::SpannedError, ser::PrettyConfig};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+
+use crate::{
+    items::ItemCount,
+    manifest::{
+        Id, Item, ItemManifest, Recipe, RecipeManifest, Structure, StructureManifest, Unit,
+        UnitManifest,
+    },
+    organisms::{
+        energy::{Energy, EnergyPool},
+        individuals::Individual,
+    },
+    signals::{SignalStrength, SignalType, Signals},
+    structures::{
+        commands::StructureCommandsExt,
+        construction::{Ghost, Preview},
+        crafting::{ActiveRecipe, CraftingState, InputInventory, OutputInventory},
+        ClipboardData,
+    },
+    terrain::{Fertility, SoilMoisture, Terrain, TerrainBundle, Zoning},
+    units::{
+        item_interaction::UnitInventory,
+        lifecycle::{Age, Remains, RemainsBundle},
+        logistics::LogisticsMemory,
+        UnitBundle,
+    },
+};
+
+use super::{
+    chronicle::{Chronicle, ChronicleEntry},
+    generation::GenerationConfig,
+    geometry::{ChunkPos, Facing, MapGeometry, TilePos},
+    lod::{LodGroup, LodSchedule},
+    time::{InGameTime, Season, SimulationTick},
+    vision::FogOfWar,
+    weather::{restore_weather, LocalWeather, Weather, WeatherFront},
+};
+
+/// The version of the save format written by this build of the game.
+///
+/// Increase this whenever the meaning of an existing field changes.
+pub const SAVE_FORMAT_VERSION: u32 = 5;
+
+/// Saves and loads the simulation in response to [`SaveGame`] and [`LoadGame`] events.
+pub(super) struct SaveLoadPlugin;
+
+impl Plugin for SaveLoadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGame>()
+            .add_event::<LoadGame>()
+            // Loading happens at the start of the frame, so every other system sees the loaded world
+            .add_system(load_game.in_base_set(CoreSet::First))
+            .add_system(save_game.in_base_set(CoreSet::Last));
+    }
+}
+
+/// Saves the current state of the simulation to `path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveGame {
+    /// The file to write to, which is overwritten if it already exists
+    pub path: PathBuf,
+}
+
+/// Replaces the current state of the simulation with the save stored at `path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadGame {
+    /// The file to read from
+    pub path: PathBuf,
+}
+
+/// The complete state of the simulation, as stored in a save file.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedWorld {
+    /// The tick on which the game was saved
+    pub tick: u64,
+    /// The seed that the world was generated with, which also seeds the simulation's randomness
+    pub seed: u64,
+    /// The radius of the map
+    pub radius: u32,
+    /// The number of in-game days that had passed, including partial days
+    pub elapsed_days: f32,
+    /// The season at the time of saving
+    pub season: Season,
+    /// The weather, and the fronts drifting across the map
+    pub weather: SavedWeather,
+    /// Every tile of terrain
+    pub tiles: Vec<SavedTile>,
+    /// Every structure, excluding ghosts and previews
+    pub structures: Vec<SavedStructure>,
+    /// The items stored in each structure
+    pub stored_items: Vec<SavedItems>,
+    /// Every unit
+    pub units: Vec<SavedUnit>,
+    /// Every pile of remains
+    pub remains: Vec<SavedRemains>,
+    /// The strength of every signal on every tile where it is present
+    pub signals: Vec<SavedSignal>,
+    /// The written history of the world, from oldest to newest
+    pub chronicle: Vec<ChronicleEntry>,
+    /// Every tile that has ever been seen
+    pub explored: Vec<TilePos>,
+    /// The traces left along the corridors that haulers have used
+    pub hauling_traces: Vec<SavedSignal>,
+    /// How far each level of detail group is towards its next run
+    pub lod_groups: Vec<SavedLodGroup>,
+}
+
+/// A single tile of terrain in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedTile {
+    /// The position of the tile
+    pub tile_pos: TilePos,
+    /// The type of terrain
+    pub terrain: Terrain,
+    /// The height of the terrain
+    pub height: f32,
+    /// How wet the soil is
+    pub soil_moisture: f32,
+    /// How rich the soil is
+    pub fertility: f32,
+    /// What should be built on the tile
+    pub zoning: SavedZoning,
+}
+
+/// The [`Zoning`] of a tile in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SavedZoning {
+    /// No zoning is set
+    None,
+    /// The tile should be kept clear
+    KeepClear,
+    /// The provided structure should be built on the tile
+    Structure {
+        /// The type of structure
+        structure_id: Id<Structure>,
+        /// The direction the structure should face
+        facing: Direction,
+        /// The recipe the structure should start with, if any
+        active_recipe: Option<Id<Recipe>>,
+    },
+}
+
+impl From<&Zoning> for SavedZoning {
+    fn from(zoning: &Zoning) -> Self {
+        match zoning {
+            Zoning::None => SavedZoning::None,
+            Zoning::KeepClear => SavedZoning::KeepClear,
+            Zoning::Structure(clipboard_data) => SavedZoning::Structure {
+                structure_id: clipboard_data.structure_id,
+                facing: clipboard_data.facing.direction,
+                active_recipe: *clipboard_data.active_recipe.recipe_id(),
+            },
+        }
+    }
+}
+
+impl From<&SavedZoning> for Zoning {
+    fn from(saved_zoning: &SavedZoning) -> Self {
+        match *saved_zoning {
+            SavedZoning::None => Zoning::None,
+            SavedZoning::KeepClear => Zoning::KeepClear,
+            SavedZoning::Structure {
+                structure_id,
+                facing,
+                active_recipe,
+            } => Zoning::Structure(ClipboardData {
+                structure_id,
+                facing: Facing { direction: facing },
+                active_recipe: saved_recipe(active_recipe),
+            }),
+        }
+    }
+}
+
+/// A single structure in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedStructure {
+    /// The position of the structure
+    pub tile_pos: TilePos,
+    /// The type of structure
+    pub structure_id: Id<Structure>,
+    /// The direction the structure faces
+    pub facing: Direction,
+    /// The recipe being crafted, if any
+    pub active_recipe: Option<Id<Recipe>>,
+    /// How far along crafting is, if the structure crafts
+    pub crafting_state: Option<CraftingState>,
+    /// The energy the structure has left, if it is an organism
+    pub energy: Option<f32>,
+    /// The name and life story of the structure, if it is an organism
+    pub individual: Option<Individual>,
+}
+
+/// Which inventory of a structure items are stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Storage {
+    /// The [`InputInventory`]
+    Input,
+    /// The [`OutputInventory`]
+    Output,
+}
+
+/// A stack of items stored in a structure in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedItems {
+    /// The position of the structure
+    pub tile_pos: TilePos,
+    /// The inventory the items are stored in
+    pub storage: Storage,
+    /// The type of item
+    pub item_id: Id<Item>,
+    /// The number of items
+    pub count: usize,
+}
+
+/// A single unit in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedUnit {
+    /// The position of the unit
+    pub tile_pos: TilePos,
+    /// The type of unit
+    pub unit_id: Id<Unit>,
+    /// The direction the unit faces
+    pub facing: Direction,
+    /// The item the unit is carrying, if any
+    pub held_item: Option<Id<Item>>,
+    /// The number of items the unit is carrying
+    pub held_count: usize,
+    /// The number of in-game days the unit has been alive
+    pub age: f32,
+    /// The energy the unit has left
+    pub energy: f32,
+    /// The name and life story of the unit
+    pub individual: Option<Individual>,
+}
+
+/// A pile of [`Remains`] in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedRemains {
+    /// The position of the pile
+    pub tile_pos: TilePos,
+    /// The number of corpses in the pile
+    pub corpses: usize,
+}
+
+/// The strength of one signal on one tile in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSignal {
+    /// The type of signal
+    pub signal_type: SignalType,
+    /// The position of the signal
+    pub tile_pos: TilePos,
+    /// The strength of the signal
+    pub strength: f32,
+}
+
+/// The weather in a [`SavedWorld`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedWeather {
+    /// The weather over the whole map
+    pub weather: Weather,
+    /// How long the current spell of weather has lasted
+    pub spell_elapsed: Duration,
+    /// How long since the fronts last drifted
+    pub drift_elapsed: Duration,
+    /// How long since the last chance for a new front to arrive
+    pub arrival_elapsed: Duration,
+    /// The fronts drifting across the map, from oldest to newest
+    pub fronts: Vec<WeatherFront>,
+}
+
+/// The progress of a single [`LodGroup`] in a [`SavedWorld`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedLodGroup {
+    /// The group
+    pub group: LodGroup,
+    /// The number of ticks that had passed since the group last ran
+    pub ticks_since_run: u64,
+    /// The in-game time that had passed since the group last ran
+    pub accumulated: Duration,
+}
+
+impl SavedWorld {
+    /// Captures the current state of the `world`.
+    pub fn capture(world: &mut World) -> SavedWorld {
+        let mut system_state: SystemState<(Res<SimulationTick>, SaveQuery)> =
+            SystemState::new(world);
+        let (simulation_tick, save_query) = system_state.get(world);
+
+        save_query.capture(simulation_tick.get())
+    }
+
+    /// The tick on which the game was saved.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The number of in-game days that had passed when the game was saved, including partial days.
+    pub fn elapsed_days(&self) -> f32 {
+        self.elapsed_days
+    }
+
+    /// Checks that every identifier in this save is found in the provided manifests.
+    ///
+    /// Returns the first unknown identifier found, as a [`SaveParseError::UnknownId`].
+    pub fn check_ids(
+        &self,
+        structure_manifest: &StructureManifest,
+        unit_manifest: &UnitManifest,
+        item_manifest: &ItemManifest,
+        recipe_manifest: &RecipeManifest,
+    ) -> Result<(), SaveParseError> {
+        let check = |known: bool, kind: &'static str, value: u64| match known {
+            true => Ok(()),
+            false => Err(SaveParseError::UnknownId { kind, value }),
+        };
+        let check_structure =
+            |id: Id<Structure>| check(structure_manifest.contains(id), "structure", id.value());
+        let check_item = |id: Id<Item>| check(item_manifest.contains(id), "item", id.value());
+        let check_recipe =
+            |id: Id<Recipe>| check(recipe_manifest.contains(id), "recipe", id.value());
+        let check_signal = |signal_type: SignalType| match signal_type {
+            SignalType::Push(item_id)
+            | SignalType::Pull(item_id)
+            | SignalType::Contains(item_id) => check_item(item_id),
+            SignalType::Work(structure_id) | SignalType::Demolish(structure_id) => {
+                check_structure(structure_id)
+            }
+            SignalType::Alarm | SignalType::Danger | SignalType::Custom(_) => Ok(()),
+        };
+
+        for tile in &self.tiles {
+            if let SavedZoning::Structure {
+                structure_id,
+                active_recipe,
+                ..
+            } = tile.zoning
+            {
+                check_structure(structure_id)?;
+                active_recipe.map_or(Ok(()), check_recipe)?;
+            }
+        }
+
+        for structure in &self.structures {
+            check_structure(structure.structure_id)?;
+            structure.active_recipe.map_or(Ok(()), check_recipe)?;
+        }
+
+        for items in &self.stored_items {
+            check_item(items.item_id)?;
+        }
+
+        for unit in &self.units {
+            check(
+                unit_manifest.contains(unit.unit_id),
+                "unit",
+                unit.unit_id.value(),
+            )?;
+            unit.held_item.map_or(Ok(()), check_item)?;
+        }
+
+        for signal in self.signals.iter().chain(&self.hauling_traces) {
+            check_signal(signal.signal_type)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The [`ActiveRecipe`] of a structure with the saved `active_recipe`.
+fn saved_recipe(active_recipe: Option<Id<Recipe>>) -> ActiveRecipe {
+    match active_recipe {
+        Some(recipe_id) => ActiveRecipe::new(recipe_id),
+        None => ActiveRecipe::default(),
+    }
+}
+
+/// An in-memory copy of the whole simulation, which can be restored any number of times.
+///
+/// Checkpoints let tools, the editor and AI experiments branch and rewind the simulation without touching the disk.
+/// Cloning a checkpoint is cheap: every clone shares the same saved state, which is never modified.
+/// Like saves, checkpoints do not include ghosts, previews, or what each unit was doing at the time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint(Arc<SavedWorld>);
+
+impl Checkpoint {
+    /// The tick on which the checkpoint was taken.
+    pub fn tick(&self) -> u64 {
+        self.0.tick
+    }
+
+    /// The saved state of the world.
+    pub fn saved_world(&self) -> &SavedWorld {
+        &self.0
+    }
+}
+
+impl From<SavedWorld> for Checkpoint {
+    fn from(saved_world: SavedWorld) -> Self {
+        Checkpoint(Arc::new(saved_world))
+    }
+}
+
+/// Records the current state of the `world` in memory.
+pub fn save_snapshot(world: &mut World) -> Checkpoint {
+    SavedWorld::capture(world).into()
+}
+
+/// Rewinds the `world` to the state recorded in the `checkpoint`.
+///
+/// Along with the terrain, structures, units and signals, this rewinds the tick, the calendar and season,
+/// the weather and its fronts, and the crafting progress, energy and age of every organism.
+/// Random choices made for the world as a whole, like the weather, are seeded from the tick, so they repeat exactly.
+/// Those made for individual organisms are also seeded from their entity, which is new after rewinding, so they do not.
+///
+/// The world is replaced immediately, so this should be called between frames rather than from within a system.
+pub fn restore_snapshot(world: &mut World, checkpoint: &Checkpoint) {
+    let mut system_state: SystemState<WorldRestorer> = SystemState::new(world);
+    system_state
+        .get_mut(world)
+        .restore(checkpoint.saved_world());
+    system_state.apply(world);
+}
+
+/// The position of `direction` in [`Direction::ALL_DIRECTIONS`], used to sort saved units.
+fn direction_index(direction: Direction) -> usize {
+    Direction::ALL_DIRECTIONS
+        .iter()
+        .position(|&candidate| candidate == direction)
+        .unwrap()
+}
+
+/// The contents of a save file: the version of the format it was written in, followed by the saved world.
+///
+/// The version comes first, so that it can be checked before trying to read the rest of the file.
+#[derive(Serialize, Deserialize)]
+struct SaveFile<W> {
+    /// The [`SAVE_FORMAT_VERSION`] that the file was written with
+    version: u32,
+    /// The saved world, which is skipped over using [`IgnoredAny`] when only the version is needed
+    world: W,
+}
+
+impl Display for SavedWorld {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let save_file = SaveFile {
+            version: SAVE_FORMAT_VERSION,
+            world: self,
+        };
+
+        // Deeper levels are written on a single line, so that each tile, structure and unit gets one line of its own
+        let pretty_config = PrettyConfig::new().depth_limit(3);
+        let text =
+            ron::ser::to_string_pretty(&save_file, pretty_config).map_err(|_| std::fmt::Error)?;
+
+        writeln!(f, "{text}")
+    }
+}
+
+/// A save file could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveParseError {
+    /// The file was written in a format that this build of the game cannot read.
+    UnsupportedVersion {
+        /// The version found in the file
+        found: u32,
+    },
+    /// The file is not a valid save.
+    Invalid(SpannedError),
+    /// The file refers to something that is not defined in this game's manifests.
+    UnknownId {
+        /// The kind of thing that the ID refers to, such as a structure or an item
+        kind: &'static str,
+        /// The raw value of the unknown ID
+        value: u64,
+    },
+}
+
+impl Display for SaveParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveParseError::UnsupportedVersion { found } => write!(
+                f,
+                "Save file has version {found}, but only version {SAVE_FORMAT_VERSION} is supported"
+            ),
+            SaveParseError::Invalid(error) => write!(f, "Could not read save file: {error}"),
+            SaveParseError::UnknownId { kind, value } => {
+                write!(f, "Save file refers to an unknown {kind} with ID {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveParseError {}
+
+impl FromStr for SavedWorld {
+    type Err = SaveParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let header: SaveFile<IgnoredAny> = ron::from_str(s).map_err(SaveParseError::Invalid)?;
+        if header.version != SAVE_FORMAT_VERSION {
+            return Err(SaveParseError::UnsupportedVersion {
+                found: header.version,
+            });
+        }
+
+        let save_file: SaveFile<SavedWorld> = ron::from_str(s).map_err(SaveParseError::Invalid)?;
+        Ok(save_file.world)
+    }
+}
+
+/// The data needed to capture a [`SavedWorld`].
+#[derive(SystemParam)]
+pub struct SaveQuery<'w, 's> {
+    /// Every tile of terrain
+    terrain_query: Query<
+        'w,
+        's,
+        (
+            &'static TilePos,
+            &'static Terrain,
+            &'static SoilMoisture,
+            &'static Fertility,
+            &'static Zoning,
+        ),
+    >,
+    /// Every structure, along with its recipe, inventories and progress
+    structure_query: Query<
+        'w,
+        's,
+        (
+            &'static TilePos,
+            &'static Id<Structure>,
+            &'static Facing,
+            Option<&'static ActiveRecipe>,
+            Option<&'static CraftingState>,
+            Option<&'static InputInventory>,
+            Option<&'static OutputInventory>,
+            Option<&'static EnergyPool>,
+            Option<&'static Individual>,
+        ),
+        (Without<Ghost>, Without<Preview>),
+    >,
+    /// Every unit, along with what it is carrying and how it is faring
+    unit_query: Query<
+        'w,
+        's,
+        (
+            &'static TilePos,
+            &'static Id<Unit>,
+            &'static Facing,
+            &'static UnitInventory,
+            &'static Age,
+            &'static EnergyPool,
+            Option<&'static Individual>,
+        ),
+    >,
+    /// Every pile of remains, along with the corpses in it
+    remains_query: Query<'w, 's, (&'static TilePos, &'static OutputInventory), With<Remains>>,
+    /// The seed of the world
+    generation_config: Res<'w, GenerationConfig>,
+    /// The in-game calendar
+    in_game_time: Res<'w, InGameTime>,
+    /// The current season
+    season: Res<'w, Season>,
+    /// The weather over the map
+    local_weather: LocalWeather<'w>,
+    /// The size of the map and the height of each tile
+    map_geometry: Res<'w, MapGeometry>,
+    /// The signals on every tile
+    signals: Res<'w, Signals>,
+    /// The history of the world
+    chronicle: Res<'w, Chronicle>,
+    /// The tiles that have been seen
+    fog_of_war: Res<'w, FogOfWar>,
+    /// The traces left by haulers
+    logistics_memory: Res<'w, LogisticsMemory>,
+    /// The progress of each level of detail group
+    lod_schedule: Res<'w, LodSchedule>,
+}
+
+impl<'w, 's> SaveQuery<'w, 's> {
+    /// Captures the current state of the world, labelling it with the provided `tick`.
+    ///
+    /// Records are sorted, so that saving the same world twice produces identical files.
+    pub fn capture(&self, tick: u64) -> SavedWorld {
+        let mut saved_world = SavedWorld {
+            tick,
+            seed: self.generation_config.seed(),
+            radius: self.map_geometry.radius,
+            elapsed_days: self.in_game_time.elapsed_days(),
+            season: *self.season,
+            weather: self.local_weather.record(),
+            chronicle: self.chronicle.entries().to_vec(),
+            explored: self.fog_of_war.explored().collect(),
+            lod_groups: self.lod_schedule.record(),
+            ..Default::default()
+        };
+
+        for (&tile_pos, &terrain, soil_moisture, fertility, zoning) in self.terrain_query.iter() {
+            saved_world.tiles.push(SavedTile {
+                tile_pos,
+                terrain,
+                height: self
+                    .map_geometry
+                    .height_index
+                    .get(&tile_pos)
+                    .copied()
+                    .unwrap_or_default(),
+                soil_moisture: soil_moisture.0,
+                fertility: fertility.0,
+                zoning: zoning.into(),
+            });
+        }
+
+        for (
+            &tile_pos,
+            &structure_id,
+            facing,
+            maybe_recipe,
+            maybe_crafting_state,
+            maybe_input,
+            maybe_output,
+            maybe_energy_pool,
+            maybe_individual,
+        ) in self.structure_query.iter()
+        {
+            saved_world.structures.push(SavedStructure {
+                tile_pos,
+                structure_id,
+                facing: facing.direction,
+                active_recipe: maybe_recipe.and_then(|recipe| *recipe.recipe_id()),
+                crafting_state: maybe_crafting_state.cloned(),
+                energy: maybe_energy_pool.map(|energy_pool| energy_pool.current().0),
+                individual: maybe_individual.cloned(),
+            });
+
+            let inventories = maybe_input
+                .map(|input| (Storage::Input, &input.inventory))
+                .into_iter()
+                .chain(maybe_output.map(|output| (Storage::Output, &output.inventory)));
+            for (storage, inventory) in inventories {
+                for slot in inventory.iter().filter(|slot| slot.count() > 0) {
+                    saved_world.stored_items.push(SavedItems {
+                        tile_pos,
+                        storage,
+                        item_id: slot.item_id(),
+                        count: slot.count(),
+                    });
+                }
+            }
+        }
+
+        for (&tile_pos, &unit_id, facing, unit_inventory, age, energy_pool, maybe_individual) in
+            self.unit_query.iter()
+        {
+            saved_world.units.push(SavedUnit {
+                tile_pos,
+                unit_id,
+                facing: facing.direction,
+                held_item: unit_inventory.held_item,
+                held_count: unit_inventory.count(),
+                age: age.in_days(),
+                energy: energy_pool.current().0,
+                individual: maybe_individual.cloned(),
+            });
+        }
+
+        for (&tile_pos, output_inventory) in self.remains_query.iter() {
+            saved_world.remains.push(SavedRemains {
+                tile_pos,
+                corpses: output_inventory.item_count(Id::corpse()),
+            });
+        }
+
+        for (signal_type, tile_pos, strength) in self.signals.iter_strengths() {
+            saved_world.signals.push(SavedSignal {
+                signal_type,
+                tile_pos,
+                strength: strength.value(),
+            });
+        }
+
+        let position_key = |tile_pos: &TilePos| (tile_pos.hex.x, tile_pos.hex.y);
+        saved_world
+            .tiles
+            .sort_by_key(|tile| position_key(&tile.tile_pos));
+        saved_world
+            .structures
+            .sort_by_key(|structure| position_key(&structure.tile_pos));
+        // Sorting is stable, so slots within each inventory keep their order
+        saved_world.stored_items.sort_by_key(|items| {
+            (
+                position_key(&items.tile_pos),
+                items.storage == Storage::Output,
+            )
+        });
+        saved_world.units.sort_by_key(|unit| {
+            (
+                position_key(&unit.tile_pos),
+                unit.unit_id,
+                direction_index(unit.facing),
+                unit.held_item,
+                // Ages are never negative, so their bits sort in the same order as their values
+                unit.age.to_bits(),
+            )
+        });
+        saved_world
+            .remains
+            .sort_by_key(|remains| position_key(&remains.tile_pos));
+        for (signal_type, tile_pos, strength) in self.logistics_memory.iter_traces() {
+            saved_world.hauling_traces.push(SavedSignal {
+                signal_type,
+                tile_pos,
+                strength: strength.value(),
+            });
+        }
+
+        saved_world
+            .signals
+            .sort_by_key(|signal| (signal.signal_type, position_key(&signal.tile_pos)));
+        saved_world
+            .hauling_traces
+            .sort_by_key(|trace| (trace.signal_type, position_key(&trace.tile_pos)));
+        saved_world.explored.sort_by_key(position_key);
+
+        saved_world
+    }
+}
+
+/// Writes the world to disk whenever a [`SaveGame`] event is sent.
+fn save_game(
+    mut save_events: EventReader<SaveGame>,
+    simulation_tick: Res<SimulationTick>,
+    save_query: SaveQuery,
+) {
+    for event in save_events.iter() {
+        let contents = save_query.capture(simulation_tick.get()).to_string();
+        let path = event.path.clone();
+
+        IoTaskPool::get()
+            .spawn(async move {
+                let result = match path.parent() {
+                    Some(folder) => std::fs::create_dir_all(folder),
+                    None => Ok(()),
+                }
+                .and_then(|_| std::fs::write(&path, contents));
+
+                match result {
+                    Ok(()) => info!("Saved game to {}", path.display()),
+                    Err(error) => error!("Could not save game to {}: {error}", path.display()),
+                }
+            })
+            .detach();
+    }
+}
+
+/// Fills the inventory of the structure at `tile_pos` with saved items.
+///
+/// This must be queued after the structure is spawned.
+struct RestoreItemsCommand {
+    /// The position of the structure
+    tile_pos: TilePos,
+    /// The inventory to fill
+    storage: Storage,
+    /// The items to add
+    item_count: ItemCount,
+}
+
+impl Command for RestoreItemsCommand {
+    fn write(self, world: &mut World) {
+        let maybe_entity = world
+            .resource::<MapGeometry>()
+            .structure_index
+            .get(&self.tile_pos)
+            .copied();
+
+        let structure_entity = match maybe_entity {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        world.resource_scope(|world, item_manifest: Mut<ItemManifest>| {
+            let mut entity_mut = world.entity_mut(structure_entity);
+            let maybe_inventory = match self.storage {
+                Storage::Input => entity_mut
+                    .get_mut::<InputInventory>()
+                    .map(|input| input.map_unchanged(|input| &mut input.inventory)),
+                Storage::Output => entity_mut
+                    .get_mut::<OutputInventory>()
+                    .map(|output| output.map_unchanged(|output| &mut output.inventory)),
+            };
+
+            let result = maybe_inventory
+                .map(|mut inventory| inventory.try_add_item(&self.item_count, &item_manifest));
+            if !matches!(result, Some(Ok(()))) {
+                warn!(
+                    "Could not restore {} to the {:?} inventory at {}",
+                    self.item_count, self.storage, self.tile_pos
+                );
+            }
+        });
+    }
+}
+
+/// Gives the structure at `tile_pos` back its saved crafting progress, energy, name and life story.
+///
+/// This must be queued after the structure is spawned.
+struct RestoreStructureCommand {
+    /// The position of the structure
+    tile_pos: TilePos,
+    /// How far along crafting was, if the structure crafts
+    crafting_state: Option<CraftingState>,
+    /// The energy the structure had left, if it is an organism
+    energy: Option<f32>,
+    /// The saved identity of the structure, if it is an organism
+    individual: Option<Individual>,
+}
+
+impl Command for RestoreStructureCommand {
+    fn write(self, world: &mut World) {
+        let maybe_entity = world
+            .resource::<MapGeometry>()
+            .structure_index
+            .get(&self.tile_pos)
+            .copied();
+
+        let structure_entity = match maybe_entity {
+            Some(entity) => entity,
+            None => {
+                warn!(
+                    "Could not restore the structure at {}, as there is no structure there",
+                    self.tile_pos
+                );
+                return;
+            }
+        };
+
+        let mut entity_mut = world.entity_mut(structure_entity);
+        if let Some(crafting_state) = self.crafting_state {
+            entity_mut.insert(crafting_state);
+        }
+        if let (Some(energy), Some(mut energy_pool)) =
+            (self.energy, entity_mut.get_mut::<EnergyPool>())
+        {
+            energy_pool.set_current(Energy(energy));
+        }
+        if let Some(individual) = self.individual {
+            entity_mut.insert(individual);
+        }
+    }
+}
+
+/// Sets the energy of a restored unit, which would otherwise start out full.
+///
+/// This must be queued after the unit is spawned.
+struct RestoreEnergyCommand {
+    /// The restored unit
+    entity: Entity,
+    /// The energy the unit had left
+    energy: f32,
+}
+
+impl Command for RestoreEnergyCommand {
+    fn write(self, world: &mut World) {
+        if let Some(mut energy_pool) = world.get_mut::<EnergyPool>(self.entity) {
+            energy_pool.set_current(Energy(self.energy));
+        }
+    }
+}
+
+/// Replaces the weather with the saved weather.
+struct RestoreWeatherCommand {
+    /// The weather to restore
+    saved_weather: SavedWeather,
+}
+
+impl Command for RestoreWeatherCommand {
+    fn write(self, world: &mut World) {
+        restore_weather(world, &self.saved_weather);
+    }
+}
+
+/// The data needed to replace the world with a [`SavedWorld`].
+#[derive(SystemParam)]
+pub struct WorldRestorer<'w, 's> {
+    /// Used to despawn the old world and spawn the saved one
+    commands: Commands<'w, 's>,
+    /// Everything that is replaced by the saved world
+    existing_query: Query<
+        'w,
+        's,
+        Entity,
+        Or<(
+            With<Terrain>,
+            With<Id<Structure>>,
+            With<Id<Unit>>,
+            With<Remains>,
+        )>,
+    >,
+    /// The map, which is rebuilt from scratch
+    map_geometry: ResMut<'w, MapGeometry>,
+    /// The signals, which are replaced by the saved ones
+    signals: ResMut<'w, Signals>,
+    /// The written history of the world
+    chronicle: ResMut<'w, Chronicle>,
+    /// The tiles that have been seen, which are replaced by the saved ones
+    fog_of_war: ResMut<'w, FogOfWar>,
+    /// The traces left by haulers, which are replaced by the saved ones
+    logistics_memory: ResMut<'w, LogisticsMemory>,
+    /// The progress of each level of detail group, which picks up where the save left off
+    lod_schedule: ResMut<'w, LodSchedule>,
+    /// The current tick, which is rewound to the saved one
+    simulation_tick: ResMut<'w, SimulationTick>,
+    /// The seed of the world, which is replaced by the saved one
+    generation_config: ResMut<'w, GenerationConfig>,
+    /// The in-game calendar, which is rewound to the saved date
+    in_game_time: ResMut<'w, InGameTime>,
+    /// The current season, which is rewound to the saved one
+    season: ResMut<'w, Season>,
+    /// The data for each kind of structure, used to check the saved IDs
+    structure_manifest: Res<'w, StructureManifest>,
+    /// The data for each kind of unit
+    unit_manifest: Res<'w, UnitManifest>,
+    /// The data for each kind of item, used to check the saved IDs and to fill piles of remains
+    item_manifest: Res<'w, ItemManifest>,
+    /// The data for each recipe, used to check the saved IDs
+    recipe_manifest: Res<'w, RecipeManifest>,
+}
+
+impl<'w, 's> WorldRestorer<'w, 's> {
+    /// Checks that every identifier in the `saved_world` is known to this game.
+    fn check_ids(&self, saved_world: &SavedWorld) -> Result<(), SaveParseError> {
+        saved_world.check_ids(
+            &self.structure_manifest,
+            &self.unit_manifest,
+            &self.item_manifest,
+            &self.recipe_manifest,
+        )
+    }
+
+    /// Despawns the current world, and queues commands to replace it with the `saved_world`.
+    fn restore(&mut self, saved_world: &SavedWorld) {
+        for entity in self.existing_query.iter() {
+            self.commands.entity(entity).despawn_recursive();
+        }
+
+        *self.map_geometry = MapGeometry::new(saved_world.radius);
+        *self.signals = Signals::default();
+        self.chronicle.restore(saved_world.chronicle.clone());
+        self.fog_of_war
+            .restore(saved_world.explored.iter().copied());
+        self.logistics_memory
+            .restore_traces(saved_world.hauling_traces.iter().map(|trace| {
+                (
+                    trace.signal_type,
+                    trace.tile_pos,
+                    SignalStrength::new(trace.strength),
+                )
+            }));
+        self.lod_schedule.restore(&saved_world.lod_groups);
+        self.simulation_tick.set(saved_world.tick);
+        self.generation_config.set_seed(saved_world.seed);
+        self.in_game_time.set_elapsed_days(saved_world.elapsed_days);
+        *self.season = saved_world.season;
+        self.commands.add(RestoreWeatherCommand {
+            saved_weather: saved_world.weather.clone(),
+        });
+
+        for tile in &saved_world.tiles {
+            // Chunks are generated whole, so any saved tile means its chunk should not be generated again
+            self.map_geometry
+                .generated_chunks
+                .insert(ChunkPos::containing(tile.tile_pos));
+            self.map_geometry
+                .height_index
+                .insert(tile.tile_pos, tile.height);
+            if tile.terrain == Terrain::Water {
+                self.map_geometry.open_water.insert(tile.tile_pos);
+            }
+
+            let terrain_entity = self
+                .commands
+                .spawn(TerrainBundle::new(tile.terrain, tile.tile_pos))
+                .insert((
+                    SoilMoisture(tile.soil_moisture),
+                    Fertility(tile.fertility),
+                    Zoning::from(&tile.zoning),
+                ))
+                .id();
+            self.map_geometry
+                .terrain_index
+                .insert(tile.tile_pos, terrain_entity);
+        }
+
+        for structure in &saved_world.structures {
+            self.commands.spawn_structure(
+                structure.tile_pos,
+                ClipboardData {
+                    structure_id: structure.structure_id,
+                    facing: Facing {
+                        direction: structure.facing,
+                    },
+                    active_recipe: saved_recipe(structure.active_recipe),
+                },
+            );
+        }
+
+        // Commands are applied in order, so each structure exists by the time its progress and items are restored
+        for structure in &saved_world.structures {
+            self.commands.add(RestoreStructureCommand {
+                tile_pos: structure.tile_pos,
+                crafting_state: structure.crafting_state.clone(),
+                energy: structure.energy,
+                individual: structure.individual.clone(),
+            });
+        }
+
+        for items in &saved_world.stored_items {
+            self.commands.add(RestoreItemsCommand {
+                tile_pos: items.tile_pos,
+                storage: items.storage,
+                item_count: ItemCount::new(items.item_id, items.count),
+            });
+        }
+
+        for unit in &saved_world.units {
+            let mut unit_commands = self.commands.spawn(UnitBundle::new(
+                unit.unit_id,
+                unit.tile_pos,
+                self.unit_manifest.get(unit.unit_id).clone(),
+            ));
+            unit_commands
+                .insert(Facing {
+                    direction: unit.facing,
+                })
+                .insert(match unit.held_item {
+                    Some(item_id) => UnitInventory::holding(item_id, unit.held_count),
+                    None => UnitInventory::default(),
+                })
+                .insert(Age::from_days(unit.age));
+
+            if let Some(individual) = &unit.individual {
+                unit_commands.insert(individual.clone());
+            }
+
+            let entity = unit_commands.id();
+            self.commands.add(RestoreEnergyCommand {
+                entity,
+                energy: unit.energy,
+            });
+        }
+
+        for remains in &saved_world.remains {
+            let remains_entity = self
+                .commands
+                .spawn(RemainsBundle::with_corpses(
+                    remains.tile_pos,
+                    remains.corpses,
+                    &self.item_manifest,
+                ))
+                .id();
+            self.map_geometry
+                .remains_index
+                .insert(remains.tile_pos, remains_entity);
+        }
+
+        for signal in &saved_world.signals {
+            self.signals.add_signal(
+                signal.signal_type,
+                signal.tile_pos,
+                SignalStrength::new(signal.strength),
+            );
+        }
+    }
+}
+
+/// Replaces the world with a saved one whenever a [`LoadGame`] event is sent.
+///
+/// If the save cannot be read, or refers to anything that this game does not define, the current world is left untouched.
+fn load_game(mut load_events: EventReader<LoadGame>, mut world_restorer: WorldRestorer) {
+    // Only the most recent request matters, as each load replaces the whole world
+    let event = match load_events.iter().last() {
+        Some(event) => event,
+        None => return,
+    };
+
+    let saved_world = match std::fs::read_to_string(&event.path)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            contents
+                .parse::<SavedWorld>()
+                .and_then(|saved_world| world_restorer.check_ids(&saved_world).map(|_| saved_world))
+                .map_err(|error| error.to_string())
+        }) {
+        Ok(saved_world) => saved_world,
+        Err(error) => {
+            error!("Could not load game from {}: {error}", event.path.display());
+            return;
+        }
+    };
+
+    world_restorer.restore(&saved_world);
+    info!("Loaded game from {}", event.path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy::utils::HashMap;
+
+    fn example_world() -> SavedWorld {
+        SavedWorld {
+            tick: 1234,
+            seed: 2378,
+            radius: 3,
+            elapsed_days: 12.437_5,
+            season: Season::Summer,
+            weather: SavedWeather {
+                weather: Weather::Rain,
+                spell_elapsed: Duration::from_nanos(12_345_678_901),
+                drift_elapsed: Duration::from_millis(1500),
+                arrival_elapsed: Duration::ZERO,
+                fronts: vec![
+                    WeatherFront {
+                        weather: Weather::Storm,
+                        center: TilePos::new(-4, 2),
+                        radius: 3,
+                        direction: Direction::TopLeft,
+                    },
+                    WeatherFront {
+                        weather: Weather::Drought,
+                        center: TilePos::new(5, 0),
+                        radius: 7,
+                        direction: Direction::Bottom,
+                    },
+                ],
+            },
+            tiles: vec![
+                SavedTile {
+                    tile_pos: TilePos::new(0, 0),
+                    terrain: Terrain::Plain,
+                    height: 2.,
+                    soil_moisture: 0.25,
+                    fertility: 0.875,
+                    zoning: SavedZoning::Structure {
+                        structure_id: Id::new(17),
+                        facing: Direction::BottomLeft,
+                        active_recipe: Some(Id::new(99)),
+                    },
+                },
+                SavedTile {
+                    tile_pos: TilePos::new(-1, 1),
+                    terrain: Terrain::Water,
+                    height: 1.5,
+                    soil_moisture: 1.,
+                    fertility: 0.,
+                    zoning: SavedZoning::KeepClear,
+                },
+            ],
+            structures: vec![SavedStructure {
+                tile_pos: TilePos::new(0, 0),
+                structure_id: Id::new(17),
+                facing: Direction::BottomLeft,
+                active_recipe: Some(Id::new(99)),
+                crafting_state: Some(CraftingState::InProgress {
+                    progress: Duration::from_nanos(1_234_567_891),
+                    required: Duration::from_secs(4),
+                    work_required: true,
+                    worker_present: false,
+                }),
+                energy: Some(37.25),
+                individual: Some(Individual {
+                    name: "Old Tal".to_string(),
+                    age: 321.5,
+                    produced: 12,
+                }),
+            }],
+            stored_items: vec![SavedItems {
+                tile_pos: TilePos::new(0, 0),
+                storage: Storage::Output,
+                item_id: Id::new(5),
+                count: 3,
+            }],
+            units: vec![
+                SavedUnit {
+                    tile_pos: TilePos::new(1, -1),
+                    unit_id: Id::new(8),
+                    facing: Direction::Top,
+                    held_item: None,
+                    held_count: 0,
+                    age: 3.5,
+                    energy: 80.,
+                    individual: Some(Individual::new("Kari".to_string())),
+                },
+                SavedUnit {
+                    tile_pos: TilePos::new(2, 0),
+                    unit_id: Id::new(8),
+                    facing: Direction::TopRight,
+                    held_item: Some(Id::new(5)),
+                    held_count: 2,
+                    age: 0.1,
+                    energy: 12.345_678,
+                    individual: None,
+                },
+            ],
+            remains: vec![SavedRemains {
+                tile_pos: TilePos::new(2, 0),
+                corpses: 3,
+            }],
+            signals: vec![
+                SavedSignal {
+                    signal_type: SignalType::Pull(Id::new(5)),
+                    tile_pos: TilePos::new(0, 0),
+                    strength: 0.123_456_79,
+                },
+                SavedSignal {
+                    signal_type: SignalType::Alarm,
+                    tile_pos: TilePos::new(-3, 2),
+                    strength: 40.,
+                },
+            ],
+            chronicle: vec![ChronicleEntry {
+                year: 2,
+                season: Season::Summer,
+                text: "The great fire of year 2 destroyed 6 structures in the east".to_string(),
+            }],
+            explored: vec![TilePos::new(-1, 1), TilePos::new(0, 0)],
+            hauling_traces: vec![SavedSignal {
+                signal_type: SignalType::Push(Id::new(5)),
+                tile_pos: TilePos::new(-1, 1),
+                strength: 0.05,
+            }],
+            lod_groups: vec![SavedLodGroup {
+                group: LodGroup::Soil,
+                ticks_since_run: 7,
+                accumulated: Duration::from_millis(112),
+            }],
+        }
+    }
+
+    #[test]
+    fn checkpoints_share_their_saved_world() {
+        let checkpoint = Checkpoint::from(example_world());
+        let branch = checkpoint.clone();
+
+        assert!(Arc::ptr_eq(&checkpoint.0, &branch.0));
+        assert_eq!(branch.tick(), 1234);
+    }
+
+    #[test]
+    fn saves_round_trip_through_text() {
+        let saved_world = example_world();
+        let parsed: SavedWorld = saved_world.to_string().parse().unwrap();
+        assert_eq!(parsed, saved_world);
+    }
+
+    #[test]
+    fn every_signal_type_round_trips() {
+        let signal_types = [
+            SignalType::Push(Id::new(1)),
+            SignalType::Pull(Id::new(2)),
+            SignalType::Contains(Id::new(3)),
+            SignalType::Work(Id::new(4)),
+            SignalType::Demolish(Id::new(5)),
+            SignalType::Alarm,
+            SignalType::Danger,
+            SignalType::Custom(Id::new(6)),
+        ];
+
+        for signal_type in signal_types {
+            let text = ron::to_string(&signal_type).unwrap();
+            assert_eq!(ron::from_str::<SignalType>(&text).unwrap(), signal_type);
+        }
+    }
+
+    #[test]
+    fn every_crafting_state_round_trips() {
+        let crafting_states = [
+            None,
+            Some(CraftingState::NeedsInput),
+            Some(CraftingState::InProgress {
+                progress: Duration::from_nanos(1),
+                required: Duration::from_secs(30),
+                work_required: false,
+                worker_present: true,
+            }),
+            Some(CraftingState::FullAndBlocked),
+            Some(CraftingState::RecipeComplete),
+            Some(CraftingState::Overproduction),
+            Some(CraftingState::NoRecipe),
+        ];
+
+        for crafting_state in crafting_states {
+            let text = ron::to_string(&crafting_state).unwrap();
+            assert_eq!(
+                ron::from_str::<Option<CraftingState>>(&text).unwrap(),
+                crafting_state
+            );
+        }
+    }
+
+    #[test]
+    fn saves_start_with_their_version() {
+        let text = example_world().to_string();
+        assert!(text.starts_with(&format!("(\n    version: {SAVE_FORMAT_VERSION},\n")));
+    }
+
+    #[test]
+    fn ids_are_saved_as_their_value() {
+        let text = ron::to_string(&SignalType::Pull(Id::new(5))).unwrap();
+        assert_eq!(text, "Pull(5)");
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        // The rest of the file is not read, as its format may have changed
+        let error = "(version: 999, world: (tick: \"later\"))"
+            .parse::<SavedWorld>()
+            .unwrap_err();
+        assert_eq!(error, SaveParseError::UnsupportedVersion { found: 999 });
+    }
+
+    #[test]
+    fn malformed_saves_are_reported() {
+        let error = format!("(version: {SAVE_FORMAT_VERSION}, world: (tick: \"later\"))")
+            .parse::<SavedWorld>()
+            .unwrap_err();
+        assert!(matches!(error, SaveParseError::Invalid(_)));
+
+        let error = "tick 1".parse::<SavedWorld>().unwrap_err();
+        assert!(matches!(error, SaveParseError::Invalid(_)));
+    }
+
+    #[test]
+    fn zoning_round_trips() {
+        let zonings = [
+            Zoning::None,
+            Zoning::KeepClear,
+            Zoning::Structure(ClipboardData {
+                structure_id: Id::new(17),
+                facing: Facing {
+                    direction: Direction::TopRight,
+                },
+                active_recipe: ActiveRecipe::new(Id::new(99)),
+            }),
+        ];
+
+        for zoning in zonings {
+            assert_eq!(Zoning::from(&SavedZoning::from(&zoning)), zoning);
+        }
+    }
+
+    #[test]
+    fn unknown_ids_are_rejected() {
+        let structure_manifest = StructureManifest::default();
+        let unit_manifest = UnitManifest::default();
+        let item_manifest = ItemManifest::new(HashMap::default());
+        let recipe_manifest = RecipeManifest::new(HashMap::default());
+        let check_ids = |saved_world: &SavedWorld| {
+            saved_world.check_ids(
+                &structure_manifest,
+                &unit_manifest,
+                &item_manifest,
+                &recipe_manifest,
+            )
+        };
+
+        assert_eq!(check_ids(&SavedWorld::default()), Ok(()));
+
+        let error = check_ids(&example_world()).unwrap_err();
+        assert_eq!(
+            error,
+            SaveParseError::UnknownId {
+                kind: "structure",
+                value: 17
+            }
+        );
+
+        let saved_world = SavedWorld {
+            signals: vec![SavedSignal {
+                signal_type: SignalType::Pull(Id::new(5)),
+                tile_pos: TilePos::ORIGIN,
+                strength: 1.,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            check_ids(&saved_world),
+            Err(SaveParseError::UnknownId {
+                kind: "item",
+                value: 5
+            })
+        );
+    }
+}
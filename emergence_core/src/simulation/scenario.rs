@@ -0,0 +1,339 @@
+//! Scenarios script events that happen over the course of a game, such as swarms of pests arriving.
+//!
+//! Each scenario is defined in its own RON file in `assets/scenarios/`, and the [`DEFAULT_SCENARIO`] is played.
+
+use crate::bevy::prelude::*;
+use hexx::{shapes::hexagon, Hex};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    manifest::{
+        definitions::{load_scenario, loaded_or_built_in, scenarios_directory},
+        Id, Unit, UnitManifest,
+    },
+    units::UnitBundle,
+};
+
+use super::{
+    alerts::Alert,
+    chronicle::HistoricalEvent,
+    geometry::{MapGeometry, TilePos},
+    jitter::{Jitter, JitterStream},
+    time::{InGameTime, Season, DAYS_PER_SEASON},
+};
+
+/// The name of the scenario that is played, stored in `assets/scenarios/default.ron`.
+pub const DEFAULT_SCENARIO: &str = "default";
+
+/// When a [`ScheduledEvent`] should occur.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum EventTrigger {
+    /// Occurs once, at the start of the provided day.
+    OnDay(u32),
+    /// Occurs at the start of `first_day`, and then again every `period` days.
+    EveryNDays {
+        /// The first day on which this event occurs
+        first_day: u32,
+        /// The number of days between each occurrence
+        period: u32,
+    },
+    /// Occurs on the first day of every occurrence of the provided season.
+    StartOfSeason(Season),
+}
+
+impl EventTrigger {
+    /// Should this event fire on the provided `day`?
+    pub fn fires_on(&self, day: u32) -> bool {
+        match *self {
+            EventTrigger::OnDay(trigger_day) => day == trigger_day,
+            EventTrigger::EveryNDays { first_day, period } => {
+                day >= first_day && (day - first_day).is_multiple_of(period.max(1))
+            }
+            EventTrigger::StartOfSeason(season) => {
+                day.is_multiple_of(DAYS_PER_SEASON) && Season::on_day(day) == season
+            }
+        }
+    }
+}
+
+/// A population of units that arrives at the edge of the map in several waves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnWaves {
+    /// The type of unit to spawn
+    pub unit_id: Id<Unit>,
+    /// The number of units spawned in each wave
+    pub units_per_wave: usize,
+    /// The total number of waves
+    pub n_waves: usize,
+    /// The number of seconds between each wave
+    pub seconds_between_waves: f32,
+}
+
+/// An event that is scripted to occur by the [`Scenario`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledEvent {
+    /// The name of the event, shown to the player when it occurs
+    pub name: String,
+    /// When this event occurs
+    pub trigger: EventTrigger,
+    /// The units that arrive as part of this event
+    pub spawn_waves: SpawnWaves,
+}
+
+/// A view of the map that the camera passes through while a [`Scenario`] is introduced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntroWaypoint {
+    /// The point on the ground that the camera looks at
+    pub translation: Vec3,
+    /// The distance from the camera to the point it is looking at
+    pub distance: f32,
+    /// How long the camera takes to travel to this waypoint from the previous one, in seconds
+    ///
+    /// This is ignored for the first waypoint.
+    pub seconds_from_previous: f32,
+}
+
+/// The set of scripted events that occur over the course of a game.
+#[derive(Resource, Debug, Clone)]
+pub struct Scenario {
+    /// The events in this scenario
+    pub events: Vec<ScheduledEvent>,
+    /// The views the camera passes through when this scenario begins, to introduce the map
+    ///
+    /// If this is empty, the camera is left where it is.
+    pub intro_pan: Vec<IntroWaypoint>,
+    /// The last day on which events were checked
+    last_checked_day: Option<u32>,
+}
+
+impl Scenario {
+    /// Creates a new scenario from the provided list of events.
+    pub fn new(events: Vec<ScheduledEvent>) -> Self {
+        Scenario {
+            events,
+            intro_pan: Vec::new(),
+            last_checked_day: None,
+        }
+    }
+
+    /// Pans the camera through the provided waypoints when this scenario begins.
+    pub fn with_intro_pan(mut self, intro_pan: Vec<IntroWaypoint>) -> Self {
+        self.intro_pan = intro_pan;
+        self
+    }
+}
+
+impl FromWorld for Scenario {
+    fn from_world(_world: &mut World) -> Self {
+        loaded_or_built_in(
+            load_scenario(&scenarios_directory(), DEFAULT_SCENARIO),
+            Scenario::built_in,
+        )
+    }
+}
+
+impl Scenario {
+    /// The scenario used when the scenario files cannot be found, which matches the shipped default scenario.
+    pub fn built_in() -> Self {
+        Scenario::new(vec![
+            ScheduledEvent {
+                name: "A locust swarm".to_string(),
+                trigger: EventTrigger::OnDay(30),
+                spawn_waves: SpawnWaves {
+                    unit_id: Id::locust(),
+                    units_per_wave: 8,
+                    n_waves: 3,
+                    seconds_between_waves: 10.,
+                },
+            },
+            ScheduledEvent {
+                name: "A migrating herd of beetles".to_string(),
+                trigger: EventTrigger::StartOfSeason(Season::Autumn),
+                spawn_waves: SpawnWaves {
+                    unit_id: Id::beetle(),
+                    units_per_wave: 4,
+                    n_waves: 2,
+                    seconds_between_waves: 20.,
+                },
+            },
+        ])
+        // Start with a view of the whole map, then swoop down to the colony
+        .with_intro_pan(vec![
+            IntroWaypoint {
+                translation: Vec3::ZERO,
+                distance: 150.,
+                seconds_from_previous: 0.,
+            },
+            IntroWaypoint {
+                translation: Vec3::ZERO,
+                distance: 30.,
+                seconds_from_previous: 4.,
+            },
+        ])
+    }
+}
+
+/// A set of waves that are currently arriving.
+#[derive(Component, Debug)]
+pub struct ActiveWaves {
+    /// What is being spawned
+    spawn_waves: SpawnWaves,
+    /// The number of waves that have already arrived
+    waves_spawned: usize,
+    /// Counts down to the next wave
+    timer: Timer,
+    /// Where on the edge of the map this group is arriving from
+    entry_point: TilePos,
+}
+
+impl ActiveWaves {
+    /// Begins spawning `spawn_waves` at the `entry_point`.
+    pub fn new(spawn_waves: SpawnWaves, entry_point: TilePos) -> Self {
+        let timer = Timer::from_seconds(spawn_waves.seconds_between_waves, TimerMode::Repeating);
+
+        ActiveWaves {
+            spawn_waves,
+            waves_spawned: 0,
+            timer,
+            entry_point,
+        }
+    }
+}
+
+/// Picks a random tile on the edge of the map, where arriving units can enter.
+pub fn random_edge_tile(map_geometry: &MapGeometry, rng: &mut impl Rng) -> Option<TilePos> {
+    let edge_tiles: Vec<Hex> = Hex::ZERO.ring(map_geometry.radius);
+    edge_tiles.choose(rng).map(|&hex| TilePos { hex })
+}
+
+/// Starts any events in the [`Scenario`] that are due today.
+fn start_scheduled_events(
+    mut scenario: ResMut<Scenario>,
+    in_game_time: Res<InGameTime>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+    mut alerts: EventWriter<Alert>,
+    mut historical_events: EventWriter<HistoricalEvent>,
+    mut commands: Commands,
+) {
+    let today = in_game_time.current_day();
+    if scenario.last_checked_day == Some(today) {
+        return;
+    }
+    scenario.last_checked_day = Some(today);
+
+    let rng = &mut jitter.world_rng(JitterStream::Scenario);
+
+    for event in scenario.events.iter() {
+        if !event.trigger.fires_on(today) {
+            continue;
+        }
+
+        if let Some(entry_point) = random_edge_tile(&map_geometry, rng) {
+            alerts.send(Alert {
+                message: format!("{} is arriving", event.name),
+                tile_pos: Some(entry_point),
+            });
+            historical_events.send(HistoricalEvent::Arrived {
+                name: event.name.clone(),
+                tile_pos: entry_point,
+            });
+
+            commands.spawn(ActiveWaves::new(event.spawn_waves.clone(), entry_point));
+        }
+    }
+}
+
+/// Spawns units at the edge of the map for each of the [`ActiveWaves`].
+fn spawn_waves(
+    time: Res<Time>,
+    mut waves_query: Query<(Entity, &mut ActiveWaves)>,
+    map_geometry: Res<MapGeometry>,
+    unit_manifest: Res<UnitManifest>,
+    jitter: Jitter,
+    mut commands: Commands,
+) {
+    /// How far from the entry point units in a wave may appear
+    const SPREAD: u32 = 2;
+
+    for (entity, mut active_waves) in waves_query.iter_mut() {
+        active_waves.timer.tick(time.delta());
+        // The first wave arrives immediately
+        if !active_waves.timer.just_finished() && active_waves.waves_spawned > 0 {
+            continue;
+        }
+
+        let entry_point = active_waves.entry_point;
+        let mut candidates: Vec<TilePos> = hexagon(entry_point.hex, SPREAD)
+            .map(|hex| TilePos { hex })
+            .filter(|tile_pos| map_geometry.is_passable(*tile_pos))
+            .collect();
+        candidates.shuffle(&mut jitter.rng(entity, JitterStream::Scenario));
+
+        let spawn_waves = &active_waves.spawn_waves;
+        let unit_data = unit_manifest.get(spawn_waves.unit_id);
+        for tile_pos in candidates.into_iter().take(spawn_waves.units_per_wave) {
+            commands.spawn(UnitBundle::new(
+                spawn_waves.unit_id,
+                tile_pos,
+                unit_data.clone(),
+            ));
+        }
+
+        active_waves.waves_spawned += 1;
+        if active_waves.waves_spawned >= active_waves.spawn_waves.n_waves {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Runs the events scripted by the current [`Scenario`].
+pub(super) struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scenario>()
+            .add_system(start_scheduled_events)
+            .add_system(spawn_waves.after(start_scheduled_events));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_off_events_fire_once() {
+        let trigger = EventTrigger::OnDay(30);
+
+        assert!(!trigger.fires_on(29));
+        assert!(trigger.fires_on(30));
+        assert!(!trigger.fires_on(31));
+    }
+
+    #[test]
+    fn recurring_events_fire_periodically() {
+        let trigger = EventTrigger::EveryNDays {
+            first_day: 20,
+            period: 40,
+        };
+
+        assert!(!trigger.fires_on(0));
+        assert!(trigger.fires_on(20));
+        assert!(!trigger.fires_on(40));
+        assert!(trigger.fires_on(60));
+        assert!(trigger.fires_on(100));
+    }
+
+    #[test]
+    fn seasonal_events_fire_at_the_start_of_the_season() {
+        let trigger = EventTrigger::StartOfSeason(Season::Autumn);
+        let autumn_start = DAYS_PER_SEASON * 2;
+
+        assert!(!trigger.fires_on(0));
+        assert!(trigger.fires_on(autumn_start));
+        assert!(!trigger.fires_on(autumn_start + 1));
+        assert!(trigger.fires_on(autumn_start + DAYS_PER_SEASON * 4));
+    }
+}
@@ -0,0 +1,504 @@
+//! Periodic summaries of the world, and tools to compare them.
+//!
+//! Snapshots are saved as plain text, so that two of them (e.g. at tick N and tick N + 1000)
+//! can be compared with [`SnapshotDiff`] to find when an emergent anomaly began.
+//! The `snapshot_diff` tool in this workspace prints the diff between two saved snapshots.
+
+use crate::bevy::{
+    ecs::system::{SystemParam, SystemState},
+    prelude::*,
+    tasks::IoTaskPool,
+};
+use core::fmt::Display;
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+
+use crate::{
+    manifest::{Id, Structure, Unit},
+    signals::Signals,
+    structures::{
+        construction::{Ghost, Preview},
+        crafting::{InputInventory, OutputInventory},
+    },
+    units::item_interaction::UnitInventory,
+};
+
+use super::time::SimulationTick;
+
+/// Controls whether and how often snapshots are saved.
+///
+/// Insert this resource before adding the [`SimulationPlugin`](super::SimulationPlugin) to customize it for a game.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SnapshotConfig {
+    /// The number of ticks between snapshots, or `None` to never save them.
+    pub interval: Option<u64>,
+    /// The folder that snapshots are saved to.
+    pub folder: PathBuf,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig {
+            interval: None,
+            folder: PathBuf::from("snapshots"),
+        }
+    }
+}
+
+/// A summary of the state of the world at a single tick.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Snapshot {
+    /// The tick on which the snapshot was taken
+    pub tick: u64,
+    /// The kind of each unit, structure and ghost, keyed by the bits of its [`Entity`]
+    pub entities: BTreeMap<u64, String>,
+    /// The total strength of each type of signal
+    pub signal_mass: BTreeMap<String, f32>,
+    /// The total number of each item, whether stored or held
+    pub item_totals: BTreeMap<String, usize>,
+}
+
+impl Snapshot {
+    /// Records the entity with the provided `kind` and `id`.
+    fn add_entity<T>(&mut self, entity: Entity, kind: &str, id: Id<T>) {
+        self.entities
+            .insert(entity.to_bits(), format!("{kind}:{id}"));
+    }
+
+    /// Captures a snapshot of the current state of the `world`.
+    pub fn capture(world: &mut World) -> Snapshot {
+        let mut system_state: SystemState<(Res<SimulationTick>, SnapshotQuery)> =
+            SystemState::new(world);
+        let (simulation_tick, snapshot_query) = system_state.get(world);
+
+        snapshot_query.capture(simulation_tick.get())
+    }
+
+    /// A checksum of the entire snapshot, used to quickly check whether two simulations have diverged.
+    ///
+    /// This is stable across runs and platforms that produce identical snapshots.
+    pub fn checksum(&self) -> u64 {
+        /// The initial state of the FNV-1a hash
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        /// The multiplier of the FNV-1a hash
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+        self.to_string()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// The number of entities of each kind.
+    pub fn entity_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for kind in self.entities.values() {
+            *counts.entry(kind.as_str()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "tick {}", self.tick)?;
+
+        for (entity, kind) in &self.entities {
+            writeln!(f, "entity {entity} {kind}")?;
+        }
+
+        for (signal_type, mass) in &self.signal_mass {
+            writeln!(f, "signal {signal_type} {mass}")?;
+        }
+
+        for (item, count) in &self.item_totals {
+            writeln!(f, "item {item} {count}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of a saved [`Snapshot`] could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotParseError {
+    /// The line number, starting from 1
+    pub line_number: usize,
+    /// The contents of the line
+    pub line: String,
+}
+
+impl Display for SnapshotParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_number = self.line_number;
+        let line = &self.line;
+
+        write!(f, "Could not parse line {line_number} of snapshot: {line}")
+    }
+}
+
+impl std::error::Error for SnapshotParseError {}
+
+impl FromStr for Snapshot {
+    type Err = SnapshotParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut snapshot = Snapshot::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let error = || SnapshotParseError {
+                line_number: index + 1,
+                line: line.to_string(),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (record, rest) = line.split_once(' ').ok_or_else(error)?;
+            // Signal types may contain spaces, so values are split from the end of the line
+            match record {
+                "tick" => snapshot.tick = rest.parse().map_err(|_| error())?,
+                "entity" => {
+                    let (entity, kind) = rest.split_once(' ').ok_or_else(error)?;
+                    let entity = entity.parse().map_err(|_| error())?;
+                    snapshot.entities.insert(entity, kind.to_string());
+                }
+                "signal" => {
+                    let (signal_type, mass) = rest.rsplit_once(' ').ok_or_else(error)?;
+                    let mass = mass.parse().map_err(|_| error())?;
+                    snapshot.signal_mass.insert(signal_type.to_string(), mass);
+                }
+                "item" => {
+                    let (item, count) = rest.rsplit_once(' ').ok_or_else(error)?;
+                    let count = count.parse().map_err(|_| error())?;
+                    snapshot.item_totals.insert(item.to_string(), count);
+                }
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// The differences between two [`Snapshot`]s.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    /// The tick of the earlier snapshot
+    pub before_tick: u64,
+    /// The tick of the later snapshot
+    pub after_tick: u64,
+    /// The number of entities of each kind that only exist in the later snapshot
+    pub added: BTreeMap<String, usize>,
+    /// The number of entities of each kind that only exist in the earlier snapshot
+    pub removed: BTreeMap<String, usize>,
+    /// The total strength of each type of signal that changed, before and after
+    pub signal_mass: BTreeMap<String, (f32, f32)>,
+    /// The total number of each item that changed, before and after
+    pub item_totals: BTreeMap<String, (usize, usize)>,
+}
+
+impl SnapshotDiff {
+    /// Compares the `before` snapshot to the `after` snapshot.
+    pub fn between(before: &Snapshot, after: &Snapshot) -> Self {
+        let mut diff = SnapshotDiff {
+            before_tick: before.tick,
+            after_tick: after.tick,
+            ..Default::default()
+        };
+
+        for (entity, kind) in &after.entities {
+            if before.entities.get(entity) != Some(kind) {
+                *diff.added.entry(kind.clone()).or_default() += 1;
+            }
+        }
+
+        for (entity, kind) in &before.entities {
+            if after.entities.get(entity) != Some(kind) {
+                *diff.removed.entry(kind.clone()).or_default() += 1;
+            }
+        }
+
+        let signal_types = before.signal_mass.keys().chain(after.signal_mass.keys());
+        for signal_type in signal_types {
+            let before_mass = before
+                .signal_mass
+                .get(signal_type)
+                .copied()
+                .unwrap_or_default();
+            let after_mass = after
+                .signal_mass
+                .get(signal_type)
+                .copied()
+                .unwrap_or_default();
+            if before_mass != after_mass {
+                diff.signal_mass
+                    .insert(signal_type.clone(), (before_mass, after_mass));
+            }
+        }
+
+        let items = before.item_totals.keys().chain(after.item_totals.keys());
+        for item in items {
+            let before_count = before.item_totals.get(item).copied().unwrap_or_default();
+            let after_count = after.item_totals.get(item).copied().unwrap_or_default();
+            if before_count != after_count {
+                diff.item_totals
+                    .insert(item.clone(), (before_count, after_count));
+            }
+        }
+
+        diff
+    }
+
+    /// Are the two snapshots identical, apart from their ticks?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.signal_mass.is_empty()
+            && self.item_totals.is_empty()
+    }
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Tick {} -> Tick {}", self.before_tick, self.after_tick)?;
+
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        if !self.added.is_empty() {
+            writeln!(f, "\nEntities added:")?;
+            for (kind, count) in &self.added {
+                writeln!(f, "  +{count} {kind}")?;
+            }
+        }
+
+        if !self.removed.is_empty() {
+            writeln!(f, "\nEntities removed:")?;
+            for (kind, count) in &self.removed {
+                writeln!(f, "  -{count} {kind}")?;
+            }
+        }
+
+        if !self.signal_mass.is_empty() {
+            writeln!(f, "\nSignal mass:")?;
+            for (signal_type, (before, after)) in &self.signal_mass {
+                let change = after - before;
+                writeln!(
+                    f,
+                    "  {signal_type}: {before:.2} -> {after:.2} ({change:+.2})"
+                )?;
+            }
+        }
+
+        if !self.item_totals.is_empty() {
+            writeln!(f, "\nItem totals:")?;
+            for (item, (before, after)) in &self.item_totals {
+                let change = *after as i64 - *before as i64;
+                writeln!(f, "  {item}: {before} -> {after} ({change:+})")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The data needed to capture a [`Snapshot`] of the world.
+#[derive(SystemParam)]
+pub struct SnapshotQuery<'w, 's> {
+    /// All units, structures and ghosts
+    entity_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Id<Unit>>,
+            Option<&'static Id<Structure>>,
+            Option<&'static Ghost>,
+        ),
+        (Or<(With<Id<Unit>>, With<Id<Structure>>)>, Without<Preview>),
+    >,
+    /// The signals on every tile
+    signals: Res<'w, Signals>,
+    /// Items waiting to be used.
+    ///
+    /// Materials delivered to ghosts are counted, as they are held in the ghost's input inventory.
+    input_inventory_query: Query<'w, 's, &'static InputInventory>,
+    /// Items that have been produced or stored
+    output_inventory_query: Query<'w, 's, &'static OutputInventory>,
+    /// Items carried by units
+    unit_inventory_query: Query<'w, 's, &'static UnitInventory>,
+}
+
+impl<'w, 's> SnapshotQuery<'w, 's> {
+    /// Summarizes the current state of the world, labelling it with the provided `tick`.
+    pub fn capture(&self, tick: u64) -> Snapshot {
+        let mut snapshot = Snapshot {
+            tick,
+            ..Default::default()
+        };
+
+        for (entity, maybe_unit_id, maybe_structure_id, maybe_ghost) in self.entity_query.iter() {
+            match (maybe_unit_id, maybe_structure_id, maybe_ghost) {
+                (Some(&unit_id), ..) => snapshot.add_entity(entity, "unit", unit_id),
+                (None, Some(&structure_id), Some(_)) => {
+                    snapshot.add_entity(entity, "ghost", structure_id)
+                }
+                (None, Some(&structure_id), None) => {
+                    snapshot.add_entity(entity, "structure", structure_id)
+                }
+                (None, None, _) => (),
+            }
+        }
+
+        for (signal_type, strength) in self.signals.total_strengths() {
+            snapshot
+                .signal_mass
+                .insert(signal_type.to_string(), strength.value());
+        }
+
+        let stored_slots = self
+            .input_inventory_query
+            .iter()
+            .flat_map(|input| input.inventory.iter())
+            .chain(
+                self.output_inventory_query
+                    .iter()
+                    .flat_map(|output| output.inventory.iter()),
+            );
+        for slot in stored_slots {
+            *snapshot
+                .item_totals
+                .entry(slot.item_id().to_string())
+                .or_default() += slot.count();
+        }
+
+        for unit_inventory in self.unit_inventory_query.iter() {
+            if let Some(item_id) = unit_inventory.held_item {
+                *snapshot.item_totals.entry(item_id.to_string()).or_default() +=
+                    unit_inventory.count();
+            }
+        }
+
+        snapshot
+    }
+}
+
+/// Captures a [`Snapshot`] of the world every [`SnapshotConfig::interval`] ticks, and saves it to disk.
+fn save_snapshots(
+    config: Res<SnapshotConfig>,
+    simulation_tick: Res<SimulationTick>,
+    snapshot_query: SnapshotQuery,
+) {
+    let interval = match config.interval {
+        Some(interval) if interval > 0 => interval,
+        _ => return,
+    };
+
+    if !simulation_tick.is_changed() || !simulation_tick.get().is_multiple_of(interval) {
+        return;
+    }
+
+    let snapshot = snapshot_query.capture(simulation_tick.get());
+    let path = config
+        .folder
+        .join(format!("snapshot_tick_{}.txt", snapshot.tick));
+    let folder = config.folder.clone();
+    let contents = snapshot.to_string();
+
+    IoTaskPool::get()
+        .spawn(async move {
+            let result =
+                std::fs::create_dir_all(&folder).and_then(|_| std::fs::write(&path, contents));
+
+            if let Err(error) = result {
+                error!("Could not save snapshot to {}: {error}", path.display());
+            }
+        })
+        .detach();
+}
+
+/// Periodically saves snapshots of the world, for debugging.
+pub(super) struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotConfig>()
+            .add_system(save_snapshots.in_base_set(CoreSet::Last));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_snapshot() -> Snapshot {
+        let mut snapshot = Snapshot {
+            tick: 100,
+            ..Default::default()
+        };
+        snapshot.entities.insert(1, "unit:ant".to_string());
+        snapshot.entities.insert(2, "structure:leuco".to_string());
+        snapshot.signal_mass.insert("Pull(#42)".to_string(), 1.5);
+        snapshot.item_totals.insert("leuco_chunk".to_string(), 3);
+        snapshot
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_text() {
+        let snapshot = example_snapshot();
+        let parsed: Snapshot = snapshot.to_string().parse().unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn malformed_lines_are_reported() {
+        let error = "tick 1\nbogus line".parse::<Snapshot>().unwrap_err();
+        assert_eq!(error.line_number, 2);
+    }
+
+    #[test]
+    fn checksums_detect_changes() {
+        let snapshot = example_snapshot();
+        let mut changed = example_snapshot();
+        changed.item_totals.insert("leuco_chunk".to_string(), 4);
+
+        assert_eq!(snapshot.checksum(), example_snapshot().checksum());
+        assert_ne!(snapshot.checksum(), changed.checksum());
+    }
+
+    #[test]
+    fn identical_snapshots_have_empty_diff() {
+        let snapshot = example_snapshot();
+        assert!(SnapshotDiff::between(&snapshot, &snapshot).is_empty());
+    }
+
+    #[test]
+    fn diff_counts_added_and_removed_entities() {
+        let before = example_snapshot();
+        let mut after = example_snapshot();
+        after.tick = 200;
+        after.entities.remove(&1);
+        after.entities.insert(3, "unit:ant".to_string());
+        after.entities.insert(4, "unit:ant".to_string());
+
+        let diff = SnapshotDiff::between(&before, &after);
+        assert_eq!(diff.added.get("unit:ant"), Some(&2));
+        assert_eq!(diff.removed.get("unit:ant"), Some(&1));
+        assert!(diff.signal_mass.is_empty());
+    }
+
+    #[test]
+    fn diff_tracks_signal_mass_and_items() {
+        let before = example_snapshot();
+        let mut after = example_snapshot();
+        after.signal_mass.insert("Pull(#42)".to_string(), 0.5);
+        after.item_totals.remove("leuco_chunk");
+
+        let diff = SnapshotDiff::between(&before, &after);
+        assert_eq!(diff.signal_mass.get("Pull(#42)"), Some(&(1.5, 0.5)));
+        assert_eq!(diff.item_totals.get("leuco_chunk"), Some(&(3, 0)));
+    }
+}
@@ -0,0 +1,157 @@
+//! Tracks how hot or cold each tile is.
+
+use crate::bevy::prelude::*;
+use hexx::shapes::hexagon;
+
+use crate::{
+    structures::{crafting::CraftingState, walls::Enclosures},
+    terrain::Terrain,
+};
+
+use super::{
+    geometry::{MapGeometry, TilePos},
+    time::Season,
+    weather::LocalWeather,
+};
+
+/// The temperature of a single tile, in degrees Celsius.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, PartialOrd, Deref, DerefMut)]
+pub struct Temperature(pub f32);
+
+/// The baseline temperature of the whole map, in degrees Celsius.
+///
+/// This follows the [`Season`], and the weather on each tile warms or cools it further.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Deref, DerefMut)]
+pub struct AmbientTemperature(pub f32);
+
+impl AmbientTemperature {
+    /// The ambient temperature in a mild spring, in degrees Celsius.
+    const BASELINE: f32 = 20.;
+
+    /// The ambient temperature during the provided `season`.
+    pub fn in_season(season: Season) -> Self {
+        AmbientTemperature(AmbientTemperature::BASELINE + season.temperature_offset())
+    }
+}
+
+impl Default for AmbientTemperature {
+    fn default() -> Self {
+        AmbientTemperature::in_season(Season::default())
+    }
+}
+
+/// An object that warms up the tiles around it.
+///
+/// If the entity can craft, heat is only produced while a recipe is in progress.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct HeatSource {
+    /// The increase in temperature at the source's own tile, in degrees Celsius.
+    pub intensity: f32,
+    /// The number of tiles away from the source that are warmed.
+    ///
+    /// Heat falls off linearly with distance.
+    pub radius: u32,
+}
+
+impl HeatSource {
+    /// The temperature increase caused by this heat source at a tile `distance` tiles away.
+    pub fn heat_at_distance(&self, distance: u32) -> f32 {
+        if distance > self.radius {
+            return 0.;
+        }
+
+        let falloff = 1. - distance as f32 / (self.radius + 1) as f32;
+        self.intensity * falloff
+    }
+}
+
+/// The temperature that sheltered tiles are drawn towards, in degrees Celsius.
+const SHELTERED_TEMPERATURE: f32 = 15.;
+
+/// The fraction of the difference between the ambient and sheltered temperatures that is blocked by a shelter.
+const SHELTER_INSULATION: f32 = 0.6;
+
+/// The temperature of a tile before heat sources are accounted for.
+///
+/// Sheltered tiles are insulated from the extremes of the ambient temperature.
+fn base_temperature(ambient_temperature: f32, sheltered: bool) -> f32 {
+    if sheltered {
+        ambient_temperature + (SHELTERED_TEMPERATURE - ambient_temperature) * SHELTER_INSULATION
+    } else {
+        ambient_temperature
+    }
+}
+
+/// Warms and cools the whole map as the seasons change.
+fn follow_the_seasons(season: Res<Season>, mut ambient_temperature: ResMut<AmbientTemperature>) {
+    let seasonal_temperature = AmbientTemperature::in_season(*season);
+
+    ambient_temperature.set_if_neq(seasonal_temperature);
+}
+
+/// Recomputes the [`Temperature`] of each tile from the ambient temperature, the local weather, shelter and all active heat sources.
+fn update_temperature(
+    ambient_temperature: Res<AmbientTemperature>,
+    local_weather: LocalWeather,
+    mut terrain_query: Query<(&TilePos, &mut Temperature), With<Terrain>>,
+    heat_source_query: Query<(&TilePos, &HeatSource, Option<&CraftingState>)>,
+    enclosures: Res<Enclosures>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (&tile_pos, mut temperature) in terrain_query.iter_mut() {
+        let outdoor_temperature =
+            ambient_temperature.0 + local_weather.at(tile_pos).temperature_offset();
+        temperature.0 = base_temperature(outdoor_temperature, enclosures.is_sheltered(tile_pos));
+    }
+
+    for (&source_pos, heat_source, maybe_crafting_state) in heat_source_query.iter() {
+        if let Some(crafting_state) = maybe_crafting_state {
+            if !matches!(crafting_state, CraftingState::InProgress { .. }) {
+                continue;
+            }
+        }
+
+        for hex in hexagon(source_pos.hex, heat_source.radius) {
+            let tile_pos = TilePos { hex };
+            if let Some(&terrain_entity) = map_geometry.terrain_index.get(&tile_pos) {
+                let distance = source_pos.hex.distance_to(hex) as u32;
+                let (_, mut temperature) = terrain_query.get_mut(terrain_entity).unwrap();
+                temperature.0 += heat_source.heat_at_distance(distance);
+            }
+        }
+    }
+}
+
+/// Controls the temperature of the map.
+pub(super) struct TemperaturePlugin;
+
+impl Plugin for TemperaturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AmbientTemperature>()
+            .add_system(follow_the_seasons)
+            .add_system(update_temperature.after(follow_the_seasons));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::freezing::FREEZING_POINT;
+
+    #[test]
+    fn winter_is_cold_enough_to_freeze_water() {
+        assert!(AmbientTemperature::in_season(Season::Winter).0 < FREEZING_POINT);
+
+        for season in [Season::Spring, Season::Summer, Season::Autumn] {
+            assert!(AmbientTemperature::in_season(season).0 > FREEZING_POINT);
+        }
+    }
+
+    #[test]
+    fn shelters_keep_out_the_winter_cold() {
+        let winter = AmbientTemperature::in_season(Season::Winter).0;
+
+        assert_eq!(base_temperature(winter, false), winter);
+        assert!(base_temperature(winter, true) > FREEZING_POINT);
+    }
+}
@@ -0,0 +1,368 @@
+//! Tracks the passage of time within the game world.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use super::alerts::Alert;
+
+/// The number of real-time seconds that make up a single in-game day.
+pub const DAY_LENGTH_IN_SECONDS: f32 = 60.;
+
+/// The number of in-game days that make up each season.
+pub const DAYS_PER_SEASON: u32 = 10;
+
+/// The amount of in-game time that has passed since the start of the game.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct InGameTime {
+    /// The number of days that have elapsed, including partial days
+    elapsed_days: f32,
+}
+
+impl InGameTime {
+    /// The current day, starting from day 0.
+    pub fn current_day(&self) -> u32 {
+        self.elapsed_days.floor() as u32
+    }
+
+    /// How far through the current day we are, from 0 (the start) to 1 (the end).
+    pub fn fraction_of_day(&self) -> f32 {
+        self.elapsed_days.fract()
+    }
+
+    /// The number of days that have elapsed, including partial days.
+    pub fn elapsed_days(&self) -> f32 {
+        self.elapsed_days
+    }
+
+    /// Sets the number of days that have elapsed, e.g. when a saved game is loaded.
+    pub fn set_elapsed_days(&mut self, elapsed_days: f32) {
+        self.elapsed_days = elapsed_days;
+    }
+}
+
+impl Display for InGameTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let day = self.current_day();
+        let percent = self.fraction_of_day() * 100.;
+
+        write!(f, "Day {day} ({percent:.0}%)")
+    }
+}
+
+/// How brightly the sun is shining, based on the [`InGameTime`].
+///
+/// Each day begins at dawn: the first half of the day is daytime, and the second half is night.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TimeOfDay {
+    /// The brightness of the sunlight, from 0 (night) to 1 (full daylight)
+    daylight: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        TimeOfDay::from_fraction_of_day(0.)
+    }
+}
+
+impl TimeOfDay {
+    /// How much faster than the sun rises the daylight brightens.
+    ///
+    /// Larger values make dawn and dusk shorter.
+    const TWILIGHT_SHARPNESS: f32 = 3.;
+
+    /// Computes the time of day `fraction_of_day` of the way through a day.
+    pub fn from_fraction_of_day(fraction_of_day: f32) -> Self {
+        let sun_height = (TAU * fraction_of_day).sin();
+
+        TimeOfDay {
+            daylight: (sun_height * TimeOfDay::TWILIGHT_SHARPNESS).clamp(0., 1.),
+        }
+    }
+
+    /// The brightness of the sunlight, from 0 (night) to 1 (full daylight).
+    pub fn daylight(&self) -> f32 {
+        self.daylight
+    }
+}
+
+impl Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self.daylight {
+            daylight if daylight >= 1. => "Day",
+            daylight if daylight > 0. => "Twilight",
+            _ => "Night",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The season of the year, which cycles every [`DAYS_PER_SEASON`] days.
+///
+/// Each game begins at the start of spring.
+/// When the season changes, a [`SeasonChanged`] event is sent.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Season {
+    /// Plants grow quickly as the world thaws.
+    #[default]
+    Spring,
+    /// Hot and bright: signals evaporate quickly.
+    Summer,
+    /// Growth slows as the leaves turn.
+    Autumn,
+    /// Cold and dark: little grows, but signals linger.
+    Winter,
+}
+
+impl Season {
+    /// The season on the provided `day`, starting from day 0.
+    pub fn on_day(day: u32) -> Self {
+        match (day / DAYS_PER_SEASON) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// The multiplier applied to the growth rate of organisms during this season.
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Season::Spring => 1.25,
+            Season::Summer => 1.,
+            Season::Autumn => 0.75,
+            Season::Winter => 0.25,
+        }
+    }
+
+    /// The multiplier applied to the rate at which signals degrade during this season.
+    pub fn signal_decay_multiplier(&self) -> f32 {
+        match self {
+            Season::Spring | Season::Autumn => 1.,
+            Season::Summer => 1.5,
+            Season::Winter => 0.5,
+        }
+    }
+
+    /// The change in ambient temperature during this season, in degrees Celsius.
+    ///
+    /// Winter is cold enough to freeze exposed water, even without snow.
+    pub fn temperature_offset(&self) -> f32 {
+        match self {
+            Season::Spring => 0.,
+            Season::Summer => 8.,
+            Season::Autumn => -5.,
+            Season::Winter => -22.,
+        }
+    }
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Sent whenever the [`Season`] changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeasonChanged {
+    /// The season that just ended
+    pub previous: Season,
+    /// The season that just began
+    pub current: Season,
+}
+
+/// The number of frames that the simulation has advanced since the start of the game.
+///
+/// Frames where the simulation is paused are not counted.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SimulationTick(u64);
+
+impl SimulationTick {
+    /// The current tick, starting from 0.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// Sets the current tick, e.g. when a saved game is loaded.
+    pub fn set(&mut self, tick: u64) {
+        self.0 = tick;
+    }
+}
+
+impl Display for SimulationTick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tick {}", self.0)
+    }
+}
+
+/// A run condition that only allows systems to run once every second.
+///
+/// Each system that uses this condition counts down with its own timer.
+/// This suits systems that refresh summaries or slowly changing state, which would be wasteful to recompute every frame.
+pub fn every_second() -> impl FnMut(Res<Time>) -> bool {
+    let mut timer = Timer::from_seconds(1., TimerMode::Repeating);
+    move |time: Res<Time>| {
+        timer.tick(time.delta());
+        timer.just_finished()
+    }
+}
+
+/// Advances the in-game clock.
+fn advance_in_game_time(time: Res<Time>, mut in_game_time: ResMut<InGameTime>) {
+    in_game_time.elapsed_days += time.delta_seconds() / DAY_LENGTH_IN_SECONDS;
+}
+
+/// Moves the sun through the sky as the in-game clock advances.
+fn update_time_of_day(in_game_time: Res<InGameTime>, mut time_of_day: ResMut<TimeOfDay>) {
+    let new_time_of_day = TimeOfDay::from_fraction_of_day(in_game_time.fraction_of_day());
+
+    time_of_day.set_if_neq(new_time_of_day);
+}
+
+/// Moves on to the next [`Season`] when the calendar says so.
+fn update_season(
+    in_game_time: Res<InGameTime>,
+    mut season: ResMut<Season>,
+    mut season_changed: EventWriter<SeasonChanged>,
+) {
+    let new_season = Season::on_day(in_game_time.current_day());
+
+    let previous = *season;
+    season.set_if_neq(new_season);
+
+    if previous != new_season {
+        season_changed.send(SeasonChanged {
+            previous,
+            current: new_season,
+        });
+    }
+}
+
+/// Lets the player know when a new season begins.
+fn announce_new_seasons(
+    mut season_changed: EventReader<SeasonChanged>,
+    mut alerts: EventWriter<Alert>,
+) {
+    for event in season_changed.iter() {
+        alerts.send(Alert {
+            message: format!("{} has begun", event.current),
+            tile_pos: None,
+        });
+    }
+}
+
+/// Counts the frames that the simulation has advanced.
+pub(super) fn advance_simulation_tick(
+    time: Res<Time>,
+    mut simulation_tick: ResMut<SimulationTick>,
+) {
+    if !time.is_paused() {
+        simulation_tick.0 += 1;
+    }
+}
+
+/// Keeps track of the in-game calendar.
+pub(super) struct InGameTimePlugin;
+
+impl Plugin for InGameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InGameTime>()
+            .init_resource::<TimeOfDay>()
+            .init_resource::<Season>()
+            .init_resource::<SimulationTick>()
+            .add_event::<SeasonChanged>()
+            .add_system(advance_in_game_time.in_base_set(CoreSet::PreUpdate))
+            .add_system(
+                update_time_of_day
+                    .after(advance_in_game_time)
+                    .in_base_set(CoreSet::PreUpdate),
+            )
+            .add_system(
+                update_season
+                    .after(advance_in_game_time)
+                    .in_base_set(CoreSet::PreUpdate),
+            )
+            .add_system(announce_new_seasons)
+            .add_system(advance_simulation_tick.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::utils::{Duration, Instant};
+
+    use super::*;
+
+    /// The number of times that [`count_runs`] has run.
+    #[derive(Resource, Debug, Default)]
+    struct Runs(u32);
+
+    /// Counts each time that it runs.
+    fn count_runs(mut runs: ResMut<Runs>) {
+        runs.0 += 1;
+    }
+
+    #[test]
+    fn every_second_runs_once_a_second() {
+        let mut app = App::new();
+
+        // The clock is never advanced, so every frame lasts for the same time
+        let mut time = Time::default();
+        let start = Instant::now();
+        time.update_with_instant(start);
+        time.update_with_instant(start + Duration::from_millis(400));
+
+        app.insert_resource(time)
+            .init_resource::<Runs>()
+            .add_system(count_runs.run_if(every_second()));
+
+        let mut runs = Vec::new();
+        for _ in 0..5 {
+            app.update();
+            runs.push(app.world.resource::<Runs>().0);
+        }
+
+        assert_eq!(runs, vec![0, 0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn days_are_bright_and_nights_are_dark() {
+        assert_eq!(TimeOfDay::from_fraction_of_day(0.25).daylight(), 1.);
+        assert_eq!(TimeOfDay::from_fraction_of_day(0.75).daylight(), 0.);
+    }
+
+    #[test]
+    fn dawn_brightens_gradually() {
+        let dawn = TimeOfDay::from_fraction_of_day(0.01);
+        assert!(dawn.daylight() > 0.);
+        assert!(dawn.daylight() < 1.);
+    }
+
+    #[test]
+    fn seasons_cycle_in_order() {
+        let year = [
+            Season::Spring,
+            Season::Summer,
+            Season::Autumn,
+            Season::Winter,
+            // A full year has passed
+            Season::Spring,
+        ];
+
+        for (i, &season) in year.iter().enumerate() {
+            let first_day = i as u32 * DAYS_PER_SEASON;
+            assert_eq!(Season::on_day(first_day), season);
+            assert_eq!(Season::on_day(first_day + DAYS_PER_SEASON - 1), season);
+        }
+    }
+}
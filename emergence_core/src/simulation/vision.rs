@@ -0,0 +1,332 @@
+//! Line of sight, and the fog of war that hides the parts of the map that cannot currently be seen.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use hexx::{shapes::hexagon, Hex};
+
+use super::geometry::{MapGeometry, TilePos};
+
+/// An object that reveals the tiles around it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct VisionSource {
+    /// The number of tiles away that can be seen.
+    pub radius: u32,
+    /// The height above the tile that vision originates from.
+    ///
+    /// Higher vantage points can see over taller obstacles.
+    pub eye_height: f32,
+    /// Do tiles seen by this source reveal their signals in full detail?
+    pub grants_intel: bool,
+}
+
+impl VisionSource {
+    /// The vision of a typical unit.
+    pub const UNIT: VisionSource = VisionSource {
+        radius: 4,
+        eye_height: 0.5,
+        grants_intel: false,
+    };
+}
+
+/// How clearly the player can make out a tile through the fog of war.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sight {
+    /// The tile is currently in view
+    Visible,
+    /// The tile has been seen before, but is not currently in view
+    Remembered,
+    /// The tile has never been seen
+    Unexplored,
+}
+
+/// Tracks which tiles the player can see.
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct FogOfWar {
+    /// The tiles that are currently in view
+    visible: HashSet<TilePos>,
+    /// The tiles that have ever been in view
+    explored: HashSet<TilePos>,
+    /// The tiles that are currently in view of a source that grants intel
+    intel: HashSet<TilePos>,
+}
+
+impl FogOfWar {
+    /// Is the provided `tile_pos` currently in view?
+    pub fn is_visible(&self, tile_pos: TilePos) -> bool {
+        self.visible.contains(&tile_pos)
+    }
+
+    /// Has the provided `tile_pos` ever been seen?
+    pub fn is_explored(&self, tile_pos: TilePos) -> bool {
+        self.explored.contains(&tile_pos)
+    }
+
+    /// How clearly the provided `tile_pos` can be made out.
+    pub fn sight(&self, tile_pos: TilePos) -> Sight {
+        if self.is_visible(tile_pos) {
+            Sight::Visible
+        } else if self.is_explored(tile_pos) {
+            Sight::Remembered
+        } else {
+            Sight::Unexplored
+        }
+    }
+
+    /// Is the provided `tile_pos` seen by a source that reveals its signals in detail?
+    pub fn has_intel(&self, tile_pos: TilePos) -> bool {
+        self.intel.contains(&tile_pos)
+    }
+
+    /// Every tile that has ever been seen.
+    pub fn explored(&self) -> impl Iterator<Item = TilePos> + '_ {
+        self.explored.iter().copied()
+    }
+
+    /// Replaces the tiles that have been seen with `explored`, e.g. when a saved game is loaded.
+    ///
+    /// Nothing is in view until the fog of war is next updated.
+    pub fn restore(&mut self, explored: impl IntoIterator<Item = TilePos>) {
+        self.visible.clear();
+        self.intel.clear();
+        self.explored = explored.into_iter().collect();
+    }
+}
+
+/// The additional height that walls add to the tile they are on, for the purposes of blocking line of sight.
+const WALL_SIGHT_HEIGHT: f32 = 1.0;
+
+/// Rounds fractional axial coordinates to the nearest hex.
+fn round_axial(x: f32, y: f32) -> Hex {
+    let z = -x - y;
+
+    let mut rounded_x = x.round();
+    let mut rounded_y = y.round();
+    let rounded_z = z.round();
+
+    let x_diff = (rounded_x - x).abs();
+    let y_diff = (rounded_y - y).abs();
+    let z_diff = (rounded_z - z).abs();
+
+    if x_diff > y_diff && x_diff > z_diff {
+        rounded_x = -rounded_y - rounded_z;
+    } else if y_diff > z_diff {
+        rounded_y = -rounded_x - rounded_z;
+    }
+
+    Hex::new(rounded_x as i32, rounded_y as i32)
+}
+
+/// Returns the hexes along a straight line from `start` to `end`, including both endpoints.
+fn hex_line(start: Hex, end: Hex) -> Vec<Hex> {
+    /// Nudges the line off of hex edges, so that ties are broken consistently
+    const NUDGE: f32 = 1e-4;
+
+    let n = start.distance_to(end);
+    if n == 0 {
+        return vec![start];
+    }
+
+    (0..=n)
+        .map(|i| {
+            let t = i as f32 / n as f32;
+            let x = start.x as f32 + NUDGE + (end.x - start.x) as f32 * t;
+            let y = start.y as f32 + NUDGE + (end.y - start.y) as f32 * t;
+            round_axial(x, y)
+        })
+        .collect()
+}
+
+impl MapGeometry {
+    /// Can an observer at `origin`, with eyes `eye_height` above the ground, see the tile at `target`?
+    ///
+    /// Sight is blocked by any tile between the two whose terrain (or wall) rises above the line of sight.
+    pub fn line_of_sight(&self, origin: TilePos, target: TilePos, eye_height: f32) -> bool {
+        let height_at =
+            |tile_pos: &TilePos| self.height_index.get(tile_pos).copied().unwrap_or_default();
+
+        let origin_height = height_at(&origin) + eye_height;
+        let target_height = height_at(&target);
+
+        let line = hex_line(origin.hex, target.hex);
+        let n = line.len() - 1;
+
+        // The endpoints can never block sight
+        for (i, &hex) in line.iter().enumerate().take(n).skip(1) {
+            let tile_pos = TilePos { hex };
+            let t = i as f32 / n as f32;
+            let sight_height = origin_height + (target_height - origin_height) * t;
+
+            let mut obstacle_height = height_at(&tile_pos);
+            if self.wall_index.contains_key(&tile_pos) {
+                obstacle_height += WALL_SIGHT_HEIGHT;
+            }
+
+            if obstacle_height > sight_height {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Recomputes which tiles are visible from each [`VisionSource`].
+fn update_fog_of_war(
+    vision_query: Query<(&TilePos, &VisionSource)>,
+    map_geometry: Res<MapGeometry>,
+    mut fog_of_war: ResMut<FogOfWar>,
+) {
+    let mut visible = HashSet::new();
+    let mut intel = HashSet::new();
+
+    for (&origin, vision_source) in vision_query.iter() {
+        for hex in hexagon(origin.hex, vision_source.radius) {
+            let target = TilePos { hex };
+
+            if map_geometry.is_valid(target)
+                && map_geometry.line_of_sight(origin, target, vision_source.eye_height)
+            {
+                visible.insert(target);
+                if vision_source.grants_intel {
+                    intel.insert(target);
+                }
+            }
+        }
+    }
+
+    // Tiles can only be newly explored when the visible tiles change, which is flagged below
+    fog_of_war
+        .bypass_change_detection()
+        .explored
+        .extend(visible.iter().copied());
+    fog_of_war
+        .reborrow()
+        .map_unchanged(|fog_of_war| &mut fog_of_war.visible)
+        .set_if_neq(visible);
+    fog_of_war
+        .map_unchanged(|fog_of_war| &mut fog_of_war.intel)
+        .set_if_neq(intel);
+}
+
+/// Tracks what can be seen.
+pub(super) struct VisionPlugin;
+
+impl Plugin for VisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FogOfWar>()
+            .add_system(update_fog_of_war);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::walls::Wall;
+
+    /// A wall that blocks nothing but sight.
+    const WALL: Wall = Wall {
+        signal_opacity: 0.,
+        supports_roof: false,
+    };
+
+    /// An app that updates the [`FogOfWar`] on a flat map of the given `radius`.
+    fn vision_app(radius: u32) -> App {
+        let mut app = App::new();
+        app.init_resource::<FogOfWar>()
+            .insert_resource(MapGeometry::new(radius))
+            .add_system(update_fog_of_war);
+
+        app
+    }
+
+    #[test]
+    fn lines_are_contiguous_and_include_both_endpoints() {
+        let start = Hex::new(-2, 1);
+        let end = Hex::new(3, -1);
+        let line = hex_line(start, end);
+
+        assert_eq!(line.first(), Some(&start));
+        assert_eq!(line.last(), Some(&end));
+        assert_eq!(line.len() as i32, start.distance_to(end) + 1);
+        for pair in line.windows(2) {
+            assert_eq!(pair[0].distance_to(pair[1]), 1);
+        }
+    }
+
+    #[test]
+    fn walls_block_sight_unless_seen_over() {
+        let mut map_geometry = MapGeometry::new(3);
+        map_geometry.wall_index.insert(TilePos::new(1, 0), WALL);
+
+        assert!(!map_geometry.line_of_sight(TilePos::ORIGIN, TilePos::new(2, 0), 0.5));
+        // The wall itself can still be seen
+        assert!(map_geometry.line_of_sight(TilePos::ORIGIN, TilePos::new(1, 0), 0.5));
+        // High vantage points see over it
+        assert!(map_geometry.line_of_sight(TilePos::ORIGIN, TilePos::new(2, 0), 3.));
+    }
+
+    #[test]
+    fn hills_block_sight() {
+        let mut map_geometry = MapGeometry::new(3);
+        map_geometry.set_height(TilePos::new(1, 0), 2.);
+
+        assert!(!map_geometry.line_of_sight(TilePos::ORIGIN, TilePos::new(2, 0), 0.5));
+        assert!(map_geometry.line_of_sight(TilePos::ORIGIN, TilePos::new(0, 2), 0.5));
+    }
+
+    #[test]
+    fn sight_reaches_exactly_as_far_as_the_radius() {
+        let mut app = vision_app(5);
+        app.world.spawn((TilePos::ORIGIN, VisionSource::UNIT));
+        app.update();
+
+        let radius = VisionSource::UNIT.radius as i32;
+        let fog_of_war = app.world.resource::<FogOfWar>();
+        assert!(fog_of_war.is_visible(TilePos::new(radius, 0)));
+        assert!(!fog_of_war.is_visible(TilePos::new(radius + 1, 0)));
+        assert!(!fog_of_war.has_intel(TilePos::ORIGIN));
+    }
+
+    #[test]
+    fn tiles_behind_walls_stay_hidden() {
+        let mut app = vision_app(3);
+        app.world
+            .resource_mut::<MapGeometry>()
+            .wall_index
+            .insert(TilePos::new(1, 0), WALL);
+        app.world.spawn((TilePos::ORIGIN, VisionSource::UNIT));
+        app.update();
+
+        let fog_of_war = app.world.resource::<FogOfWar>();
+        assert_eq!(fog_of_war.sight(TilePos::new(1, 0)), Sight::Visible);
+        assert_eq!(fog_of_war.sight(TilePos::new(2, 0)), Sight::Unexplored);
+    }
+
+    #[test]
+    fn explored_tiles_are_remembered() {
+        let mut app = vision_app(5);
+        let watcher = app
+            .world
+            .spawn((
+                TilePos::new(-5, 0),
+                VisionSource {
+                    radius: 1,
+                    eye_height: 0.5,
+                    grants_intel: true,
+                },
+            ))
+            .id();
+        app.update();
+        assert!(app
+            .world
+            .resource::<FogOfWar>()
+            .has_intel(TilePos::new(-5, 0)));
+
+        *app.world.get_mut::<TilePos>(watcher).unwrap() = TilePos::new(5, 0);
+        app.update();
+
+        let fog_of_war = app.world.resource::<FogOfWar>();
+        assert_eq!(fog_of_war.sight(TilePos::new(-5, 0)), Sight::Remembered);
+        assert!(!fog_of_war.has_intel(TilePos::new(-5, 0)));
+        assert_eq!(fog_of_war.sight(TilePos::new(5, 0)), Sight::Visible);
+    }
+}
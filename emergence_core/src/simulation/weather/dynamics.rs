@@ -0,0 +1,570 @@
+//! How the weather changes by itself: spells of weather, drifting fronts, storms and lightning.
+//!
+//! This module is only compiled with the `weather` feature.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use hexx::{shapes::hexagon, Direction, Hex};
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom, Rng};
+
+use crate::{
+    manifest::{Id, Structure},
+    organisms::{
+        energy::{Energy, EnergyPool},
+        Organism,
+    },
+    signals::Signals,
+    simulation::{
+        alerts::Alert,
+        chronicle::{Disaster, HistoricalEvent},
+        fire::OnFire,
+        geometry::{MapGeometry, TilePos},
+        jitter::{Jitter, JitterStream},
+        lod::{lod_group_ready, LodGroup, LodSchedule},
+        time::Season,
+        wind::Wind,
+    },
+    structures::{commands::StructureCommandsExt, walls::Enclosures, Fragile},
+    terrain::{Fertility, SoilMoisture},
+};
+
+use super::{CurrentWeather, LocalWeather, Weather, WeatherFront, WeatherFronts};
+
+impl Weather {
+    /// The change in soil moisture per second caused by this weather.
+    fn moisture_change_per_second(&self) -> f32 {
+        match self {
+            Weather::Clear => 0.,
+            Weather::Rain => 0.02,
+            Weather::Storm => 0.04,
+            Weather::Drought => -0.01,
+            Weather::Snow => 0.,
+        }
+    }
+
+    /// The change in soil fertility per second caused by this weather.
+    ///
+    /// Gentle rain washes nutrients into the soil, while storms and droughts strip away the topsoil.
+    fn fertility_change_per_second(&self) -> f32 {
+        match self {
+            Weather::Clear => 0.,
+            Weather::Rain => 0.0005,
+            Weather::Storm => -0.002,
+            Weather::Drought => -0.001,
+            Weather::Snow => 0.,
+        }
+    }
+
+    /// The fraction of the signal on each tile that is washed away each second by this weather.
+    fn signal_washout_per_second(&self) -> f32 {
+        match self {
+            Weather::Rain => 0.05,
+            Weather::Storm => 0.15,
+            Weather::Clear | Weather::Drought | Weather::Snow => 0.,
+        }
+    }
+
+    /// The relative likelihood of each type of weather following this one, during the provided `season`.
+    ///
+    /// Weather that is out of season cannot occur, and ends as soon as its season is over.
+    fn transition_weights(&self, season: Season) -> [(Weather, f32); 5] {
+        let persistence = match self {
+            Weather::Clear => 4.,
+            Weather::Rain | Weather::Drought | Weather::Snow => 2.,
+            Weather::Storm => 0.5,
+        };
+
+        let (drought, snow) = match season {
+            Season::Spring | Season::Autumn => (0.5, 0.),
+            Season::Summer => (1.5, 0.),
+            Season::Winter => (0., 1.5),
+        };
+
+        [
+            (Weather::Clear, 4.),
+            (Weather::Rain, 2.),
+            (Weather::Storm, 0.5),
+            (Weather::Drought, drought),
+            (Weather::Snow, snow),
+        ]
+        .map(|(weather, weight)| {
+            if weather == *self && weight > 0. {
+                (weather, persistence)
+            } else {
+                (weather, weight)
+            }
+        })
+    }
+}
+
+impl WeatherFront {
+    /// Every tile beneath this front, including those that are off the map.
+    pub fn tiles(&self) -> impl Iterator<Item = TilePos> {
+        hexagon(self.center.hex, self.radius).map(|hex| TilePos { hex })
+    }
+
+    /// Has this front drifted entirely off the map?
+    fn has_left(&self, map_geometry: &MapGeometry) -> bool {
+        self.center.unsigned_distance_to(Hex::ZERO) > map_geometry.radius + self.radius
+    }
+}
+
+impl WeatherFronts {
+    /// The chance that a new front arrives each time the arrival timer finishes.
+    const ARRIVAL_CHANCE: f64 = 0.5;
+
+    /// The smallest and largest radius of new fronts.
+    const RADIUS_RANGE: (u32, u32) = (3, 7);
+
+    /// Iterates over the active fronts.
+    pub fn iter(&self) -> impl Iterator<Item = &WeatherFront> {
+        self.fronts.iter()
+    }
+
+    /// Adds a new front.
+    fn add(&mut self, front: WeatherFront) {
+        self.fronts.push(front);
+    }
+}
+
+impl<'w> LocalWeather<'w> {
+    /// Is there a storm anywhere on the map?
+    fn any_storms(&self) -> bool {
+        self.current_weather.get() == Weather::Storm
+            || self
+                .weather_fronts
+                .iter()
+                .any(|front| front.weather == Weather::Storm)
+    }
+}
+
+/// The relative likelihood of a front bringing each type of weather, during the provided `season`.
+fn front_weights(season: Season) -> [(Weather, f32); 4] {
+    let (drought, snow) = match season {
+        Season::Spring | Season::Autumn => (0.5, 0.),
+        Season::Summer => (2., 0.),
+        Season::Winter => (0., 3.),
+    };
+
+    [
+        (Weather::Rain, 3.),
+        (Weather::Storm, 1.),
+        (Weather::Drought, drought),
+        (Weather::Snow, snow),
+    ]
+}
+
+/// The tile just beyond the edge of the map where a front of the provided `radius` should arrive,
+/// so that drifting in `direction` carries it across the map.
+fn front_entry_point(
+    map_geometry: &MapGeometry,
+    direction: Direction,
+    radius: u32,
+    rng: &mut impl Rng,
+) -> Option<TilePos> {
+    // Only consider the upwind half of the edge, so that fronts cross the map rather than skimming it
+    let upwind_edge: Vec<Hex> = Hex::ZERO
+        .ring(map_geometry.radius)
+        .into_iter()
+        .filter(|&hex| {
+            hex.neighbor(direction).unsigned_distance_to(Hex::ZERO)
+                < hex.unsigned_distance_to(Hex::ZERO)
+        })
+        .collect();
+
+    let &edge_hex = upwind_edge.choose(rng)?;
+    // Back the front away from the edge, so that it drifts onto the map rather than appearing on it
+    let upwind_step = edge_hex - edge_hex.neighbor(direction);
+    let mut center = edge_hex;
+    for _ in 0..radius {
+        center += upwind_step;
+    }
+
+    Some(TilePos { hex: center })
+}
+
+/// Drifts each front downwind, removes those that have left the map, and occasionally brings in new ones.
+fn move_weather_fronts(
+    time: Res<Time>,
+    season: Res<Season>,
+    wind: Res<Wind>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+    mut weather_fronts: ResMut<WeatherFronts>,
+    mut alerts: EventWriter<Alert>,
+) {
+    weather_fronts.drift_timer.tick(time.delta());
+    if weather_fronts.drift_timer.just_finished() {
+        for front in weather_fronts.fronts.iter_mut() {
+            front.center = front.center.neighbor(front.direction);
+        }
+
+        weather_fronts
+            .fronts
+            .retain(|front| !front.has_left(&map_geometry));
+    }
+
+    weather_fronts.arrival_timer.tick(time.delta());
+    if !weather_fronts.arrival_timer.just_finished() {
+        return;
+    }
+
+    let rng = &mut jitter.world_rng(JitterStream::Fronts);
+    if !rng.gen_bool(WeatherFronts::ARRIVAL_CHANCE) {
+        return;
+    }
+
+    let weights = front_weights(*season);
+    let distribution = WeightedIndex::new(weights.map(|(_, weight)| weight)).unwrap();
+    let weather = weights[distribution.sample(rng)].0;
+    let (min_radius, max_radius) = WeatherFronts::RADIUS_RANGE;
+    let radius = rng.gen_range(min_radius..=max_radius);
+    let direction = wind.direction;
+
+    if let Some(center) = front_entry_point(&map_geometry, direction, radius, rng) {
+        alerts.send(Alert {
+            message: format!("A front of {weather} is approaching"),
+            tile_pos: None,
+        });
+
+        weather_fronts.add(WeatherFront {
+            weather,
+            center,
+            radius,
+            direction,
+        });
+    }
+}
+
+/// Rain and storms wash signals out of the air.
+fn wash_away_signals(
+    time: Res<Time>,
+    local_weather: LocalWeather,
+    map_geometry: Res<MapGeometry>,
+    mut signals: ResMut<Signals>,
+) {
+    let delta = time.delta_seconds();
+
+    // Only the tiles with something to wash away need to be checked
+    let occupied_tiles: HashSet<TilePos> = signals
+        .iter_strengths()
+        .map(|(_, tile_pos, _)| tile_pos)
+        .filter(|&tile_pos| map_geometry.is_valid(tile_pos))
+        .collect();
+
+    let washed_tiles: Vec<(TilePos, f32)> = occupied_tiles
+        .into_iter()
+        .filter_map(|tile_pos| {
+            let washout = local_weather.at(tile_pos).signal_washout_per_second() * delta;
+            (washout > 0.).then_some((tile_pos, (1. - washout).max(0.)))
+        })
+        .collect();
+
+    for (tile_pos, factor) in washed_tiles {
+        signals.scale_at(tile_pos, factor);
+    }
+}
+
+/// Randomly moves the weather to its next state once the current spell is over.
+fn advance_weather(
+    time: Res<Time>,
+    season: Res<Season>,
+    jitter: Jitter,
+    mut current_weather: ResMut<CurrentWeather>,
+    mut alerts: EventWriter<Alert>,
+) {
+    current_weather.timer.tick(time.delta());
+    if !current_weather.timer.just_finished() {
+        return;
+    }
+
+    let transition_weights = current_weather.weather.transition_weights(*season);
+    let distribution = WeightedIndex::new(transition_weights.map(|(_, weight)| weight)).unwrap();
+    let next_weather =
+        transition_weights[distribution.sample(&mut jitter.world_rng(JitterStream::Weather))].0;
+
+    if next_weather != current_weather.weather && next_weather != Weather::Clear {
+        alerts.send(Alert {
+            message: format!("The weather has turned: {next_weather}"),
+            tile_pos: None,
+        });
+    }
+
+    current_weather.weather = next_weather;
+}
+
+/// Rain and drought change the moisture and fertility of the soil beneath them, except where it is sheltered.
+fn apply_weather_to_soil(
+    lod_schedule: Res<LodSchedule>,
+    local_weather: LocalWeather,
+    mut soil_query: Query<(&TilePos, &mut SoilMoisture, &mut Fertility)>,
+    enclosures: Res<Enclosures>,
+) {
+    let delta = lod_schedule.delta_seconds(LodGroup::Soil);
+
+    for (&tile_pos, mut soil_moisture, mut fertility) in soil_query.iter_mut() {
+        if enclosures.is_sheltered(tile_pos) {
+            continue;
+        }
+
+        let weather = local_weather.at(tile_pos);
+
+        let moisture_change = weather.moisture_change_per_second() * delta;
+        let new_moisture = (soil_moisture.0 + moisture_change).clamp(0., 1.);
+        soil_moisture.set_if_neq(SoilMoisture(new_moisture));
+
+        let fertility_change = weather.fertility_change_per_second() * delta;
+        if fertility_change > 0. {
+            fertility.replenish(fertility_change);
+        } else if fertility_change < 0. {
+            fertility.deplete(-fertility_change);
+        }
+    }
+}
+
+/// The energy drained from living structures by storms, per second.
+const STORM_DAMAGE_PER_SECOND: Energy = Energy(1.);
+
+/// The chance per second that a storm destroys each fragile structure.
+const STORM_BREAK_CHANCE_PER_SECOND: f64 = 0.01;
+
+/// Storms damage exposed living structures, and can destroy fragile ones.
+#[allow(clippy::too_many_arguments)]
+fn storm_damage(
+    time: Res<Time>,
+    local_weather: LocalWeather,
+    mut organism_query: Query<(&TilePos, &mut EnergyPool), (With<Organism>, With<Id<Structure>>)>,
+    fragile_query: Query<(Entity, &TilePos, &Id<Structure>), With<Fragile>>,
+    enclosures: Res<Enclosures>,
+    jitter: Jitter,
+    mut alerts: EventWriter<Alert>,
+    mut historical_events: EventWriter<HistoricalEvent>,
+    mut commands: Commands,
+) {
+    if !local_weather.any_storms() {
+        return;
+    }
+
+    let delta = time.delta_seconds();
+    for (&tile_pos, mut energy_pool) in organism_query.iter_mut() {
+        if enclosures.is_sheltered(tile_pos) || local_weather.at(tile_pos) != Weather::Storm {
+            continue;
+        }
+
+        let proposed = energy_pool.current() - STORM_DAMAGE_PER_SECOND * delta;
+        energy_pool.set_current(proposed);
+    }
+
+    let break_chance = (STORM_BREAK_CHANCE_PER_SECOND * delta as f64).min(1.);
+    for (entity, &tile_pos, structure_id) in fragile_query.iter() {
+        if !enclosures.is_sheltered(tile_pos)
+            && local_weather.at(tile_pos) == Weather::Storm
+            && jitter
+                .rng(entity, JitterStream::Weather)
+                .gen_bool(break_chance)
+        {
+            commands.despawn_structure(tile_pos);
+            alerts.send(Alert {
+                message: format!("A storm destroyed a {structure_id}"),
+                tile_pos: Some(tile_pos),
+            });
+            historical_events.send(HistoricalEvent::Destroyed {
+                structure_id: *structure_id,
+                disaster: Disaster::Storm,
+                tile_pos,
+            });
+        }
+    }
+}
+
+/// A bolt of lightning hitting a tile during a storm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightningStrike {
+    /// The tile that was struck
+    pub tile_pos: TilePos,
+}
+
+/// The chance per second that lightning strikes somewhere on the map during a storm.
+const LIGHTNING_CHANCE_PER_SECOND: f64 = 0.2;
+
+/// The energy drained from living structures that are struck by lightning.
+const LIGHTNING_DAMAGE: Energy = Energy(30.);
+
+/// During storms, lightning strikes random tiles beneath them, setting exposed structures alight.
+#[allow(clippy::too_many_arguments)]
+fn strike_lightning(
+    time: Res<Time>,
+    local_weather: LocalWeather,
+    map_geometry: Res<MapGeometry>,
+    enclosures: Res<Enclosures>,
+    jitter: Jitter,
+    mut energy_query: Query<&mut EnergyPool, With<Id<Structure>>>,
+    mut lightning_events: EventWriter<LightningStrike>,
+    mut alerts: EventWriter<Alert>,
+    mut commands: Commands,
+) {
+    if !local_weather.any_storms() {
+        return;
+    }
+
+    let rng = &mut jitter.world_rng(JitterStream::Lightning);
+    let strike_chance = (LIGHTNING_CHANCE_PER_SECOND * time.delta_seconds() as f64).min(1.);
+    if !rng.gen_bool(strike_chance) {
+        return;
+    }
+
+    let stormy_tiles: Vec<TilePos> = hexagon(Hex::ZERO, map_geometry.radius)
+        .map(|hex| TilePos { hex })
+        .filter(|&tile_pos| local_weather.at(tile_pos) == Weather::Storm)
+        .collect();
+    let tile_pos = match stormy_tiles.choose(rng) {
+        Some(&tile_pos) => tile_pos,
+        None => return,
+    };
+
+    // Roofs take the hit instead
+    if enclosures.is_sheltered(tile_pos) {
+        return;
+    }
+
+    lightning_events.send(LightningStrike { tile_pos });
+
+    if let Some(&structure_entity) = map_geometry.structure_index.get(&tile_pos) {
+        if let Ok(mut energy_pool) = energy_query.get_mut(structure_entity) {
+            let proposed = energy_pool.current() - LIGHTNING_DAMAGE;
+            energy_pool.set_current(proposed);
+        }
+
+        commands.entity(structure_entity).insert(OnFire::default());
+
+        alerts.send(Alert {
+            message: "Lightning started a fire".to_string(),
+            tile_pos: Some(tile_pos),
+        });
+    }
+}
+
+/// Controls the weather, and its effects on the world.
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentWeather>()
+            .init_resource::<WeatherFronts>()
+            .add_event::<LightningStrike>()
+            .add_system(advance_weather)
+            .add_system(move_weather_fronts)
+            .add_system(wash_away_signals.after(move_weather_fronts))
+            .add_system(
+                apply_weather_to_soil
+                    .after(advance_weather)
+                    .run_if(lod_group_ready(LodGroup::Soil)),
+            )
+            .add_system(storm_damage.after(advance_weather))
+            // Lightning must ignite structures before storms can destroy them
+            .add_system(strike_lightning.after(advance_weather).before(storm_damage));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn newer_fronts_override_older_ones() {
+        let mut weather_fronts = WeatherFronts::default();
+        weather_fronts.add(WeatherFront {
+            weather: Weather::Rain,
+            center: TilePos::ORIGIN,
+            radius: 3,
+            direction: Direction::Top,
+        });
+        weather_fronts.add(WeatherFront {
+            weather: Weather::Storm,
+            center: TilePos::new(3, 0),
+            radius: 1,
+            direction: Direction::Top,
+        });
+
+        assert_eq!(
+            weather_fronts.weather_at(TilePos::ORIGIN),
+            Some(Weather::Rain)
+        );
+        assert_eq!(
+            weather_fronts.weather_at(TilePos::new(3, 0)),
+            Some(Weather::Storm)
+        );
+        assert_eq!(weather_fronts.weather_at(TilePos::new(10, 0)), None);
+    }
+
+    #[test]
+    fn fronts_arrive_off_the_map_and_drift_across_it() {
+        let map_geometry = MapGeometry::new(10);
+        let rng = &mut StdRng::seed_from_u64(0);
+
+        for direction in Direction::ALL_DIRECTIONS {
+            let mut front = WeatherFront {
+                weather: Weather::Rain,
+                center: front_entry_point(&map_geometry, direction, 3, rng).unwrap(),
+                radius: 3,
+                direction,
+            };
+
+            assert!(!map_geometry.is_valid(front.center));
+
+            // The front must pass over the map before leaving it
+            let mut covered_the_map = false;
+            let mut steps = 0;
+            while !front.has_left(&map_geometry) {
+                front.center = front.center.neighbor(direction);
+                covered_the_map |= front
+                    .tiles()
+                    .any(|tile_pos| map_geometry.is_valid(tile_pos));
+                steps += 1;
+                assert!(steps < 100, "The front never left the map");
+            }
+            assert!(covered_the_map);
+        }
+    }
+
+    #[test]
+    fn snow_only_falls_in_winter() {
+        for season in [Season::Spring, Season::Summer, Season::Autumn] {
+            assert!(front_weights(season)
+                .iter()
+                .all(|&(weather, weight)| weather != Weather::Snow || weight == 0.));
+        }
+
+        assert!(front_weights(Season::Winter)
+            .iter()
+            .any(|&(weather, weight)| weather == Weather::Snow && weight > 0.));
+    }
+
+    #[test]
+    fn no_snow_in_summer() {
+        for weather in [
+            Weather::Clear,
+            Weather::Rain,
+            Weather::Storm,
+            Weather::Drought,
+            Weather::Snow,
+        ] {
+            for season in [Season::Spring, Season::Summer, Season::Autumn] {
+                // Even a spell of snow left over from winter must end
+                assert!(weather
+                    .transition_weights(season)
+                    .iter()
+                    .all(|&(next, weight)| next != Weather::Snow || weight == 0.));
+            }
+        }
+
+        assert!(Weather::Snow
+            .transition_weights(Season::Winter)
+            .iter()
+            .any(|&(next, weight)| next == Weather::Snow && weight > 0.));
+        assert!(Weather::Clear
+            .transition_weights(Season::Winter)
+            .iter()
+            .all(|&(next, weight)| next != Weather::Drought || weight == 0.));
+    }
+}
@@ -0,0 +1,232 @@
+//! Weather changes over time, wetting, drying, warming and battering the map.
+//!
+//! The [`CurrentWeather`] covers the whole map, but [`WeatherFronts`] drift across it with the wind,
+//! bringing their own weather to the tiles beneath them.
+//! Use [`LocalWeather`] to find the weather on a particular tile.
+//!
+//! The weather only changes by itself with the `weather` feature, which adds the [`WeatherPlugin`].
+//! Without it, the skies stay clear and no fronts arrive unless the director calls up a storm.
+
+use crate::bevy::{ecs::system::SystemParam, prelude::*};
+use core::fmt::Display;
+use hexx::Direction;
+use serde::{Deserialize, Serialize};
+
+use super::{geometry::TilePos, save::SavedWeather};
+
+#[cfg(feature = "weather")]
+mod dynamics;
+
+#[cfg(feature = "weather")]
+pub use dynamics::LightningStrike;
+#[cfg(feature = "weather")]
+pub(super) use dynamics::WeatherPlugin;
+
+/// The state of the sky over the whole map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Weather {
+    /// Nothing out of the ordinary.
+    #[default]
+    Clear,
+    /// Rain wets the soil.
+    Rain,
+    /// Storms soak the soil and damage exposed structures.
+    Storm,
+    /// Droughts bake the moisture out of the soil.
+    Drought,
+    /// Snow chills the whole map.
+    Snow,
+}
+
+impl Weather {
+    /// The change in ambient temperature caused by this weather, in degrees Celsius.
+    pub fn temperature_offset(&self) -> f32 {
+        match self {
+            Weather::Clear => 0.,
+            Weather::Rain => -3.,
+            Weather::Storm => -5.,
+            Weather::Drought => 10.,
+            Weather::Snow => -25.,
+        }
+    }
+
+    /// The multiplier applied to the walking speed of units caught out in this weather.
+    pub fn walking_speed_multiplier(&self) -> f32 {
+        match self {
+            Weather::Clear | Weather::Drought => 1.,
+            Weather::Rain => 0.8,
+            Weather::Storm => 0.6,
+            Weather::Snow => 0.7,
+        }
+    }
+
+    /// How quickly fires burn out in this weather, relative to clear skies.
+    ///
+    /// Wet weather helps put fires out, while droughts keep them going.
+    pub fn burn_rate(&self) -> f32 {
+        match self {
+            Weather::Rain | Weather::Storm | Weather::Snow => 2.,
+            Weather::Clear => 1.,
+            Weather::Drought => 0.5,
+        }
+    }
+}
+
+impl Display for Weather {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Weather::Clear => "Clear",
+            Weather::Rain => "Rain",
+            Weather::Storm => "Storm",
+            Weather::Drought => "Drought",
+            Weather::Snow => "Snow",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The weather over the map, and how long until it next changes.
+#[derive(Resource, Debug)]
+pub struct CurrentWeather {
+    /// The current weather
+    weather: Weather,
+    /// Counts down until the weather changes
+    timer: Timer,
+}
+
+impl CurrentWeather {
+    /// The number of seconds that each spell of weather lasts.
+    const DURATION_IN_SECONDS: f32 = 30.;
+
+    /// The current weather.
+    pub fn get(&self) -> Weather {
+        self.weather
+    }
+
+    /// Immediately changes the weather, which will last for a full spell.
+    pub fn set(&mut self, weather: Weather) {
+        self.weather = weather;
+        self.timer.reset();
+    }
+}
+
+impl Default for CurrentWeather {
+    fn default() -> Self {
+        CurrentWeather {
+            weather: Weather::default(),
+            timer: Timer::from_seconds(CurrentWeather::DURATION_IN_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+/// A patch of weather that drifts across the map with the wind, overriding the [`CurrentWeather`] beneath it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherFront {
+    /// The weather under this front
+    pub weather: Weather,
+    /// The tile at the center of the front
+    pub center: TilePos,
+    /// The number of tiles from the center to the edge of the front
+    pub radius: u32,
+    /// The direction that the front is drifting in
+    pub direction: Direction,
+}
+
+impl WeatherFront {
+    /// Is `tile_pos` beneath this front?
+    pub fn covers(&self, tile_pos: TilePos) -> bool {
+        self.center.unsigned_distance_to(tile_pos.hex) <= self.radius
+    }
+}
+
+/// The weather fronts currently drifting across the map.
+#[derive(Resource, Debug)]
+pub struct WeatherFronts {
+    /// The active fronts, from oldest to newest
+    fronts: Vec<WeatherFront>,
+    /// Counts down until each front drifts another tile
+    drift_timer: Timer,
+    /// Counts down until the next chance for a new front to arrive
+    arrival_timer: Timer,
+}
+
+impl WeatherFronts {
+    /// The number of seconds it takes each front to drift a single tile.
+    const SECONDS_PER_TILE: f32 = 3.;
+
+    /// The number of seconds between each chance for a new front to arrive.
+    const SECONDS_BETWEEN_ARRIVALS: f32 = 20.;
+
+    /// The weather brought by the newest front covering `tile_pos`, if any.
+    pub fn weather_at(&self, tile_pos: TilePos) -> Option<Weather> {
+        self.fronts
+            .iter()
+            .rev()
+            .find(|front| front.covers(tile_pos))
+            .map(|front| front.weather)
+    }
+}
+
+impl Default for WeatherFronts {
+    fn default() -> Self {
+        WeatherFronts {
+            fronts: Vec::new(),
+            drift_timer: Timer::from_seconds(WeatherFronts::SECONDS_PER_TILE, TimerMode::Repeating),
+            arrival_timer: Timer::from_seconds(
+                WeatherFronts::SECONDS_BETWEEN_ARRIVALS,
+                TimerMode::Repeating,
+            ),
+        }
+    }
+}
+
+/// Looks up the weather on individual tiles, accounting for both the [`CurrentWeather`] and any [`WeatherFronts`].
+#[derive(SystemParam)]
+pub struct LocalWeather<'w> {
+    /// The weather over the whole map
+    current_weather: Res<'w, CurrentWeather>,
+    /// The fronts that override it
+    weather_fronts: Res<'w, WeatherFronts>,
+}
+
+impl<'w> LocalWeather<'w> {
+    /// The weather on the tile at `tile_pos`.
+    pub fn at(&self, tile_pos: TilePos) -> Weather {
+        self.weather_fronts
+            .weather_at(tile_pos)
+            .unwrap_or_else(|| self.current_weather.get())
+    }
+
+    /// The weather, its fronts and how far along each of their timers are, so that they can be saved.
+    pub fn record(&self) -> SavedWeather {
+        SavedWeather {
+            weather: self.current_weather.get(),
+            spell_elapsed: self.current_weather.timer.elapsed(),
+            drift_elapsed: self.weather_fronts.drift_timer.elapsed(),
+            arrival_elapsed: self.weather_fronts.arrival_timer.elapsed(),
+            fronts: self.weather_fronts.fronts.clone(),
+        }
+    }
+}
+
+/// Replaces the weather and its fronts with the `saved_weather`, e.g. when a saved game is loaded.
+pub fn restore_weather(world: &mut World, saved_weather: &SavedWeather) {
+    let mut current_weather = world.resource_mut::<CurrentWeather>();
+    current_weather.weather = saved_weather.weather;
+    current_weather.timer.reset();
+    current_weather
+        .timer
+        .set_elapsed(saved_weather.spell_elapsed);
+
+    let mut weather_fronts = world.resource_mut::<WeatherFronts>();
+    weather_fronts.fronts = saved_weather.fronts.clone();
+    weather_fronts.drift_timer.reset();
+    weather_fronts
+        .drift_timer
+        .set_elapsed(saved_weather.drift_elapsed);
+    weather_fronts.arrival_timer.reset();
+    weather_fronts
+        .arrival_timer
+        .set_elapsed(saved_weather.arrival_elapsed);
+}
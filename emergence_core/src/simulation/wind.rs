@@ -0,0 +1,63 @@
+//! Wind blows across the whole map, carrying signals downwind as they diffuse.
+
+use crate::bevy::prelude::*;
+use hexx::Direction;
+
+/// The wind blowing across the map.
+///
+/// Each tick, `advection_fraction` of the signal that remains on a tile after diffusion is carried to the downwind neighbor.
+/// Insert this resource before adding the simulation plugins to change the starting wind.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// The direction the wind is blowing towards
+    pub direction: Direction,
+    /// The fraction of each tile's signal that is carried downwind each tick.
+    ///
+    /// This must be between 0 and 1.
+    pub advection_fraction: f32,
+}
+
+impl Wind {
+    /// No wind at all: signals diffuse evenly in every direction.
+    pub const CALM: Wind = Wind {
+        direction: Direction::Top,
+        advection_fraction: 0.,
+    };
+
+    /// Creates a new wind blowing towards `direction`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `advection_fraction` is not between 0 and 1.
+    pub fn new(direction: Direction, advection_fraction: f32) -> Self {
+        assert!(
+            (0. ..=1.).contains(&advection_fraction),
+            "Advection fraction must be between 0 and 1, but was {advection_fraction}"
+        );
+
+        Wind {
+            direction,
+            advection_fraction,
+        }
+    }
+
+    /// Is the wind blowing at all?
+    pub fn is_calm(&self) -> bool {
+        self.advection_fraction == 0.
+    }
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind::CALM
+    }
+}
+
+/// Sets up the [`Wind`] resource.
+pub(super) struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>();
+    }
+}
@@ -0,0 +1,285 @@
+//! Automation rules let structures react to their own state, forming a simple programmable logistics layer.
+//!
+//! Each rule pairs a [`Condition`] with an [`Effect`], and is checked every tick.
+//! Rules are plain data, stored on each structure in [`AutomationRules`].
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+
+use crate::{
+    manifest::{Id, Item},
+    signals::{Emitter, SignalType},
+};
+
+use super::crafting::{set_emitter, InputInventory, OutputInventory};
+
+/// Evaluates the [`AutomationRules`] of each structure.
+pub(super) struct AutomationPlugin;
+
+impl Plugin for AutomationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_automation_rules.after(set_emitter));
+    }
+}
+
+/// A test of a structure's state, which decides whether an [`AutomationRule`] applies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Fewer than `count` of `item_id` are stored across the structure's input and output inventories.
+    StoredBelow {
+        /// The item to count
+        item_id: Id<Item>,
+        /// The threshold
+        count: usize,
+    },
+    /// More than `count` of `item_id` are stored across the structure's input and output inventories.
+    StoredAbove {
+        /// The item to count
+        item_id: Id<Item>,
+        /// The threshold
+        count: usize,
+    },
+    /// Every slot of the structure's output inventory is full.
+    OutputFull,
+}
+
+impl Condition {
+    /// Does this condition hold for a structure with the provided inventories?
+    fn is_met(&self, input: &InputInventory, output: &OutputInventory) -> bool {
+        let stored = |item_id: Id<Item>| input.item_count(item_id) + output.item_count(item_id);
+
+        match *self {
+            Condition::StoredBelow { item_id, count } => stored(item_id) < count,
+            Condition::StoredAbove { item_id, count } => stored(item_id) > count,
+            Condition::OutputFull => output.is_full(),
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::StoredBelow { item_id, count } => write!(f, "stored {item_id} < {count}"),
+            Condition::StoredAbove { item_id, count } => write!(f, "stored {item_id} > {count}"),
+            Condition::OutputFull => write!(f, "output full"),
+        }
+    }
+}
+
+/// What happens to a structure while the [`Condition`] of an [`AutomationRule`] holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// Multiplies the strength of any `signal_type` signal that the structure emits.
+    BoostEmission {
+        /// The signal to boost
+        signal_type: SignalType,
+        /// The factor to multiply the signal strength by
+        multiplier: f32,
+    },
+    /// Stops the structure from starting or progressing its recipe.
+    PauseRecipe,
+}
+
+impl Display for Effect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Effect::BoostEmission {
+                signal_type,
+                multiplier,
+            } => write!(f, "boost {signal_type} emission x{multiplier}"),
+            Effect::PauseRecipe => write!(f, "pause recipe"),
+        }
+    }
+}
+
+/// When the `condition` holds, apply the `effect`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutomationRule {
+    /// When should this rule apply?
+    pub condition: Condition,
+    /// What does this rule do?
+    pub effect: Effect,
+}
+
+impl Display for AutomationRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "When {}, {}", self.condition, self.effect)
+    }
+}
+
+/// The automation rules configured for a single structure.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct AutomationRules {
+    /// The rules, in the order that they are applied
+    pub rules: Vec<AutomationRule>,
+}
+
+impl AutomationRules {
+    /// Adds a rule that applies `effect` when `condition` holds.
+    pub fn with_rule(mut self, condition: Condition, effect: Effect) -> Self {
+        self.rules.push(AutomationRule { condition, effect });
+        self
+    }
+
+    /// Are there no rules at all?
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl Display for AutomationRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{rule}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Marks structures whose recipe has been paused by an [`AutomationRule`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecipePaused;
+
+/// Applies the effects of every automation rule whose condition holds.
+///
+/// This runs after [`set_emitter`], which recomputes emitted signals from scratch every tick,
+/// so boosts never compound.
+fn apply_automation_rules(
+    mut structure_query: Query<(
+        Entity,
+        &AutomationRules,
+        &InputInventory,
+        &OutputInventory,
+        &mut Emitter,
+        Option<&RecipePaused>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, automation_rules, input, output, mut emitter, maybe_paused) in
+        structure_query.iter_mut()
+    {
+        let mut should_pause = false;
+
+        for rule in &automation_rules.rules {
+            if !rule.condition.is_met(input, output) {
+                continue;
+            }
+
+            match rule.effect {
+                Effect::BoostEmission {
+                    signal_type,
+                    multiplier,
+                } => {
+                    for (emitted_type, strength) in emitter.signals.iter_mut() {
+                        if *emitted_type == signal_type {
+                            *strength = *strength * multiplier;
+                        }
+                    }
+                }
+                Effect::PauseRecipe => should_pause = true,
+            }
+        }
+
+        match (should_pause, maybe_paused.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(RecipePaused);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<RecipePaused>();
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::inventory::Inventory;
+    use crate::items::ItemCount;
+    use crate::manifest::StructureManifest;
+    use crate::signals::SignalStrength;
+
+    /// An inventory holding exactly `count` of `item_id`.
+    fn filled_inventory(item_id: Id<Item>, count: usize) -> Inventory {
+        let mut inventory = Inventory::new_from_item(ItemCount::new(item_id, count));
+        for slot in inventory.iter_mut() {
+            slot.add_all_or_nothing(count).unwrap();
+        }
+        inventory
+    }
+
+    #[test]
+    fn stored_items_are_counted_across_inventories() {
+        let item_id = Id::new(1);
+        let input = InputInventory {
+            inventory: filled_inventory(item_id, 2),
+        };
+        let output = OutputInventory {
+            inventory: filled_inventory(item_id, 3),
+        };
+
+        assert!(Condition::StoredBelow { item_id, count: 6 }.is_met(&input, &output));
+        assert!(!Condition::StoredBelow { item_id, count: 5 }.is_met(&input, &output));
+        assert!(Condition::StoredAbove { item_id, count: 4 }.is_met(&input, &output));
+        assert!(!Condition::StoredAbove { item_id, count: 5 }.is_met(&input, &output));
+    }
+
+    #[test]
+    fn rules_are_displayed_as_sentences() {
+        let rules = AutomationRules::default()
+            .with_rule(Condition::OutputFull, Effect::PauseRecipe)
+            .with_rule(
+                Condition::OutputFull,
+                Effect::BoostEmission {
+                    signal_type: SignalType::Alarm,
+                    multiplier: 2.,
+                },
+            );
+
+        assert_eq!(
+            rules.to_string(),
+            "When output full, pause recipe\nWhen output full, boost Alarm emission x2\n"
+        );
+    }
+
+    #[test]
+    fn ant_hives_push_out_eggs_that_pile_up() {
+        let automation = StructureManifest::default()
+            .get(Id::from_string_id("ant_hive"))
+            .automation
+            .clone();
+        let push_eggs = SignalType::Push(Id::ant_egg());
+
+        let mut app = App::new();
+        app.add_system(apply_automation_rules);
+
+        let mut spawn_hive = |n_eggs: usize| {
+            app.world
+                .spawn((
+                    automation.clone(),
+                    InputInventory {
+                        inventory: filled_inventory(Id::leuco_chunk(), 5),
+                    },
+                    OutputInventory {
+                        inventory: filled_inventory(Id::ant_egg(), n_eggs),
+                    },
+                    Emitter {
+                        signals: vec![(push_eggs, SignalStrength::new(1.))],
+                        ..Default::default()
+                    },
+                ))
+                .id()
+        };
+        let crowded = spawn_hive(3);
+        let quiet = spawn_hive(2);
+
+        app.update();
+
+        let push_strength =
+            |app: &App, entity: Entity| app.world.get::<Emitter>(entity).unwrap().signals[0].1;
+        assert_eq!(push_strength(&app, crowded), SignalStrength::new(2.));
+        assert_eq!(push_strength(&app, quiet), SignalStrength::new(1.));
+    }
+}
@@ -0,0 +1,318 @@
+//! Decides which of the queued ghosts should be built first.
+//!
+//! Units follow signals to their work, so ghosts that should wait simply stay quiet until their turn.
+//! A ghost waits if no member of the colony can reach it yet, which means that roads and bridges leading to it are built first.
+//! Ghosts that block movement also wait if building them would cut the colony off from other ghosts,
+//! so that walls are not closed around interiors that still need work.
+//! Blocking ghosts are considered from the outside in, starting with those farthest from the colony,
+//! so that workers finish the far side of a wall and retreat towards home rather than stranding themselves.
+//!
+//! Some ghosts wait no matter what the rest of the queue looks like:
+//! ghosts that did not suit their tile when they were zoned, and crops outside of their sowing season.
+//! These are left out of the queue entirely, so they never hold up or cut off anything else.
+
+use crate::bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use std::collections::VecDeque;
+
+use crate::{
+    manifest::{Id, Structure, StructureManifest, Unit, UnitManifest},
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        time::{every_second, Season},
+    },
+};
+
+use super::{
+    construction::{Ghost, Unsuitable},
+    StructureData,
+};
+
+/// Keeps the [`BuildOrder`] of each ghost up to date.
+pub(super) struct BuildOrderPlugin;
+
+impl Plugin for BuildOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(order_construction.run_if(every_second()));
+    }
+}
+
+/// Whether a ghost should be built now, or wait for other ghosts to be built first.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BuildOrder {
+    /// The ghost can be built right away.
+    #[default]
+    Ready,
+    /// The ghost is waiting, and will not ask for materials or work.
+    Postponed,
+}
+
+/// A ghost in the construction queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedGhost {
+    /// The tile that the ghost is on
+    tile_pos: TilePos,
+    /// Will the finished structure stop units from walking across its tile?
+    blocks_movement: bool,
+}
+
+/// The distance that a unit starting from one of the `starts` would need to walk to reach each tile.
+///
+/// Tiles in `blocked` are treated as impassable, as if the ghosts there had already been built.
+fn walking_distances(
+    starts: &[TilePos],
+    blocked: &HashSet<TilePos>,
+    map_geometry: &MapGeometry,
+) -> HashMap<TilePos, usize> {
+    let mut distances = HashMap::new();
+    let mut frontier = VecDeque::new();
+
+    for &start in starts {
+        if distances.insert(start, 0).is_none() {
+            frontier.push_back(start);
+        }
+    }
+
+    while let Some(tile_pos) = frontier.pop_front() {
+        let distance = distances[&tile_pos];
+
+        for neighbor in tile_pos.reachable_neighbors(map_geometry) {
+            if !blocked.contains(&neighbor) && !distances.contains_key(&neighbor) {
+                distances.insert(neighbor, distance + 1);
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Should a ghost of `structure_data` wait, regardless of the other ghosts in the queue?
+fn is_waiting(structure_data: &StructureData, unsuitable: bool, season: Season) -> bool {
+    let out_of_season = structure_data
+        .crop_schedule()
+        .map_or(false, |crop_schedule| !crop_schedule.can_be_sown(season));
+
+    unsuitable || out_of_season
+}
+
+/// The walking distance to the closest tile from which the ghost at `tile_pos` can be built, if there is one.
+///
+/// Structures usually block movement, so standing on any neighboring tile is enough.
+fn distance_to_ghost(
+    tile_pos: TilePos,
+    distances: &HashMap<TilePos, usize>,
+    map_geometry: &MapGeometry,
+) -> Option<usize> {
+    std::iter::once(tile_pos)
+        .chain(tile_pos.all_neighbors(map_geometry))
+        .filter_map(|tile_pos| distances.get(&tile_pos).copied())
+        .min()
+}
+
+/// The tiles of the `queued_ghosts` that should wait for others to be built first.
+///
+/// `colony_positions` are the tiles where the workers who will build the ghosts are standing.
+///
+/// Every ghost that is not postponed can be finished in any order without making any other ghost unreachable,
+/// because making more tiles passable never cuts anything off.
+fn postponed_ghosts(
+    queued_ghosts: &[QueuedGhost],
+    colony_positions: &[TilePos],
+    map_geometry: &MapGeometry,
+) -> HashSet<TilePos> {
+    let mut blocked = HashSet::new();
+    let distances = walking_distances(colony_positions, &blocked, map_geometry);
+    let mut postponed = HashSet::new();
+    let mut blocking_ghosts = Vec::new();
+
+    for ghost in queued_ghosts {
+        match distance_to_ghost(ghost.tile_pos, &distances, map_geometry) {
+            // Roads and bridges leading to this ghost must be built first
+            None => {
+                postponed.insert(ghost.tile_pos);
+            }
+            Some(distance) if ghost.blocks_movement => {
+                blocking_ghosts.push((distance, ghost.tile_pos))
+            }
+            Some(_) => (),
+        }
+    }
+
+    // From the outside in: the farthest ghosts are built first
+    blocking_ghosts.sort_by(|(a_distance, a), (b_distance, b)| {
+        b_distance.cmp(a_distance).then((a.x, a.y).cmp(&(b.x, b.y)))
+    });
+
+    for (_, tile_pos) in blocking_ghosts {
+        blocked.insert(tile_pos);
+        let new_distances = walking_distances(colony_positions, &blocked, map_geometry);
+
+        let cuts_off_other_ghosts = queued_ghosts.iter().any(|ghost| {
+            !postponed.contains(&ghost.tile_pos)
+                && distance_to_ghost(ghost.tile_pos, &new_distances, map_geometry).is_none()
+        });
+
+        if cuts_off_other_ghosts {
+            blocked.remove(&tile_pos);
+            postponed.insert(tile_pos);
+        }
+    }
+
+    postponed
+}
+
+/// Decides which ghosts should be built now, and which should wait their turn, once a second.
+fn order_construction(
+    mut ghost_query: Query<
+        (
+            &TilePos,
+            &Id<Structure>,
+            &mut BuildOrder,
+            Option<&Unsuitable>,
+        ),
+        With<Ghost>,
+    >,
+    unit_query: Query<(&TilePos, &Id<Unit>)>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    map_geometry: Res<MapGeometry>,
+    season: Res<Season>,
+) {
+    // Intruders are not expected to help out
+    let colony_positions: Vec<TilePos> = unit_query
+        .iter()
+        .filter(|(_, &unit_id)| unit_manifest.get(unit_id).nest().is_some())
+        .map(|(&tile_pos, _)| tile_pos)
+        .collect();
+
+    let mut waiting = HashSet::new();
+    let mut queued_ghosts = Vec::new();
+    for (&tile_pos, &structure_id, _, maybe_unsuitable) in ghost_query.iter() {
+        let structure_data = structure_manifest.get(structure_id);
+
+        if is_waiting(structure_data, maybe_unsuitable.is_some(), *season) {
+            waiting.insert(tile_pos);
+        } else {
+            queued_ghosts.push(QueuedGhost {
+                tile_pos,
+                blocks_movement: !structure_data.is_crossing(),
+            });
+        }
+    }
+
+    let postponed = postponed_ghosts(&queued_ghosts, &colony_positions, &map_geometry);
+
+    for (tile_pos, _, mut build_order, _) in ghost_query.iter_mut() {
+        let new_build_order = if waiting.contains(tile_pos) || postponed.contains(tile_pos) {
+            BuildOrder::Postponed
+        } else {
+            BuildOrder::Ready
+        };
+
+        build_order.set_if_neq(new_build_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ghost of a structure that blocks movement.
+    fn wall(x: i32, y: i32) -> QueuedGhost {
+        QueuedGhost {
+            tile_pos: TilePos::new(x, y),
+            blocks_movement: true,
+        }
+    }
+
+    #[test]
+    fn unreachable_ghosts_wait_for_roads() {
+        let mut map_geometry = MapGeometry::new(5);
+        let far_bank = TilePos::new(3, 0);
+        // Cut the far bank off from the rest of the map
+        for neighbor in far_bank.all_neighbors(&map_geometry) {
+            map_geometry.open_water.insert(neighbor);
+        }
+
+        let bridge = QueuedGhost {
+            tile_pos: TilePos::new(2, 0),
+            blocks_movement: false,
+        };
+        let house = wall(3, 0);
+
+        let postponed = postponed_ghosts(&[bridge, house], &[TilePos::ORIGIN], &map_geometry);
+        assert_eq!(postponed, HashSet::from_iter([house.tile_pos]));
+    }
+
+    #[test]
+    fn enclosures_are_not_closed_around_their_interior() {
+        let map_geometry = MapGeometry::new(5);
+        let interior = wall(3, -1);
+        let mut queued_ghosts: Vec<QueuedGhost> = interior
+            .tile_pos
+            .all_neighbors(&map_geometry)
+            .into_iter()
+            .map(|tile_pos| QueuedGhost {
+                tile_pos,
+                blocks_movement: true,
+            })
+            .collect();
+        queued_ghosts.push(interior);
+
+        let postponed = postponed_ghosts(&queued_ghosts, &[TilePos::ORIGIN], &map_geometry);
+
+        // Only the last gap in the ring waits
+        assert_eq!(postponed.len(), 1);
+        assert!(!postponed.contains(&interior.tile_pos));
+    }
+
+    #[test]
+    fn dead_ends_are_built_from_the_far_end() {
+        let mut map_geometry = MapGeometry::new(5);
+        let near = wall(1, 0);
+        let far = wall(2, 0);
+        // A dead end corridor: the far tile can only be reached through the near one
+        let corridor = [TilePos::ORIGIN, near.tile_pos, far.tile_pos];
+        for tile_pos in [near.tile_pos, far.tile_pos] {
+            for neighbor in tile_pos.all_neighbors(&map_geometry) {
+                if !corridor.contains(&neighbor) {
+                    map_geometry.open_water.insert(neighbor);
+                }
+            }
+        }
+
+        let postponed = postponed_ghosts(&[near, far], &[TilePos::ORIGIN], &map_geometry);
+        assert_eq!(postponed, HashSet::from_iter([near.tile_pos]));
+    }
+
+    #[test]
+    fn crops_wait_for_their_sowing_season() {
+        let structure_manifest = StructureManifest::default();
+        let acacia = structure_manifest.get(Id::from_string_id("acacia"));
+        let crop_schedule = acacia.crop_schedule().unwrap();
+
+        for season in [
+            Season::Spring,
+            Season::Summer,
+            Season::Autumn,
+            Season::Winter,
+        ] {
+            assert_eq!(
+                is_waiting(acacia, false, season),
+                !crop_schedule.can_be_sown(season)
+            );
+        }
+    }
+
+    #[test]
+    fn unsuitable_ghosts_wait() {
+        let structure_manifest = StructureManifest::default();
+        let leuco = structure_manifest.get(Id::from_string_id("leuco"));
+
+        assert!(!is_waiting(leuco, false, Season::Spring));
+        assert!(is_waiting(leuco, true, Season::Spring));
+    }
+}
@@ -1,31 +1,32 @@
 //! Methods to use [`Commands`] to manipulate structures.
 
-use bevy::{
+use crate::bevy::{
     ecs::system::Command,
     prelude::{Commands, DespawnRecursiveExt, Mut, World},
+    time::Time,
 };
 use hexx::Direction;
-use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{
-    asset_management::{
-        manifest::{ItemManifest, RecipeManifest},
-        structures::StructureHandles,
-    },
-    graphics::InheritedMaterial,
+    items::{inventory::Inventory, spoilage::Preserving},
+    manifest::{ItemManifest, RecipeManifest},
     organisms::OrganismBundle,
-    player_interaction::clipboard::ClipboardData,
+    signals::{EmissionArea, Emitter},
     simulation::geometry::{Facing, MapGeometry, TilePos},
 };
 
 use super::{
-    construction::{GhostBundle, GhostKind, PreviewBundle},
-    crafting::CraftingBundle,
-    StructureBundle, StructureManifest,
+    construction::{Forbidden, GhostBundle, PreviewBundle, Unsuitable},
+    crafting::{CraftingBundle, InputInventory},
+    express::ExpressNode,
+    irrigation::{Cistern, IrrigationChannel, WaterworksKind},
+    traps::BAIT_SCENT_RADIUS,
+    ClipboardData, Fragile, StructureBundle, StructureManifest,
 };
 
 /// An extension trait for [`Commands`] for working with structures.
-pub(crate) trait StructureCommandsExt {
+pub trait StructureCommandsExt {
     /// Spawns a structure defined by `data` at `tile_pos`.
     ///
     /// Has no effect if the tile position is already occupied by an existing structure.
@@ -39,7 +40,7 @@ pub(crate) trait StructureCommandsExt {
         &mut self,
         tile_pos: TilePos,
         data: ClipboardData,
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
     );
 
     /// Despawns any structure at the provided `tile_pos`.
@@ -50,7 +51,8 @@ pub(crate) trait StructureCommandsExt {
     /// Spawns a ghost with data defined by `data` at `tile_pos`.
     ///
     /// Replaces any existing ghost.
-    fn spawn_ghost(&mut self, tile_pos: TilePos, data: ClipboardData);
+    /// Ghosts that are `unsuitable` for their tile wait rather than being built.
+    fn spawn_ghost(&mut self, tile_pos: TilePos, data: ClipboardData, unsuitable: bool);
 
     /// Despawns any ghost at the provided `tile_pos`.
     ///
@@ -73,7 +75,7 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         self.add(SpawnStructureCommand {
             tile_pos,
             data,
-            randomized: false,
+            randomization_seed: None,
         });
     }
 
@@ -81,7 +83,7 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         &mut self,
         tile_pos: TilePos,
         mut data: ClipboardData,
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
     ) {
         let direction = *Direction::ALL_DIRECTIONS.choose(rng).unwrap();
         data.facing = Facing { direction };
@@ -89,7 +91,7 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         self.add(SpawnStructureCommand {
             tile_pos,
             data,
-            randomized: true,
+            randomization_seed: Some(rng.gen()),
         });
     }
 
@@ -97,8 +99,12 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         self.add(DespawnStructureCommand { tile_pos });
     }
 
-    fn spawn_ghost(&mut self, tile_pos: TilePos, data: ClipboardData) {
-        self.add(SpawnGhostCommand { tile_pos, data });
+    fn spawn_ghost(&mut self, tile_pos: TilePos, data: ClipboardData, unsuitable: bool) {
+        self.add(SpawnGhostCommand {
+            tile_pos,
+            data,
+            unsuitable,
+        });
     }
 
     fn despawn_ghost(&mut self, tile_pos: TilePos) {
@@ -124,8 +130,10 @@ struct SpawnStructureCommand {
     tile_pos: TilePos,
     /// Data about the structure to spawn.
     data: ClipboardData,
-    /// Should the generated structure be randomized
-    randomized: bool,
+    /// The seed used to randomize the generated structure, if it should be randomized.
+    ///
+    /// This is drawn from the caller's random number generator, so seeded world generation stays reproducible.
+    randomization_seed: Option<u64>,
 }
 
 impl Command for SpawnStructureCommand {
@@ -142,53 +150,111 @@ impl Command for SpawnStructureCommand {
             return;
         }
 
+        let structure_id = self.data.structure_id;
         let structure_variety = world
             .resource::<StructureManifest>()
-            .get(self.data.structure_id)
+            .get(structure_id)
             .clone();
 
-        let structure_handles = world.resource::<StructureHandles>();
-
-        let picking_mesh = structure_handles.picking_mesh.clone_weak();
-        let scene_handle = structure_handles
-            .scenes
-            .get(&self.data.structure_id)
-            .unwrap()
-            .clone_weak();
-        let world_pos = self
-            .tile_pos
-            .into_world_pos(world.resource::<MapGeometry>());
-
         let structure_entity = world
-            .spawn(StructureBundle::new(
-                self.tile_pos,
-                self.data,
-                picking_mesh,
-                scene_handle,
-                world_pos,
-            ))
+            .spawn(StructureBundle::new(self.tile_pos, self.data))
             .id();
 
         // PERF: these operations could be done in a single archetype move with more branching
         if let Some(organism_details) = &structure_variety.organism {
+            world.entity_mut(structure_entity).insert((
+                OrganismBundle::new(
+                    organism_details.energy_pool.clone(),
+                    organism_details.cold_tolerance,
+                ),
+                organism_details.growth_requirements.clone(),
+            ));
+        };
+
+        if let Some(heat_source) = &structure_variety.heat_source {
             world
                 .entity_mut(structure_entity)
-                .insert(OrganismBundle::new(organism_details.energy_pool.clone()));
-        };
+                .insert(heat_source.clone());
+        }
+
+        if structure_variety.fragile {
+            world.entity_mut(structure_entity).insert(Fragile);
+        }
+
+        if structure_variety.preserves_items {
+            world.entity_mut(structure_entity).insert(Preserving);
+        }
+
+        if let Some(vision_source) = structure_variety.vision {
+            world.entity_mut(structure_entity).insert(vision_source);
+        }
+
+        if !structure_variety.automation.is_empty() {
+            world
+                .entity_mut(structure_entity)
+                .insert(structure_variety.automation.clone());
+        }
+
+        if let Some(guard_post) = structure_variety.guard_post {
+            world.entity_mut(structure_entity).insert(guard_post);
+        }
+
+        if let Some(traversal_time) = structure_variety.express_traversal_time {
+            let built_at = world.resource::<Time>().elapsed();
+            world
+                .entity_mut(structure_entity)
+                .insert(ExpressNode::new(traversal_time, built_at));
+        }
+
+        if let Some(trap) = &structure_variety.trap {
+            let item_manifest = world.resource::<ItemManifest>();
+            let mut bait_inventory = Inventory::new(1);
+            // New inventories allow every item, so this cannot fail
+            bait_inventory
+                .add_empty_slot(trap.bait, item_manifest)
+                .unwrap();
+
+            world.entity_mut(structure_entity).insert((
+                trap.clone(),
+                InputInventory {
+                    inventory: bait_inventory,
+                },
+                Emitter {
+                    area: EmissionArea::Radius(BAIT_SCENT_RADIUS),
+                    ..Default::default()
+                },
+            ));
+        }
+
+        match structure_variety.waterworks {
+            Some(WaterworksKind::Cistern) => {
+                world
+                    .entity_mut(structure_entity)
+                    .insert(Cistern::default());
+            }
+            Some(WaterworksKind::Channel) => {
+                world
+                    .entity_mut(structure_entity)
+                    .insert(IrrigationChannel::default());
+            }
+            None => (),
+        }
 
         if structure_variety.crafts {
             world.resource_scope(|world, recipe_manifest: Mut<RecipeManifest>| {
                 world.resource_scope(|world, item_manifest: Mut<ItemManifest>| {
-                    let crafting_bundle = match self.randomized {
-                        false => CraftingBundle::new(
+                    let crafting_bundle = match self.randomization_seed {
+                        None => CraftingBundle::new(
                             structure_variety.starting_recipe,
+                            structure_id,
                             &recipe_manifest,
                             &item_manifest,
                         ),
-                        true => {
-                            let rng = &mut thread_rng();
+                        Some(seed) => {
+                            let rng = &mut SmallRng::seed_from_u64(seed);
                             CraftingBundle::randomized(
                                 structure_variety.starting_recipe,
+                                structure_id,
                                 &recipe_manifest,
                                 &item_manifest,
                                 rng,
@@ -205,6 +271,14 @@ impl Command for SpawnStructureCommand {
         geometry
             .structure_index
             .insert(self.tile_pos, structure_entity);
+
+        if let Some(crossing) = structure_variety.crossing {
+            geometry.crossing_index.insert(self.tile_pos, crossing);
+        }
+
+        if let Some(wall) = structure_variety.wall {
+            geometry.wall_index.insert(self.tile_pos, wall);
+        }
     }
 }
 
@@ -218,6 +292,9 @@ impl Command for DespawnStructureCommand {
     fn write(self, world: &mut World) {
         let mut geometry = world.resource_mut::<MapGeometry>();
         let maybe_entity = geometry.structure_index.remove(&self.tile_pos);
+        geometry.crossing_index.remove(&self.tile_pos);
+        geometry.wall_index.remove(&self.tile_pos);
+        geometry.express_index.remove(&self.tile_pos);
 
         // Check that there's something there to despawn
         if maybe_entity.is_none() {
@@ -236,6 +313,8 @@ struct SpawnGhostCommand {
     tile_pos: TilePos,
     /// Data about the structure to spawn.
     data: ClipboardData,
+    /// Did the tile fail to meet the structure's requirements?
+    unsuitable: bool,
 }
 
 impl Command for SpawnGhostCommand {
@@ -257,35 +336,15 @@ impl Command for SpawnGhostCommand {
         let structure_manifest = world.resource::<StructureManifest>();
 
         // Spawn a ghost
-        let structure_handles = world.resource::<StructureHandles>();
-
-        let picking_mesh = structure_handles.picking_mesh.clone_weak();
-        let scene_handle = structure_handles
-            .scenes
-            .get(&self.data.structure_id)
-            .unwrap()
-            .clone_weak();
-        let ghostly_handle = structure_handles
-            .ghost_materials
-            .get(&GhostKind::Ghost)
-            .unwrap();
-        let inherited_material = InheritedMaterial(ghostly_handle.clone_weak());
-
-        let world_pos = self
-            .tile_pos
-            .into_world_pos(world.resource::<MapGeometry>());
-
-        let ghost_entity = world
-            .spawn(GhostBundle::new(
-                self.tile_pos,
-                self.data,
-                structure_manifest,
-                picking_mesh,
-                scene_handle,
-                inherited_material,
-                world_pos,
-            ))
-            .id();
+        let mut ghost_entity = world.spawn(GhostBundle::new(
+            self.tile_pos,
+            self.data,
+            structure_manifest,
+        ));
+        if self.unsuitable {
+            ghost_entity.insert(Unsuitable);
+        }
+        let ghost_entity = ghost_entity.id();
 
         let mut geometry = world.resource_mut::<MapGeometry>();
         geometry.ghost_index.insert(self.tile_pos, ghost_entity);
@@ -333,9 +392,6 @@ impl Command for SpawnPreviewCommand {
             return;
         }
 
-        // Compute the world position
-        let world_pos = self.tile_pos.into_world_pos(&map_geometry);
-
         // Remove any existing previews
         let maybe_existing_preview = map_geometry.preview_index.remove(&self.tile_pos);
 
@@ -343,31 +399,12 @@ impl Command for SpawnPreviewCommand {
             world.entity_mut(existing_preview).despawn_recursive();
         }
 
-        let structure_handles = world.resource::<StructureHandles>();
-        let scene_handle = structure_handles
-            .scenes
-            .get(&self.data.structure_id)
-            .unwrap()
-            .clone_weak();
-
-        let ghost_kind = match self.forbidden {
-            true => GhostKind::ForbiddenPreview,
-            false => GhostKind::Preview,
-        };
-
-        let preview_handle = structure_handles.ghost_materials.get(&ghost_kind).unwrap();
-        let inherited_material = InheritedMaterial(preview_handle.clone_weak());
-
         // Spawn a preview
-        let preview_entity = world
-            .spawn(PreviewBundle::new(
-                self.tile_pos,
-                self.data,
-                scene_handle,
-                inherited_material,
-                world_pos,
-            ))
-            .id();
+        let mut preview_entity = world.spawn(PreviewBundle::new(self.tile_pos, self.data));
+        if self.forbidden {
+            preview_entity.insert(Forbidden);
+        }
+        let preview_entity = preview_entity.id();
 
         let mut geometry = world.resource_mut::<MapGeometry>();
         geometry.preview_index.insert(self.tile_pos, preview_entity);
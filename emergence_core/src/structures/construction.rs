@@ -0,0 +1,519 @@
+//! Ghosts are translucent phantom structures, used to show structures that could be or are planned to be built.
+//!
+//! There is an important distinction between "ghosts" and "previews", even though they appear similarly to players.
+//! Ghosts are buildings that are genuinely planned to be built.
+//! Previews are simply hovered, and used as a visual aid to show placement.
+
+use crate::bevy::utils::Duration;
+use crate::bevy::{ecs::system::SystemParam, prelude::*};
+use crate::manifest::StructureManifest;
+use crate::simulation::geometry::MapGeometry;
+
+use crate::{
+    items::ItemCount,
+    manifest::{Id, ItemManifest, Structure},
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::geometry::{Facing, TilePos},
+};
+
+use super::{
+    build_order::BuildOrder,
+    commands::StructureCommandsExt,
+    crafting::{ActiveRecipe, CraftingState, InputInventory, OutputInventory},
+    ClipboardData,
+};
+
+/// A marker component that indicates that this structure is planned to be built, rather than actually existing.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Ghost;
+
+/// The set of components needed to spawn a ghost.
+#[derive(Bundle)]
+pub(super) struct GhostBundle {
+    /// Marker component
+    ghost: Ghost,
+    /// The location of the ghost
+    tile_pos: TilePos,
+    /// The variety of structure
+    structure_id: Id<Structure>,
+    /// The direction the ghost is facing
+    facing: Facing,
+    /// The items required to actually seed this item
+    construction_materials: InputInventory,
+    /// Tracks work that needs to be done on this building
+    crafting_state: CraftingState,
+    /// What should the structure craft when it is first built?
+    active_recipe: ActiveRecipe,
+    /// Emits signals, drawing units towards this ghost to build it
+    emitter: Emitter,
+    /// How far along construction is, used to pick the model to show
+    construction_stage: ConstructionStage,
+    /// Should this ghost be built now, or wait for other ghosts?
+    build_order: BuildOrder,
+}
+
+impl GhostBundle {
+    /// Creates a new [`GhostBundle`].
+    ///
+    /// The ghostly model used to draw and pick the ghost is added separately, by the graphics.
+    pub(super) fn new(
+        tile_pos: TilePos,
+        clipboard_data: ClipboardData,
+        structure_manifest: &StructureManifest,
+    ) -> Self {
+        let structure_id = clipboard_data.structure_id;
+        let structure_data = structure_manifest.get(structure_id);
+
+        GhostBundle {
+            ghost: Ghost,
+            tile_pos,
+            structure_id,
+            facing: clipboard_data.facing,
+            construction_materials: structure_data.construction_materials.clone(),
+            crafting_state: CraftingState::NeedsInput,
+            active_recipe: clipboard_data.active_recipe,
+            emitter: Emitter::default(),
+            construction_stage: ConstructionStage::Foundation,
+            build_order: BuildOrder::default(),
+        }
+    }
+}
+
+/// How far along the construction of a ghost is.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConstructionStage {
+    /// Less than half of the work has been done.
+    ///
+    /// Ghosts waiting for their materials are always at this stage.
+    Foundation,
+    /// At least half of the work has been done.
+    Framework,
+    /// Construction is finished, and the ghost is about to become a structure.
+    Complete,
+}
+
+impl ConstructionStage {
+    /// The fraction of the build duration after which the [`ConstructionStage::Framework`] is shown.
+    const FRAMEWORK_THRESHOLD: f32 = 0.5;
+
+    /// The stage of construction reached by a ghost in `crafting_state`.
+    pub fn from_crafting_state(crafting_state: &CraftingState) -> Self {
+        match *crafting_state {
+            CraftingState::InProgress {
+                progress, required, ..
+            } => {
+                if required.is_zero() || progress >= required {
+                    ConstructionStage::Complete
+                } else if progress.as_secs_f32() / required.as_secs_f32()
+                    >= ConstructionStage::FRAMEWORK_THRESHOLD
+                {
+                    ConstructionStage::Framework
+                } else {
+                    ConstructionStage::Foundation
+                }
+            }
+            CraftingState::RecipeComplete => ConstructionStage::Complete,
+            _ => ConstructionStage::Foundation,
+        }
+    }
+}
+
+/// The models shown for each [`ConstructionStage`] of a structure, as part of its [`StructureData`](super::StructureData).
+///
+/// Each model is the name of a file in `assets/structures`, without its extension.
+/// Stages without their own model show the finished model, squashed down to hint at how much is left to build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstructionModels {
+    /// The model shown from the start of construction
+    pub foundation: Option<&'static str>,
+    /// The model shown once half of the work has been done
+    pub framework: Option<&'static str>,
+}
+
+impl ConstructionModels {
+    /// The model for `stage`, if the structure has its own artwork for it.
+    ///
+    /// Completed ghosts always use the structure's finished model.
+    pub fn get(&self, stage: ConstructionStage) -> Option<&'static str> {
+        match stage {
+            ConstructionStage::Foundation => self.foundation,
+            ConstructionStage::Framework => self.framework,
+            ConstructionStage::Complete => None,
+        }
+    }
+}
+
+/// A marker component that indicates that this structure is planned to be built, rather than actually existing.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Preview;
+
+/// A marker component for previews that cannot be built in their current location.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Forbidden;
+
+/// A marker component for ghosts whose tile did not meet the structure's requirements when they were zoned.
+///
+/// The zoning order is kept, but the ghost waits rather than asking for materials or work.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Unsuitable;
+
+/// The set of components needed to spawn a structure preview.
+#[derive(Bundle)]
+pub(super) struct PreviewBundle {
+    /// Marker component
+    preview: Preview,
+    /// The location of the preview
+    tile_pos: TilePos,
+    /// The variety of structure
+    structure_id: Id<Structure>,
+    /// The direction the preview is facing
+    facing: Facing,
+}
+
+impl PreviewBundle {
+    /// Creates a new [`PreviewBundle`].
+    ///
+    /// The ghostly model used to draw the preview is added separately, by the graphics.
+    pub(super) fn new(tile_pos: TilePos, data: ClipboardData) -> Self {
+        PreviewBundle {
+            preview: Preview,
+            tile_pos,
+            structure_id: data.structure_id,
+            facing: data.facing,
+        }
+    }
+}
+
+/// Marker component for structures that are intended to be deconstructed
+#[derive(Component, Debug)]
+pub struct MarkedForDemolition;
+
+/// Computes the correct signals for ghosts to send throughout their lifecycle
+///
+/// Ghosts whose [`BuildOrder`] is postponed stay silent until it is their turn to be built.
+pub(super) fn ghost_signals(
+    mut ghost_query: Query<
+        (
+            &Id<Structure>,
+            &mut Emitter,
+            Ref<CraftingState>,
+            Ref<BuildOrder>,
+            &InputInventory,
+        ),
+        With<Ghost>,
+    >,
+) {
+    // Ghosts that are ignored will slowly become more important to build.
+    for (&structure_id, mut emitter, crafting_state, build_order, input_inventory) in
+        ghost_query.iter_mut()
+    {
+        if !crafting_state.is_changed() && !build_order.is_changed() {
+            continue;
+        }
+
+        // Reset and recompute all signals
+        emitter.signals.clear();
+
+        if *build_order == BuildOrder::Postponed {
+            continue;
+        }
+
+        match *crafting_state {
+            CraftingState::NeedsInput => {
+                // Emit signals to cause workers to bring the correct item to this ghost
+                for item_slot in input_inventory.iter() {
+                    let signal_type = SignalType::Pull(item_slot.item_id());
+                    let signal_strength = SignalStrength::new(10.);
+                    emitter.signals.push((signal_type, signal_strength))
+                }
+            }
+            CraftingState::InProgress {
+                progress: _,
+                required: _,
+                work_required,
+                worker_present: _,
+            } if work_required => {
+                let signal_type = SignalType::Work(structure_id);
+                let signal_strength = SignalStrength::new(10.);
+                emitter.signals.push((signal_type, signal_strength))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Manages the progression of ghosts from input needed -> work needed -> built.
+///
+/// Transforms ghosts into structures once all of their construction materials have been supplied and enough work has been performed.
+pub(super) fn ghost_lifecyle(
+    mut ghost_query: Query<
+        (
+            &mut CraftingState,
+            &InputInventory,
+            &TilePos,
+            &Id<Structure>,
+            &Facing,
+            &ActiveRecipe,
+        ),
+        With<Ghost>,
+    >,
+    structure_manifest: Res<StructureManifest>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (mut crafting_state, input_inventory, &tile_pos, &structure_id, &facing, active_recipe) in
+        ghost_query.iter_mut()
+    {
+        match *crafting_state {
+            CraftingState::NeedsInput => {
+                *crafting_state = match input_inventory.is_full() {
+                    true => {
+                        let structure_details = structure_manifest.get(structure_id);
+                        CraftingState::InProgress {
+                            progress: Duration::ZERO,
+                            required: structure_details.build_duration,
+                            work_required: structure_details.build_duration > Duration::ZERO,
+                            worker_present: false,
+                        }
+                    }
+                    false => CraftingState::NeedsInput,
+                };
+            }
+            CraftingState::InProgress {
+                progress,
+                required,
+                work_required,
+                worker_present,
+            } => {
+                let mut updated_progress = progress;
+
+                if !work_required || worker_present {
+                    updated_progress += time.delta();
+                }
+
+                *crafting_state = if updated_progress >= required {
+                    CraftingState::RecipeComplete
+                } else {
+                    CraftingState::InProgress {
+                        progress: updated_progress,
+                        required,
+                        work_required,
+                        worker_present,
+                    }
+                }
+            }
+            CraftingState::RecipeComplete => {
+                commands.despawn_ghost(tile_pos);
+                commands.spawn_structure(
+                    tile_pos,
+                    ClipboardData {
+                        structure_id,
+                        facing,
+                        active_recipe: active_recipe.clone(),
+                    },
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Advances the [`ConstructionStage`] of each ghost as work is done on it.
+pub(super) fn update_construction_stage(
+    mut ghost_query: Query<(&CraftingState, &mut ConstructionStage), With<Ghost>>,
+) {
+    for (crafting_state, mut construction_stage) in ghost_query.iter_mut() {
+        let new_stage = ConstructionStage::from_crafting_state(crafting_state);
+
+        construction_stage.set_if_neq(new_stage);
+    }
+}
+
+/// A query for.
+#[derive(SystemParam)]
+pub struct DemolitionQuery<'w, 's> {
+    /// The contained query type.
+    query: Query<'w, 's, &'static Id<Structure>, With<MarkedForDemolition>>,
+}
+
+impl<'w, 's> DemolitionQuery<'w, 's> {
+    /// Is there a structure of type `structure_id` at `structure_pos` that needs to be demolished?
+    ///
+    /// If so, returns `Some(matching_structure_entity_that_needs_to_be_demolished)`.
+    pub fn needs_demolition(
+        &self,
+        structure_pos: TilePos,
+        structure_id: Id<Structure>,
+        map_geometry: &MapGeometry,
+    ) -> Option<Entity> {
+        let entity = *map_geometry.structure_index.get(&structure_pos)?;
+
+        let &found_structure_id = self.query.get(entity).ok()?;
+
+        match found_structure_id == structure_id {
+            true => Some(entity),
+            false => None,
+        }
+    }
+}
+
+/// How much of a structure's construction materials are returned when it is demolished.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct DemolitionRefund {
+    /// The fraction of each construction material that is returned, rounded down
+    pub fraction: f32,
+    /// How far away, in tiles, a structure can be and still store the refunded items
+    pub max_distance: u32,
+}
+
+impl Default for DemolitionRefund {
+    fn default() -> Self {
+        DemolitionRefund {
+            fraction: 0.5,
+            max_distance: 5,
+        }
+    }
+}
+
+impl DemolitionRefund {
+    /// The items returned when tearing down a structure built from `construction_materials`.
+    ///
+    /// The size of each slot is the amount of that material needed to build the structure.
+    pub fn refunded_items(&self, construction_materials: &InputInventory) -> Vec<ItemCount> {
+        construction_materials
+            .iter()
+            .map(|item_slot| {
+                let count =
+                    (item_slot.max_item_count() as f32 * self.fraction.clamp(0., 1.)).floor();
+                ItemCount::new(item_slot.item_id(), count as usize)
+            })
+            .filter(|item_count| item_count.count() > 0)
+            .collect()
+    }
+}
+
+/// Sent when a unit finishes tearing down a structure.
+#[derive(Debug, Clone, Copy)]
+pub struct StructureDemolished {
+    /// The kind of structure that was demolished
+    pub structure_id: Id<Structure>,
+    /// Where the structure stood
+    pub tile_pos: TilePos,
+}
+
+/// Returns part of the construction materials of demolished structures to the nearest structures with room to store them.
+///
+/// Items that do not fit anywhere nearby are lost.
+pub(super) fn refund_demolished_structures(
+    mut demolition_events: EventReader<StructureDemolished>,
+    mut storage_query: Query<(&TilePos, &mut OutputInventory), Without<Ghost>>,
+    structure_manifest: Res<StructureManifest>,
+    item_manifest: Res<ItemManifest>,
+    demolition_refund: Res<DemolitionRefund>,
+) {
+    for event in demolition_events.iter() {
+        let construction_materials = &structure_manifest
+            .get(event.structure_id)
+            .construction_materials;
+
+        let mut storage: Vec<(u32, Mut<OutputInventory>)> = storage_query
+            .iter_mut()
+            .map(|(tile_pos, output_inventory)| {
+                (
+                    tile_pos.hex.unsigned_distance_to(event.tile_pos.hex),
+                    output_inventory,
+                )
+            })
+            .filter(|(distance, _)| *distance <= demolition_refund.max_distance)
+            .collect();
+        storage.sort_by_key(|(distance, _)| *distance);
+
+        for item_count in demolition_refund.refunded_items(construction_materials) {
+            let mut remaining = item_count;
+
+            for (_, output_inventory) in storage.iter_mut() {
+                if remaining.count() == 0 {
+                    break;
+                }
+
+                // Avoid triggering change detection on inventories that are already full
+                if output_inventory.remaining_space_for_item(remaining.item_id(), &item_manifest)
+                    == 0
+                {
+                    continue;
+                }
+
+                remaining = match output_inventory.try_add_item(&remaining, &item_manifest) {
+                    Ok(()) => ItemCount::new(remaining.item_id(), 0),
+                    Err(error) => error.excess_count,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::inventory::Inventory;
+
+    #[test]
+    fn refunds_round_down() {
+        let construction_materials = InputInventory {
+            inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 3)),
+        };
+
+        let half = DemolitionRefund {
+            fraction: 0.5,
+            ..default()
+        };
+        assert_eq!(
+            half.refunded_items(&construction_materials),
+            vec![ItemCount::new(Id::leuco_chunk(), 1)]
+        );
+
+        let nothing = DemolitionRefund {
+            fraction: 0.,
+            ..default()
+        };
+        assert!(nothing.refunded_items(&construction_materials).is_empty());
+    }
+
+    /// A ghost that is `progress` seconds into a build that takes `required` seconds.
+    fn in_progress(progress: u64, required: u64) -> CraftingState {
+        CraftingState::InProgress {
+            progress: Duration::from_secs(progress),
+            required: Duration::from_secs(required),
+            work_required: true,
+            worker_present: false,
+        }
+    }
+
+    #[test]
+    fn stages_advance_as_work_is_done() {
+        assert_eq!(
+            ConstructionStage::from_crafting_state(&CraftingState::NeedsInput),
+            ConstructionStage::Foundation
+        );
+        assert_eq!(
+            ConstructionStage::from_crafting_state(&in_progress(1, 10)),
+            ConstructionStage::Foundation
+        );
+        assert_eq!(
+            ConstructionStage::from_crafting_state(&in_progress(5, 10)),
+            ConstructionStage::Framework
+        );
+        assert_eq!(
+            ConstructionStage::from_crafting_state(&CraftingState::RecipeComplete),
+            ConstructionStage::Complete
+        );
+    }
+
+    #[test]
+    fn instant_builds_are_complete() {
+        assert_eq!(
+            ConstructionStage::from_crafting_state(&in_progress(0, 0)),
+            ConstructionStage::Complete
+        );
+    }
+}
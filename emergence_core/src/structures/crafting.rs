@@ -2,25 +2,45 @@
 
 use std::{fmt::Display, time::Duration};
 
-use bevy::{
+use crate::bevy::{
     ecs::{query::WorldQuery, system::SystemParam},
     prelude::*,
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
-use leafwing_abilities::prelude::Pool;
-use rand::{distributions::Uniform, prelude::Distribution, rngs::ThreadRng};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    asset_management::manifest::{Id, ItemManifest, Recipe, RecipeManifest, Structure},
-    items::{inventory::Inventory, recipe::RecipeData, ItemData},
-    organisms::{energy::EnergyPool, Organism},
-    signals::{Emitter, SignalStrength, SignalType},
-    simulation::geometry::{MapGeometry, TilePos},
+    items::{
+        inventory::{Inventory, ItemFilter},
+        recipe::RecipeData,
+        ItemData,
+    },
+    manifest::{
+        definitions::{definitions_directory, load_items, load_recipes, loaded_or_built_in},
+        Id, ItemManifest, Recipe, RecipeManifest, Structure,
+    },
+    organisms::{
+        energy::EnergyPool,
+        growth::{fertility_growth_multiplier, Stunted},
+        Organism,
+    },
+    signals::{Emitter, SignalModulator, SignalStrength, SignalType},
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        lod::{Distant, LodGroup, LodSchedule},
+        time::Season,
+    },
+    structures::{
+        automation::RecipePaused,
+        irrigation::{Irrigated, IRRIGATION_GROWTH_MULTIPLIER},
+    },
+    terrain::Fertility,
 };
 
 /// The current state in the crafting progress.
-#[derive(Component, Debug, Default, Clone, PartialEq)]
-pub(crate) enum CraftingState {
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CraftingState {
     /// There are resources missing for the recipe.
     #[default]
     NeedsInput,
@@ -86,14 +106,14 @@ impl Display for CraftingState {
 
 /// The input inventory for a structure.
 #[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
-pub(crate) struct InputInventory {
+pub struct InputInventory {
     /// Inner storage
-    pub(crate) inventory: Inventory,
+    pub inventory: Inventory,
 }
 
 impl InputInventory {
     /// Randomizes the contents of this inventory so that each slot is somewhere between empty and full.
-    pub(super) fn randomize(&mut self, rng: &mut ThreadRng) {
+    pub(super) fn randomize(&mut self, rng: &mut impl Rng) {
         for item_slot in self.iter_mut() {
             item_slot.randomize(rng);
         }
@@ -102,14 +122,14 @@ impl InputInventory {
 
 /// The output inventory for a structure.
 #[derive(Component, Debug, Default, Deref, DerefMut)]
-pub(crate) struct OutputInventory {
+pub struct OutputInventory {
     /// Inner storage
-    pub(crate) inventory: Inventory,
+    pub inventory: Inventory,
 }
 
 impl OutputInventory {
     /// Randomizes the contents of this inventory so that each slot is somewhere between empty and full.
-    pub(super) fn randomize(&mut self, rng: &mut ThreadRng) {
+    pub(super) fn randomize(&mut self, rng: &mut impl Rng) {
         for item_slot in self.iter_mut() {
             item_slot.randomize(rng);
         }
@@ -118,7 +138,7 @@ impl OutputInventory {
 
 /// The recipe that is currently being crafted, if any.
 #[derive(Component, Debug, Default, PartialEq, Eq, Clone)]
-pub(crate) struct ActiveRecipe(Option<Id<Recipe>>);
+pub struct ActiveRecipe(Option<Id<Recipe>>);
 
 impl Display for ActiveRecipe {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -133,19 +153,19 @@ impl Display for ActiveRecipe {
 
 impl ActiveRecipe {
     /// Creates a new [`ActiveRecipe`], set to `recipe_id`
-    pub(crate) fn new(recipe_id: Id<Recipe>) -> Self {
+    pub fn new(recipe_id: Id<Recipe>) -> Self {
         ActiveRecipe(Some(recipe_id))
     }
 
     /// The ID of the currently active recipe, if one has been selected.
-    pub(crate) fn recipe_id(&self) -> &Option<Id<Recipe>> {
+    pub fn recipe_id(&self) -> &Option<Id<Recipe>> {
         &self.0
     }
 }
 
 /// All components needed to craft stuff.
 #[derive(Debug, Bundle)]
-pub(crate) struct CraftingBundle {
+pub struct CraftingBundle {
     /// The input inventory for the items needed for crafting.
     input_inventory: InputInventory,
 
@@ -160,16 +180,47 @@ pub(crate) struct CraftingBundle {
 
     /// Emits signals, drawing units towards this structure to ensure crafting flows smoothly
     emitter: Emitter,
+
+    /// Scales the emitted signals by how full the inventories are
+    signal_modulator: SignalModulator,
 }
 
 impl CraftingBundle {
+    /// The recipe from `starting_recipe` that a structure of type `structure_id` can actually craft, if any.
+    ///
+    /// Recipes that must be made somewhere else are discarded, leaving the structure without a recipe.
+    fn craftable_recipe(
+        starting_recipe: ActiveRecipe,
+        structure_id: Id<Structure>,
+        recipe_manifest: &RecipeManifest,
+    ) -> Option<Id<Recipe>> {
+        let recipe_id = starting_recipe.0?;
+        let recipe = recipe_manifest.get(recipe_id);
+
+        if recipe.can_be_crafted_at(structure_id) {
+            Some(recipe_id)
+        } else {
+            warn!(
+                "{structure_id} cannot craft {recipe_id}, which must be made at {}",
+                recipe.required_structure()
+            );
+            None
+        }
+    }
+
     /// Create a new crafting bundle with empty inventories.
-    pub(crate) fn new(
+    ///
+    /// Structures without a recipe keep a single slot of storage, for whatever is left with them.
+    /// Corpses are refused, and are left in their piles of remains until they can be composted.
+    pub fn new(
         starting_recipe: ActiveRecipe,
+        structure_id: Id<Structure>,
         recipe_manifest: &RecipeManifest,
         item_manifest: &ItemManifest,
     ) -> Self {
-        if let Some(recipe_id) = starting_recipe.0 {
+        if let Some(recipe_id) =
+            CraftingBundle::craftable_recipe(starting_recipe, structure_id, recipe_manifest)
+        {
             let recipe = recipe_manifest.get(recipe_id);
 
             Self {
@@ -178,6 +229,7 @@ impl CraftingBundle {
                 active_recipe: ActiveRecipe(Some(recipe_id)),
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
+                signal_modulator: SignalModulator::default(),
             }
         } else {
             Self {
@@ -185,23 +237,28 @@ impl CraftingBundle {
                     inventory: Inventory::new(0),
                 },
                 output_inventory: OutputInventory {
-                    inventory: Inventory::new(1),
+                    inventory: Inventory::new(1)
+                        .with_filter(ItemFilter::Deny(HashSet::from_iter([Id::corpse()]))),
                 },
                 active_recipe: ActiveRecipe(None),
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
+                signal_modulator: SignalModulator::default(),
             }
         }
     }
 
     /// Generates a new crafting bundle that is at a random point in its cycle.
-    pub(crate) fn randomized(
+    pub fn randomized(
         starting_recipe: ActiveRecipe,
+        structure_id: Id<Structure>,
         recipe_manifest: &RecipeManifest,
         item_manifest: &ItemManifest,
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
     ) -> Self {
-        if let Some(recipe_id) = starting_recipe.0 {
+        if let Some(recipe_id) =
+            CraftingBundle::craftable_recipe(starting_recipe, structure_id, recipe_manifest)
+        {
             let recipe = recipe_manifest.get(recipe_id);
 
             let mut input_inventory = recipe.input_inventory(item_manifest);
@@ -223,9 +280,15 @@ impl CraftingBundle {
                     worker_present: false,
                 },
                 emitter: Emitter::default(),
+                signal_modulator: SignalModulator::default(),
             }
         } else {
-            CraftingBundle::new(starting_recipe, recipe_manifest, item_manifest)
+            CraftingBundle::new(
+                ActiveRecipe(None),
+                structure_id,
+                recipe_manifest,
+                item_manifest,
+            )
         }
     }
 }
@@ -233,7 +296,7 @@ impl CraftingBundle {
 /// Data needed for [`progress_crafting`].
 #[derive(WorldQuery)]
 #[world_query(mutable)]
-struct CraftingQuery {
+pub struct CraftingQuery {
     /// The recipe of the crafter
     active_recipe: &'static ActiveRecipe,
     /// The status of crafting
@@ -244,21 +307,50 @@ struct CraftingQuery {
     output: &'static mut OutputInventory,
     /// Is this an organism?
     maybe_organism: Option<&'static Organism>,
+    /// Where is this crafter?
+    tile_pos: &'static TilePos,
+    /// Is this organism unable to grow at its current location?
+    maybe_stunted: Option<&'static Stunted>,
+    /// Is this organism growing in irrigated soil?
+    maybe_irrigated: Option<&'static Irrigated>,
+    /// Has an automation rule paused this recipe?
+    maybe_paused: Option<&'static RecipePaused>,
+    /// Is this organism far from any unit, and so simulated at a reduced rate?
+    maybe_distant: Option<&'static Distant>,
 }
 
 /// Progress the state of recipes that are being crafted.
-fn progress_crafting(
+///
+/// Organisms far from any unit only progress when [`LodGroup::DistantOrganisms`] runs, catching up all at once.
+#[allow(clippy::too_many_arguments)]
+pub fn progress_crafting(
     time: Res<Time>,
+    season: Res<Season>,
+    lod_schedule: Res<LodSchedule>,
     recipe_manifest: Res<RecipeManifest>,
     item_manifest: Res<ItemManifest>,
     mut crafting_query: Query<CraftingQuery>,
+    fertility_query: Query<&Fertility>,
+    map_geometry: Res<MapGeometry>,
 ) {
     for mut crafter in crafting_query.iter_mut() {
+        let delta = match crafter.maybe_distant {
+            Some(_) if !lod_schedule.is_ready(LodGroup::DistantOrganisms) => continue,
+            Some(_) => lod_schedule.delta(LodGroup::DistantOrganisms),
+            None => time.delta(),
+        };
+
         *crafter.state = match *crafter.state {
             CraftingState::NoRecipe => match crafter.active_recipe.recipe_id() {
                 Some(_) => CraftingState::NeedsInput,
                 None => CraftingState::NoRecipe,
             },
+            // Paused recipes hold onto their inputs until they are resumed
+            CraftingState::NeedsInput | CraftingState::Overproduction
+                if crafter.maybe_paused.is_some() =>
+            {
+                crafter.state.clone()
+            }
             CraftingState::NeedsInput | CraftingState::Overproduction => {
                 if let Some(recipe_id) = crafter.active_recipe.recipe_id() {
                     let recipe = recipe_manifest.get(*recipe_id);
@@ -283,8 +375,30 @@ fn progress_crafting(
             } => {
                 let mut updated_progress = progress;
 
-                if !work_required || worker_present {
-                    updated_progress += time.delta();
+                // Organisms whose growth requirements are not met cannot make progress, nor can paused recipes
+                if (!work_required || worker_present)
+                    && crafter.maybe_stunted.is_none()
+                    && crafter.maybe_paused.is_none()
+                {
+                    let mut growth_multiplier = match crafter.maybe_irrigated {
+                        Some(_) => IRRIGATION_GROWTH_MULTIPLIER,
+                        None => 1.,
+                    };
+
+                    // Organisms grow faster in rich soil, and in the warmer seasons
+                    if crafter.maybe_organism.is_some() {
+                        growth_multiplier *= season.growth_multiplier();
+
+                        if let Some(fertility) = map_geometry
+                            .terrain_index
+                            .get(crafter.tile_pos)
+                            .and_then(|&terrain_entity| fertility_query.get(terrain_entity).ok())
+                        {
+                            growth_multiplier *= fertility_growth_multiplier(fertility.0);
+                        }
+                    }
+
+                    updated_progress += delta.mul_f32(growth_multiplier);
                 }
 
                 if updated_progress >= required {
@@ -355,7 +469,7 @@ fn gain_energy_when_crafting_completes(
 
 /// Causes crafting structures to emit signals based on the items they have and need.
 // TODO: change neglect based on inventory fullness and structure energy level
-pub(crate) fn set_emitter(
+pub fn set_emitter(
     mut crafting_query: Query<(
         &mut Emitter,
         &InputInventory,
@@ -381,11 +495,14 @@ pub(crate) fn set_emitter(
 
         // Output signals
         for item_slot in output_inventory.iter() {
-            if item_slot.is_full() {
+            // Push signals are scaled by how full the slot is by the structure's SignalModulator
+            if !item_slot.is_empty() {
                 let signal_type = SignalType::Push(item_slot.item_id());
                 let signal_strength = SignalStrength::new(10.);
                 emitter.signals.push((signal_type, signal_strength));
-            } else if !item_slot.is_empty() {
+            }
+
+            if !item_slot.is_empty() && !item_slot.is_full() {
                 let signal_type = SignalType::Contains(item_slot.item_id());
                 let signal_strength = SignalStrength::new(10.);
                 emitter.signals.push((signal_type, signal_strength));
@@ -410,9 +527,34 @@ pub(crate) fn set_emitter(
     }
 }
 
+/// Scales each crafting structure's signals by how full its inventories are.
+///
+/// Pull signals get stronger as the input slot empties, while push signals get stronger as the output slot fills up,
+/// so the most urgent deliveries and pickups are the easiest to find.
+fn modulate_emission(
+    mut crafting_query: Query<(&mut SignalModulator, &InputInventory, &OutputInventory)>,
+) {
+    for (mut signal_modulator, input_inventory, output_inventory) in crafting_query.iter_mut() {
+        let mut new_modulator = SignalModulator::default();
+
+        for item_slot in input_inventory.iter() {
+            new_modulator.set(
+                SignalType::Pull(item_slot.item_id()),
+                1. - item_slot.fullness(),
+            );
+        }
+
+        for item_slot in output_inventory.iter() {
+            new_modulator.set(SignalType::Push(item_slot.item_id()), item_slot.fullness());
+        }
+
+        signal_modulator.set_if_neq(new_modulator);
+    }
+}
+
 /// A query about the [`CraftingState`] of a structure that might need work done.
 #[derive(SystemParam)]
-pub(crate) struct WorkplaceQuery<'w, 's> {
+pub struct WorkplaceQuery<'w, 's> {
     /// The contained query type.
     query: Query<'w, 's, (&'static CraftingState, &'static Id<Structure>)>,
 }
@@ -421,7 +563,7 @@ impl<'w, 's> WorkplaceQuery<'w, 's> {
     /// Is there a structure of type `structure_id` at `structure_pos` that needs work done by a unit?
     ///
     /// If so, returns `Some(matching_structure_entity_that_needs_work)`.
-    pub(crate) fn needs_work(
+    pub fn needs_work(
         &self,
         structure_pos: TilePos,
         structure_id: Id<Structure>,
@@ -458,34 +600,109 @@ impl<'w, 's> WorkplaceQuery<'w, 's> {
     }
 }
 
+/// The items used when `items.ron` cannot be loaded.
+fn built_in_items() -> ItemManifest {
+    let mut item_manifest = HashMap::new();
+    item_manifest.insert(Id::acacia_leaf(), ItemData::acacia_leaf());
+    item_manifest.insert(Id::leuco_chunk(), ItemData::leuco_chunk());
+    item_manifest.insert(Id::ant_egg(), ItemData::ant_egg());
+    item_manifest.insert(Id::fertilizer(), ItemData::fertilizer());
+    item_manifest.insert(Id::water(), ItemData::water());
+    item_manifest.insert(Id::corpse(), ItemData::corpse());
+
+    ItemManifest::new(item_manifest)
+}
+
+/// The recipes used when `recipes.ron` cannot be loaded.
+fn built_in_recipes() -> RecipeManifest {
+    let mut recipe_manifest = HashMap::new();
+    recipe_manifest.insert(
+        Id::acacia_leaf_production(),
+        RecipeData::acacia_leaf_production(),
+    );
+    recipe_manifest.insert(
+        Id::leuco_chunk_production(),
+        RecipeData::leuco_chunk_production(),
+    );
+    recipe_manifest.insert(Id::ant_egg_production(), RecipeData::ant_egg_production());
+    recipe_manifest.insert(Id::hatch_ants(), RecipeData::hatch_ants());
+    recipe_manifest.insert(Id::composting(), RecipeData::composting());
+    recipe_manifest.insert(Id::corpse_composting(), RecipeData::corpse_composting());
+    recipe_manifest.insert(Id::water_collection(), RecipeData::water_collection());
+
+    RecipeManifest::new(recipe_manifest)
+}
+
 /// Add crafting capabilities to structures.
-pub(crate) struct CraftingPlugin;
+pub struct CraftingPlugin;
 
 impl Plugin for CraftingPlugin {
     fn build(&self, app: &mut App) {
-        // TODO: Load this from an asset file
-        let mut item_manifest = HashMap::new();
-        item_manifest.insert(Id::acacia_leaf(), ItemData::acacia_leaf());
-        item_manifest.insert(Id::leuco_chunk(), ItemData::leuco_chunk());
-        item_manifest.insert(Id::ant_egg(), ItemData::ant_egg());
-
-        // TODO: Load this from an asset file
-        let mut recipe_manifest = HashMap::new();
-        recipe_manifest.insert(
-            Id::acacia_leaf_production(),
-            RecipeData::acacia_leaf_production(),
-        );
-        recipe_manifest.insert(
-            Id::leuco_chunk_production(),
-            RecipeData::leuco_chunk_production(),
-        );
-        recipe_manifest.insert(Id::ant_egg_production(), RecipeData::ant_egg_production());
-        recipe_manifest.insert(Id::hatch_ants(), RecipeData::hatch_ants());
+        let directory = definitions_directory();
+        let item_manifest = loaded_or_built_in(load_items(&directory), built_in_items);
+        let recipe_manifest =
+            loaded_or_built_in(load_recipes(&directory, &item_manifest), built_in_recipes);
 
-        app.insert_resource(ItemManifest::new(item_manifest))
-            .insert_resource(RecipeManifest::new(recipe_manifest))
+        app.insert_resource(item_manifest)
+            .insert_resource(recipe_manifest)
             .add_system(progress_crafting)
             .add_system(gain_energy_when_crafting_completes.after(progress_crafting))
-            .add_system(set_emitter.after(progress_crafting));
+            .add_system(set_emitter.after(progress_crafting))
+            .add_system(modulate_emission.after(progress_crafting));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ItemCount;
+
+    #[test]
+    fn recipes_are_only_crafted_at_their_structure() {
+        let mut recipes = HashMap::new();
+        recipes.insert(Id::hatch_ants(), RecipeData::hatch_ants());
+        let recipe_manifest = RecipeManifest::new(recipes);
+        let starting_recipe = ActiveRecipe::new(Id::hatch_ants());
+
+        assert_eq!(
+            CraftingBundle::craftable_recipe(
+                starting_recipe.clone(),
+                Id::from_string_id("hatchery"),
+                &recipe_manifest
+            ),
+            Some(Id::hatch_ants())
+        );
+        assert_eq!(
+            CraftingBundle::craftable_recipe(
+                starting_recipe,
+                Id::from_string_id("ant_hive"),
+                &recipe_manifest
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn structures_without_a_recipe_store_anything_but_corpses() {
+        let mut items = HashMap::new();
+        items.insert(Id::acacia_leaf(), ItemData::acacia_leaf());
+        items.insert(Id::corpse(), ItemData::corpse());
+        let item_manifest = ItemManifest::new(items);
+        let recipe_manifest = RecipeManifest::new(HashMap::new());
+
+        let mut crafting_bundle = CraftingBundle::new(
+            ActiveRecipe(None),
+            Id::from_string_id("ant_hive"),
+            &recipe_manifest,
+            &item_manifest,
+        );
+        let storage = &mut crafting_bundle.output_inventory;
+
+        assert!(storage
+            .try_add_item(&ItemCount::one(Id::corpse()), &item_manifest)
+            .is_err());
+        assert!(storage
+            .try_add_item(&ItemCount::one(Id::acacia_leaf()), &item_manifest)
+            .is_ok());
     }
 }
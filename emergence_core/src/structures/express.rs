@@ -0,0 +1,142 @@
+//! Express routes, such as tunnels, link two distant tiles so that units can travel between them quickly.
+//!
+//! Each express route is made of a pair of linked structures, one at each end.
+//! Newly built ends are linked in the order they were built, so building two tunnel entrances makes a tunnel between them.
+//! When either end is destroyed, the other end waits to be linked again.
+//!
+//! The links are stored in the [`MapGeometry`], so that the pathfinder can plan routes through them when they are cheaper than walking.
+
+use crate::bevy::{prelude::*, utils::Duration};
+
+use crate::simulation::geometry::{MapGeometry, TilePos};
+
+/// Links the ends of express routes.
+pub(super) struct ExpressPlugin;
+
+impl Plugin for ExpressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(link_express_routes);
+    }
+}
+
+/// One end of an express route.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ExpressNode {
+    /// The number of seconds it takes to travel from this end of the route to the other
+    pub traversal_time: f32,
+    /// When this end was built, measured from the start of the game
+    pub built_at: Duration,
+    /// The other end of the route, once it has been linked
+    pub partner: Option<Entity>,
+}
+
+impl ExpressNode {
+    /// A new, unlinked end of an express route, built at `built_at`, that takes `traversal_time` seconds to travel along.
+    pub fn new(traversal_time: f32, built_at: Duration) -> Self {
+        ExpressNode {
+            traversal_time,
+            built_at,
+            partner: None,
+        }
+    }
+}
+
+/// A link from one tile to a distant tile, stored in the [`MapGeometry`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpressLink {
+    /// The tile at the other end of the link
+    pub exit: TilePos,
+    /// The number of seconds it takes to travel along the link
+    pub traversal_time: f32,
+}
+
+/// Pairs up the `unlinked` ends of express routes, in the order they were built.
+///
+/// Each end is given with the time at which it was built.
+/// If there is an odd number of ends, the newest is left waiting for a partner.
+fn pair_up(mut unlinked: Vec<(Duration, Entity)>) -> Vec<(Entity, Entity)> {
+    unlinked.sort();
+
+    unlinked
+        .chunks_exact(2)
+        .map(|pair| (pair[0].1, pair[1].1))
+        .collect()
+}
+
+/// Links unlinked ends of express routes, and updates the links stored in the [`MapGeometry`].
+fn link_express_routes(
+    mut node_query: Query<(Entity, &TilePos, &mut ExpressNode)>,
+    mut removed_nodes: RemovedComponents<ExpressNode>,
+    mut map_geometry: ResMut<MapGeometry>,
+) {
+    let mut changed = removed_nodes.iter().count() > 0;
+
+    // Forget partners that have been destroyed
+    let existing: Vec<Entity> = node_query.iter().map(|(entity, ..)| entity).collect();
+    for (_, _, mut node) in node_query.iter_mut() {
+        if let Some(partner) = node.partner {
+            if !existing.contains(&partner) {
+                node.partner = None;
+                changed = true;
+            }
+        }
+    }
+
+    let unlinked: Vec<(Duration, Entity)> = node_query
+        .iter()
+        .filter(|(_, _, node)| node.partner.is_none())
+        .map(|(entity, _, node)| (node.built_at, entity))
+        .collect();
+
+    for (a, b) in pair_up(unlinked) {
+        if let Ok((.., mut node)) = node_query.get_mut(a) {
+            node.partner = Some(b);
+        }
+        if let Ok((.., mut node)) = node_query.get_mut(b) {
+            node.partner = Some(a);
+        }
+        changed = true;
+    }
+
+    // Rebuilding the index marks the map as changed, which throws away every cached path
+    if !changed {
+        return;
+    }
+
+    map_geometry.express_index.clear();
+    for (_, &tile_pos, node) in node_query.iter() {
+        if let Some(partner) = node.partner {
+            if let Ok((_, &exit, _)) = node_query.get(partner) {
+                map_geometry.express_index.insert(
+                    tile_pos,
+                    ExpressLink {
+                        exit,
+                        traversal_time: node.traversal_time,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ends_are_linked_in_the_order_they_were_built() {
+        // Entity ids are recycled, so they say nothing about when each end was built
+        let ends: Vec<(Duration, Entity)> = [(3, 0), (0, 4), (2, 1), (1, 3), (4, 2)]
+            .into_iter()
+            .map(|(built_at, id)| (Duration::from_secs(built_at), Entity::from_raw(id)))
+            .collect();
+
+        assert_eq!(
+            pair_up(ends),
+            vec![
+                (Entity::from_raw(4), Entity::from_raw(3)),
+                (Entity::from_raw(1), Entity::from_raw(0)),
+            ]
+        );
+    }
+}
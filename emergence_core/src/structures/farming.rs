@@ -0,0 +1,465 @@
+//! Crops are plants grown on tiles that the player has zoned for them.
+//!
+//! Workers sow, tend and harvest crops on a seasonal [`CropSchedule`]:
+//! - ghosts of crops wait for a sowing season before asking for their seeds,
+//! - every few days, the next batch of produce cannot grow until a worker has tended the crop,
+//! - produce is only collected during a harvest season, and stays on the plant until then.
+//!
+//! Crops also ask for fertilizer whenever their soil is poorer than normal.
+//! Fertilizer delivered to a crop is dug into the soil around it, replenishing its [`Fertility`].
+//!
+//! Plants growing wild, on tiles that were not zoned for them, are left to themselves.
+
+use crate::bevy::prelude::*;
+
+use crate::{
+    items::ItemCount,
+    manifest::{Id, Item, ItemManifest, Structure, StructureManifest},
+    organisms::{growth::NORMAL_FERTILITY, Organism},
+    signals::{Emitter, SignalType},
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        time::{InGameTime, Season},
+    },
+    terrain::{Decompose, Fertility, Zoning},
+};
+
+use super::crafting::{set_emitter, CraftingState, InputInventory};
+
+/// The fertility returned to the soil by each item of fertilizer dug in around a crop.
+const FERTILIZER_NUTRIENTS: f32 = 0.1;
+
+/// Sows, tends and harvests the crops of the colony.
+pub(super) struct FarmingPlugin;
+
+impl Plugin for FarmingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(mark_crops)
+            .add_system(tend_crops.after(mark_crops))
+            .add_system(make_room_for_fertilizer.after(mark_crops))
+            .add_system(dig_in_fertilizer.after(make_room_for_fertilizer))
+            // Must run after crafting emitters in order to wipe out their signals
+            .add_system(hold_harvest.after(set_emitter))
+            .add_system(ask_for_fertilizer.after(set_emitter));
+    }
+}
+
+/// When a crop is sown, tended and harvested, as part of its [`OrganismVariety`](crate::organisms::OrganismVariety).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropSchedule {
+    /// The seasons in which ghosts of this crop are sown
+    pub sowing_seasons: Vec<Season>,
+    /// The seasons in which the produce of this crop is collected
+    pub harvest_seasons: Vec<Season>,
+    /// The number of in-game days that this crop grows for between each time it is tended
+    pub days_between_tending: f32,
+}
+
+impl CropSchedule {
+    /// Can this crop be sown in the provided `season`?
+    pub fn can_be_sown(&self, season: Season) -> bool {
+        self.sowing_seasons.contains(&season)
+    }
+
+    /// Can the produce of this crop be collected in the provided `season`?
+    pub fn can_be_harvested(&self, season: Season) -> bool {
+        self.harvest_seasons.contains(&season)
+    }
+}
+
+/// A plant growing on a tile that was zoned for it, and so looked after by the colony.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Crop {
+    /// The value of [`InGameTime::elapsed_days`] when this crop was last tended
+    last_tended: f32,
+}
+
+/// Marks the plants growing on tiles zoned for them as [`Crop`]s, and unmarks them if that zoning is changed.
+fn mark_crops(
+    plant_query: Query<(Entity, &TilePos, &Id<Structure>, Option<&Crop>), With<Organism>>,
+    terrain_query: Query<&Zoning>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+    in_game_time: Res<InGameTime>,
+    mut commands: Commands,
+) {
+    for (entity, tile_pos, &structure_id, maybe_crop) in plant_query.iter() {
+        if structure_manifest
+            .get(structure_id)
+            .crop_schedule()
+            .is_none()
+        {
+            continue;
+        }
+
+        let zoned_for_this_plant = map_geometry
+            .terrain_index
+            .get(tile_pos)
+            .and_then(|&terrain_entity| terrain_query.get(terrain_entity).ok())
+            .map_or(false, |zoning| match zoning {
+                Zoning::Structure(clipboard_data) => clipboard_data.structure_id == structure_id,
+                Zoning::None | Zoning::KeepClear => false,
+            });
+
+        match (zoned_for_this_plant, maybe_crop) {
+            // Freshly sown crops do not need tending straight away
+            (true, None) => {
+                commands.entity(entity).insert(Crop {
+                    last_tended: in_game_time.elapsed_days(),
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<Crop>();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Crops that are due to be tended need a worker before their current batch of produce can finish growing.
+fn tend_crops(
+    mut crop_query: Query<(&mut Crop, &mut CraftingState, &Id<Structure>)>,
+    structure_manifest: Res<StructureManifest>,
+    in_game_time: Res<InGameTime>,
+) {
+    let today = in_game_time.elapsed_days();
+
+    for (mut crop, mut crafting_state, &structure_id) in crop_query.iter_mut() {
+        let Some(crop_schedule) = structure_manifest.get(structure_id).crop_schedule() else {
+            continue;
+        };
+
+        if let CraftingState::InProgress {
+            progress,
+            required,
+            work_required,
+            worker_present,
+        } = *crafting_state
+        {
+            if worker_present {
+                crop.last_tended = today;
+            } else if !work_required
+                && today - crop.last_tended >= crop_schedule.days_between_tending
+            {
+                *crafting_state = CraftingState::InProgress {
+                    progress,
+                    required,
+                    work_required: true,
+                    worker_present,
+                };
+            }
+        }
+    }
+}
+
+/// Gives each crop a slot for fertilizer, so that it can be delivered alongside the inputs of its recipe.
+///
+/// The slot is added again whenever the crop's inventory is replaced, such as when its recipe changes.
+fn make_room_for_fertilizer(
+    mut crop_query: Query<&mut InputInventory, With<Crop>>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let fertilizer = Id::<Item>::fertilizer();
+
+    for mut input_inventory in crop_query.iter_mut() {
+        if !input_inventory
+            .iter()
+            .any(|slot| slot.is_for_item(fertilizer))
+        {
+            input_inventory.allow_item(fertilizer);
+            // Fertilizer was just allowed, so this cannot fail
+            input_inventory
+                .add_empty_slot(fertilizer, &item_manifest)
+                .unwrap();
+        }
+    }
+}
+
+/// Digs the fertilizer delivered to each crop into the soil around it.
+fn dig_in_fertilizer(
+    mut crop_query: Query<(&TilePos, &mut InputInventory), With<Crop>>,
+    mut decompose_events: EventWriter<Decompose>,
+) {
+    let fertilizer = Id::<Item>::fertilizer();
+
+    for (&tile_pos, mut input_inventory) in crop_query.iter_mut() {
+        let n_delivered = input_inventory.item_count(fertilizer);
+        if n_delivered == 0 {
+            continue;
+        }
+
+        if input_inventory
+            .try_remove_item(&ItemCount::new(fertilizer, n_delivered))
+            .is_ok()
+        {
+            decompose_events.send(Decompose {
+                tile_pos,
+                nutrients: n_delivered as f32 * FERTILIZER_NUTRIENTS,
+            });
+        }
+    }
+}
+
+/// Crops only ask for fertilizer while their soil is poorer than normal.
+fn ask_for_fertilizer(
+    mut crop_query: Query<(&mut Emitter, &TilePos), With<Crop>>,
+    fertility_query: Query<&Fertility>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (mut emitter, tile_pos) in crop_query.iter_mut() {
+        let needs_fertilizer = map_geometry
+            .terrain_index
+            .get(tile_pos)
+            .and_then(|&terrain_entity| fertility_query.get(terrain_entity).ok())
+            .map_or(false, |fertility| fertility.0 < NORMAL_FERTILITY);
+
+        if !needs_fertilizer {
+            emitter
+                .signals
+                .retain(|(signal_type, _)| *signal_type != SignalType::Pull(Id::fertilizer()));
+        }
+    }
+}
+
+/// Keeps the produce of crops on the plant until one of their harvest seasons.
+fn hold_harvest(
+    mut crop_query: Query<(&mut Emitter, &Id<Structure>), With<Crop>>,
+    structure_manifest: Res<StructureManifest>,
+    season: Res<Season>,
+) {
+    for (mut emitter, &structure_id) in crop_query.iter_mut() {
+        let Some(crop_schedule) = structure_manifest.get(structure_id).crop_schedule() else {
+            continue;
+        };
+
+        if !crop_schedule.can_be_harvested(*season) {
+            emitter.signals.retain(|(signal_type, _)| {
+                !matches!(signal_type, SignalType::Push(_) | SignalType::Contains(_))
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy::utils::{Duration, HashMap};
+    use crate::items::ItemData;
+    use crate::signals::SignalStrength;
+    use crate::simulation::geometry::Facing;
+    use crate::structures::ClipboardData;
+
+    /// An app with only the farming systems.
+    fn farming_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<StructureManifest>()
+            .insert_resource(ItemManifest::new(HashMap::from_iter([(
+                Id::fertilizer(),
+                ItemData::fertilizer(),
+            )])))
+            .add_event::<Decompose>()
+            .init_resource::<InGameTime>()
+            .init_resource::<Season>()
+            .insert_resource(MapGeometry::new(3))
+            .add_plugin(FarmingPlugin);
+
+        app
+    }
+
+    /// Spawns a tile of poor soil at `tile_pos` with the provided `zoning`, and an acacia growing on it.
+    fn spawn_acacia(app: &mut App, tile_pos: TilePos, zoning: Zoning) -> Entity {
+        let terrain_entity = app.world.spawn((tile_pos, zoning, Fertility(0.2))).id();
+        app.world
+            .resource_mut::<MapGeometry>()
+            .terrain_index
+            .insert(tile_pos, terrain_entity);
+
+        app.world
+            .spawn((
+                Organism,
+                tile_pos,
+                Id::<Structure>::from_string_id("acacia"),
+                InputInventory::default(),
+                CraftingState::InProgress {
+                    progress: Duration::ZERO,
+                    required: Duration::from_secs(3),
+                    work_required: false,
+                    worker_present: false,
+                },
+                Emitter {
+                    signals: vec![
+                        (
+                            SignalType::Push(Id::acacia_leaf()),
+                            SignalStrength::new(10.),
+                        ),
+                        (SignalType::Pull(Id::fertilizer()), SignalStrength::new(10.)),
+                    ],
+                },
+            ))
+            .id()
+    }
+
+    /// Zoning that asks for an acacia to be grown.
+    fn acacia_zoning() -> Zoning {
+        let structure_id = Id::from_string_id("acacia");
+
+        Zoning::Structure(ClipboardData {
+            structure_id,
+            facing: Facing::default(),
+            active_recipe: StructureManifest::default()
+                .get(structure_id)
+                .starting_recipe()
+                .clone(),
+        })
+    }
+
+    /// Is a worker needed before the crafting of `entity` can progress?
+    fn work_required(app: &App, entity: Entity) -> bool {
+        match app.world.get::<CraftingState>(entity).unwrap() {
+            CraftingState::InProgress { work_required, .. } => *work_required,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn only_plants_on_tiles_zoned_for_them_are_crops() {
+        let mut app = farming_app();
+        let crop = spawn_acacia(&mut app, TilePos::ORIGIN, acacia_zoning());
+        let wild = spawn_acacia(&mut app, TilePos::new(1, 0), Zoning::None);
+        app.update();
+
+        assert!(app.world.get::<Crop>(crop).is_some());
+        assert!(app.world.get::<Crop>(wild).is_none());
+    }
+
+    #[test]
+    fn crops_need_tending_once_they_are_due() {
+        let mut app = farming_app();
+        let crop = spawn_acacia(&mut app, TilePos::ORIGIN, acacia_zoning());
+        app.update();
+        assert!(!work_required(&app, crop));
+
+        let days_between_tending = app
+            .world
+            .resource::<StructureManifest>()
+            .get(Id::from_string_id("acacia"))
+            .crop_schedule()
+            .unwrap()
+            .days_between_tending;
+        app.world
+            .resource_mut::<InGameTime>()
+            .set_elapsed_days(days_between_tending);
+        app.update();
+        assert!(work_required(&app, crop));
+
+        // A worker arrives to tend the crop
+        *app.world.get_mut::<CraftingState>(crop).unwrap() = CraftingState::InProgress {
+            progress: Duration::ZERO,
+            required: Duration::from_secs(3),
+            work_required: true,
+            worker_present: true,
+        };
+        app.update();
+        assert_eq!(
+            app.world.get::<Crop>(crop).unwrap().last_tended,
+            days_between_tending
+        );
+    }
+
+    #[test]
+    fn produce_is_only_collected_in_harvest_season() {
+        let mut app = farming_app();
+        let crop = spawn_acacia(&mut app, TilePos::ORIGIN, acacia_zoning());
+        let harvest_season = app
+            .world
+            .resource::<StructureManifest>()
+            .get(Id::from_string_id("acacia"))
+            .crop_schedule()
+            .unwrap()
+            .harvest_seasons[0];
+        let off_season = Season::Winter;
+        assert_ne!(harvest_season, off_season);
+
+        *app.world.resource_mut::<Season>() = off_season;
+        app.update();
+        // The crop was only marked this frame, so check again now that it is one
+        app.update();
+        let signals = &app.world.get::<Emitter>(crop).unwrap().signals;
+        assert!(signals
+            .iter()
+            .all(|(signal_type, _)| !matches!(signal_type, SignalType::Push(_))));
+        // The crop can still ask for what it needs
+        assert!(signals
+            .iter()
+            .any(|(signal_type, _)| matches!(signal_type, SignalType::Pull(_))));
+
+        let mut crop_emitter = app.world.get_mut::<Emitter>(crop).unwrap();
+        crop_emitter.signals = vec![(
+            SignalType::Push(Id::acacia_leaf()),
+            SignalStrength::new(10.),
+        )];
+        *app.world.resource_mut::<Season>() = harvest_season;
+        app.update();
+        assert_eq!(app.world.get::<Emitter>(crop).unwrap().signals.len(), 1);
+    }
+
+    #[test]
+    fn fertilizer_delivered_to_crops_is_dug_into_the_soil() {
+        let mut app = farming_app();
+        let crop = spawn_acacia(&mut app, TilePos::ORIGIN, acacia_zoning());
+        app.update();
+        app.update();
+
+        // Workers can now deliver fertilizer to the crop
+        let item_manifest = ItemManifest::new(HashMap::from_iter([(
+            Id::fertilizer(),
+            ItemData::fertilizer(),
+        )]));
+        let mut input_inventory = app.world.get_mut::<InputInventory>(crop).unwrap();
+        assert!(input_inventory.remaining_reserved_space_for_item(Id::fertilizer()) > 0);
+        input_inventory
+            .try_add_item(&ItemCount::new(Id::fertilizer(), 2), &item_manifest)
+            .unwrap();
+        app.update();
+
+        let input_inventory = app.world.get::<InputInventory>(crop).unwrap();
+        assert_eq!(input_inventory.item_count(Id::fertilizer()), 0);
+
+        let decompose_events = app.world.resource::<Events<Decompose>>();
+        let dug_in: Vec<Decompose> = decompose_events
+            .get_reader()
+            .iter(decompose_events)
+            .copied()
+            .collect();
+        assert_eq!(
+            dug_in,
+            vec![Decompose {
+                tile_pos: TilePos::ORIGIN,
+                nutrients: 2. * FERTILIZER_NUTRIENTS,
+            }]
+        );
+    }
+
+    #[test]
+    fn crops_only_ask_for_fertilizer_on_poor_soil() {
+        let mut app = farming_app();
+        let crop = spawn_acacia(&mut app, TilePos::ORIGIN, acacia_zoning());
+        app.update();
+        app.update();
+
+        let asks_for_fertilizer = |app: &App| {
+            app.world
+                .get::<Emitter>(crop)
+                .unwrap()
+                .signals
+                .iter()
+                .any(|(signal_type, _)| *signal_type == SignalType::Pull(Id::fertilizer()))
+        };
+        assert!(asks_for_fertilizer(&app));
+
+        let terrain_entity = app.world.resource::<MapGeometry>().terrain_index[&TilePos::ORIGIN];
+        app.world.get_mut::<Fertility>(terrain_entity).unwrap().0 = NORMAL_FERTILITY;
+        app.update();
+        assert!(!asks_for_fertilizer(&app));
+    }
+}
@@ -0,0 +1,171 @@
+//! Storing water in cisterns, and carrying it through channels to irrigate nearby soil.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use hexx::shapes::hexagon;
+
+use crate::{
+    items::ItemCount,
+    manifest::Id,
+    organisms::Organism,
+    simulation::{
+        freezing::Frozen,
+        geometry::{MapGeometry, TilePos},
+    },
+    terrain::SoilMoisture,
+};
+
+use super::crafting::OutputInventory;
+
+/// The role a structure plays in the irrigation network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaterworksKind {
+    /// Stores water, supplying any connected channels.
+    Cistern,
+    /// Carries water from a connected cistern, wetting the surrounding soil.
+    Channel,
+}
+
+/// A structure that stores water in its [`OutputInventory`], supplying any connected [`IrrigationChannel`]s.
+#[derive(Component, Debug, Default)]
+pub struct Cistern {
+    /// Water that has been used by channels, but not yet removed from storage.
+    pending_consumption: f32,
+}
+
+/// A structure that carries water from a connected [`Cistern`], wetting the soil around it.
+///
+/// Channels are connected to a cistern if they are adjacent to it, or to another connected channel.
+#[derive(Component, Debug, Default)]
+pub struct IrrigationChannel {
+    /// Is this channel currently connected to a cistern with water in it?
+    pub supplied: bool,
+}
+
+/// Marks organisms that are growing in irrigated soil, causing them to grow faster.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Irrigated;
+
+/// The number of water items used by each supplied channel per second
+const WATER_PER_CHANNEL_PER_SECOND: f32 = 0.1;
+
+/// How quickly supplied channels raise the moisture of the tiles around them, per second
+const IRRIGATION_RATE: f32 = 0.2;
+
+/// How many tiles away from a supplied channel are irrigated
+const IRRIGATION_RADIUS: u32 = 1;
+
+/// The multiplier applied to the crafting speed of [`Irrigated`] organisms
+pub const IRRIGATION_GROWTH_MULTIPLIER: f32 = 1.5;
+
+/// Flows water from each cistern out through its network of connected channels.
+fn supply_irrigation_channels(
+    time: Res<Time>,
+    mut cistern_query: Query<(&TilePos, &mut Cistern, &mut OutputInventory), Without<Frozen>>,
+    mut channel_query: Query<&mut IrrigationChannel>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for mut channel in channel_query.iter_mut() {
+        channel.supplied = false;
+    }
+
+    for (&cistern_pos, mut cistern, mut storage) in cistern_query.iter_mut() {
+        // Flood fill outwards to find all connected channels
+        let mut network: Vec<Entity> = Vec::new();
+        let mut visited: HashSet<TilePos> = HashSet::new();
+        let mut frontier: Vec<TilePos> = vec![cistern_pos];
+        visited.insert(cistern_pos);
+
+        while let Some(tile_pos) = frontier.pop() {
+            for neighbor in tile_pos.all_neighbors(&map_geometry) {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+
+                if let Some(&structure_entity) = map_geometry.structure_index.get(&neighbor) {
+                    if channel_query.contains(structure_entity) {
+                        network.push(structure_entity);
+                        frontier.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        if network.is_empty() {
+            continue;
+        }
+
+        cistern.pending_consumption +=
+            network.len() as f32 * WATER_PER_CHANNEL_PER_SECOND * time.delta_seconds();
+
+        let mut supplied = true;
+        while cistern.pending_consumption >= 1. {
+            match storage.remove_item_all_or_nothing(&ItemCount::one(Id::water())) {
+                Ok(()) => cistern.pending_consumption -= 1.,
+                Err(_) => {
+                    // Don't let debt build up while the cistern is dry
+                    cistern.pending_consumption = 0.;
+                    supplied = false;
+                    break;
+                }
+            }
+        }
+
+        if storage.item_count(Id::water()) == 0 {
+            supplied = false;
+        }
+
+        for channel_entity in network {
+            let mut channel = channel_query.get_mut(channel_entity).unwrap();
+            channel.supplied |= supplied;
+        }
+    }
+}
+
+/// Supplied channels wet the soil around them, and speed the growth of nearby organisms.
+fn irrigate_soil(
+    time: Res<Time>,
+    channel_query: Query<(&TilePos, &IrrigationChannel)>,
+    mut terrain_query: Query<&mut SoilMoisture>,
+    organism_query: Query<(Entity, &TilePos, Option<&Irrigated>), With<Organism>>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    let mut irrigated_tiles: HashSet<TilePos> = HashSet::new();
+
+    for (&channel_pos, channel) in channel_query.iter() {
+        if channel.supplied {
+            for hex in hexagon(channel_pos.hex, IRRIGATION_RADIUS) {
+                irrigated_tiles.insert(TilePos { hex });
+            }
+        }
+    }
+
+    for tile_pos in irrigated_tiles.iter() {
+        if let Some(&terrain_entity) = map_geometry.terrain_index.get(tile_pos) {
+            let mut soil_moisture = terrain_query.get_mut(terrain_entity).unwrap();
+            soil_moisture.0 = (soil_moisture.0 + IRRIGATION_RATE * time.delta_seconds()).min(1.);
+        }
+    }
+
+    for (entity, tile_pos, maybe_irrigated) in organism_query.iter() {
+        match (irrigated_tiles.contains(tile_pos), maybe_irrigated) {
+            (true, None) => {
+                commands.entity(entity).insert(Irrigated);
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<Irrigated>();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Logic for cisterns and irrigation channels.
+pub(super) struct IrrigationPlugin;
+
+impl Plugin for IrrigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(supply_irrigation_channels)
+            .add_system(irrigate_soil.after(supply_irrigation_channels));
+    }
+}
@@ -0,0 +1,927 @@
+//! Structures (or buildings) are plants and fungi that serve a role in the bustling organic factory.
+//!
+//! Typically, these will produce and transform resources (much like machines in other factory builders),
+//! but they can also be used for defense, research, reproduction, storage and more exotic effects.
+
+use crate::bevy::{
+    prelude::*,
+    utils::{Duration, HashMap, HashSet},
+};
+
+use crate::{
+    items::{inventory::Inventory, ItemCount},
+    manifest::{
+        definitions::{definitions_directory, load_structures, loaded_or_built_in},
+        Id, ItemManifest, Structure, StructureManifest,
+    },
+    organisms::{
+        energy::{Energy, EnergyPool},
+        growth::GrowthRequirements,
+        OrganismVariety,
+    },
+    signals::SignalType,
+    simulation::{
+        freezing::ColdTolerance,
+        geometry::{Crossing, Facing, TilePos},
+        temperature::HeatSource,
+        time::Season,
+        vision::VisionSource,
+    },
+    terrain::Terrain,
+    units::soldiers::GuardPost,
+};
+
+use self::{
+    automation::{AutomationPlugin, AutomationRules, Condition, Effect},
+    build_order::BuildOrderPlugin,
+    construction::{
+        ghost_lifecyle, ghost_signals, refund_demolished_structures, update_construction_stage,
+        ConstructionModels, DemolitionRefund, StructureDemolished,
+    },
+    crafting::{ActiveRecipe, CraftingPlugin, InputInventory},
+    express::ExpressPlugin,
+    farming::{CropSchedule, FarmingPlugin},
+    irrigation::{IrrigationPlugin, WaterworksKind},
+    traps::{Trap, TrapsPlugin},
+    walls::{Wall, WallsPlugin},
+};
+
+pub mod automation;
+pub mod build_order;
+pub mod commands;
+pub mod construction;
+pub mod crafting;
+pub mod express;
+pub mod farming;
+pub mod irrigation;
+pub mod traps;
+pub mod walls;
+
+/// Information about a single [`Id<Structure>`] variety of structure.
+#[derive(Debug, Clone)]
+pub struct StructureData {
+    /// Data needed for living structures
+    organism: Option<OrganismVariety>,
+    /// Can this structure make things?
+    crafts: bool,
+    /// Does this structure start with a recipe pre-selected?
+    starting_recipe: ActiveRecipe,
+    /// The amount of work by units required to complete the construction of this building.
+    ///
+    /// If this is [`Duration::ZERO`], no work will be needed at all.
+    build_duration: Duration,
+    /// The set of items needed to create a new copy of this structure
+    construction_materials: InputInventory,
+    /// Does this structure warm up the tiles around it?
+    heat_source: Option<HeatSource>,
+    /// Is this structure part of an irrigation network?
+    waterworks: Option<WaterworksKind>,
+    /// Can units walk across this structure?
+    crossing: Option<Crossing>,
+    /// Does this structure act as a barrier to signals?
+    wall: Option<Wall>,
+    /// Does this structure reveal the tiles around it?
+    vision: Option<VisionSource>,
+    /// Does this structure catch units that wander into it?
+    trap: Option<Trap>,
+    /// Can soldiers be assigned to patrol around this structure?
+    guard_post: Option<GuardPost>,
+    /// If this structure is one end of an express route, the number of seconds it takes to travel to the other end
+    express_traversal_time: Option<f32>,
+    /// The automation rules that new copies of this structure start with
+    automation: AutomationRules,
+    /// Can this structure be destroyed by bad weather?
+    fragile: bool,
+    /// Do items stored in this structure keep without spoiling?
+    preserves_items: bool,
+    /// The models shown while this structure is being built
+    construction_models: ConstructionModels,
+    /// The set of terrain types that this structure can be built on
+    pub allowed_terrain_types: HashSet<Terrain>,
+}
+
+impl StructureData {
+    /// A structure with no special behavior, which can be built on the `allowed_terrain_types`.
+    ///
+    /// It can be built instantly, at no cost.
+    pub fn plain(allowed_terrain_types: HashSet<Terrain>) -> Self {
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            build_duration: Duration::ZERO,
+            construction_materials: InputInventory::default(),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types,
+        }
+    }
+
+    /// Sets the items needed to build this structure.
+    pub fn with_construction_materials(mut self, construction_materials: InputInventory) -> Self {
+        self.construction_materials = construction_materials;
+        self
+    }
+
+    /// Sets the amount of work by units needed to build this structure.
+    pub fn with_build_duration(mut self, build_duration: Duration) -> Self {
+        self.build_duration = build_duration;
+        self
+    }
+
+    /// Lets units walk across this structure, at the speed given by `crossing`.
+    pub fn with_crossing(mut self, crossing: Crossing) -> Self {
+        self.crossing = Some(crossing);
+        self
+    }
+
+    /// Sets the conditions that this structure needs to grow.
+    ///
+    /// Structures that are not living organisms have no growth requirements, and are left unchanged.
+    pub fn with_growth_requirements(mut self, growth_requirements: GrowthRequirements) -> Self {
+        if let Some(organism) = &mut self.organism {
+            organism.growth_requirements = growth_requirements;
+        }
+        self
+    }
+
+    /// Returns the starting recipe of the structure
+    pub fn starting_recipe(&self) -> &ActiveRecipe {
+        &self.starting_recipe
+    }
+
+    /// Returns the models shown while this structure is being built
+    pub fn construction_models(&self) -> &ConstructionModels {
+        &self.construction_models
+    }
+
+    /// Returns the light and moisture requirements of this structure, if it is a living organism
+    pub fn growth_requirements(&self) -> Option<&GrowthRequirements> {
+        self.organism
+            .as_ref()
+            .map(|organism| &organism.growth_requirements)
+    }
+
+    /// Returns when this structure is sown, tended and harvested, if it is a crop
+    pub fn crop_schedule(&self) -> Option<&CropSchedule> {
+        self.organism
+            .as_ref()
+            .and_then(|organism| organism.crop_schedule.as_ref())
+    }
+
+    /// Returns the set of items needed to build this structure
+    pub fn construction_materials(&self) -> &InputInventory {
+        &self.construction_materials
+    }
+
+    /// Returns the amount of work by units needed to build this structure
+    pub fn build_duration(&self) -> Duration {
+        self.build_duration
+    }
+
+    /// Returns how units walk across this structure, if they can
+    pub fn crossing(&self) -> Option<Crossing> {
+        self.crossing
+    }
+
+    /// Can units walk across this structure once it is built?
+    pub fn is_crossing(&self) -> bool {
+        self.crossing.is_some()
+    }
+
+    /// Descriptive tags for this structure, used when searching for it by function.
+    pub fn tags(&self) -> Vec<&'static str> {
+        let mut tags = Vec::new();
+
+        if self.organism.is_some() {
+            tags.push("organism");
+        }
+        if self.crafts {
+            tags.push("crafting");
+        }
+        if self.heat_source.is_some() {
+            tags.push("heat");
+        }
+        if self.waterworks.is_some() {
+            tags.push("water");
+        }
+        if self.crossing.is_some() {
+            tags.push("crossing");
+        }
+        if self.wall.is_some() {
+            tags.push("wall");
+        }
+        if self.vision.is_some() {
+            tags.push("vision");
+        }
+        if self.trap.is_some() {
+            tags.push("trap");
+        }
+        if self.guard_post.is_some() {
+            tags.push("guard");
+        }
+        if self.express_traversal_time.is_some() {
+            tags.push("express");
+        }
+        if !self.automation.is_empty() {
+            tags.push("automation");
+        }
+        if self.fragile {
+            tags.push("fragile");
+        }
+        if self.preserves_items {
+            tags.push("preserving");
+        }
+
+        tags
+    }
+
+    /// Can this structure be placed on a tile with the provided `terrain`, `light`, `moisture` and `fertility`?
+    pub fn can_be_placed(
+        &self,
+        terrain: &Terrain,
+        light: f32,
+        moisture: f32,
+        fertility: f32,
+    ) -> bool {
+        if !self.allowed_terrain_types.contains(terrain) {
+            return false;
+        }
+
+        match self.growth_requirements() {
+            Some(growth_requirements) => growth_requirements
+                .check(light, moisture, fertility)
+                .is_ok(),
+            None => true,
+        }
+    }
+}
+
+impl Default for StructureManifest {
+    fn default() -> Self {
+        StructureManifest::new(built_in_structures())
+    }
+}
+
+/// The structures built into the game, which `structures.ron` adds to.
+///
+/// Definition files can override the footprint, construction cost, passability and growth requirements of these structures,
+/// but their behavior is only defined here.
+pub fn built_in_structures() -> HashMap<Id<Structure>, StructureData> {
+    let mut map = HashMap::default();
+
+    let leuco_construction_materials = InputInventory {
+        inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 1)),
+    };
+
+    map.insert(
+        Id::from_string_id("leuco"),
+        StructureData {
+            organism: Some(OrganismVariety {
+                energy_pool: EnergyPool::new_full(Energy(100.), Energy(-1.)),
+                // Fungi don't need light, but do need damp soil
+                growth_requirements: GrowthRequirements {
+                    min_light: 0.,
+                    min_moisture: 0.3,
+                    max_moisture: 1.,
+                    min_fertility: 0.2,
+                },
+                // Fungi shelter underground, and are hardy to the cold
+                cold_tolerance: ColdTolerance(-15.),
+                // Fungi fruit whenever the soil is damp, so can be sown and picked all year round
+                crop_schedule: Some(CropSchedule {
+                    sowing_seasons: vec![
+                        Season::Spring,
+                        Season::Summer,
+                        Season::Autumn,
+                        Season::Winter,
+                    ],
+                    harvest_seasons: vec![
+                        Season::Spring,
+                        Season::Summer,
+                        Season::Autumn,
+                        Season::Winter,
+                    ],
+                    days_between_tending: 3.,
+                }),
+            }),
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::leuco_chunk_production()),
+            build_duration: Duration::from_secs(5),
+            construction_materials: leuco_construction_materials,
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Plain, Terrain::Muddy]),
+        },
+    );
+
+    let acacia_construction_materials = InputInventory {
+        inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 2)),
+    };
+
+    map.insert(
+        Id::from_string_id("acacia"),
+        StructureData {
+            organism: Some(OrganismVariety {
+                energy_pool: EnergyPool::new_full(Energy(100.), Energy(-1.)),
+                growth_requirements: GrowthRequirements {
+                    min_light: 0.6,
+                    min_moisture: 0.2,
+                    max_moisture: 1.,
+                    min_fertility: 0.2,
+                },
+                cold_tolerance: ColdTolerance(-2.),
+                crop_schedule: Some(CropSchedule {
+                    sowing_seasons: vec![Season::Spring, Season::Summer],
+                    harvest_seasons: vec![Season::Summer, Season::Autumn],
+                    days_between_tending: 2.,
+                }),
+            }),
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::acacia_leaf_production()),
+            build_duration: Duration::ZERO,
+            construction_materials: acacia_construction_materials,
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Plain, Terrain::Muddy]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("ant_hive"),
+        StructureData {
+            organism: None,
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::ant_egg_production()),
+            construction_materials: InputInventory::default(),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default()
+                // Call for food more urgently when the larder is nearly empty
+                .with_rule(
+                    Condition::StoredBelow {
+                        item_id: Id::leuco_chunk(),
+                        count: 2,
+                    },
+                    Effect::BoostEmission {
+                        signal_type: SignalType::Pull(Id::leuco_chunk()),
+                        multiplier: 3.,
+                    },
+                )
+                // Hurry eggs off to the hatchery before they pile up
+                .with_rule(
+                    Condition::StoredAbove {
+                        item_id: Id::ant_egg(),
+                        count: 2,
+                    },
+                    Effect::BoostEmission {
+                        signal_type: SignalType::Push(Id::ant_egg()),
+                        multiplier: 2.,
+                    },
+                ),
+            fragile: false,
+            // Stores kept deep in the nest stay cool and dry
+            preserves_items: true,
+            construction_models: ConstructionModels::default(),
+            build_duration: Duration::from_secs(10),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("hatchery"),
+        StructureData {
+            organism: None,
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::hatch_ants()),
+            construction_materials: InputInventory::default(),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            build_duration: Duration::from_secs(5),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Plain, Terrain::Rocky]),
+        },
+    );
+
+    let composter_construction_materials = InputInventory {
+        inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 2)),
+    };
+
+    map.insert(
+        Id::from_string_id("composter"),
+        StructureData {
+            organism: None,
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::composting()),
+            construction_materials: composter_construction_materials,
+            build_duration: Duration::from_secs(5),
+            // Decomposition is hot work
+            heat_source: Some(HeatSource {
+                intensity: 10.,
+                radius: 2,
+            }),
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            // Don't waste leaves when there's nowhere to put the fertilizer
+            automation: AutomationRules::default()
+                .with_rule(Condition::OutputFull, Effect::PauseRecipe),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("cistern"),
+        StructureData {
+            organism: None,
+            crafts: true,
+            starting_recipe: ActiveRecipe::new(Id::water_collection()),
+            construction_materials: InputInventory::default(),
+            build_duration: Duration::from_secs(5),
+            heat_source: None,
+            waterworks: Some(WaterworksKind::Cistern),
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("irrigation_channel"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory::default(),
+            build_duration: Duration::from_secs(2),
+            heat_source: None,
+            waterworks: Some(WaterworksKind::Channel),
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: true,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Plain, Terrain::Muddy]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("bridge"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 2)),
+            },
+            build_duration: Duration::from_secs(5),
+            heat_source: None,
+            waterworks: None,
+            // Bridges let units cross boggy ground quickly
+            crossing: Some(Crossing {
+                walking_speed: 1.5,
+                climbs_cliffs: false,
+            }),
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Muddy, Terrain::Water]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("ramp"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 2)),
+            },
+            build_duration: Duration::from_secs(5),
+            heat_source: None,
+            waterworks: None,
+            crossing: Some(Crossing {
+                walking_speed: 0.75,
+                climbs_cliffs: true,
+            }),
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("wall"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 1)),
+            },
+            build_duration: Duration::from_secs(3),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: Some(Wall {
+                signal_opacity: 1.,
+                supports_roof: true,
+            }),
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    // Fences block units, but let signals through
+    map.insert(
+        Id::from_string_id("fence"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 1)),
+            },
+            build_duration: Duration::from_secs(2),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            // Fences are too flimsy to hold up a roof
+            wall: Some(Wall {
+                signal_opacity: 0.,
+                supports_roof: false,
+            }),
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: true,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    // Watchtowers see far over the surrounding terrain, revealing signals in detail
+    map.insert(
+        Id::from_string_id("watchtower"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 2)),
+            },
+            build_duration: Duration::from_secs(5),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: Some(VisionSource {
+                radius: 8,
+                eye_height: 3.,
+                grants_intel: true,
+            }),
+            trap: None,
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([Terrain::Plain, Terrain::Rocky]),
+        },
+    );
+
+    map.insert(
+        Id::from_string_id("trap"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 2)),
+            },
+            build_duration: Duration::from_secs(3),
+            heat_source: None,
+            waterworks: None,
+            // Prey must be able to walk into the trap
+            crossing: Some(Crossing {
+                walking_speed: 1.,
+                climbs_cliffs: false,
+            }),
+            wall: None,
+            vision: None,
+            trap: Some(Trap::new(
+                Id::locust(),
+                Id::acacia_leaf(),
+                true,
+                5,
+                Duration::from_secs(10),
+            )),
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: true,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    // Cages hold their prey alive, rather than killing it
+    map.insert(
+        Id::from_string_id("cage"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 2)),
+            },
+            build_duration: Duration::from_secs(4),
+            heat_source: None,
+            waterworks: None,
+            crossing: Some(Crossing {
+                walking_speed: 1.,
+                climbs_cliffs: false,
+            }),
+            wall: None,
+            vision: None,
+            trap: Some(Trap::new(
+                Id::beetle(),
+                Id::acacia_leaf(),
+                false,
+                3,
+                Duration::from_secs(5),
+            )),
+            guard_post: None,
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: true,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    // Guard posts give soldiers somewhere to patrol other than the nest
+    map.insert(
+        Id::from_string_id("guard_post"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::leuco_chunk(), 1)),
+            },
+            build_duration: Duration::from_secs(3),
+            heat_source: None,
+            waterworks: None,
+            crossing: None,
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: Some(GuardPost { capacity: 3 }),
+            express_traversal_time: None,
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    // Tunnels are built in pairs, and carry units between their two entrances
+    map.insert(
+        Id::from_string_id("tunnel"),
+        StructureData {
+            organism: None,
+            crafts: false,
+            starting_recipe: ActiveRecipe::default(),
+            construction_materials: InputInventory {
+                inventory: Inventory::new_from_item(ItemCount::new(Id::acacia_leaf(), 5)),
+            },
+            build_duration: Duration::from_secs(20),
+            heat_source: None,
+            waterworks: None,
+            // Units must be able to step onto the entrance to use it
+            crossing: Some(Crossing {
+                walking_speed: 1.,
+                climbs_cliffs: false,
+            }),
+            wall: None,
+            vision: None,
+            trap: None,
+            guard_post: None,
+            express_traversal_time: Some(3.),
+            automation: AutomationRules::default(),
+            fragile: false,
+            preserves_items: false,
+            construction_models: ConstructionModels::default(),
+            allowed_terrain_types: HashSet::from_iter([
+                Terrain::Plain,
+                Terrain::Muddy,
+                Terrain::Rocky,
+            ]),
+        },
+    );
+
+    map
+}
+
+/// The identity, orientation and recipe of a single structure.
+///
+/// This is what the clipboard holds, and what zoning asks to be built.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClipboardData {
+    /// The identity of the structure.
+    pub structure_id: Id<Structure>,
+    /// The orientation of the structure.
+    pub facing: Facing,
+    /// The recipe that this structure makes, if any
+    pub active_recipe: ActiveRecipe,
+}
+
+/// Marks structures that can be destroyed by storms.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragile;
+
+/// The data needed to build a structure
+#[derive(Bundle)]
+struct StructureBundle {
+    /// Unique identifier of structure variety
+    structure: Id<Structure>,
+    /// The direction this structure is facing
+    facing: Facing,
+    /// The location of this structure
+    tile_pos: TilePos,
+}
+
+impl StructureBundle {
+    /// Creates a new structure
+    ///
+    /// The model used to draw and pick the structure is added separately, by the graphics.
+    fn new(tile_pos: TilePos, data: ClipboardData) -> Self {
+        StructureBundle {
+            structure: data.structure_id,
+            facing: data.facing,
+            tile_pos,
+        }
+    }
+}
+
+/// The systems that make structures tick.
+pub(super) struct StructuresPlugin;
+
+impl Plugin for StructuresPlugin {
+    fn build(&self, app: &mut App) {
+        // The items used to build each structure are checked against the items added by the `CraftingPlugin`
+        app.add_plugin(CraftingPlugin);
+        let structure_manifest = loaded_or_built_in(
+            load_structures(
+                &definitions_directory(),
+                app.world.resource::<ItemManifest>(),
+            ),
+            StructureManifest::default,
+        );
+
+        app.insert_resource(structure_manifest)
+            .add_plugin(AutomationPlugin)
+            .add_plugin(BuildOrderPlugin)
+            .add_plugin(ExpressPlugin)
+            .add_plugin(FarmingPlugin)
+            .add_plugin(IrrigationPlugin)
+            .add_plugin(TrapsPlugin)
+            .add_plugin(WallsPlugin)
+            .init_resource::<DemolitionRefund>()
+            .add_event::<StructureDemolished>()
+            .add_system(ghost_signals)
+            .add_system(ghost_lifecyle)
+            .add_system(update_construction_stage.after(ghost_lifecyle))
+            .add_system(refund_demolished_structures);
+    }
+}
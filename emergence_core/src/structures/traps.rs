@@ -0,0 +1,374 @@
+//! Traps lure pests in with bait, and catch them when they step inside.
+
+use crate::bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use core::fmt::Display;
+
+use crate::{
+    items::ItemCount,
+    manifest::{Id, Item, Unit},
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::geometry::TilePos,
+    units::alarm::ViolentDeath,
+};
+
+use super::crafting::InputInventory;
+
+/// A structure that catches units of a specific species that wander into it.
+///
+/// Each catch uses up one bait item, and the trap must then rearm before it can catch again.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Trap {
+    /// The species of unit that this trap catches
+    pub prey: Id<Unit>,
+    /// The item used to lure prey into the trap
+    pub bait: Id<Item>,
+    /// Does this trap kill its prey, or hold it captive?
+    pub lethal: bool,
+    /// The number of catches this trap can make before it is spent
+    pub capacity: u8,
+    /// The number of catches this trap has made so far
+    pub catches: u8,
+    /// How long it takes the trap to rearm after each catch
+    pub rearm_duration: Duration,
+    /// How much longer until the trap is ready to catch again
+    pub rearm_remaining: Duration,
+}
+
+impl Trap {
+    /// Creates a new, empty trap.
+    pub fn new(
+        prey: Id<Unit>,
+        bait: Id<Item>,
+        lethal: bool,
+        capacity: u8,
+        rearm_duration: Duration,
+    ) -> Self {
+        Trap {
+            prey,
+            bait,
+            lethal,
+            capacity,
+            catches: 0,
+            rearm_duration,
+            rearm_remaining: Duration::ZERO,
+        }
+    }
+
+    /// Has this trap used up all of its capacity?
+    pub fn is_spent(&self) -> bool {
+        self.catches >= self.capacity
+    }
+
+    /// Is this trap currently rearming after a catch?
+    pub fn is_rearming(&self) -> bool {
+        self.rearm_remaining > Duration::ZERO
+    }
+}
+
+impl Display for Trap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prey = self.prey;
+        let catches = self.catches;
+        let capacity = self.capacity;
+
+        let status = if self.is_spent() {
+            "Spent"
+        } else if self.is_rearming() {
+            "Rearming"
+        } else {
+            "Set"
+        };
+
+        write!(
+            f,
+            "Trap for {prey}: {status} ({catches} / {capacity} catches)"
+        )
+    }
+}
+
+/// Marks units that are being held captive by a non-lethal [`Trap`].
+///
+/// Captured units cannot act, and are released if the trap is removed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Captured {
+    /// The trap entity that is holding this unit
+    pub trap: Entity,
+}
+
+/// Tracks how many units of each species have been caught by traps.
+#[derive(Resource, Debug, Default)]
+pub struct TrapStatistics {
+    /// The number of units of each species that have been killed
+    pub killed: HashMap<Id<Unit>, u32>,
+    /// The number of units of each species that have been captured alive
+    pub captured: HashMap<Id<Unit>, u32>,
+}
+
+impl TrapStatistics {
+    /// The total number of units of the given `species` that have been caught, dead or alive.
+    pub fn total_caught(&self, species: Id<Unit>) -> u32 {
+        self.killed.get(&species).copied().unwrap_or_default()
+            + self.captured.get(&species).copied().unwrap_or_default()
+    }
+}
+
+/// The strength of the signal that traps emit to lure prey in.
+const BAIT_SIGNAL_STRENGTH: f32 = 20.;
+
+/// The distance over which the scent of bait is spread around each trap, so prey can find it from further away.
+pub(super) const BAIT_SCENT_RADIUS: u32 = 1;
+
+/// Traps ask for bait while they are not full, and advertise the bait they hold to lure prey.
+fn set_trap_emitter(mut trap_query: Query<(&Trap, &InputInventory, &mut Emitter)>) {
+    for (trap, bait_inventory, mut emitter) in trap_query.iter_mut() {
+        emitter.signals.clear();
+
+        if trap.is_spent() {
+            continue;
+        }
+
+        if !bait_inventory.is_full() {
+            emitter
+                .signals
+                .push((SignalType::Pull(trap.bait), SignalStrength::new(10.)));
+        }
+
+        if bait_inventory.item_count(trap.bait) > 0 && !trap.is_rearming() {
+            emitter.signals.push((
+                SignalType::Contains(trap.bait),
+                SignalStrength::new(BAIT_SIGNAL_STRENGTH),
+            ));
+        }
+    }
+}
+
+/// Ticks down the rearming time of each trap.
+fn rearm_traps(time: Res<Time>, mut trap_query: Query<&mut Trap>) {
+    for mut trap in trap_query.iter_mut() {
+        if trap.is_rearming() {
+            trap.rearm_remaining = trap.rearm_remaining.saturating_sub(time.delta());
+        }
+    }
+}
+
+/// Catches prey that have stepped onto a baited and armed trap.
+fn spring_traps(
+    mut trap_query: Query<(Entity, &TilePos, &mut Trap, &mut InputInventory)>,
+    unit_query: Query<(Entity, &TilePos, &Id<Unit>), Without<Captured>>,
+    mut trap_statistics: ResMut<TrapStatistics>,
+    mut violent_deaths: EventWriter<ViolentDeath>,
+    mut commands: Commands,
+) {
+    for (unit_entity, unit_pos, &species) in unit_query.iter() {
+        for (trap_entity, &trap_pos, mut trap, mut bait_inventory) in trap_query.iter_mut() {
+            if trap_pos != *unit_pos
+                || trap.prey != species
+                || trap.is_spent()
+                || trap.is_rearming()
+            {
+                continue;
+            }
+
+            // An unbaited trap will not trigger
+            if bait_inventory
+                .remove_item_all_or_nothing(&ItemCount::one(trap.bait))
+                .is_err()
+            {
+                continue;
+            }
+
+            trap.catches += 1;
+            trap.rearm_remaining = trap.rearm_duration;
+
+            if trap.lethal {
+                *trap_statistics.killed.entry(species).or_default() += 1;
+                commands.entity(unit_entity).despawn_recursive();
+                violent_deaths.send(ViolentDeath {
+                    unit_id: species,
+                    tile_pos: trap_pos,
+                });
+            } else {
+                *trap_statistics.captured.entry(species).or_default() += 1;
+                commands
+                    .entity(unit_entity)
+                    .insert(Captured { trap: trap_entity });
+            }
+
+            break;
+        }
+    }
+}
+
+/// Frees captured units whose trap no longer exists.
+fn release_captives(
+    captive_query: Query<(Entity, &Captured)>,
+    trap_query: Query<(), With<Trap>>,
+    mut commands: Commands,
+) {
+    for (captive_entity, captured) in captive_query.iter() {
+        if !trap_query.contains(captured.trap) {
+            commands.entity(captive_entity).remove::<Captured>();
+        }
+    }
+}
+
+/// Logic for traps and the units they catch.
+pub(super) struct TrapsPlugin;
+
+impl Plugin for TrapsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrapStatistics>()
+            .add_system(rearm_traps)
+            .add_system(spring_traps.after(rearm_traps))
+            .add_system(set_trap_emitter.after(spring_traps))
+            .add_system(release_captives);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::utils::Instant;
+
+    use super::*;
+    use crate::{
+        items::{inventory::Inventory, ItemData},
+        manifest::ItemManifest,
+    };
+
+    /// The time that passes during each update of the apps made by [`trap_app`].
+    const FRAME_DURATION: Duration = Duration::from_millis(500);
+
+    /// An app with a live trap for locusts at the origin, holding `n_bait` pieces of bait.
+    fn trap_app(n_bait: usize) -> (App, Entity) {
+        let mut app = App::new();
+
+        // The clock is never advanced, so every frame lasts for the same time
+        let mut time = Time::default();
+        let start = Instant::now();
+        time.update_with_instant(start);
+        time.update_with_instant(start + FRAME_DURATION);
+
+        app.insert_resource(time)
+            .init_resource::<TrapStatistics>()
+            .add_event::<ViolentDeath>()
+            .add_system(rearm_traps)
+            .add_system(spring_traps.after(rearm_traps))
+            .add_system(release_captives);
+
+        let bait = Id::test();
+        let item_manifest = ItemManifest::new(HashMap::from([(bait, ItemData::new(10, None))]));
+        let mut inventory = Inventory::new(1);
+        inventory
+            .add_item_all_or_nothing(&ItemCount::new(bait, n_bait), &item_manifest)
+            .unwrap();
+
+        let trap = Trap::new(Id::locust(), bait, false, 2, FRAME_DURATION * 2);
+        let bait_inventory = InputInventory { inventory };
+        let trap_entity = app
+            .world
+            .spawn((TilePos::ORIGIN, trap, bait_inventory))
+            .id();
+
+        (app, trap_entity)
+    }
+
+    /// Spawns a unit of the kind `unit_id` on the trap.
+    fn spawn_unit(app: &mut App, unit_id: Id<Unit>) -> Entity {
+        app.world.spawn((TilePos::ORIGIN, unit_id)).id()
+    }
+
+    #[test]
+    fn baited_traps_catch_their_prey() {
+        let (mut app, trap_entity) = trap_app(2);
+        let locust = spawn_unit(&mut app, Id::locust());
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Captured>(locust),
+            Some(&Captured { trap: trap_entity })
+        );
+        let trap = app.world.get::<Trap>(trap_entity).unwrap();
+        assert_eq!(trap.catches, 1);
+        assert!(trap.is_rearming());
+        let bait = app.world.get::<InputInventory>(trap_entity).unwrap();
+        assert_eq!(bait.item_count(trap.bait), 1);
+        assert_eq!(
+            app.world
+                .resource::<TrapStatistics>()
+                .total_caught(Id::locust()),
+            1
+        );
+    }
+
+    #[test]
+    fn traps_ignore_other_species() {
+        let (mut app, _) = trap_app(2);
+        let ant = spawn_unit(&mut app, Id::ant());
+        app.update();
+
+        assert!(app.world.get::<Captured>(ant).is_none());
+    }
+
+    #[test]
+    fn unbaited_traps_do_not_trigger() {
+        let (mut app, trap_entity) = trap_app(0);
+        let locust = spawn_unit(&mut app, Id::locust());
+        app.update();
+
+        assert!(app.world.get::<Captured>(locust).is_none());
+        assert_eq!(app.world.get::<Trap>(trap_entity).unwrap().catches, 0);
+    }
+
+    #[test]
+    fn lethal_traps_kill_their_prey() {
+        let (mut app, trap_entity) = trap_app(2);
+        app.world.get_mut::<Trap>(trap_entity).unwrap().lethal = true;
+        let locust = spawn_unit(&mut app, Id::locust());
+        app.update();
+
+        assert!(app.world.get_entity(locust).is_none());
+        let trap_statistics = app.world.resource::<TrapStatistics>();
+        assert_eq!(trap_statistics.killed[&Id::locust()], 1);
+        assert!(trap_statistics.captured.is_empty());
+        assert_eq!(app.world.resource::<Events<ViolentDeath>>().len(), 1);
+    }
+
+    #[test]
+    fn traps_rearm_before_catching_again() {
+        let (mut app, trap_entity) = trap_app(2);
+        spawn_unit(&mut app, Id::locust());
+        app.update();
+
+        let second_locust = spawn_unit(&mut app, Id::locust());
+        // Half of the rearming time has passed
+        app.update();
+        assert!(app.world.get::<Captured>(second_locust).is_none());
+
+        app.update();
+        assert!(app.world.get::<Captured>(second_locust).is_some());
+
+        // The trap has reached its capacity, and will never catch again
+        let trap = app.world.get::<Trap>(trap_entity).unwrap();
+        assert!(trap.is_spent());
+        let third_locust = spawn_unit(&mut app, Id::locust());
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world.get::<Captured>(third_locust).is_none());
+    }
+
+    #[test]
+    fn captives_are_released_when_their_trap_is_removed() {
+        let (mut app, trap_entity) = trap_app(2);
+        let locust = spawn_unit(&mut app, Id::locust());
+        app.update();
+        assert!(app.world.get::<Captured>(locust).is_some());
+
+        app.world.despawn(trap_entity);
+        app.update();
+        assert!(app.world.get::<Captured>(locust).is_none());
+    }
+}
@@ -0,0 +1,261 @@
+//! Walls and other barriers, used to shape both movement and the flow of signals.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use hexx::{shapes::hexagon, Hex};
+
+use crate::simulation::geometry::{MapGeometry, TilePos};
+
+/// A structure that blocks movement, and may also block signals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wall {
+    /// The fraction of signal strength that is blocked when diffusing into this wall, from 0 to 1.
+    ///
+    /// Walls with an opacity of 0 are transparent to signals, while walls with an opacity of 1 block them entirely.
+    pub signal_opacity: f32,
+    /// Can this wall hold up a roof, sheltering the region it encloses?
+    pub supports_roof: bool,
+}
+
+/// The set of tiles that are completely cut off from the edge of the map.
+///
+/// This is used to give players feedback about the areas that their walls have closed off.
+///
+/// Small enclosed regions whose walls can all support a roof are sheltered from the weather.
+#[derive(Resource, Debug, Default)]
+pub struct Enclosures {
+    /// The passable tiles which cannot be reached from the edge of the map
+    enclosed_tiles: HashSet<TilePos>,
+    /// The enclosed tiles which are covered by a roof
+    sheltered_tiles: HashSet<TilePos>,
+    /// The tiles beneath each separate roof
+    shelters: Vec<HashSet<TilePos>>,
+}
+
+impl Enclosures {
+    /// Is the provided `tile_pos` walled off from the edge of the map?
+    pub fn is_enclosed(&self, tile_pos: TilePos) -> bool {
+        self.enclosed_tiles.contains(&tile_pos)
+    }
+
+    /// Is the provided `tile_pos` roofed over, sheltering it from the weather?
+    pub fn is_sheltered(&self, tile_pos: TilePos) -> bool {
+        self.sheltered_tiles.contains(&tile_pos)
+    }
+
+    /// The tiles beneath each separate roof.
+    pub fn shelters(&self) -> &[HashSet<TilePos>] {
+        &self.shelters
+    }
+
+    /// The index into [`Enclosures::shelters`] of the roof that covers `tile_pos`, if any.
+    pub fn shelter_containing(&self, tile_pos: TilePos) -> Option<usize> {
+        if !self.is_sheltered(tile_pos) {
+            return None;
+        }
+
+        self.shelters
+            .iter()
+            .position(|shelter| shelter.contains(&tile_pos))
+    }
+}
+
+/// The largest number of tiles that can be covered by a single roof.
+const MAX_SHELTER_SIZE: usize = 37;
+
+/// Flood fills from the edge of the map to find all enclosed regions.
+fn detect_enclosures(map_geometry: Res<MapGeometry>, mut enclosures: ResMut<Enclosures>) {
+    if !map_geometry.is_changed() {
+        return;
+    }
+
+    let mut reached: HashSet<TilePos> = HashSet::new();
+    let mut frontier: Vec<TilePos> = Vec::new();
+
+    for hex in Hex::ZERO.ring(map_geometry.radius) {
+        let tile_pos = TilePos { hex };
+        if map_geometry.is_passable(tile_pos) && reached.insert(tile_pos) {
+            frontier.push(tile_pos);
+        }
+    }
+
+    while let Some(tile_pos) = frontier.pop() {
+        for neighbor in tile_pos.reachable_neighbors(&map_geometry) {
+            if reached.insert(neighbor) {
+                frontier.push(neighbor);
+            }
+        }
+    }
+
+    enclosures.enclosed_tiles = hexagon(Hex::ZERO, map_geometry.radius)
+        .map(|hex| TilePos { hex })
+        .filter(|tile_pos| map_geometry.is_passable(*tile_pos) && !reached.contains(tile_pos))
+        .collect();
+
+    enclosures.shelters = detect_shelters(&enclosures.enclosed_tiles, &map_geometry);
+    enclosures.sheltered_tiles = enclosures.shelters.iter().flatten().copied().collect();
+}
+
+/// Splits the enclosed tiles into connected regions, and returns every region that can be roofed over.
+///
+/// A region can be roofed if it is small enough, and every tile bordering it holds a wall that supports a roof.
+fn detect_shelters(
+    enclosed_tiles: &HashSet<TilePos>,
+    map_geometry: &MapGeometry,
+) -> Vec<HashSet<TilePos>> {
+    let mut shelters = Vec::new();
+    let mut visited: HashSet<TilePos> = HashSet::new();
+
+    for &start in enclosed_tiles {
+        if !visited.insert(start) {
+            continue;
+        }
+
+        let mut region: HashSet<TilePos> = HashSet::new();
+        let mut frontier: Vec<TilePos> = vec![start];
+        region.insert(start);
+
+        while let Some(tile_pos) = frontier.pop() {
+            for neighbor in tile_pos.reachable_neighbors(map_geometry) {
+                if enclosed_tiles.contains(&neighbor) && region.insert(neighbor) {
+                    visited.insert(neighbor);
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        if region.len() > MAX_SHELTER_SIZE {
+            continue;
+        }
+
+        let roofable = region.iter().all(|tile_pos| {
+            tile_pos
+                .all_neighbors(map_geometry)
+                .into_iter()
+                .filter(|neighbor| !region.contains(neighbor))
+                .all(|neighbor| {
+                    map_geometry
+                        .wall_index
+                        .get(&neighbor)
+                        .is_some_and(|wall| wall.supports_roof)
+                })
+        });
+
+        if roofable {
+            shelters.push(region);
+        }
+    }
+
+    shelters
+}
+
+/// Logic for walls and the regions they enclose.
+pub(super) struct WallsPlugin;
+
+impl Plugin for WallsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Enclosures>()
+            .add_system(detect_enclosures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A wall that can hold up a roof.
+    const ROOF_WALL: Wall = Wall {
+        signal_opacity: 1.,
+        supports_roof: true,
+    };
+
+    /// Finds the [`Enclosures`] on a map of the given `radius`, with a `wall` on each of the `wall_tiles`.
+    fn find_enclosures(
+        radius: u32,
+        wall_tiles: impl IntoIterator<Item = TilePos>,
+        wall: Wall,
+    ) -> Enclosures {
+        let mut map_geometry = MapGeometry::new(radius);
+        for tile_pos in wall_tiles {
+            map_geometry
+                .structure_index
+                .insert(tile_pos, Entity::PLACEHOLDER);
+            map_geometry.wall_index.insert(tile_pos, wall);
+        }
+
+        let mut app = App::new();
+        app.init_resource::<Enclosures>()
+            .insert_resource(map_geometry)
+            .add_system(detect_enclosures);
+        app.update();
+
+        app.world.remove_resource::<Enclosures>().unwrap()
+    }
+
+    /// The tiles in a ring of the given `radius` around `center`.
+    fn ring(center: TilePos, radius: u32) -> Vec<TilePos> {
+        center
+            .hex
+            .ring(radius)
+            .into_iter()
+            .map(|hex| TilePos { hex })
+            .collect()
+    }
+
+    #[test]
+    fn closed_rings_enclose_and_shelter_their_center() {
+        let enclosures = find_enclosures(4, ring(TilePos::ORIGIN, 1), ROOF_WALL);
+
+        assert!(enclosures.is_enclosed(TilePos::ORIGIN));
+        assert!(enclosures.is_sheltered(TilePos::ORIGIN));
+        assert_eq!(enclosures.shelter_containing(TilePos::ORIGIN), Some(0));
+        assert_eq!(enclosures.shelters().len(), 1);
+
+        // The outside of the ring is still open
+        assert!(!enclosures.is_enclosed(TilePos::new(2, 0)));
+        assert_eq!(enclosures.shelter_containing(TilePos::new(2, 0)), None);
+    }
+
+    #[test]
+    fn rings_with_a_gap_enclose_nothing() {
+        let mut walls = ring(TilePos::ORIGIN, 1);
+        walls.pop();
+        let enclosures = find_enclosures(4, walls, ROOF_WALL);
+
+        assert!(!enclosures.is_enclosed(TilePos::ORIGIN));
+        assert!(!enclosures.is_sheltered(TilePos::ORIGIN));
+        assert!(enclosures.shelters().is_empty());
+    }
+
+    #[test]
+    fn regions_touching_the_map_edge_are_not_enclosed() {
+        let edge_tile = TilePos::new(3, 0);
+        let enclosures = find_enclosures(3, ring(edge_tile, 1), ROOF_WALL);
+
+        assert!(!enclosures.is_enclosed(edge_tile));
+        assert!(!enclosures.is_sheltered(edge_tile));
+        assert!(enclosures.shelters().is_empty());
+    }
+
+    #[test]
+    fn walls_that_cannot_hold_a_roof_enclose_without_sheltering() {
+        let fence = Wall {
+            signal_opacity: 0.,
+            supports_roof: false,
+        };
+        let enclosures = find_enclosures(4, ring(TilePos::ORIGIN, 1), fence);
+
+        assert!(enclosures.is_enclosed(TilePos::ORIGIN));
+        assert!(!enclosures.is_sheltered(TilePos::ORIGIN));
+    }
+
+    #[test]
+    fn large_enclosures_are_too_big_to_roof() {
+        // A ring of radius 4 encloses 37 tiles, which fits beneath a single roof
+        let enclosures = find_enclosures(6, ring(TilePos::ORIGIN, 4), ROOF_WALL);
+        assert!(enclosures.is_sheltered(TilePos::ORIGIN));
+
+        let enclosures = find_enclosures(7, ring(TilePos::ORIGIN, 5), ROOF_WALL);
+        assert!(enclosures.is_enclosed(TilePos::ORIGIN));
+        assert!(!enclosures.is_sheltered(TilePos::ORIGIN));
+    }
+}
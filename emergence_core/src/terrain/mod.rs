@@ -0,0 +1,299 @@
+//! Generating and representing terrain as game objects.
+
+use crate::bevy::prelude::*;
+
+use crate as emergence_core;
+
+use crate::bevy::ecs::component::Component;
+use crate::simulation::geometry::{MapGeometry, TilePos};
+use crate::simulation::lod::{lod_group_ready, LodGroup, LodSchedule};
+use crate::simulation::temperature::Temperature;
+use crate::structures::ClipboardData;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use emergence_macros::IterableEnum;
+
+/// Available terrain types.
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Hash,
+    Eq,
+    PartialEq,
+    IterableEnum,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+pub enum Terrain {
+    /// Terrain with no distinguishing characteristics.
+    Plain,
+    /// Terrain that is rocky, and thus difficult to traverse.
+    Rocky,
+    /// Terrain that is unusually muddy.
+    Muddy,
+    /// Terrain covered by water, which can only be crossed when frozen or bridged.
+    Water,
+}
+
+impl Terrain {
+    /// The walking speed multiplier associated with this terrain type.
+    ///
+    /// These values should always be strictly positive.
+    /// Higher values make units walk faster.
+    pub const fn walking_speed(&self) -> f32 {
+        match self {
+            Terrain::Plain => 1.0,
+            Terrain::Rocky => 2.0,
+            Terrain::Muddy => 0.5,
+            // Ice is slippery, but flat
+            Terrain::Water => 1.5,
+        }
+    }
+
+    /// The soil moisture that tiles of this terrain type start with, from 0 to 1.
+    pub const fn base_moisture(&self) -> f32 {
+        match self {
+            Terrain::Plain => 0.4,
+            Terrain::Rocky => 0.1,
+            Terrain::Muddy => 0.9,
+            Terrain::Water => 1.0,
+        }
+    }
+
+    /// The soil fertility that tiles of this terrain type start with, from 0 to 1.
+    pub const fn base_fertility(&self) -> f32 {
+        match self {
+            Terrain::Plain => 0.5,
+            Terrain::Rocky => 0.2,
+            Terrain::Muddy => 0.7,
+            Terrain::Water => 0.,
+        }
+    }
+}
+
+/// The zoning of a given tile, which specifies which structure *should* be built there.
+#[derive(Component, PartialEq, Eq, Clone, Debug)]
+pub enum Zoning {
+    /// The provided structure should be built on this tile.
+    Structure(ClipboardData),
+    /// No zoning is set.
+    None,
+    /// Zoning is set to keep the tile clear.
+    KeepClear,
+}
+
+impl std::fmt::Display for Zoning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Zoning::Structure(clipboard_data) => {
+                let id = clipboard_data.structure_id;
+                format!("{id}")
+            }
+            Zoning::None => "None".to_string(),
+            Zoning::KeepClear => "Keep Clear".to_string(),
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// How wet the soil of a tile is, from 0 (bone dry) to 1 (waterlogged).
+#[derive(Component, Clone, Copy, Debug, PartialEq, PartialOrd, Deref, DerefMut)]
+pub struct SoilMoisture(pub f32);
+
+/// How rich the soil of a tile is in nutrients, from 0 (barren) to 1 (lush).
+///
+/// Growing plants deplete the soil, while decaying matter replenishes it.
+/// Depleted soil also slowly recovers on its own, back towards the natural fertility of its terrain type.
+#[derive(Component, Clone, Copy, Debug, PartialEq, PartialOrd, Deref, DerefMut)]
+pub struct Fertility(pub f32);
+
+impl Fertility {
+    /// Adds `nutrients` to the soil, up to the maximum fertility.
+    pub fn replenish(&mut self, nutrients: f32) {
+        self.0 = (self.0 + nutrients).min(1.);
+    }
+
+    /// Removes `nutrients` from the soil, down to the minimum fertility.
+    pub fn deplete(&mut self, nutrients: f32) {
+        self.0 = (self.0 - nutrients).max(0.);
+    }
+
+    /// Recovers `elapsed_seconds` worth of fertility, if the soil is poorer than its `base_fertility`.
+    ///
+    /// Soil that has been enriched beyond its base fertility keeps its extra nutrients until plants draw them out.
+    /// The gap closes exponentially, so the result does not depend on how often this is called.
+    pub fn recover(&mut self, base_fertility: f32, elapsed_seconds: f32) {
+        /// The rate at which the gap to the base fertility closes, per second
+        const RECOVERY_RATE: f32 = 0.002;
+
+        let gap = base_fertility - self.0;
+        if gap > 0. {
+            self.0 += gap * (1. - (-RECOVERY_RATE * elapsed_seconds).exp());
+        }
+    }
+}
+
+/// Organic matter that has decayed into the soil at `tile_pos`, returning its nutrients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decompose {
+    /// The tile where the matter decayed
+    pub tile_pos: TilePos,
+    /// The fertility returned to the soil
+    pub nutrients: f32,
+}
+
+/// Decomposing matter enriches the soil where it decays, and a little of the soil around it.
+fn return_nutrients(
+    mut decompose_events: EventReader<Decompose>,
+    mut fertility_query: Query<&mut Fertility>,
+    map_geometry: Res<MapGeometry>,
+) {
+    /// The fraction of the nutrients that spreads to each neighboring tile
+    const NEIGHBOR_SHARE: f32 = 0.1;
+
+    for event in decompose_events.iter() {
+        let neighbors: Vec<TilePos> = event
+            .tile_pos
+            .all_neighbors(&map_geometry)
+            .into_iter()
+            .collect();
+        let neighbor_nutrients = event.nutrients * NEIGHBOR_SHARE;
+        let central_nutrients = event.nutrients - neighbor_nutrients * neighbors.len() as f32;
+
+        let shares = neighbors
+            .into_iter()
+            .map(|tile_pos| (tile_pos, neighbor_nutrients))
+            .chain(std::iter::once((event.tile_pos, central_nutrients)));
+
+        for (tile_pos, nutrients) in shares {
+            if let Some(&terrain_entity) = map_geometry.terrain_index.get(&tile_pos) {
+                if let Ok(mut fertility) = fertility_query.get_mut(terrain_entity) {
+                    fertility.replenish(nutrients);
+                }
+            }
+        }
+    }
+}
+
+/// Soil slowly returns to the natural moisture level of its terrain type.
+///
+/// The gap closes exponentially, so the result does not depend on how often this system runs.
+fn dry_out_soil(
+    lod_schedule: Res<LodSchedule>,
+    mut terrain_query: Query<(&Terrain, &mut SoilMoisture)>,
+) {
+    /// The rate at which the gap to the base moisture closes, per second
+    const DRYING_RATE: f32 = 0.02;
+
+    let fraction_closed = 1. - (-DRYING_RATE * lod_schedule.delta_seconds(LodGroup::Soil)).exp();
+
+    for (terrain, mut soil_moisture) in terrain_query.iter_mut() {
+        let gap = terrain.base_moisture() - soil_moisture.0;
+        soil_moisture.0 += gap * fraction_closed;
+    }
+}
+
+/// Depleted soil slowly recovers the natural fertility of its terrain type.
+fn regenerate_soil(
+    lod_schedule: Res<LodSchedule>,
+    mut terrain_query: Query<(&Terrain, &mut Fertility)>,
+) {
+    let elapsed_seconds = lod_schedule.delta_seconds(LodGroup::Soil);
+
+    for (terrain, mut fertility) in terrain_query.iter_mut() {
+        // Avoid triggering change detection for the many tiles that have nothing to recover
+        if fertility.0 < terrain.base_fertility() {
+            fertility.recover(terrain.base_fertility(), elapsed_seconds);
+        }
+    }
+}
+
+/// Simulates changes to the terrain over time.
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Decompose>()
+            .add_system(dry_out_soil.run_if(lod_group_ready(LodGroup::Soil)))
+            .add_system(regenerate_soil.run_if(lod_group_ready(LodGroup::Soil)))
+            .add_system(return_nutrients);
+    }
+}
+
+/// All of the components needed to define a piece of terrain.
+#[derive(Bundle)]
+pub struct TerrainBundle {
+    /// The type of terrain
+    terrain_type: Terrain,
+    /// The location of this terrain hex
+    tile_pos: TilePos,
+    /// The structure that should be built here.
+    zoning: Zoning,
+    /// How wet the soil is
+    soil_moisture: SoilMoisture,
+    /// How rich the soil is
+    fertility: Fertility,
+    /// How hot or cold this tile is
+    temperature: Temperature,
+}
+
+impl TerrainBundle {
+    /// Creates a new Terrain entity.
+    ///
+    /// The mesh used to draw and pick the tile is added separately, by the graphics.
+    pub fn new(terrain_type: Terrain, tile_pos: TilePos) -> Self {
+        TerrainBundle {
+            terrain_type,
+            tile_pos,
+            zoning: Zoning::None,
+            soil_moisture: SoilMoisture(terrain_type.base_moisture()),
+            fertility: Fertility(terrain_type.base_fertility()),
+            temperature: Temperature::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depleted_soil_recovers() {
+        let base_fertility = Terrain::Plain.base_fertility();
+        let mut fertility = Fertility(0.);
+
+        fertility.recover(base_fertility, 60.);
+        assert!(fertility.0 > 0.);
+        assert!(fertility.0 < base_fertility);
+
+        // Given long enough, the soil is almost back to normal
+        fertility.recover(base_fertility, 3600.);
+        assert!(fertility.0 > 0.99 * base_fertility);
+        assert!(fertility.0 <= base_fertility);
+    }
+
+    #[test]
+    fn soil_recovers_at_the_same_pace_however_often_it_is_updated() {
+        let mut in_one_step = Fertility(0.1);
+        in_one_step.recover(0.5, 100.);
+
+        let mut in_many_steps = Fertility(0.1);
+        for _ in 0..100 {
+            in_many_steps.recover(0.5, 1.);
+        }
+
+        assert!((in_one_step.0 - in_many_steps.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn enriched_soil_keeps_its_nutrients() {
+        let mut fertility = Fertility(0.9);
+        fertility.recover(0.5, 600.);
+        assert_eq!(fertility, Fertility(0.9));
+    }
+}
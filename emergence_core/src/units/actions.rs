@@ -0,0 +1,1431 @@
+//! What are units currently doing?
+
+use crate::bevy::{ecs::query::WorldQuery, prelude::*};
+use core::fmt::Display;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    items::ItemCount,
+    manifest::{Id, Item, ItemManifest, Structure, Unit, UnitManifest},
+    organisms::energy::{Energy, EnergyPool},
+    signals::{SignalSensitivity, Signals, StepSelection},
+    simulation::{
+        flow_fields::FlowFields,
+        geometry::{Facing, MapGeometry, RotationDirection, TilePos},
+        jitter::{Jitter, JitterStream},
+        weather::LocalWeather,
+    },
+    structures::{
+        commands::StructureCommandsExt,
+        construction::{DemolitionQuery, Ghost, Preview, StructureDemolished},
+        crafting::{CraftingState, InputInventory, OutputInventory, WorkplaceQuery},
+        traps::Captured,
+    },
+    terrain::Terrain,
+};
+
+use super::{
+    crowding::{avoid_crowds, TileOccupancy},
+    goals::Goal,
+    hunger::Diet,
+    impatience::ImpatiencePool,
+    item_interaction::UnitInventory,
+    routes::Route,
+    soldiers::{Caste, PATROL_RADIUS},
+};
+
+/// The time in seconds that it takes a standard unit to walk to an adjacent tile.
+pub const BASE_WALKING_DURATION: f32 = 0.5;
+
+/// The energy spent walking to an adjacent tile.
+const WALKING_ENERGY_COST: Energy = Energy(0.25);
+
+/// The energy spent on a single bout of work or demolition.
+const WORKING_ENERGY_COST: Energy = Energy(0.5);
+
+/// Ticks the timer for each [`CurrentAction`].
+///
+/// Units that walk faster or slower than a standard unit finish their movement sooner or later.
+pub(super) fn advance_action_timer(
+    mut units_query: Query<(&mut CurrentAction, &TilePos, Option<&Id<Unit>>)>,
+    unit_manifest: Res<UnitManifest>,
+    local_weather: LocalWeather,
+    time: Res<Time>,
+) {
+    let delta = time.delta();
+
+    for (mut current_action, &tile_pos, maybe_unit_id) in units_query.iter_mut() {
+        let walking_speed = match (current_action.action(), maybe_unit_id) {
+            (UnitAction::MoveForward, Some(unit_id)) => {
+                // Bad weather slows everyone down
+                unit_manifest.get(*unit_id).walking_speed()
+                    * local_weather.at(tile_pos).walking_speed_multiplier()
+            }
+            _ => 1.,
+        };
+
+        current_action.timer.tick(delta.mul_f32(walking_speed));
+    }
+}
+
+/// The time in seconds that it takes a standard unit to walk from `tile_pos` to the adjacent `target_tile_pos`.
+///
+/// Structures that can be walked across override the walking speed of the terrain beneath them.
+/// Walking uphill takes longer, as described by [`MapGeometry::slope_cost`].
+pub fn walking_duration(
+    tile_pos: TilePos,
+    target_tile_pos: TilePos,
+    map_geometry: &MapGeometry,
+    terrain_query: &Query<&Terrain>,
+) -> f32 {
+    let walking_speed = match map_geometry.crossing_index.get(&tile_pos) {
+        Some(crossing) => crossing.walking_speed,
+        None => {
+            let entity_standing_on = *map_geometry.terrain_index.get(&tile_pos).unwrap();
+            let terrain_standing_on = terrain_query.get(entity_standing_on).unwrap();
+            terrain_standing_on.walking_speed()
+        }
+    };
+
+    BASE_WALKING_DURATION / walking_speed * map_geometry.slope_cost(tile_pos, target_tile_pos)
+}
+
+/// The shortest time it could take any unit to walk between two adjacent tiles, at the standard walking speed.
+///
+/// Slopes only ever make steps take longer, so they are ignored here.
+/// This is a lower bound on [`walking_duration`], as needed to search for paths.
+pub fn min_walking_duration(terrain_query: &Query<&Terrain>, map_geometry: &MapGeometry) -> f32 {
+    let max_walking_speed = terrain_query
+        .iter()
+        .map(|terrain| terrain.walking_speed())
+        .chain(
+            map_geometry
+                .crossing_index
+                .values()
+                .map(|crossing| crossing.walking_speed),
+        )
+        .fold(1., f32::max);
+
+    BASE_WALKING_DURATION / max_walking_speed
+}
+
+/// Choose the unit's action for this turn
+#[allow(clippy::too_many_arguments)]
+pub(super) fn choose_actions(
+    mut units_query: Query<
+        (
+            Entity,
+            &TilePos,
+            &Id<Unit>,
+            &Facing,
+            &Goal,
+            &mut CurrentAction,
+            &UnitInventory,
+            &mut Route,
+        ),
+        Without<Captured>,
+    >,
+    // This must be compatible with units_query
+    intruder_query: Query<(Entity, &TilePos, &Id<Unit>), Without<Captured>>,
+    unit_manifest: Res<UnitManifest>,
+    input_inventory_query: Query<&InputInventory>,
+    output_inventory_query: Query<&OutputInventory>,
+    workplace_query: WorkplaceQuery,
+    demolition_query: DemolitionQuery,
+    nest_query: Query<(&TilePos, &Id<Structure>), (Without<Ghost>, Without<Preview>)>,
+    signals: Res<Signals>,
+    terrain_query: Query<&Terrain>,
+    mut flow_fields: ResMut<FlowFields>,
+    tile_occupancy: Res<TileOccupancy>,
+    step_selection: Res<StepSelection>,
+    jitter: Jitter,
+) {
+    let map_geometry = map_geometry.into_inner();
+
+    for (
+        unit_entity,
+        &unit_tile_pos,
+        &unit_id,
+        facing,
+        goal,
+        mut action,
+        unit_inventory,
+        mut route,
+    ) in units_query.iter_mut()
+    {
+        if action.finished() {
+            let rng = &mut jitter.rng(unit_entity, JitterStream::Actions);
+            let goal_weights = unit_manifest.get(unit_id).signal_sensitivity();
+
+            let new_action = match goal {
+                // Alternate between spinning and moving forward.
+                Goal::Wander => match action.action() {
+                    UnitAction::Spin { .. } => CurrentAction::move_forward(
+                        unit_tile_pos,
+                        facing,
+                        map_geometry,
+                        &terrain_query,
+                    ),
+                    _ => CurrentAction::random_spin(rng),
+                },
+                Goal::Pickup(item_id) => {
+                    if unit_inventory.held_item.is_some()
+                        && unit_inventory.held_item != Some(*item_id)
+                    {
+                        CurrentAction::abandon()
+                    } else {
+                        CurrentAction::find_item(
+                            *item_id,
+                            unit_tile_pos,
+                            facing,
+                            goal,
+                            &output_inventory_query,
+                            &signals,
+                            goal_weights,
+                            *step_selection,
+                            rng,
+                            &terrain_query,
+                            map_geometry,
+                        )
+                    }
+                }
+                Goal::DropOff(item_id) => {
+                    if unit_inventory.held_item.is_some()
+                        && unit_inventory.held_item != Some(*item_id)
+                    {
+                        CurrentAction::abandon()
+                    } else {
+                        CurrentAction::find_receptacle(
+                            *item_id,
+                            unit_tile_pos,
+                            facing,
+                            goal,
+                            &input_inventory_query,
+                            &signals,
+                            goal_weights,
+                            *step_selection,
+                            rng,
+                            &terrain_query,
+                            map_geometry,
+                        )
+                    }
+                }
+                Goal::Eat(item_id) => {
+                    if let Some(held_item) = unit_inventory.held_item {
+                        if held_item == *item_id {
+                            CurrentAction::eat()
+                        } else {
+                            CurrentAction::abandon()
+                        }
+                    } else {
+                        CurrentAction::find_item(
+                            *item_id,
+                            unit_tile_pos,
+                            facing,
+                            goal,
+                            &output_inventory_query,
+                            &signals,
+                            goal_weights,
+                            *step_selection,
+                            rng,
+                            &terrain_query,
+                            map_geometry,
+                        )
+                    }
+                }
+                Goal::Work(structure_id) => CurrentAction::find_workplace(
+                    *structure_id,
+                    unit_tile_pos,
+                    facing,
+                    &workplace_query,
+                    &signals,
+                    goal_weights,
+                    *step_selection,
+                    rng,
+                    &mut route,
+                    &terrain_query,
+                    map_geometry,
+                ),
+                Goal::Demolish(structure_id) => CurrentAction::find_demolition_site(
+                    *structure_id,
+                    unit_tile_pos,
+                    facing,
+                    &demolition_query,
+                    &signals,
+                    goal_weights,
+                    *step_selection,
+                    rng,
+                    &mut route,
+                    &terrain_query,
+                    map_geometry,
+                ),
+                Goal::Guard(structure_id) => CurrentAction::guard_nest(
+                    *structure_id,
+                    unit_tile_pos,
+                    facing,
+                    &nest_query,
+                    &mut flow_fields,
+                    &terrain_query,
+                    map_geometry,
+                ),
+                Goal::Patrol(center) => {
+                    if unit_tile_pos.hex.distance_to(center.hex) > PATROL_RADIUS {
+                        // Follow the planned route if there is one, and head straight there while waiting for it
+                        match CurrentAction::follow_route(
+                            unit_tile_pos,
+                            &mut route,
+                            facing,
+                            &terrain_query,
+                            map_geometry,
+                        ) {
+                            Some(action) => action,
+                            None => CurrentAction::step_towards(
+                                unit_tile_pos,
+                                *center,
+                                facing,
+                                &terrain_query,
+                                map_geometry,
+                            ),
+                        }
+                    } else {
+                        // Within the patrol area, walk the beat like a wandering unit
+                        match action.action() {
+                            UnitAction::Spin { .. } => CurrentAction::move_forward(
+                                unit_tile_pos,
+                                facing,
+                                map_geometry,
+                                &terrain_query,
+                            ),
+                            _ => CurrentAction::random_spin(rng),
+                        }
+                    }
+                }
+                Goal::Fight => match unit_manifest.get(unit_id).caste() {
+                    Caste::Soldier { attack_damage } => CurrentAction::fight(
+                        attack_damage,
+                        unit_tile_pos,
+                        facing,
+                        &intruder_query,
+                        &unit_manifest,
+                        &signals,
+                        goal_weights,
+                        *step_selection,
+                        rng,
+                        &terrain_query,
+                        map_geometry,
+                    ),
+                    // Only soldiers know how to fight
+                    Caste::Worker => CurrentAction::idle(),
+                },
+                Goal::Avoid => CurrentAction::flee(
+                    unit_tile_pos,
+                    facing,
+                    &signals,
+                    &terrain_query,
+                    map_geometry,
+                ),
+            };
+
+            *action = avoid_crowds(
+                new_action,
+                unit_tile_pos,
+                facing,
+                &tile_occupancy,
+                &terrain_query,
+                map_geometry,
+                rng,
+            );
+        }
+    }
+}
+
+/// Exhaustively handles each planned action
+#[allow(clippy::too_many_arguments)]
+pub(super) fn handle_actions(
+    mut unit_query: Query<ActionDataQuery, Without<Captured>>,
+    mut input_query: Query<&mut InputInventory>,
+    mut output_query: Query<&mut OutputInventory>,
+    mut workplace_query: Query<&mut CraftingState>,
+    // This must be compatible with unit_query
+    structure_query: Query<(&TilePos, &Id<Structure>), Without<Goal>>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+    map_geometry: Res<MapGeometry>,
+    item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut demolition_events: EventWriter<StructureDemolished>,
+    mut commands: Commands,
+) {
+    let item_manifest = &*item_manifest;
+    // Attacks are resolved after all units have acted, as the target is also in unit_query
+    let mut attacks: Vec<(Entity, Energy)> = Vec::new();
+
+    for mut unit in unit_query.iter_mut() {
+        if unit.action.finished() {
+            // Exertion adds to hunger, and can work a starving unit to death
+            let energy_cost = unit.action.action().energy_cost();
+            if energy_cost > Energy(0.) {
+                let proposed = unit.energy_pool.current() - energy_cost;
+                unit.energy_pool.set_current(proposed);
+            }
+
+            match unit.action.action() {
+                UnitAction::Idle => {
+                    unit.impatience.increment();
+                }
+                UnitAction::PickUp {
+                    item_id,
+                    output_entity,
+                } => {
+                    if let Ok(mut output_inventory) = output_query.get_mut(*output_entity) {
+                        *unit.goal = match unit.unit_inventory.held_item {
+                            // We shouldn't be holding anything yet, but if we are get rid of it
+                            Some(held_item_id) => Goal::DropOff(held_item_id),
+                            None => {
+                                // Carry as many as we can manage in a single trip
+                                let carry_capacity =
+                                    unit_manifest.get(*unit.unit_id).carry_capacity();
+                                let n_items = output_inventory
+                                    .item_count(*item_id)
+                                    .min(carry_capacity)
+                                    .max(1);
+                                let item_count = ItemCount::new(*item_id, n_items);
+                                let transfer_result =
+                                    output_inventory.remove_item_all_or_nothing(&item_count);
+
+                                // If our unit's all loaded, swap to delivering it
+                                match transfer_result {
+                                    Ok(()) => {
+                                        unit.unit_inventory.hold(*item_id, n_items);
+                                        Goal::DropOff(*item_id)
+                                    }
+                                    Err(..) => Goal::Pickup(*item_id),
+                                }
+                            }
+                        }
+                    } else {
+                        // If the target isn't there, pick a new goal
+                        *unit.goal = Goal::Wander;
+                    }
+                }
+                UnitAction::DropOff {
+                    item_id,
+                    input_entity,
+                } => {
+                    if let Ok(mut input_inventory) = input_query.get_mut(*input_entity) {
+                        *unit.goal = match unit.unit_inventory.held_item {
+                            // We should be holding something, if we're not find something else to do
+                            None => Goal::Wander,
+                            Some(held_item_id) => {
+                                if held_item_id == *item_id {
+                                    let item_count =
+                                        ItemCount::new(held_item_id, unit.unit_inventory.count());
+                                    let transfer_result =
+                                        input_inventory.try_add_item(&item_count, item_manifest);
+
+                                    // If our unit is unloaded, swap to wandering to find something else to do
+                                    match transfer_result {
+                                        Ok(()) => {
+                                            unit.unit_inventory.clear();
+                                            Goal::Wander
+                                        }
+                                        // Keep whatever did not fit, and look for somewhere else to put it
+                                        Err(error) => {
+                                            unit.unit_inventory
+                                                .hold(held_item_id, error.excess_count.count());
+                                            Goal::DropOff(held_item_id)
+                                        }
+                                    }
+                                } else {
+                                    // Somehow we're holding the wrong thing
+                                    Goal::DropOff(held_item_id)
+                                }
+                            }
+                        }
+                    } else {
+                        // If the target isn't there, pick a new goal
+                        *unit.goal = Goal::Wander;
+                    }
+                }
+                UnitAction::Spin { rotation_direction } => match rotation_direction {
+                    RotationDirection::Left => unit.facing.rotate_left(),
+                    RotationDirection::Right => unit.facing.rotate_right(),
+                },
+                UnitAction::MoveForward => {
+                    let direction = unit.facing.direction;
+                    let target_tile = unit.tile_pos.neighbor(direction);
+
+                    // Another unit may have filled the tile since this move was chosen
+                    if !tile_occupancy.is_full(target_tile) {
+                        tile_occupancy.move_unit(*unit.tile_pos, target_tile);
+                        *unit.tile_pos = target_tile;
+                    }
+                }
+                UnitAction::TakeExpressRoute { exit } => {
+                    // The route may have been destroyed while the unit was travelling along it
+                    let still_linked = map_geometry
+                        .express_index
+                        .get(&*unit.tile_pos)
+                        .map(|link| link.exit)
+                        == Some(*exit);
+
+                    // Units are drawn at their tile, so they pop out of the other end
+                    if still_linked && !tile_occupancy.is_full(*exit) {
+                        tile_occupancy.move_unit(*unit.tile_pos, *exit);
+                        *unit.tile_pos = *exit;
+                    }
+                }
+                UnitAction::Work { structure_entity } => {
+                    // If something went wrong, give up on this goal
+                    // This temporary variable is just to avoid horribly complex nesting
+                    let mut success = false;
+
+                    if let Ok(mut crafting_state) = workplace_query.get_mut(*structure_entity) {
+                        if let CraftingState::InProgress {
+                            progress,
+                            required,
+                            work_required,
+                            worker_present: _,
+                        } = *crafting_state
+                        {
+                            if work_required {
+                                *crafting_state = CraftingState::InProgress {
+                                    progress,
+                                    required,
+                                    work_required,
+                                    // FIXME: this will stay true indefinitely
+                                    worker_present: true,
+                                };
+                                success = true;
+                            }
+                        }
+                    }
+
+                    if !success {
+                        *unit.goal = Goal::Wander;
+                    }
+                }
+                UnitAction::Demolish { structure_entity } => {
+                    if let Ok((&tile_pos, &structure_id)) = structure_query.get(*structure_entity) {
+                        // TOOD: this should probably take time and use work?
+                        commands.despawn_structure(tile_pos);
+                        demolition_events.send(StructureDemolished {
+                            structure_id,
+                            tile_pos,
+                        });
+                    }
+
+                    // Whether we succeeded or failed, pick something else to do
+                    *unit.goal = Goal::Wander;
+                }
+                UnitAction::Eat => {
+                    match unit.unit_inventory.held_item {
+                        // Only a single item is eaten, and the rest are kept for later
+                        Some(held_item) if held_item == unit.diet.item() => {
+                            let proposed = unit.energy_pool.current() + unit.diet.energy();
+                            unit.energy_pool.set_current(proposed);
+                            unit.unit_inventory.remove(1);
+                        }
+                        _ => unit.unit_inventory.clear(),
+                    }
+                }
+                UnitAction::Abandon => {
+                    // TODO: actually put these dropped items somewhere
+                    unit.unit_inventory.clear();
+                }
+                UnitAction::Attack { target, damage } => {
+                    attacks.push((*target, *damage));
+                }
+            }
+        }
+    }
+
+    // Targets that run out of energy are cleaned up by kill_organisms_when_out_of_energy
+    for (target, damage) in attacks {
+        if let Ok(mut target_unit) = unit_query.get_mut(target) {
+            let proposed = target_unit.energy_pool.current() - damage;
+            target_unit.energy_pool.set_current(proposed);
+        }
+    }
+}
+
+/// All of the data needed to handle unit actions correctly
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub(super) struct ActionDataQuery {
+    /// The kind of unit
+    unit_id: &'static Id<Unit>,
+    /// The unit's goal
+    goal: &'static mut Goal,
+    /// The unit's action
+    action: &'static CurrentAction,
+    /// What the unit is holding
+    unit_inventory: &'static mut UnitInventory,
+    /// The tile that the unit is on
+    tile_pos: &'static mut TilePos,
+    /// What the unit eats
+    diet: &'static Diet,
+    /// How much energy the unit has
+    energy_pool: &'static mut EnergyPool,
+    /// How frustrated this unit is about not being able to progress towards its goal
+    impatience: &'static mut ImpatiencePool,
+    /// The direction this unit is facing
+    facing: &'static mut Facing,
+}
+
+/// An action that a unit can take.
+#[derive(Default, Clone, Debug)]
+pub(super) enum UnitAction {
+    /// Do nothing for now
+    #[default]
+    Idle,
+    /// Pick up the `item_id` from the `output_entity`.
+    PickUp {
+        /// The item to pickup.
+        item_id: Id<Item>,
+        /// The entity to grab it from, which must have an [`OutputInventory`] component.
+        output_entity: Entity,
+    },
+    /// Drops off the `item_id` at the `output_entity`.
+    DropOff {
+        /// The item that this unit is carrying that we should drop off.
+        item_id: Id<Item>,
+        /// The entity to drop it off at, which must have an [`InputInventory`] component.
+        input_entity: Entity,
+    },
+    /// Perform work at the provided `structure_entity`
+    Work {
+        /// The structure to work at.
+        structure_entity: Entity,
+    },
+    /// Attempt to deconstruct the provided `structure_entity`
+    Demolish {
+        /// The structure to work at.
+        structure_entity: Entity,
+    },
+    /// Spin left or right.
+    Spin {
+        /// The direction to turn in.
+        rotation_direction: RotationDirection,
+    },
+    /// Move one tile forward, as determined by the unit's [`Facing`].
+    MoveForward,
+    /// Travel along the express route that starts on the unit's tile.
+    TakeExpressRoute {
+        /// The tile at the other end of the express route.
+        exit: TilePos,
+    },
+    /// Eats one of the currently held object
+    Eat,
+    /// Abandon whatever you are currently holding
+    Abandon,
+    /// Attack the `target` unit, draining its energy
+    Attack {
+        /// The unit to attack.
+        target: Entity,
+        /// The amount of energy removed from the target.
+        damage: Energy,
+    },
+}
+
+impl UnitAction {
+    /// The energy spent by a unit completing this action, on top of its passive metabolism.
+    pub(super) fn energy_cost(&self) -> Energy {
+        match self {
+            // Tunnels are walked through too, however far apart their ends are
+            UnitAction::MoveForward | UnitAction::TakeExpressRoute { .. } => WALKING_ENERGY_COST,
+            UnitAction::Work { .. } | UnitAction::Demolish { .. } => WORKING_ENERGY_COST,
+            UnitAction::Idle
+            | UnitAction::PickUp { .. }
+            | UnitAction::DropOff { .. }
+            | UnitAction::Spin { .. }
+            | UnitAction::Eat
+            | UnitAction::Abandon
+            | UnitAction::Attack { .. } => Energy(0.),
+        }
+    }
+}
+
+impl Display for UnitAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string: String = match self {
+            UnitAction::Idle => "Idling".to_string(),
+            UnitAction::PickUp {
+                item_id,
+                output_entity,
+            } => format!("Picking up {item_id} from {output_entity:?}"),
+            UnitAction::DropOff {
+                item_id,
+                input_entity,
+            } => format!("Dropping off {item_id} at {input_entity:?}"),
+            UnitAction::Work { structure_entity } => format!("Working at {structure_entity:?}"),
+            UnitAction::Demolish { structure_entity } => {
+                format!("Demolishing {structure_entity:?}")
+            }
+            UnitAction::Spin { rotation_direction } => format!("Spinning {rotation_direction}"),
+            UnitAction::MoveForward => "Moving forward".to_string(),
+            UnitAction::TakeExpressRoute { exit } => format!("Travelling express to {exit}"),
+            UnitAction::Eat => "Eating".to_string(),
+            UnitAction::Abandon => "Abandoning held object".to_string(),
+            UnitAction::Attack { target, damage } => format!("Attacking {target:?} for {damage}"),
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+/// The action a unit is undertaking.
+pub struct CurrentAction {
+    /// The type of action being undertaken.
+    action: UnitAction,
+    /// The amount of time left to complete the action.
+    timer: Timer,
+}
+
+impl Default for CurrentAction {
+    fn default() -> Self {
+        CurrentAction::idle()
+    }
+}
+
+impl Display for CurrentAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = &self.action;
+        let time_remaining = self.timer.remaining_secs();
+
+        write!(f, "{action}\nRemaining: {time_remaining:.2} s.")
+    }
+}
+
+impl CurrentAction {
+    /// Get the action that the unit is currently undertaking.
+    pub(super) fn action(&self) -> &UnitAction {
+        &self.action
+    }
+
+    /// Have we waited long enough to perform this action?
+    pub(super) fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    /// The structure that this action is directed at, if any.
+    pub fn target_entity(&self) -> Option<Entity> {
+        match self.action {
+            UnitAction::PickUp { output_entity, .. } => Some(output_entity),
+            UnitAction::DropOff { input_entity, .. } => Some(input_entity),
+            UnitAction::Work { structure_entity } | UnitAction::Demolish { structure_entity } => {
+                Some(structure_entity)
+            }
+            _ => None,
+        }
+    }
+
+    /// Attempt to locate a source of the provided `item_id`.
+    #[allow(clippy::too_many_arguments)]
+    fn find_item(
+        item_id: Id<Item>,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        goal: &Goal,
+        output_inventory_query: &Query<&OutputInventory>,
+        signals: &Signals,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let neighboring_tiles = unit_tile_pos.all_neighbors(map_geometry);
+        let mut sources: Vec<(Entity, TilePos)> = Vec::new();
+
+        for tile_pos in neighboring_tiles {
+            // Structures
+            if let Some(&structure_entity) = map_geometry.structure_index.get(&tile_pos) {
+                if let Ok(output_inventory) = output_inventory_query.get(structure_entity) {
+                    if output_inventory.item_count(item_id) > 0 {
+                        sources.push((structure_entity, tile_pos));
+                    }
+                }
+            }
+
+            // Remains
+            if let Some(&remains_entity) = map_geometry.remains_index.get(&tile_pos) {
+                if let Ok(output_inventory) = output_inventory_query.get(remains_entity) {
+                    if output_inventory.item_count(item_id) > 0 {
+                        sources.push((remains_entity, tile_pos));
+                    }
+                }
+            }
+        }
+
+        if let Some((output_entity, output_tile_pos)) = sources.choose(rng) {
+            CurrentAction::pickup(
+                item_id,
+                *output_entity,
+                facing,
+                unit_tile_pos,
+                *output_tile_pos,
+            )
+        } else if let Some(upstream) = signals.choose_upstream(
+            unit_tile_pos,
+            goal,
+            goal_weights,
+            step_selection,
+            rng,
+            map_geometry,
+        ) {
+            CurrentAction::move_or_spin(
+                unit_tile_pos,
+                upstream,
+                facing,
+                terrain_query,
+                map_geometry,
+            )
+        } else {
+            CurrentAction::idle()
+        }
+    }
+
+    /// Attempt to locate a place to put an item of type `item_id`.
+    #[allow(clippy::too_many_arguments)]
+    fn find_receptacle(
+        item_id: Id<Item>,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        goal: &Goal,
+        input_inventory_query: &Query<&InputInventory>,
+        signals: &Signals,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let neighboring_tiles = unit_tile_pos.all_neighbors(map_geometry);
+        let mut receptacles: Vec<(Entity, TilePos)> = Vec::new();
+
+        for tile_pos in neighboring_tiles {
+            // Ghosts
+            if let Some(&ghost_entity) = map_geometry.ghost_index.get(&tile_pos) {
+                if let Ok(input_inventory) = input_inventory_query.get(ghost_entity) {
+                    if input_inventory.remaining_reserved_space_for_item(item_id) > 0 {
+                        receptacles.push((ghost_entity, tile_pos));
+                    }
+                }
+            }
+
+            // Structures
+            if let Some(&structure_entity) = map_geometry.structure_index.get(&tile_pos) {
+                if let Ok(input_inventory) = input_inventory_query.get(structure_entity) {
+                    if input_inventory.remaining_reserved_space_for_item(item_id) > 0 {
+                        receptacles.push((structure_entity, tile_pos));
+                    }
+                }
+            }
+        }
+
+        if let Some((input_entity, input_tile_pos)) = receptacles.choose(rng) {
+            CurrentAction::dropoff(
+                item_id,
+                *input_entity,
+                facing,
+                unit_tile_pos,
+                *input_tile_pos,
+            )
+        } else if let Some(upstream) = signals.choose_upstream(
+            unit_tile_pos,
+            goal,
+            goal_weights,
+            step_selection,
+            rng,
+            map_geometry,
+        ) {
+            CurrentAction::move_or_spin(
+                unit_tile_pos,
+                upstream,
+                facing,
+                terrain_query,
+                map_geometry,
+            )
+        } else {
+            CurrentAction::idle()
+        }
+    }
+
+    /// Attempt to find a structure of type `structure_id` to perform work
+    #[allow(clippy::too_many_arguments)]
+    fn find_workplace(
+        structure_id: Id<Structure>,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        workplace_query: &WorkplaceQuery,
+        signals: &Signals,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        route: &mut Route,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let ahead = unit_tile_pos.neighbor(facing.direction);
+        if let Some(workplace) = workplace_query.needs_work(ahead, structure_id, map_geometry) {
+            CurrentAction::work(workplace)
+        // Let units work even if they're standing on the structure
+        // This is particularly relevant in the case of ghosts, where it's easy enough to end up on top of the structure trying to work on it
+        } else if let Some(workplace) =
+            workplace_query.needs_work(unit_tile_pos, structure_id, map_geometry)
+        {
+            CurrentAction::work(workplace)
+        } else {
+            let neighboring_tiles = unit_tile_pos.all_neighbors(map_geometry);
+            let mut workplaces: Vec<(Entity, TilePos)> = Vec::new();
+
+            for neighbor in neighboring_tiles {
+                if let Some(workplace) =
+                    workplace_query.needs_work(neighbor, structure_id, map_geometry)
+                {
+                    workplaces.push((workplace, neighbor));
+                }
+            }
+
+            if let Some(chosen_workplace) = workplaces.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_tile_pos,
+                    chosen_workplace.1,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+            } else if let Some(upstream) = signals.choose_upstream(
+                unit_tile_pos,
+                &Goal::Work(structure_id),
+                goal_weights,
+                step_selection,
+                rng,
+                map_geometry,
+            ) {
+                CurrentAction::move_or_spin(
+                    unit_tile_pos,
+                    upstream,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+            } else {
+                // The signal is too weak to follow, so head for the nearest site instead
+                CurrentAction::follow_route(
+                    unit_tile_pos,
+                    route,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+                .unwrap_or_else(CurrentAction::idle)
+            }
+        }
+    }
+
+    /// Attempt to find a structure of type `structure_id` to perform work
+    #[allow(clippy::too_many_arguments)]
+    fn find_demolition_site(
+        structure_id: Id<Structure>,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        demolition_query: &DemolitionQuery,
+        signals: &Signals,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        route: &mut Route,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let ahead = unit_tile_pos.neighbor(facing.direction);
+        if let Some(workplace) =
+            demolition_query.needs_demolition(ahead, structure_id, map_geometry)
+        {
+            CurrentAction::demolish(workplace)
+        } else if let Some(workplace) =
+            demolition_query.needs_demolition(unit_tile_pos, structure_id, map_geometry)
+        {
+            CurrentAction::demolish(workplace)
+        } else {
+            let neighboring_tiles = unit_tile_pos.all_neighbors(map_geometry);
+            let mut demo_sites: Vec<(Entity, TilePos)> = Vec::new();
+
+            for neighbor in neighboring_tiles {
+                if let Some(demo_site) =
+                    demolition_query.needs_demolition(neighbor, structure_id, map_geometry)
+                {
+                    demo_sites.push((demo_site, neighbor));
+                }
+            }
+
+            if let Some(chosen_demo_site) = demo_sites.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_tile_pos,
+                    chosen_demo_site.1,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+            } else if let Some(upstream) = signals.choose_upstream(
+                unit_tile_pos,
+                &Goal::Demolish(structure_id),
+                goal_weights,
+                step_selection,
+                rng,
+                map_geometry,
+            ) {
+                CurrentAction::move_or_spin(
+                    unit_tile_pos,
+                    upstream,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+            } else {
+                // The signal is too weak to follow, so head for the nearest site instead
+                CurrentAction::follow_route(
+                    unit_tile_pos,
+                    route,
+                    facing,
+                    terrain_query,
+                    map_geometry,
+                )
+                .unwrap_or_else(CurrentAction::idle)
+            }
+        }
+    }
+
+    /// Return to the nearest structure of type `structure_id`, then wait beside it.
+    ///
+    /// Every guard heading to the same nest shares a single [`FlowField`](crate::simulation::flow_fields::FlowField).
+    fn guard_nest(
+        structure_id: Id<Structure>,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        nest_query: &Query<(&TilePos, &Id<Structure>), (Without<Ghost>, Without<Preview>)>,
+        flow_fields: &mut FlowFields,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let distance_to =
+            |tile_pos: TilePos, nest_pos: TilePos| tile_pos.hex.distance_to(nest_pos.hex);
+
+        let maybe_nest_pos = nest_query
+            .iter()
+            .filter(|(_, &nest_id)| nest_id == structure_id)
+            .map(|(&nest_pos, _)| nest_pos)
+            .min_by_key(|&nest_pos| distance_to(unit_tile_pos, nest_pos));
+
+        // With no nest to return to, hold position
+        let nest_pos = match maybe_nest_pos {
+            Some(nest_pos) => nest_pos,
+            None => return CurrentAction::idle(),
+        };
+
+        if distance_to(unit_tile_pos, nest_pos) <= 1 {
+            return CurrentAction::idle();
+        }
+
+        let flow_field = flow_fields.get_or_compute(map_geometry, nest_pos, |tile_pos, target| {
+            walking_duration(tile_pos, target, map_geometry, terrain_query)
+        });
+
+        match flow_field.next_step(unit_tile_pos) {
+            Some(next_tile) => CurrentAction::move_or_spin(
+                unit_tile_pos,
+                next_tile,
+                facing,
+                terrain_query,
+                map_geometry,
+            ),
+            // The nest cannot be reached from here, so get as close as possible
+            None => CurrentAction::step_towards(
+                unit_tile_pos,
+                nest_pos,
+                facing,
+                terrain_query,
+                map_geometry,
+            ),
+        }
+    }
+
+    /// Take one greedy step towards the `target_tile_pos`, or idle if no neighboring tile is closer.
+    fn step_towards(
+        unit_tile_pos: TilePos,
+        target_tile_pos: TilePos,
+        facing: &Facing,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let distance_to = |tile_pos: TilePos| tile_pos.hex.distance_to(target_tile_pos.hex);
+        let current_distance = distance_to(unit_tile_pos);
+
+        let maybe_closer_tile = unit_tile_pos
+            .reachable_neighbors(map_geometry)
+            .into_iter()
+            .filter(|&neighbor| distance_to(neighbor) < current_distance)
+            .min_by_key(|&neighbor| distance_to(neighbor));
+
+        match maybe_closer_tile {
+            Some(closer_tile) => CurrentAction::move_or_spin(
+                unit_tile_pos,
+                closer_tile,
+                facing,
+                terrain_query,
+                map_geometry,
+            ),
+            None => CurrentAction::idle(),
+        }
+    }
+
+    /// Attack an adjacent intruder, or follow the alarm to find one.
+    #[allow(clippy::too_many_arguments)]
+    fn fight(
+        attack_damage: Energy,
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        intruder_query: &Query<(Entity, &TilePos, &Id<Unit>), Without<Captured>>,
+        unit_manifest: &UnitManifest,
+        signals: &Signals,
+        goal_weights: &SignalSensitivity,
+        step_selection: StepSelection,
+        rng: &mut impl Rng,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let maybe_adjacent_intruder = intruder_query
+            .iter()
+            .filter(|(_, _, &unit_id)| unit_manifest.get(unit_id).nest().is_none())
+            .find(|(_, intruder_pos, _)| unit_tile_pos.hex.distance_to(intruder_pos.hex) <= 1);
+
+        if let Some((target, &target_tile_pos, _)) = maybe_adjacent_intruder {
+            CurrentAction::attack(
+                target,
+                attack_damage,
+                facing,
+                unit_tile_pos,
+                target_tile_pos,
+            )
+        } else if let Some(upstream) = signals.choose_upstream(
+            unit_tile_pos,
+            &Goal::Fight,
+            goal_weights,
+            step_selection,
+            rng,
+            map_geometry,
+        ) {
+            CurrentAction::move_or_spin(
+                unit_tile_pos,
+                upstream,
+                facing,
+                terrain_query,
+                map_geometry,
+            )
+        } else {
+            CurrentAction::idle()
+        }
+    }
+
+    /// Walk down the danger gradient, or hold position if there is nowhere safer to go.
+    fn flee(
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        signals: &Signals,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        match signals.downstream(unit_tile_pos, &Goal::Avoid, map_geometry) {
+            Some(downstream) => CurrentAction::move_or_spin(
+                unit_tile_pos,
+                downstream,
+                facing,
+                terrain_query,
+                map_geometry,
+            ),
+            None => CurrentAction::idle(),
+        }
+    }
+
+    /// Spins 60 degrees left or right.
+    pub(super) fn spin(rotation_direction: RotationDirection) -> Self {
+        CurrentAction {
+            action: UnitAction::Spin { rotation_direction },
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        }
+    }
+
+    /// Rotate to face the `required_direction`.
+    fn spin_towards(facing: &Facing, required_direction: hexx::Direction) -> Self {
+        let mut working_direction_left = facing.direction;
+        let mut working_direction_right = facing.direction;
+
+        // Let's race!
+        // Left gets an arbitrary unfair advantage though.
+        // PERF: this could use a lookup table instead, and would probably be faster
+        loop {
+            working_direction_left = working_direction_left.left();
+            if working_direction_left == required_direction {
+                return CurrentAction::spin(RotationDirection::Left);
+            }
+
+            working_direction_right = working_direction_right.right();
+            if working_direction_right == required_direction {
+                return CurrentAction::spin(RotationDirection::Right);
+            }
+        }
+    }
+
+    /// Spins 60 degrees in a random direction
+    pub(super) fn random_spin(rng: &mut impl Rng) -> Self {
+        let rotation_direction = RotationDirection::random(rng);
+
+        CurrentAction::spin(rotation_direction)
+    }
+
+    /// Move toward the tile this unit is facing if able
+    pub(super) fn move_forward(
+        unit_tile_pos: TilePos,
+        facing: &Facing,
+        map_geometry: &MapGeometry,
+        terrain_query: &Query<&Terrain>,
+    ) -> Self {
+        let target_tile = unit_tile_pos.neighbor(facing.direction);
+        let walking_duration =
+            walking_duration(unit_tile_pos, target_tile, map_geometry, terrain_query);
+
+        if map_geometry.can_step(unit_tile_pos, target_tile) {
+            CurrentAction {
+                action: UnitAction::MoveForward,
+                timer: Timer::from_seconds(walking_duration, TimerMode::Once),
+            }
+        } else {
+            CurrentAction::idle()
+        }
+    }
+
+    /// Takes the next step along the planned `route`, travelling express where the route does.
+    ///
+    /// Returns `None` if there is no step to take, because the route is complete or has not been planned yet.
+    fn follow_route(
+        unit_tile_pos: TilePos,
+        route: &mut Route,
+        facing: &Facing,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> Option<Self> {
+        let next_tile = route.next_step(unit_tile_pos, map_geometry)?;
+        let express_exit = map_geometry
+            .express_index
+            .get(&unit_tile_pos)
+            .map(|link| link.exit);
+
+        if express_exit == Some(next_tile) {
+            Some(CurrentAction::take_express_route(
+                unit_tile_pos,
+                map_geometry,
+            ))
+        } else {
+            Some(CurrentAction::move_or_spin(
+                unit_tile_pos,
+                next_tile,
+                facing,
+                terrain_query,
+                map_geometry,
+            ))
+        }
+    }
+
+    /// Travels along the express route that starts at `unit_tile_pos`, if there is one.
+    pub(super) fn take_express_route(unit_tile_pos: TilePos, map_geometry: &MapGeometry) -> Self {
+        match map_geometry.express_index.get(&unit_tile_pos) {
+            Some(link) => CurrentAction {
+                action: UnitAction::TakeExpressRoute { exit: link.exit },
+                timer: Timer::from_seconds(link.traversal_time, TimerMode::Once),
+            },
+            None => CurrentAction::idle(),
+        }
+    }
+
+    /// Attempt to move toward the `target_tile_pos`.
+    pub(super) fn move_or_spin(
+        unit_tile_pos: TilePos,
+        target_tile_pos: TilePos,
+        facing: &Facing,
+        terrain_query: &Query<&Terrain>,
+        map_geometry: &MapGeometry,
+    ) -> Self {
+        let required_direction = unit_tile_pos.direction_to(target_tile_pos.hex);
+
+        if required_direction == facing.direction {
+            CurrentAction::move_forward(unit_tile_pos, facing, map_geometry, terrain_query)
+        } else {
+            CurrentAction::spin_towards(facing, required_direction)
+        }
+    }
+
+    /// Wait, as there is nothing to be done.
+    pub(super) fn idle() -> Self {
+        CurrentAction {
+            action: UnitAction::Idle,
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        }
+    }
+
+    /// Picks up the `item_id` at the `output_entity`.
+    pub(super) fn pickup(
+        item_id: Id<Item>,
+        output_entity: Entity,
+        facing: &Facing,
+        unit_tile_pos: TilePos,
+        output_tile_pos: TilePos,
+    ) -> Self {
+        let required_direction = unit_tile_pos.direction_to(output_tile_pos.hex);
+
+        if required_direction == facing.direction {
+            CurrentAction {
+                action: UnitAction::PickUp {
+                    item_id,
+                    output_entity,
+                },
+                timer: Timer::from_seconds(0.5, TimerMode::Once),
+            }
+        } else {
+            CurrentAction::spin_towards(facing, required_direction)
+        }
+    }
+
+    /// Drops off the `item_id` at the `input_entity`.
+    pub(super) fn dropoff(
+        item_id: Id<Item>,
+        input_entity: Entity,
+        facing: &Facing,
+        unit_tile_pos: TilePos,
+        input_tile_pos: TilePos,
+    ) -> Self {
+        let required_direction = unit_tile_pos.direction_to(input_tile_pos.hex);
+
+        if required_direction == facing.direction {
+            CurrentAction {
+                action: UnitAction::DropOff {
+                    item_id,
+                    input_entity,
+                },
+                timer: Timer::from_seconds(0.2, TimerMode::Once),
+            }
+        } else {
+            CurrentAction::spin_towards(facing, required_direction)
+        }
+    }
+
+    /// Eats one of the currently held item.
+    pub(super) fn eat() -> Self {
+        CurrentAction {
+            action: UnitAction::Eat,
+            timer: Timer::from_seconds(0.5, TimerMode::Once),
+        }
+    }
+
+    /// Work at the specified structure
+    pub(super) fn work(structure_entity: Entity) -> Self {
+        CurrentAction {
+            action: UnitAction::Work { structure_entity },
+            timer: Timer::from_seconds(1.0, TimerMode::Once),
+        }
+    }
+
+    /// Demolish the specified structure
+    pub(super) fn demolish(structure_entity: Entity) -> Self {
+        CurrentAction {
+            action: UnitAction::Demolish { structure_entity },
+            timer: Timer::from_seconds(1.0, TimerMode::Once),
+        }
+    }
+
+    /// Attacks the `target` unit, turning to face it first if needed.
+    pub(super) fn attack(
+        target: Entity,
+        damage: Energy,
+        facing: &Facing,
+        unit_tile_pos: TilePos,
+        target_tile_pos: TilePos,
+    ) -> Self {
+        // Intruders standing on the same tile can be attacked without turning
+        if target_tile_pos == unit_tile_pos
+            || unit_tile_pos.direction_to(target_tile_pos.hex) == facing.direction
+        {
+            CurrentAction {
+                action: UnitAction::Attack { target, damage },
+                timer: Timer::from_seconds(0.8, TimerMode::Once),
+            }
+        } else {
+            CurrentAction::spin_towards(facing, unit_tile_pos.direction_to(target_tile_pos.hex))
+        }
+    }
+
+    /// Eats one of the currently held item.
+    pub(super) fn abandon() -> Self {
+        CurrentAction {
+            action: UnitAction::Abandon,
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::utils::{Duration, HashMap};
+
+    use super::*;
+
+    /// An app that only handles the actions of units.
+    fn acting_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<TileOccupancy>()
+            .insert_resource(MapGeometry::new(3))
+            .insert_resource(ItemManifest::new(HashMap::default()))
+            .init_resource::<UnitManifest>()
+            .add_event::<StructureDemolished>()
+            .add_system(handle_actions);
+
+        app
+    }
+
+    /// Spawns an ant at the origin with full energy, which has just finished waiting to perform `action`.
+    fn spawn_ant(app: &mut App, action: UnitAction) -> Entity {
+        let mut timer = Timer::from_seconds(1., TimerMode::Once);
+        timer.tick(Duration::from_secs(1));
+
+        app.world
+            .spawn((
+                Id::ant(),
+                Goal::default(),
+                CurrentAction { action, timer },
+                UnitInventory::default(),
+                TilePos::ORIGIN,
+                Diet::new(Id::leuco_chunk(), Energy(10.)),
+                EnergyPool::new_full(Energy(100.), Energy(0.)),
+                ImpatiencePool::new(10),
+                Facing::default(),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn walking_and_working_drain_energy() {
+        let mut app = acting_app();
+        let structure_entity = app.world.spawn_empty().id();
+        let walker = spawn_ant(&mut app, UnitAction::MoveForward);
+        let worker = spawn_ant(&mut app, UnitAction::Work { structure_entity });
+        let idler = spawn_ant(&mut app, UnitAction::Idle);
+
+        app.update();
+
+        let energy = |entity| app.world.get::<EnergyPool>(entity).unwrap().current();
+        assert_eq!(energy(walker), Energy(100.) - WALKING_ENERGY_COST);
+        assert_eq!(energy(worker), Energy(100.) - WORKING_ENERGY_COST);
+        assert_eq!(energy(idler), Energy(100.));
+    }
+}
@@ -0,0 +1,382 @@
+//! Alarms are raised when the colony is threatened, and cause nearby units to fall back and guard their nest.
+//!
+//! The alarm is spread using [`SignalType::Alarm`], so it fades with distance and time like any other signal.
+//! Units that sense enough of it drop what they are doing and switch to [`Goal::Guard`] until it dies down.
+//! Soldiers instead switch to [`Goal::Fight`], and head towards the source of the alarm.
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+
+use crate::{
+    manifest::{Id, Unit, UnitManifest},
+    signals::{SignalSchedule, SignalStrength, SignalType, Signals},
+    simulation::{
+        alerts::Alert,
+        geometry::{MapGeometry, TilePos},
+        vision::VisionSource,
+    },
+};
+
+use super::{goals::Goal, soldiers::Caste, UnitSystem};
+
+/// The strength of the alarm raised each tick by each intruder that can be seen by the colony.
+const INTRUDER_ALARM_STRENGTH: f32 = 20.;
+
+/// The strength of the alarm raised when a member of the colony dies violently.
+const VIOLENT_DEATH_ALARM_STRENGTH: f32 = 500.;
+
+/// The perceived strength of the alarm above which units will guard their nest.
+const ALARM_THRESHOLD: f32 = 1.;
+
+/// Raises alarms, and makes units respond to them.
+pub(super) struct AlarmPlugin;
+
+impl Plugin for AlarmPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ViolentDeath>()
+            .init_resource::<ColonyAlertStatus>()
+            .add_system(
+                detect_intruders
+                    .in_schedule(SignalSchedule)
+                    .before(crate::signals::emit_signals),
+            )
+            .add_system(raise_alarm_on_violent_death.before(UnitSystem::ChooseGoal))
+            .add_system(
+                respond_to_alarm
+                    .in_set(UnitSystem::ChooseGoal)
+                    .after(super::goals::choose_goal),
+            )
+            .add_system(update_colony_alert_status.after(UnitSystem::ChooseGoal));
+    }
+}
+
+/// A unit was killed by something other than starvation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolentDeath {
+    /// The species of the unit that died
+    pub unit_id: Id<Unit>,
+    /// Where the unit died
+    pub tile_pos: TilePos,
+}
+
+/// Is the colony as a whole responding to an alarm?
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColonyAlertStatus {
+    /// No units are responding to an alarm.
+    #[default]
+    Calm,
+    /// Some units are guarding their nest or fighting intruders.
+    Alarmed {
+        /// The number of units that are guarding their nest
+        n_guarding: usize,
+        /// The number of soldiers that are fighting intruders
+        n_fighting: usize,
+    },
+}
+
+impl Display for ColonyAlertStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColonyAlertStatus::Calm => write!(f, "Calm"),
+            ColonyAlertStatus::Alarmed {
+                n_guarding,
+                n_fighting,
+            } => write!(f, "On alert ({n_guarding} guarding, {n_fighting} fighting)"),
+        }
+    }
+}
+
+/// Intruders that can be seen by a member of the colony raise the alarm where they stand.
+fn detect_intruders(
+    unit_query: Query<(&TilePos, &Id<Unit>, &VisionSource)>,
+    unit_manifest: Res<UnitManifest>,
+    mut signals: ResMut<Signals>,
+) {
+    let lookouts: Vec<(TilePos, u32)> = unit_query
+        .iter()
+        .filter(|(_, &unit_id, _)| unit_manifest.get(unit_id).nest().is_some())
+        .map(|(&tile_pos, _, vision_source)| (tile_pos, vision_source.radius))
+        .collect();
+
+    for (&intruder_pos, &unit_id, _) in unit_query.iter() {
+        if unit_manifest.get(unit_id).nest().is_some() {
+            continue;
+        }
+
+        let spotted = lookouts.iter().any(|&(lookout_pos, radius)| {
+            lookout_pos.hex.distance_to(intruder_pos.hex) as u32 <= radius
+        });
+
+        if spotted {
+            signals.add_signal(
+                SignalType::Alarm,
+                intruder_pos,
+                SignalStrength::new(INTRUDER_ALARM_STRENGTH),
+            );
+        }
+    }
+}
+
+/// Members of the colony that die violently raise a strong alarm where they fell.
+fn raise_alarm_on_violent_death(
+    mut violent_deaths: EventReader<ViolentDeath>,
+    unit_manifest: Res<UnitManifest>,
+    mut signals: ResMut<Signals>,
+) {
+    for violent_death in violent_deaths.iter() {
+        if unit_manifest.get(violent_death.unit_id).nest().is_some() {
+            signals.add_signal(
+                SignalType::Alarm,
+                violent_death.tile_pos,
+                SignalStrength::new(VIOLENT_DEATH_ALARM_STRENGTH),
+            );
+        }
+    }
+}
+
+/// Units that sense the alarm guard their nest until it fades away, while soldiers go and fight.
+///
+/// Hungry units keep looking for food: a starving guard is no use to anyone.
+pub(super) fn respond_to_alarm(
+    mut unit_query: Query<(&TilePos, &Id<Unit>, &mut Goal)>,
+    unit_manifest: Res<UnitManifest>,
+    signals: Res<Signals>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (&tile_pos, &unit_id, mut goal) in unit_query.iter_mut() {
+        let unit_data = unit_manifest.get(unit_id);
+        let nest = match unit_data.nest() {
+            Some(nest) => nest,
+            None => continue,
+        };
+
+        if let Goal::Eat(_) = *goal {
+            continue;
+        }
+
+        let alarm = unit_data.signal_sensitivity().perceive(
+            SignalType::Alarm,
+            signals.sensed_strength(
+                SignalType::Alarm,
+                tile_pos,
+                unit_data.sensing_radius(),
+                &map_geometry,
+            ),
+        );
+
+        let response = match unit_data.caste() {
+            Caste::Worker => Goal::Guard(nest),
+            Caste::Soldier { .. } => Goal::Fight,
+        };
+
+        if alarm.value() > ALARM_THRESHOLD {
+            goal.set_if_neq(response);
+        } else if *goal == response {
+            *goal = Goal::Wander;
+        }
+    }
+}
+
+/// Counts the units that are responding to the alarm, and raises an [`Alert`] when the colony becomes alarmed.
+fn update_colony_alert_status(
+    goal_query: Query<&Goal>,
+    mut status: ResMut<ColonyAlertStatus>,
+    mut alerts: EventWriter<Alert>,
+) {
+    let n_guarding = goal_query
+        .iter()
+        .filter(|goal| matches!(goal, Goal::Guard(_)))
+        .count();
+    let n_fighting = goal_query
+        .iter()
+        .filter(|goal| matches!(goal, Goal::Fight))
+        .count();
+
+    let new_status = if n_guarding + n_fighting == 0 {
+        ColonyAlertStatus::Calm
+    } else {
+        ColonyAlertStatus::Alarmed {
+            n_guarding,
+            n_fighting,
+        }
+    };
+
+    if *status == ColonyAlertStatus::Calm && new_status != ColonyAlertStatus::Calm {
+        alerts.send(Alert {
+            message: "The colony is on alert".to_string(),
+            tile_pos: None,
+        });
+    }
+
+    status.set_if_neq(new_status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::Structure;
+
+    /// An app with an empty map of signals, which runs `system` each update.
+    fn alarm_app<Params>(system: impl IntoSystemAppConfig<Params>) -> App {
+        let mut app = App::new();
+        app.init_resource::<Signals>()
+            .init_resource::<UnitManifest>()
+            .init_resource::<ColonyAlertStatus>()
+            .insert_resource(MapGeometry::new(10))
+            .add_event::<ViolentDeath>()
+            .add_event::<Alert>()
+            .add_system(system);
+
+        app
+    }
+
+    /// The strength of the alarm on the tile at `tile_pos`.
+    fn alarm_at(app: &App, tile_pos: TilePos) -> f32 {
+        app.world
+            .resource::<Signals>()
+            .get(SignalType::Alarm, tile_pos)
+            .value()
+    }
+
+    /// The nest that ants guard.
+    fn ant_nest() -> Id<Structure> {
+        UnitManifest::default().get(Id::ant()).nest().unwrap()
+    }
+
+    #[test]
+    fn intruders_in_sight_raise_the_alarm() {
+        let mut app = alarm_app(detect_intruders);
+        app.world
+            .spawn((TilePos::ORIGIN, Id::ant(), VisionSource::UNIT));
+        let radius = VisionSource::UNIT.radius as i32;
+        let spotted = TilePos::new(radius, 0);
+        let hidden = TilePos::new(-radius - 1, 0);
+        for tile_pos in [spotted, hidden] {
+            app.world
+                .spawn((tile_pos, Id::locust(), VisionSource::UNIT));
+        }
+        app.update();
+
+        assert_eq!(alarm_at(&app, spotted), INTRUDER_ALARM_STRENGTH);
+        assert_eq!(alarm_at(&app, hidden), 0.);
+        // Members of the colony are not intruders
+        assert_eq!(alarm_at(&app, TilePos::ORIGIN), 0.);
+    }
+
+    #[test]
+    fn only_violent_deaths_in_the_colony_raise_the_alarm() {
+        let mut app = alarm_app(raise_alarm_on_violent_death);
+        let ant_death = TilePos::new(1, 0);
+        let locust_death = TilePos::new(-1, 0);
+        app.world.send_event(ViolentDeath {
+            unit_id: Id::ant(),
+            tile_pos: ant_death,
+        });
+        app.world.send_event(ViolentDeath {
+            unit_id: Id::locust(),
+            tile_pos: locust_death,
+        });
+        app.update();
+
+        assert_eq!(alarm_at(&app, ant_death), VIOLENT_DEATH_ALARM_STRENGTH);
+        assert_eq!(alarm_at(&app, locust_death), 0.);
+    }
+
+    #[test]
+    fn units_respond_to_the_alarm_until_it_fades() {
+        let mut app = alarm_app(respond_to_alarm);
+        let worker = app
+            .world
+            .spawn((TilePos::ORIGIN, Id::ant(), Goal::Wander))
+            .id();
+        let soldier = app
+            .world
+            .spawn((TilePos::ORIGIN, Id::soldier_ant(), Goal::Wander))
+            .id();
+        let hungry = Goal::Eat(Id::from_string_id("acacia_leaf"));
+        let hungry_worker = app
+            .world
+            .spawn((TilePos::ORIGIN, Id::ant(), hungry.clone()))
+            .id();
+
+        app.world.resource_mut::<Signals>().add_signal(
+            SignalType::Alarm,
+            TilePos::ORIGIN,
+            SignalStrength::new(VIOLENT_DEATH_ALARM_STRENGTH),
+        );
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Goal>(worker),
+            Some(&Goal::Guard(ant_nest()))
+        );
+        assert_eq!(app.world.get::<Goal>(soldier), Some(&Goal::Fight));
+        assert_eq!(app.world.get::<Goal>(hungry_worker), Some(&hungry));
+
+        app.insert_resource(Signals::default());
+        app.update();
+
+        assert_eq!(app.world.get::<Goal>(worker), Some(&Goal::Wander));
+        assert_eq!(app.world.get::<Goal>(soldier), Some(&Goal::Wander));
+    }
+
+    #[test]
+    fn the_alarm_spreads_to_nearby_units() {
+        let mut app = alarm_app(respond_to_alarm);
+        let sensing_radius = UnitManifest::default().get(Id::ant()).sensing_radius() as i32;
+        let nearby = app
+            .world
+            .spawn((TilePos::new(sensing_radius, 0), Id::ant(), Goal::Wander))
+            .id();
+        let distant = app
+            .world
+            .spawn((TilePos::new(sensing_radius + 1, 0), Id::ant(), Goal::Wander))
+            .id();
+
+        app.world.resource_mut::<Signals>().add_signal(
+            SignalType::Alarm,
+            TilePos::ORIGIN,
+            SignalStrength::new(VIOLENT_DEATH_ALARM_STRENGTH),
+        );
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Goal>(nearby),
+            Some(&Goal::Guard(ant_nest()))
+        );
+        assert_eq!(app.world.get::<Goal>(distant), Some(&Goal::Wander));
+    }
+
+    #[test]
+    fn the_colony_is_alerted_once_until_it_calms_down() {
+        let mut app = alarm_app(update_colony_alert_status);
+        let guard = app.world.spawn(Goal::Guard(ant_nest())).id();
+        app.world.spawn(Goal::Fight);
+        app.world.spawn(Goal::Wander);
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<ColonyAlertStatus>(),
+            ColonyAlertStatus::Alarmed {
+                n_guarding: 1,
+                n_fighting: 1,
+            }
+        );
+        assert_eq!(app.world.resource::<Events<Alert>>().len(), 1);
+
+        // Staying alarmed does not raise another alert
+        app.world.resource_mut::<Events<Alert>>().clear();
+        *app.world.get_mut::<Goal>(guard).unwrap() = Goal::Wander;
+        app.update();
+        assert!(app.world.resource::<Events<Alert>>().is_empty());
+
+        app.world
+            .query::<&mut Goal>()
+            .for_each_mut(&mut app.world, |mut goal| *goal = Goal::Wander);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<ColonyAlertStatus>(),
+            ColonyAlertStatus::Calm
+        );
+    }
+}
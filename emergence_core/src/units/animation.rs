@@ -0,0 +1,108 @@
+//! Units are animated according to what they are doing.
+//!
+//! Which clip is played in each [`AnimationState`] is set per species in `units.ron`,
+//! and stored in its [`UnitVariety`](super::varieties::UnitVariety).
+//! Species without artwork for a state fall back to their idle clip.
+
+use crate::bevy::utils::HashMap;
+use serde::Deserialize;
+
+use super::{
+    actions::{CurrentAction, UnitAction},
+    item_interaction::UnitInventory,
+};
+
+/// What a unit looks like it is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum AnimationState {
+    /// Standing around.
+    Idle,
+    /// Moving or turning empty-handed.
+    Walking,
+    /// Moving or turning while holding an item.
+    Carrying,
+    /// Interacting with a structure, an item or another unit.
+    Working,
+    /// Unable to act, such as while caught in a trap.
+    Sleeping,
+}
+
+impl AnimationState {
+    /// The state of a unit performing `current_action` while holding the contents of `unit_inventory`.
+    ///
+    /// Units that are `captured` are always [`AnimationState::Sleeping`].
+    pub fn new(
+        current_action: &CurrentAction,
+        unit_inventory: &UnitInventory,
+        captured: bool,
+    ) -> Self {
+        if captured {
+            return AnimationState::Sleeping;
+        }
+
+        match current_action.action() {
+            UnitAction::Idle | UnitAction::Abandon => AnimationState::Idle,
+            UnitAction::MoveForward
+            | UnitAction::TakeExpressRoute { .. }
+            | UnitAction::Spin { .. } => match unit_inventory.held_item {
+                Some(_) => AnimationState::Carrying,
+                None => AnimationState::Walking,
+            },
+            UnitAction::PickUp { .. }
+            | UnitAction::DropOff { .. }
+            | UnitAction::Work { .. }
+            | UnitAction::Demolish { .. }
+            | UnitAction::Eat
+            | UnitAction::Attack { .. } => AnimationState::Working,
+        }
+    }
+}
+
+/// The animation clip played in each [`AnimationState`], as the index of the clip in the unit's model.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitAnimations {
+    /// The clip for each state that has its own artwork
+    clips: HashMap<AnimationState, usize>,
+}
+
+impl UnitAnimations {
+    /// Plays the clip at `index` in the provided `state`.
+    pub fn with_clip(mut self, state: AnimationState, index: usize) -> Self {
+        self.clips.insert(state, index);
+        self
+    }
+
+    /// The clip to play in `state`.
+    ///
+    /// States without their own clip use the idle clip instead.
+    /// Returns `None` if the unit has no animations at all.
+    pub fn clip(&self, state: AnimationState) -> Option<usize> {
+        self.clips
+            .get(&state)
+            .or_else(|| self.clips.get(&AnimationState::Idle))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_clips_fall_back_to_idle() {
+        let animations = UnitAnimations::default()
+            .with_clip(AnimationState::Idle, 0)
+            .with_clip(AnimationState::Walking, 1);
+
+        assert_eq!(animations.clip(AnimationState::Walking), Some(1));
+        assert_eq!(animations.clip(AnimationState::Sleeping), Some(0));
+    }
+
+    #[test]
+    fn units_without_animations_play_nothing() {
+        let animations = UnitAnimations::default();
+
+        assert_eq!(animations.clip(AnimationState::Idle), None);
+        assert_eq!(animations.clip(AnimationState::Working), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! Only so many units fit on a single tile.
+//!
+//! Units won't step onto a tile that is already full, so queues form naturally at bottlenecks.
+//! Idle units standing in a crowd are also gently pushed apart, drifting towards emptier tiles nearby.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use rand::Rng;
+
+use crate::{
+    manifest::{Id, Unit},
+    simulation::geometry::{Facing, MapGeometry, TilePos},
+    terrain::Terrain,
+};
+
+use super::{
+    actions::{CurrentAction, UnitAction},
+    UnitSystem,
+};
+
+/// The maximum number of units that can stand on a single tile.
+pub const MAX_UNITS_PER_TILE: usize = 4;
+
+/// The number of units that can share a tile before they start to feel crowded.
+const COMFORTABLE_OCCUPANCY: usize = 1;
+
+/// The chance that an idle unit in a crowd steps away from it, each time it chooses an action.
+const CROWD_PRESSURE: f64 = 0.2;
+
+/// Tracks how many units are standing on each tile.
+pub(super) struct CrowdingPlugin;
+
+impl Plugin for CrowdingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileOccupancy>()
+            .add_system(count_occupants.before(UnitSystem::Act));
+    }
+}
+
+/// The number of units standing on each tile.
+#[derive(Resource, Debug, Default)]
+pub struct TileOccupancy {
+    /// The number of units on each occupied tile
+    counts: HashMap<TilePos, usize>,
+}
+
+impl TileOccupancy {
+    /// The number of units standing on `tile_pos`.
+    pub fn get(&self, tile_pos: TilePos) -> usize {
+        self.counts.get(&tile_pos).copied().unwrap_or_default()
+    }
+
+    /// Is there room for no more units on `tile_pos`?
+    pub fn is_full(&self, tile_pos: TilePos) -> bool {
+        self.get(tile_pos) >= MAX_UNITS_PER_TILE
+    }
+
+    /// Records a new unit appearing on `tile_pos`, such as one that was just born.
+    pub(super) fn add_unit(&mut self, tile_pos: TilePos) {
+        *self.counts.entry(tile_pos).or_default() += 1;
+    }
+
+    /// Records a unit walking from `origin` to `target`.
+    pub(super) fn move_unit(&mut self, origin: TilePos, target: TilePos) {
+        if let Some(count) = self.counts.get_mut(&origin) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.counts.remove(&origin);
+            }
+        }
+
+        *self.counts.entry(target).or_default() += 1;
+    }
+
+    /// The adjacent tile that a unit on `tile_pos` could walk to with the fewest units on it.
+    ///
+    /// Ties are broken in favor of the first tile found.
+    fn least_crowded_neighbor(
+        &self,
+        tile_pos: TilePos,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        tile_pos
+            .reachable_neighbors(map_geometry)
+            .into_iter()
+            .min_by_key(|&neighbor| self.get(neighbor))
+    }
+}
+
+/// Counts the units standing on each tile.
+fn count_occupants(
+    unit_query: Query<&TilePos, With<Id<Unit>>>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+) {
+    tile_occupancy.counts.clear();
+
+    for &tile_pos in unit_query.iter() {
+        *tile_occupancy.counts.entry(tile_pos).or_default() += 1;
+    }
+}
+
+/// Adjusts the `action` chosen by a unit on `unit_tile_pos` to account for the crowds around it.
+///
+/// Units wait rather than stepping onto a full tile,
+/// and idle units in a crowd sometimes step to a less crowded tile nearby.
+pub(super) fn avoid_crowds(
+    action: CurrentAction,
+    unit_tile_pos: TilePos,
+    facing: &Facing,
+    tile_occupancy: &TileOccupancy,
+    terrain_query: &Query<&Terrain>,
+    map_geometry: &MapGeometry,
+    rng: &mut impl Rng,
+) -> CurrentAction {
+    match action.action() {
+        UnitAction::MoveForward => {
+            let target_tile = unit_tile_pos.neighbor(facing.direction);
+            if tile_occupancy.is_full(target_tile) {
+                CurrentAction::idle()
+            } else {
+                action
+            }
+        }
+        UnitAction::Idle => {
+            let n_here = tile_occupancy.get(unit_tile_pos);
+            if n_here <= COMFORTABLE_OCCUPANCY || !rng.gen_bool(CROWD_PRESSURE) {
+                return action;
+            }
+
+            match tile_occupancy.least_crowded_neighbor(unit_tile_pos, map_geometry) {
+                // Only step away if doing so would leave both tiles less crowded
+                Some(neighbor) if tile_occupancy.get(neighbor) + 1 < n_here => {
+                    CurrentAction::move_or_spin(
+                        unit_tile_pos,
+                        neighbor,
+                        facing,
+                        terrain_query,
+                        map_geometry,
+                    )
+                }
+                _ => action,
+            }
+        }
+        _ => action,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moving_units_are_counted() {
+        let mut tile_occupancy = TileOccupancy::default();
+        tile_occupancy.counts.insert(TilePos::ORIGIN, 2);
+
+        tile_occupancy.move_unit(TilePos::ORIGIN, TilePos::new(1, 0));
+        tile_occupancy.move_unit(TilePos::ORIGIN, TilePos::new(1, 0));
+
+        assert_eq!(tile_occupancy.get(TilePos::ORIGIN), 0);
+        assert_eq!(tile_occupancy.get(TilePos::new(1, 0)), 2);
+        assert!(!tile_occupancy.counts.contains_key(&TilePos::ORIGIN));
+    }
+
+    #[test]
+    fn tiles_fill_up() {
+        let mut tile_occupancy = TileOccupancy::default();
+
+        for _ in 0..MAX_UNITS_PER_TILE {
+            assert!(!tile_occupancy.is_full(TilePos::ORIGIN));
+            tile_occupancy.move_unit(TilePos::new(1, 0), TilePos::ORIGIN);
+        }
+
+        assert!(tile_occupancy.is_full(TilePos::ORIGIN));
+    }
+
+    #[test]
+    fn crowds_spread_to_empty_tiles() {
+        let map_geometry = MapGeometry::new(3);
+        let mut tile_occupancy = TileOccupancy::default();
+        tile_occupancy.counts.insert(TilePos::ORIGIN, 3);
+        for neighbor in TilePos::ORIGIN.all_neighbors(&map_geometry) {
+            tile_occupancy.counts.insert(neighbor, 1);
+        }
+        let empty = TilePos::new(0, 1);
+        tile_occupancy.counts.remove(&empty);
+
+        assert_eq!(
+            tile_occupancy.least_crowded_neighbor(TilePos::ORIGIN, &map_geometry),
+            Some(empty)
+        );
+    }
+}
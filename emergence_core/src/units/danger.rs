@@ -0,0 +1,101 @@
+//! Danger is given off by hostile creatures and hazards, and is avoided by members of the colony.
+//!
+//! Unlike the alarm, which rallies the colony to defend its nest, danger simply pushes units away.
+//! Units that sense enough [`SignalType::Danger`] switch to [`Goal::Avoid`], and walk down its gradient until it fades.
+
+use crate::bevy::prelude::*;
+
+use crate::{
+    manifest::{Id, Unit, UnitManifest},
+    signals::{SignalSchedule, SignalStrength, SignalType, Signals},
+    simulation::{fire::OnFire, geometry::TilePos},
+};
+
+use super::{goals::Goal, soldiers::Caste, UnitSystem};
+
+/// The strength of the danger given off each tick by each hostile creature.
+const HOSTILE_DANGER_STRENGTH: f32 = 10.;
+
+/// The strength of the danger given off each tick by each burning structure.
+const FIRE_DANGER_STRENGTH: f32 = 30.;
+
+/// The perceived strength of danger above which units will flee.
+const DANGER_THRESHOLD: f32 = 1.;
+
+/// Emits danger, and makes units avoid it.
+pub(super) struct DangerPlugin;
+
+impl Plugin for DangerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            emit_danger
+                .in_schedule(SignalSchedule)
+                .before(crate::signals::emit_signals),
+        )
+        .add_system(
+            avoid_danger
+                .in_set(UnitSystem::ChooseGoal)
+                .after(super::alarm::respond_to_alarm),
+        );
+    }
+}
+
+/// Hostile creatures and burning structures give off danger where they are.
+fn emit_danger(
+    unit_query: Query<(&TilePos, &Id<Unit>)>,
+    fire_query: Query<&TilePos, With<OnFire>>,
+    unit_manifest: Res<UnitManifest>,
+    mut signals: ResMut<Signals>,
+) {
+    for (&tile_pos, &unit_id) in unit_query.iter() {
+        // Creatures without a nest are not part of the colony
+        if unit_manifest.get(unit_id).nest().is_none() {
+            signals.add_signal(
+                SignalType::Danger,
+                tile_pos,
+                SignalStrength::new(HOSTILE_DANGER_STRENGTH),
+            );
+        }
+    }
+
+    for &tile_pos in fire_query.iter() {
+        signals.add_signal(
+            SignalType::Danger,
+            tile_pos,
+            SignalStrength::new(FIRE_DANGER_STRENGTH),
+        );
+    }
+}
+
+/// Members of the colony that sense danger flee from it, until it fades away.
+///
+/// Soldiers stand their ground, while units that are eating or responding to an alarm carry on.
+fn avoid_danger(
+    mut unit_query: Query<(&TilePos, &Id<Unit>, &mut Goal)>,
+    unit_manifest: Res<UnitManifest>,
+    signals: Res<Signals>,
+) {
+    for (&tile_pos, &unit_id, mut goal) in unit_query.iter_mut() {
+        let unit_data = unit_manifest.get(unit_id);
+        if unit_data.nest().is_none() || matches!(unit_data.caste(), Caste::Soldier { .. }) {
+            continue;
+        }
+
+        if matches!(*goal, Goal::Eat(_) | Goal::Guard(_) | Goal::Fight) {
+            continue;
+        }
+
+        let danger = unit_data.signal_sensitivity().perceive(
+            SignalType::Danger,
+            signals.get(SignalType::Danger, tile_pos),
+        );
+
+        if danger.value() > DANGER_THRESHOLD {
+            if *goal != Goal::Avoid {
+                *goal = Goal::Avoid;
+            }
+        } else if *goal == Goal::Avoid {
+            *goal = Goal::Wander;
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Units can fall sick, which drains their energy until they recover.
+//!
+//! Sickness is caught by units near piles of [`Remains`](super::lifecycle::Remains) that have been left lying around,
+//! and spreads from sick units to the units on neighboring tiles.
+//! Carrying corpses away promptly keeps the colony healthy.
+
+use crate::bevy::{prelude::*, utils::HashSet};
+use rand::Rng;
+
+use crate::{
+    manifest::{Id, Unit},
+    organisms::energy::{Energy, EnergyPool},
+    simulation::{
+        alerts::Alert,
+        geometry::{MapGeometry, TilePos},
+        jitter::{Jitter, JitterStream},
+        time::DAY_LENGTH_IN_SECONDS,
+    },
+};
+
+/// The chance per in-game day that a unit near a pile of remains falls sick.
+const CATCH_CHANCE_PER_DAY: f64 = 0.5;
+
+/// The chance per in-game day that a sick unit passes its sickness on to each unit near it.
+const SPREAD_CHANCE_PER_DAY: f64 = 0.3;
+
+/// The number of in-game days that a unit stays sick for.
+const SICKNESS_DURATION_IN_DAYS: f32 = 2.;
+
+/// The energy drained from sick units per second, on top of their usual needs.
+const SICKNESS_DRAIN_PER_SECOND: Energy = Energy(1.);
+
+/// Spreads sickness between units, and lets sick units recover.
+pub(super) struct DiseasePlugin;
+
+impl Plugin for DiseasePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(catch_sickness)
+            .add_system(suffer_sickness.after(catch_sickness));
+    }
+}
+
+/// A unit that is sick, and is draining energy until it recovers.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Sickness {
+    /// The number of in-game days until this unit recovers, including partial days
+    days_remaining: f32,
+}
+
+impl Default for Sickness {
+    fn default() -> Self {
+        Sickness {
+            days_remaining: SICKNESS_DURATION_IN_DAYS,
+        }
+    }
+}
+
+impl Sickness {
+    /// The number of in-game days until this unit recovers, including partial days.
+    pub fn days_remaining(&self) -> f32 {
+        self.days_remaining
+    }
+}
+
+/// Is `tile_pos` or any of its neighbors in `tiles`?
+fn is_near(tile_pos: TilePos, tiles: &HashSet<TilePos>, map_geometry: &MapGeometry) -> bool {
+    tiles.contains(&tile_pos)
+        || tile_pos
+            .all_neighbors(map_geometry)
+            .into_iter()
+            .any(|neighbor| tiles.contains(&neighbor))
+}
+
+/// Makes healthy units sick, if they are near remains or other sick units.
+fn catch_sickness(
+    time: Res<Time>,
+    healthy_query: Query<(Entity, &TilePos, &Id<Unit>), Without<Sickness>>,
+    sick_query: Query<&TilePos, With<Sickness>>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+    mut alerts: EventWriter<Alert>,
+    mut commands: Commands,
+) {
+    let days = (time.delta_seconds() / DAY_LENGTH_IN_SECONDS) as f64;
+    let catch_chance = (CATCH_CHANCE_PER_DAY * days).min(1.);
+    let spread_chance = (SPREAD_CHANCE_PER_DAY * days).min(1.);
+
+    let remains_tiles: HashSet<TilePos> = map_geometry.remains_index.keys().copied().collect();
+    let sick_tiles: HashSet<TilePos> = sick_query.iter().copied().collect();
+
+    for (entity, &tile_pos, unit_id) in healthy_query.iter() {
+        let rng = &mut jitter.rng(entity, JitterStream::Disease);
+
+        if is_near(tile_pos, &remains_tiles, &map_geometry) && rng.gen_bool(catch_chance) {
+            commands.entity(entity).insert(Sickness::default());
+            alerts.send(Alert {
+                message: format!("A {unit_id} fell sick from uncollected corpses"),
+                tile_pos: Some(tile_pos),
+            });
+        } else if is_near(tile_pos, &sick_tiles, &map_geometry) && rng.gen_bool(spread_chance) {
+            commands.entity(entity).insert(Sickness::default());
+        }
+    }
+}
+
+/// Drains the energy of sick units, until they recover.
+fn suffer_sickness(
+    time: Res<Time>,
+    mut sick_query: Query<(Entity, &mut Sickness, &mut EnergyPool)>,
+    mut commands: Commands,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut sickness, mut energy_pool) in sick_query.iter_mut() {
+        let proposed = energy_pool.current() - SICKNESS_DRAIN_PER_SECOND * delta;
+        energy_pool.set_current(proposed);
+
+        sickness.days_remaining -= delta / DAY_LENGTH_IN_SECONDS;
+        if sickness.days_remaining <= 0. {
+            commands.entity(entity).remove::<Sickness>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bevy::utils::{Duration, Instant};
+
+    use super::*;
+    use crate::simulation::{generation::GenerationConfig, time::SimulationTick};
+
+    /// An app that spreads sickness, where every frame lasts for `frame_days` in-game days.
+    fn disease_app(frame_days: f32) -> App {
+        let mut app = App::new();
+
+        // The clock is never advanced, so every frame lasts for the same time
+        let mut time = Time::default();
+        let start = Instant::now();
+        time.update_with_instant(start);
+        time.update_with_instant(
+            start + Duration::from_secs_f32(frame_days * DAY_LENGTH_IN_SECONDS),
+        );
+
+        app.insert_resource(time)
+            .insert_resource(MapGeometry::new(5))
+            .init_resource::<SimulationTick>()
+            .init_resource::<GenerationConfig>()
+            .add_event::<Alert>()
+            .add_plugin(DiseasePlugin);
+
+        app
+    }
+
+    /// Spawns an ant at `tile_pos`, with plenty of energy.
+    fn spawn_ant(app: &mut App, tile_pos: TilePos) -> Entity {
+        app.world
+            .spawn((
+                tile_pos,
+                Id::<Unit>::from_string_id("ant"),
+                EnergyPool::new_full(Energy(1000.), Energy(0.)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn units_near_remains_fall_sick() {
+        // Long enough that the sickness is certain to be caught
+        let mut app = disease_app(2.);
+        let remains = app.world.spawn(TilePos::ORIGIN).id();
+        app.world
+            .resource_mut::<MapGeometry>()
+            .remains_index
+            .insert(TilePos::ORIGIN, remains);
+        let near = spawn_ant(&mut app, TilePos::new(1, 0));
+        let far = spawn_ant(&mut app, TilePos::new(3, 0));
+
+        app.update();
+
+        assert!(app.world.get::<Sickness>(near).is_some());
+        assert!(app.world.get::<Sickness>(far).is_none());
+    }
+
+    #[test]
+    fn sickness_spreads_to_neighbors() {
+        // Long enough that the sickness is certain to spread
+        let mut app = disease_app(4.);
+        let sick = spawn_ant(&mut app, TilePos::ORIGIN);
+        app.world.entity_mut(sick).insert(Sickness {
+            days_remaining: 10.,
+        });
+        let neighbor = spawn_ant(&mut app, TilePos::new(1, 0));
+        let far = spawn_ant(&mut app, TilePos::new(3, 0));
+
+        app.update();
+
+        assert!(app.world.get::<Sickness>(neighbor).is_some());
+        assert!(app.world.get::<Sickness>(far).is_none());
+        assert!(app.world.get::<Sickness>(sick).is_some());
+    }
+
+    #[test]
+    fn sick_units_lose_energy_until_they_recover() {
+        let mut app = disease_app(SICKNESS_DURATION_IN_DAYS);
+        let ant = spawn_ant(&mut app, TilePos::ORIGIN);
+        app.world.entity_mut(ant).insert(Sickness::default());
+
+        app.update();
+
+        assert!(app.world.get::<EnergyPool>(ant).unwrap().current() < Energy(1000.));
+        assert!(app.world.get::<Sickness>(ant).is_none());
+    }
+}
@@ -0,0 +1,366 @@
+//! What are units attempting to achieve?
+
+use crate::bevy::prelude::*;
+use core::fmt::Display;
+use rand::Rng;
+
+use crate::manifest::{Id, Item, Structure, Unit, UnitManifest};
+use crate::signals::{SignalType, Signals};
+use crate::simulation::geometry::{MapGeometry, TilePos};
+use crate::simulation::jitter::{Jitter, JitterStream};
+
+use crate::organisms::energy::EnergyPool;
+
+use super::hunger::Diet;
+use super::impatience::ImpatiencePool;
+use super::item_interaction::UnitInventory;
+
+/// A unit's current goals.
+///
+/// Units will stick with any task other than [`Goal::Wander`] until it is complete (or overridden),
+/// unless another task becomes clearly more valuable to them (see [`choose_goal`]).
+/// Once a goal is complete, they will typically transition back into [`Goal::Wander`] and attempt to find something new to do.
+///
+/// This component serves as a state machine.
+#[derive(Component, PartialEq, Eq, Clone, Default, Debug)]
+pub enum Goal {
+    /// Attempting to find something useful to do
+    ///
+    /// Units will try and follow a signal, if they can pick up a trail, but will not fixate on it until the signal is strong enough.
+    #[default]
+    Wander,
+    /// Attempting to pick up an object
+    #[allow(dead_code)]
+    Pickup(Id<Item>),
+    /// Attempting to drop off an object
+    #[allow(dead_code)]
+    DropOff(Id<Item>),
+    /// Attempting to perform work at a structure
+    #[allow(dead_code)]
+    Work(Id<Structure>),
+    /// Attempt to feed self
+    Eat(Id<Item>),
+    /// Attempting to destroy a structure
+    Demolish(Id<Structure>),
+    /// Returning to the nearest structure of this type and standing guard, in response to an alarm
+    Guard(Id<Structure>),
+    /// Walking a beat around the provided tile, ready to respond to trouble
+    Patrol(TilePos),
+    /// Following the alarm to its source and attacking any intruders found there
+    Fight,
+    /// Fleeing down the gradient of danger, away from threats and hazards
+    Avoid,
+}
+
+impl TryFrom<SignalType> for Goal {
+    // This conversion fails for signals that are not used to pick goals.
+    type Error = ();
+
+    fn try_from(value: SignalType) -> Result<Goal, Self::Error> {
+        match value {
+            // Go grab the item, so you can later take it away
+            SignalType::Push(item_id) => Ok(Goal::Pickup(item_id)),
+            // Go grab the item, so you can bring it to me
+            SignalType::Pull(item_id) => Ok(Goal::Pickup(item_id)),
+            SignalType::Contains(_) => Err(()),
+            SignalType::Work(structure_id) => Ok(Goal::Work(structure_id)),
+            SignalType::Demolish(structure_id) => Ok(Goal::Demolish(structure_id)),
+            SignalType::Alarm => Err(()),
+            SignalType::Danger => Err(()),
+            SignalType::Custom(_) => Err(()),
+        }
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string: String = match self {
+            Goal::Wander => "Wander".to_string(),
+            Goal::Pickup(item) => format!("Pickup {item}"),
+            Goal::DropOff(item) => format!("Dropoff {item}"),
+            Goal::Work(structure) => format!("Work at {structure}"),
+            Goal::Demolish(structure) => format!("Demolish {structure}"),
+            Goal::Guard(structure) => format!("Guard {structure}"),
+            Goal::Patrol(tile_pos) => format!("Patrol around {tile_pos}"),
+            Goal::Fight => "Fight intruders".to_string(),
+            Goal::Avoid => "Avoid danger".to_string(),
+            Goal::Eat(item) => format!("Eat {item}"),
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The fraction by which another goal must outscore a unit's current goal before the unit switches to it.
+///
+/// This prevents units from thrashing between goals of similar value.
+const HYSTERESIS_MARGIN: f32 = 0.5;
+
+/// The largest random boost given to the utility of each goal, so that neighboring units spread out over different tasks.
+///
+/// This must be smaller than the [`HYSTERESIS_MARGIN`], or units would switch goals on noise alone.
+const UTILITY_NOISE: f32 = 0.2;
+
+/// How much more a unit values delivering the item it is carrying than it values other tasks.
+const CARRIED_ITEM_BONUS: f32 = 2.;
+
+/// The internal state of a unit that affects how much it values each goal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Needs {
+    /// The fraction of its maximum energy that the unit has left, between 0 and 1
+    energy_fraction: f32,
+    /// The item the unit is carrying, if any
+    held_item: Option<Id<Item>>,
+    /// The item that the unit eats
+    food: Id<Item>,
+}
+
+impl Needs {
+    /// How hungry the unit is, from 0 when its energy is full to 1 when it is empty.
+    fn hunger(&self) -> f32 {
+        1. - self.energy_fraction
+    }
+
+    /// The goal that a unit with these needs pursues in response to `signal_type`, if any.
+    fn goal_for(&self, signal_type: SignalType) -> Option<Goal> {
+        match signal_type {
+            SignalType::Pull(item_id) if self.held_item == Some(item_id) => {
+                Some(Goal::DropOff(item_id))
+            }
+            _ => signal_type.try_into().ok(),
+        }
+    }
+
+    /// How strongly a unit with these needs is drawn to `goal`, relative to the strength of the signals that suggest it.
+    fn multiplier(&self, goal: &Goal) -> f32 {
+        match (goal, self.held_item) {
+            (Goal::DropOff(item_id), Some(held_item_id)) if *item_id == held_item_id => {
+                CARRIED_ITEM_BONUS
+            }
+            (Goal::Pickup(item_id), Some(held_item_id)) if *item_id == held_item_id => 1.,
+            // Units with full hands can only deliver what they are carrying
+            (_, Some(_)) | (Goal::DropOff(_), None) => 0.,
+            (Goal::Pickup(item_id), None) if *item_id == self.food => 1. + self.hunger(),
+            // Tired units put off hard work
+            (Goal::Work(_) | Goal::Demolish(_), None) => self.energy_fraction,
+            _ => 1.,
+        }
+    }
+
+    /// The total utility of each goal suggested by the perceived strength of the `signals`.
+    fn score_goals(
+        &self,
+        signals: impl IntoIterator<Item = (SignalType, f32)>,
+    ) -> Vec<(Goal, f32)> {
+        let mut scores: Vec<(Goal, f32)> = Vec::new();
+
+        for (signal_type, strength) in signals {
+            let goal = match self.goal_for(signal_type) {
+                Some(goal) => goal,
+                None => continue,
+            };
+            let utility = strength * self.multiplier(&goal);
+
+            match scores
+                .iter_mut()
+                .find(|(scored_goal, _)| *scored_goal == goal)
+            {
+                Some((_, total)) => *total += utility,
+                None => scores.push((goal, utility)),
+            }
+        }
+
+        scores
+    }
+}
+
+/// Can this goal be replaced by the utility arbiter?
+///
+/// Other goals are set and cleared by the systems responsible for them, such as hunger and alarms.
+fn is_arbitrated(goal: &Goal) -> bool {
+    matches!(
+        goal,
+        Goal::Wander | Goal::Pickup(_) | Goal::DropOff(_) | Goal::Work(_) | Goal::Demolish(_)
+    )
+}
+
+/// The goal that a unit pursuing `current_goal` should switch to, given the utility of each available goal.
+///
+/// Returns `None` if the unit should stick with its current goal.
+fn arbitrate(current_goal: &Goal, scores: &[(Goal, f32)]) -> Option<Goal> {
+    let current_utility = scores
+        .iter()
+        .find(|(goal, _)| goal == current_goal)
+        .map(|&(_, utility)| utility)
+        .unwrap_or_default();
+
+    let (best_goal, best_utility) = scores.iter().max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+    if *best_utility > 0.
+        && *best_utility > current_utility * (1. + HYSTERESIS_MARGIN)
+        && best_goal != current_goal
+    {
+        Some(best_goal.clone())
+    } else {
+        None
+    }
+}
+
+/// Choose this unit's new goal if needed
+///
+/// Every goal suggested by the signals around the unit is scored against its needs,
+/// and the unit switches to the goal with the highest utility if it is clearly better than its current goal.
+/// Signals are weighted by the [`SignalSensitivity`](crate::signals::SignalSensitivity) of each unit's species,
+/// and sensed from as far away as its sensing radius allows.
+pub(super) fn choose_goal(
+    mut units_query: Query<(
+        Entity,
+        &TilePos,
+        &Id<Unit>,
+        &mut Goal,
+        &mut ImpatiencePool,
+        &UnitInventory,
+        &EnergyPool,
+        &Diet,
+    )>,
+    signals: Res<Signals>,
+    unit_manifest: Res<UnitManifest>,
+    map_geometry: Res<MapGeometry>,
+    jitter: Jitter,
+) {
+    for (
+        entity,
+        &tile_pos,
+        &unit_id,
+        mut goal,
+        mut impatience_pool,
+        unit_inventory,
+        energy_pool,
+        diet,
+    ) in units_query.iter_mut()
+    {
+        // If we're out of patience, give up and choose a new goal
+        if impatience_pool.is_full() {
+            *goal = Goal::Wander;
+        }
+
+        if !is_arbitrated(&goal) {
+            continue;
+        }
+
+        let unit_data = unit_manifest.get(unit_id);
+        let current_signals =
+            signals.signals_in_radius(tile_pos, unit_data.sensing_radius(), &map_geometry);
+        let sensitivity = unit_data.signal_sensitivity();
+        let rng = &mut jitter.rng(entity, JitterStream::Goals);
+
+        let needs = Needs {
+            energy_fraction: match energy_pool.max().0 {
+                max if max > 0. => (energy_pool.current().0 / max).clamp(0., 1.),
+                _ => 0.,
+            },
+            held_item: unit_inventory.held_item,
+            food: diet.item(),
+        };
+        let scores = needs.score_goals(current_signals.goal_relevant_signals().map(
+            |(&signal_type, &strength)| {
+                let noise = 1. + UTILITY_NOISE * rng.gen::<f32>();
+                (
+                    signal_type,
+                    sensitivity.perceive(signal_type, strength).value() * noise,
+                )
+            },
+        ));
+
+        if let Some(new_goal) = arbitrate(&goal, &scores) {
+            *goal = new_goal;
+            // Reset impatience when we choose a new goal
+            impatience_pool.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The item carried and eaten in these tests
+    const FOOD: Id<Item> = Id::new(1);
+    /// An item that cannot be eaten
+    const STONE: Id<Item> = Id::new(2);
+
+    /// The needs of a well-fed unit that isn't carrying anything.
+    fn rested() -> Needs {
+        Needs {
+            energy_fraction: 1.,
+            held_item: None,
+            food: FOOD,
+        }
+    }
+
+    #[test]
+    fn carrying_units_prefer_delivering_their_item() {
+        let needs = Needs {
+            held_item: Some(STONE),
+            ..rested()
+        };
+        let structure_id = Id::from_string_id("acacia");
+
+        let scores = needs.score_goals([
+            (SignalType::Pull(STONE), 1.),
+            (SignalType::Work(structure_id), 1.5),
+            (SignalType::Push(FOOD), 1.5),
+        ]);
+
+        assert_eq!(
+            arbitrate(&Goal::Wander, &scores),
+            Some(Goal::DropOff(STONE))
+        );
+    }
+
+    #[test]
+    fn tired_units_put_off_hard_work() {
+        let structure_id = Id::from_string_id("acacia");
+        let signals = [
+            (SignalType::Work(structure_id), 1.),
+            (SignalType::Push(STONE), 0.8),
+        ];
+
+        let rested_scores = rested().score_goals(signals);
+        assert_eq!(
+            arbitrate(&Goal::Wander, &rested_scores),
+            Some(Goal::Work(structure_id))
+        );
+
+        let tired = Needs {
+            energy_fraction: 0.3,
+            ..rested()
+        };
+        let tired_scores = tired.score_goals(signals);
+        assert_eq!(
+            arbitrate(&Goal::Wander, &tired_scores),
+            Some(Goal::Pickup(STONE))
+        );
+    }
+
+    #[test]
+    fn units_only_switch_to_clearly_better_goals() {
+        let current_goal = Goal::Pickup(STONE);
+
+        let close_scores = vec![(Goal::Pickup(STONE), 1.), (Goal::Pickup(FOOD), 1.3)];
+        assert_eq!(arbitrate(&current_goal, &close_scores), None);
+
+        let clear_scores = vec![(Goal::Pickup(STONE), 1.), (Goal::Pickup(FOOD), 1.6)];
+        assert_eq!(
+            arbitrate(&current_goal, &clear_scores),
+            Some(Goal::Pickup(FOOD))
+        );
+
+        // Goals that are no longer suggested by any signal are abandoned for anything better than nothing
+        let faded_scores = vec![(Goal::Pickup(FOOD), 0.1)];
+        assert_eq!(
+            arbitrate(&current_goal, &faded_scores),
+            Some(Goal::Pickup(FOOD))
+        );
+    }
+}
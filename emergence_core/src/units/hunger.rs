@@ -1,10 +1,10 @@
 //! Logic for finding and eating food when the [`EnergyPool`] is low.
 
-use bevy::prelude::*;
+use crate::bevy::prelude::*;
 use core::fmt::Display;
 
 use crate::{
-    asset_management::manifest::{Id, Item},
+    manifest::{Id, Item},
     organisms::energy::{Energy, EnergyPool},
 };
 
@@ -12,7 +12,7 @@ use super::goals::Goal;
 
 /// The item(s) that a unit must consume to gain [`Energy`].
 #[derive(Component, Clone, Debug)]
-pub(crate) struct Diet {
+pub struct Diet {
     /// The item that must be eaten
     item: Id<Item>,
     /// The amount of energy restored per item destroyed
@@ -21,17 +21,17 @@ pub(crate) struct Diet {
 
 impl Diet {
     /// Creates a new [`Diet`] component.
-    pub(crate) fn new(item: Id<Item>, energy: Energy) -> Self {
+    pub fn new(item: Id<Item>, energy: Energy) -> Self {
         Diet { item, energy }
     }
 
     /// The type of item that this unit must consume.
-    pub(crate) fn item(&self) -> Id<Item> {
+    pub fn item(&self) -> Id<Item> {
         self.item
     }
 
     /// The amount of [`Energy`] gained when a single item of the correct type is consumed.
-    pub(crate) fn energy(&self) -> Energy {
+    pub fn energy(&self) -> Energy {
         self.energy
     }
 }
@@ -1,14 +1,14 @@
 //! Tracks impatience (frustration) of units,
 //! causing them to give up impossible tasks.
 
-use bevy::prelude::*;
+use crate::bevy::prelude::*;
 use core::fmt::Display;
 
 /// The patience of a unit.
 ///
 /// If current >= max, they will abandon their current goal.
 #[derive(Debug, Clone, PartialEq, Component, Resource)]
-pub(crate) struct ImpatiencePool {
+pub struct ImpatiencePool {
     /// The current impatience of this unit.
     current: u8,
     /// The maximum impatience of this unit.
@@ -0,0 +1,83 @@
+//! Holding, using and carrying items.
+
+use crate::bevy::prelude::*;
+
+use crate::manifest::{Id, Item};
+use core::fmt::Display;
+
+/// The item(s) that a unit is carrying.
+///
+/// Units only ever carry one kind of item at a time,
+/// but some varieties of unit can carry several of them at once.
+#[derive(Component, Default, Clone, Debug, PartialEq)]
+pub struct UnitInventory {
+    /// The kind of item the unit is currently holding
+    pub held_item: Option<Id<Item>>,
+    /// The number of items held
+    held_count: usize,
+}
+
+impl UnitInventory {
+    /// An inventory holding `count` of `item_id`, or nothing if `count` is 0.
+    pub fn holding(item_id: Id<Item>, count: usize) -> Self {
+        let mut unit_inventory = UnitInventory::default();
+        unit_inventory.hold(item_id, count);
+        unit_inventory
+    }
+
+    /// The number of items held.
+    pub fn count(&self) -> usize {
+        self.held_count
+    }
+
+    /// Replaces the contents of this inventory with `count` of `item_id`.
+    pub fn hold(&mut self, item_id: Id<Item>, count: usize) {
+        if count == 0 {
+            self.clear();
+        } else {
+            self.held_item = Some(item_id);
+            self.held_count = count;
+        }
+    }
+
+    /// Removes up to `count` of the held items, emptying this inventory once the last is gone.
+    pub fn remove(&mut self, count: usize) {
+        self.held_count = self.held_count.saturating_sub(count);
+        if self.held_count == 0 {
+            self.held_item = None;
+        }
+    }
+
+    /// Removes everything from this inventory.
+    pub fn clear(&mut self) {
+        self.held_item = None;
+        self.held_count = 0;
+    }
+}
+
+impl Display for UnitInventory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.held_item {
+            Some(item) if self.held_count > 1 => write!(f, "{item} ({})", self.held_count),
+            Some(item) => write!(f, "{item}"),
+            None => write!(f, "Nothing"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inventories_empty_once_the_last_item_is_removed() {
+        let mut unit_inventory = UnitInventory::holding(Id::new(1), 3);
+
+        unit_inventory.remove(2);
+        assert_eq!(unit_inventory.held_item, Some(Id::new(1)));
+        assert_eq!(unit_inventory.count(), 1);
+
+        unit_inventory.remove(1);
+        assert_eq!(unit_inventory, UnitInventory::default());
+    }
+}
@@ -0,0 +1,255 @@
+//! Units grow old, and eventually die of old age.
+//!
+//! New units hatch from the colony's nest structures (see [`hatch_ant_eggs`](super::reproduction::hatch_ant_eggs)),
+//! and then age over in-game time until they reach the lifespan of their species.
+//! Units that die of old age leave their corpse on the tile where they fell, in a pile of [`Remains`].
+//! The remains emit [`SignalType::Push`] signals, so that workers carry the corpses away before they rot.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    items::{inventory::Inventory, ItemCount},
+    manifest::{Id, ItemManifest, Unit, UnitManifest},
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::{
+        chronicle::HistoricalEvent,
+        geometry::{MapGeometry, TilePos},
+        time::DAY_LENGTH_IN_SECONDS,
+    },
+    structures::crafting::OutputInventory,
+    terrain::Decompose,
+};
+
+/// The number of corpses that fit in a single pile of [`Remains`].
+const MAX_CORPSES_PER_TILE: usize = 4;
+
+/// The fertility returned to the soil by a corpse that does not fit in the remains on its tile.
+const CORPSE_NUTRIENTS: f32 = 0.2;
+
+/// Ages units, and clears up after them once they die of old age.
+pub(super) struct LifecyclePlugin;
+
+impl Plugin for LifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(age_units)
+            .add_system(die_of_old_age.after(age_units))
+            .add_system(emit_from_remains)
+            .add_system(clear_away_remains.before(die_of_old_age));
+    }
+}
+
+/// How long a unit has been alive.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Age {
+    /// The number of in-game days since this unit was born, including partial days
+    days: f32,
+}
+
+impl Age {
+    /// An age of `days` in-game days, e.g. for a unit restored from a save.
+    pub fn from_days(days: f32) -> Self {
+        Age { days }
+    }
+
+    /// The number of in-game days since this unit was born, including partial days.
+    pub fn in_days(&self) -> f32 {
+        self.days
+    }
+
+    /// Ages this unit by `days`.
+    fn grow_older(&mut self, days: f32) {
+        self.days += days;
+    }
+
+    /// Has this unit lived out a lifespan of `lifespan_in_days`?
+    fn is_past(&self, lifespan_in_days: f32) -> bool {
+        self.days >= lifespan_in_days
+    }
+}
+
+/// A pile of corpses, left where units died of old age.
+///
+/// The corpses are stored in the [`OutputInventory`] of this entity, so they can be picked up like any other item.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Remains;
+
+/// The components needed to create a pile of [`Remains`].
+#[derive(Bundle)]
+pub(crate) struct RemainsBundle {
+    /// Marker component
+    remains: Remains,
+    /// The tile that the remains lie on
+    tile_pos: TilePos,
+    /// The corpses in the pile
+    output_inventory: OutputInventory,
+    /// Calls for the corpses to be taken away
+    emitter: Emitter,
+}
+
+impl RemainsBundle {
+    /// An empty pile of remains on `tile_pos`.
+    fn new(tile_pos: TilePos) -> Self {
+        RemainsBundle {
+            remains: Remains,
+            tile_pos,
+            output_inventory: OutputInventory {
+                inventory: Inventory::new(MAX_CORPSES_PER_TILE),
+            },
+            emitter: Emitter::default(),
+        }
+    }
+
+    /// A pile of `n_corpses` corpses on `tile_pos`, such as one restored from a save.
+    pub(crate) fn with_corpses(
+        tile_pos: TilePos,
+        n_corpses: usize,
+        item_manifest: &ItemManifest,
+    ) -> Self {
+        let mut remains_bundle = RemainsBundle::new(tile_pos);
+        lay_to_rest(
+            &mut remains_bundle.output_inventory,
+            n_corpses,
+            item_manifest,
+        );
+        remains_bundle
+    }
+}
+
+/// Adds `n_corpses` corpses to the `remains`, returning the number that did not fit.
+fn lay_to_rest(
+    remains: &mut OutputInventory,
+    n_corpses: usize,
+    item_manifest: &ItemManifest,
+) -> usize {
+    match remains.try_add_item(&ItemCount::new(Id::corpse(), n_corpses), item_manifest) {
+        Ok(()) => 0,
+        Err(error) => error.excess_count.count(),
+    }
+}
+
+/// Ages every unit by the in-game time that has passed.
+fn age_units(time: Res<Time>, mut unit_query: Query<&mut Age>) {
+    let days = time.delta_seconds() / DAY_LENGTH_IN_SECONDS;
+
+    for mut age in unit_query.iter_mut() {
+        age.grow_older(days);
+    }
+}
+
+/// Despawns units that have lived out their lifespan, leaving their corpses behind.
+#[allow(clippy::too_many_arguments)]
+fn die_of_old_age(
+    unit_query: Query<(Entity, &TilePos, &Id<Unit>, &Age)>,
+    mut remains_query: Query<&mut OutputInventory, With<Remains>>,
+    unit_manifest: Res<UnitManifest>,
+    item_manifest: Res<ItemManifest>,
+    mut map_geometry: ResMut<MapGeometry>,
+    mut decompose_events: EventWriter<Decompose>,
+    mut historical_events: EventWriter<HistoricalEvent>,
+    mut commands: Commands,
+) {
+    let mut corpses: HashMap<TilePos, usize> = HashMap::new();
+
+    for (entity, &tile_pos, &unit_id, age) in unit_query.iter() {
+        if age.is_past(unit_manifest.get(unit_id).lifespan_in_days()) {
+            commands.entity(entity).despawn_recursive();
+            historical_events.send(HistoricalEvent::DiedOfOldAge { tile_pos });
+            *corpses.entry(tile_pos).or_default() += 1;
+        }
+    }
+
+    for (tile_pos, n_corpses) in corpses {
+        let n_excess = match map_geometry.remains_index.get(&tile_pos) {
+            Some(&remains_entity) => match remains_query.get_mut(remains_entity) {
+                Ok(mut remains) => lay_to_rest(&mut remains, n_corpses, &item_manifest),
+                Err(_) => n_corpses,
+            },
+            None => {
+                let mut remains_bundle = RemainsBundle::new(tile_pos);
+                let n_excess = lay_to_rest(
+                    &mut remains_bundle.output_inventory,
+                    n_corpses,
+                    &item_manifest,
+                );
+                let remains_entity = commands.spawn(remains_bundle).id();
+                map_geometry.remains_index.insert(tile_pos, remains_entity);
+                n_excess
+            }
+        };
+
+        if n_excess > 0 {
+            decompose_events.send(Decompose {
+                tile_pos,
+                nutrients: n_excess as f32 * CORPSE_NUTRIENTS,
+            });
+        }
+    }
+}
+
+/// Calls for the contents of each pile of remains to be carried away.
+fn emit_from_remains(mut remains_query: Query<(&OutputInventory, &mut Emitter), With<Remains>>) {
+    for (output_inventory, mut emitter) in remains_query.iter_mut() {
+        // Reset and recompute all signals
+        emitter.signals.clear();
+
+        for item_slot in output_inventory.iter() {
+            if !item_slot.is_empty() {
+                let signal_type = SignalType::Push(item_slot.item_id());
+                let signal_strength = SignalStrength::new(10.);
+                emitter.signals.push((signal_type, signal_strength));
+            }
+        }
+    }
+}
+
+/// Despawns piles of remains once everything in them has been carried away.
+fn clear_away_remains(
+    remains_query: Query<(Entity, &TilePos, &OutputInventory), With<Remains>>,
+    mut map_geometry: ResMut<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, tile_pos, output_inventory) in remains_query.iter() {
+        if output_inventory.is_empty() {
+            map_geometry.remains_index.remove(tile_pos);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::items::ItemData;
+
+    /// An item manifest containing only corpses.
+    fn item_manifest() -> ItemManifest {
+        let mut map = HashMap::new();
+        map.insert(Id::corpse(), ItemData::corpse());
+        ItemManifest::new(map)
+    }
+
+    #[test]
+    fn units_die_once_they_outlive_their_lifespan() {
+        let mut age = Age::default();
+        age.grow_older(29.5);
+        assert!(!age.is_past(30.));
+
+        age.grow_older(0.5);
+        assert!(age.is_past(30.));
+        assert_eq!(age.in_days(), 30.);
+    }
+
+    #[test]
+    fn remains_only_hold_so_many_corpses() {
+        let item_manifest = item_manifest();
+        let mut remains = RemainsBundle::new(TilePos::ORIGIN).output_inventory;
+
+        assert_eq!(lay_to_rest(&mut remains, 1, &item_manifest), 0);
+        assert_eq!(
+            lay_to_rest(&mut remains, MAX_CORPSES_PER_TILE, &item_manifest),
+            1
+        );
+        assert_eq!(remains.item_count(Id::corpse()), MAX_CORPSES_PER_TILE);
+    }
+}
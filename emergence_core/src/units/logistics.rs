@@ -0,0 +1,394 @@
+//! A "logistics memory" that helps haulers find routes that other haulers have already used.
+//!
+//! Each completed haul leaves a faint, persistent trace along the corridor it followed:
+//! [`SignalType::Pull`] signals that grow stronger towards the destination, and [`SignalType::Push`] signals that grow stronger towards the source.
+//! These traces are re-emitted every signal tick, so frequently used corridors stay marked even when their emitters are far away.
+//! Traces fade over time, so corridors that are no longer used are eventually forgotten.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    manifest::{Id, Item},
+    signals::{emit_signals, SignalSchedule, SignalStrength, SignalType, Signals},
+    simulation::geometry::TilePos,
+};
+
+use super::{
+    actions::{CurrentAction, UnitAction},
+    item_interaction::UnitInventory,
+    UnitSystem,
+};
+
+/// The strength added to the trace at the end of a corridor by a single haul.
+const HAUL_TRACE_STRENGTH: f32 = 0.05;
+
+/// The strongest that a trace on a single tile can become, no matter how often the corridor is used.
+const MAX_TRACE_STRENGTH: f32 = 0.5;
+
+/// The fraction of each trace that fades away each signal tick.
+///
+/// This is much slower than the decay of ordinary signals, so the memory outlasts the hauls that made it.
+const TRACE_FADE_FRACTION: f32 = 0.0002;
+
+/// Traces weaker than this are forgotten entirely.
+const MIN_TRACE_STRENGTH: f32 = 0.001;
+
+/// The longest corridor that will be remembered, in tiles.
+///
+/// Longer hauls only remember the tiles nearest to their destination.
+const MAX_CORRIDOR_LENGTH: usize = 64;
+
+/// Records completed hauls and re-emits their traces.
+pub(super) struct LogisticsPlugin;
+
+impl Plugin for LogisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LogisticsMemory>()
+            .add_system(
+                track_hauls
+                    .after(UnitSystem::Act)
+                    .before(UnitSystem::ChooseNewAction),
+            )
+            .add_system(
+                emit_logistics_memory
+                    .before(emit_signals)
+                    .in_schedule(SignalSchedule),
+            );
+    }
+}
+
+/// The faint traces left along corridors that haulers have used.
+///
+/// The memory can be disabled, to compare the colony's logistics against the baseline without it.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LogisticsMemory {
+    /// Are hauls being remembered and re-emitted?
+    enabled: bool,
+    /// The strength of the trace left on each tile, for each signal type
+    traces: HashMap<SignalType, HashMap<TilePos, SignalStrength>>,
+}
+
+impl Default for LogisticsMemory {
+    fn default() -> Self {
+        LogisticsMemory {
+            enabled: true,
+            traces: HashMap::default(),
+        }
+    }
+}
+
+impl LogisticsMemory {
+    /// Creates an empty memory, which only records hauls if `enabled`.
+    pub fn new(enabled: bool) -> Self {
+        LogisticsMemory {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Are hauls being remembered and re-emitted?
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns the memory on or off.
+    ///
+    /// Turning the memory off forgets every trace.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.traces.clear();
+        }
+    }
+
+    /// The strength of the trace of `signal_type` left on `tile_pos`.
+    pub fn get(&self, signal_type: SignalType, tile_pos: TilePos) -> SignalStrength {
+        self.traces
+            .get(&signal_type)
+            .and_then(|trace| trace.get(&tile_pos))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of tiles with a trace of any type on them.
+    pub fn n_traces(&self) -> usize {
+        self.traces.values().map(|trace| trace.len()).sum()
+    }
+
+    /// Remembers that `item_id` was carried along the `corridor`, from its first tile to its last.
+    pub fn record_haul(&mut self, item_id: Id<Item>, corridor: &[TilePos]) {
+        if !self.enabled || corridor.len() < 2 {
+            return;
+        }
+
+        let corridor = &corridor[corridor.len().saturating_sub(MAX_CORRIDOR_LENGTH)..];
+        let length = corridor.len() as f32;
+
+        for (index, &tile_pos) in corridor.iter().enumerate() {
+            let progress = (index + 1) as f32 / length;
+
+            // Pull signals lead carriers towards the destination, and push signals lead collectors back to the source
+            self.strengthen(
+                SignalType::Pull(item_id),
+                tile_pos,
+                HAUL_TRACE_STRENGTH * progress,
+            );
+            self.strengthen(
+                SignalType::Push(item_id),
+                tile_pos,
+                HAUL_TRACE_STRENGTH * (1. - progress + 1. / length),
+            );
+        }
+    }
+
+    /// Adds `amount` to the trace of `signal_type` on `tile_pos`, up to [`MAX_TRACE_STRENGTH`].
+    fn strengthen(&mut self, signal_type: SignalType, tile_pos: TilePos, amount: f32) {
+        let strength = self
+            .traces
+            .entry(signal_type)
+            .or_default()
+            .entry(tile_pos)
+            .or_default();
+
+        *strength = SignalStrength::new((strength.value() + amount).min(MAX_TRACE_STRENGTH));
+    }
+
+    /// Fades every trace by `TRACE_FADE_FRACTION`, forgetting those that become too weak.
+    pub fn fade(&mut self) {
+        for trace in self.traces.values_mut() {
+            trace.retain(|_, strength| {
+                *strength = *strength * (1. - TRACE_FADE_FRACTION);
+                strength.value() >= MIN_TRACE_STRENGTH
+            });
+        }
+
+        self.traces.retain(|_, trace| !trace.is_empty());
+    }
+
+    /// Every trace, along with the tile it is on and its strength.
+    pub fn iter_traces(&self) -> impl Iterator<Item = (SignalType, TilePos, SignalStrength)> + '_ {
+        self.traces.iter().flat_map(|(&signal_type, trace)| {
+            trace
+                .iter()
+                .map(move |(&tile_pos, &strength)| (signal_type, tile_pos, strength))
+        })
+    }
+
+    /// Replaces every trace with the provided `traces`, e.g. when a saved game is loaded.
+    pub fn restore_traces(
+        &mut self,
+        traces: impl IntoIterator<Item = (SignalType, TilePos, SignalStrength)>,
+    ) {
+        self.traces.clear();
+        for (signal_type, tile_pos, strength) in traces {
+            self.traces
+                .entry(signal_type)
+                .or_default()
+                .insert(tile_pos, strength);
+        }
+    }
+
+    /// Emits every trace into the `signals`.
+    pub fn emit(&self, signals: &mut Signals) {
+        for (&signal_type, trace) in &self.traces {
+            for (&tile_pos, &strength) in trace {
+                signals.add_signal(signal_type, tile_pos, strength);
+            }
+        }
+    }
+}
+
+/// The tiles that a unit has walked along while carrying an item.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct HaulTrail {
+    /// The item being carried, if any
+    item_id: Option<Id<Item>>,
+    /// The tiles walked since the item was picked up, in order
+    tiles: Vec<TilePos>,
+}
+
+impl HaulTrail {
+    /// Follows a unit standing on `tile_pos` and holding `held_item`.
+    ///
+    /// Returns the item and the corridor it was carried along once the unit stops holding it.
+    fn follow(
+        &mut self,
+        tile_pos: TilePos,
+        held_item: Option<Id<Item>>,
+    ) -> Option<(Id<Item>, Vec<TilePos>)> {
+        if self.item_id == held_item {
+            if held_item.is_some() && self.tiles.last() != Some(&tile_pos) {
+                self.tiles.push(tile_pos);
+                if self.tiles.len() > MAX_CORRIDOR_LENGTH {
+                    self.tiles.remove(0);
+                }
+            }
+            return None;
+        }
+
+        let finished = self
+            .item_id
+            .map(|item_id| (item_id, std::mem::take(&mut self.tiles)));
+
+        self.item_id = held_item;
+        self.tiles.clear();
+        if held_item.is_some() {
+            self.tiles.push(tile_pos);
+        }
+
+        finished
+    }
+}
+
+/// Records the corridor walked by each unit that has just delivered an item.
+///
+/// Items that were eaten or abandoned were not hauled anywhere, so they are not remembered.
+fn track_hauls(
+    mut unit_query: Query<(&TilePos, &UnitInventory, &CurrentAction, &mut HaulTrail)>,
+    mut logistics_memory: ResMut<LogisticsMemory>,
+) {
+    if !logistics_memory.is_enabled() {
+        return;
+    }
+
+    for (&tile_pos, unit_inventory, current_action, mut haul_trail) in unit_query.iter_mut() {
+        if let Some((item_id, corridor)) = haul_trail.follow(tile_pos, unit_inventory.held_item) {
+            let delivered = current_action.finished()
+                && matches!(current_action.action(), UnitAction::DropOff { .. });
+
+            if delivered {
+                logistics_memory.record_haul(item_id, &corridor);
+            }
+        }
+    }
+}
+
+/// Fades the logistics memory, and re-emits what is left of it.
+fn emit_logistics_memory(
+    mut logistics_memory: ResMut<LogisticsMemory>,
+    mut signals: ResMut<Signals>,
+) {
+    if !logistics_memory.is_enabled() {
+        return;
+    }
+
+    logistics_memory.fade();
+    logistics_memory.emit(&mut signals);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        signals::SignalSensitivity, simulation::geometry::MapGeometry, units::goals::Goal,
+    };
+
+    const TEST_ITEM: Id<Item> = Id::new(12345);
+
+    /// A corridor along the x-axis, from the origin to `length` tiles away.
+    fn corridor(length: i32) -> Vec<TilePos> {
+        (0..=length).map(|x| TilePos::new(x, 0)).collect()
+    }
+
+    #[test]
+    fn traces_lead_in_both_directions() {
+        let mut logistics_memory = LogisticsMemory::default();
+        logistics_memory.record_haul(TEST_ITEM, &corridor(3));
+
+        let pull = SignalType::Pull(TEST_ITEM);
+        let push = SignalType::Push(TEST_ITEM);
+        assert!(
+            logistics_memory.get(pull, TilePos::new(3, 0))
+                > logistics_memory.get(pull, TilePos::ORIGIN)
+        );
+        assert!(
+            logistics_memory.get(push, TilePos::ORIGIN)
+                > logistics_memory.get(push, TilePos::new(3, 0))
+        );
+    }
+
+    #[test]
+    fn traces_help_haulers_find_their_way() {
+        let map_geometry = MapGeometry::new(5);
+        let mut logistics_memory = LogisticsMemory::default();
+        let mut signals = Signals::default();
+
+        // Without any memory, there is nothing to follow
+        assert_eq!(
+            signals.upstream(
+                TilePos::new(1, 0),
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            None
+        );
+
+        logistics_memory.record_haul(TEST_ITEM, &corridor(3));
+        logistics_memory.emit(&mut signals);
+
+        assert_eq!(
+            signals.upstream(
+                TilePos::new(1, 0),
+                &Goal::DropOff(TEST_ITEM),
+                &SignalSensitivity::default(),
+                &map_geometry
+            ),
+            Some(TilePos::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn frequent_hauls_are_capped() {
+        let mut logistics_memory = LogisticsMemory::default();
+        for _ in 0..1000 {
+            logistics_memory.record_haul(TEST_ITEM, &corridor(3));
+        }
+
+        assert_eq!(
+            logistics_memory.get(SignalType::Pull(TEST_ITEM), TilePos::new(3, 0)),
+            SignalStrength::new(MAX_TRACE_STRENGTH)
+        );
+    }
+
+    #[test]
+    fn unused_corridors_are_forgotten() {
+        let mut logistics_memory = LogisticsMemory::default();
+        logistics_memory.record_haul(TEST_ITEM, &corridor(3));
+
+        let mut n_ticks = 0;
+        while logistics_memory.n_traces() > 0 {
+            logistics_memory.fade();
+            n_ticks += 1;
+        }
+
+        // Traces outlast the hauls that made them, but not forever
+        assert!(n_ticks > 1000);
+        assert!(n_ticks < 100_000);
+    }
+
+    #[test]
+    fn disabled_memory_records_nothing() {
+        let mut logistics_memory = LogisticsMemory::new(false);
+        logistics_memory.record_haul(TEST_ITEM, &corridor(3));
+
+        assert_eq!(logistics_memory.n_traces(), 0);
+    }
+
+    #[test]
+    fn trails_are_finished_when_the_item_is_put_down() {
+        let mut haul_trail = HaulTrail::default();
+
+        assert_eq!(haul_trail.follow(TilePos::ORIGIN, Some(TEST_ITEM)), None);
+        assert_eq!(haul_trail.follow(TilePos::new(1, 0), Some(TEST_ITEM)), None);
+        // Standing still doesn't lengthen the trail
+        assert_eq!(haul_trail.follow(TilePos::new(1, 0), Some(TEST_ITEM)), None);
+        assert_eq!(haul_trail.follow(TilePos::new(2, 0), Some(TEST_ITEM)), None);
+
+        assert_eq!(
+            haul_trail.follow(TilePos::new(2, 0), None),
+            Some((TEST_ITEM, corridor(2)))
+        );
+        assert_eq!(haul_trail.follow(TilePos::new(2, 0), None), None);
+    }
+}
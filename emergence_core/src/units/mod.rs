@@ -0,0 +1,400 @@
+//! Units are organisms that can move freely.
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use crate::{
+    manifest::{
+        definitions::{definitions_directory, load_unit_varieties, loaded_or_built_in},
+        Id, Structure, Unit, UnitManifest,
+    },
+    organisms::energy::{Energy, EnergyPool},
+    signals::SignalSensitivity,
+    simulation::{
+        freezing::ColdTolerance,
+        geometry::{Facing, TilePos},
+        vision::VisionSource,
+    },
+};
+
+use self::{
+    actions::CurrentAction,
+    animation::UnitAnimations,
+    goals::Goal,
+    hunger::Diet,
+    impatience::ImpatiencePool,
+    item_interaction::UnitInventory,
+    lifecycle::Age,
+    logistics::HaulTrail,
+    routes::Route,
+    soldiers::Caste,
+    varieties::{built_in_unit_varieties, UnitVariety},
+};
+
+use crate::organisms::OrganismBundle;
+
+pub mod actions;
+pub mod alarm;
+pub mod animation;
+pub mod crowding;
+pub mod danger;
+#[cfg(feature = "disease")]
+pub mod disease;
+pub mod goals;
+pub mod hunger;
+pub mod impatience;
+pub mod item_interaction;
+pub mod lifecycle;
+pub mod logistics;
+mod reproduction;
+pub mod routes;
+pub mod soldiers;
+pub mod varieties;
+
+/// The data associated with each variety of unit
+#[derive(Debug, Clone)]
+pub struct UnitData {
+    /// The energy pool of this unit
+    energy_pool: EnergyPool,
+    /// What this unit type needs to eat
+    diet: Diet,
+    /// How much impatience this unit can accumulate before getting too frustrated and picking a new task.
+    max_impatience: u8,
+    /// The lowest temperature this unit can endure without being harmed
+    cold_tolerance: ColdTolerance,
+    /// How this unit moves, carries items and weighs up its goals
+    variety: UnitVariety,
+    /// How many tiles away this unit can sense signals from
+    ///
+    /// A radius of 0 means that only signals on the unit's own tile are sensed.
+    sensing_radius: u32,
+    /// The structure that this unit lives in and defends, if it is part of the colony
+    ///
+    /// Units without a nest are treated as intruders.
+    nest: Option<Id<Structure>>,
+    /// The role this unit plays in its colony
+    caste: Caste,
+    /// The number of in-game days that this unit lives for, before dying of old age
+    lifespan_in_days: f32,
+    /// The average number of offspring this unit has each in-game day, while it is well-fed and close to its nest
+    births_per_day: f32,
+}
+
+impl UnitData {
+    /// How quickly this unit walks, relative to a standard unit
+    pub fn walking_speed(&self) -> f32 {
+        self.variety.walking_speed
+    }
+
+    /// The number of items this unit can carry at once
+    pub fn carry_capacity(&self) -> usize {
+        self.variety.carry_capacity
+    }
+
+    /// How strongly this unit perceives each category of signal when choosing and pursuing a goal
+    pub fn signal_sensitivity(&self) -> &SignalSensitivity {
+        &self.variety.goal_weights
+    }
+
+    /// How many tiles away this unit can sense signals from
+    pub fn sensing_radius(&self) -> u32 {
+        self.sensing_radius
+    }
+
+    /// The structure that this unit lives in and defends, if it is part of the colony
+    pub fn nest(&self) -> Option<Id<Structure>> {
+        self.nest
+    }
+
+    /// The role this unit plays in its colony
+    pub fn caste(&self) -> Caste {
+        self.caste
+    }
+
+    /// The number of in-game days that this unit lives for, before dying of old age
+    pub fn lifespan_in_days(&self) -> f32 {
+        self.lifespan_in_days
+    }
+
+    /// The average number of offspring this unit has each in-game day, while it is well-fed and close to its nest
+    pub fn births_per_day(&self) -> f32 {
+        self.births_per_day
+    }
+
+    /// The animation clip shown for each thing this unit can be doing
+    pub fn animations(&self) -> &UnitAnimations {
+        &self.variety.animations
+    }
+}
+
+impl UnitManifest {
+    /// The data for every kind of unit, moving, carrying and prioritizing goals according to the provided `varieties`.
+    ///
+    /// Kinds of unit that are missing from `varieties` use their built-in variety instead.
+    pub fn with_varieties(mut varieties: HashMap<Id<Unit>, UnitVariety>) -> Self {
+        let mut built_in_varieties = built_in_unit_varieties();
+        let mut variety = |unit_id: Id<Unit>| {
+            varieties
+                .remove(&unit_id)
+                .or_else(|| built_in_varieties.remove(&unit_id))
+                .unwrap_or_else(|| panic!("No variety is defined for {unit_id}"))
+        };
+
+        let mut map = HashMap::new();
+
+        // TODO: load the rest of this from disk
+        map.insert(
+            Id::ant(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(100.), Energy(-1.)),
+                diet: Diet::new(Id::leuco_chunk(), Energy(50.)),
+                max_impatience: 10,
+                cold_tolerance: ColdTolerance(0.),
+                variety: variety(Id::ant()),
+                sensing_radius: 0,
+                nest: Some(Id::from_string_id("ant_hive")),
+                caste: Caste::Worker,
+                lifespan_in_days: 30.,
+                births_per_day: 0.5,
+            },
+        );
+
+        map.insert(
+            Id::hauler_ant(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(120.), Energy(-1.2)),
+                diet: Diet::new(Id::leuco_chunk(), Energy(50.)),
+                // Haulers are patient, as full loads take a while to deliver
+                max_impatience: 15,
+                cold_tolerance: ColdTolerance(0.),
+                variety: variety(Id::hauler_ant()),
+                sensing_radius: 0,
+                nest: Some(Id::from_string_id("ant_hive")),
+                caste: Caste::Worker,
+                lifespan_in_days: 30.,
+                births_per_day: 0.,
+            },
+        );
+
+        map.insert(
+            Id::scout_ant(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(80.), Energy(-1.)),
+                diet: Diet::new(Id::leuco_chunk(), Energy(50.)),
+                max_impatience: 8,
+                cold_tolerance: ColdTolerance(0.),
+                variety: variety(Id::scout_ant()),
+                // Scouts notice signals from further away, so they find new resources quickly
+                sensing_radius: 3,
+                nest: Some(Id::from_string_id("ant_hive")),
+                caste: Caste::Worker,
+                lifespan_in_days: 25.,
+                births_per_day: 0.,
+            },
+        );
+
+        map.insert(
+            Id::soldier_ant(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(150.), Energy(-1.5)),
+                diet: Diet::new(Id::leuco_chunk(), Energy(50.)),
+                max_impatience: 20,
+                cold_tolerance: ColdTolerance(0.),
+                variety: variety(Id::soldier_ant()),
+                // Soldiers keep a wider watch, so they can hear alarms from further away
+                sensing_radius: 2,
+                nest: Some(Id::from_string_id("ant_hive")),
+                caste: Caste::Soldier {
+                    attack_damage: Energy(20.),
+                },
+                // Soldiers live fast and die young
+                lifespan_in_days: 20.,
+                // Soldiers leave the raising of young to the workers
+                births_per_day: 0.,
+            },
+        );
+
+        map.insert(
+            Id::locust(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(50.), Energy(-2.)),
+                diet: Diet::new(Id::acacia_leaf(), Energy(25.)),
+                max_impatience: 5,
+                cold_tolerance: ColdTolerance(5.),
+                variety: variety(Id::locust()),
+                sensing_radius: 0,
+                nest: None,
+                caste: Caste::Worker,
+                lifespan_in_days: 10.,
+                births_per_day: 0.,
+            },
+        );
+
+        map.insert(
+            Id::beetle(),
+            UnitData {
+                energy_pool: EnergyPool::new_full(Energy(150.), Energy(-1.)),
+                diet: Diet::new(Id::acacia_leaf(), Energy(50.)),
+                max_impatience: 15,
+                // Beetles are well-armored against the cold
+                cold_tolerance: ColdTolerance(-15.),
+                variety: variety(Id::beetle()),
+                sensing_radius: 0,
+                nest: None,
+                caste: Caste::Worker,
+                lifespan_in_days: 60.,
+                births_per_day: 0.,
+            },
+        );
+
+        UnitManifest::new(map)
+    }
+}
+
+impl Default for UnitManifest {
+    fn default() -> Self {
+        UnitManifest::with_varieties(built_in_unit_varieties())
+    }
+}
+
+impl Id<Unit> {
+    // TODO: read these from disk
+    /// The id of an ant
+    pub fn ant() -> Self {
+        Self::from_string_id("ant")
+    }
+
+    /// The id of a hauler ant, which carries goods around the colony in bulk
+    pub fn hauler_ant() -> Self {
+        Self::from_string_id("hauler_ant")
+    }
+
+    /// The id of a scout ant, which ranges quickly in search of resources
+    pub fn scout_ant() -> Self {
+        Self::from_string_id("scout_ant")
+    }
+
+    /// The id of a soldier ant, which defends the colony from intruders
+    pub fn soldier_ant() -> Self {
+        Self::from_string_id("soldier_ant")
+    }
+
+    /// The id of a locust, a crop-eating pest that arrives in swarms
+    pub fn locust() -> Self {
+        Self::from_string_id("locust")
+    }
+
+    /// The id of a beetle, which migrates across the map in herds
+    pub fn beetle() -> Self {
+        Self::from_string_id("beetle")
+    }
+}
+
+/// An organism that can move around freely.
+#[derive(Bundle)]
+pub struct UnitBundle {
+    /// Marker component.
+    unit_id: Id<Unit>,
+    /// The tile the unit is above.
+    tile_pos: TilePos,
+    /// The direction that the unit is facing.
+    facing: Facing,
+    /// What is the unit working towards.
+    current_goal: Goal,
+    /// How frustrated this unit is.
+    ///
+    /// When full, the current goal will be abandoned.
+    impatience: ImpatiencePool,
+    /// What is the unit currently doing.
+    current_action: CurrentAction,
+    /// The path this unit is following, if it is travelling somewhere far away.
+    route: Route,
+    /// What is the unit currently holding, if anything?
+    held_item: UnitInventory,
+    /// The tiles walked while carrying the held item, remembered once it is delivered.
+    haul_trail: HaulTrail,
+    /// What does this unit need to eat?
+    diet: Diet,
+    /// How long this unit has been alive.
+    age: Age,
+    /// Organism data
+    organism_bundle: OrganismBundle,
+    /// How far this unit can see
+    vision_source: VisionSource,
+}
+
+impl UnitBundle {
+    /// Initializes a new unit
+    ///
+    /// The model used to draw and pick the unit is added separately, by the graphics.
+    // TODO: use a UnitManifest
+    pub fn new(unit_id: Id<Unit>, tile_pos: TilePos, unit_data: UnitData) -> Self {
+        UnitBundle {
+            unit_id,
+            tile_pos,
+            facing: Facing::default(),
+            current_goal: Goal::default(),
+            impatience: ImpatiencePool::new(unit_data.max_impatience),
+            current_action: CurrentAction::default(),
+            route: Route::default(),
+            held_item: UnitInventory::default(),
+            haul_trail: HaulTrail::default(),
+            diet: unit_data.diet,
+            age: Age::default(),
+            organism_bundle: OrganismBundle::new(unit_data.energy_pool, unit_data.cold_tolerance),
+            vision_source: VisionSource::UNIT,
+        }
+    }
+}
+
+/// System sets for unit behavior
+///
+/// Player interaction configures its own sets relative to these, as the simulation does not know about it.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum UnitSystem {
+    /// Advances the timer of all unit actions.
+    AdvanceTimers,
+    /// Carry out the chosen action
+    Act,
+    /// Pick a higher level goal to pursue
+    ChooseGoal,
+    /// Pick an action that will get the agent closer to the goal being pursued
+    ChooseNewAction,
+}
+
+/// Contains unit behavior
+pub struct UnitsPlugin;
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        let unit_varieties = loaded_or_built_in(
+            load_unit_varieties(&definitions_directory()),
+            built_in_unit_varieties,
+        );
+
+        app.insert_resource(UnitManifest::with_varieties(unit_varieties))
+            .add_plugin(alarm::AlarmPlugin)
+            .add_plugin(soldiers::SoldiersPlugin)
+            .add_plugin(danger::DangerPlugin)
+            .add_plugin(crowding::CrowdingPlugin)
+            .add_plugin(routes::RoutesPlugin)
+            .add_plugin(logistics::LogisticsPlugin)
+            .add_plugin(lifecycle::LifecyclePlugin)
+            .add_system(actions::advance_action_timer.in_set(UnitSystem::AdvanceTimers))
+            .add_system(
+                actions::handle_actions
+                    .in_set(UnitSystem::Act)
+                    .after(UnitSystem::AdvanceTimers),
+            )
+            .add_system(goals::choose_goal.in_set(UnitSystem::ChooseGoal))
+            .add_system(
+                actions::choose_actions
+                    .in_set(UnitSystem::ChooseNewAction)
+                    .after(UnitSystem::Act)
+                    .after(UnitSystem::ChooseGoal),
+            )
+            .add_system(reproduction::hatch_ant_eggs)
+            .add_system(reproduction::breed_near_nests)
+            .add_system(hunger::check_for_hunger.before(UnitSystem::ChooseNewAction));
+
+        #[cfg(feature = "disease")]
+        app.add_plugin(disease::DiseasePlugin);
+    }
+}
@@ -0,0 +1,291 @@
+//! Making more units
+
+use crate::bevy::{prelude::*, utils::HashMap};
+use rand::prelude::IteratorRandom;
+use rand::Rng;
+
+use crate::{
+    manifest::{Id, Structure, Unit, UnitManifest},
+    organisms::{
+        energy::EnergyPool,
+        genealogy::{FamilyTree, Lineage},
+    },
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        jitter::{Jitter, JitterStream},
+        time::{InGameTime, DAY_LENGTH_IN_SECONDS},
+    },
+    structures::{
+        construction::{Ghost, Preview},
+        crafting::{ActiveRecipe, CraftingState},
+    },
+};
+
+use super::{crowding::TileOccupancy, UnitBundle};
+
+/// The chance that each hatched egg becomes a soldier, rather than a worker.
+const SOLDIER_HATCH_CHANCE: f64 = 0.2;
+
+/// The chance that each hatched egg becomes a hauler, rather than a worker.
+const HAULER_HATCH_CHANCE: f64 = 0.2;
+
+/// The chance that each hatched egg becomes a scout, rather than a worker.
+const SCOUT_HATCH_CHANCE: f64 = 0.1;
+
+/// How many tiles away from a nest structure a unit can be while still able to breed.
+const NEST_RADIUS: u32 = 3;
+
+/// The fraction of its maximum energy that a unit spends on giving birth.
+///
+/// Only well-fed units breed, so this leaves the parent hungry but not starving.
+const BIRTH_ENERGY_COST: f32 = 0.4;
+
+/// The kind of ant that hatches from an egg, chosen by a `roll` between 0 and 1.
+///
+/// Most eggs hatch into workers, but some become soldiers, haulers or scouts.
+fn hatchling(roll: f64) -> Id<Unit> {
+    if roll < SOLDIER_HATCH_CHANCE {
+        Id::soldier_ant()
+    } else if roll < SOLDIER_HATCH_CHANCE + HAULER_HATCH_CHANCE {
+        Id::hauler_ant()
+    } else if roll < SOLDIER_HATCH_CHANCE + HAULER_HATCH_CHANCE + SCOUT_HATCH_CHANCE {
+        Id::scout_ant()
+    } else {
+        Id::ant()
+    }
+}
+
+/// Chooses a tile next to `tile_pos` for a newborn unit, avoiding structures and tiles that are already full.
+fn birthplace(
+    tile_pos: TilePos,
+    map_geometry: &MapGeometry,
+    tile_occupancy: &TileOccupancy,
+    rng: &mut impl Rng,
+) -> Option<TilePos> {
+    tile_pos
+        .empty_neighbors(map_geometry)
+        .into_iter()
+        .filter(|&neighbor| !tile_occupancy.is_full(neighbor))
+        .choose(rng)
+}
+
+/// Spawn ants when eggs have hatched
+///
+/// Most eggs hatch into workers, but some become soldiers, haulers or scouts.
+/// Each hatchling is recorded in the [`FamilyTree`] as a child of the structure it hatched from.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn hatch_ant_eggs(
+    structure_query: Query<(
+        Entity,
+        &TilePos,
+        &CraftingState,
+        &ActiveRecipe,
+        Option<&Lineage>,
+    )>,
+    jitter: Jitter,
+    map_geometry: Res<MapGeometry>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+    unit_manifest: Res<UnitManifest>,
+    in_game_time: Res<InGameTime>,
+    mut family_tree: ResMut<FamilyTree>,
+    mut commands: Commands,
+) {
+    let today = in_game_time.current_day();
+
+    // PERF: I don't like the linear time polling here. This really feels like it should be push-based with one-shot system callbacks on the recipe.
+    for (structure_entity, tile_pos, crafting_state, active_recipe, maybe_lineage) in
+        structure_query.iter()
+    {
+        if let Some(recipe_id) = active_recipe.recipe_id() {
+            if *recipe_id == Id::hatch_ants()
+                && matches!(crafting_state, CraftingState::RecipeComplete)
+            {
+                let rng = &mut jitter.rng(structure_entity, JitterStream::Breeding);
+                if let Some(pos_to_spawn) =
+                    birthplace(*tile_pos, &map_geometry, &tile_occupancy, rng)
+                {
+                    let unit_id = hatchling(rng.gen());
+
+                    // Hatcheries found their own lineage when their first egg hatches
+                    let parent = match maybe_lineage {
+                        Some(lineage) => *lineage,
+                        None => {
+                            let lineage = family_tree.record_founder(structure_entity, today);
+                            commands.entity(structure_entity).insert(lineage);
+                            lineage
+                        }
+                    };
+
+                    let unit_entity = commands
+                        .spawn(UnitBundle::new(
+                            unit_id,
+                            pos_to_spawn,
+                            unit_manifest.get(unit_id).clone(),
+                        ))
+                        .id();
+                    let lineage = family_tree.record_birth(unit_entity, parent, today);
+                    commands.entity(unit_entity).insert(lineage);
+                    tile_occupancy.add_unit(pos_to_spawn);
+                }
+            }
+        }
+    }
+}
+
+/// The chance that a unit that has `births_per_day` offspring on average gives birth during `delta_seconds`.
+fn birth_chance(births_per_day: f32, delta_seconds: f32) -> f64 {
+    (births_per_day * delta_seconds / DAY_LENGTH_IN_SECONDS).clamp(0., 1.) as f64
+}
+
+/// Is `tile_pos` close enough to one of the `nests` to breed there?
+fn is_near_nest(tile_pos: TilePos, nests: &[TilePos]) -> bool {
+    nests
+        .iter()
+        .any(|nest| nest.unsigned_distance_to(tile_pos.hex) <= NEST_RADIUS)
+}
+
+/// Well-fed units close to their nest occasionally give birth to a new unit of their own kind.
+///
+/// The rate is controlled by the [`births_per_day`](super::UnitData::births_per_day) of each kind of unit,
+/// and giving birth costs the parent some of its energy.
+/// Each offspring is recorded in the [`FamilyTree`] as a child of its parent.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn breed_near_nests(
+    mut unit_query: Query<(
+        Entity,
+        &Id<Unit>,
+        &TilePos,
+        &mut EnergyPool,
+        Option<&Lineage>,
+    )>,
+    structure_query: Query<(&Id<Structure>, &TilePos), (Without<Ghost>, Without<Preview>)>,
+    time: Res<Time>,
+    jitter: Jitter,
+    map_geometry: Res<MapGeometry>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+    unit_manifest: Res<UnitManifest>,
+    in_game_time: Res<InGameTime>,
+    mut family_tree: ResMut<FamilyTree>,
+    mut commands: Commands,
+) {
+    let today = in_game_time.current_day();
+
+    let mut nests: HashMap<Id<Structure>, Vec<TilePos>> = HashMap::new();
+    for (&structure_id, &tile_pos) in structure_query.iter() {
+        nests.entry(structure_id).or_default().push(tile_pos);
+    }
+
+    for (entity, &unit_id, &tile_pos, mut energy_pool, maybe_lineage) in unit_query.iter_mut() {
+        let unit_data = unit_manifest.get(unit_id);
+
+        if !energy_pool.is_satiated() {
+            continue;
+        }
+
+        let near_nest = match unit_data.nest() {
+            Some(nest_id) => nests
+                .get(&nest_id)
+                .is_some_and(|nests| is_near_nest(tile_pos, nests)),
+            None => false,
+        };
+        if !near_nest {
+            continue;
+        }
+
+        let rng = &mut jitter.rng(entity, JitterStream::Breeding);
+        if !rng.gen_bool(birth_chance(
+            unit_data.births_per_day(),
+            time.delta_seconds(),
+        )) {
+            continue;
+        }
+
+        if let Some(pos_to_spawn) = birthplace(tile_pos, &map_geometry, &tile_occupancy, rng) {
+            let energy_cost = energy_pool.max() * BIRTH_ENERGY_COST;
+            let remaining = energy_pool.current() - energy_cost;
+            energy_pool.set_current(remaining);
+
+            let offspring_entity = commands
+                .spawn(UnitBundle::new(unit_id, pos_to_spawn, unit_data.clone()))
+                .id();
+            // Parents that have not yet been added to the family tree leave their offspring to found their own lineage
+            if let Some(&parent) = maybe_lineage {
+                let lineage = family_tree.record_birth(offspring_entity, parent, today);
+                commands.entity(offspring_entity).insert(lineage);
+            }
+            tile_occupancy.add_unit(pos_to_spawn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::crowding::MAX_UNITS_PER_TILE;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn most_eggs_hatch_into_workers() {
+        assert_eq!(hatchling(0.), Id::soldier_ant());
+        assert_eq!(hatchling(SOLDIER_HATCH_CHANCE), Id::hauler_ant());
+        assert_eq!(
+            hatchling(SOLDIER_HATCH_CHANCE + HAULER_HATCH_CHANCE),
+            Id::scout_ant()
+        );
+        assert_eq!(hatchling(0.99), Id::ant());
+    }
+
+    #[test]
+    fn births_happen_at_the_configured_rate() {
+        assert_eq!(birth_chance(0., 1.), 0.);
+        assert!((birth_chance(2., DAY_LENGTH_IN_SECONDS / 4.) - 0.5).abs() < 1e-6);
+        // Very long frames can't make a birth more than certain
+        assert_eq!(birth_chance(1., DAY_LENGTH_IN_SECONDS * 10.), 1.);
+    }
+
+    #[test]
+    fn only_units_near_a_nest_can_breed() {
+        let nests = [TilePos::new(5, 0)];
+
+        assert!(is_near_nest(TilePos::new(5, 0), &nests));
+        assert!(is_near_nest(
+            TilePos::new(5 - NEST_RADIUS as i32, 0),
+            &nests
+        ));
+        assert!(!is_near_nest(TilePos::ORIGIN, &nests));
+        assert!(!is_near_nest(TilePos::ORIGIN, &[]));
+    }
+
+    #[test]
+    fn units_are_not_born_onto_full_tiles() {
+        let map_geometry = MapGeometry::new(3);
+        let mut tile_occupancy = TileOccupancy::default();
+        let rng = &mut StdRng::seed_from_u64(42);
+
+        let mut neighbors: Vec<TilePos> = TilePos::ORIGIN
+            .empty_neighbors(&map_geometry)
+            .into_iter()
+            .collect();
+        let last_free = neighbors.pop().unwrap();
+        for &neighbor in &neighbors {
+            for _ in 0..MAX_UNITS_PER_TILE {
+                tile_occupancy.add_unit(neighbor);
+            }
+        }
+
+        for _ in 0..10 {
+            assert_eq!(
+                birthplace(TilePos::ORIGIN, &map_geometry, &tile_occupancy, rng),
+                Some(last_free)
+            );
+        }
+
+        for _ in 0..MAX_UNITS_PER_TILE {
+            tile_occupancy.add_unit(last_free);
+        }
+        assert_eq!(
+            birthplace(TilePos::ORIGIN, &map_geometry, &tile_occupancy, rng),
+            None
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Units travelling to somewhere far away ask the [`PathfindingService`] for a route,
+//! rather than walking greedily into whatever is in their way.
+//!
+//! Most units are guided by signals instead, so routes are only used by soldiers returning to their patrol,
+//! and by units whose signals are too weak to have a gradient but who know of a concrete site to head for.
+//! Without a route, those units would wander indefinitely, waiting to stumble across a signal.
+
+use crate::bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use std::collections::VecDeque;
+
+use crate::{
+    manifest::{Id, Structure, Unit, UnitManifest},
+    signals::Signals,
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        pathfinding::{PathFound, PathRequest, PathfindingService},
+    },
+    structures::{
+        construction::{MarkedForDemolition, Preview},
+        crafting::CraftingState,
+        traps::Captured,
+    },
+};
+
+use super::{goals::Goal, soldiers::PATROL_RADIUS, UnitSystem};
+
+/// The priority of the routes requested by units.
+const ROUTE_PRIORITY: u8 = 0;
+
+/// Requests and stores routes for units.
+pub(super) struct RoutesPlugin;
+
+impl Plugin for RoutesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            request_routes
+                .after(UnitSystem::ChooseGoal)
+                .before(UnitSystem::ChooseNewAction),
+        )
+        .add_system(receive_routes.before(UnitSystem::ChooseNewAction));
+    }
+}
+
+/// The route a unit is following to reach its destination.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct Route {
+    /// The goal that this route was requested for
+    goal: Option<Goal>,
+    /// The tile that this route leads to, once one has been planned
+    destination: Option<TilePos>,
+    /// The tiles left to walk, in order
+    ///
+    /// This is empty if the destination has been reached, or could not be reached at all.
+    tiles: VecDeque<TilePos>,
+}
+
+impl Route {
+    /// Has a route to `destination` already been planned?
+    ///
+    /// This is true even if no path could be found, so unreachable destinations aren't searched for over and over.
+    fn is_planned_to(&self, destination: TilePos) -> bool {
+        self.destination == Some(destination)
+    }
+
+    /// Is this route still leading the unit somewhere to meet `goal`?
+    fn is_in_progress_for(&self, goal: &Goal) -> bool {
+        self.goal.as_ref() == Some(goal) && !self.tiles.is_empty()
+    }
+
+    /// The next tile that a unit standing on `unit_tile_pos` should step onto, or travel to along an express route.
+    ///
+    /// Returns `None` if the route has been completed, or if the unit has strayed from it.
+    /// Straying from the route forgets it, so that a new one will be planned.
+    pub fn next_step(
+        &mut self,
+        unit_tile_pos: TilePos,
+        map_geometry: &MapGeometry,
+    ) -> Option<TilePos> {
+        if let Some(index) = self.tiles.iter().position(|&tile| tile == unit_tile_pos) {
+            self.tiles.drain(..=index);
+        }
+
+        let express_exit = map_geometry
+            .express_index
+            .get(&unit_tile_pos)
+            .map(|link| link.exit);
+
+        match self.tiles.front() {
+            Some(&next_tile)
+                if next_tile.hex.distance_to(unit_tile_pos.hex) == 1
+                    || express_exit == Some(next_tile) =>
+            {
+                Some(next_tile)
+            }
+            Some(_) => {
+                *self = Route::default();
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// Finds the structures that units could travel to in order to meet their goals.
+#[derive(SystemParam)]
+struct SiteQuery<'w, 's> {
+    /// Every structure and ghost that could be travelled to
+    query: Query<
+        'w,
+        's,
+        (
+            &'static TilePos,
+            &'static Id<Structure>,
+            Option<&'static CraftingState>,
+            Option<&'static MarkedForDemolition>,
+        ),
+        Without<Preview>,
+    >,
+}
+
+impl<'w, 's> SiteQuery<'w, 's> {
+    /// Sorts every structure where a unit could meet its goal by the kind of structure.
+    fn index(&self) -> SiteIndex {
+        let mut site_index = SiteIndex::default();
+
+        for (&site, &structure_id, maybe_crafting_state, maybe_marked) in self.query.iter() {
+            if matches!(
+                maybe_crafting_state,
+                Some(CraftingState::InProgress {
+                    work_required: true,
+                    ..
+                })
+            ) {
+                site_index
+                    .workplaces
+                    .entry(structure_id)
+                    .or_default()
+                    .push(site);
+            }
+
+            if maybe_marked.is_some() {
+                site_index
+                    .demolition_sites
+                    .entry(structure_id)
+                    .or_default()
+                    .push(site);
+            }
+        }
+
+        site_index
+    }
+}
+
+/// The structures where units could meet their goals, indexed by structure id.
+///
+/// Each unit only needs to search through the structures of the kind it is looking for.
+#[derive(Debug, Default)]
+struct SiteIndex {
+    /// The tiles of the structures that are waiting for work, by structure id
+    workplaces: HashMap<Id<Structure>, Vec<TilePos>>,
+    /// The tiles of the structures that are marked for demolition, by structure id
+    demolition_sites: HashMap<Id<Structure>, Vec<TilePos>>,
+}
+
+impl SiteIndex {
+    /// The tile of the structure nearest to `tile_pos` where a unit could meet its `goal`.
+    ///
+    /// Returns `None` if there is no such structure, or if the `goal` is not met at a structure.
+    fn nearest(&self, goal: &Goal, tile_pos: TilePos) -> Option<TilePos> {
+        let sites = match *goal {
+            Goal::Work(structure_id) => self.workplaces.get(&structure_id),
+            Goal::Demolish(structure_id) => self.demolition_sites.get(&structure_id),
+            _ => None,
+        }?;
+
+        sites
+            .iter()
+            .copied()
+            .min_by_key(|site| site.hex.distance_to(tile_pos.hex))
+    }
+}
+
+/// The tile that a unit on `unit_tile_pos` should walk to in order to reach the structure on `site`.
+///
+/// Structures usually block movement, so this is the passable neighbor of the `site` closest to the unit,
+/// unless the `site` itself can be walked onto.
+/// Returns `None` if the `site` is completely surrounded.
+fn approach_tile(
+    site: TilePos,
+    unit_tile_pos: TilePos,
+    map_geometry: &MapGeometry,
+) -> Option<TilePos> {
+    if map_geometry.is_passable(site) {
+        return Some(site);
+    }
+
+    site.all_neighbors(map_geometry)
+        .into_iter()
+        .filter(|&neighbor| map_geometry.is_passable(neighbor))
+        .min_by_key(|neighbor| neighbor.hex.distance_to(unit_tile_pos.hex))
+}
+
+/// Requests routes for units that have somewhere concrete to go, but can't simply follow a signal there.
+///
+/// Soldiers far from their patrol always travel by route.
+/// Units looking for a workplace or demolition site only fall back to a route to the nearest site
+/// when the signals for their goal are too weak or flat to point the way.
+/// Once such a route has been requested, it is followed to the end rather than reconsidered every frame.
+fn request_routes(
+    mut unit_query: Query<(Entity, &TilePos, &Id<Unit>, &Goal, &mut Route), Without<Captured>>,
+    site_query: SiteQuery,
+    unit_manifest: Res<UnitManifest>,
+    signals: Res<Signals>,
+    map_geometry: Res<MapGeometry>,
+    mut pathfinding_service: ResMut<PathfindingService>,
+) {
+    // Only built if some unit actually needs to search for a site
+    let mut site_index: Option<SiteIndex> = None;
+
+    for (entity, &unit_tile_pos, &unit_id, goal, mut route) in unit_query.iter_mut() {
+        let destination = match *goal {
+            Goal::Patrol(center) => match unit_tile_pos.hex.distance_to(center.hex) > PATROL_RADIUS
+            {
+                true => Some(center),
+                false => None,
+            },
+            Goal::Work(_) | Goal::Demolish(_) => {
+                if route.is_in_progress_for(goal) || pathfinding_service.is_pending(entity) {
+                    continue;
+                }
+
+                let goal_weights = unit_manifest.get(unit_id).signal_sensitivity();
+                match signals.upstream(unit_tile_pos, goal, goal_weights, &map_geometry) {
+                    Some(_) => None,
+                    None => site_index
+                        .get_or_insert_with(|| site_query.index())
+                        .nearest(goal, unit_tile_pos)
+                        .and_then(|site| approach_tile(site, unit_tile_pos, &map_geometry)),
+                }
+            }
+            _ => None,
+        };
+
+        match destination {
+            Some(destination) => {
+                if !route.is_planned_to(destination) && !pathfinding_service.is_pending(entity) {
+                    route.goal = Some(goal.clone());
+                    pathfinding_service.request(PathRequest {
+                        requester: entity,
+                        start: unit_tile_pos,
+                        goal: destination,
+                        priority: ROUTE_PRIORITY,
+                    });
+                }
+            }
+            None => {
+                route.set_if_neq(Route::default());
+            }
+        }
+    }
+}
+
+/// Stores the answers from the [`PathfindingService`] on the units that asked for them.
+fn receive_routes(mut path_events: EventReader<PathFound>, mut route_query: Query<&mut Route>) {
+    for event in path_events.iter() {
+        // The unit may have died while waiting
+        if let Ok(mut route) = route_query.get_mut(event.requester) {
+            route.destination = Some(event.goal);
+            route.tiles = match &event.path {
+                Some(path) => path.tiles.iter().copied().collect(),
+                None => VecDeque::new(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::express::ExpressLink;
+
+    /// A route along the x-axis, from the origin to `length` tiles away.
+    fn straight_route(length: i32) -> Route {
+        Route {
+            goal: Some(Goal::Work(Id::from_string_id("test"))),
+            destination: Some(TilePos::new(length, 0)),
+            tiles: (0..=length).map(|x| TilePos::new(x, 0)).collect(),
+        }
+    }
+
+    #[test]
+    fn routes_are_followed_in_order() {
+        let map_geometry = MapGeometry::new(5);
+        let mut route = straight_route(3);
+
+        assert_eq!(
+            route.next_step(TilePos::ORIGIN, &map_geometry),
+            Some(TilePos::new(1, 0))
+        );
+        assert_eq!(
+            route.next_step(TilePos::new(1, 0), &map_geometry),
+            Some(TilePos::new(2, 0))
+        );
+        assert_eq!(
+            route.next_step(TilePos::new(2, 0), &map_geometry),
+            Some(TilePos::new(3, 0))
+        );
+        assert_eq!(route.next_step(TilePos::new(3, 0), &map_geometry), None);
+        assert!(route.is_planned_to(TilePos::new(3, 0)));
+    }
+
+    #[test]
+    fn routes_are_in_progress_until_completed() {
+        let map_geometry = MapGeometry::new(5);
+        let mut route = straight_route(1);
+        let goal = Goal::Work(Id::from_string_id("test"));
+
+        assert!(route.is_in_progress_for(&goal));
+        assert!(!route.is_in_progress_for(&Goal::Demolish(Id::from_string_id("test"))));
+
+        route.next_step(TilePos::ORIGIN, &map_geometry);
+        route.next_step(TilePos::new(1, 0), &map_geometry);
+        assert!(!route.is_in_progress_for(&goal));
+    }
+
+    #[test]
+    fn sites_are_found_by_structure_id() {
+        let acacia = Id::from_string_id("acacia");
+        let leuco = Id::from_string_id("leuco");
+        let mut site_index = SiteIndex::default();
+        site_index
+            .workplaces
+            .insert(acacia, vec![TilePos::new(3, 0), TilePos::new(1, 0)]);
+        site_index
+            .demolition_sites
+            .insert(leuco, vec![TilePos::new(2, 0)]);
+
+        assert_eq!(
+            site_index.nearest(&Goal::Work(acacia), TilePos::ORIGIN),
+            Some(TilePos::new(1, 0))
+        );
+        assert_eq!(
+            site_index.nearest(&Goal::Work(leuco), TilePos::ORIGIN),
+            None
+        );
+        assert_eq!(
+            site_index.nearest(&Goal::Demolish(leuco), TilePos::ORIGIN),
+            Some(TilePos::new(2, 0))
+        );
+        assert_eq!(site_index.nearest(&Goal::Wander, TilePos::ORIGIN), None);
+    }
+
+    #[test]
+    fn straying_forgets_the_route() {
+        let map_geometry = MapGeometry::new(5);
+        let mut route = straight_route(3);
+
+        assert_eq!(route.next_step(TilePos::new(0, 3), &map_geometry), None);
+        assert!(!route.is_planned_to(TilePos::new(3, 0)));
+    }
+
+    #[test]
+    fn routes_can_take_express_routes() {
+        let mut map_geometry = MapGeometry::new(5);
+        let exit = TilePos::new(3, 0);
+        map_geometry.express_index.insert(
+            TilePos::ORIGIN,
+            ExpressLink {
+                exit,
+                traversal_time: 1.,
+            },
+        );
+        let mut route = Route {
+            goal: None,
+            destination: Some(exit),
+            tiles: VecDeque::from([TilePos::ORIGIN, exit]),
+        };
+
+        assert_eq!(route.next_step(TilePos::ORIGIN, &map_geometry), Some(exit));
+    }
+
+    #[test]
+    fn structures_are_approached_from_the_nearest_open_side() {
+        let mut map_geometry = MapGeometry::new(5);
+        let site = TilePos::new(2, 0);
+        map_geometry
+            .structure_index
+            .insert(site, Entity::from_raw(0));
+
+        let approach = approach_tile(site, TilePos::ORIGIN, &map_geometry).unwrap();
+        assert_eq!(approach, TilePos::new(1, 0));
+
+        // Ghosts and other passable sites can be walked onto directly
+        let open_site = TilePos::new(-2, 0);
+        assert_eq!(
+            approach_tile(open_site, TilePos::ORIGIN, &map_geometry),
+            Some(open_site)
+        );
+
+        for neighbor in site.all_neighbors(&map_geometry) {
+            map_geometry
+                .structure_index
+                .insert(neighbor, Entity::from_raw(1));
+        }
+        assert_eq!(approach_tile(site, TilePos::ORIGIN, &map_geometry), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Soldiers are a caste of colony units that defend the colony rather than working.
+//!
+//! When things are calm, soldiers patrol around the nest, or around a guard post placed by the player.
+//! When the alarm is raised, they follow it to its source and attack any intruders they find there.
+
+use crate::bevy::prelude::*;
+
+use crate::{
+    manifest::{Id, Structure, Unit, UnitManifest},
+    organisms::energy::Energy,
+    simulation::geometry::TilePos,
+    structures::construction::{Ghost, Preview},
+};
+
+use super::{goals::Goal, UnitSystem};
+
+/// How far soldiers will stray from the center of their patrol before heading back.
+pub(super) const PATROL_RADIUS: i32 = 3;
+
+/// Assigns soldiers to guard posts, and sends idle soldiers out on patrol.
+pub(super) struct SoldiersPlugin;
+
+impl Plugin for SoldiersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(assign_guard_posts.before(UnitSystem::ChooseGoal))
+            .add_system(
+                start_patrols
+                    .in_set(UnitSystem::ChooseGoal)
+                    .after(super::alarm::respond_to_alarm),
+            );
+    }
+}
+
+/// The role that a unit plays in its colony.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Caste {
+    /// Follows signals to gather, haul, build and craft.
+    #[default]
+    Worker,
+    /// Patrols, and attacks intruders when the alarm is raised.
+    Soldier {
+        /// The amount of energy removed from an intruder with each attack
+        attack_damage: Energy,
+    },
+}
+
+/// A structure that soldiers can be assigned to patrol around.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuardPost {
+    /// The maximum number of soldiers that can be assigned to this post
+    pub capacity: usize,
+}
+
+/// The guard post that this soldier patrols around.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignedPost {
+    /// The guard post entity
+    pub post: Entity,
+}
+
+/// Picks the nearest post to `tile_pos` that still has room, from a list of `(post, position, open slots)`.
+fn nearest_open_post(tile_pos: TilePos, posts: &[(Entity, TilePos, usize)]) -> Option<usize> {
+    posts
+        .iter()
+        .enumerate()
+        .filter(|(_, &(_, _, open_slots))| open_slots > 0)
+        .min_by_key(|(_, &(_, post_pos, _))| tile_pos.hex.distance_to(post_pos.hex))
+        .map(|(index, _)| index)
+}
+
+/// Unassigns soldiers from posts that no longer exist, and assigns unassigned soldiers to the nearest post with room.
+fn assign_guard_posts(
+    soldier_query: Query<(Entity, &TilePos, &Id<Unit>, Option<&AssignedPost>)>,
+    post_query: Query<(Entity, &TilePos, &GuardPost), (Without<Ghost>, Without<Preview>)>,
+    unit_manifest: Res<UnitManifest>,
+    mut commands: Commands,
+) {
+    let mut posts: Vec<(Entity, TilePos, usize)> = post_query
+        .iter()
+        .map(|(entity, &tile_pos, guard_post)| (entity, tile_pos, guard_post.capacity))
+        .collect();
+
+    let mut unassigned = Vec::new();
+
+    for (entity, &tile_pos, &unit_id, maybe_assigned_post) in soldier_query.iter() {
+        if !matches!(unit_manifest.get(unit_id).caste(), Caste::Soldier { .. }) {
+            continue;
+        }
+
+        match maybe_assigned_post {
+            Some(assigned_post) => {
+                match posts
+                    .iter_mut()
+                    .find(|(post, ..)| *post == assigned_post.post)
+                {
+                    Some((_, _, open_slots)) => *open_slots = open_slots.saturating_sub(1),
+                    None => {
+                        commands.entity(entity).remove::<AssignedPost>();
+                        unassigned.push((entity, tile_pos));
+                    }
+                }
+            }
+            None => unassigned.push((entity, tile_pos)),
+        }
+    }
+
+    for (entity, tile_pos) in unassigned {
+        if let Some(index) = nearest_open_post(tile_pos, &posts) {
+            let (post, _, open_slots) = &mut posts[index];
+            *open_slots -= 1;
+            commands.entity(entity).insert(AssignedPost { post: *post });
+        }
+    }
+}
+
+/// Soldiers with nothing better to do patrol around their assigned post, or around the nearest nest.
+fn start_patrols(
+    mut soldier_query: Query<(&TilePos, &Id<Unit>, &mut Goal, Option<&AssignedPost>)>,
+    post_query: Query<&TilePos, With<GuardPost>>,
+    nest_query: Query<(&TilePos, &Id<Structure>), (Without<Ghost>, Without<Preview>)>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    for (&tile_pos, &unit_id, mut goal, maybe_assigned_post) in soldier_query.iter_mut() {
+        let unit_data = unit_manifest.get(unit_id);
+        if !matches!(unit_data.caste(), Caste::Soldier { .. }) {
+            continue;
+        }
+
+        if !matches!(*goal, Goal::Wander | Goal::Patrol(_)) {
+            continue;
+        }
+
+        let maybe_post_pos = maybe_assigned_post
+            .and_then(|assigned_post| post_query.get(assigned_post.post).ok())
+            .copied();
+
+        let maybe_center = match maybe_post_pos {
+            Some(post_pos) => Some(post_pos),
+            None => unit_data.nest().and_then(|nest_id| {
+                nest_query
+                    .iter()
+                    .filter(|(_, &structure_id)| structure_id == nest_id)
+                    .map(|(&nest_pos, _)| nest_pos)
+                    .min_by_key(|nest_pos| tile_pos.hex.distance_to(nest_pos.hex))
+            }),
+        };
+
+        let new_goal = match maybe_center {
+            Some(center) => Goal::Patrol(center),
+            None => Goal::Wander,
+        };
+
+        goal.set_if_neq(new_goal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_post_with_room_is_chosen() {
+        let near = TilePos::new(1, 0);
+        let far = TilePos::new(5, 0);
+        let mut posts = vec![
+            (Entity::from_raw(0), far, 1),
+            (Entity::from_raw(1), near, 1),
+        ];
+
+        assert_eq!(nearest_open_post(TilePos::ORIGIN, &posts), Some(1));
+
+        posts[1].2 = 0;
+        assert_eq!(nearest_open_post(TilePos::ORIGIN, &posts), Some(0));
+
+        posts[0].2 = 0;
+        assert_eq!(nearest_open_post(TilePos::ORIGIN, &posts), None);
+    }
+}
@@ -0,0 +1,159 @@
+//! Varieties of unit move, carry and prioritize their work differently.
+//!
+//! Workers do a bit of everything, haulers carry heavy loads between structures, and scouts range quickly in search of resources.
+//! The variety of each kind of unit is read from `units.ron`,
+//! falling back to the [`built_in_unit_varieties`] when that file cannot be loaded.
+
+use crate::bevy::utils::HashMap;
+
+use crate::{
+    manifest::{Id, Unit},
+    signals::{SignalCategory, SignalSensitivity},
+};
+
+use super::animation::{AnimationState, UnitAnimations};
+
+/// How a kind of unit moves, carries items and weighs up its goals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitVariety {
+    /// How quickly this unit walks, relative to a standard unit
+    pub walking_speed: f32,
+    /// The number of items this unit can carry at once
+    pub carry_capacity: usize,
+    /// How strongly this unit weighs each category of signal when choosing and pursuing a goal
+    pub goal_weights: SignalSensitivity,
+    /// The path to the model of this unit, relative to the asset directory
+    pub model: String,
+    /// The animation clip of the model shown for each thing this unit can be doing
+    pub animations: UnitAnimations,
+}
+
+impl UnitVariety {
+    /// A variety that walks at `walking_speed`, carries `carry_capacity` items and uses the `model` in the `units` folder.
+    ///
+    /// Every category of signal is weighed equally, and no animations are played.
+    fn new(walking_speed: f32, carry_capacity: usize, model: &str) -> Self {
+        UnitVariety {
+            walking_speed,
+            carry_capacity,
+            goal_weights: SignalSensitivity::default(),
+            model: format!("units/{model}.gltf#Scene0"),
+            animations: UnitAnimations::default(),
+        }
+    }
+
+    /// Plays the provided `animations` of this variety's model.
+    fn with_animations(mut self, animations: UnitAnimations) -> Self {
+        self.animations = animations;
+        self
+    }
+
+    /// Sets the weight given to signals of the provided `category`.
+    fn with_weight(mut self, category: SignalCategory, weight: f32) -> Self {
+        self.goal_weights = self.goal_weights.with_multiplier(category, weight);
+        self
+    }
+
+    /// Makes this variety ignore signals of the provided `category` entirely.
+    fn ignoring(mut self, category: SignalCategory) -> Self {
+        self.goal_weights = self.goal_weights.blind_to(category);
+        self
+    }
+}
+
+/// The animations of the ant model, which only has a walk cycle so far.
+fn ant_animations() -> UnitAnimations {
+    UnitAnimations::default()
+        .with_clip(AnimationState::Walking, 0)
+        .with_clip(AnimationState::Carrying, 0)
+}
+
+/// The variety of each kind of unit, used when `units.ron` cannot be loaded.
+pub fn built_in_unit_varieties() -> HashMap<Id<Unit>, UnitVariety> {
+    let mut map = HashMap::new();
+
+    map.insert(
+        Id::ant(),
+        UnitVariety::new(1., 1, "ant").with_animations(ant_animations()),
+    );
+
+    // Haulers are slow, but move goods around in bulk
+    map.insert(
+        Id::hauler_ant(),
+        UnitVariety::new(0.8, 3, "ant")
+            .with_animations(ant_animations())
+            .with_weight(SignalCategory::Push, 2.)
+            .with_weight(SignalCategory::Pull, 2.)
+            .with_weight(SignalCategory::Work, 0.25)
+            .with_weight(SignalCategory::Demolish, 0.25),
+    );
+
+    // Scouts are quick, and seek out stores of items rather than working
+    map.insert(
+        Id::scout_ant(),
+        UnitVariety::new(1.6, 1, "ant")
+            .with_animations(ant_animations())
+            .with_weight(SignalCategory::Push, 0.5)
+            .with_weight(SignalCategory::Pull, 0.5)
+            .with_weight(SignalCategory::Contains, 2.)
+            .ignoring(SignalCategory::Work)
+            .ignoring(SignalCategory::Demolish),
+    );
+
+    // Soldiers leave the logistics to the workers
+    map.insert(
+        Id::soldier_ant(),
+        UnitVariety::new(1.2, 1, "ant")
+            .with_animations(ant_animations())
+            .ignoring(SignalCategory::Push)
+            .ignoring(SignalCategory::Pull)
+            .ignoring(SignalCategory::Work)
+            .ignoring(SignalCategory::Demolish),
+    );
+
+    // Pests have no interest in the colony's logistics
+    // TODO: replace these with real models
+    map.insert(
+        Id::locust(),
+        UnitVariety::new(1.5, 1, "ant")
+            .with_animations(ant_animations())
+            .ignoring(SignalCategory::Push)
+            .ignoring(SignalCategory::Pull)
+            .ignoring(SignalCategory::Work)
+            .ignoring(SignalCategory::Demolish)
+            .ignoring(SignalCategory::Alarm),
+    );
+
+    // Beetles are slowed down by their heavy armor
+    map.insert(
+        Id::beetle(),
+        UnitVariety::new(0.6, 1, "ant")
+            .with_animations(ant_animations())
+            .ignoring(SignalCategory::Push)
+            .ignoring(SignalCategory::Pull)
+            .ignoring(SignalCategory::Work)
+            .ignoring(SignalCategory::Demolish)
+            .ignoring(SignalCategory::Alarm),
+    );
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn haulers_favor_logistics_over_work() {
+        let varieties = built_in_unit_varieties();
+        let hauler = &varieties[&Id::hauler_ant()];
+        let worker = &varieties[&Id::ant()];
+
+        assert!(hauler.carry_capacity > worker.carry_capacity);
+        assert!(
+            hauler.goal_weights.multiplier(SignalCategory::Pull)
+                > hauler.goal_weights.multiplier(SignalCategory::Work)
+        );
+        assert_eq!(worker.goal_weights, SignalSensitivity::default());
+    }
+}
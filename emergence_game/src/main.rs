@@ -1,23 +1,89 @@
 use bevy::prelude::*;
 use bevy::window::{PresentMode, WindowPlugin};
+use emergence_lib::player_interaction::recording::{InputPlayback, InputRecording};
 use emergence_lib::simulation::generation::GenerationConfig;
+use emergence_lib::simulation::invariants::{InvariantsPlugin, SoakTest};
+use emergence_lib::EmergencePlugins;
+use std::time::Duration;
+
+/// The default length of a soak test, in seconds of simulated time.
+const DEFAULT_SOAK_SECONDS: u64 = 600;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Emergence".to_string(),
-                present_mode: PresentMode::AutoNoVsync,
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(index) = args.iter().position(|arg| arg == "--playback") {
+        let path = args
+            .get(index + 1)
+            .expect("A path to an input recording must follow --playback");
+        let contents = std::fs::read_to_string(path).expect("Could not read the input recording");
+        let recording: InputRecording = match contents.parse() {
+            Ok(recording) => recording,
+            Err(error) => panic!("{error}"),
+        };
+        run_game(Some(InputPlayback::new(recording)));
+        return;
+    }
+
+    match args.iter().position(|arg| arg == "--soak") {
+        Some(index) => {
+            let seconds = match args.get(index + 1) {
+                Some(seconds) => seconds
+                    .parse()
+                    .expect("The duration of a soak test must be a whole number of seconds"),
+                None => DEFAULT_SOAK_SECONDS,
+            };
+            run_soak_test(Duration::from_secs(seconds));
+        }
+        None => run_game(None),
+    }
+}
+
+/// Runs the game normally, with a window.
+///
+/// If `maybe_playback` is provided, the recorded inputs are replayed as soon as the game starts.
+fn run_game(maybe_playback: Option<InputPlayback>) {
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Emergence".to_string(),
+                    present_mode: PresentMode::AutoNoVsync,
+                    ..default()
+                }),
+                ..Default::default()
+            })
+            .set(AssetPlugin {
+                watch_for_changes: cfg!(feature = "hot_reload"),
                 ..default()
             }),
-            ..Default::default()
-        }))
+    )
+    .add_plugins(EmergencePlugins {
+        gen_config: GenerationConfig::default(),
+    });
+
+    if let Some(playback) = maybe_playback {
+        app.insert_resource(playback);
+    }
+
+    app.run();
+}
+
+/// Runs the simulation headlessly for `duration`, checking invariants every frame.
+///
+/// The process panics and dumps the state of the world if any invariant is violated.
+fn run_soak_test(duration: Duration) {
+    info!("Running soak test for {} seconds", duration.as_secs());
+
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugin(bevy::log::LogPlugin::default())
+        .insert_resource(SoakTest { duration })
         .add_plugin(emergence_lib::simulation::SimulationPlugin {
             gen_config: GenerationConfig::default(),
         })
-        .add_plugin(emergence_lib::player_interaction::InteractionPlugin)
-        .add_plugin(emergence_lib::graphics::GraphicsPlugin)
-        .add_plugin(emergence_lib::ui::UiPlugin)
-        .add_plugin(emergence_lib::asset_management::AssetManagementPlugin)
+        .add_plugin(InvariantsPlugin)
         .run();
 }
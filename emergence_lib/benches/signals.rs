@@ -1,14 +1,17 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use bevy::tasks::TaskPoolBuilder;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use emergence_lib::asset_management::manifest::Id;
-use emergence_lib::signals::{SignalStrength, SignalType, Signals, DIFFUSION_FRACTION};
+use emergence_lib::signals::{SignalConfig, SignalStrength, SignalType, Signals};
 use emergence_lib::simulation::geometry::{MapGeometry, TilePos};
-use rand::thread_rng;
+use emergence_lib::simulation::wind::Wind;
+use emergence_lib::units::logistics::LogisticsMemory;
+use rand::{rngs::SmallRng, SeedableRng};
 
 /// Setup function
 fn add_signals(settings: Settings) -> (Signals, MapGeometry) {
     let mut signals = Signals::default();
     let map_geometry = MapGeometry::new(settings.map_radius);
-    let mut rng = thread_rng();
+    let mut rng = SmallRng::seed_from_u64(0);
 
     for i in 0..settings.n_signals {
         let signal_type = SignalType::Pull(Id::new(i));
@@ -26,10 +29,46 @@ fn add_signals(settings: Settings) -> (Signals, MapGeometry) {
 /// Benchmarks the signal diffusion process
 fn signal_diffusion(settings: Settings) {
     let (mut signals, map_geometry) = add_signals(settings);
-    signals.diffuse(&map_geometry, DIFFUSION_FRACTION);
+    signals.diffuse(&map_geometry, &SignalConfig::default(), &Wind::CALM);
+}
+
+/// Remembers a haul between each pair of signal sources, as a well-used colony would.
+fn remember_hauls(settings: Settings, enabled: bool) -> (Signals, MapGeometry, LogisticsMemory) {
+    let (signals, map_geometry) = add_signals(settings);
+    let mut logistics_memory = LogisticsMemory::new(enabled);
+    let mut rng = SmallRng::seed_from_u64(1);
+
+    for i in 0..settings.n_signals {
+        for _ in 0..settings.n_sources {
+            let start = TilePos::random(&map_geometry, &mut rng);
+            let end = TilePos::random(&map_geometry, &mut rng);
+            let corridor: Vec<TilePos> = start
+                .line_to(*end)
+                .map(|hex| TilePos::new(hex.x, hex.y))
+                .collect();
+
+            logistics_memory.record_haul(Id::new(i), &corridor);
+        }
+    }
+
+    (signals, map_geometry, logistics_memory)
+}
+
+/// A full signal tick, including the upkeep of the logistics memory if it is enabled.
+fn signal_tick(
+    signals: &mut Signals,
+    map_geometry: &MapGeometry,
+    logistics_memory: &mut LogisticsMemory,
+) {
+    if logistics_memory.is_enabled() {
+        logistics_memory.fade();
+        logistics_memory.emit(signals);
+    }
+    signals.diffuse(map_geometry, &SignalConfig::default(), &Wind::CALM);
 }
 
 /// Benchmark settings, in a reusable form
+#[derive(Clone, Copy)]
 struct Settings {
     map_radius: u32,
     n_signals: u64,
@@ -60,6 +99,13 @@ impl Settings {
         n_sources: 100 * 100 / Self::SPARSITY,
     };
 
+    /// Roughly a 256 by 256 map
+    const LARGE: Settings = Settings {
+        map_radius: 128,
+        n_signals: 20,
+        n_sources: 256 * 256 / Self::SPARSITY,
+    };
+
     // const HUGE: Settings = Settings {
     //     map_radius: 1000,
     //     n_signals: 1000,
@@ -88,6 +134,49 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("signal_diffusion_modest", |b| {
         b.iter(|| signal_diffusion(Settings::MODEST))
     });
+
+    // Compares a signal tick with the logistics memory against the baseline without it
+    for enabled in [false, true] {
+        let name = if enabled {
+            "signal_tick_modest_with_logistics_memory"
+        } else {
+            "signal_tick_modest_without_logistics_memory"
+        };
+
+        c.bench_function(name, |b| {
+            b.iter_batched(
+                || remember_hauls(Settings::MODEST, enabled),
+                |(mut signals, map_geometry, mut logistics_memory)| {
+                    signal_tick(&mut signals, &map_geometry, &mut logistics_memory)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    // Compares diffusion on a single thread to diffusion spread across every available core
+    let serial_pool = TaskPoolBuilder::new().num_threads(1).build();
+    let parallel_pool = TaskPoolBuilder::new().build();
+    let config = SignalConfig::default();
+
+    c.bench_function("signal_diffusion_large_serial", |b| {
+        b.iter_batched(
+            || add_signals(Settings::LARGE),
+            |(mut signals, map_geometry)| {
+                signals.diffuse_with(&map_geometry, &config, &Wind::CALM, &serial_pool)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("signal_diffusion_large_parallel", |b| {
+        b.iter_batched(
+            || add_signals(Settings::LARGE),
+            |(mut signals, map_geometry)| {
+                signals.diffuse_with(&map_geometry, &config, &Wind::CALM, &parallel_pool)
+            },
+            BatchSize::LargeInput,
+        )
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
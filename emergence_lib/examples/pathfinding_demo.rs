@@ -0,0 +1,85 @@
+//! Finds a path across a small map split by a ridge, and draws it as text.
+//!
+//! Units cannot climb cliffs, so the path must detour through the gap in the ridge.
+//!
+//! Run with `cargo run --example pathfinding_demo`.
+
+use emergence_lib::simulation::geometry::{MapGeometry, TilePos};
+use emergence_lib::simulation::pathfinding::find_path;
+
+/// The number of tiles from the center of the map to its edge.
+const MAP_RADIUS: u32 = 6;
+
+/// The height of the ridge, which is far too steep to climb.
+const RIDGE_HEIGHT: f32 = 3.;
+
+/// The tile where the path starts.
+const START: TilePos = TilePos::ORIGIN;
+
+fn main() {
+    let mut map_geometry = MapGeometry::new(MAP_RADIUS);
+    let radius = MAP_RADIUS as i32;
+
+    // A ridge runs across the map, with a single gap near one end
+    let gap = TilePos::new(2, -radius + 1);
+    let ridge: Vec<TilePos> = (-radius..=radius)
+        .map(|y| TilePos::new(2, y))
+        .filter(|&tile_pos| map_geometry.is_valid(tile_pos) && tile_pos != gap)
+        .collect();
+    for &tile_pos in &ridge {
+        map_geometry.set_height(tile_pos, RIDGE_HEIGHT);
+    }
+
+    let goal = TilePos::new(4, 0);
+    let path = find_path(&map_geometry, START, goal, 1., |origin, target| {
+        map_geometry.slope_cost(origin, target)
+    });
+
+    match path {
+        Some(path) => {
+            println!(
+                "Found a path of {} steps, costing {:.1}:",
+                path.steps(),
+                path.cost
+            );
+            draw_map(&map_geometry, &ridge, &path.tiles, goal);
+        }
+        None => println!("No path could be found from {START} to {goal}."),
+    }
+}
+
+/// Draws the map, with each row of hexes offset to line up with its neighbors.
+fn draw_map(map_geometry: &MapGeometry, ridge: &[TilePos], path: &[TilePos], goal: TilePos) {
+    let radius = MAP_RADIUS as i32;
+
+    for y in -radius..=radius {
+        let mut line = String::new();
+        for x in -radius..=radius {
+            let tile_pos = TilePos::new(x, y);
+            if !map_geometry.is_valid(tile_pos) {
+                continue;
+            }
+
+            // Each row is shifted by half a tile relative to the one above it
+            let column = (2 * x + y + 2 * radius) as usize;
+            while line.len() < column {
+                line.push(' ');
+            }
+
+            let symbol = if tile_pos == START {
+                'S'
+            } else if tile_pos == goal {
+                'G'
+            } else if path.contains(&tile_pos) {
+                '*'
+            } else if ridge.contains(&tile_pos) {
+                '#'
+            } else {
+                '.'
+            };
+            line.push(symbol);
+        }
+
+        println!("{line}");
+    }
+}
@@ -0,0 +1,81 @@
+//! Drops a few signals onto an empty map, lets them spread, and prints how they change over time.
+//!
+//! Useful for tuning the diffusion and degradation rates in [`SignalConfig`] without running the whole game.
+//!
+//! Run with `cargo run --example signals_playground`, or add `--windy` to see signals carried downwind.
+
+use emergence_lib::signals::{SignalConfig, SignalRates, SignalStrength, SignalType, Signals};
+use emergence_lib::simulation::geometry::{MapGeometry, TilePos};
+use emergence_lib::simulation::wind::Wind;
+use hexx::Direction;
+
+/// The number of tiles from the center of the map to its edge.
+const MAP_RADIUS: u32 = 8;
+
+/// The number of times signals are diffused and degraded.
+const N_TICKS: usize = 20;
+
+/// How often the state of the signals is printed, in ticks.
+const PRINT_INTERVAL: usize = 5;
+
+fn main() {
+    let windy = std::env::args().any(|arg| arg == "--windy");
+    let wind = if windy {
+        Wind::new(Direction::BottomRight, 0.2)
+    } else {
+        Wind::CALM
+    };
+
+    let map_geometry = MapGeometry::new(MAP_RADIUS);
+    // Danger fades much faster than the default, so the two signals can be compared
+    let config =
+        SignalConfig::default().with_type_rates(SignalType::Danger, SignalRates::new(0.1, 0.2));
+
+    let mut signals = Signals::default();
+    signals.add_signal(
+        SignalType::Alarm,
+        TilePos::new(-4, 0),
+        SignalStrength::new(100.),
+    );
+    signals.add_signal(
+        SignalType::Danger,
+        TilePos::new(4, 0),
+        SignalStrength::new(100.),
+    );
+
+    for tick in 0..=N_TICKS {
+        if tick % PRINT_INTERVAL == 0 {
+            print_signals(&signals, tick);
+        }
+
+        signals.diffuse(&map_geometry, &config, &wind);
+        signals.degrade(&config);
+    }
+}
+
+/// Prints the strength of each signal along the row of tiles through the center of the map.
+fn print_signals(signals: &Signals, tick: usize) {
+    println!("Tick {tick}");
+
+    let radius = MAP_RADIUS as i32;
+    for signal_type in signals.signal_types() {
+        let row: Vec<String> = (-radius..=radius)
+            .map(|x| {
+                format!(
+                    "{:5.1}",
+                    signals.get(signal_type, TilePos::new(x, 0)).value()
+                )
+            })
+            .collect();
+
+        println!(
+            "  {signal_type:>6} (max {:5.1}): {}",
+            signals.max_strength(signal_type).value(),
+            row.join(" ")
+        );
+    }
+
+    for (signal_type, total) in signals.total_strengths() {
+        println!("  Total {signal_type}: {:.1}", total.value());
+    }
+}
@@ -0,0 +1,82 @@
+//! Runs the simulation headlessly with a crowd of ants, logging how long each frame takes.
+//!
+//! Use this to check the performance impact of changes to unit behavior, pathfinding or signals.
+//!
+//! Run with `cargo run --release --example stress_test -- <number of ants> <number of frames>`.
+
+use bevy::app::AppExit;
+use bevy::diagnostic::{
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use emergence_lib::simulation::geometry::{MapGeometry, TilePos};
+use emergence_lib::terrain::Terrain;
+use emergence_lib::testing::{minimal_app, WorldBuilder};
+use rand::{rngs::SmallRng, SeedableRng};
+
+/// The number of tiles from the center of the map to its edge.
+const MAP_RADIUS: u32 = 50;
+
+/// The number of ants spawned if none is provided.
+const DEFAULT_N_ANTS: usize = 500;
+
+/// The number of frames to run if none is provided.
+const DEFAULT_N_FRAMES: usize = 1000;
+
+/// The number of frames to run before exiting.
+#[derive(Resource, Debug)]
+struct FrameLimit(usize);
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let n_ants = match args.first() {
+        Some(n_ants) => n_ants
+            .parse()
+            .expect("The number of ants must be a whole number"),
+        None => DEFAULT_N_ANTS,
+    };
+    let n_frames = match args.get(1) {
+        Some(n_frames) => n_frames
+            .parse()
+            .expect("The number of frames must be a whole number"),
+        None => DEFAULT_N_FRAMES,
+    };
+
+    // Only used to pick valid positions: the simulation creates its own
+    let map_geometry = MapGeometry::new(MAP_RADIUS);
+
+    let mut world_builder = WorldBuilder::hex_radius(MAP_RADIUS)
+        .with_terrain_fill(Terrain::Plain)
+        .spawn_structure("ant_hive", (0, 0));
+    // Seeded from the world, so that every run of the stress test does the same work
+    let mut rng = SmallRng::seed_from_u64(world_builder.gen_config().seed());
+    for _ in 0..n_ants {
+        world_builder = world_builder.spawn_unit("ant", TilePos::random(&map_geometry, &mut rng));
+    }
+
+    info!("Running stress test with {n_ants} ants for {n_frames} frames");
+
+    let mut app = minimal_app();
+    app.add_plugin(bevy::log::LogPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(EntityCountDiagnosticsPlugin)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .insert_resource(FrameLimit(n_frames))
+        .add_system(exit_after_frame_limit);
+    world_builder.build(&mut app);
+
+    app.run();
+}
+
+/// Exits the app once the [`FrameLimit`] has been reached.
+fn exit_after_frame_limit(
+    frame_limit: Res<FrameLimit>,
+    mut frames: Local<usize>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    *frames += 1;
+
+    if *frames >= frame_limit.0 {
+        exit_events.send(AppExit);
+    }
+}
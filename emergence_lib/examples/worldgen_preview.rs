@@ -0,0 +1,50 @@
+//! Generates a map without any organisms, so the terrain can be inspected with the usual camera controls.
+//!
+//! Run with `cargo run --example worldgen_preview -- <seed>`.
+
+use bevy::asset::AssetPlugin;
+use bevy::prelude::*;
+use emergence_lib::asset_management::AssetManagementPlugin;
+use emergence_lib::graphics::GraphicsPlugin;
+use emergence_lib::player_interaction::InteractionPlugin;
+use emergence_lib::testing::WorldBuilder;
+
+/// The number of tiles from the center of the map to its edge.
+const MAP_RADIUS: u32 = 30;
+
+/// The seed used if none is provided.
+const DEFAULT_SEED: u64 = 2378;
+
+fn main() {
+    let seed = match std::env::args().nth(1) {
+        Some(seed) => seed.parse().expect("The seed must be a whole number"),
+        None => DEFAULT_SEED,
+    };
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: format!("Emergence world generation preview (seed {seed})"),
+                    ..default()
+                }),
+                ..default()
+            })
+            // The assets live alongside the game, rather than the library
+            .set(AssetPlugin {
+                asset_folder: "../emergence_game/assets".to_string(),
+                ..default()
+            }),
+    );
+
+    // The UI is left out, to keep the terrain unobstructed
+    WorldBuilder::hex_radius(MAP_RADIUS)
+        .with_seed(seed)
+        .build(&mut app);
+    app.add_plugin(InteractionPlugin)
+        .add_plugin(GraphicsPlugin)
+        .add_plugin(AssetManagementPlugin);
+
+    app.run();
+}
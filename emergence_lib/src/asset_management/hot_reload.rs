@@ -0,0 +1,198 @@
+//! Applies changes to the definition files while the game is running, to speed up content iteration.
+//!
+//! Models are reloaded by the [`AssetServer`] itself, once it has been told to watch for changes.
+//! The definition files are read outside of the asset server, so they are polled here instead:
+//! tweaked item, recipe, structure and unit numbers replace the manifests, and structures whose model was changed swap scenes in place.
+//! Structures that are already built keep their footprint and construction cost.
+
+use bevy::{prelude::*, utils::HashMap};
+use std::{fs, path::PathBuf, time::SystemTime};
+
+use crate::asset_management::manifest::{
+    definitions::{
+        definitions_directory, load_items, load_recipes, load_structure_models, load_structures,
+        load_unit_varieties,
+    },
+    Id, ItemManifest, RecipeManifest, Structure, StructureManifest, UnitManifest,
+};
+
+use super::{structures::StructureHandles, units::UnitHandles};
+
+/// How often the definition files are checked for changes, in seconds.
+const POLL_INTERVAL: f32 = 1.;
+
+/// The definition files that are watched for changes.
+const DEFINITION_FILES: [&str; 4] = ["items.ron", "recipes.ron", "structures.ron", "units.ron"];
+
+/// Reloads definition files when they change on disk.
+pub(super) struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DefinitionWatcher::new(definitions_directory()))
+            .add_system(reload_changed_definitions);
+    }
+}
+
+/// Tracks when each definition file was last modified.
+#[derive(Resource, Debug)]
+struct DefinitionWatcher {
+    /// The directory containing the definition files
+    directory: PathBuf,
+    /// Counts down to the next check
+    timer: Timer,
+    /// When each file was last modified, or `None` if it does not exist
+    modified: HashMap<&'static str, Option<SystemTime>>,
+}
+
+impl DefinitionWatcher {
+    /// Starts watching the definition files in `directory`, as they are now.
+    fn new(directory: PathBuf) -> Self {
+        let mut watcher = DefinitionWatcher {
+            directory,
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            modified: HashMap::default(),
+        };
+        watcher.changed_files();
+        watcher
+    }
+
+    /// When the file `file_name` was last modified.
+    fn last_modified(&self, file_name: &str) -> Option<SystemTime> {
+        fs::metadata(self.directory.join(file_name))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// The files that have been modified, created or deleted since the last check.
+    fn changed_files(&mut self) -> Vec<&'static str> {
+        let mut changed_files = Vec::new();
+
+        for file_name in DEFINITION_FILES {
+            let last_modified = self.last_modified(file_name);
+            if self.modified.insert(file_name, last_modified) != Some(last_modified) {
+                changed_files.push(file_name);
+            }
+        }
+
+        changed_files
+    }
+}
+
+/// Reloads the definition files that have changed, keeping the current definitions if the new ones are broken.
+#[allow(clippy::too_many_arguments)]
+fn reload_changed_definitions(
+    time: Res<Time>,
+    mut watcher: ResMut<DefinitionWatcher>,
+    mut item_manifest: ResMut<ItemManifest>,
+    mut recipe_manifest: ResMut<RecipeManifest>,
+    mut structure_manifest: ResMut<StructureManifest>,
+    mut unit_manifest: ResMut<UnitManifest>,
+    mut structure_handles: ResMut<StructureHandles>,
+    mut unit_handles: ResMut<UnitHandles>,
+    mut scene_query: Query<&mut Handle<Scene>, With<Id<Structure>>>,
+    asset_server: Res<AssetServer>,
+) {
+    // Real time is used, so that definitions can be tweaked while the game is paused
+    if !watcher.timer.tick(time.raw_delta()).just_finished() {
+        return;
+    }
+
+    let changed_files = watcher.changed_files();
+    if changed_files.is_empty() {
+        return;
+    }
+    info!("Reloading definitions, as {changed_files:?} changed");
+
+    // Recipes are checked against the items, so both must be reloaded when either changes
+    if changed_files.contains(&"items.ron") || changed_files.contains(&"recipes.ron") {
+        let reloaded = load_items(&watcher.directory).and_then(|new_items| {
+            load_recipes(&watcher.directory, &new_items).map(|new_recipes| (new_items, new_recipes))
+        });
+
+        match reloaded {
+            Ok((new_items, new_recipes)) => {
+                *item_manifest = new_items;
+                *recipe_manifest = new_recipes;
+            }
+            Err(error) => error!("Keeping the current items and recipes: {error}"),
+        }
+    }
+
+    // Construction materials are checked against the items, so structures are reloaded when either changes
+    if changed_files.contains(&"items.ron") || changed_files.contains(&"structures.ron") {
+        match load_structures(&watcher.directory, &item_manifest) {
+            Ok(new_structures) => *structure_manifest = new_structures,
+            Err(error) => error!("Keeping the current structures: {error}"),
+        }
+    }
+
+    if changed_files.contains(&"structures.ron") {
+        match load_structure_models(&watcher.directory) {
+            Ok(structure_models) => {
+                let mut swapped_scenes = HashMap::new();
+
+                for (structure_id, model_path) in structure_models {
+                    let new_scene = asset_server.load(model_path);
+                    if let Some(old_scene) = structure_handles
+                        .scenes
+                        .insert(structure_id, new_scene.clone())
+                    {
+                        if old_scene != new_scene {
+                            swapped_scenes.insert(old_scene, new_scene);
+                        }
+                    }
+                }
+
+                // Only scenes that match the old model are swapped, so ghosts keep their construction models
+                for mut scene_handle in scene_query.iter_mut() {
+                    if let Some(new_scene) = swapped_scenes.get(&*scene_handle) {
+                        *scene_handle = new_scene.clone();
+                    }
+                }
+            }
+            Err(error) => error!("Keeping the current structure models: {error}"),
+        }
+    }
+
+    // Units already in the world keep their models, but move and choose goals according to their new variety
+    if changed_files.contains(&"units.ron") {
+        match load_unit_varieties(&watcher.directory) {
+            Ok(unit_varieties) => {
+                for (&unit_id, variety) in unit_varieties.iter() {
+                    let new_scene = asset_server.load(variety.model.clone());
+                    unit_handles.scenes.insert(unit_id, new_scene);
+                }
+
+                *unit_manifest = UnitManifest::with_varieties(unit_varieties);
+            }
+            Err(error) => error!("Keeping the current unit varieties: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_and_deleted_files_are_noticed() {
+        let directory =
+            std::env::temp_dir().join(format!("emergence_hot_reload_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let items_path = directory.join("items.ron");
+        let _ = fs::remove_file(&items_path);
+
+        let mut watcher = DefinitionWatcher::new(directory.clone());
+        assert!(watcher.changed_files().is_empty());
+
+        fs::write(&items_path, "[]").unwrap();
+        assert_eq!(watcher.changed_files(), vec!["items.ron"]);
+        assert!(watcher.changed_files().is_empty());
+
+        fs::remove_file(&items_path).unwrap();
+        assert_eq!(watcher.changed_files(), vec!["items.ron"]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
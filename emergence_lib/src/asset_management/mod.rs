@@ -10,18 +10,20 @@
 //! - sitting just on top of the XY plane
 //! - be exported as embedded gltF files
 
-use std::any::TypeId;
+use std::{any::TypeId, collections::BTreeMap, fmt::Display};
 
 use self::{structures::StructureHandles, terrain::TerrainHandles, units::UnitHandles};
 use bevy::{
-    asset::LoadState,
+    asset::{Asset, LoadState},
     prelude::*,
     render::{mesh::Indices, render_resource::PrimitiveTopology},
     utils::HashSet,
 };
 use hexx::{Hex, HexLayout, MeshInfo};
 
-pub mod manifest;
+#[cfg(feature = "hot_reload")]
+mod hot_reload;
+pub use emergence_core::manifest;
 pub(crate) mod palette;
 pub(crate) mod structures;
 pub(crate) mod terrain;
@@ -35,7 +37,11 @@ impl Plugin for AssetManagementPlugin {
         app.init_resource::<TerrainHandles>()
             .add_state::<AssetState>()
             .add_asset_collection::<StructureHandles>()
-            .add_asset_collection::<UnitHandles>();
+            .add_asset_collection::<UnitHandles>()
+            .add_system(report_loading_time.in_schedule(OnEnter(AssetState::Ready)));
+
+        #[cfg(feature = "hot_reload")]
+        app.add_plugin(hot_reload::HotReloadPlugin);
     }
 }
 
@@ -69,12 +75,18 @@ impl AssetsToLoad {
         self.set.remove(&type_id);
     }
 
-    /// A system that checks if the asset collection of type `T` loaded.
+    /// A system that checks if the asset collection of type `T` loaded, and records how far along it is.
     fn check_loaded<T: Loadable>(
         asset_collection: Res<T>,
         asset_server: Res<AssetServer>,
         mut assets_to_load: ResMut<AssetsToLoad>,
+        loading_progress: ResMut<LoadingProgress>,
     ) {
+        let progress = asset_collection.load_progress(&asset_server);
+        loading_progress
+            .map_unchanged(|loading_progress| loading_progress.layers.entry(T::NAME).or_default())
+            .set_if_neq(progress);
+
         if asset_collection.load_state(&asset_server) == LoadState::Loaded {
             assets_to_load.remove::<T>();
         }
@@ -91,12 +103,96 @@ impl AssetsToLoad {
     }
 }
 
+/// Logs how long it took to load every asset, to help spot slow startups.
+fn report_loading_time(time: Res<Time>, loading_progress: Res<LoadingProgress>) {
+    let total = loading_progress.total();
+    info!(
+        "Loaded {} assets in {:.2} s",
+        total.total,
+        time.raw_elapsed_seconds()
+    );
+}
+
 /// An asset collection that must be loaded before the game can start.
 ///
 /// This asset collection should begin async asset loading in its [`FromWorld`] implementation.
 pub trait Loadable: Resource + FromWorld + Sized {
+    /// The name of this collection, as shown while loading.
+    const NAME: &'static str;
+
     /// How far along are we in loading these assets?
     fn load_state(&self, asset_server: &AssetServer) -> LoadState;
+
+    /// How many of these assets have finished loading?
+    fn load_progress(&self, asset_server: &AssetServer) -> LoadProgress;
+}
+
+/// The number of assets in a collection that have finished loading.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// The number of assets that are fully loaded
+    pub loaded: usize,
+    /// The number of assets in the collection
+    pub total: usize,
+}
+
+impl LoadProgress {
+    /// Counts how many of `handles` have finished loading.
+    pub fn from_handles<'a, T: Asset>(
+        handles: impl IntoIterator<Item = &'a Handle<T>>,
+        asset_server: &AssetServer,
+    ) -> Self {
+        handles
+            .into_iter()
+            .fold(LoadProgress::default(), |progress, handle| LoadProgress {
+                loaded: progress.loaded
+                    + (asset_server.get_load_state(handle) == LoadState::Loaded) as usize,
+                total: progress.total + 1,
+            })
+    }
+
+    /// The fraction of assets that have finished loading, between 0 and 1.
+    ///
+    /// An empty collection is always complete.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+}
+
+impl Display for LoadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.loaded, self.total)
+    }
+}
+
+/// How far along each [`Loadable`] collection is in loading, keyed by its [`Loadable::NAME`].
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct LoadingProgress {
+    /// The progress of each collection
+    layers: BTreeMap<&'static str, LoadProgress>,
+}
+
+impl LoadingProgress {
+    /// Iterates over the progress of each collection, in alphabetical order.
+    pub fn layers(&self) -> impl Iterator<Item = (&'static str, LoadProgress)> + '_ {
+        self.layers
+            .iter()
+            .map(|(&name, &progress)| (name, progress))
+    }
+
+    /// The combined progress of every collection.
+    pub fn total(&self) -> LoadProgress {
+        self.layers
+            .values()
+            .fold(LoadProgress::default(), |total, progress| LoadProgress {
+                loaded: total.loaded + progress.loaded,
+                total: total.total + progress.total,
+            })
+    }
 }
 
 /// An [`App`] extension trait to add and setup [`Loadable`] collections.
@@ -114,7 +210,9 @@ impl AssetCollectionExt for App {
             self.add_system(
                 AssetsToLoad::transition_when_complete.run_if(in_state(AssetState::Loading)),
             );
-            self.init_resource::<AssetsToLoad>();
+            self.init_resource::<AssetsToLoad>()
+                .init_resource::<LoadingProgress>();
+            self.world.resource_mut::<AssetsToLoad>().insert::<T>();
         }
 
         // Store the asset collection as a resource
@@ -127,7 +225,7 @@ impl AssetCollectionExt for App {
 }
 
 /// Constructs the mesh for a single hexagonal column with the specified height.
-fn hexagonal_column(hex_layout: &HexLayout, hex_height: f32) -> Mesh {
+pub(crate) fn hexagonal_column(hex_layout: &HexLayout, hex_height: f32) -> Mesh {
     let mesh_info = MeshInfo::partial_hexagonal_column(hex_layout, Hex::ZERO, hex_height);
     let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_info.vertices.to_vec());
@@ -136,3 +234,34 @@ fn hexagonal_column(hex_layout: &HexLayout, hex_height: f32) -> Mesh {
     mesh.set_indices(Some(Indices::U16(mesh_info.indices)));
     mesh
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loading_progress_is_combined_across_layers() {
+        let mut loading_progress = LoadingProgress::default();
+        assert_eq!(loading_progress.total().fraction(), 1.);
+
+        loading_progress.layers.insert(
+            "Structures",
+            LoadProgress {
+                loaded: 1,
+                total: 3,
+            },
+        );
+        loading_progress.layers.insert(
+            "Units",
+            LoadProgress {
+                loaded: 1,
+                total: 1,
+            },
+        );
+
+        let total = loading_progress.total();
+        assert_eq!(total.loaded, 2);
+        assert_eq!(total.total, 4);
+        assert_eq!(total.fraction(), 0.5);
+    }
+}
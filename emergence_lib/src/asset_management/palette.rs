@@ -1,6 +1,7 @@
 //! A central source of truth for the game and UI's color palettes.
 
-use bevy::prelude::Color;
+use bevy::prelude::{Color, Resource};
+use emergence_macros::IterableEnum;
 
 /// The hue of selected objects
 pub(crate) const SELECTION_HUE: f32 = 100.;
@@ -42,6 +43,13 @@ pub(crate) const SELECTED_GHOST_COLOR: Color = Color::hsla(
     SELECTION_LIGHTNESS,
     GHOST_ALPHA,
 );
+/// The color used to tint ghosts whose tile does not suit them
+pub(crate) const UNSUITABLE_GHOST_COLOR: Color = Color::hsla(
+    FORBIDDEN_HUE,
+    HOVER_SATURATION,
+    GHOST_LIGHTNESS,
+    GHOST_ALPHA,
+);
 
 /// The color used to tint previews
 pub(crate) const PREVIEW_COLOR: Color =
@@ -60,3 +68,54 @@ pub(crate) const SELECTION_AND_HOVER_COLOR: Color = Color::hsl(
     (SELECTION_SATURATION + HOVER_SATURATION) / 2.,
     (SELECTION_LIGHTNESS + HOVER_LIGHTNESS) / 2.,
 );
+
+/// The set of colors used to encode game state that players need to tell apart at a glance.
+///
+/// Insert this resource before the graphics plugin to change the starting palette.
+#[derive(Resource, IterableEnum, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ColorPalette {
+    /// The default colors.
+    #[default]
+    Standard,
+    /// Colors drawn from the Okabe-Ito palette, which stay distinct for the common forms of color blindness.
+    Colorblind,
+}
+
+/// The color used to tint tiles that contain members of the colony.
+pub(crate) const fn colony_tint(palette: ColorPalette) -> Color {
+    match palette {
+        ColorPalette::Standard => Color::rgb(0.3, 0.5, 0.9),
+        ColorPalette::Colorblind => Color::rgb(0.0, 0.45, 0.7),
+    }
+}
+
+/// The color used to tint tiles that contain wild creatures.
+pub(crate) const fn wild_tint(palette: ColorPalette) -> Color {
+    match palette {
+        ColorPalette::Standard => Color::rgb(0.85, 0.35, 0.2),
+        ColorPalette::Colorblind => Color::rgb(0.9, 0.62, 0.0),
+    }
+}
+
+/// The color used to tint tiles that contain a burning organism.
+pub(crate) const fn burning_tint(palette: ColorPalette) -> Color {
+    match palette {
+        ColorPalette::Standard => Color::rgb(1.0, 0.27, 0.0),
+        ColorPalette::Colorblind => Color::rgb(0.84, 0.37, 0.0),
+    }
+}
+
+/// The color used to tint tiles that contain a captured creature.
+pub(crate) const fn captured_tint(palette: ColorPalette) -> Color {
+    match palette {
+        ColorPalette::Standard => Color::rgb(0.6, 0.2, 0.8),
+        ColorPalette::Colorblind => Color::rgb(0.8, 0.47, 0.65),
+    }
+}
+
+/// The color used to tint tiles that contain a dormant organism, which cannot currently grow.
+///
+/// This is a dim grey in every palette, as it is distinguished by its lightness rather than its hue.
+pub(crate) const fn dormant_tint(_palette: ColorPalette) -> Color {
+    Color::rgb(0.3, 0.3, 0.3)
+}
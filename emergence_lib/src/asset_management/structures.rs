@@ -3,15 +3,56 @@
 use crate::{
     asset_management::hexagonal_column, enum_iter::IterableEnum,
     player_interaction::selection::ObjectInteraction, simulation::geometry::MapGeometry,
-    structures::construction::GhostKind,
 };
 use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use emergence_macros::IterableEnum;
 
 use super::{
-    manifest::{Id, Structure},
-    Loadable,
+    manifest::{
+        definitions::{definitions_directory, load_structure_models, loaded_or_built_in},
+        Id, Structure,
+    },
+    LoadProgress, Loadable,
 };
 
+/// The variety of ghostly structure.
+#[derive(IterableEnum, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum GhostKind {
+    /// A structure that is going to be built.
+    Ghost,
+    /// A ghost, but currently selected
+    SelectedGhost,
+    /// A ghost whose tile did not suit it when it was zoned, which waits rather than being built
+    UnsuitableGhost,
+    /// A structure that players are holding in their clipboard and planning to place
+    Preview,
+    /// A preview that cannot be built in its current location
+    ForbiddenPreview,
+}
+
+impl GhostKind {
+    /// The material associated with each ghostly structure.
+    pub(crate) fn material(&self) -> StandardMaterial {
+        use crate::asset_management::palette::{
+            FORBIDDEN_PREVIEW_COLOR, GHOST_COLOR, PREVIEW_COLOR, SELECTED_GHOST_COLOR,
+            UNSUITABLE_GHOST_COLOR,
+        };
+
+        let base_color = match self {
+            GhostKind::Ghost => GHOST_COLOR,
+            GhostKind::SelectedGhost => SELECTED_GHOST_COLOR,
+            GhostKind::UnsuitableGhost => UNSUITABLE_GHOST_COLOR,
+            GhostKind::Preview => PREVIEW_COLOR,
+            GhostKind::ForbiddenPreview => FORBIDDEN_PREVIEW_COLOR,
+        };
+
+        StandardMaterial {
+            base_color,
+            ..Default::default()
+        }
+    }
+}
+
 /// Stores material handles for the different tile types.
 #[derive(Resource)]
 pub(crate) struct StructureHandles {
@@ -21,6 +62,20 @@ pub(crate) struct StructureHandles {
     pub(crate) ghost_materials: HashMap<GhostKind, Handle<StandardMaterial>>,
     /// The raycasting mesh used to select structures
     pub(crate) picking_mesh: Handle<Mesh>,
+    /// The color of the icon used for each type of structure in menus
+    pub(crate) icon_colors: HashMap<Id<Structure>, Color>,
+}
+
+impl StructureHandles {
+    /// The color of the icon used for `structure_id` in menus.
+    ///
+    /// Structures without a color of their own are shown in gray.
+    pub(crate) fn icon_color(&self, structure_id: Id<Structure>) -> Color {
+        self.icon_colors
+            .get(&structure_id)
+            .copied()
+            .unwrap_or(Color::GRAY)
+    }
 }
 
 impl FromWorld for StructureHandles {
@@ -51,21 +106,44 @@ impl FromWorld for StructureHandles {
             ghost_materials.insert(variant, material_handle);
         }
 
+        let icon_colors = [
+            ("leuco", Color::ORANGE_RED),
+            ("acacia", Color::GREEN),
+            ("ant_hive", Color::BEIGE),
+            ("hatchery", Color::BLUE),
+            ("composter", Color::DARK_GREEN),
+            ("cistern", Color::TEAL),
+            ("irrigation_channel", Color::CYAN),
+            ("bridge", Color::SALMON),
+            ("ramp", Color::OLIVE),
+            ("wall", Color::DARK_GRAY),
+            ("fence", Color::MAROON),
+            ("watchtower", Color::GOLD),
+            ("trap", Color::CRIMSON),
+            ("cage", Color::ORANGE_RED),
+            ("guard_post", Color::SILVER),
+            ("tunnel", Color::DARK_GRAY),
+        ]
+        .into_iter()
+        .map(|(id, color)| (Id::from_string_id(id), color))
+        .collect();
+
         let mut handles = StructureHandles {
             scenes: HashMap::default(),
             ghost_materials,
             picking_mesh,
+            icon_colors,
         };
 
         let asset_server = world.resource::<AssetServer>();
 
-        // TODO: discover this from the file directory
-        let structure_names = vec!["acacia", "leuco", "ant_hive", "hatchery"];
+        let structure_models = loaded_or_built_in(
+            load_structure_models(&definitions_directory()),
+            built_in_structure_models,
+        );
 
-        for id in structure_names {
-            let structure_id = Id::from_string_id(id);
-            let structure_path = format!("structures/{id}.gltf#Scene0");
-            let scene = asset_server.load(structure_path);
+        for (structure_id, model_path) in structure_models {
+            let scene = asset_server.load(model_path);
             handles.scenes.insert(structure_id, scene);
         }
 
@@ -73,7 +151,43 @@ impl FromWorld for StructureHandles {
     }
 }
 
+/// The models used for each structure when `structures.ron` cannot be loaded.
+fn built_in_structure_models() -> HashMap<Id<Structure>, String> {
+    let structure_names = vec!["acacia", "leuco", "ant_hive", "hatchery"];
+
+    // TODO: replace these with dedicated models
+    // Pairs of (structure, model to borrow) for structures without their own model yet
+    let placeholder_models = vec![
+        ("composter", "hatchery"),
+        ("cistern", "ant_hive"),
+        ("irrigation_channel", "hatchery"),
+        ("bridge", "hatchery"),
+        ("ramp", "hatchery"),
+        ("wall", "ant_hive"),
+        ("fence", "hatchery"),
+        ("watchtower", "ant_hive"),
+        ("trap", "hatchery"),
+        ("cage", "hatchery"),
+        ("guard_post", "hatchery"),
+        ("tunnel", "hatchery"),
+    ];
+
+    structure_names
+        .into_iter()
+        .map(|id| (id, id))
+        .chain(placeholder_models)
+        .map(|(id, model)| {
+            (
+                Id::from_string_id(id),
+                format!("structures/{model}.gltf#Scene0"),
+            )
+        })
+        .collect()
+}
+
 impl Loadable for StructureHandles {
+    const NAME: &'static str = "Structures";
+
     fn load_state(&self, asset_server: &AssetServer) -> LoadState {
         for (structure, scene_handle) in &self.scenes {
             let scene_load_state = asset_server.get_load_state(scene_handle);
@@ -86,4 +200,8 @@ impl Loadable for StructureHandles {
 
         LoadState::Loaded
     }
+
+    fn load_progress(&self, asset_server: &AssetServer) -> LoadProgress {
+        LoadProgress::from_handles(self.scenes.values(), asset_server)
+    }
 }
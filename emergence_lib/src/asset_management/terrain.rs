@@ -3,33 +3,69 @@
 use bevy::{prelude::*, utils::HashMap};
 
 use crate::{
-    enum_iter::IterableEnum, player_interaction::selection::ObjectInteraction,
-    simulation::geometry::MapGeometry, terrain::Terrain,
+    enum_iter::IterableEnum,
+    graphics::tint::TileTint,
+    player_interaction::selection::ObjectInteraction,
+    simulation::{geometry::MapGeometry, vision::Sight},
+    terrain::Terrain,
 };
 
-use super::hexagonal_column;
+use super::{hexagonal_column, palette::ColorPalette};
 
 /// Stores material handles for the different tile types.
 #[derive(Resource)]
 pub(crate) struct TerrainHandles {
     /// The material used for each type of terrain
     pub(crate) terrain_materials: HashMap<Terrain, Handle<StandardMaterial>>,
+    /// The material used for each type of terrain when it has been explored, but is hidden by the fog of war
+    pub(crate) fogged_materials: HashMap<Terrain, Handle<StandardMaterial>>,
+    /// The material used for each type of terrain when it has never been explored
+    pub(crate) unexplored_materials: HashMap<Terrain, Handle<StandardMaterial>>,
+    /// The material used for water that has frozen into ice
+    pub(crate) ice_material: Handle<StandardMaterial>,
     /// The mesh used for each type of structure
     pub(crate) mesh: Handle<Mesh>,
     /// The materials used for tiles when they are selected or otherwise interacted with
     pub(crate) interaction_materials: HashMap<ObjectInteraction, Handle<StandardMaterial>>,
+    /// The materials used to draw the signal overlay, from weakest to strongest
+    pub(crate) heatmap_materials: Vec<Handle<StandardMaterial>>,
+    /// The materials used for tiles tinted by the organisms on them, in each palette
+    pub(crate) tint_materials: HashMap<(TileTint, ColorPalette), Handle<StandardMaterial>>,
 }
 
 impl TerrainHandles {
     /// Returns a weakly cloned handle to the correct material for a terrain tile
+    ///
+    /// If the signal overlay is active, `heat` is the relative strength of the overlaid signal on this tile, between 0 and 1.
+    /// Visible tiles with a `tint` are drawn in that tint's color from the provided palette.
+    /// Tiles out of `sight` are darkened, and tiles that have never been explored are darkened further.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn get_material(
         &self,
         terrain: &Terrain,
         hovered: bool,
         selected: bool,
+        sight: Sight,
+        frozen: bool,
+        heat: Option<f32>,
+        tint: Option<(TileTint, ColorPalette)>,
     ) -> Handle<StandardMaterial> {
         let maybe_handle = match (hovered, selected) {
-            (false, false) => self.terrain_materials.get(terrain),
+            (false, false) => {
+                if let Some(heat) = heat {
+                    let max_level = self.heatmap_materials.len() - 1;
+                    let level = (heat.clamp(0., 1.) * max_level as f32).round() as usize;
+                    self.heatmap_materials.get(level)
+                } else {
+                    match (sight, tint) {
+                        (Sight::Visible, Some(tint)) => self.tint_materials.get(&tint),
+                        (Sight::Visible, None) if frozen => Some(&self.ice_material),
+                        (Sight::Visible, None) => self.terrain_materials.get(terrain),
+                        (Sight::Remembered, _) => self.fogged_materials.get(terrain),
+                        (Sight::Unexplored, _) => self.unexplored_materials.get(terrain),
+                    }
+                }
+            }
             (true, false) => self.interaction_materials.get(&ObjectInteraction::Hovered),
             (false, true) => self.interaction_materials.get(&ObjectInteraction::Selected),
             (true, true) => self
@@ -41,16 +77,72 @@ impl TerrainHandles {
     }
 }
 
+/// The rendering material associated with each terrain type.
+pub(crate) fn terrain_material(terrain: Terrain) -> StandardMaterial {
+    let base_color = match terrain {
+        Terrain::Plain => Color::BEIGE,
+        Terrain::Rocky => Color::GRAY,
+        Terrain::Muddy => Color::BISQUE,
+        Terrain::Water => Color::MIDNIGHT_BLUE,
+    };
+
+    StandardMaterial {
+        base_color,
+        perceptual_roughness: 0.6,
+        metallic: 0.01,
+        ..Default::default()
+    }
+}
+
+/// How much darker terrain is when hidden by the fog of war
+pub(crate) const FOG_BRIGHTNESS: f32 = 0.4;
+
+/// How much darker terrain is when it has never been explored
+const UNEXPLORED_BRIGHTNESS: f32 = 0.1;
+
+/// The number of distinct colors used to draw the signal overlay
+const N_HEATMAP_LEVELS: usize = 10;
+
+/// The color of the signal overlay where the signal is weakest
+const HEATMAP_COLD: Color = Color::MIDNIGHT_BLUE;
+
+/// The color of the signal overlay where the signal is strongest
+const HEATMAP_HOT: Color = Color::ORANGE_RED;
+
 impl FromWorld for TerrainHandles {
     fn from_world(world: &mut World) -> Self {
         let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
 
         let mut terrain_materials = HashMap::new();
+        let mut fogged_materials = HashMap::new();
+        let mut unexplored_materials = HashMap::new();
         for variant in Terrain::variants() {
-            let material_handle = material_assets.add(variant.material());
+            let material_handle = material_assets.add(terrain_material(variant));
             terrain_materials.insert(variant, material_handle);
+
+            let darkened_material = |brightness: f32| {
+                let mut material = terrain_material(variant);
+                let [r, g, b, a] = material.base_color.as_rgba_f32();
+                material.base_color =
+                    Color::rgba(r * brightness, g * brightness, b * brightness, a);
+                material
+            };
+            fogged_materials.insert(
+                variant,
+                material_assets.add(darkened_material(FOG_BRIGHTNESS)),
+            );
+            unexplored_materials.insert(
+                variant,
+                material_assets.add(darkened_material(UNEXPLORED_BRIGHTNESS)),
+            );
         }
 
+        let ice_material = material_assets.add(StandardMaterial {
+            base_color: Color::ALICE_BLUE,
+            perceptual_roughness: 0.2,
+            ..Default::default()
+        });
+
         let mut interaction_materials = HashMap::new();
         for variant in ObjectInteraction::variants() {
             if let Some(material) = variant.material() {
@@ -59,6 +151,36 @@ impl FromWorld for TerrainHandles {
             }
         }
 
+        let [cold_r, cold_g, cold_b, _] = HEATMAP_COLD.as_rgba_f32();
+        let [hot_r, hot_g, hot_b, _] = HEATMAP_HOT.as_rgba_f32();
+        let heatmap_materials = (0..N_HEATMAP_LEVELS)
+            .map(|level| {
+                let t = level as f32 / (N_HEATMAP_LEVELS - 1) as f32;
+                material_assets.add(StandardMaterial {
+                    base_color: Color::rgb(
+                        cold_r + (hot_r - cold_r) * t,
+                        cold_g + (hot_g - cold_g) * t,
+                        cold_b + (hot_b - cold_b) * t,
+                    ),
+                    // The overlay should be readable at any time of day
+                    unlit: true,
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let mut tint_materials = HashMap::new();
+        for tint in TileTint::variants() {
+            for palette in ColorPalette::variants() {
+                let material_handle = material_assets.add(StandardMaterial {
+                    base_color: tint.color(palette),
+                    perceptual_roughness: 0.6,
+                    ..Default::default()
+                });
+                tint_materials.insert((tint, palette), material_handle);
+            }
+        }
+
         let map_geometry = world.resource::<MapGeometry>();
         let mesh_object = hexagonal_column(&map_geometry.layout, 1.0);
         let mut mesh_assets = world.resource_mut::<Assets<Mesh>>();
@@ -66,8 +188,13 @@ impl FromWorld for TerrainHandles {
 
         TerrainHandles {
             terrain_materials,
+            fogged_materials,
+            unexplored_materials,
+            ice_material,
             mesh,
             interaction_materials,
+            heatmap_materials,
+            tint_materials,
         }
     }
 }
@@ -1,12 +1,15 @@
 //! Asset loading for units
 
-use crate::simulation::geometry::MapGeometry;
+use crate::{simulation::geometry::MapGeometry, units::varieties::built_in_unit_varieties};
 use bevy::{asset::LoadState, prelude::*, utils::HashMap};
 
 use super::{
     hexagonal_column,
-    manifest::{Id, Unit},
-    Loadable,
+    manifest::{
+        definitions::{definitions_directory, load_unit_varieties, loaded_or_built_in},
+        Id, Unit,
+    },
+    LoadProgress, Loadable,
 };
 
 /// Stores material handles for the different tile types.
@@ -37,14 +40,16 @@ impl FromWorld for UnitHandles {
 
         let asset_server = world.resource::<AssetServer>();
 
-        // TODO: discover this from the file directory
-        let unit_names = vec!["ant"];
+        // Units that are missing from `units.ron` keep their built-in model
+        let mut unit_varieties = built_in_unit_varieties();
+        unit_varieties.extend(loaded_or_built_in(
+            load_unit_varieties(&definitions_directory()),
+            HashMap::default,
+        ));
 
-        for str in unit_names {
-            let structure_id = Id::from_string_id(str);
-            let structure_path = format!("units/{str}.gltf#Scene0");
-            let scene = asset_server.load(structure_path);
-            handles.scenes.insert(structure_id, scene);
+        for (unit_id, variety) in unit_varieties {
+            let scene = asset_server.load(variety.model);
+            handles.scenes.insert(unit_id, scene);
         }
 
         handles
@@ -52,6 +57,8 @@ impl FromWorld for UnitHandles {
 }
 
 impl Loadable for UnitHandles {
+    const NAME: &'static str = "Units";
+
     fn load_state(&self, asset_server: &AssetServer) -> LoadState {
         for (unit, scene_handle) in &self.scenes {
             let scene_load_state = asset_server.get_load_state(scene_handle);
@@ -64,4 +71,8 @@ impl Loadable for UnitHandles {
 
         LoadState::Loaded
     }
+
+    fn load_progress(&self, asset_server: &AssetServer) -> LoadProgress {
+        LoadProgress::from_handles(self.scenes.values(), asset_server)
+    }
 }
@@ -0,0 +1,69 @@
+//! Sound effects that draw the player's attention to what is happening in the world.
+
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{
+    asset_management::{AssetCollectionExt, AssetState, LoadProgress, Loadable},
+    simulation::alerts::Alert,
+};
+
+/// Plays sound effects in response to events in the simulation.
+pub struct SoundEffectsPlugin;
+
+impl Plugin for SoundEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset_collection::<SoundHandles>()
+            .add_system(play_alert_chime.run_if(in_state(AssetState::Ready)));
+    }
+}
+
+/// The volume of the chime played when an alert is raised, relative to the sound file.
+const ALERT_VOLUME: f32 = 0.5;
+
+/// Stores the handles of each sound effect.
+#[derive(Resource)]
+struct SoundHandles {
+    /// The chime played when an alert is raised
+    alert: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundHandles {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        SoundHandles {
+            alert: asset_server.load("sounds/alert.wav"),
+        }
+    }
+}
+
+impl Loadable for SoundHandles {
+    const NAME: &'static str = "Sounds";
+
+    fn load_state(&self, asset_server: &AssetServer) -> LoadState {
+        asset_server.get_load_state(&self.alert)
+    }
+
+    fn load_progress(&self, asset_server: &AssetServer) -> LoadProgress {
+        LoadProgress::from_handles([&self.alert], asset_server)
+    }
+}
+
+/// Plays a chime when any [`Alert`] is raised.
+///
+/// Only a single chime is played for all of the alerts raised in the same frame.
+fn play_alert_chime(
+    mut alert_events: EventReader<Alert>,
+    audio: Res<Audio>,
+    sound_handles: Res<SoundHandles>,
+) {
+    if alert_events.is_empty() {
+        return;
+    }
+    alert_events.clear();
+
+    audio.play_with_settings(
+        sound_handles.alert.clone_weak(),
+        PlaybackSettings::ONCE.with_volume(ALERT_VOLUME),
+    );
+}
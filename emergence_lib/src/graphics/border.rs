@@ -0,0 +1,91 @@
+//! Marks the edge of the map with a ring of dark tiles that fade away into the void.
+
+use bevy::prelude::*;
+use hexx::{shapes::hexagon, Hex};
+
+use crate::{asset_management::hexagonal_column, simulation::geometry::MapGeometry};
+
+/// The number of rings of void tiles drawn around the edge of the map.
+const BORDER_WIDTH: u32 = 3;
+
+/// The height of each void tile.
+const VOID_HEIGHT: f32 = 0.5;
+
+/// The color of the void surrounding the map.
+const VOID_COLOR: Color = Color::rgb(0.05, 0.05, 0.08);
+
+/// Draws the edge of the map.
+pub(super) struct BorderGraphicsPlugin;
+
+impl Plugin for BorderGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_map_border);
+    }
+}
+
+/// A purely decorative tile just beyond the edge of the map.
+///
+/// These are not [`TilePos`](crate::simulation::geometry::TilePos)s, and have no effect on gameplay.
+#[derive(Component, Debug)]
+struct VoidTile;
+
+/// The opacity of void tiles that are `ring` tiles past the edge of the map.
+///
+/// The first ring is almost opaque, and each ring further out is fainter.
+fn void_opacity(ring: u32) -> f32 {
+    1. - ring as f32 / (BORDER_WIDTH + 1) as f32
+}
+
+/// Spawns rings of [`VoidTile`]s around the map, based on the size of the [`MapGeometry`].
+fn spawn_map_border(
+    mut commands: Commands,
+    map_geometry: Res<MapGeometry>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(hexagonal_column(&map_geometry.layout, VOID_HEIGHT));
+    let ring_materials: Vec<Handle<StandardMaterial>> = (1..=BORDER_WIDTH)
+        .map(|ring| {
+            materials.add(StandardMaterial {
+                base_color: VOID_COLOR.with_a(void_opacity(ring)),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        })
+        .collect();
+
+    for hex in hexagon(Hex::ZERO, map_geometry.radius + BORDER_WIDTH) {
+        let distance_from_center = hex.distance_to(Hex::ZERO) as u32;
+        if distance_from_center <= map_geometry.radius {
+            continue;
+        }
+
+        let ring = distance_from_center - map_geometry.radius;
+        let xz = map_geometry.layout.hex_to_world_pos(hex);
+
+        commands.spawn((
+            VoidTile,
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: ring_materials[ring as usize - 1].clone(),
+                transform: Transform::from_xyz(xz.x, 0., xz.y),
+                ..default()
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn void_fades_away_from_the_map() {
+        for ring in 1..BORDER_WIDTH {
+            assert!(void_opacity(ring) > void_opacity(ring + 1));
+        }
+
+        assert!(void_opacity(BORDER_WIDTH) > 0.);
+    }
+}
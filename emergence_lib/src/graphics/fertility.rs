@@ -0,0 +1,89 @@
+//! Shows how fertile the soil is across the map.
+
+use bevy::prelude::*;
+
+use crate::{
+    player_interaction::overlay::FertilityOverlay, simulation::geometry::TilePos,
+    terrain::Fertility,
+};
+
+use super::{
+    overlay_layers::{OverlayLayer, OverlayLayers},
+    quality::GraphicsQuality,
+};
+
+/// Displays the fertility of the soil as an overlay.
+pub(super) struct FertilityGraphicsPlugin;
+
+impl Plugin for FertilityGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(display_fertility);
+    }
+}
+
+/// The color of tiles with no fertility at all.
+const BARREN_COLOR: Color = Color::rgba(0.6, 0.4, 0.2, 0.6);
+
+/// The color of tiles with the highest possible fertility.
+const LUSH_COLOR: Color = Color::rgba(0.1, 0.8, 0.2, 0.6);
+
+/// The color used to draw a tile with the provided `fertility`.
+fn fertility_color(fertility: f32) -> Color {
+    let t = fertility.clamp(0., 1.);
+    let barren = Vec4::from(BARREN_COLOR.as_rgba_f32());
+    let lush = Vec4::from(LUSH_COLOR.as_rgba_f32());
+
+    Color::from(barren.lerp(lush, t))
+}
+
+/// Redraws the [`OverlayLayer::Fertility`] while the [`FertilityOverlay`] is enabled.
+///
+/// Fertility changes slowly, so the layer is only refreshed as often as the signal overlay.
+fn display_fertility(
+    fertility_overlay: Res<FertilityOverlay>,
+    fertility_query: Query<(&TilePos, &Fertility)>,
+    graphics_quality: Res<GraphicsQuality>,
+    mut overlay_layers: ResMut<OverlayLayers>,
+    mut frames_since_refresh: Local<u32>,
+) {
+    if fertility_overlay.is_changed() {
+        overlay_layers.set_visible(OverlayLayer::Fertility, fertility_overlay.enabled);
+    }
+
+    if !fertility_overlay.enabled {
+        return;
+    }
+
+    *frames_since_refresh += 1;
+    if !fertility_overlay.is_changed()
+        && *frames_since_refresh < graphics_quality.overlay_refresh_interval()
+    {
+        return;
+    }
+    *frames_since_refresh = 0;
+
+    let layer = overlay_layers.layer_mut(OverlayLayer::Fertility);
+    layer.clear();
+
+    for (&tile_pos, fertility) in fertility_query.iter() {
+        layer.color_tile(tile_pos, fertility_color(fertility.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Are the two colors equal, up to floating point error?
+    fn colors_match(a: Color, b: Color) -> bool {
+        let difference = Vec4::from(a.as_rgba_f32()) - Vec4::from(b.as_rgba_f32());
+        difference.abs().max_element() < 1e-5
+    }
+
+    #[test]
+    fn fertility_colors_span_barren_to_lush() {
+        assert!(colors_match(fertility_color(0.), BARREN_COLOR));
+        assert!(colors_match(fertility_color(1.), LUSH_COLOR));
+        assert!(colors_match(fertility_color(2.), LUSH_COLOR));
+    }
+}
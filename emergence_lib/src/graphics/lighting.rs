@@ -2,28 +2,84 @@
 
 use bevy::prelude::*;
 
+use crate::simulation::time::TimeOfDay;
+
 /// Handles all lighting logic
 pub(super) struct LightingPlugin;
 
 impl Plugin for LightingPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(AmbientLight {
-            brightness: 0.5,
+            brightness: DAY_AMBIENT_BRIGHTNESS,
             color: Color::WHITE,
         })
-        .add_startup_system(spawn_sun);
+        .add_startup_system(spawn_sun)
+        .add_system(tint_scene_by_time_of_day);
     }
 }
 
+/// The brightness of the sun at midday.
+const DAY_ILLUMINANCE: f32 = 50000.;
+
+/// The brightness of the moon, which lights the scene at night.
+const NIGHT_ILLUMINANCE: f32 = 5000.;
+
+/// The brightness of the ambient light during the day.
+const DAY_AMBIENT_BRIGHTNESS: f32 = 0.5;
+
+/// The brightness of the ambient light at night.
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 0.15;
+
+/// The color of the light at night, which is cool and dim.
+const NIGHT_COLOR: Color = Color::rgb(0.4, 0.5, 0.9);
+
+/// Marks the directional light that stands in for the sun.
+#[derive(Component, Debug)]
+struct Sun;
+
 /// Spawns a directional light source to illuminate the scene
 fn spawn_sun(mut commands: Commands) {
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            color: Color::WHITE,
-            illuminance: 50000.,
-            ..Default::default()
+    commands.spawn((
+        Sun,
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                color: Color::WHITE,
+                illuminance: DAY_ILLUMINANCE,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(30., 100., 30.).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
         },
-        transform: Transform::from_xyz(30., 100., 30.).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+    ));
+}
+
+/// Blends between the day and night versions of a lighting value, based on the `daylight`.
+fn blend(night: f32, day: f32, daylight: f32) -> f32 {
+    night + (day - night) * daylight
+}
+
+/// Dims and cools the lighting as night falls, and restores it at dawn.
+fn tint_scene_by_time_of_day(
+    time_of_day: Res<TimeOfDay>,
+    mut sun_query: Query<&mut DirectionalLight, With<Sun>>,
+    mut ambient_light: ResMut<AmbientLight>,
+) {
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    let daylight = time_of_day.daylight();
+    let color = Color::rgb(
+        blend(NIGHT_COLOR.r(), 1., daylight),
+        blend(NIGHT_COLOR.g(), 1., daylight),
+        blend(NIGHT_COLOR.b(), 1., daylight),
+    );
+
+    for mut sun in sun_query.iter_mut() {
+        sun.illuminance = blend(NIGHT_ILLUMINANCE, DAY_ILLUMINANCE, daylight);
+        sun.color = color;
+    }
+
+    ambient_light.brightness = blend(NIGHT_AMBIENT_BRIGHTNESS, DAY_AMBIENT_BRIGHTNESS, daylight);
+    ambient_light.color = color;
 }
@@ -1,15 +1,36 @@
 //! Rendering and animation logic.
 
 use bevy::prelude::*;
+use std::f32::consts::PI;
 
-use crate::{asset_management::AssetState, player_interaction::InteractionSystem};
+use crate::{
+    asset_management::AssetState,
+    player_interaction::InteractionSystem,
+    simulation::geometry::{Facing, MapGeometry},
+    units::UnitSystem,
+};
 
-use self::lighting::LightingPlugin;
+use self::{
+    border::BorderGraphicsPlugin, fertility::FertilityGraphicsPlugin, lighting::LightingPlugin,
+    overlay_layers::OverlayLayersPlugin, quality::QualityPlugin, roofs::RoofGraphicsPlugin,
+    ruler::RulerGraphicsPlugin, seasons::SeasonGraphicsPlugin, tint::TintPlugin,
+};
 
+mod border;
+mod fertility;
 mod lighting;
+pub(crate) mod overlay_layers;
+pub mod quality;
+pub(crate) mod roofs;
+mod ruler;
+mod seasons;
 mod selection;
 mod structures;
+mod terrain;
+pub(crate) mod tint;
 mod units;
+#[cfg(feature = "weather")]
+mod weather;
 
 /// Adds all logic required to render the game.
 ///
@@ -19,9 +40,54 @@ pub struct GraphicsPlugin;
 impl Plugin for GraphicsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(LightingPlugin)
+            .add_plugin(RulerGraphicsPlugin)
+            .add_plugin(TintPlugin)
+            .add_plugin(BorderGraphicsPlugin)
+            .add_plugin(QualityPlugin)
+            .add_plugin(OverlayLayersPlugin)
+            .add_plugin(FertilityGraphicsPlugin)
+            .add_plugin(RoofGraphicsPlugin)
+            .add_plugin(SeasonGraphicsPlugin)
+            // Models are added to newly spawned game objects before anything else tries to draw them
+            .add_systems(
+                (
+                    terrain::spawn_terrain_meshes,
+                    structures::spawn_structure_models,
+                    units::spawn_unit_models,
+                )
+                    .in_base_set(CoreSet::PreUpdate),
+            )
+            .add_system(sync_rotation_to_facing)
             .add_system(units::display_held_item.run_if(in_state(AssetState::Ready)))
+            .add_system(units::arrange_crowds.after(UnitSystem::Act))
+            .add_system(
+                units::animate_units
+                    .run_if(in_state(AssetState::Ready))
+                    .after(UnitSystem::ChooseNewAction),
+            )
+            .add_system(structures::display_construction_stage.run_if(in_state(AssetState::Ready)))
             .add_system(inherit_materials.in_base_set(CoreSet::PostUpdate))
             .add_system(selection::display_tile_interactions.after(InteractionSystem::SelectTiles));
+
+        #[cfg(feature = "weather")]
+        app.add_plugin(weather::WeatherGraphicsPlugin);
+    }
+}
+
+/// Rotates objects so they are facing the correct direction.
+fn sync_rotation_to_facing(
+    // Camera requires different logic, it rotates "around" a central point
+    // PERF: re-enable change detection. For some reason this wasn't working on structures,
+    // but was on ghosts.
+    mut query: Query<(&mut Transform, &Facing), Without<Camera3d>>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (mut transform, &facing) in query.iter_mut() {
+        // Rotate the object in the correct direction
+        // We want to be aligned with the faces of the hexes, not their points
+        let angle = facing.direction.angle(&map_geometry.layout.orientation) + PI / 6.;
+        let target = Quat::from_axis_angle(Vec3::Y, angle);
+        transform.rotation = target;
     }
 }
 
@@ -0,0 +1,479 @@
+//! A shared way to draw colored tiles, lines and labels over the map.
+//!
+//! Feature code describes what it wants drawn by submitting it to an [`OverlayLayer`] in the [`OverlayLayers`] resource.
+//! Each layer is drawn as a single batched mesh for its tiles and another for its lines, and only rebuilt when its contents change.
+//! Layers can be shown and hidden independently, without discarding what was submitted to them.
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    utils::{HashMap, HashSet},
+};
+use emergence_macros::IterableEnum;
+
+use crate::{
+    enum_iter::IterableEnum,
+    simulation::geometry::{MapGeometry, TilePos},
+};
+
+/// How far above the surface of each tile overlays are drawn, to avoid flickering against the terrain.
+const OVERLAY_HEIGHT: f32 = 0.02;
+
+/// How far above the surface of each tile lines are drawn, so they sit on top of colored tiles.
+const LINE_HEIGHT: f32 = 0.05;
+
+/// How far above the surface of each tile labels are anchored.
+const LABEL_HEIGHT: f32 = 0.5;
+
+/// The font used for labels.
+const LABEL_FONT: &str = "fonts/FiraSans-Medium.ttf";
+
+/// Draws the contents of each [`OverlayLayer`].
+pub(super) struct OverlayLayersPlugin;
+
+impl Plugin for OverlayLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlayLayers>()
+            .add_startup_system(spawn_overlay_layers)
+            .add_system(rebuild_overlay_meshes.in_base_set(CoreSet::PostUpdate))
+            .add_system(
+                position_overlay_labels
+                    .after(rebuild_overlay_meshes)
+                    .in_base_set(CoreSet::PostUpdate),
+            );
+    }
+}
+
+/// A set of tiles, lines and labels that are drawn, shown and hidden together.
+///
+/// Later layers are drawn on top of earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, IterableEnum)]
+pub(crate) enum OverlayLayer {
+    /// The weather fronts drifting across the map.
+    Weather,
+    /// The fertility of the soil on each tile.
+    Fertility,
+    /// The number of units hidden on crowded tiles.
+    Crowds,
+    /// The path and distances measured by the ruler.
+    Ruler,
+}
+
+/// Everything that has been submitted to a single [`OverlayLayer`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct OverlayContents {
+    /// The color that each tile is filled with
+    tile_colors: HashMap<TilePos, Color>,
+    /// Lines that pass through the center of each tile in turn
+    polylines: Vec<(Vec<TilePos>, Color)>,
+    /// Text that floats above each tile
+    labels: Vec<(TilePos, String)>,
+}
+
+impl OverlayContents {
+    /// Fills `tile_pos` with `color`, replacing any color it already had.
+    pub(crate) fn color_tile(&mut self, tile_pos: TilePos, color: Color) {
+        self.tile_colors.insert(tile_pos, color);
+    }
+
+    /// Draws a line of `color` through the center of each of `tiles`, in order.
+    pub(crate) fn polyline(&mut self, tiles: Vec<TilePos>, color: Color) {
+        if tiles.len() >= 2 {
+            self.polylines.push((tiles, color));
+        }
+    }
+
+    /// Writes `text` above `tile_pos`.
+    pub(crate) fn label(&mut self, tile_pos: TilePos, text: impl Into<String>) {
+        self.labels.push((tile_pos, text.into()));
+    }
+
+    /// Removes everything from this layer.
+    pub(crate) fn clear(&mut self) {
+        self.tile_colors.clear();
+        self.polylines.clear();
+        self.labels.clear();
+    }
+
+    /// Builds a single mesh containing a flat, colored hexagon for every colored tile.
+    fn tile_mesh(&self, map_geometry: &MapGeometry) -> Mesh {
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(self.tile_colors.len() * 7);
+        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(self.tile_colors.len() * 7);
+        let mut indices: Vec<u32> = Vec::with_capacity(self.tile_colors.len() * 18);
+
+        for (&tile_pos, &color) in self.tile_colors.iter() {
+            let center_index = positions.len() as u32;
+            let center = overlay_pos(tile_pos, map_geometry, OVERLAY_HEIGHT);
+
+            positions.push(center.into());
+            for corner in hex_corners(tile_pos, map_geometry) {
+                positions.push([corner.x, center.y, corner.y]);
+            }
+            colors.extend([color.as_linear_rgba_f32(); 7]);
+
+            for i in 0..6 {
+                indices.extend([
+                    center_index,
+                    center_index + 1 + (i + 1) % 6,
+                    center_index + 1 + i,
+                ]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        let normals = vec![[0., 1., 0.]; positions.len()];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+
+    /// Builds a single mesh containing every line segment of every polyline.
+    fn line_mesh(&self, map_geometry: &MapGeometry) -> Mesh {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut colors: Vec<[f32; 4]> = Vec::new();
+
+        for (tiles, color) in self.polylines.iter() {
+            for segment in tiles.windows(2) {
+                positions.push(overlay_pos(segment[0], map_geometry, LINE_HEIGHT).into());
+                positions.push(overlay_pos(segment[1], map_geometry, LINE_HEIGHT).into());
+                colors.extend([color.as_linear_rgba_f32(); 2]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+        let normals = vec![[0., 1., 0.]; positions.len()];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh
+    }
+}
+
+/// The point `height` above the center of the surface of `tile_pos`.
+fn overlay_pos(tile_pos: TilePos, map_geometry: &MapGeometry, height: f32) -> Vec3 {
+    let xz = map_geometry.layout.hex_to_world_pos(tile_pos.hex);
+    let surface = map_geometry
+        .height_index
+        .get(&tile_pos)
+        .copied()
+        .unwrap_or_default();
+
+    Vec3::new(xz.x, surface + height, xz.y)
+}
+
+/// The horizontal position of each corner of `tile_pos`, in order around the tile.
+///
+/// Each corner is shared by the tile and two of its neighbors, so it lies at the average of their centers.
+fn hex_corners(tile_pos: TilePos, map_geometry: &MapGeometry) -> [Vec2; 6] {
+    let center = map_geometry.layout.hex_to_world_pos(tile_pos.hex);
+    let neighbors = tile_pos
+        .hex
+        .all_neighbors()
+        .map(|neighbor| map_geometry.layout.hex_to_world_pos(neighbor));
+
+    std::array::from_fn(|i| (center + neighbors[i] + neighbors[(i + 1) % 6]) / 3.)
+}
+
+/// The contents of every [`OverlayLayer`], and whether or not each is shown.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct OverlayLayers {
+    /// What has been submitted to each layer
+    contents: HashMap<OverlayLayer, OverlayContents>,
+    /// The layers that have been hidden
+    hidden: HashSet<OverlayLayer>,
+    /// The layers that have changed since they were last drawn
+    dirty: HashSet<OverlayLayer>,
+}
+
+impl OverlayLayers {
+    /// Returns the contents of `layer`, to be added to or cleared.
+    ///
+    /// The layer will be redrawn at the end of the frame.
+    pub(crate) fn layer_mut(&mut self, layer: OverlayLayer) -> &mut OverlayContents {
+        self.dirty.insert(layer);
+        self.contents.entry(layer).or_default()
+    }
+
+    /// Shows or hides `layer`, keeping its contents.
+    pub(crate) fn set_visible(&mut self, layer: OverlayLayer, visible: bool) {
+        let changed = if visible {
+            self.hidden.remove(&layer)
+        } else {
+            self.hidden.insert(layer)
+        };
+
+        if changed {
+            self.dirty.insert(layer);
+        }
+    }
+
+    /// Is `layer` currently shown?
+    pub(crate) fn is_visible(&self, layer: OverlayLayer) -> bool {
+        !self.hidden.contains(&layer)
+    }
+}
+
+/// The entities used to draw a single [`OverlayLayer`].
+#[derive(Component, Debug)]
+struct OverlayLayerGraphics {
+    /// The layer that is drawn
+    layer: OverlayLayer,
+}
+
+/// Marks the entity used to draw the colored tiles of an [`OverlayLayer`].
+#[derive(Component, Debug)]
+struct OverlayTiles;
+
+/// Marks the entity used to draw the lines of an [`OverlayLayer`].
+#[derive(Component, Debug)]
+struct OverlayLines;
+
+/// The UI node used to draw a label of an [`OverlayLayer`], and the tile it belongs above.
+#[derive(Component, Debug)]
+struct OverlayLabel {
+    /// The tile that the label floats above
+    tile_pos: TilePos,
+}
+
+/// Spawns a pair of mesh entities for each [`OverlayLayer`], which are filled in as the layers change.
+fn spawn_overlay_layers(
+    mut commands: Commands,
+    map_geometry: Res<MapGeometry>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Colors come from the vertices, so every layer can share a single material
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        // Overlays are flat, so they should be visible no matter which way their triangles wind
+        cull_mode: None,
+        ..default()
+    });
+
+    for layer in OverlayLayer::variants() {
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(OverlayContents::default().tile_mesh(&map_geometry)),
+                material: material.clone(),
+                ..default()
+            },
+            OverlayLayerGraphics { layer },
+            OverlayTiles,
+        ));
+
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(OverlayContents::default().line_mesh(&map_geometry)),
+                material: material.clone(),
+                ..default()
+            },
+            OverlayLayerGraphics { layer },
+            OverlayLines,
+        ));
+    }
+}
+
+/// Rebuilds the meshes and labels of every [`OverlayLayer`] that has changed.
+fn rebuild_overlay_meshes(
+    mut overlay_layers: ResMut<OverlayLayers>,
+    map_geometry: Res<MapGeometry>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut graphics_query: Query<(
+        &OverlayLayerGraphics,
+        &Handle<Mesh>,
+        &mut Visibility,
+        Option<&OverlayTiles>,
+    )>,
+    label_query: Query<(Entity, &OverlayLayerGraphics), With<OverlayLabel>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    if overlay_layers.dirty.is_empty() {
+        return;
+    }
+
+    let dirty = std::mem::take(&mut overlay_layers.dirty);
+
+    for (graphics, mesh_handle, mut visibility, maybe_tiles) in graphics_query.iter_mut() {
+        if !dirty.contains(&graphics.layer) {
+            continue;
+        }
+
+        let new_visibility = if overlay_layers.is_visible(graphics.layer) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(new_visibility);
+
+        let contents = overlay_layers
+            .contents
+            .get(&graphics.layer)
+            .cloned()
+            .unwrap_or_default();
+        let mesh = match maybe_tiles {
+            Some(_) => contents.tile_mesh(&map_geometry),
+            None => contents.line_mesh(&map_geometry),
+        };
+
+        if let Some(existing_mesh) = meshes.get_mut(mesh_handle) {
+            *existing_mesh = mesh;
+        }
+    }
+
+    // Labels are few and far between, so they are simply respawned
+    for (entity, graphics) in label_query.iter() {
+        if dirty.contains(&graphics.layer) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font: asset_server.load(LABEL_FONT),
+        font_size: 16.,
+    };
+
+    for &layer in dirty.iter() {
+        if !overlay_layers.is_visible(layer) {
+            continue;
+        }
+
+        let contents = match overlay_layers.contents.get(&layer) {
+            Some(contents) => contents,
+            None => continue,
+        };
+
+        for (tile_pos, text) in contents.labels.iter() {
+            commands.spawn((
+                TextBundle {
+                    text: Text::from_section(text.clone(), text_style.clone()),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                    ..default()
+                },
+                OverlayLayerGraphics { layer },
+                OverlayLabel {
+                    tile_pos: *tile_pos,
+                },
+            ));
+        }
+    }
+}
+
+/// Moves each label so that it stays above its tile as the camera moves.
+fn position_overlay_labels(
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    map_geometry: Res<MapGeometry>,
+    mut label_query: Query<(&OverlayLabel, &mut Style, &mut Visibility)>,
+) {
+    let (camera, camera_transform) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let viewport_height = match camera.logical_viewport_size() {
+        Some(size) => size.y,
+        None => return,
+    };
+
+    for (label, mut style, mut visibility) in label_query.iter_mut() {
+        let world_pos = overlay_pos(label.tile_pos, &map_geometry, LABEL_HEIGHT);
+
+        match camera.world_to_viewport(camera_transform, world_pos) {
+            Some(viewport_pos) => {
+                // Viewport coordinates start at the bottom left, but UI coordinates start at the top left
+                style.position.left = Val::Px(viewport_pos.x);
+                style.position.top = Val::Px(viewport_height - viewport_pos.y);
+                if *visibility != Visibility::Inherited {
+                    *visibility = Visibility::Inherited;
+                }
+            }
+            None => {
+                if *visibility != Visibility::Hidden {
+                    *visibility = Visibility::Hidden;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_are_batched_into_one_mesh() {
+        let map_geometry = MapGeometry::new(2);
+        let mut contents = OverlayContents::default();
+
+        contents.color_tile(TilePos::ORIGIN, Color::RED);
+        contents.color_tile(TilePos::new(1, 0), Color::BLUE);
+        // Recoloring a tile replaces its old color
+        contents.color_tile(TilePos::ORIGIN, Color::GREEN);
+
+        let mesh = contents.tile_mesh(&map_geometry);
+        assert_eq!(mesh.count_vertices(), 14);
+        assert_eq!(mesh.indices().unwrap().len(), 36);
+    }
+
+    #[test]
+    fn polylines_are_split_into_segments() {
+        let map_geometry = MapGeometry::new(2);
+        let mut contents = OverlayContents::default();
+
+        contents.polyline(
+            vec![TilePos::ORIGIN, TilePos::new(1, 0), TilePos::new(2, 0)],
+            Color::RED,
+        );
+        // A single tile is not a line
+        contents.polyline(vec![TilePos::ORIGIN], Color::RED);
+
+        assert_eq!(contents.line_mesh(&map_geometry).count_vertices(), 4);
+    }
+
+    #[test]
+    fn hex_corners_are_shared_with_neighbors() {
+        let map_geometry = MapGeometry::new(2);
+        let corners = hex_corners(TilePos::ORIGIN, &map_geometry);
+
+        for neighbor in TilePos::ORIGIN.hex.all_neighbors() {
+            let neighbor_corners = hex_corners(TilePos { hex: neighbor }, &map_geometry);
+            let n_shared = corners
+                .iter()
+                .filter(|corner| {
+                    neighbor_corners
+                        .iter()
+                        .any(|other| other.distance(**corner) < 1e-4)
+                })
+                .count();
+
+            assert_eq!(n_shared, 2);
+        }
+    }
+
+    #[test]
+    fn hidden_layers_keep_their_contents() {
+        let mut overlay_layers = OverlayLayers::default();
+        overlay_layers
+            .layer_mut(OverlayLayer::Ruler)
+            .label(TilePos::ORIGIN, "Here");
+
+        overlay_layers.set_visible(OverlayLayer::Ruler, false);
+        assert!(!overlay_layers.is_visible(OverlayLayer::Ruler));
+        assert_eq!(
+            overlay_layers.contents[&OverlayLayer::Ruler].labels.len(),
+            1
+        );
+
+        overlay_layers.set_visible(OverlayLayer::Ruler, true);
+        assert!(overlay_layers.is_visible(OverlayLayer::Ruler));
+    }
+}
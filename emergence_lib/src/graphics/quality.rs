@@ -0,0 +1,222 @@
+//! Automatically lowers the graphics quality when frames take too long, and raises it again once there is headroom.
+//!
+//! Large colonies can overwhelm slower machines.
+//! Rather than dropping frames, purely cosmetic work is scaled back until the frame time fits within the budget.
+
+use bevy::prelude::*;
+
+use crate::player_interaction::InteractionSystem;
+
+use super::selection::display_tile_interactions;
+
+/// Adapts the [`GraphicsQuality`] to the frame time.
+pub(super) struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphicsSettings>()
+            .init_resource::<GraphicsQuality>()
+            .add_system(
+                adapt_graphics_quality
+                    .after(InteractionSystem::SelectTiles)
+                    .before(display_tile_interactions),
+            );
+    }
+}
+
+/// How much cosmetic work is done each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QualityLevel {
+    /// Visual effects are disabled, and the signal overlay is rarely refreshed.
+    Low,
+    /// The signal overlay is refreshed less often.
+    Medium,
+    /// Everything is drawn every frame.
+    High,
+}
+
+impl QualityLevel {
+    /// The next lower level, if any.
+    fn lower(self) -> Self {
+        match self {
+            QualityLevel::High => QualityLevel::Medium,
+            QualityLevel::Medium | QualityLevel::Low => QualityLevel::Low,
+        }
+    }
+
+    /// The next higher level, if any.
+    fn higher(self) -> Self {
+        match self {
+            QualityLevel::Low => QualityLevel::Medium,
+            QualityLevel::Medium | QualityLevel::High => QualityLevel::High,
+        }
+    }
+}
+
+/// Player-controlled settings for the graphics quality.
+///
+/// Insert this resource before adding the [`GraphicsPlugin`](super::GraphicsPlugin) to customize it for a game.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GraphicsSettings {
+    /// Forces the graphics to this quality, instead of adapting to the frame time.
+    pub quality_override: Option<QualityLevel>,
+    /// The longest that a frame should take, in seconds.
+    pub frame_budget: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            quality_override: None,
+            frame_budget: 1. / 60.,
+        }
+    }
+}
+
+/// The graphics quality currently in use.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(crate) struct GraphicsQuality {
+    /// The current quality
+    level: QualityLevel,
+    /// The number of consecutive frames that took longer than the budget
+    frames_over_budget: u32,
+    /// The number of consecutive frames that took much less time than the budget
+    frames_with_headroom: u32,
+}
+
+impl Default for GraphicsQuality {
+    fn default() -> Self {
+        GraphicsQuality {
+            level: QualityLevel::High,
+            frames_over_budget: 0,
+            frames_with_headroom: 0,
+        }
+    }
+}
+
+impl GraphicsQuality {
+    /// The number of consecutive slow frames before the quality is lowered.
+    const DEGRADE_AFTER_FRAMES: u32 = 30;
+
+    /// The number of consecutive fast frames before the quality is raised.
+    ///
+    /// This is much longer than [`GraphicsQuality::DEGRADE_AFTER_FRAMES`], so the quality doesn't flicker back and forth.
+    const RESTORE_AFTER_FRAMES: u32 = 300;
+
+    /// Frames that take less than this fraction of the budget have enough headroom to raise the quality.
+    const HEADROOM_FRACTION: f32 = 0.7;
+
+    /// Should purely decorative effects, such as lightning flashes, be shown?
+    #[cfg(any(test, feature = "weather"))]
+    pub(crate) fn show_effects(&self) -> bool {
+        self.level > QualityLevel::Low
+    }
+
+    /// The number of frames between each refresh of the signal overlay.
+    pub(crate) fn overlay_refresh_interval(&self) -> u32 {
+        match self.level {
+            QualityLevel::High => 1,
+            QualityLevel::Medium => 4,
+            QualityLevel::Low => 15,
+        }
+    }
+
+    /// Records a frame that took `frame_time` seconds, changing the quality if needed to fit within `frame_budget`.
+    fn record_frame(&mut self, frame_time: f32, frame_budget: f32) {
+        if frame_time > frame_budget {
+            self.frames_over_budget += 1;
+            self.frames_with_headroom = 0;
+        } else if frame_time < frame_budget * GraphicsQuality::HEADROOM_FRACTION {
+            self.frames_with_headroom += 1;
+            self.frames_over_budget = 0;
+        } else {
+            self.frames_over_budget = 0;
+            self.frames_with_headroom = 0;
+        }
+
+        if self.frames_over_budget >= GraphicsQuality::DEGRADE_AFTER_FRAMES {
+            self.level = self.level.lower();
+            self.frames_over_budget = 0;
+        } else if self.frames_with_headroom >= GraphicsQuality::RESTORE_AFTER_FRAMES {
+            self.level = self.level.higher();
+            self.frames_with_headroom = 0;
+        }
+    }
+}
+
+/// Lowers the graphics quality when frames are too slow, and raises it when they are fast, unless the player has chosen a quality.
+fn adapt_graphics_quality(
+    time: Res<Time>,
+    settings: Res<GraphicsSettings>,
+    mut quality: ResMut<GraphicsQuality>,
+) {
+    match settings.quality_override {
+        Some(level) => {
+            quality
+                .map_unchanged(|quality| &mut quality.level)
+                .set_if_neq(level);
+        }
+        None => {
+            let previous_level = quality.level;
+            // Work directly on the inner value, so the frame counters don't trigger change detection
+            quality
+                .bypass_change_detection()
+                .record_frame(time.raw_delta_seconds(), settings.frame_budget);
+
+            if quality.level != previous_level {
+                info!("Graphics quality changed to {:?}", quality.level);
+                quality.set_changed();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The frame budget used in these tests.
+    const BUDGET: f32 = 1. / 60.;
+
+    #[test]
+    fn slow_frames_lower_quality() {
+        let mut quality = GraphicsQuality::default();
+
+        for _ in 0..GraphicsQuality::DEGRADE_AFTER_FRAMES {
+            quality.record_frame(BUDGET * 2., BUDGET);
+        }
+        assert_eq!(quality.level, QualityLevel::Medium);
+
+        for _ in 0..GraphicsQuality::DEGRADE_AFTER_FRAMES {
+            quality.record_frame(BUDGET * 2., BUDGET);
+        }
+        assert_eq!(quality.level, QualityLevel::Low);
+        assert!(!quality.show_effects());
+    }
+
+    #[test]
+    fn occasional_slow_frames_are_tolerated() {
+        let mut quality = GraphicsQuality::default();
+
+        for _ in 0..100 {
+            quality.record_frame(BUDGET * 2., BUDGET);
+            quality.record_frame(BUDGET / 2., BUDGET);
+        }
+
+        assert_eq!(quality.level, QualityLevel::High);
+    }
+
+    #[test]
+    fn quality_is_restored_with_headroom() {
+        let mut quality = GraphicsQuality {
+            level: QualityLevel::Low,
+            ..default()
+        };
+
+        for _ in 0..GraphicsQuality::RESTORE_AFTER_FRAMES {
+            quality.record_frame(BUDGET / 2., BUDGET);
+        }
+
+        assert_eq!(quality.level, QualityLevel::Medium);
+    }
+}
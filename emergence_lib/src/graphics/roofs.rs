@@ -0,0 +1,184 @@
+//! Draws roofs over sheltered regions, and cuts them away so the player can see inside.
+//!
+//! Which tiles are covered by each roof is determined by the [`Enclosures`] detected by the walls.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::hexagonal_column, player_interaction::camera::FocusedTile,
+    simulation::geometry::MapGeometry, structures::walls::Enclosures,
+};
+
+/// The distance between the highest tile under a roof and the roof itself.
+const ROOF_CLEARANCE: f32 = 2.;
+
+/// The thickness of each roof tile.
+const ROOF_THICKNESS: f32 = 0.1;
+
+/// The color of roofs.
+const ROOF_COLOR: Color = Color::rgb(0.45, 0.3, 0.2);
+
+/// The opacity of a roof while it is cut away.
+const CUTAWAY_OPACITY: f32 = 0.15;
+
+/// The change in opacity per second while a roof fades in or out.
+const FADE_SPEED: f32 = 3.;
+
+/// Draws roofs, and fades them out when the camera focuses on the region beneath.
+pub(super) struct RoofGraphicsPlugin;
+
+impl Plugin for RoofGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoofSettings>()
+            .init_resource::<RoofMaterials>()
+            .add_system(spawn_roofs)
+            .add_system(fade_roofs.after(spawn_roofs));
+    }
+}
+
+/// Controls how roofs are drawn.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RoofSettings {
+    /// Should the roof over the region that the camera is focused on be cut away?
+    pub(crate) cutaway: bool,
+}
+
+impl Default for RoofSettings {
+    fn default() -> Self {
+        RoofSettings { cutaway: true }
+    }
+}
+
+/// A purely decorative roof tile, covering a single sheltered tile.
+#[derive(Component, Debug)]
+struct Roof;
+
+/// The material of each roof, indexed in the same order as [`Enclosures::shelters`].
+///
+/// Each roof has its own material, so they can be faded independently.
+#[derive(Resource, Debug, Default)]
+struct RoofMaterials {
+    /// One material per roof
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+/// Rebuilds every roof whenever the [`Enclosures`] change.
+fn spawn_roofs(
+    mut commands: Commands,
+    enclosures: Res<Enclosures>,
+    map_geometry: Res<MapGeometry>,
+    roof_query: Query<Entity, With<Roof>>,
+    mut roof_materials: ResMut<RoofMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !enclosures.is_changed() {
+        return;
+    }
+
+    for entity in roof_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for handle in roof_materials.materials.drain(..) {
+        materials.remove(handle);
+    }
+
+    let mesh = meshes.add(hexagonal_column(&map_geometry.layout, ROOF_THICKNESS));
+
+    for shelter in enclosures.shelters() {
+        let material = materials.add(StandardMaterial {
+            base_color: ROOF_COLOR,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+
+        // Roofs are flat, so they must clear the highest tile that they cover
+        let roof_height = shelter
+            .iter()
+            .map(|tile_pos| tile_pos.into_world_pos(&map_geometry).y)
+            .fold(f32::MIN, f32::max)
+            + ROOF_CLEARANCE;
+
+        for tile_pos in shelter {
+            let xz = map_geometry.layout.hex_to_world_pos(tile_pos.hex);
+
+            commands.spawn((
+                Roof,
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(xz.x, roof_height, xz.y),
+                    ..default()
+                },
+            ));
+        }
+
+        roof_materials.materials.push(material);
+    }
+}
+
+/// Moves `current` towards `target`, changing it by no more than `max_delta`.
+fn fade_towards(current: f32, target: f32, max_delta: f32) -> f32 {
+    if current < target {
+        (current + max_delta).min(target)
+    } else {
+        (current - max_delta).max(target)
+    }
+}
+
+/// Fades out the roof over the tile that the camera is focused on, and fades every other roof back in.
+fn fade_roofs(
+    time: Res<Time>,
+    settings: Res<RoofSettings>,
+    focused_tile: Res<FocusedTile>,
+    enclosures: Res<Enclosures>,
+    roof_materials: Res<RoofMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cut_away = if settings.cutaway {
+        enclosures.shelter_containing(focused_tile.tile_pos)
+    } else {
+        None
+    };
+    let max_delta = FADE_SPEED * time.delta_seconds();
+
+    for (index, handle) in roof_materials.materials.iter().enumerate() {
+        let target = if cut_away == Some(index) {
+            CUTAWAY_OPACITY
+        } else {
+            1.
+        };
+
+        // Borrowing the material mutably marks it as modified, so only do so when it needs to fade
+        let current = match materials.get(handle) {
+            Some(material) => material.base_color.a(),
+            None => continue,
+        };
+
+        if current != target {
+            if let Some(material) = materials.get_mut(handle) {
+                material
+                    .base_color
+                    .set_a(fade_towards(current, target, max_delta));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fading_stops_at_the_target() {
+        assert_eq!(fade_towards(1., CUTAWAY_OPACITY, 10.), CUTAWAY_OPACITY);
+        assert_eq!(fade_towards(CUTAWAY_OPACITY, 1., 10.), 1.);
+    }
+
+    #[test]
+    fn fading_is_gradual() {
+        assert_eq!(fade_towards(1., 0., 0.25), 0.75);
+        assert_eq!(fade_towards(0., 1., 0.25), 0.25);
+    }
+}
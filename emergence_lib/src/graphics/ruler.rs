@@ -0,0 +1,52 @@
+//! Draws the ruler used to measure distances on the map.
+
+use bevy::prelude::*;
+
+use crate::player_interaction::ruler::Ruler;
+
+use super::overlay_layers::{OverlayLayer, OverlayLayers};
+
+/// Displays the ruler as a line over the map.
+pub(super) struct RulerGraphicsPlugin;
+
+impl Plugin for RulerGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(display_ruler);
+    }
+}
+
+/// The color of the tiles at either end of the ruler.
+const END_COLOR: Color = Color::rgba(1.0, 0.27, 0.0, 0.6);
+
+/// The color of the path between the ends of the ruler.
+const PATH_COLOR: Color = Color::GOLD;
+
+/// Redraws the [`OverlayLayer::Ruler`] whenever the [`Ruler`] is moved.
+fn display_ruler(ruler: Res<Ruler>, mut overlay_layers: ResMut<OverlayLayers>) {
+    if !ruler.is_changed() {
+        return;
+    }
+
+    // Keep the last measurement around, so the layer can simply be hidden while the ruler is put away
+    overlay_layers.set_visible(OverlayLayer::Ruler, ruler.start().is_some());
+    if ruler.start().is_none() {
+        return;
+    }
+
+    let layer = overlay_layers.layer_mut(OverlayLayer::Ruler);
+    layer.clear();
+
+    for tile_pos in ruler.start().into_iter().chain(ruler.end()) {
+        layer.color_tile(tile_pos, END_COLOR);
+    }
+
+    if let Some(measurement) = ruler.measurement() {
+        if let Some(path) = &measurement.path {
+            layer.polyline(path.tiles.clone(), PATH_COLOR);
+        }
+
+        if let Some(end) = ruler.end() {
+            layer.label(end, format!("{} tiles", measurement.hex_distance));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Recolors the landscape as the seasons change.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::terrain::{terrain_material, TerrainHandles, FOG_BRIGHTNESS},
+    simulation::time::Season,
+    terrain::Terrain,
+};
+
+/// The color that dry land turns in autumn.
+const AUTUMN_COLOR: Color = Color::rgb(0.8, 0.45, 0.15);
+
+/// The color of snow, which covers dry land in winter.
+const SNOW_COLOR: Color = Color::rgb(0.95, 0.95, 1.);
+
+/// Swaps the terrain colors for seasonal variants.
+pub(super) struct SeasonGraphicsPlugin;
+
+impl Plugin for SeasonGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(recolor_terrain_for_season);
+    }
+}
+
+/// The color of `terrain` during `season`.
+///
+/// Water is left untouched: freezing is handled separately.
+fn seasonal_color(terrain: Terrain, season: Season) -> Color {
+    let base_color = terrain_material(terrain).base_color;
+
+    let (tint, strength) = match (terrain, season) {
+        (Terrain::Water, _) | (_, Season::Spring | Season::Summer) => return base_color,
+        (_, Season::Autumn) => (AUTUMN_COLOR, 0.4),
+        (_, Season::Winter) => (SNOW_COLOR, 0.8),
+    };
+
+    let [r, g, b, a] = base_color.as_rgba_f32();
+    let [tint_r, tint_g, tint_b, _] = tint.as_rgba_f32();
+    Color::rgba(
+        r + (tint_r - r) * strength,
+        g + (tint_g - g) * strength,
+        b + (tint_b - b) * strength,
+        a,
+    )
+}
+
+/// Updates the shared terrain materials whenever the [`Season`] changes.
+fn recolor_terrain_for_season(
+    season: Res<Season>,
+    terrain_handles: Res<TerrainHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !season.is_changed() {
+        return;
+    }
+
+    for (&terrain, handle) in terrain_handles.terrain_materials.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = seasonal_color(terrain, *season);
+        }
+    }
+
+    for (&terrain, handle) in terrain_handles.fogged_materials.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            let [r, g, b, a] = seasonal_color(terrain, *season).as_rgba_f32();
+            material.base_color = Color::rgba(
+                r * FOG_BRIGHTNESS,
+                g * FOG_BRIGHTNESS,
+                b * FOG_BRIGHTNESS,
+                a,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_keeps_its_color() {
+        assert_eq!(
+            seasonal_color(Terrain::Water, Season::Winter),
+            Terrain::Water.material().base_color
+        );
+    }
+
+    #[test]
+    fn winter_whitens_the_land() {
+        let summer = seasonal_color(Terrain::Rocky, Season::Summer).as_rgba_f32();
+        let winter = seasonal_color(Terrain::Rocky, Season::Winter).as_rgba_f32();
+
+        assert!(winter[0] > summer[0]);
+        assert!(winter[2] > summer[2]);
+    }
+}
@@ -3,22 +3,75 @@
 use bevy::prelude::*;
 
 use crate::{
-    asset_management::terrain::TerrainHandles,
-    player_interaction::selection::{CurrentSelection, HoveredTiles},
-    simulation::geometry::TilePos,
+    asset_management::{palette::ColorPalette, terrain::TerrainHandles},
+    player_interaction::{
+        overlay::SignalOverlay,
+        selection::{CurrentSelection, HoveredTiles},
+    },
+    signals::{SignalStrength, Signals},
+    simulation::{freezing::Frozen, geometry::TilePos, vision::FogOfWar},
     terrain::Terrain,
 };
 
-/// Shows which tiles are being hovered and selected.
+use super::{quality::GraphicsQuality, tint::TileTints};
+
+/// The number of orders of magnitude below the strongest signal that are distinguished by the signal overlay.
+///
+/// Signals fall off exponentially with distance, so a logarithmic scale shows gradients far more clearly.
+const HEATMAP_DECADES: f32 = 4.;
+
+/// Shows which tiles are being hovered and selected, which are hidden by the fog of war or unexplored, and which have frozen over.
+///
+/// When the [`SignalOverlay`] is active, tiles with the chosen signal are colored by its strength instead.
+/// Otherwise, tiles containing organisms are colored by their [`TileTints`].
+///
+/// The overlay is refreshed less often when the [`GraphicsQuality`] is reduced.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn display_tile_interactions(
     current_selection: Res<CurrentSelection>,
     hovered_tiles: Res<HoveredTiles>,
-    mut terrain_query: Query<(&mut Handle<StandardMaterial>, &Terrain, &TilePos)>,
+    fog_of_war: Res<FogOfWar>,
+    mut terrain_query: Query<(
+        &mut Handle<StandardMaterial>,
+        &Terrain,
+        &TilePos,
+        Option<&Frozen>,
+    )>,
+    newly_frozen_query: Query<(), (Added<Frozen>, With<Terrain>)>,
+    mut thawed: RemovedComponents<Frozen>,
     materials: Res<TerrainHandles>,
+    signal_overlay: Res<SignalOverlay>,
+    signals: Res<Signals>,
+    tile_tints: Res<TileTints>,
+    palette: Res<ColorPalette>,
+    graphics_quality: Res<GraphicsQuality>,
+    mut frames_since_overlay_refresh: Local<u32>,
 ) {
-    if current_selection.is_changed() || hovered_tiles.is_changed() {
+    let freezing_changed = !newly_frozen_query.is_empty() || thawed.iter().next().is_some();
+
+    // Signals change every frame, so the overlay must be redrawn constantly while it is active
+    let overlay_active = signal_overlay.signal_type().is_some();
+    *frames_since_overlay_refresh += 1;
+    let overlay_refresh_due = overlay_active
+        && *frames_since_overlay_refresh >= graphics_quality.overlay_refresh_interval();
+
+    if current_selection.is_changed()
+        || hovered_tiles.is_changed()
+        || fog_of_war.is_changed()
+        || freezing_changed
+        || signal_overlay.is_changed()
+        || tile_tints.is_changed()
+        || palette.is_changed()
+        || overlay_refresh_due
+    {
+        *frames_since_overlay_refresh = 0;
+
+        let max_strength = signal_overlay
+            .signal_type()
+            .map(|signal_type| signals.max_strength(signal_type));
+
         // PERF: We should probably avoid a linear scan over all tiles here
-        for (mut material, terrain, &tile_pos) in terrain_query.iter_mut() {
+        for (mut material, terrain, &tile_pos, maybe_frozen) in terrain_query.iter_mut() {
             let hovered = hovered_tiles.contains(&tile_pos);
             let selected = if let CurrentSelection::Terrain(selected_tiles) = &*current_selection {
                 selected_tiles.contains_tile(tile_pos)
@@ -26,7 +79,30 @@ pub(super) fn display_tile_interactions(
                 false
             };
 
-            *material = materials.get_material(terrain, hovered, selected);
+            let sight = fog_of_war.sight(tile_pos);
+
+            let frozen = maybe_frozen.is_some();
+
+            // Tiles without the signal are drawn as normal, so the heatmap shows where the signal ends
+            let heat = match (signal_overlay.signal_type(), max_strength) {
+                (Some(signal_type), Some(max_strength)) if max_strength > SignalStrength::ZERO => {
+                    let strength = signals.get(signal_type, tile_pos);
+                    if strength > SignalStrength::ZERO {
+                        let relative_strength = strength.value() / max_strength.value();
+                        Some((1. + relative_strength.log10() / HEATMAP_DECADES).max(0.))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+
+            let tint = tile_tints.get(tile_pos).map(|tint| (tint, *palette));
+
+            let new_material =
+                materials.get_material(terrain, hovered, selected, sight, frozen, heat, tint);
+
+            material.set_if_neq(new_material);
         }
     }
 }
@@ -1 +1,145 @@
 //! Graphics and animation code for structures.
+
+use bevy::prelude::*;
+use bevy_mod_raycast::RaycastMesh;
+
+use crate::{
+    asset_management::{
+        manifest::{Id, Structure, StructureManifest},
+        structures::{GhostKind, StructureHandles},
+    },
+    player_interaction::selection::ObjectInteraction,
+    simulation::geometry::{MapGeometry, TilePos},
+    structures::construction::{ConstructionStage, Forbidden, Ghost, Preview, Unsuitable},
+};
+
+use super::InheritedMaterial;
+
+/// Adds the model used to draw each newly spawned structure, ghost and preview.
+///
+/// Structures and ghosts can also be picked with the cursor.
+pub(super) fn spawn_structure_models(
+    structure_query: Query<
+        (
+            Entity,
+            &Id<Structure>,
+            &TilePos,
+            Option<&Ghost>,
+            Option<&Preview>,
+            Option<&Forbidden>,
+            Option<&Unsuitable>,
+        ),
+        Added<Id<Structure>>,
+    >,
+    structure_handles: Res<StructureHandles>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (
+        entity,
+        structure_id,
+        tile_pos,
+        maybe_ghost,
+        maybe_preview,
+        maybe_forbidden,
+        maybe_unsuitable,
+    ) in structure_query.iter()
+    {
+        let scene_bundle = SceneBundle {
+            scene: structure_handles
+                .scenes
+                .get(structure_id)
+                .unwrap()
+                .clone_weak(),
+            transform: Transform::from_translation(tile_pos.into_world_pos(&map_geometry)),
+            ..default()
+        };
+        let picking_mesh = structure_handles.picking_mesh.clone_weak();
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(scene_bundle);
+
+        let ghost_kind = match (
+            maybe_ghost,
+            maybe_preview,
+            maybe_forbidden,
+            maybe_unsuitable,
+        ) {
+            (Some(_), _, _, None) => GhostKind::Ghost,
+            (Some(_), _, _, Some(_)) => GhostKind::UnsuitableGhost,
+            (None, Some(_), None, _) => GhostKind::Preview,
+            (None, Some(_), Some(_), _) => GhostKind::ForbiddenPreview,
+            (None, None, ..) => {
+                entity_commands.insert((
+                    RaycastMesh::<Id<Structure>>::default(),
+                    ObjectInteraction::None,
+                    picking_mesh,
+                ));
+                continue;
+            }
+        };
+
+        let ghostly_handle = structure_handles.ghost_materials.get(&ghost_kind).unwrap();
+        entity_commands.insert(InheritedMaterial(ghostly_handle.clone_weak()));
+
+        if maybe_ghost.is_some() {
+            entity_commands.insert((RaycastMesh::<Ghost>::default(), picking_mesh));
+        }
+    }
+}
+
+/// The height of a ghost without its own model for its [`ConstructionStage`], relative to the finished structure.
+fn placeholder_height(stage: ConstructionStage) -> f32 {
+    match stage {
+        ConstructionStage::Foundation => 0.3,
+        ConstructionStage::Framework => 0.65,
+        ConstructionStage::Complete => 1.,
+    }
+}
+
+/// Swaps the model of each ghost as it passes through each [`ConstructionStage`].
+///
+/// Structures without artwork for a stage grow taller instead, so they don't pop straight from a ghost to the finished model.
+pub(super) fn display_construction_stage(
+    mut ghost_query: Query<
+        (
+            &Id<Structure>,
+            &ConstructionStage,
+            &mut Handle<Scene>,
+            &mut Transform,
+        ),
+        (
+            With<Ghost>,
+            Or<(Changed<ConstructionStage>, Added<Handle<Scene>>)>,
+        ),
+    >,
+    structure_manifest: Res<StructureManifest>,
+    structure_handles: Res<StructureHandles>,
+    asset_server: Res<AssetServer>,
+) {
+    for (&structure_id, &stage, mut scene_handle, mut transform) in ghost_query.iter_mut() {
+        let construction_models = structure_manifest.get(structure_id).construction_models();
+
+        let (new_scene, height) = match construction_models.get(stage) {
+            // The asset server only loads each file once, and hands back the same handle afterwards
+            Some(model) => (
+                asset_server.load(format!("structures/{model}.gltf#Scene0")),
+                1.,
+            ),
+            None => (
+                structure_handles
+                    .scenes
+                    .get(&structure_id)
+                    .unwrap()
+                    .clone_weak(),
+                placeholder_height(stage),
+            ),
+        };
+
+        scene_handle.set_if_neq(new_scene);
+
+        if transform.scale.y != height {
+            transform.scale.y = height;
+        }
+    }
+}
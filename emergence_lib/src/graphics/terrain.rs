@@ -0,0 +1,42 @@
+//! Graphics for terrain tiles.
+
+use bevy::prelude::*;
+use bevy_mod_raycast::RaycastMesh;
+
+use crate::{
+    asset_management::terrain::TerrainHandles,
+    simulation::geometry::{MapGeometry, TilePos},
+    terrain::Terrain,
+};
+
+/// Adds the mesh and material used to draw and pick each newly spawned terrain tile.
+///
+/// Tiles are drawn as columns, stretched to the height of the tile.
+pub(super) fn spawn_terrain_meshes(
+    terrain_query: Query<(Entity, &Terrain, &TilePos), Added<Terrain>>,
+    terrain_handles: Res<TerrainHandles>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, terrain, tile_pos) in terrain_query.iter() {
+        let world_pos = tile_pos.into_world_pos(&map_geometry);
+
+        commands.entity(entity).insert((
+            RaycastMesh::<Terrain>::default(),
+            PbrBundle {
+                mesh: terrain_handles.mesh.clone_weak(),
+                material: terrain_handles
+                    .terrain_materials
+                    .get(terrain)
+                    .unwrap()
+                    .clone_weak(),
+                transform: Transform::from_xyz(world_pos.x, 0.0, world_pos.z).with_scale(Vec3 {
+                    x: 1.,
+                    y: world_pos.y,
+                    z: 1.,
+                }),
+                ..default()
+            },
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! Tints the tiles under organisms, so players can see who they belong to and what is wrong with them at a glance.
+//!
+//! Colors are drawn from the current [`ColorPalette`], so they remain distinct for colorblind players.
+
+use bevy::{prelude::*, utils::HashMap};
+use emergence_macros::IterableEnum;
+
+use crate::{
+    asset_management::{
+        manifest::{Id, Unit, UnitManifest},
+        palette::{
+            burning_tint, captured_tint, colony_tint, dormant_tint, wild_tint, ColorPalette,
+        },
+    },
+    organisms::{growth::Stunted, Organism},
+    player_interaction::InteractionSystem,
+    simulation::{fire::OnFire, geometry::TilePos},
+    structures::traps::Captured,
+};
+
+use super::selection::display_tile_interactions;
+
+/// Computes the tint of each tile that contains an organism.
+pub(super) struct TintPlugin;
+
+impl Plugin for TintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColorPalette>()
+            .init_resource::<TintSettings>()
+            .init_resource::<TileTints>()
+            .add_system(
+                update_tile_tints
+                    .after(InteractionSystem::SelectTiles)
+                    .before(display_tile_interactions),
+            );
+    }
+}
+
+/// Controls which tile tints are shown.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TintSettings {
+    /// Should tiles be tinted by the faction of the creatures on them?
+    pub(crate) show_factions: bool,
+    /// Should tiles be tinted by the status effects of the organisms on them?
+    pub(crate) show_status: bool,
+}
+
+impl Default for TintSettings {
+    fn default() -> Self {
+        TintSettings {
+            show_factions: true,
+            show_status: true,
+        }
+    }
+}
+
+/// The tint applied to a tile because of the organisms on it.
+///
+/// Variants are ordered from least to most important: when several apply to one tile, the last wins.
+#[derive(IterableEnum, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum TileTint {
+    /// A member of the colony.
+    Colony,
+    /// A creature that does not belong to the colony.
+    Wild,
+    /// An organism whose growth requirements are not met.
+    Dormant,
+    /// A creature held by a trap.
+    Captured,
+    /// An organism that is on fire.
+    Burning,
+}
+
+impl TileTint {
+    /// The color of this tint in the provided `palette`.
+    pub(crate) fn color(&self, palette: ColorPalette) -> Color {
+        match self {
+            TileTint::Colony => colony_tint(palette),
+            TileTint::Wild => wild_tint(palette),
+            TileTint::Dormant => dormant_tint(palette),
+            TileTint::Captured => captured_tint(palette),
+            TileTint::Burning => burning_tint(palette),
+        }
+    }
+}
+
+/// The tint of every tile that has one.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub(crate) struct TileTints {
+    /// The tint of each tinted tile
+    tints: HashMap<TilePos, TileTint>,
+}
+
+impl TileTints {
+    /// The tint of the tile at `tile_pos`, if any.
+    pub(crate) fn get(&self, tile_pos: TilePos) -> Option<TileTint> {
+        self.tints.get(&tile_pos).copied()
+    }
+}
+
+/// The status of a single organism that can be shown as a tint.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct OrganismStatus {
+    /// Does this creature belong to the colony? [`None`] for organisms that aren't part of any faction, like plants.
+    is_colony: Option<bool>,
+    /// Is it on fire?
+    burning: bool,
+    /// Is it unable to grow?
+    dormant: bool,
+    /// Is it stuck in a trap?
+    captured: bool,
+}
+
+/// Picks the most important tint for an organism, given the `settings`.
+fn organism_tint(status: OrganismStatus, settings: TintSettings) -> Option<TileTint> {
+    if settings.show_status {
+        if status.burning {
+            return Some(TileTint::Burning);
+        } else if status.captured {
+            return Some(TileTint::Captured);
+        } else if status.dormant {
+            return Some(TileTint::Dormant);
+        }
+    }
+
+    if settings.show_factions {
+        match status.is_colony {
+            Some(true) => return Some(TileTint::Colony),
+            Some(false) => return Some(TileTint::Wild),
+            None => (),
+        }
+    }
+
+    None
+}
+
+/// Recomputes the tint of each tile from the organisms standing on it.
+fn update_tile_tints(
+    organism_query: Query<
+        (
+            &TilePos,
+            Option<&Id<Unit>>,
+            Option<&OnFire>,
+            Option<&Stunted>,
+            Option<&Captured>,
+        ),
+        With<Organism>,
+    >,
+    unit_manifest: Res<UnitManifest>,
+    settings: Res<TintSettings>,
+    tile_tints: ResMut<TileTints>,
+) {
+    let mut tints: HashMap<TilePos, TileTint> = HashMap::default();
+
+    for (&tile_pos, maybe_unit_id, maybe_on_fire, maybe_stunted, maybe_captured) in
+        organism_query.iter()
+    {
+        let status = OrganismStatus {
+            is_colony: maybe_unit_id.map(|&unit_id| unit_manifest.get(unit_id).nest().is_some()),
+            burning: maybe_on_fire.is_some(),
+            dormant: maybe_stunted.is_some(),
+            captured: maybe_captured.is_some(),
+        };
+
+        if let Some(tint) = organism_tint(status, *settings) {
+            let existing = tints.entry(tile_pos).or_insert(tint);
+            *existing = (*existing).max(tint);
+        }
+    }
+
+    tile_tints
+        .map_unchanged(|tile_tints| &mut tile_tints.tints)
+        .set_if_neq(tints);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_tints_override_faction_tints() {
+        let settings = TintSettings::default();
+        let burning_ant = OrganismStatus {
+            is_colony: Some(true),
+            burning: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            organism_tint(burning_ant, settings),
+            Some(TileTint::Burning)
+        );
+
+        let faction_only = TintSettings {
+            show_factions: true,
+            show_status: false,
+        };
+        assert_eq!(
+            organism_tint(burning_ant, faction_only),
+            Some(TileTint::Colony)
+        );
+    }
+
+    #[test]
+    fn healthy_plants_are_not_tinted() {
+        let plant = OrganismStatus::default();
+        assert_eq!(organism_tint(plant, TintSettings::default()), None);
+
+        let dormant_plant = OrganismStatus {
+            dormant: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            organism_tint(dormant_plant, TintSettings::default()),
+            Some(TileTint::Dormant)
+        );
+    }
+}
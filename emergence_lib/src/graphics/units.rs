@@ -1,12 +1,54 @@
 //! Graphics and animation code for units.
 
-use bevy::prelude::*;
+use bevy::{asset::AssetPath, prelude::*, utils::HashMap};
+use bevy_mod_raycast::RaycastMesh;
+use std::f32::consts::TAU;
 
 use crate::{
-    asset_management::manifest::{Id, Unit},
-    units::item_interaction::UnitInventory,
+    asset_management::{
+        manifest::{Id, Unit, UnitManifest},
+        units::UnitHandles,
+    },
+    simulation::geometry::{MapGeometry, TilePos},
+    structures::traps::Captured,
+    units::{actions::CurrentAction, animation::AnimationState, item_interaction::UnitInventory},
 };
 
+use super::overlay_layers::{OverlayLayer, OverlayLayers};
+
+/// The most units that are drawn on a single tile.
+///
+/// Any others are hidden, and counted in a badge above the tile instead.
+const MAX_UNITS_SHOWN_PER_TILE: usize = 6;
+
+/// How far from the center of the tile crowded units stand, relative to the size of a tile.
+const CROWD_RADIUS: f32 = 0.3;
+
+/// How much higher each unit in a crowd is drawn than the last, so overlapping models are always ordered the same way.
+const CROWD_LAYER_HEIGHT: f32 = 0.01;
+
+/// Adds the model used to draw and pick each newly spawned unit.
+///
+/// Units are moved to the right spot on their tile by [`arrange_crowds`].
+pub(super) fn spawn_unit_models(
+    unit_query: Query<(Entity, &Id<Unit>, &TilePos), Added<Id<Unit>>>,
+    unit_handles: Res<UnitHandles>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, unit_id, tile_pos) in unit_query.iter() {
+        commands.entity(entity).insert((
+            RaycastMesh::<Id<Unit>>::default(),
+            unit_handles.picking_mesh.clone_weak(),
+            SceneBundle {
+                scene: unit_handles.scenes.get(unit_id).unwrap().clone_weak(),
+                transform: Transform::from_translation(tile_pos.into_world_pos(&map_geometry)),
+                ..default()
+            },
+        ));
+    }
+}
+
 /// Shows the item that each unit is holding
 pub(super) fn display_held_item(
     unit_query: Query<&UnitInventory, (With<Id<Unit>>, Changed<UnitInventory>)>,
@@ -15,3 +57,172 @@ pub(super) fn display_held_item(
         // TODO: actually display this
     }
 }
+
+/// The animation clip that a unit is playing, so that it is only restarted when it changes.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct CurrentClip {
+    /// The index of the clip in the unit's model, or `None` if the unit has no animations
+    index: Option<usize>,
+}
+
+/// Plays the animation clip that matches what each unit is doing.
+pub(super) fn animate_units(
+    unit_query: Query<(
+        Entity,
+        &Id<Unit>,
+        &CurrentAction,
+        &UnitInventory,
+        Option<&Captured>,
+        &Handle<Scene>,
+        Option<&CurrentClip>,
+    )>,
+    children: Query<&Children>,
+    mut player_query: Query<&mut AnimationPlayer>,
+    unit_manifest: Res<UnitManifest>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (
+        entity,
+        &unit_id,
+        current_action,
+        unit_inventory,
+        maybe_captured,
+        scene_handle,
+        maybe_clip,
+    ) in unit_query.iter()
+    {
+        let state = AnimationState::new(current_action, unit_inventory, maybe_captured.is_some());
+        let index = unit_manifest.get(unit_id).animations().clip(state);
+        let current_clip = CurrentClip { index };
+
+        if maybe_clip == Some(&current_clip) {
+            continue;
+        }
+
+        // The animation player is only found once the scene has been spawned
+        let maybe_player_entity = children
+            .iter_descendants(entity)
+            .find(|&child| player_query.contains(child));
+
+        if let Some(index) = index {
+            let maybe_scene_path = asset_server.get_handle_path(scene_handle);
+
+            let (scene_path, player_entity) = match (maybe_scene_path, maybe_player_entity) {
+                (Some(scene_path), Some(player_entity)) => (scene_path, player_entity),
+                _ => continue,
+            };
+
+            // Clips are stored alongside the scene in the same model file
+            let clip = asset_server.load(AssetPath::new(
+                scene_path.path().to_path_buf(),
+                Some(format!("Animation{index}")),
+            ));
+            let mut player = player_query.get_mut(player_entity).unwrap();
+            player.play(clip).repeat();
+            player.resume();
+        } else if let Some(player_entity) = maybe_player_entity {
+            // Without a clip for this state, hold still rather than carrying on with the last one
+            player_query.get_mut(player_entity).unwrap().pause();
+        }
+
+        commands.entity(entity).insert(current_clip);
+    }
+}
+
+/// The offset from the center of the tile of the unit in `slot`, when `n_shown` units are drawn on the same tile.
+///
+/// A lone unit stands in the center, while crowds are spread evenly around it.
+fn crowd_offset(slot: usize, n_shown: usize, map_geometry: &MapGeometry) -> Vec3 {
+    if n_shown <= 1 {
+        return Vec3::ZERO;
+    }
+
+    let angle = TAU * slot as f32 / n_shown as f32;
+    let radius = CROWD_RADIUS * map_geometry.layout.hex_size.x;
+
+    Vec3::new(
+        radius * angle.cos(),
+        CROWD_LAYER_HEIGHT * slot as f32,
+        radius * angle.sin(),
+    )
+}
+
+/// Spreads out units that share a tile so they don't overlap, hiding any past [`MAX_UNITS_SHOWN_PER_TILE`].
+///
+/// Units are always arranged in the same order, so crowds don't shuffle around from frame to frame.
+/// The number of hidden units on each tile is shown on the [`OverlayLayer::Crowds`].
+pub(super) fn arrange_crowds(
+    mut unit_query: Query<(Entity, &TilePos, &mut Transform, &mut Visibility), With<Id<Unit>>>,
+    map_geometry: Res<MapGeometry>,
+    mut overlay_layers: ResMut<OverlayLayers>,
+    mut previous_hidden_counts: Local<HashMap<TilePos, usize>>,
+) {
+    let mut crowds: HashMap<TilePos, Vec<Entity>> = HashMap::new();
+    for (entity, &tile_pos, ..) in unit_query.iter() {
+        crowds.entry(tile_pos).or_default().push(entity);
+    }
+
+    let mut hidden_counts: HashMap<TilePos, usize> = HashMap::new();
+
+    for (tile_pos, mut crowd) in crowds {
+        crowd.sort();
+        let n_shown = crowd.len().min(MAX_UNITS_SHOWN_PER_TILE);
+        if crowd.len() > n_shown {
+            hidden_counts.insert(tile_pos, crowd.len() - n_shown);
+        }
+
+        for (slot, entity) in crowd.into_iter().enumerate() {
+            let (_, _, mut transform, mut visibility) = unit_query.get_mut(entity).unwrap();
+
+            let new_visibility = if slot < n_shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            let new_translation =
+                tile_pos.into_world_pos(&map_geometry) + crowd_offset(slot, n_shown, &map_geometry);
+
+            visibility.set_if_neq(new_visibility);
+            if slot < n_shown && transform.translation != new_translation {
+                transform.translation = new_translation;
+            }
+        }
+    }
+
+    if *previous_hidden_counts != hidden_counts {
+        let layer = overlay_layers.layer_mut(OverlayLayer::Crowds);
+        layer.clear();
+        for (&tile_pos, &n_hidden) in hidden_counts.iter() {
+            layer.label(tile_pos, format!("+{n_hidden}"));
+        }
+
+        *previous_hidden_counts = hidden_counts;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lone_units_stand_in_the_center() {
+        let map_geometry = MapGeometry::new(1);
+
+        assert_eq!(crowd_offset(0, 1, &map_geometry), Vec3::ZERO);
+    }
+
+    #[test]
+    fn crowded_units_do_not_overlap() {
+        let map_geometry = MapGeometry::new(1);
+        let offsets: Vec<Vec3> = (0..MAX_UNITS_SHOWN_PER_TILE)
+            .map(|slot| crowd_offset(slot, MAX_UNITS_SHOWN_PER_TILE, &map_geometry))
+            .collect();
+
+        for (i, a) in offsets.iter().enumerate() {
+            for b in offsets.iter().skip(i + 1) {
+                assert!(a.distance(*b) > 0.1);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Visual effects for the weather.
+
+use bevy::prelude::*;
+
+use crate::simulation::{
+    geometry::MapGeometry,
+    weather::{LightningStrike, Weather, WeatherFront, WeatherFronts},
+};
+
+use super::{
+    overlay_layers::{OverlayLayer, OverlayLayers},
+    quality::GraphicsQuality,
+};
+
+/// Handles the display of weather effects.
+pub(super) struct WeatherGraphicsPlugin;
+
+impl Plugin for WeatherGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_lightning_flashes)
+            .add_system(fade_lightning_flashes)
+            .add_system(draw_weather_fronts);
+    }
+}
+
+/// A brief burst of light where lightning has struck.
+#[derive(Component, Debug)]
+struct LightningFlash {
+    /// Counts down until the flash disappears
+    timer: Timer,
+}
+
+/// The brightness of a lightning flash at the moment of the strike.
+const FLASH_INTENSITY: f32 = 50_000.;
+
+/// Creates a flash of light for each lightning strike, unless effects are disabled by the [`GraphicsQuality`].
+fn spawn_lightning_flashes(
+    mut lightning_events: EventReader<LightningStrike>,
+    map_geometry: Res<MapGeometry>,
+    graphics_quality: Res<GraphicsQuality>,
+    mut commands: Commands,
+) {
+    /// How far above the ground the flash is
+    const FLASH_HEIGHT: f32 = 5.;
+    /// How long the flash lasts
+    const FLASH_DURATION_IN_SECONDS: f32 = 0.3;
+
+    if !graphics_quality.show_effects() {
+        // Drain the events, so old strikes don't flash once effects are turned back on
+        lightning_events.clear();
+        return;
+    }
+
+    for strike in lightning_events.iter() {
+        let position = strike.tile_pos.into_world_pos(&map_geometry) + Vec3::Y * FLASH_HEIGHT;
+
+        commands.spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color: Color::ALICE_BLUE,
+                    intensity: FLASH_INTENSITY,
+                    range: 30.,
+                    ..default()
+                },
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            LightningFlash {
+                timer: Timer::from_seconds(FLASH_DURATION_IN_SECONDS, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+/// Dims lightning flashes over time, removing them once they have faded out.
+fn fade_lightning_flashes(
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut LightningFlash, &mut PointLight)>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut point_light) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+
+        if flash.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            point_light.intensity = FLASH_INTENSITY * flash.timer.percent_left();
+        }
+    }
+}
+
+/// The translucent color used to shade tiles beneath a front of `weather`.
+fn front_color(weather: Weather) -> Color {
+    match weather {
+        Weather::Clear => Color::NONE,
+        Weather::Rain => Color::rgba(0.3, 0.4, 0.8, 0.25),
+        Weather::Storm => Color::rgba(0.2, 0.2, 0.35, 0.4),
+        Weather::Drought => Color::rgba(0.9, 0.7, 0.3, 0.25),
+        Weather::Snow => Color::rgba(0.95, 0.95, 1.0, 0.35),
+    }
+}
+
+/// Shades the tiles beneath each weather front, unless effects are disabled by the [`GraphicsQuality`].
+fn draw_weather_fronts(
+    weather_fronts: Res<WeatherFronts>,
+    graphics_quality: Res<GraphicsQuality>,
+    map_geometry: Res<MapGeometry>,
+    mut drawn_fronts: Local<Vec<WeatherFront>>,
+    mut overlay_layers: ResMut<OverlayLayers>,
+) {
+    if graphics_quality.is_changed() {
+        overlay_layers.set_visible(OverlayLayer::Weather, graphics_quality.show_effects());
+    }
+
+    // The fronts resource changes every frame as its timers tick, so compare the fronts themselves
+    if weather_fronts.iter().eq(drawn_fronts.iter()) {
+        return;
+    }
+
+    *drawn_fronts = weather_fronts.iter().cloned().collect();
+    let layer = overlay_layers.layer_mut(OverlayLayer::Weather);
+    layer.clear();
+
+    // Later fronts override earlier ones, just like in the simulation
+    for front in drawn_fronts.iter() {
+        for tile_pos in front.tiles() {
+            if map_geometry.is_valid(tile_pos) {
+                layer.color_tile(tile_pos, front_color(front.weather));
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 //! Provides plugins needed by the Emergence game.
+//!
+//! The simulation itself lives in [`emergence_core`], and is re-exported here.
 #![deny(missing_docs)]
 #![deny(clippy::missing_docs_in_private_items)]
 #![forbid(unsafe_code)]
@@ -6,28 +8,67 @@
 // Often exceeded by queries
 #![allow(clippy::type_complexity)]
 
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+use simulation::generation::GenerationConfig;
+
 pub mod asset_management;
-pub mod curves;
-pub mod enum_iter;
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod graphics;
-pub mod items;
-pub mod organisms;
 pub mod player_interaction;
-pub mod signals;
-pub mod simulation;
-pub mod structures;
-pub mod terrain;
+pub mod research;
 pub mod ui;
-pub mod units;
+
+pub use emergence_core::{
+    curves, enum_iter, items, organisms, signals, simulation, structures, terrain, units,
+};
+
+/// Every plugin needed to play Emergence, to be added after [`DefaultPlugins`](bevy::DefaultPlugins).
+///
+/// Heavy optional subsystems are controlled by cargo features, rather than by this group:
+/// - `audio`: sound effects (enabled by default)
+/// - `weather`: changing weather, storms and lightning (enabled by default)
+/// - `disease`: sickness caught from uncollected corpses, which spreads between units (enabled by default)
+/// - `mods`: extra definition files, read from the folders in `assets/definitions/mods/`
+/// - `inspector`: the egui world inspector
+pub struct EmergencePlugins {
+    /// Configuration settings for world generation
+    pub gen_config: GenerationConfig,
+}
+
+impl PluginGroup for EmergencePlugins {
+    // Without the optional features, the group is returned as soon as it is built
+    #[allow(clippy::let_and_return)]
+    fn build(self) -> PluginGroupBuilder {
+        let plugins = PluginGroupBuilder::start::<Self>()
+            .add(simulation::SimulationPlugin {
+                gen_config: self.gen_config,
+            })
+            .add(player_interaction::InteractionPlugin)
+            .add(graphics::GraphicsPlugin)
+            .add(ui::UiPlugin)
+            .add(asset_management::AssetManagementPlugin);
+
+        #[cfg(feature = "audio")]
+        let plugins = plugins.add(audio::SoundEffectsPlugin);
+
+        plugins
+    }
+}
 
 /// Various app configurations, used for testing.
 ///
 /// Importing between files shared in the `tests` directory appears to be broken with this workspace config?
 /// Followed directions from <https://doc.rust-lang.org/rust-by-example/testing/integration_testing.html>
 pub mod testing {
+    use crate::asset_management::manifest::Id;
     use crate::simulation::generation::GenerationConfig;
+    use crate::simulation::geometry::TilePos;
     use crate::simulation::SimulationPlugin;
+    use crate::terrain::Terrain;
     use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
 
     /// Just [`MinimalPlugins`].
     pub fn minimal_app() -> App {
@@ -38,13 +79,26 @@ pub mod testing {
         app
     }
 
-    /// Just the game logic and simulation
+    /// Just the game logic and simulation, which runs headlessly
     pub fn simulation_app(gen_config: GenerationConfig) -> App {
         let mut app = minimal_app();
         app.add_plugin(SimulationPlugin { gen_config });
         app
     }
 
+    /// Advances `app` by `n_ticks` updates, each exactly `tick_duration` long.
+    ///
+    /// [`TimeUpdateStrategy::ManualDuration`] still counts from the wall clock,
+    /// so slow updates would otherwise make the simulation run further ahead.
+    pub fn run_ticks(app: &mut App, n_ticks: usize, tick_duration: Duration) {
+        for _ in 0..n_ticks {
+            let time = app.world.resource::<Time>();
+            let next_update = time.last_update().unwrap_or_else(|| time.startup()) + tick_duration;
+            app.insert_resource(TimeUpdateStrategy::ManualInstant(next_update));
+            app.update();
+        }
+    }
+
     /// Test users interacting with the app
     pub fn interaction_app(gen_config: GenerationConfig) -> App {
         let mut app = simulation_app(gen_config);
@@ -52,4 +106,87 @@ pub mod testing {
             .add_plugin(crate::player_interaction::InteractionPlugin);
         app
     }
+
+    /// Describes a small, hand-made world to run tests and examples in.
+    ///
+    /// Unlike [`GenerationConfig::default`], no organisms are placed randomly:
+    /// only the units and structures requested are spawned.
+    ///
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use emergence_lib::terrain::Terrain;
+    /// use emergence_lib::testing::{minimal_app, WorldBuilder};
+    ///
+    /// let mut app = minimal_app();
+    /// WorldBuilder::hex_radius(10)
+    ///     .with_terrain_fill(Terrain::Plain)
+    ///     .spawn_unit("ant", (0, 0))
+    ///     .spawn_structure("ant_hive", (1, 0))
+    ///     .build(&mut app);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct WorldBuilder {
+        /// The generation settings that describe the world
+        gen_config: GenerationConfig,
+    }
+
+    impl WorldBuilder {
+        /// Starts describing a world that extends `radius` tiles from the origin in every direction.
+        pub fn hex_radius(radius: u32) -> Self {
+            WorldBuilder {
+                gen_config: GenerationConfig::default()
+                    .with_map_radius(radius)
+                    .without_random_organisms(),
+            }
+        }
+
+        /// Sets the seed used to generate the terrain height.
+        pub fn with_seed(mut self, seed: u64) -> Self {
+            self.gen_config = self.gen_config.with_seed(seed);
+            self
+        }
+
+        /// Covers every tile of the world in `terrain`.
+        pub fn with_terrain_fill(mut self, terrain: Terrain) -> Self {
+            self.gen_config = self.gen_config.with_terrain_fill(terrain);
+            self
+        }
+
+        /// Spawns the unit with the string identifier `unit_id` at `tile_pos`.
+        pub fn spawn_unit(mut self, unit_id: &'static str, tile_pos: impl Into<TilePos>) -> Self {
+            self.gen_config = self
+                .gen_config
+                .with_unit(Id::from_string_id(unit_id), tile_pos.into());
+            self
+        }
+
+        /// Spawns the structure with the string identifier `structure_id` at `tile_pos`.
+        pub fn spawn_structure(
+            mut self,
+            structure_id: &'static str,
+            tile_pos: impl Into<TilePos>,
+        ) -> Self {
+            self.gen_config = self
+                .gen_config
+                .with_structure(Id::from_string_id(structure_id), tile_pos.into());
+            self
+        }
+
+        /// The [`GenerationConfig`] that produces this world.
+        pub fn gen_config(&self) -> &GenerationConfig {
+            &self.gen_config
+        }
+
+        /// Adds the simulation to `app`, which will generate this world on startup.
+        pub fn build(self, app: &mut App) {
+            app.add_plugin(SimulationPlugin {
+                gen_config: self.gen_config,
+            });
+        }
+
+        /// Creates an app that runs just the simulation, in this world.
+        pub fn simulation_app(self) -> App {
+            simulation_app(self.gen_config)
+        }
+    }
 }
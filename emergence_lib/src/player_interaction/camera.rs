@@ -1,6 +1,8 @@
 //! Camera controls and movement.
 //!
 //! This RTS-style camera can zoom, pan and rotate.
+//! Panning can be done with the keyboard, by grabbing the map with the mouse, or by pushing the cursor against the edge of the screen.
+//! The camera glides smoothly towards its [`CameraFocus`], rather than jumping there.
 
 use std::f32::consts::PI;
 use std::f32::consts::TAU;
@@ -8,12 +10,14 @@ use std::f32::consts::TAU;
 use bevy::input::mouse::MouseMotion;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use bevy_mod_raycast::RaycastSource;
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::asset_management::manifest::Id;
 use crate::asset_management::manifest::Structure;
 use crate::asset_management::manifest::Unit;
+use crate::simulation::generation::GenerationFocus;
 use crate::simulation::geometry::Facing;
 use crate::simulation::geometry::MapGeometry;
 use crate::simulation::geometry::TilePos;
@@ -22,6 +26,8 @@ use crate::terrain::Terrain;
 
 use self::speed::Speed;
 
+use super::cursor::CursorPos;
+use super::pause::UiClock;
 use super::selection::CurrentSelection;
 use super::InteractionSystem;
 use super::PlayerAction;
@@ -31,14 +37,24 @@ pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_camera)
+        app.init_resource::<FocusedTile>()
+            .add_startup_system(setup_camera)
             .add_system(mousewheel_zoom.before(zoom))
             .add_system(zoom)
             .add_system(drag_camera.before(set_camera_inclination))
             .add_system(set_camera_inclination.before(InteractionSystem::MoveCamera))
             .add_system(rotate_camera.before(InteractionSystem::MoveCamera))
             .add_system(translate_camera.before(InteractionSystem::MoveCamera))
-            .add_system(move_camera_to_goal.in_set(InteractionSystem::MoveCamera));
+            .add_system(grab_camera.before(InteractionSystem::MoveCamera))
+            .add_system(
+                resist_leaving_map
+                    .after(translate_camera)
+                    .after(grab_camera)
+                    .after(zoom)
+                    .before(InteractionSystem::MoveCamera),
+            )
+            .add_system(move_camera_to_goal.in_set(InteractionSystem::MoveCamera))
+            .add_system(update_focused_tile.after(InteractionSystem::MoveCamera));
     }
 }
 
@@ -47,6 +63,9 @@ impl Plugin for CameraPlugin {
 /// Should be between the default values of [`CameraSettings`] `min_zoom` and `max_zoom`.
 const STARTING_DISTANCE_FROM_ORIGIN: f32 = 30.;
 
+/// The vertical field of view of the camera, in radians.
+const FIELD_OF_VIEW: f32 = 0.2;
+
 /// Spawns a [`Camera3dBundle`] and associated camera components.
 fn setup_camera(mut commands: Commands, map_geometry: Res<MapGeometry>) {
     let focus = CameraFocus::default();
@@ -56,7 +75,7 @@ fn setup_camera(mut commands: Commands, map_geometry: Res<MapGeometry>) {
 
     let transform = compute_camera_transform(&focus, planar_angle, settings.inclination);
     let projection = Projection::Perspective(PerspectiveProjection {
-        fov: 0.2,
+        fov: FIELD_OF_VIEW,
         ..Default::default()
     });
 
@@ -78,14 +97,14 @@ fn setup_camera(mut commands: Commands, map_geometry: Res<MapGeometry>) {
 /// The position that the camera is looking at.
 ///
 /// When panning and zooming, this struct is updated, rather than modifying the camera's [`Transform`] directly.
-#[derive(Component, Debug)]
-struct CameraFocus {
+#[derive(Component, Debug, Clone)]
+pub(super) struct CameraFocus {
     /// The coordinate that the camera is looking at.
     ///
     /// This should be the top of the column at the center of the screen.
-    translation: Vec3,
+    pub(super) translation: Vec3,
     /// The distance from the camera to the target
-    distance: f32,
+    pub(super) distance: f32,
 }
 
 impl Default for CameraFocus {
@@ -97,9 +116,16 @@ impl Default for CameraFocus {
     }
 }
 
+/// The tile at the center of the screen, which the camera is looking at.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct FocusedTile {
+    /// The tile under the [`CameraFocus`]
+    pub(crate) tile_pos: TilePos,
+}
+
 /// Configure how the camera moves and feels.
 #[derive(Component)]
-struct CameraSettings {
+pub(super) struct CameraSettings {
     /// Controls how fast the camera zooms in and out.
     zoom_speed: Speed,
     /// Controls the rate that the camera can moves from side to side.
@@ -128,6 +154,22 @@ struct CameraSettings {
     ///
     /// This value should be positive.
     inclination_speed: f32,
+    /// How far past the edge of the map the camera's focus can be pushed, in world units.
+    ///
+    /// This value should be positive.
+    border_padding: f32,
+    /// How strongly the camera is pulled back towards the map when its focus is past the edge.
+    ///
+    /// This is the fraction of the overshoot that is removed each second.
+    border_resistance: f32,
+    /// How close the cursor must be to the edge of the screen to pan the camera, in logical pixels.
+    ///
+    /// Set this to zero to disable edge scrolling.
+    edge_scroll_margin: f32,
+    /// How quickly the camera catches up with its [`CameraFocus`].
+    ///
+    /// Higher values are snappier: this is the rate of an exponential decay, per second.
+    smoothing: f32,
 }
 
 impl Default for CameraSettings {
@@ -141,6 +183,10 @@ impl Default for CameraSettings {
             float_radius: 3,
             inclination: 0.5 * PI / 2.,
             inclination_speed: 1.,
+            border_padding: 10.,
+            border_resistance: 0.9,
+            edge_scroll_margin: 5.,
+            smoothing: 12.,
         }
     }
 }
@@ -243,14 +289,14 @@ fn drag_camera(
 fn set_camera_inclination(
     mut camera_query: Query<&mut CameraSettings, With<Camera3d>>,
     actions: Res<ActionState<PlayerAction>>,
-    time: Res<Time>,
+    ui_clock: Res<UiClock>,
 ) {
     let mut settings = camera_query.single_mut();
 
     let delta = if actions.pressed(PlayerAction::TiltCameraUp) {
-        settings.inclination_speed * time.delta_seconds()
+        settings.inclination_speed * ui_clock.delta_seconds()
     } else if actions.pressed(PlayerAction::TiltCameraDown) {
-        -settings.inclination_speed * time.delta_seconds()
+        -settings.inclination_speed * ui_clock.delta_seconds()
     } else {
         return;
     };
@@ -259,10 +305,13 @@ fn set_camera_inclination(
 }
 
 /// Zooms the camera in and out
+///
+/// The point under the cursor stays under the cursor, so players can zoom in on whatever they are pointing at.
 fn zoom(
     mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
     actions: Res<ActionState<PlayerAction>>,
-    time: Res<Time>,
+    cursor_pos: Res<CursorPos>,
+    ui_clock: Res<UiClock>,
 ) {
     let (mut focus, mut settings) = camera_query.single_mut();
 
@@ -270,8 +319,8 @@ fn zoom(
         actions.pressed(PlayerAction::ZoomIn),
         actions.pressed(PlayerAction::ZoomOut),
     ) {
-        (true, false) => -settings.zoom_speed.delta(time.delta()),
-        (false, true) => settings.zoom_speed.delta(time.delta()),
+        (true, false) => -settings.zoom_speed.delta(ui_clock.delta()),
+        (false, true) => settings.zoom_speed.delta(ui_clock.delta()),
         _ => {
             settings.zoom_speed.reset_speed();
             0.0
@@ -279,16 +328,81 @@ fn zoom(
     };
 
     // Zoom in / out on whatever we're looking at
+    let old_distance = focus.distance;
     focus.distance = (focus.distance + delta_zoom).clamp(settings.min_zoom, settings.max_zoom);
+
+    // Slide the focus towards the cursor by the same fraction that the camera moved in
+    if let Some(cursor_world_pos) = cursor_pos.maybe_world_pos() {
+        let zoom_fraction = 1. - focus.distance / old_distance;
+        let offset = (cursor_world_pos - focus.translation) * zoom_fraction;
+        focus.translation.x += offset.x;
+        focus.translation.z += offset.z;
+    }
+}
+
+/// Returns the direction to pan in when the cursor at `cursor_pos` is pushed against the edge of a window of `window_size`.
+///
+/// The cursor position is measured from the bottom-left corner, and the returned direction points right and up the screen.
+/// Each axis is -1, 0 or 1.
+fn edge_scroll_direction(cursor_pos: Vec2, window_size: Vec2, margin: f32) -> Vec2 {
+    /// Which way to scroll along a single axis of the screen.
+    fn axis_direction(position: f32, length: f32, margin: f32) -> f32 {
+        if position < margin {
+            -1.
+        } else if position > length - margin {
+            1.
+        } else {
+            0.
+        }
+    }
+
+    if margin <= 0. {
+        return Vec2::ZERO;
+    }
+
+    Vec2::new(
+        axis_direction(cursor_pos.x, window_size.x, margin),
+        axis_direction(cursor_pos.y, window_size.y, margin),
+    )
+}
+
+/// Moves the `focus` by `xy`, measured relative to the screen, and keeps it floating above the terrain.
+///
+/// Positive x is to the right of the screen, and positive y is up the screen.
+fn pan_focus(
+    focus: &mut CameraFocus,
+    xy: Vec2,
+    facing: &Facing,
+    camera_translation: Vec3,
+    settings: &CameraSettings,
+    map_geometry: &MapGeometry,
+) {
+    // Plane is XZ, but screens and gamepads are XY
+    let unoriented_translation = Vec3 {
+        x: xy.y,
+        y: 0.,
+        z: xy.x,
+    };
+
+    let facing_angle = facing.direction.angle(&map_geometry.layout.orientation);
+    let rotation = Quat::from_rotation_y(facing_angle);
+    let oriented_translation = rotation.mul_vec3(unoriented_translation);
+
+    focus.translation += oriented_translation;
+
+    let nearest_tile_pos = TilePos::from_world_pos(camera_translation, map_geometry);
+    focus.translation.y = map_geometry.average_height(nearest_tile_pos, settings.float_radius);
 }
 
-/// Pan the camera
-fn translate_camera(
+/// Pan the camera, using the keyboard, gamepad or the edges of the screen
+#[allow(clippy::too_many_arguments)]
+pub(super) fn translate_camera(
     mut camera_query: Query<
         (&Transform, &mut CameraFocus, &Facing, &mut CameraSettings),
         With<Camera3d>,
     >,
-    time: Res<Time>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    ui_clock: Res<UiClock>,
     actions: Res<ActionState<PlayerAction>>,
     map_geometry: Res<MapGeometry>,
     selection: Res<CurrentSelection>,
@@ -296,29 +410,34 @@ fn translate_camera(
 ) {
     let (transform, mut focus, facing, mut settings) = camera_query.single_mut();
 
-    // Pan
-    if actions.pressed(PlayerAction::Pan) {
-        let dual_axis_data = actions.axis_pair(PlayerAction::Pan).unwrap();
-        let base_xy = dual_axis_data.xy();
-        let scaled_xy = base_xy
-            * time.delta_seconds()
-            * settings.pan_speed.delta(time.delta())
-            * focus.distance;
-        // Plane is XZ, but gamepads are XY
-        let unoriented_translation = Vec3 {
-            x: scaled_xy.y,
-            y: 0.,
-            z: scaled_xy.x,
-        };
+    let mut base_xy = match actions.axis_pair(PlayerAction::Pan) {
+        Some(dual_axis_data) if actions.pressed(PlayerAction::Pan) => dual_axis_data.xy(),
+        _ => Vec2::ZERO,
+    };
 
-        let facing_angle = facing.direction.angle(&map_geometry.layout.orientation);
-        let rotation = Quat::from_rotation_y(facing_angle);
-        let oriented_translation = rotation.mul_vec3(unoriented_translation);
+    // Only scroll while the player is actually looking at the game
+    if let Ok(window) = window_query.get_single() {
+        if let (true, Some(cursor_pos)) = (window.focused, window.cursor_position()) {
+            let window_size = Vec2::new(window.width(), window.height());
+            base_xy += edge_scroll_direction(cursor_pos, window_size, settings.edge_scroll_margin);
+        }
+    }
 
-        focus.translation += oriented_translation;
+    // Pan
+    if base_xy != Vec2::ZERO {
+        let scaled_xy = base_xy.clamp_length_max(1.)
+            * ui_clock.delta_seconds()
+            * settings.pan_speed.delta(ui_clock.delta())
+            * focus.distance;
 
-        let nearest_tile_pos = TilePos::from_world_pos(transform.translation, &map_geometry);
-        focus.translation.y = map_geometry.average_height(nearest_tile_pos, settings.float_radius);
+        pan_focus(
+            &mut focus,
+            scaled_xy,
+            facing,
+            transform.translation,
+            &settings,
+            &map_geometry,
+        );
     } else {
         settings.pan_speed.reset_speed();
     }
@@ -339,6 +458,108 @@ fn translate_camera(
     }
 }
 
+/// Hold shift and the middle mouse button to grab the map and drag it around
+fn grab_camera(
+    mut camera_query: Query<
+        (&Transform, &mut CameraFocus, &Facing, &CameraSettings),
+        With<Camera3d>,
+    >,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    actions: Res<ActionState<PlayerAction>>,
+    map_geometry: Res<MapGeometry>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+) {
+    if !actions.pressed(PlayerAction::GrabCamera) {
+        mouse_motion_events.clear();
+        return;
+    }
+
+    let (transform, mut focus, facing, settings) = camera_query.single_mut();
+    let window_height = match window_query.get_single() {
+        Ok(window) => window.height(),
+        Err(_) => return,
+    };
+
+    // Move the map by the same distance as the cursor, so the point that was grabbed stays under it
+    let world_units_per_pixel = focus.distance * FIELD_OF_VIEW / window_height;
+
+    for event in mouse_motion_events.iter() {
+        // Mouse motion is measured downwards, and the camera moves opposite to the map
+        let xy = Vec2::new(-event.delta.x, event.delta.y) * world_units_per_pixel;
+
+        pan_focus(
+            &mut focus,
+            xy,
+            facing,
+            transform.translation,
+            settings,
+            &map_geometry,
+        );
+    }
+}
+
+/// Returns where the camera focus at `xz` should be moved to, given the edge of the map is `world_radius` from the origin.
+///
+/// Past the edge, the focus is pulled back towards the map, removing `resistance` of the overshoot every second.
+/// It can never be pushed more than `padding` past the edge.
+fn resist_border(
+    xz: Vec2,
+    world_radius: f32,
+    padding: f32,
+    resistance: f32,
+    delta_seconds: f32,
+) -> Vec2 {
+    let distance = xz.length();
+    if distance <= world_radius {
+        return xz;
+    }
+
+    let overshoot = (distance - world_radius).min(padding);
+    let remaining_overshoot = overshoot * (1. - resistance).powf(delta_seconds);
+
+    xz * ((world_radius + remaining_overshoot) / distance)
+}
+
+/// Softly pushes the camera back towards the map when it is panned or zoomed past the edge.
+///
+/// The bounds are read from [`MapGeometry`], so they adapt to the size of the map.
+fn resist_leaving_map(
+    mut camera_query: Query<(&mut CameraFocus, &CameraSettings), With<Camera3d>>,
+    map_geometry: Res<MapGeometry>,
+    ui_clock: Res<UiClock>,
+) {
+    let (focus, settings) = camera_query.single_mut();
+
+    let xz = Vec2::new(focus.translation.x, focus.translation.z);
+    let new_xz = resist_border(
+        xz,
+        map_geometry.world_radius(),
+        settings.border_padding,
+        settings.border_resistance,
+        ui_clock.delta_seconds(),
+    );
+
+    let new_translation = Vec3::new(new_xz.x, focus.translation.y, new_xz.y);
+    focus
+        .map_unchanged(|focus| &mut focus.translation)
+        .set_if_neq(new_translation);
+}
+
+/// Tracks the tile that the camera is looking at, and generates the map around it.
+fn update_focused_tile(
+    camera_query: Query<&CameraFocus, (With<Camera3d>, Changed<CameraFocus>)>,
+    map_geometry: Res<MapGeometry>,
+    mut focused_tile: ResMut<FocusedTile>,
+    mut generation_focus: ResMut<GenerationFocus>,
+) {
+    if let Ok(focus) = camera_query.get_single() {
+        let tile_pos = TilePos::from_world_pos(focus.translation, &map_geometry);
+
+        focused_tile.set_if_neq(FocusedTile { tile_pos });
+        generation_focus.set_if_neq(GenerationFocus { tile_pos });
+    }
+}
+
 /// Rotates the camera around the [`CameraFocus`].
 fn rotate_camera(
     mut query: Query<&mut Facing, With<Camera3d>>,
@@ -356,12 +577,22 @@ fn rotate_camera(
     }
 }
 
+/// The fraction of the remaining distance that the camera should cover this frame, to catch up with its focus at `rate`.
+///
+/// This is frame-rate independent: two short frames cover the same distance as one long one.
+fn smoothing_fraction(rate: f32, delta_seconds: f32) -> f32 {
+    1. - (-rate * delta_seconds).exp()
+}
+
 /// Move the camera around a central point, constantly looking at it and maintaining a fixed distance.
+///
+/// The camera glides towards the [`CameraFocus`], rather than jumping to it.
 fn move_camera_to_goal(
     mut query: Query<(&mut Transform, &Facing, &CameraFocus, &mut CameraSettings), With<Camera3d>>,
     map_geometry: Res<MapGeometry>,
     mut cached_planar_angle: Local<Option<f32>>,
-    time: Res<Time>,
+    mut smoothed_focus: Local<Option<CameraFocus>>,
+    ui_clock: Res<UiClock>,
 ) {
     /// Differences in target angle below this amount are ignored.
     ///
@@ -386,7 +617,7 @@ fn move_camera_to_goal(
         *cached_planar_angle = Some(final_planar_angle);
     } else {
         // Compute the correct rotation
-        let max_rotation = settings.rotation_speed.delta(time.delta());
+        let max_rotation = settings.rotation_speed.delta(ui_clock.delta());
 
         // Make sure not to overshoot
         let actual_signed_distance = if signed_rotation > 0. {
@@ -399,8 +630,18 @@ fn move_camera_to_goal(
         *cached_planar_angle = Some(intermediate_planar_angle + actual_signed_distance);
     }
 
+    // Like the angle, the smoothed focus starts at its goal, so the camera doesn't sweep in from the origin
+    let fraction = smoothing_fraction(settings.smoothing, ui_clock.delta_seconds());
+    let smoothed_focus = smoothed_focus.get_or_insert_with(|| focus.clone());
+    smoothed_focus.translation = smoothed_focus.translation.lerp(focus.translation, fraction);
+    smoothed_focus.distance += (focus.distance - smoothed_focus.distance) * fraction;
+
     // Replace the previous transform
-    *transform = compute_camera_transform(focus, intermediate_planar_angle, settings.inclination);
+    *transform = compute_camera_transform(
+        smoothed_focus,
+        intermediate_planar_angle,
+        settings.inclination,
+    );
 }
 
 /// Computes the camera transform such that it is looking at `focus`
@@ -423,3 +664,64 @@ fn compute_camera_transform(focus: &CameraFocus, planar_angle: f32, inclination:
 
     transform
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_is_free_inside_the_map() {
+        let xz = Vec2::new(3., -4.);
+        assert_eq!(resist_border(xz, 10., 5., 0.9, 0.1), xz);
+    }
+
+    #[test]
+    fn camera_is_pulled_back_towards_the_map() {
+        let xz = Vec2::new(12., 0.);
+        let new_xz = resist_border(xz, 10., 5., 0.9, 0.1);
+
+        assert!(new_xz.x < 12.);
+        assert!(new_xz.x > 10.);
+        assert_eq!(new_xz.y, 0.);
+    }
+
+    #[test]
+    fn camera_cannot_pass_the_padding() {
+        let xz = Vec2::new(0., -100.);
+        let new_xz = resist_border(xz, 10., 5., 0., 0.1);
+
+        assert!((new_xz.length() - 15.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn edge_scrolling_only_happens_near_the_edges() {
+        let window_size = Vec2::new(800., 600.);
+
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(400., 300.), window_size, 5.),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(2., 300.), window_size, 5.),
+            Vec2::new(-1., 0.)
+        );
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(799., 599.), window_size, 5.),
+            Vec2::new(1., 1.)
+        );
+        // A margin of zero disables edge scrolling
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(0., 0.), window_size, 0.),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn smoothing_is_frame_rate_independent() {
+        let one_long_frame = smoothing_fraction(12., 0.1);
+        let two_short_frames = 1. - (1. - smoothing_fraction(12., 0.05)).powi(2);
+
+        assert!((one_long_frame - two_short_frames).abs() < 1e-5);
+        assert!(one_long_frame > 0. && one_long_frame < 1.);
+    }
+}
@@ -0,0 +1,341 @@
+//! Records and plays back smooth camera paths, for intro pans and cinematic shots.
+//!
+//! Players record a path by dropping waypoints wherever the camera is looking, and scenarios can provide an intro pan.
+//! While a path plays, it drives the [`CameraFocus`]: any attempt to move the camera by hand stops it.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::simulation::scenario::{IntroWaypoint, Scenario};
+
+use super::{
+    camera::{translate_camera, CameraFocus},
+    pause::UiClock,
+    InteractionSystem, PlayerAction,
+};
+
+/// The number of seconds between each recorded waypoint during playback.
+const SECONDS_BETWEEN_RECORDED_WAYPOINTS: f32 = 3.;
+
+/// Records and plays back camera paths.
+pub(super) struct CameraPathPlugin;
+
+impl Plugin for CameraPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CinematicCamera>()
+            .add_system(record_camera_path)
+            .add_system(play_scenario_intro)
+            .add_system(
+                play_camera_path
+                    .after(record_camera_path)
+                    .after(play_scenario_intro)
+                    .after(translate_camera)
+                    .before(InteractionSystem::MoveCamera),
+            );
+    }
+}
+
+/// A single point along a [`CameraPath`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CameraWaypoint {
+    /// The point on the ground that the camera looks at
+    pub(crate) translation: Vec3,
+    /// The distance from the camera to the point it is looking at
+    pub(crate) distance: f32,
+    /// How long the camera takes to travel to this waypoint from the previous one, in seconds
+    ///
+    /// This is ignored for the first waypoint.
+    pub(crate) seconds_from_previous: f32,
+}
+
+impl From<IntroWaypoint> for CameraWaypoint {
+    fn from(waypoint: IntroWaypoint) -> Self {
+        CameraWaypoint {
+            translation: waypoint.translation,
+            distance: waypoint.distance,
+            seconds_from_previous: waypoint.seconds_from_previous,
+        }
+    }
+}
+
+/// A smooth path for the camera to follow, passing through each of its waypoints in turn.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CameraPath {
+    /// The points that the camera passes through, in order
+    waypoints: Vec<CameraWaypoint>,
+}
+
+impl CameraPath {
+    /// Adds a waypoint to the end of this path.
+    pub(crate) fn with_waypoint(mut self, waypoint: CameraWaypoint) -> Self {
+        self.push(waypoint);
+        self
+    }
+
+    /// Adds a waypoint to the end of this path.
+    pub(crate) fn push(&mut self, waypoint: CameraWaypoint) {
+        self.waypoints.push(waypoint);
+    }
+
+    /// Does this path have any waypoints?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.waypoints.is_empty()
+    }
+
+    /// The total time taken to play this path, in seconds.
+    pub(crate) fn duration(&self) -> f32 {
+        self.waypoints
+            .iter()
+            .skip(1)
+            .map(|waypoint| waypoint.seconds_from_previous)
+            .sum()
+    }
+
+    /// Where the camera should be looking and how far away it should be, `elapsed` seconds into the path.
+    ///
+    /// Returns [`None`] once the path is over.
+    /// The path passes exactly through each waypoint, curving smoothly between them.
+    pub(crate) fn sample(&self, elapsed: f32) -> Option<(Vec3, f32)> {
+        if elapsed > self.duration() {
+            return None;
+        }
+
+        // The translation and distance are interpolated together
+        let point = |index: usize| {
+            let waypoint = self.waypoints[index.min(self.waypoints.len() - 1)];
+            waypoint.translation.extend(waypoint.distance)
+        };
+
+        let mut segment_start = 0.;
+        for index in 0..self.waypoints.len().saturating_sub(1) {
+            let segment_duration = self.waypoints[index + 1].seconds_from_previous;
+
+            if elapsed <= segment_start + segment_duration {
+                let t = if segment_duration > 0. {
+                    (elapsed - segment_start) / segment_duration
+                } else {
+                    1.
+                };
+
+                let sampled = catmull_rom(
+                    point(index.saturating_sub(1)),
+                    point(index),
+                    point(index + 1),
+                    point(index + 2),
+                    t,
+                );
+                return Some((sampled.truncate(), sampled.w));
+            }
+
+            segment_start += segment_duration;
+        }
+
+        // Paths with a single waypoint simply look at it
+        let only = point(0);
+        Some((only.truncate(), only.w))
+    }
+}
+
+/// Interpolates between `p1` and `p2` with a Catmull-Rom spline, using `p0` and `p3` to shape the curve.
+///
+/// `t` runs from 0 (at `p1`) to 1 (at `p2`).
+fn catmull_rom(p0: Vec4, p1: Vec4, p2: Vec4, p3: Vec4, t: f32) -> Vec4 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * (2. * p1
+        + (p2 - p0) * t
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+        + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+}
+
+/// A [`CameraPath`] that is currently being played.
+#[derive(Debug, Clone)]
+struct Playback {
+    /// The path being followed
+    path: CameraPath,
+    /// The time since playback began, in seconds
+    elapsed: f32,
+}
+
+/// The camera path that the player has recorded, and the path that is currently playing, if any.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct CinematicCamera {
+    /// The waypoints recorded by the player so far
+    recorded: CameraPath,
+    /// The path that is currently controlling the camera
+    playback: Option<Playback>,
+}
+
+impl CinematicCamera {
+    /// Starts playing `path` from the beginning, replacing any path that is already playing.
+    pub(crate) fn play(&mut self, path: CameraPath) {
+        if !path.is_empty() {
+            self.playback = Some(Playback { path, elapsed: 0. });
+        }
+    }
+
+    /// Stops the path that is currently playing, leaving the camera where it is.
+    pub(crate) fn stop(&mut self) {
+        self.playback = None;
+    }
+
+    /// Is a path currently controlling the camera?
+    pub(crate) fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+}
+
+/// Records the current view as a waypoint, clears the recording, or starts and stops its playback.
+fn record_camera_path(
+    actions: Res<ActionState<PlayerAction>>,
+    camera_query: Query<&CameraFocus, With<Camera3d>>,
+    mut cinematic_camera: ResMut<CinematicCamera>,
+) {
+    if actions.just_pressed(PlayerAction::RecordCameraWaypoint) {
+        let focus = camera_query.single();
+        cinematic_camera.recorded.push(CameraWaypoint {
+            translation: focus.translation,
+            distance: focus.distance,
+            seconds_from_previous: SECONDS_BETWEEN_RECORDED_WAYPOINTS,
+        });
+    }
+
+    if actions.just_pressed(PlayerAction::ClearCameraPath) {
+        cinematic_camera.recorded = CameraPath::default();
+    }
+
+    if actions.just_pressed(PlayerAction::ToggleCameraPlayback) {
+        if cinematic_camera.is_playing() {
+            cinematic_camera.stop();
+        } else {
+            let recorded = cinematic_camera.recorded.clone();
+            cinematic_camera.play(recorded);
+        }
+    }
+}
+
+/// Plays the intro pan of the [`Scenario`] whenever a new scenario begins.
+fn play_scenario_intro(scenario: Res<Scenario>, mut cinematic_camera: ResMut<CinematicCamera>) {
+    // The scenario is modified as its events fire, so only look for new scenarios
+    if !scenario.is_added() {
+        return;
+    }
+
+    let intro_pan = scenario
+        .intro_pan
+        .iter()
+        .fold(CameraPath::default(), |path, &waypoint| {
+            path.with_waypoint(waypoint.into())
+        });
+
+    if !intro_pan.is_empty() {
+        cinematic_camera.play(intro_pan);
+    }
+}
+
+/// Moves the [`CameraFocus`] along the path that is currently playing.
+///
+/// Moving the camera by hand hands control back to the player.
+fn play_camera_path(
+    actions: Res<ActionState<PlayerAction>>,
+    ui_clock: Res<UiClock>,
+    mut camera_query: Query<&mut CameraFocus, With<Camera3d>>,
+    mut cinematic_camera: ResMut<CinematicCamera>,
+) {
+    if !cinematic_camera.is_playing() {
+        return;
+    }
+
+    let manual_control = [
+        PlayerAction::Pan,
+        PlayerAction::GrabCamera,
+        PlayerAction::ZoomIn,
+        PlayerAction::ZoomOut,
+        PlayerAction::SnapToSelection,
+    ];
+    if manual_control
+        .into_iter()
+        .any(|action| actions.pressed(action))
+    {
+        cinematic_camera.stop();
+        return;
+    }
+
+    let mut focus = camera_query.single_mut();
+    let playback = cinematic_camera.playback.as_mut().unwrap();
+    playback.elapsed += ui_clock.delta_seconds();
+
+    match playback.path.sample(playback.elapsed) {
+        Some((translation, distance)) => {
+            focus.translation = translation;
+            focus.distance = distance;
+        }
+        None => cinematic_camera.stop(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A waypoint looking at `x` along the x axis from `distance` away.
+    fn waypoint(x: f32, distance: f32, seconds_from_previous: f32) -> CameraWaypoint {
+        CameraWaypoint {
+            translation: Vec3::new(x, 0., 0.),
+            distance,
+            seconds_from_previous,
+        }
+    }
+
+    #[test]
+    fn paths_pass_through_their_waypoints() {
+        let path = CameraPath::default()
+            .with_waypoint(waypoint(0., 10., 0.))
+            .with_waypoint(waypoint(5., 20., 2.))
+            .with_waypoint(waypoint(-3., 30., 1.));
+
+        assert_eq!(path.duration(), 3.);
+
+        for (elapsed, expected) in [(0., 0), (2., 1), (3., 2)] {
+            let (translation, distance) = path.sample(elapsed).unwrap();
+            let waypoint = path.waypoints[expected];
+
+            assert!(translation.distance(waypoint.translation) < 1e-4);
+            assert!((distance - waypoint.distance).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn paths_end_after_their_duration() {
+        let path = CameraPath::default()
+            .with_waypoint(waypoint(0., 10., 0.))
+            .with_waypoint(waypoint(5., 20., 2.));
+
+        assert!(path.sample(1.).is_some());
+        assert!(path.sample(2.5).is_none());
+    }
+
+    #[test]
+    fn paths_move_smoothly_between_waypoints() {
+        let path = CameraPath::default()
+            .with_waypoint(waypoint(0., 10., 0.))
+            .with_waypoint(waypoint(10., 10., 1.));
+
+        let mut previous_x = 0.;
+        for step in 1..=10 {
+            let (translation, _) = path.sample(step as f32 / 10.).unwrap();
+            assert!(translation.x > previous_x);
+            assert!(translation.x - previous_x < 2.);
+            previous_x = translation.x;
+        }
+    }
+
+    #[test]
+    fn empty_paths_cannot_be_played() {
+        let mut cinematic_camera = CinematicCamera::default();
+        cinematic_camera.play(CameraPath::default());
+
+        assert!(!cinematic_camera.is_playing());
+    }
+}
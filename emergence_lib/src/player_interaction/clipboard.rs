@@ -7,8 +7,11 @@ use leafwing_input_manager::prelude::ActionState;
 use crate::{
     asset_management::manifest::{Id, Structure, StructureManifest},
     simulation::geometry::{Facing, MapGeometry, TilePos},
-    structures::{commands::StructureCommandsExt, construction::Preview, crafting::ActiveRecipe},
-    terrain::Terrain,
+    structures::{
+        commands::StructureCommandsExt, construction::Preview, crafting::ActiveRecipe,
+        ClipboardData,
+    },
+    terrain::{Fertility, SoilMoisture, Terrain},
 };
 
 use super::{cursor::CursorPos, selection::CurrentSelection, InteractionSystem, PlayerAction};
@@ -59,17 +62,6 @@ impl Clipboard {
     }
 }
 
-/// The data copied via the clipboard for a single structure.
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub(crate) struct ClipboardData {
-    /// The identity of the structure.
-    pub(crate) structure_id: Id<Structure>,
-    /// The orientation of the structure.
-    pub(crate) facing: Facing,
-    /// The recipe that this structure makes, if any
-    pub(crate) active_recipe: ActiveRecipe,
-}
-
 impl Clipboard {
     /// Normalizes the positions of the items on the clipboard.
     ///
@@ -248,7 +240,7 @@ fn display_selection(
     preview_query: Query<(&TilePos, &Id<Structure>, &Facing), With<Preview>>,
     structure_manifest: Res<StructureManifest>,
     map_geometry: Res<MapGeometry>,
-    terrain_query: Query<&Terrain>,
+    terrain_query: Query<(&Terrain, &SoilMoisture, &Fertility)>,
 ) {
     if let Some(cursor_pos) = cursor_pos.maybe_tile_pos() {
         let mut desired_previews: HashMap<TilePos, ClipboardData> =
@@ -280,12 +272,17 @@ fn display_selection(
 
         // Handle any remaining new ghosts
         for (&tile_pos, clipboard_data) in desired_previews.iter() {
-            let allowed_terrain_types = structure_manifest
-                .get(clipboard_data.structure_id)
-                .allowed_terrain_types();
+            let structure_data = structure_manifest.get(clipboard_data.structure_id);
             if let Some(terrain_entity) = map_geometry.terrain_index.get(&tile_pos) {
-                let terrain_type = terrain_query.get(*terrain_entity).unwrap();
-                let forbidden = !allowed_terrain_types.contains(terrain_type);
+                let (terrain_type, soil_moisture, fertility) =
+                    terrain_query.get(*terrain_entity).unwrap();
+                let light = map_geometry.light_level(tile_pos);
+                let forbidden = !structure_data.can_be_placed(
+                    terrain_type,
+                    light,
+                    soil_moisture.0,
+                    fertility.0,
+                );
                 commands.spawn_preview(tile_pos, clipboard_data.clone(), forbidden);
             }
         }
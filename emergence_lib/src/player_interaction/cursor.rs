@@ -7,7 +7,7 @@ use leafwing_input_manager::prelude::ActionState;
 use super::{InteractionSystem, PlayerAction};
 use crate::{
     asset_management::manifest::{Id, Structure, Unit},
-    simulation::geometry::TilePos,
+    simulation::geometry::{MapGeometry, TilePos},
     structures::construction::Ghost,
     terrain::Terrain,
 };
@@ -37,10 +37,15 @@ impl Plugin for CursorPlugin {
 }
 
 /// The position of the mouse cursor and what it is hovering over.
+///
+/// This is the single source of truth for converting the cursor's screen position into world and tile coordinates:
+/// systems that need to know where the player is pointing should read this, rather than casting their own rays.
 #[derive(Resource, Default, Debug, Clone, Copy)]
 pub(crate) struct CursorPos {
     /// The tile position that the cursor is over top of.
     tile_pos: Option<TilePos>,
+    /// The point on the surface of the terrain that the cursor is over top of.
+    world_pos: Option<Vec3>,
     /// The screen position of the cursor.
     ///
     /// Measured from the top-left corner in logical units.
@@ -61,11 +66,30 @@ impl CursorPos {
         self.tile_pos
     }
 
+    /// The point in the world under the cursor, if it is on the hex map.
+    ///
+    /// This lies on the surface of the terrain, and so accounts for the height of each tile.
+    pub(crate) fn maybe_world_pos(&self) -> Option<Vec3> {
+        self.world_pos
+    }
+
     /// The position of the cursor on the screen, if available.
     pub(crate) fn maybe_screen_pos(&self) -> Option<Vec2> {
         self.screen_pos
     }
 
+    /// Moves the cursor over the center of the provided tile, or off the map if [`None`] is provided.
+    ///
+    /// Used when playing back recorded inputs, which store tiles rather than screen positions.
+    pub(super) fn set_tile_pos(
+        &mut self,
+        maybe_tile_pos: Option<TilePos>,
+        map_geometry: &MapGeometry,
+    ) {
+        self.tile_pos = maybe_tile_pos;
+        self.world_pos = maybe_tile_pos.map(|tile_pos| tile_pos.into_world_pos(map_geometry));
+    }
+
     /// The hovered unit, if available.
     pub(crate) fn maybe_unit(&self) -> Option<Entity> {
         self.hovered_unit
@@ -115,10 +139,13 @@ fn update_raycast_with_cursor(
 }
 
 /// Updates the location of the cursor and what it is hovering over
+#[allow(clippy::too_many_arguments)]
 fn update_cursor_pos(
     mut cursor_pos: ResMut<CursorPos>,
     camera_query: Query<
         (
+            &Camera,
+            &GlobalTransform,
             &mut RaycastSource<Terrain>,
             &mut RaycastSource<Id<Structure>>,
             &mut RaycastSource<Id<Unit>>,
@@ -126,21 +153,41 @@ fn update_cursor_pos(
         ),
         With<Camera>,
     >,
+    map_geometry: Res<MapGeometry>,
     terrain_query: Query<&TilePos, With<Terrain>>,
     structure_query: Query<Entity, With<Id<Structure>>>,
     unit_query: Query<Entity, With<Id<Unit>>>,
     ghost_query: Query<Entity, With<Ghost>>,
     mut cursor_moved_events: EventReader<CursorMoved>,
 ) {
-    let (terrain_raycast, structure_raycast, unit_raycast, ghost_raycast) = camera_query.single();
+    let (camera, camera_transform, terrain_raycast, structure_raycast, unit_raycast, ghost_raycast) =
+        camera_query.single();
 
-    cursor_pos.tile_pos = if let Some((terrain_entity, _intersection_data)) =
-        terrain_raycast.get_nearest_intersection()
-    {
-        terrain_query.get(terrain_entity).ok().copied()
-    } else {
-        None
+    if let Some(last_mouse_position) = cursor_moved_events.iter().last() {
+        cursor_pos.screen_pos = Some(last_mouse_position.position);
+    }
+
+    // Prefer the terrain column under the cursor, as rounding the hit point would be ambiguous on the sides of columns.
+    // When the terrain is missed, fall back to the ground plane,
+    // using the map layout to convert from world space to the hex coordinate system.
+    let (maybe_tile_pos, maybe_world_pos) = match terrain_raycast.get_nearest_intersection() {
+        Some((terrain_entity, intersection_data)) => (
+            terrain_query.get(terrain_entity).ok().copied(),
+            Some(intersection_data.position()),
+        ),
+        None => {
+            let maybe_world_pos = cursor_pos.screen_pos.and_then(|screen_pos| {
+                ground_plane_intersection(camera, camera_transform, screen_pos)
+            });
+            let maybe_tile_pos = maybe_world_pos
+                .map(|world_pos| TilePos::from_world_pos(world_pos, &map_geometry))
+                .filter(|&tile_pos| map_geometry.is_valid(tile_pos));
+
+            (maybe_tile_pos, maybe_world_pos)
+        }
     };
+    cursor_pos.tile_pos = maybe_tile_pos;
+    cursor_pos.world_pos = maybe_world_pos;
 
     cursor_pos.hovered_structure = if let Some((structure_entity, _intersection_data)) =
         structure_raycast.get_nearest_intersection()
@@ -164,10 +211,28 @@ fn update_cursor_pos(
     } else {
         None
     };
+}
 
-    if let Some(last_mouse_position) = cursor_moved_events.iter().last() {
-        cursor_pos.screen_pos = Some(last_mouse_position.position);
+/// Finds where the ray cast from the `camera` through the `screen_pos` meets the horizontal plane at a height of 0.
+///
+/// Returns `None` if the ray points away from the plane.
+fn ground_plane_intersection(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    screen_pos: Vec2,
+) -> Option<Vec3> {
+    let ray = camera.viewport_to_world(camera_transform, screen_pos)?;
+
+    if ray.direction.y.abs() <= f32::EPSILON {
+        return None;
     }
+
+    let distance = -ray.origin.y / ray.direction.y;
+    if distance < 0. {
+        return None;
+    }
+
+    Some(ray.get_point(distance))
 }
 
 /// Moves the cursor on the screen, based on gamepad or keyboard inputs
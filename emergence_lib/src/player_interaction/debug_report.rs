@@ -0,0 +1,169 @@
+//! Bundles up the state of the game into a text report that players can attach to bug reports.
+//!
+//! The report is assembled in a single frame and written to disk on a background thread,
+//! so the simulation keeps running while it is generated.
+
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+    tasks::IoTaskPool,
+};
+use core::fmt::Display;
+use leafwing_input_manager::prelude::ActionState;
+use std::path::PathBuf;
+
+use crate::{
+    organisms::individuals::NotableIndividuals,
+    simulation::{
+        alerts::AlertLog,
+        director::{DirectorConfig, Prosperity},
+        generation::GenerationConfig,
+        time::{InGameTime, SimulationTick},
+        weather::CurrentWeather,
+    },
+    ui::scaling::UiSettings,
+};
+
+use super::PlayerAction;
+
+/// Lets the player generate debug reports.
+pub(super) struct DebugReportPlugin;
+
+impl Plugin for DebugReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(generate_debug_report);
+    }
+}
+
+/// The folder that debug reports are saved to, relative to the working directory.
+const DEBUG_REPORT_FOLDER: &str = "debug_reports";
+
+/// A plain text summary of the state of the game, split into titled sections.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct DebugReport {
+    /// The title and contents of each section, in order
+    sections: Vec<(&'static str, String)>,
+}
+
+impl DebugReport {
+    /// Adds a section with the provided `title` to the end of the report.
+    pub(crate) fn add_section(&mut self, title: &'static str, contents: impl Display) {
+        self.sections.push((title, contents.to_string()));
+    }
+}
+
+impl Display for DebugReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Emergence debug report")?;
+
+        for (title, contents) in &self.sections {
+            writeln!(f, "\n== {title} ==")?;
+            writeln!(f, "{contents}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Assembles a [`DebugReport`] and saves it to the [`DEBUG_REPORT_FOLDER`] when requested.
+#[allow(clippy::too_many_arguments)]
+fn generate_debug_report(
+    actions: Res<ActionState<PlayerAction>>,
+    simulation_tick: Res<SimulationTick>,
+    in_game_time: Res<InGameTime>,
+    gen_config: Res<GenerationConfig>,
+    director_config: Res<DirectorConfig>,
+    prosperity: Res<Prosperity>,
+    current_weather: Res<CurrentWeather>,
+    alert_log: Res<AlertLog>,
+    notable_individuals: Res<NotableIndividuals>,
+    maybe_ui_settings: Option<Res<UiSettings>>,
+    maybe_diagnostics: Option<Res<Diagnostics>>,
+) {
+    if !actions.just_pressed(PlayerAction::GenerateDebugReport) {
+        return;
+    }
+
+    let mut report = DebugReport::default();
+
+    report.add_section(
+        "Game",
+        format!(
+            "Version: {}\n{}\n{}",
+            env!("CARGO_PKG_VERSION"),
+            *simulation_tick,
+            *in_game_time
+        ),
+    );
+    report.add_section(
+        "World generation",
+        format!("Seed: {}\n{:?}", gen_config.seed(), *gen_config),
+    );
+
+    let fps = maybe_diagnostics
+        .as_ref()
+        .and_then(|diagnostics| diagnostics.get(FrameTimeDiagnosticsPlugin::FPS))
+        .and_then(|fps| fps.smoothed());
+    let fps = match fps {
+        Some(fps) => format!("{fps:.1}"),
+        None => "unknown".to_string(),
+    };
+    report.add_section(
+        "Metrics",
+        format!(
+            "Units: {}\nStructures: {}\nStored items: {}\nProsperity: {:.1}\nWeather: {}\nFPS: {fps}",
+            prosperity.n_units,
+            prosperity.n_structures,
+            prosperity.n_stored_items,
+            prosperity.score(),
+            current_weather.get(),
+        ),
+    );
+
+    report.add_section("Notable individuals", &*notable_individuals);
+
+    let mut recent_alerts = String::new();
+    for (day, alert) in alert_log.recent() {
+        recent_alerts += &format!("[Day {day}] {alert}\n");
+    }
+    report.add_section("Recent alerts", recent_alerts.trim_end());
+
+    let ui_settings = match maybe_ui_settings {
+        Some(ui_settings) => format!("{:?}", *ui_settings),
+        None => "No UI".to_string(),
+    };
+    report.add_section("Settings", format!("{:?}\n{ui_settings}", *director_config));
+
+    let path = PathBuf::from(DEBUG_REPORT_FOLDER)
+        .join(format!("debug_report_tick_{}.txt", simulation_tick.get()));
+    let contents = report.to_string();
+
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = std::fs::create_dir_all(DEBUG_REPORT_FOLDER)
+                .and_then(|_| std::fs::write(&path, contents));
+
+            match result {
+                Ok(()) => info!("Saved debug report to {}", path.display()),
+                Err(error) => error!("Could not save debug report to {}: {error}", path.display()),
+            }
+        })
+        .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DebugReport;
+
+    #[test]
+    fn sections_are_titled_in_order() {
+        let mut report = DebugReport::default();
+        report.add_section("First", 1);
+        report.add_section("Second", "two");
+
+        let text = report.to_string();
+        let first = text.find("== First ==\n1").unwrap();
+        let second = text.find("== Second ==\ntwo").unwrap();
+        assert!(first < second);
+    }
+}
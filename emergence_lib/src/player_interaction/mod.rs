@@ -7,11 +7,21 @@ use leafwing_input_manager::{
     Actionlike,
 };
 
+use crate::units::UnitSystem;
+
 pub(crate) mod abilities;
 pub(crate) mod camera;
+pub(crate) mod camera_path;
 pub(crate) mod clipboard;
 pub(crate) mod cursor;
+pub(crate) mod debug_report;
 pub(crate) mod intent;
+pub(crate) mod naming;
+pub(crate) mod orders;
+pub(crate) mod overlay;
+pub(crate) mod pause;
+pub mod recording;
+pub(crate) mod ruler;
 pub(crate) mod selection;
 pub(crate) mod zoning;
 
@@ -24,12 +34,23 @@ impl Plugin for InteractionPlugin {
             .init_resource::<ActionState<PlayerAction>>()
             .insert_resource(PlayerAction::default_input_map())
             .add_plugin(camera::CameraPlugin)
+            .add_plugin(camera_path::CameraPathPlugin)
             .add_plugin(abilities::AbilitiesPlugin)
             .add_plugin(cursor::CursorPlugin)
+            .add_plugin(debug_report::DebugReportPlugin)
             .add_plugin(intent::IntentPlugin)
+            .add_plugin(naming::NamingPlugin)
+            .add_plugin(orders::OrderHistoryPlugin)
+            .add_plugin(overlay::SignalOverlayPlugin)
+            .add_plugin(pause::PausePlugin)
+            .add_plugin(recording::RecordingPlugin)
+            .add_plugin(ruler::RulerPlugin)
             .add_plugin(selection::SelectionPlugin)
             .add_plugin(clipboard::ClipboardPlugin)
-            .add_plugin(zoning::ZoningPlugin);
+            .add_plugin(zoning::ZoningPlugin)
+            // Units must act after MarkedForDemolition is added,
+            // or we'll get a panic due to inserting a component on a despawned entity
+            .configure_set(InteractionSystem::ManagePreviews.before(UnitSystem::Act));
 
         #[cfg(feature = "debug_tools")]
         app.add_plugin(debug_tools::DebugToolsPlugin);
@@ -62,7 +83,7 @@ pub(crate) enum InteractionSystem {
 /// Actions that the player can take to modify the game world or their view of it.
 ///
 /// This should only store actions that need a dedicated keybinding.
-#[derive(Actionlike, Clone, Debug)]
+#[derive(Actionlike, Clone, Debug, PartialEq, Eq)]
 pub(crate) enum PlayerAction {
     /// Selects a tile or group of tiles.
     Select,
@@ -78,6 +99,8 @@ pub(crate) enum PlayerAction {
     Area,
     /// Modifies the selection to cover a line between the start and end of the selection.
     Line,
+    /// Modifies the selection to cover a rectangle, with the start and end of the selection at opposite corners.
+    Rectangle,
     /// Selects a structure from a wheel menu.
     SelectStructure,
     /// Selects the structure on the tile under the player's cursor.
@@ -86,12 +109,16 @@ pub(crate) enum PlayerAction {
     Pipette,
     /// Sets the zoning of all currently selected tiles to the currently selected structure.
     ///
-    /// If no structure is selected to build, zoning will be set to [`Zoning::None`](zoning::Zoning::None).
+    /// If no structure is selected to build, zoning will be set to [`Zoning::None`](crate::terrain::Zoning::None).
     Zone,
-    /// Sets the zoning of all currently selected tiles to [`Zoning::None`](zoning::Zoning::None).
+    /// Sets the zoning of all currently selected tiles to [`Zoning::None`](crate::terrain::Zoning::None).
     ClearZoning,
-    /// Sets the zoning of all currently selected tiles to [`Zoning::KeepClear`](zoning::Zoning::KeepClear).
+    /// Sets the zoning of all currently selected tiles to [`Zoning::KeepClear`](crate::terrain::Zoning::KeepClear).
     KeepClear,
+    /// Undoes the most recent zoning order, on tiles where work has not yet started.
+    Undo,
+    /// Redoes the most recently undone zoning order.
+    Redo,
     /// Rotates the conents of the clipboard counterclockwise.
     RotateClipboardLeft,
     /// Rotates the contents of the clipboard clockwise.
@@ -100,6 +127,8 @@ pub(crate) enum PlayerAction {
     SnapToSelection,
     /// Drag the camera with the cursor
     DragCamera,
+    /// Grab the map with the cursor and drag it around
+    GrabCamera,
     /// Move the camera from side to side
     Pan,
     /// Move the cursor around the screen
@@ -116,6 +145,44 @@ pub(crate) enum PlayerAction {
     RotateCameraLeft,
     /// Rotates the camera clockwise
     RotateCameraRight,
+    /// Adds the current view to the end of the recorded camera path
+    RecordCameraWaypoint,
+    /// Discards the recorded camera path
+    ClearCameraPath,
+    /// Starts or stops playing the recorded camera path
+    ToggleCameraPlayback,
+    /// Moves focus to the next element of the UI
+    FocusNext,
+    /// Moves focus to the previous element of the UI
+    FocusPrevious,
+    /// Activates the focused element of the UI
+    ActivateFocus,
+    /// Pauses or resumes the simulation
+    TogglePause,
+    /// Places one end of the ruler on the hovered tile
+    Measure,
+    /// Saves a report describing the state of the game, to attach to bug reports
+    GenerateDebugReport,
+    /// Starts or stops recording the player's inputs, to be played back later
+    ToggleInputRecording,
+    /// Shows the next type of signal as a heatmap over the terrain, or hides the heatmap
+    CycleSignalOverlay,
+    /// Shows or hides the fertility of the soil
+    ToggleFertilityOverlay,
+    /// Starts typing a new name for the selected organism
+    RenameSelection,
+    /// Shows or hides the chronicle of past seasons
+    ToggleChronicle,
+    /// Reads the chronicle of the season before the one being read
+    ChronicleEarlier,
+    /// Reads the chronicle of the season after the one being read
+    ChronicleLater,
+    /// Picks the next structure on the hotbar to place
+    NextHotbarSlot,
+    /// Picks the previous structure on the hotbar to place
+    PreviousHotbarSlot,
+    /// Shows or hides the family tree of the colony
+    ToggleGenealogy,
 }
 
 impl PlayerAction {
@@ -131,15 +198,19 @@ impl PlayerAction {
             Multiple => Modifier::Shift.into(),
             Area => Modifier::Control.into(),
             Line => Modifier::Alt.into(),
+            Rectangle => KeyCode::B.into(),
             SelectStructure => KeyCode::E.into(),
             Pipette => KeyCode::Q.into(),
             Zone => KeyCode::Space.into(),
             ClearZoning => KeyCode::Back.into(),
             KeepClear => KeyCode::Delete.into(),
+            Undo => UserInput::modified(Modifier::Control, KeyCode::Z),
+            Redo => UserInput::modified(Modifier::Control, KeyCode::Y),
             RotateClipboardLeft => UserInput::modified(Modifier::Shift, KeyCode::R),
             RotateClipboardRight => KeyCode::R.into(),
             SnapToSelection => KeyCode::Return.into(),
             DragCamera => MouseButton::Middle.into(),
+            GrabCamera => UserInput::modified(Modifier::Shift, MouseButton::Middle),
             Pan => VirtualDPad::wasd().into(),
             MoveCursor => VirtualDPad::arrow_keys().into(),
             // Plus and Equals are swapped. See: https://github.com/rust-windowing/winit/issues/2682
@@ -150,6 +221,25 @@ impl PlayerAction {
             TiltCameraDown => UserInput::modified(Modifier::Alt, KeyCode::Minus),
             RotateCameraLeft => KeyCode::Z.into(),
             RotateCameraRight => KeyCode::C.into(),
+            RecordCameraWaypoint => KeyCode::K.into(),
+            ClearCameraPath => UserInput::modified(Modifier::Shift, KeyCode::K),
+            ToggleCameraPlayback => KeyCode::J.into(),
+            FocusNext => KeyCode::Tab.into(),
+            FocusPrevious => UserInput::modified(Modifier::Shift, KeyCode::Tab),
+            ActivateFocus => KeyCode::F.into(),
+            TogglePause => KeyCode::P.into(),
+            Measure => KeyCode::M.into(),
+            GenerateDebugReport => KeyCode::F12.into(),
+            ToggleInputRecording => KeyCode::F10.into(),
+            CycleSignalOverlay => KeyCode::O.into(),
+            ToggleFertilityOverlay => KeyCode::L.into(),
+            RenameSelection => KeyCode::N.into(),
+            ToggleChronicle => KeyCode::H.into(),
+            ChronicleEarlier => KeyCode::LBracket.into(),
+            ChronicleLater => KeyCode::RBracket.into(),
+            NextHotbarSlot => KeyCode::Period.into(),
+            PreviousHotbarSlot => KeyCode::Comma.into(),
+            ToggleGenealogy => KeyCode::G.into(),
         }
     }
 
@@ -169,15 +259,19 @@ impl PlayerAction {
             DecreaseSelectionRadius => UserInput::chord([radius_modifier, DPadDown]),
             Area => LeftTrigger.into(),
             Line => LeftTrigger2.into(),
+            Rectangle => UserInput::chord([LeftTrigger, LeftTrigger2]),
             SelectStructure => RightThumb.into(),
             Pipette => West.into(),
             Zone => North.into(),
             ClearZoning => DPadUp.into(),
             KeepClear => DPadDown.into(),
+            Undo => UserInput::chord([camera_modifier, GamepadButtonType::Select]),
+            Redo => UserInput::chord([camera_modifier, GamepadButtonType::Start]),
             RotateClipboardLeft => DPadLeft.into(),
             RotateClipboardRight => DPadRight.into(),
             SnapToSelection => GamepadButtonType::LeftThumb.into(),
             DragCamera => GamepadButtonType::RightThumb.into(),
+            GrabCamera => UserInput::chord([radius_modifier, RightThumb]),
             Pan => DualAxis::left_stick().into(),
             MoveCursor => DualAxis::right_stick().into(),
             ZoomIn => UserInput::chord([camera_modifier, DPadUp]),
@@ -186,6 +280,25 @@ impl PlayerAction {
             TiltCameraDown => UserInput::chord([RightTrigger, DPadDown]),
             RotateCameraLeft => UserInput::chord([camera_modifier, DPadLeft]),
             RotateCameraRight => UserInput::chord([camera_modifier, DPadRight]),
+            RecordCameraWaypoint => UserInput::chord([radius_modifier, DPadLeft]),
+            ClearCameraPath => UserInput::chord([radius_modifier, South]),
+            ToggleCameraPlayback => UserInput::chord([radius_modifier, DPadRight]),
+            FocusNext => GamepadButtonType::Select.into(),
+            FocusPrevious => UserInput::chord([radius_modifier, GamepadButtonType::Select]),
+            ActivateFocus => GamepadButtonType::Start.into(),
+            TogglePause => GamepadButtonType::Mode.into(),
+            Measure => UserInput::chord([radius_modifier, West]),
+            GenerateDebugReport => UserInput::chord([radius_modifier, GamepadButtonType::Start]),
+            ToggleInputRecording => UserInput::chord([radius_modifier, GamepadButtonType::Mode]),
+            CycleSignalOverlay => UserInput::chord([radius_modifier, North]),
+            ToggleFertilityOverlay => UserInput::chord([radius_modifier, East]),
+            RenameSelection => UserInput::chord([camera_modifier, North]),
+            ToggleChronicle => UserInput::chord([camera_modifier, West]),
+            ChronicleEarlier => UserInput::chord([camera_modifier, East]),
+            ChronicleLater => UserInput::chord([camera_modifier, South]),
+            NextHotbarSlot => UserInput::chord([camera_modifier, LeftThumb]),
+            PreviousHotbarSlot => UserInput::chord([radius_modifier, LeftThumb]),
+            ToggleGenealogy => UserInput::chord([radius_modifier, camera_modifier]),
         }
     }
 
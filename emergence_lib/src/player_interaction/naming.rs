@@ -0,0 +1,110 @@
+//! Lets the player give their own names to the selected organism.
+//!
+//! While a name is being typed, other player actions are disabled so that keystrokes are not treated as commands.
+
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::ToggleActions, prelude::ActionState};
+
+use crate::organisms::individuals::{Individual, RenameIndividual};
+
+use super::{selection::CurrentSelection, InteractionSystem, PlayerAction};
+
+/// Handles renaming organisms.
+pub(super) struct NamingPlugin;
+
+impl Plugin for NamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NameEditor>()
+            // Typing runs first, so that the key that starts renaming isn't typed into the name
+            .add_system(type_name)
+            .add_system(
+                start_renaming
+                    .after(type_name)
+                    .after(InteractionSystem::SelectTiles),
+            );
+    }
+}
+
+/// The name that the player is currently typing, if any.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct NameEditor {
+    /// The organism being renamed
+    target: Option<Entity>,
+    /// The name typed so far
+    text: String,
+}
+
+impl NameEditor {
+    /// The name typed so far, if an organism is being renamed.
+    ///
+    /// The selection cannot change while renaming, so this is always the name of the selected organism.
+    pub(crate) fn editing(&self) -> Option<&str> {
+        self.target.map(|_| self.text.as_str())
+    }
+}
+
+/// Starts renaming the selected organism, beginning from its current name.
+fn start_renaming(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    individual_query: Query<&Individual>,
+    mut name_editor: ResMut<NameEditor>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    if !actions.just_pressed(PlayerAction::RenameSelection) {
+        return;
+    }
+
+    let entity = match *current_selection {
+        CurrentSelection::Structure(entity) | CurrentSelection::Unit(entity) => entity,
+        _ => return,
+    };
+
+    if let Ok(individual) = individual_query.get(entity) {
+        name_editor.target = Some(entity);
+        name_editor.text = individual.name.clone();
+        toggle_actions.enabled = false;
+    }
+}
+
+/// Edits the name from typed characters.
+///
+/// Enter applies the new name, while Escape keeps the old one.
+fn type_name(
+    mut character_events: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut name_editor: ResMut<NameEditor>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut rename_events: EventWriter<RenameIndividual>,
+) {
+    let target = match name_editor.target {
+        Some(target) => target,
+        None => {
+            // Drop any characters typed while not renaming
+            character_events.clear();
+            return;
+        }
+    };
+
+    for event in character_events.iter() {
+        if !event.char.is_control() {
+            name_editor.text.push(event.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        name_editor.text.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        rename_events.send(RenameIndividual {
+            entity: target,
+            name: name_editor.text.clone(),
+        });
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+        *name_editor = NameEditor::default();
+        toggle_actions.enabled = true;
+    }
+}
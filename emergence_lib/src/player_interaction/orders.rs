@@ -0,0 +1,434 @@
+//! The history of the orders given by the player, so they can be undone and redone.
+//!
+//! Each [`Order`] is a set of zoning changes, recorded while a zoning key is held down.
+//! Undoing an order applies its inverse, but only to tiles where nothing has happened since:
+//! ghosts that have received materials and structures that have already been built or demolished are left alone.
+//! Simulation outcomes are never undone.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use std::collections::VecDeque;
+
+use crate::{
+    simulation::geometry::{MapGeometry, TilePos},
+    structures::{
+        construction::{Ghost, MarkedForDemolition},
+        crafting::{CraftingState, InputInventory},
+    },
+    terrain::{Terrain, Zoning},
+};
+
+use super::{InteractionSystem, PlayerAction};
+
+/// The maximum number of orders that can be undone.
+const MAX_UNDOABLE_ORDERS: usize = 100;
+
+/// Records orders and undoes or redoes them on request.
+pub(super) struct OrderHistoryPlugin;
+
+impl Plugin for OrderHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrderHistory>().add_system(
+            undo_and_redo
+                .after(InteractionSystem::ApplyZoning)
+                .before(InteractionSystem::ManagePreviews),
+        );
+    }
+}
+
+/// A change to the zoning of a single tile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ZoningChange {
+    /// The tile whose zoning changed
+    pub(crate) tile_pos: TilePos,
+    /// The zoning of the tile before the change
+    pub(crate) before: Zoning,
+    /// The zoning of the tile after the change
+    pub(crate) after: Zoning,
+}
+
+impl ZoningChange {
+    /// The change that reverses this one.
+    fn inverse(&self) -> ZoningChange {
+        ZoningChange {
+            tile_pos: self.tile_pos,
+            before: self.after.clone(),
+            after: self.before.clone(),
+        }
+    }
+}
+
+/// A single order given by the player, made of one or more changes to zoning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Order {
+    /// The changes made by this order, in the order they were made
+    changes: Vec<ZoningChange>,
+}
+
+impl Order {
+    /// Adds `change` to this order.
+    ///
+    /// Tiles that are changed several times keep their original zoning as the `before` value,
+    /// and tiles that are changed back to how they started are forgotten.
+    fn record(&mut self, change: ZoningChange) {
+        match self
+            .changes
+            .iter()
+            .position(|existing| existing.tile_pos == change.tile_pos)
+        {
+            Some(index) => {
+                if self.changes[index].before == change.after {
+                    self.changes.remove(index);
+                } else {
+                    self.changes[index].after = change.after;
+                }
+            }
+            None => self.changes.push(change),
+        }
+    }
+
+    /// The order that reverses this one.
+    fn inverse(&self) -> Order {
+        Order {
+            changes: self
+                .changes
+                .iter()
+                .rev()
+                .map(ZoningChange::inverse)
+                .collect(),
+        }
+    }
+
+    /// Does this order change nothing?
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// The orders that the player has given, and those they have undone.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct OrderHistory {
+    /// The order currently being given, which is finished once the zoning keys are released
+    in_progress: Order,
+    /// Orders that can be undone, oldest first
+    undoable: VecDeque<Order>,
+    /// Orders that have been undone and can be redone, most recently undone last
+    redoable: Vec<Order>,
+}
+
+impl OrderHistory {
+    /// Records a change to the zoning of `tile_pos` as part of the order in progress.
+    pub(crate) fn record(&mut self, tile_pos: TilePos, before: Zoning, after: Zoning) {
+        if before != after {
+            self.in_progress.record(ZoningChange {
+                tile_pos,
+                before,
+                after,
+            });
+        }
+    }
+
+    /// Finishes the order in progress, so that it can be undone.
+    ///
+    /// Giving a new order means that orders which were undone can no longer be redone.
+    fn finish_order(&mut self) {
+        if self.in_progress.is_empty() {
+            return;
+        }
+
+        let order = std::mem::take(&mut self.in_progress);
+        self.push_undoable(order);
+        self.redoable.clear();
+    }
+
+    /// Stores an order that can be undone, forgetting the oldest order if there are too many.
+    fn push_undoable(&mut self, order: Order) {
+        self.undoable.push_back(order);
+        if self.undoable.len() > MAX_UNDOABLE_ORDERS {
+            self.undoable.pop_front();
+        }
+    }
+}
+
+/// Can the zoning of a tile be changed away from `current_zoning` without undoing anything that has happened since?
+fn can_be_revised(
+    tile_pos: TilePos,
+    current_zoning: &Zoning,
+    map_geometry: &MapGeometry,
+    ghost_query: &Query<(&CraftingState, &InputInventory), With<Ghost>>,
+) -> bool {
+    match current_zoning {
+        Zoning::Structure(_) => match map_geometry.ghost_index.get(&tile_pos) {
+            // Work has not started as long as no materials have been delivered
+            Some(&ghost_entity) => match ghost_query.get(ghost_entity) {
+                Ok((crafting_state, construction_materials)) => {
+                    *crafting_state == CraftingState::NeedsInput
+                        && construction_materials.is_empty()
+                }
+                Err(_) => true,
+            },
+            // The ghost has already been built
+            None => !map_geometry.structure_index.contains_key(&tile_pos),
+        },
+        // Structures that are only marked for demolition can still be saved
+        Zoning::KeepClear | Zoning::None => true,
+    }
+}
+
+/// Applies as much of `order` as can be applied, returning the changes that were actually made.
+fn apply_order(
+    order: &Order,
+    terrain_query: &mut Query<(&TilePos, &mut Zoning), With<Terrain>>,
+    ghost_query: &Query<(&CraftingState, &InputInventory), With<Ghost>>,
+    map_geometry: &MapGeometry,
+    commands: &mut Commands,
+) -> Order {
+    let mut applied = Order::default();
+
+    for change in &order.changes {
+        let terrain_entity = match map_geometry.terrain_index.get(&change.tile_pos) {
+            Some(&terrain_entity) => terrain_entity,
+            None => continue,
+        };
+        let (_, mut zoning) = match terrain_query.get_mut(terrain_entity) {
+            Ok(query_item) => query_item,
+            Err(_) => continue,
+        };
+
+        // The tile has been rezoned since, so this change no longer applies
+        if *zoning != change.before {
+            continue;
+        }
+
+        if !can_be_revised(change.tile_pos, &zoning, map_geometry, ghost_query) {
+            continue;
+        }
+
+        if change.before == Zoning::KeepClear {
+            if let Some(&structure_entity) = map_geometry.structure_index.get(&change.tile_pos) {
+                commands
+                    .entity(structure_entity)
+                    .remove::<MarkedForDemolition>();
+            }
+        }
+
+        *zoning = change.after.clone();
+        applied.changes.push(change.clone());
+    }
+
+    applied
+}
+
+/// Finishes orders once the zoning keys are released, and undoes or redoes orders when asked.
+fn undo_and_redo(
+    actions: Res<ActionState<PlayerAction>>,
+    mut order_history: ResMut<OrderHistory>,
+    mut terrain_query: Query<(&TilePos, &mut Zoning), With<Terrain>>,
+    ghost_query: Query<(&CraftingState, &InputInventory), With<Ghost>>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    let giving_order = [
+        PlayerAction::Zone,
+        PlayerAction::ClearZoning,
+        PlayerAction::KeepClear,
+    ]
+    .into_iter()
+    .any(|action| actions.pressed(action));
+
+    if !giving_order {
+        order_history.finish_order();
+    }
+
+    if actions.just_pressed(PlayerAction::Undo) {
+        if let Some(order) = order_history.undoable.pop_back() {
+            let undone = apply_order(
+                &order.inverse(),
+                &mut terrain_query,
+                &ghost_query,
+                &map_geometry,
+                &mut commands,
+            );
+
+            if !undone.is_empty() {
+                order_history.redoable.push(undone.inverse());
+            }
+        }
+    } else if actions.just_pressed(PlayerAction::Redo) {
+        if let Some(order) = order_history.redoable.pop() {
+            let redone = apply_order(
+                &order,
+                &mut terrain_query,
+                &ghost_query,
+                &map_geometry,
+                &mut commands,
+            );
+
+            if !redone.is_empty() {
+                order_history.push_undoable(redone);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A change to the zoning of the tile at `x` along the x-axis.
+    fn change(x: i32, before: Zoning, after: Zoning) -> ZoningChange {
+        ZoningChange {
+            tile_pos: TilePos::new(x, 0),
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn repeated_changes_keep_the_original_zoning() {
+        let mut order = Order::default();
+        order.record(change(0, Zoning::None, Zoning::KeepClear));
+        order.record(change(1, Zoning::None, Zoning::KeepClear));
+        // Changing a tile back to how it started forgets it
+        order.record(change(1, Zoning::KeepClear, Zoning::None));
+
+        assert_eq!(
+            order.changes,
+            vec![change(0, Zoning::None, Zoning::KeepClear)]
+        );
+    }
+
+    #[test]
+    fn inverses_reverse_each_change() {
+        let mut order = Order::default();
+        order.record(change(0, Zoning::None, Zoning::KeepClear));
+        order.record(change(1, Zoning::KeepClear, Zoning::None));
+
+        assert_eq!(
+            order.inverse().changes,
+            vec![
+                change(1, Zoning::None, Zoning::KeepClear),
+                change(0, Zoning::KeepClear, Zoning::None),
+            ]
+        );
+        assert_eq!(order.inverse().inverse(), order);
+    }
+
+    /// The number of unzoned tiles in the apps made by [`order_app`].
+    const N_TILES: i32 = MAX_UNDOABLE_ORDERS as i32 + 1;
+
+    /// An app that undoes and redoes orders on [`N_TILES`] unzoned tiles, one for each `x` along the x-axis.
+    fn order_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<OrderHistory>()
+            .init_resource::<ActionState<PlayerAction>>()
+            .add_system(undo_and_redo);
+
+        let mut map_geometry = MapGeometry::new(1);
+        for x in 0..N_TILES {
+            let tile_pos = TilePos::new(x, 0);
+            let terrain_entity = app
+                .world
+                .spawn((tile_pos, Zoning::None, Terrain::Plain))
+                .id();
+            map_geometry.terrain_index.insert(tile_pos, terrain_entity);
+        }
+        app.insert_resource(map_geometry);
+
+        app
+    }
+
+    /// Rezones the tile at `x` as a single order, the way the zoning tools do.
+    fn rezone(app: &mut App, x: i32, new_zoning: Zoning) {
+        let tile_pos = TilePos::new(x, 0);
+        let terrain_entity = app.world.resource::<MapGeometry>().terrain_index[&tile_pos];
+        let mut zoning = app.world.get_mut::<Zoning>(terrain_entity).unwrap();
+        let before = std::mem::replace(&mut *zoning, new_zoning.clone());
+        app.world
+            .resource_mut::<OrderHistory>()
+            .record(tile_pos, before, new_zoning);
+
+        // The order is finished once no zoning keys are held
+        app.update();
+    }
+
+    /// Presses `action` for a single frame.
+    fn press(app: &mut App, action: PlayerAction) {
+        app.world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .press(action.clone());
+        app.update();
+        app.world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .release(action);
+    }
+
+    /// The zoning of the tile at `x`.
+    fn zoning_at(app: &App, x: i32) -> Zoning {
+        let terrain_entity = app.world.resource::<MapGeometry>().terrain_index[&TilePos::new(x, 0)];
+        app.world.get::<Zoning>(terrain_entity).unwrap().clone()
+    }
+
+    #[test]
+    fn orders_can_be_undone_and_redone() {
+        let mut app = order_app();
+        rezone(&mut app, 0, Zoning::KeepClear);
+
+        press(&mut app, PlayerAction::Undo);
+        assert_eq!(zoning_at(&app, 0), Zoning::None);
+
+        press(&mut app, PlayerAction::Redo);
+        assert_eq!(zoning_at(&app, 0), Zoning::KeepClear);
+    }
+
+    #[test]
+    fn new_orders_cannot_be_redone_past() {
+        let mut app = order_app();
+        rezone(&mut app, 0, Zoning::KeepClear);
+        press(&mut app, PlayerAction::Undo);
+
+        rezone(&mut app, 1, Zoning::KeepClear);
+        press(&mut app, PlayerAction::Redo);
+        assert_eq!(zoning_at(&app, 0), Zoning::None);
+
+        // Only the new order is left to undo
+        press(&mut app, PlayerAction::Undo);
+        press(&mut app, PlayerAction::Undo);
+        assert_eq!(zoning_at(&app, 1), Zoning::None);
+        assert_eq!(zoning_at(&app, 0), Zoning::None);
+    }
+
+    #[test]
+    fn orders_are_not_undone_over_later_changes() {
+        let mut app = order_app();
+        rezone(&mut app, 0, Zoning::KeepClear);
+
+        // Changing the zoning without recording it, so the tile no longer matches the order
+        let terrain_entity = app.world.resource::<MapGeometry>().terrain_index[&TilePos::ORIGIN];
+        *app.world.get_mut::<Zoning>(terrain_entity).unwrap() = Zoning::None;
+
+        press(&mut app, PlayerAction::Undo);
+        // Nothing was undone, so there is nothing to redo
+        press(&mut app, PlayerAction::Redo);
+        assert_eq!(zoning_at(&app, 0), Zoning::None);
+    }
+
+    #[test]
+    fn only_recent_orders_are_remembered() {
+        let mut app = order_app();
+
+        for x in 0..N_TILES {
+            rezone(&mut app, x, Zoning::KeepClear);
+        }
+
+        for _ in 0..N_TILES {
+            press(&mut app, PlayerAction::Undo);
+        }
+
+        // The oldest order was forgotten
+        assert_eq!(zoning_at(&app, 0), Zoning::KeepClear);
+        for x in 1..N_TILES {
+            assert_eq!(zoning_at(&app, x), Zoning::None);
+        }
+    }
+}
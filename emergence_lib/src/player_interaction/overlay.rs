@@ -0,0 +1,96 @@
+//! Lets the player pick a signal to visualize as a heatmap over the terrain, or show the fertility of the soil.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::signals::{SignalType, Signals};
+
+use super::PlayerAction;
+
+/// Lets the player choose which signal is overlaid on the map.
+pub(super) struct SignalOverlayPlugin;
+
+impl Plugin for SignalOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SignalOverlay>()
+            .init_resource::<FertilityOverlay>()
+            .add_system(cycle_signal_overlay)
+            .add_system(toggle_fertility_overlay);
+    }
+}
+
+/// The type of signal that is currently displayed as a heatmap over the terrain, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SignalOverlay {
+    /// The signal being displayed
+    signal_type: Option<SignalType>,
+}
+
+impl SignalOverlay {
+    /// The signal being displayed, if any.
+    pub(crate) fn signal_type(&self) -> Option<SignalType> {
+        self.signal_type
+    }
+}
+
+/// Is the fertility of the soil displayed over the terrain?
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FertilityOverlay {
+    /// Should the overlay be shown?
+    pub(crate) enabled: bool,
+}
+
+/// Returns the signal type that follows `current` in `available`, wrapping around to no overlay at the end.
+fn next_signal_type(current: Option<SignalType>, available: &[SignalType]) -> Option<SignalType> {
+    match current {
+        None => available.first().copied(),
+        // If the current signal has vanished, the overlay is turned off
+        Some(current) => available
+            .iter()
+            .position(|&signal_type| signal_type == current)
+            .and_then(|index| available.get(index + 1))
+            .copied(),
+    }
+}
+
+/// Cycles through each signal type that exists, then turns the overlay off.
+fn cycle_signal_overlay(
+    actions: Res<ActionState<PlayerAction>>,
+    signals: Res<Signals>,
+    mut signal_overlay: ResMut<SignalOverlay>,
+) {
+    if actions.just_pressed(PlayerAction::CycleSignalOverlay) {
+        signal_overlay.signal_type =
+            next_signal_type(signal_overlay.signal_type, &signals.signal_types());
+    }
+}
+
+/// Shows or hides the [`FertilityOverlay`].
+fn toggle_fertility_overlay(
+    actions: Res<ActionState<PlayerAction>>,
+    mut fertility_overlay: ResMut<FertilityOverlay>,
+) {
+    if actions.just_pressed(PlayerAction::ToggleFertilityOverlay) {
+        fertility_overlay.enabled = !fertility_overlay.enabled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_management::manifest::Id;
+
+    #[test]
+    fn overlay_cycles_through_signals_then_turns_off() {
+        let available = [SignalType::Push(Id::new(1)), SignalType::Alarm];
+
+        let first = next_signal_type(None, &available);
+        assert_eq!(first, Some(SignalType::Push(Id::new(1))));
+
+        let second = next_signal_type(first, &available);
+        assert_eq!(second, Some(SignalType::Alarm));
+
+        assert_eq!(next_signal_type(second, &available), None);
+        assert_eq!(next_signal_type(None, &[]), None);
+    }
+}
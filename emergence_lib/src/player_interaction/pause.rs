@@ -0,0 +1,55 @@
+//! Pausing the simulation, and the real-time clock that keeps the interface running while it is paused.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use std::time::Duration;
+
+use super::PlayerAction;
+
+/// Real time, unaffected by pausing or changes to the speed of the simulation.
+///
+/// All UI and camera timing should use this clock, rather than [`Time`].
+#[derive(Resource, Debug, Default)]
+pub(crate) struct UiClock {
+    /// The real time elapsed since the last frame
+    delta: Duration,
+}
+
+impl UiClock {
+    /// The real time elapsed since the last frame.
+    pub(crate) fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// The real time elapsed since the last frame, in seconds.
+    pub(crate) fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
+
+/// Advances the [`UiClock`] by the real time that has passed, even if [`Time`] is paused.
+fn update_ui_clock(time: Res<Time>, mut ui_clock: ResMut<UiClock>) {
+    ui_clock.delta = time.raw_delta();
+}
+
+/// Pauses and unpauses the simulation.
+fn toggle_pause(actions: Res<ActionState<PlayerAction>>, mut time: ResMut<Time>) {
+    if actions.just_pressed(PlayerAction::TogglePause) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
+
+/// Lets the player pause the simulation.
+pub(super) struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiClock>()
+            .add_system(update_ui_clock.in_base_set(CoreSet::First))
+            .add_system(toggle_pause);
+    }
+}
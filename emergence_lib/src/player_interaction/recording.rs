@@ -0,0 +1,479 @@
+//! Records the actions taken by the player, and plays them back deterministically.
+//!
+//! Recordings store which `PlayerAction`s are pressed and which tile is hovered on each frame.
+//! This is enough to replay workflows like "select a tile, then zone it for a structure" in end-to-end tests,
+//! without depending on the window, the camera or the screen position of the cursor.
+//! Axis data, such as panning the camera with a joystick, is not recorded.
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use core::fmt::Display;
+use leafwing_input_manager::{
+    plugin::InputManagerSystem,
+    prelude::{ActionState, InputMap},
+    Actionlike,
+};
+use std::{path::PathBuf, str::FromStr};
+
+use crate::simulation::{
+    geometry::{MapGeometry, TilePos},
+    time::SimulationTick,
+};
+
+use super::{cursor::CursorPos, InteractionSystem, PlayerAction};
+
+/// Records and plays back player inputs.
+pub(super) struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRecorder>()
+            .add_system(
+                record_inputs
+                    .after(InteractionSystem::ComputeCursorPos)
+                    .before(InteractionSystem::SelectTiles),
+            )
+            .add_system(
+                play_back_actions
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputManagerSystem::Update)
+                    .run_if(resource_exists::<InputPlayback>()),
+            )
+            .add_system(
+                play_back_cursor
+                    .after(InteractionSystem::ComputeCursorPos)
+                    .before(InteractionSystem::SelectTiles)
+                    .run_if(resource_exists::<InputPlayback>()),
+            );
+    }
+}
+
+/// The folder that input recordings are saved to, relative to the working directory.
+const INPUT_RECORDING_FOLDER: &str = "input_recordings";
+
+/// The state of the player's inputs on a single frame.
+#[derive(Debug, Clone, PartialEq)]
+struct RecordedFrame {
+    /// The number of frames since the recording started
+    frame: u64,
+    /// The tile that the cursor was over, if any
+    cursor: Option<TilePos>,
+    /// The actions that were held down
+    pressed: Vec<PlayerAction>,
+}
+
+impl Display for RecordedFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frame = self.frame;
+
+        let cursor = match self.cursor {
+            Some(tile_pos) => format!("{},{}", tile_pos.x, tile_pos.y),
+            None => "-".to_string(),
+        };
+
+        let pressed = if self.pressed.is_empty() {
+            "-".to_string()
+        } else {
+            self.pressed
+                .iter()
+                .map(|action| format!("{action:?}"))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        write!(f, "{frame} {cursor} {pressed}")
+    }
+}
+
+/// A sequence of player inputs, indexed by frame.
+///
+/// Only frames on which the inputs changed are stored.
+/// Recordings are saved as plain text, with one line per stored frame:
+/// the frame number, the hovered tile in axial coordinates (or `-`) and the pressed actions (or `-`).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputRecording {
+    /// The stored frames, in increasing order
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Sets the hovered tile and the pressed actions from `frame` onwards.
+    ///
+    /// # Panics
+    ///
+    /// Frames must be added in increasing order.
+    pub(crate) fn push(&mut self, frame: u64, cursor: Option<TilePos>, pressed: Vec<PlayerAction>) {
+        if let Some(last_frame) = self.frames.last() {
+            assert!(
+                frame > last_frame.frame,
+                "Frame {frame} was recorded after frame {}",
+                last_frame.frame
+            );
+        }
+
+        self.frames.push(RecordedFrame {
+            frame,
+            cursor,
+            pressed,
+        });
+    }
+
+    /// The number of frames that the recording lasts for.
+    pub fn n_frames(&self) -> u64 {
+        self.frames
+            .last()
+            .map_or(0, |last_frame| last_frame.frame + 1)
+    }
+
+    /// Stores the inputs on `frame`, unless they are unchanged from the previously stored frame.
+    fn record(&mut self, frame: u64, cursor: Option<TilePos>, pressed: Vec<PlayerAction>) {
+        let unchanged = self
+            .frames
+            .last()
+            .is_some_and(|last_frame| last_frame.cursor == cursor && last_frame.pressed == pressed);
+
+        if !unchanged {
+            self.push(frame, cursor, pressed);
+        }
+    }
+}
+
+impl Display for InputRecording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for frame in &self.frames {
+            writeln!(f, "{frame}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An error encountered when parsing an [`InputRecording`] from text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputRecordingParseError {
+    /// The line number, starting from 1
+    pub line_number: usize,
+    /// The contents of the line
+    pub line: String,
+}
+
+impl Display for InputRecordingParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_number = self.line_number;
+        let line = &self.line;
+
+        write!(
+            f,
+            "Could not parse line {line_number} of input recording: {line}"
+        )
+    }
+}
+
+impl std::error::Error for InputRecordingParseError {}
+
+impl FromStr for InputRecording {
+    type Err = InputRecordingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut frames: Vec<RecordedFrame> = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let error = || InputRecordingParseError {
+                line_number: index + 1,
+                line: line.to_string(),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let (frame, cursor, pressed) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(frame), Some(cursor), Some(pressed)) => (frame, cursor, pressed),
+                _ => return Err(error()),
+            };
+
+            let frame: u64 = frame.parse().map_err(|_| error())?;
+            if frames.last().is_some_and(|last| frame <= last.frame) {
+                return Err(error());
+            }
+
+            let cursor = match cursor {
+                "-" => None,
+                cursor => {
+                    let (x, y) = cursor.split_once(',').ok_or_else(error)?;
+                    let x = x.parse().map_err(|_| error())?;
+                    let y = y.parse().map_err(|_| error())?;
+                    Some(TilePos::new(x, y))
+                }
+            };
+
+            let mut pressed_actions = Vec::new();
+            if pressed != "-" {
+                for name in pressed.split(',') {
+                    let action = PlayerAction::variants()
+                        .find(|action| format!("{action:?}") == name)
+                        .ok_or_else(error)?;
+                    pressed_actions.push(action);
+                }
+            }
+
+            frames.push(RecordedFrame {
+                frame,
+                cursor,
+                pressed: pressed_actions,
+            });
+        }
+
+        Ok(InputRecording { frames })
+    }
+}
+
+/// Records the player's inputs while [`PlayerAction::ToggleInputRecording`] is active.
+#[derive(Resource, Debug, Default)]
+struct InputRecorder {
+    /// The recording in progress, if any
+    recording: Option<InputRecording>,
+    /// The number of frames since the recording started
+    frame: u64,
+}
+
+/// Replays an [`InputRecording`], starting on the frame that this resource is inserted.
+///
+/// While the recording is playing, the [`InputMap`] is removed so that real inputs cannot interfere.
+/// Once it is finished, the [`InputMap`] is restored and this resource removes itself.
+#[derive(Resource, Debug)]
+pub struct InputPlayback {
+    /// The recording to replay
+    recording: InputRecording,
+    /// The number of frames since playback started
+    frame: u64,
+    /// The index of the next stored frame to apply
+    next_index: usize,
+    /// The tile that the cursor is over, if any
+    cursor: Option<TilePos>,
+    /// The actions that should be held down
+    pressed: Vec<PlayerAction>,
+    /// The player's input map, stored while the recording plays
+    input_map: Option<InputMap<PlayerAction>>,
+}
+
+impl InputPlayback {
+    /// Creates a new [`InputPlayback`], which begins replaying `recording` as soon as it is inserted.
+    pub fn new(recording: InputRecording) -> Self {
+        InputPlayback {
+            recording,
+            frame: 0,
+            next_index: 0,
+            cursor: None,
+            pressed: Vec::new(),
+            input_map: None,
+        }
+    }
+}
+
+/// Toggles recording on and off, saving the recording to the [`INPUT_RECORDING_FOLDER`] when it ends.
+///
+/// While recording, the hovered tile and the pressed actions are stored every frame.
+fn record_inputs(
+    mut recorder: ResMut<InputRecorder>,
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    simulation_tick: Res<SimulationTick>,
+) {
+    if actions.just_pressed(PlayerAction::ToggleInputRecording) {
+        match recorder.recording.take() {
+            Some(mut recording) => {
+                // Make sure that nothing is left held down when the recording is played back
+                recording.record(recorder.frame, cursor_pos.maybe_tile_pos(), Vec::new());
+                save_recording(recording, simulation_tick.get());
+            }
+            None => {
+                info!("Started recording inputs");
+                recorder.recording = Some(InputRecording::default());
+                recorder.frame = 0;
+            }
+        }
+    }
+
+    let frame = recorder.frame;
+    if let Some(recording) = &mut recorder.recording {
+        let pressed = PlayerAction::variants()
+            // The recording should not turn itself off when it is played back
+            .filter(|action| *action != PlayerAction::ToggleInputRecording)
+            .filter(|action| actions.pressed(action.clone()))
+            .collect();
+
+        recording.record(frame, cursor_pos.maybe_tile_pos(), pressed);
+        recorder.frame += 1;
+    }
+}
+
+/// Writes the `recording` to disk on a background thread.
+fn save_recording(recording: InputRecording, tick: u64) {
+    let path =
+        PathBuf::from(INPUT_RECORDING_FOLDER).join(format!("input_recording_tick_{tick}.txt"));
+    let contents = recording.to_string();
+
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = std::fs::create_dir_all(INPUT_RECORDING_FOLDER)
+                .and_then(|_| std::fs::write(&path, contents));
+
+            match result {
+                Ok(()) => info!("Saved input recording to {}", path.display()),
+                Err(error) => error!(
+                    "Could not save input recording to {}: {error}",
+                    path.display()
+                ),
+            }
+        })
+        .detach();
+}
+
+/// Presses and releases actions to match the [`InputPlayback`].
+///
+/// This runs after the [`InputMap`] would normally have updated the [`ActionState`],
+/// so the recorded actions are seen by all gameplay systems.
+fn play_back_actions(
+    mut playback: ResMut<InputPlayback>,
+    mut actions: ResMut<ActionState<PlayerAction>>,
+    maybe_input_map: Option<Res<InputMap<PlayerAction>>>,
+    mut commands: Commands,
+) {
+    if playback.next_index >= playback.recording.frames.len() {
+        actions.release_all();
+        if let Some(input_map) = playback.input_map.take() {
+            commands.insert_resource(input_map);
+        }
+        commands.remove_resource::<InputPlayback>();
+        info!("Finished playing back inputs");
+        return;
+    }
+
+    if playback.input_map.is_none() {
+        if let Some(input_map) = maybe_input_map {
+            playback.input_map = Some(input_map.clone());
+            commands.remove_resource::<InputMap<PlayerAction>>();
+        }
+    }
+
+    while let Some(recorded_frame) = playback.recording.frames.get(playback.next_index) {
+        if recorded_frame.frame > playback.frame {
+            break;
+        }
+
+        let recorded_frame = recorded_frame.clone();
+        playback.cursor = recorded_frame.cursor;
+        playback.pressed = recorded_frame.pressed;
+        playback.next_index += 1;
+    }
+
+    for action in PlayerAction::variants() {
+        let should_be_pressed = playback.pressed.contains(&action);
+        if should_be_pressed && !actions.pressed(action.clone()) {
+            actions.press(action);
+        } else if !should_be_pressed && actions.pressed(action.clone()) {
+            actions.release(action);
+        }
+    }
+
+    playback.frame += 1;
+}
+
+/// Moves the cursor to the tile stored in the [`InputPlayback`].
+fn play_back_cursor(
+    playback: Res<InputPlayback>,
+    mut cursor_pos: ResMut<CursorPos>,
+    map_geometry: Res<MapGeometry>,
+) {
+    cursor_pos.set_tile_pos(playback.cursor, &map_geometry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id,
+        player_interaction::clipboard::Clipboard,
+        simulation::{generation::GenerationConfig, geometry::Facing},
+        structures::{construction::Ghost, crafting::ActiveRecipe, ClipboardData},
+        terrain::Zoning,
+        testing::interaction_app,
+    };
+
+    #[test]
+    fn recordings_round_trip_through_text() {
+        let mut recording = InputRecording::default();
+        recording.push(0, Some(TilePos::new(1, -2)), Vec::new());
+        recording.push(3, None, vec![PlayerAction::Select, PlayerAction::Area]);
+        recording.push(7, Some(TilePos::ORIGIN), vec![PlayerAction::Zone]);
+
+        let parsed: InputRecording = recording.to_string().parse().unwrap();
+        assert_eq!(parsed, recording);
+        assert_eq!(parsed.n_frames(), 8);
+    }
+
+    #[test]
+    fn unchanged_frames_are_not_stored() {
+        let mut recording = InputRecording::default();
+        recording.record(0, Some(TilePos::ORIGIN), vec![PlayerAction::Select]);
+        recording.record(1, Some(TilePos::ORIGIN), vec![PlayerAction::Select]);
+        recording.record(2, Some(TilePos::ORIGIN), Vec::new());
+
+        assert_eq!(recording.frames.len(), 2);
+        assert_eq!(recording.frames[1].frame, 2);
+    }
+
+    #[test]
+    fn malformed_recordings_are_rejected() {
+        assert!("0 - Jump".parse::<InputRecording>().is_err());
+        assert!("0 1 Select".parse::<InputRecording>().is_err());
+        assert!("3 - -\n1 - -".parse::<InputRecording>().is_err());
+    }
+
+    #[test]
+    #[ignore = "Cannot test interaction without a virtual window."]
+    fn zoning_a_selected_tile_starts_construction() {
+        let mut app = interaction_app(GenerationConfig::default());
+        app.update();
+
+        let tile_pos = TilePos::new(1, 1);
+
+        // Stand in for picking a structure from the build menu
+        app.world
+            .resource_mut::<Clipboard>()
+            .set(Some(ClipboardData {
+                structure_id: Id::from_string_id("leuco"),
+                facing: Facing::default(),
+                active_recipe: ActiveRecipe::default(),
+            }));
+
+        let mut recording = InputRecording::default();
+        recording.push(0, Some(tile_pos), Vec::new());
+        recording.push(2, Some(tile_pos), vec![PlayerAction::Select]);
+        recording.push(4, Some(tile_pos), Vec::new());
+        recording.push(6, Some(tile_pos), vec![PlayerAction::Zone]);
+        recording.push(8, Some(tile_pos), Vec::new());
+
+        let n_frames = recording.n_frames();
+        app.insert_resource(InputPlayback::new(recording));
+        // Give the ghost time to be spawned after the last input
+        for _ in 0..n_frames + 3 {
+            app.update();
+        }
+
+        assert!(app.world.get_resource::<InputPlayback>().is_none());
+        assert!(app.world.get_resource::<InputMap<PlayerAction>>().is_some());
+
+        let mut zoning_query = app.world.query::<(&TilePos, &Zoning)>();
+        assert!(zoning_query
+            .iter(&app.world)
+            .any(|(&zoned_pos, zoning)| zoned_pos == tile_pos
+                && matches!(zoning, Zoning::Structure(_))));
+
+        let mut ghost_query = app.world.query_filtered::<&TilePos, With<Ghost>>();
+        assert!(ghost_query
+            .iter(&app.world)
+            .any(|&ghost_pos| ghost_pos == tile_pos));
+    }
+}
@@ -0,0 +1,133 @@
+//! Measures the distance between two tiles, and how long it would take each species to walk between them.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::{Id, Unit, UnitManifest},
+    simulation::{
+        geometry::{MapGeometry, TilePos},
+        pathfinding::{find_path, Path},
+    },
+    terrain::Terrain,
+    units::actions::{min_walking_duration, walking_duration},
+};
+
+use super::{cursor::CursorPos, InteractionSystem, PlayerAction};
+
+/// Lets the player measure distances on the map.
+pub(super) struct RulerPlugin;
+
+impl Plugin for RulerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ruler>()
+            .add_system(measure.after(InteractionSystem::ComputeCursorPos));
+    }
+}
+
+/// The tiles that the player is measuring between.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct Ruler {
+    /// The first tile chosen
+    start: Option<TilePos>,
+    /// The second tile chosen
+    end: Option<TilePos>,
+    /// The results of the measurement, once both tiles have been chosen
+    measurement: Option<Measurement>,
+}
+
+impl Ruler {
+    /// The first tile chosen, if any.
+    pub(crate) fn start(&self) -> Option<TilePos> {
+        self.start
+    }
+
+    /// The second tile chosen, if any.
+    pub(crate) fn end(&self) -> Option<TilePos> {
+        self.end
+    }
+
+    /// The results of the measurement, once both tiles have been chosen.
+    pub(crate) fn measurement(&self) -> Option<&Measurement> {
+        self.measurement.as_ref()
+    }
+}
+
+/// The distances between the two ends of the [`Ruler`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Measurement {
+    /// The number of tiles between the ends, ignoring obstacles
+    pub(crate) hex_distance: u32,
+    /// The fastest walkable route between the ends, if one exists
+    ///
+    /// The cost of this path is the time in seconds that a standard unit takes to walk it.
+    pub(crate) path: Option<Path>,
+    /// How long each species of unit would take to walk the path, in seconds
+    pub(crate) travel_times: Vec<(Id<Unit>, f32)>,
+}
+
+/// Places the ends of the ruler on the hovered tile.
+///
+/// The first press places the start, the second places the end and takes the measurement,
+/// and a third press starts a new measurement.
+fn measure(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    map_geometry: Res<MapGeometry>,
+    terrain_query: Query<&Terrain>,
+    unit_manifest: Res<UnitManifest>,
+    mut ruler: ResMut<Ruler>,
+) {
+    if !actions.just_pressed(PlayerAction::Measure) {
+        return;
+    }
+
+    let hovered_tile = match cursor_pos.maybe_tile_pos() {
+        Some(tile_pos) => tile_pos,
+        None => return,
+    };
+
+    let start = match (ruler.start, ruler.end) {
+        (Some(start), None) => start,
+        _ => {
+            *ruler = Ruler {
+                start: Some(hovered_tile),
+                end: None,
+                measurement: None,
+            };
+            return;
+        }
+    };
+
+    // The ruler answers immediately, rather than waiting its turn in the pathfinding service
+    let path = find_path(
+        &map_geometry,
+        start,
+        hovered_tile,
+        min_walking_duration(&terrain_query, &map_geometry),
+        |tile_pos, target_tile_pos| {
+            walking_duration(tile_pos, target_tile_pos, &map_geometry, &terrain_query)
+        },
+    );
+
+    let travel_times = match &path {
+        Some(path) => unit_manifest
+            .variants()
+            .into_iter()
+            .map(|unit_id| {
+                (
+                    unit_id,
+                    path.cost / unit_manifest.get(unit_id).walking_speed(),
+                )
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    ruler.end = Some(hovered_tile);
+    ruler.measurement = Some(Measurement {
+        hex_distance: start.hex.distance_to(hovered_tile.hex) as u32,
+        path,
+        travel_times,
+    });
+}
@@ -17,8 +17,9 @@ use crate::asset_management::manifest::RecipeManifest;
 use crate::signals::Signals;
 use crate::simulation::geometry::MapGeometry;
 use crate::simulation::geometry::TilePos;
-
-use crate as emergence_lib;
+use crate::simulation::vision::FogOfWar;
+use crate::structures::traps::TrapStatistics;
+use crate::structures::walls::Enclosures;
 
 use super::{cursor::CursorPos, InteractionSystem, PlayerAction};
 
@@ -56,7 +57,7 @@ pub(crate) struct SelectedTiles {
 
 impl SelectedTiles {
     /// Selects a single tile
-    pub(super) fn add_tile(&mut self, tile_pos: TilePos) {
+    pub(crate) fn add_tile(&mut self, tile_pos: TilePos) {
         self.selected.insert(tile_pos);
     }
 
@@ -90,6 +91,33 @@ impl SelectedTiles {
         HashSet::from_iter(hex_coord.map(|hex| TilePos { hex }))
     }
 
+    /// Draws every tile whose center lies within the rectangle with the centers of `start` and `end` at opposite corners.
+    ///
+    /// The rectangle is aligned with the world axes.
+    fn draw_rectangle(
+        start: TilePos,
+        end: TilePos,
+        map_geometry: &MapGeometry,
+    ) -> HashSet<TilePos> {
+        let start_pos = map_geometry.layout.hex_to_world_pos(start.hex);
+        let end_pos = map_geometry.layout.hex_to_world_pos(end.hex);
+        let min = start_pos.min(end_pos);
+        let max = start_pos.max(end_pos);
+
+        // Every corner of the rectangle is within this many tiles of the start
+        let radius = start.unsigned_distance_to(end.hex);
+
+        hexagon(start.hex, radius)
+            .filter(|&hex| {
+                let pos = map_geometry.layout.hex_to_world_pos(hex);
+                // Allow for rounding errors, so tiles exactly on the edge are included
+                pos.cmpge(min - f32::EPSILON * 100.).all()
+                    && pos.cmple(max + f32::EPSILON * 100.).all()
+            })
+            .map(|hex| TilePos { hex })
+            .collect()
+    }
+
     /// Computes the set of hexagons between `start` and `end`, with a thickness determnind by `radius`.
     fn draw_line(start: TilePos, end: TilePos, radius: u32) -> HashSet<TilePos> {
         let line = start.line_to(end.hex);
@@ -168,6 +196,9 @@ impl SelectedTiles {
             SelectionShape::Line { start } => {
                 SelectedTiles::draw_line(start, hovered_tile, selection_state.brush_size)
             }
+            SelectionShape::Rectangle { start } => {
+                SelectedTiles::draw_rectangle(start, hovered_tile, map_geometry)
+            }
         }
         // PERF: we could be faster about this by only collecting once
         .into_iter()
@@ -186,7 +217,12 @@ pub(crate) struct HoveredTiles {
 
 impl HoveredTiles {
     /// Updates the set of hovered actions based on the current cursor position and player inputs.
-    fn update(&mut self, hovered_tile: TilePos, selection_state: &SelectionState) {
+    fn update(
+        &mut self,
+        hovered_tile: TilePos,
+        selection_state: &SelectionState,
+        map_geometry: &MapGeometry,
+    ) {
         self.hovered = match selection_state.shape {
             SelectionShape::Single => {
                 SelectedTiles::draw_hexagon(hovered_tile, selection_state.brush_size)
@@ -200,6 +236,9 @@ impl HoveredTiles {
             SelectionShape::Line { start } => {
                 SelectedTiles::draw_line(start, hovered_tile, selection_state.brush_size)
             }
+            SelectionShape::Rectangle { start } => {
+                SelectedTiles::draw_rectangle(start, hovered_tile, map_geometry)
+            }
         };
     }
 }
@@ -466,6 +505,11 @@ enum SelectionShape {
         /// The start of the line
         start: TilePos,
     },
+    /// A rectangle dragged out from one corner to the other
+    Rectangle {
+        /// The corner where the drag began
+        start: TilePos,
+    },
 }
 
 impl SelectionState {
@@ -475,7 +519,15 @@ impl SelectionState {
 
         self.multiple = actions.pressed(PlayerAction::Multiple);
 
-        self.shape = if actions.pressed(Line) {
+        self.shape = if actions.pressed(Rectangle) {
+            let start = if let SelectionShape::Rectangle { start } = self.shape {
+                start
+            } else {
+                hovered_tile
+            };
+
+            SelectionShape::Rectangle { start }
+        } else if actions.pressed(Line) {
             let start = if let SelectionShape::Line { start } = self.shape {
                 start
             } else {
@@ -507,7 +559,9 @@ impl SelectionState {
                     SelectionAction::Preview
                 }
             }
-            SelectionShape::Area { .. } | SelectionShape::Line { .. } => {
+            SelectionShape::Area { .. }
+            | SelectionShape::Line { .. }
+            | SelectionShape::Rectangle { .. } => {
                 // Trigger on just released in order to enable a drag-and-preview effect
                 if actions.just_released(Select) {
                     SelectionAction::Select
@@ -542,7 +596,7 @@ fn set_selection(
     selection_state.compute(actions, hovered_tile);
 
     // Update hovered tiles
-    hovered_tiles.update(hovered_tile, &selection_state);
+    hovered_tiles.update(hovered_tile, &selection_state, map_geometry);
 
     // Select and deselect tiles
     match (selection_state.action, selection_state.shape) {
@@ -556,7 +610,10 @@ fn set_selection(
                 start: hovered_tile,
             };
         }
-        (SelectionAction::Select, SelectionShape::Area { .. }) => {
+        (
+            SelectionAction::Select,
+            SelectionShape::Area { .. } | SelectionShape::Rectangle { .. },
+        ) => {
             *current_selection =
                 current_selection.select_terrain(hovered_tile, &selection_state, map_geometry);
         }
@@ -586,20 +643,21 @@ fn set_selection(
                 )
             }
         }
-        (SelectionAction::Deselect, SelectionShape::Area { .. } | SelectionShape::Single) => {
-            match &mut *current_selection {
-                CurrentSelection::Terrain(ref mut selected_tiles) => {
-                    if let Some(hovered_tile) = cursor_pos.maybe_tile_pos() {
-                        selected_tiles.remove_from_selection(
-                            hovered_tile,
-                            &selection_state,
-                            map_geometry,
-                        );
-                    }
+        (
+            SelectionAction::Deselect,
+            SelectionShape::Area { .. } | SelectionShape::Rectangle { .. } | SelectionShape::Single,
+        ) => match &mut *current_selection {
+            CurrentSelection::Terrain(ref mut selected_tiles) => {
+                if let Some(hovered_tile) = cursor_pos.maybe_tile_pos() {
+                    selected_tiles.remove_from_selection(
+                        hovered_tile,
+                        &selection_state,
+                        map_geometry,
+                    );
                 }
-                _ => *current_selection = CurrentSelection::None,
             }
-        }
+            _ => *current_selection = CurrentSelection::None,
+        },
         (SelectionAction::Deselect, SelectionShape::Line { .. }) => {
             match &mut *current_selection {
                 CurrentSelection::Terrain(ref mut selected_tiles) => {
@@ -628,7 +686,7 @@ pub(crate) enum SelectionDetails {
     /// A ghost is selected
     Ghost(GhostDetails),
     /// A structure is selected
-    Structure(StructureDetails),
+    Structure(Box<StructureDetails>),
     /// A tile is selected.
     Terrain(TerrainDetails),
     /// A unit is selected
@@ -651,6 +709,9 @@ fn get_details(
     map_geometry: Res<MapGeometry>,
     recipe_manifest: Res<RecipeManifest>,
     signals: Res<Signals>,
+    enclosures: Res<Enclosures>,
+    fog_of_war: Res<FogOfWar>,
+    trap_statistics: Res<TrapStatistics>,
 ) -> Result<(), QueryEntityError> {
     *selection_details = match &*selection_type {
         CurrentSelection::Ghost(ghost_entity) => {
@@ -690,14 +751,20 @@ fn get_details(
                 .ok()
                 .map(|item| item.into());
 
-            SelectionDetails::Structure(StructureDetails {
+            SelectionDetails::Structure(Box::new(StructureDetails {
                 entity: structure_query_item.entity,
                 tile_pos: *structure_query_item.tile_pos,
                 structure_id: *structure_query_item.structure_id,
                 crafting_details,
                 maybe_organism_details,
                 marked_for_removal: structure_query_item.marked_for_removal.is_some(),
-            })
+                trap_details: structure_query_item
+                    .trap
+                    .map(|trap| (trap.clone(), trap_statistics.total_caught(trap.prey))),
+                on_fire: structure_query_item.on_fire.is_some(),
+                automation: structure_query_item.automation.cloned(),
+                paused: structure_query_item.paused.is_some(),
+            }))
         }
         CurrentSelection::Terrain(selected_tiles) => {
             // FIXME: display info about multiple tiles correctly
@@ -711,6 +778,15 @@ fn get_details(
                     tile_pos: *tile_pos,
                     signals: signals.all_signals_at_position(*tile_pos),
                     zoning: terrain_query_item.zoning.clone(),
+                    soil_moisture: *terrain_query_item.soil_moisture,
+                    fertility: *terrain_query_item.fertility,
+                    light_level: map_geometry.light_level(*tile_pos),
+                    temperature: *terrain_query_item.temperature,
+                    enclosed: enclosures.is_enclosed(*tile_pos),
+                    sheltered: enclosures.is_sheltered(*tile_pos),
+                    visible: fog_of_war.is_visible(*tile_pos),
+                    intel: fog_of_war.has_intel(*tile_pos),
+                    frozen: terrain_query_item.frozen.is_some(),
                 })
             } else {
                 SelectionDetails::None
@@ -729,6 +805,7 @@ fn get_details(
                 goal: unit_query_item.goal.clone(),
                 action: unit_query_item.action.clone(),
                 impatience_pool: unit_query_item.impatience_pool.clone(),
+                age: *unit_query_item.age,
                 organism_details,
             })
         }
@@ -826,7 +903,11 @@ Construction materials: {input_inventory}
 mod organism_details {
     use bevy::ecs::query::WorldQuery;
 
-    use crate::organisms::energy::EnergyPool;
+    use crate::organisms::{
+        energy::EnergyPool,
+        growth::{Stunted, UnmetGrowthRequirement},
+        individuals::Individual,
+    };
     use core::fmt::Display;
 
     /// Data needed to populate [`OrganismDetails`].
@@ -834,6 +915,10 @@ mod organism_details {
     pub(super) struct OrganismDetailsQuery {
         /// The current and max energy
         pub(super) energy_pool: &'static EnergyPool,
+        /// Why this organism cannot grow, if it cannot
+        pub(super) maybe_stunted: Option<&'static Stunted>,
+        /// The name and life story of this organism
+        pub(super) maybe_individual: Option<&'static Individual>,
     }
 
     /// Detailed info about a given organism.
@@ -841,12 +926,18 @@ mod organism_details {
     pub(crate) struct OrganismDetails {
         /// The current and max energy
         pub(super) energy_pool: EnergyPool,
+        /// Why this organism cannot grow, if it cannot
+        pub(super) unmet_growth_requirement: Option<UnmetGrowthRequirement>,
+        /// The name and life story of this organism
+        pub(super) individual: Option<Individual>,
     }
 
     impl From<OrganismDetailsQueryItem<'_>> for OrganismDetails {
         fn from(item: OrganismDetailsQueryItem) -> Self {
             OrganismDetails {
                 energy_pool: item.energy_pool.clone(),
+                unmet_growth_requirement: item.maybe_stunted.map(|stunted| stunted.0),
+                individual: item.maybe_individual.cloned(),
             }
         }
     }
@@ -854,7 +945,15 @@ mod organism_details {
     impl Display for OrganismDetails {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             let energy_pool = &self.energy_pool;
-            let string = format!("Energy: {energy_pool}");
+            let mut string = match &self.individual {
+                Some(individual) => format!("Name: {individual}\n"),
+                None => String::new(),
+            };
+            string += &format!("Energy: {energy_pool}");
+
+            if let Some(unmet_growth_requirement) = &self.unmet_growth_requirement {
+                string += &format!("\nCannot grow: {unmet_growth_requirement}");
+            }
 
             write!(f, "{string}")
         }
@@ -871,10 +970,12 @@ mod structure_details {
     use crate::{
         asset_management::manifest::{Id, Structure},
         items::{inventory::Inventory, recipe::RecipeData},
-        simulation::geometry::TilePos,
+        simulation::{fire::OnFire, geometry::TilePos},
         structures::{
+            automation::{AutomationRules, RecipePaused},
             construction::MarkedForDemolition,
             crafting::{ActiveRecipe, CraftingState, InputInventory, OutputInventory},
+            traps::Trap,
         },
     };
 
@@ -896,6 +997,14 @@ mod structure_details {
         )>,
         /// Is this structure marked for removal?
         pub(super) marked_for_removal: Option<&'static MarkedForDemolition>,
+        /// Is this structure a trap?
+        pub(super) trap: Option<&'static Trap>,
+        /// Is this structure burning?
+        pub(super) on_fire: Option<&'static OnFire>,
+        /// The automation rules configured for this structure
+        pub(super) automation: Option<&'static AutomationRules>,
+        /// Has an automation rule paused this structure's recipe?
+        pub(super) paused: Option<&'static RecipePaused>,
     }
 
     /// Detailed info about a given structure.
@@ -913,6 +1022,14 @@ mod structure_details {
         pub(crate) maybe_organism_details: Option<OrganismDetails>,
         /// Is this structure slated for removal?
         pub(crate) marked_for_removal: bool,
+        /// The state of this trap, and the total number of its prey caught by all traps, if it is one.
+        pub(crate) trap_details: Option<(Trap, u32)>,
+        /// Is this structure burning?
+        pub(crate) on_fire: bool,
+        /// The automation rules configured for this structure, if any
+        pub(crate) automation: Option<AutomationRules>,
+        /// Has an automation rule paused this structure's recipe?
+        pub(crate) paused: bool,
     }
 
     impl Display for StructureDetails {
@@ -931,14 +1048,31 @@ Tile: {tile_pos}"
                 string += "\nMarked for removal!";
             }
 
+            if self.on_fire {
+                string += "\nOn fire!";
+            }
+
             if let Some(crafting) = &self.crafting_details {
                 string += &format!("\n{crafting}");
             }
 
+            if self.paused {
+                string += "\nPaused by automation";
+            }
+
+            if let Some(automation) = &self.automation {
+                string += &format!("\nAutomation:\n{}", automation.to_string().trim_end());
+            }
+
             if let Some(organism) = &self.maybe_organism_details {
                 string += &format!("\n{organism}");
             };
 
+            if let Some((trap, total_caught)) = &self.trap_details {
+                let prey = trap.prey;
+                string += &format!("\n{trap}\nTotal {prey} caught: {total_caught}");
+            }
+
             write!(f, "{string}")
         }
     }
@@ -987,8 +1121,9 @@ mod terrain_details {
     use std::fmt::Display;
 
     use crate::{
-        player_interaction::zoning::Zoning, signals::LocalSignals, simulation::geometry::TilePos,
-        terrain::Terrain,
+        signals::LocalSignals,
+        simulation::{freezing::Frozen, geometry::TilePos, temperature::Temperature},
+        terrain::{Fertility, SoilMoisture, Terrain, Zoning},
     };
 
     /// Data needed to populate [`TerrainDetails`].
@@ -1000,6 +1135,14 @@ mod terrain_details {
         pub(super) terrain_type: &'static Terrain,
         /// The zoning applied to this terrain
         pub(super) zoning: &'static Zoning,
+        /// How wet the soil is
+        pub(super) soil_moisture: &'static SoilMoisture,
+        /// How rich the soil is
+        pub(super) fertility: &'static Fertility,
+        /// How hot or cold this tile is
+        pub(super) temperature: &'static Temperature,
+        /// Is this tile frozen over?
+        pub(super) frozen: Option<&'static Frozen>,
     }
 
     /// Detailed info about a given piece of terrain.
@@ -1015,6 +1158,24 @@ mod terrain_details {
         pub(super) signals: LocalSignals,
         /// The zoning of this tile
         pub(super) zoning: Zoning,
+        /// How wet the soil is
+        pub(super) soil_moisture: SoilMoisture,
+        /// How rich the soil is
+        pub(super) fertility: Fertility,
+        /// The fraction of light that reaches this tile
+        pub(super) light_level: f32,
+        /// How hot or cold this tile is
+        pub(super) temperature: Temperature,
+        /// Is this tile walled off from the edge of the map?
+        pub(super) enclosed: bool,
+        /// Is this tile roofed over, sheltering it from the weather?
+        pub(super) sheltered: bool,
+        /// Is this tile currently in view?
+        pub(super) visible: bool,
+        /// Is this tile in view of a watchtower, revealing its signals in detail?
+        pub(super) intel: bool,
+        /// Has the water on this tile frozen into ice?
+        pub(super) frozen: bool,
     }
 
     impl Display for TerrainDetails {
@@ -1022,8 +1183,23 @@ mod terrain_details {
             let entity = self.entity;
             let terrain_type = &self.terrain_type;
             let tile_pos = &self.tile_pos;
-            let signals = &self.signals;
+            // Signals can only be read on tiles that can be seen, and are only precise near watchtowers
+            let signals = match (self.visible, self.intel) {
+                (_, true) => format!("{:.4}", self.signals),
+                (true, false) => format!("{:.1}", self.signals),
+                (false, false) => "Not in view".to_string(),
+            };
             let zoning = &self.zoning;
+            let soil_moisture = self.soil_moisture.0;
+            let fertility = self.fertility.0;
+            let light_level = self.light_level;
+            let temperature = self.temperature.0;
+            let enclosed = match (self.enclosed, self.sheltered) {
+                (_, true) => "\nSheltered",
+                (true, false) => "\nEnclosed",
+                (false, false) => "",
+            };
+            let frozen = if self.frozen { "\nFrozen" } else { "" };
 
             write!(
                 f,
@@ -1031,6 +1207,10 @@ mod terrain_details {
 Terrain type: {terrain_type}
 Tile: {tile_pos}
 Zoning: {zoning}
+Soil moisture: {soil_moisture:.2}
+Fertility: {fertility:.2}
+Light: {light_level:.2}
+Temperature: {temperature:.1} C{enclosed}{frozen}
 Signals:
 {signals}"
             )
@@ -1048,7 +1228,7 @@ mod unit_details {
         simulation::geometry::TilePos,
         units::{
             actions::CurrentAction, goals::Goal, impatience::ImpatiencePool,
-            item_interaction::UnitInventory,
+            item_interaction::UnitInventory, lifecycle::Age,
         },
     };
 
@@ -1071,6 +1251,8 @@ mod unit_details {
         pub(super) action: &'static CurrentAction,
         /// How frustrated the unit is
         pub(super) impatience_pool: &'static ImpatiencePool,
+        /// How long the unit has been alive
+        pub(super) age: &'static Age,
     }
 
     /// Detailed info about a given unit.
@@ -1092,6 +1274,8 @@ mod unit_details {
         pub(crate) organism_details: OrganismDetails,
         /// How frustrated the unit is
         pub(super) impatience_pool: ImpatiencePool,
+        /// How long the unit has been alive
+        pub(super) age: Age,
     }
 
     impl Display for UnitDetails {
@@ -1103,6 +1287,7 @@ mod unit_details {
             let goal = &self.goal;
             let action = &self.action;
             let impatience_pool = &self.impatience_pool;
+            let age = self.age.in_days();
             let organism_details = &self.organism_details;
 
             write!(
@@ -1114,6 +1299,7 @@ Holding: {held_item}
 Goal: {goal}
 Action: {action}
 Impatience: {impatience_pool}
+Age: {age:.1} days
 {organism_details}"
             )
         }
@@ -1123,7 +1309,7 @@ Impatience: {impatience_pool}
 #[cfg(test)]
 mod tests {
     use super::SelectedTiles;
-    use crate::simulation::geometry::TilePos;
+    use crate::simulation::geometry::{MapGeometry, TilePos};
 
     #[test]
     fn simple_selection() {
@@ -1165,4 +1351,32 @@ mod tests {
         selected_tiles.clear_selection();
         assert_eq!(selected_tiles.selected.len(), 0);
     }
+
+    #[test]
+    fn rectangles_include_their_corners() {
+        let map_geometry = MapGeometry::new(10);
+        let start = TilePos::new(-2, 1);
+        let end = TilePos::new(3, -1);
+
+        let rectangle = SelectedTiles::draw_rectangle(start, end, &map_geometry);
+
+        assert!(rectangle.contains(&start));
+        assert!(rectangle.contains(&end));
+        // Rectangles can be dragged in either direction
+        assert_eq!(
+            rectangle,
+            SelectedTiles::draw_rectangle(end, start, &map_geometry)
+        );
+    }
+
+    #[test]
+    fn degenerate_rectangles_select_a_single_tile() {
+        let map_geometry = MapGeometry::new(10);
+        let tile_pos = TilePos::new(1, 1);
+
+        let rectangle = SelectedTiles::draw_rectangle(tile_pos, tile_pos, &map_geometry);
+
+        assert_eq!(rectangle.len(), 1);
+        assert!(rectangle.contains(&tile_pos));
+    }
 }
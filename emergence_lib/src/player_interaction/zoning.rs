@@ -1,7 +1,6 @@
 //! Zoning is used to indicate that a tile should contain the specified structure.
 
 use bevy::prelude::*;
-use core::fmt::Display;
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
@@ -9,12 +8,13 @@ use crate::{
     signals::{Emitter, SignalStrength, SignalType},
     simulation::geometry::{MapGeometry, TilePos},
     structures::{commands::StructureCommandsExt, construction::MarkedForDemolition},
-    terrain::Terrain,
+    terrain::{Fertility, SoilMoisture, Terrain, Zoning},
 };
 
 use super::{
-    clipboard::{Clipboard, ClipboardData},
+    clipboard::Clipboard,
     cursor::CursorPos,
+    orders::OrderHistory,
     selection::{CurrentSelection, SelectedTiles},
     InteractionSystem, PlayerAction,
 };
@@ -40,43 +40,27 @@ impl Plugin for ZoningPlugin {
     }
 }
 
-/// The zoning of a given tile, which specifies which structure *should* be built there.
-#[derive(Component, PartialEq, Eq, Clone, Debug)]
-pub(crate) enum Zoning {
-    /// The provided structure should be built on this tile.
-    Structure(ClipboardData),
-    /// No zoning is set.
-    None,
-    /// Zoning is set to keep the tile clear.
-    KeepClear,
-}
-
-impl Display for Zoning {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            Zoning::Structure(clipboard_data) => {
-                let id = clipboard_data.structure_id;
-                format!("{id}")
-            }
-            Zoning::None => "None".to_string(),
-            Zoning::KeepClear => "Keep Clear".to_string(),
-        };
-
-        write!(f, "{str}")
-    }
-}
-
 /// Applies zoning to an area, causing structures to be created (or removed) there.
 ///
 /// This system also handles the "paste" functionality.
+/// Every change is recorded in the [`OrderHistory`], so that it can be undone.
 fn set_zoning(
     cursor: Res<CursorPos>,
     actions: Res<ActionState<PlayerAction>>,
     clipboard: Res<Clipboard>,
-    mut terrain_query: Query<&mut Zoning, With<Terrain>>,
+    mut terrain_query: Query<(&mut Zoning, &TilePos), With<Terrain>>,
     current_selection: Res<CurrentSelection>,
     map_geometry: Res<MapGeometry>,
+    mut order_history: ResMut<OrderHistory>,
 ) {
+    let mut rezone = |terrain_entity: Entity, new_zoning: Zoning| {
+        let (mut zoning, &tile_pos) = terrain_query.get_mut(terrain_entity).unwrap();
+        if *zoning != new_zoning {
+            order_history.record(tile_pos, zoning.clone(), new_zoning.clone());
+            *zoning = new_zoning;
+        }
+    };
+
     if let Some(cursor_tile_pos) = cursor.maybe_tile_pos() {
         let selected_tiles = match &*current_selection {
             CurrentSelection::Terrain(selected_tiles) => selected_tiles.clone(),
@@ -100,8 +84,7 @@ fn set_zoning(
         // Try to remove everything at the location
         if actions.pressed(PlayerAction::KeepClear) {
             for terrain_entity in relevant_terrain_entities {
-                let mut zoning = terrain_query.get_mut(terrain_entity).unwrap();
-                *zoning = Zoning::KeepClear;
+                rezone(terrain_entity, Zoning::KeepClear);
             }
 
             // Don't try to clear and zone in the same frame
@@ -111,8 +94,7 @@ fn set_zoning(
         // Explicitly clear the selection
         if actions.pressed(PlayerAction::ClearZoning) {
             for terrain_entity in relevant_terrain_entities {
-                let mut zoning = terrain_query.get_mut(terrain_entity).unwrap();
-                *zoning = Zoning::None;
+                rezone(terrain_entity, Zoning::None);
             }
 
             // Don't try to clear and zone in the same frame
@@ -124,23 +106,20 @@ fn set_zoning(
             if clipboard.is_empty() {
                 // Clear zoning
                 for terrain_entity in relevant_terrain_entities {
-                    let mut zoning = terrain_query.get_mut(terrain_entity).unwrap();
-                    *zoning = Zoning::None;
+                    rezone(terrain_entity, Zoning::None);
                 }
             // Zone using the single selected structure
             } else if clipboard.len() == 1 {
                 let clipboard_item = clipboard.values().next().unwrap();
                 for terrain_entity in relevant_terrain_entities {
-                    let mut zoning = terrain_query.get_mut(terrain_entity).unwrap();
-                    *zoning = Zoning::Structure(clipboard_item.clone());
+                    rezone(terrain_entity, Zoning::Structure(clipboard_item.clone()));
                 }
             // Paste the selection
             } else {
                 for (tile_pos, clipboard_item) in clipboard.offset_positions(cursor_tile_pos) {
                     // Avoid trying to operate on terrain that doesn't exist
                     if let Some(&terrain_entity) = map_geometry.terrain_index.get(&tile_pos) {
-                        let mut zoning = terrain_query.get_mut(terrain_entity).unwrap();
-                        *zoning = Zoning::Structure(clipboard_item.clone());
+                        rezone(terrain_entity, Zoning::Structure(clipboard_item.clone()));
                     }
                 }
             }
@@ -149,27 +128,36 @@ fn set_zoning(
 }
 
 /// Spawn and despawn ghosts based on zoning.
-fn manage_previews_from_zoning(
-    // We cannot use change detection here, or tiles would not be kept clear when built upon after zoning is set
-    mut terrain_query: Query<(&mut Zoning, &TilePos, &Terrain)>,
+///
+/// Whether the tile suits the structure is checked once, when the zoning is set.
+/// Unsuitable ghosts keep their zoning, but wait rather than being built.
+/// Once a zoned structure is gone, such as a crop that has died, a fresh ghost is spawned to replace it.
+pub(crate) fn manage_previews_from_zoning(
+    terrain_query: Query<(Ref<Zoning>, &TilePos, &Terrain, &SoilMoisture, &Fertility)>,
     structure_manifest: Res<StructureManifest>,
     mut commands: Commands,
     map_geometry: Res<MapGeometry>,
 ) {
-    for (mut zoning, &tile_pos, terrain) in terrain_query.iter_mut() {
-        // Reborrowing here would trigger change detection, causing this system to constantly check
-        match zoning.bypass_change_detection() {
+    for (zoning, &tile_pos, terrain, soil_moisture, fertility) in terrain_query.iter() {
+        match &*zoning {
             Zoning::Structure(clipboard_data) => {
-                let structure_data = structure_manifest.get(clipboard_data.structure_id);
-                if structure_data.allowed_terrain_types().contains(terrain) {
-                    commands.spawn_ghost(tile_pos, clipboard_data.clone())
-                } else {
-                    *zoning = Zoning::None;
-                    // We bypassed change detection above, so need to manually trigger it here.
-                    zoning.set_changed();
+                let replant = !map_geometry.structure_index.contains_key(&tile_pos)
+                    && !map_geometry.ghost_index.contains_key(&tile_pos);
+
+                if zoning.is_changed() || replant {
+                    let structure_data = structure_manifest.get(clipboard_data.structure_id);
+                    let light = map_geometry.light_level(tile_pos);
+                    let suitable =
+                        structure_data.can_be_placed(terrain, light, soil_moisture.0, fertility.0);
+                    commands.spawn_ghost(tile_pos, clipboard_data.clone(), !suitable);
+                }
+            }
+            Zoning::None => {
+                if zoning.is_changed() {
+                    commands.despawn_ghost(tile_pos);
                 }
             }
-            Zoning::None => commands.despawn_ghost(tile_pos),
+            // We cannot use change detection here, or tiles would not be kept clear when built upon after zoning is set
             Zoning::KeepClear => {
                 if let Some(structure_entity) = map_geometry.structure_index.get(&tile_pos) {
                     commands
@@ -182,7 +170,7 @@ fn manage_previews_from_zoning(
 }
 
 /// Keeps marked tiles clear by sending removal signals from structures that are marked for removal
-fn keep_tiles_clear(
+pub(crate) fn keep_tiles_clear(
     mut structure_query: Query<(&mut Emitter, &Id<Structure>), With<MarkedForDemolition>>,
 ) {
     for (mut doomed_emitter, &structure_id) in structure_query.iter_mut() {
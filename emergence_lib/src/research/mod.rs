@@ -0,0 +1,484 @@
+//! A programmatic interface to a headless simulation, for experimenting with AI.
+//!
+//! A [`ResearchEnvironment`] advances the simulation only when asked to,
+//! reports [`Observation`]s of the world and accepts [`Order`]s.
+//! Its state can be saved to an in-memory [`Checkpoint`] and rewound to later, to explore several branches of the same game.
+//! Orders are applied by zoning tiles, exactly as a player would.
+//!
+//! For machine learning, dense [`TensorObservation`]s of just the channels chosen in an [`ObservationSpec`]
+//! can be made on demand, or written to a file each tick with [`ResearchEnvironment::export_observations`].
+//! Pointing that file at shared memory (e.g. `/dev/shm` on Linux) lets another process read it without copying through a disk.
+//!
+//! The `emergence_py` crate in this workspace wraps this interface in Python bindings,
+//! so that an external Python process can drive the simulation.
+
+use bevy::{
+    ecs::system::{SystemParam, SystemState},
+    prelude::*,
+    utils::HashMap,
+};
+use core::fmt::Display;
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{
+    asset_management::manifest::{Id, Structure, StructureManifest, Unit},
+    organisms::{population::PopulationCensus, Organism},
+    player_interaction::zoning::{keep_tiles_clear, manage_previews_from_zoning},
+    signals::Signals,
+    simulation::{
+        generation::GenerationConfig,
+        geometry::{Facing, MapGeometry, TilePos},
+        save::{restore_snapshot, save_snapshot, Checkpoint},
+        time::SimulationTick,
+    },
+    structures::{
+        construction::{Ghost, Preview},
+        crafting::set_emitter,
+        ClipboardData,
+    },
+    terrain::{Terrain, Zoning},
+    testing::simulation_app,
+};
+
+use self::tensor::{Channel, ObservationSpec, TensorBuilder, TensorObservation};
+
+pub mod tensor;
+
+/// A headless simulation that is stepped, observed and ordered about from code.
+pub struct ResearchEnvironment {
+    /// The app running the simulation
+    app: App,
+}
+
+impl ResearchEnvironment {
+    /// Creates a new simulation, generating its world from `gen_config`.
+    ///
+    /// The world is generated by the first call to [`ResearchEnvironment::step`].
+    pub fn new(gen_config: GenerationConfig) -> Self {
+        let mut app = simulation_app(gen_config);
+        // Zoning is usually managed by the player interaction plugin, which requires a window
+        app.add_system(manage_previews_from_zoning)
+            // Must run after crafting emitters in order to wipe out their signals
+            .add_system(keep_tiles_clear.after(set_emitter))
+            .add_system(export_observations.in_base_set(CoreSet::Last));
+
+        ResearchEnvironment { app }
+    }
+
+    /// Advances the simulation by `n_frames` frames.
+    pub fn step(&mut self, n_frames: u32) {
+        for _ in 0..n_frames {
+            self.app.update();
+        }
+    }
+
+    /// The number of frames that the simulation has advanced since the start of the game.
+    pub fn tick(&self) -> u64 {
+        self.app
+            .world
+            .get_resource::<SimulationTick>()
+            .map(SimulationTick::get)
+            .unwrap_or_default()
+    }
+
+    /// Records the current state of the simulation in memory, so that it can be rewound to later.
+    pub fn save_snapshot(&mut self) -> Checkpoint {
+        save_snapshot(&mut self.app.world)
+    }
+
+    /// Rewinds the simulation to the state recorded in the `checkpoint`.
+    ///
+    /// Any number of branches can be explored from the same checkpoint.
+    pub fn restore_snapshot(&mut self, checkpoint: &Checkpoint) {
+        restore_snapshot(&mut self.app.world, checkpoint);
+    }
+
+    /// Reports the current state of the world.
+    pub fn observe(&mut self) -> Observation {
+        let mut system_state: SystemState<ObservationQuery> = SystemState::new(&mut self.app.world);
+        let observation_query = system_state.get(&self.app.world);
+
+        observation_query.observe()
+    }
+
+    /// Observes only the parts of the world described by the `spec`.
+    pub fn observe_tensor(&mut self, spec: &ObservationSpec) -> TensorObservation {
+        let mut system_state: SystemState<ObservationQuery> = SystemState::new(&mut self.app.world);
+        let observation_query = system_state.get(&self.app.world);
+
+        observation_query.observe_tensor(spec)
+    }
+
+    /// Writes a [`TensorObservation`] described by the `spec` to the file at `path` after every tick.
+    ///
+    /// The file contains the bytes produced by [`TensorObservation::to_bytes`],
+    /// and is replaced in a single step so that readers never see a partially written observation.
+    /// Pass `None` to stop exporting.
+    pub fn export_observations(&mut self, export: Option<(ObservationSpec, PathBuf)>) {
+        match export {
+            Some((spec, path)) => self
+                .app
+                .world
+                .insert_resource(ObservationExport { spec, path }),
+            None => {
+                self.app.world.remove_resource::<ObservationExport>();
+            }
+        }
+    }
+
+    /// Gives an `order` to the colony, which will be carried out over the next frames.
+    pub fn submit(&mut self, order: &Order) -> Result<(), OrderError> {
+        let world = &mut self.app.world;
+        let tile_pos = order.tile_pos();
+        let terrain_entity = world
+            .resource::<MapGeometry>()
+            .terrain_index
+            .get(&tile_pos)
+            .copied()
+            .ok_or(OrderError::OffMap { tile_pos })?;
+
+        let new_zoning = match order {
+            Order::Build { structure, .. } => {
+                let structure_manifest = world.resource::<StructureManifest>();
+                let structure_id = structure_manifest
+                    .variants()
+                    .into_iter()
+                    .find(|structure_id| structure_id.name() == Some(structure.as_str()))
+                    .ok_or_else(|| OrderError::UnknownStructure(structure.clone()))?;
+
+                Zoning::Structure(ClipboardData {
+                    structure_id,
+                    facing: Facing::default(),
+                    active_recipe: structure_manifest
+                        .get(structure_id)
+                        .starting_recipe()
+                        .clone(),
+                })
+            }
+            Order::Demolish { .. } => Zoning::KeepClear,
+            Order::Clear { .. } => Zoning::None,
+        };
+
+        let mut zoning = world
+            .get_mut::<Zoning>(terrain_entity)
+            .ok_or(OrderError::OffMap { tile_pos })?;
+        zoning.set_if_neq(new_zoning);
+
+        Ok(())
+    }
+}
+
+/// An instruction given to the colony, mirroring the zoning tools available to the player.
+///
+/// Coordinates are axial, as in [`TilePos::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Order {
+    /// Build the structure with the string identifier `structure` on this tile.
+    ///
+    /// Like the player's zoning, the order is silently dropped if the structure cannot be placed here.
+    Build {
+        /// The string identifier of the structure to build
+        structure: String,
+        /// The first axial coordinate of the tile
+        x: i32,
+        /// The second axial coordinate of the tile
+        y: i32,
+    },
+    /// Demolish anything built on this tile, and keep it clear.
+    Demolish {
+        /// The first axial coordinate of the tile
+        x: i32,
+        /// The second axial coordinate of the tile
+        y: i32,
+    },
+    /// Remove any previous order for this tile.
+    Clear {
+        /// The first axial coordinate of the tile
+        x: i32,
+        /// The second axial coordinate of the tile
+        y: i32,
+    },
+}
+
+impl Order {
+    /// The tile that this order applies to.
+    pub fn tile_pos(&self) -> TilePos {
+        match *self {
+            Order::Build { x, y, .. } | Order::Demolish { x, y } | Order::Clear { x, y } => {
+                TilePos::new(x, y)
+            }
+        }
+    }
+}
+
+/// The reasons an [`Order`] can be refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// The order targets a tile that is not on the map.
+    OffMap {
+        /// The tile that was targeted
+        tile_pos: TilePos,
+    },
+    /// No structure has this string identifier.
+    UnknownStructure(String),
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::OffMap { tile_pos } => write!(f, "The tile {tile_pos} is not on the map"),
+            OrderError::UnknownStructure(structure) => {
+                write!(f, "No structure is called {structure}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Everything that can be observed about the world on a single tick.
+///
+/// Tiles and signals are sorted by position, so that identical worlds produce identical observations.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Observation {
+    /// The tick on which the observation was made
+    pub tick: u64,
+    /// Every tile on the map
+    pub tiles: Vec<TileObservation>,
+    /// Every signal on every tile where it is present
+    pub signals: Vec<SignalObservation>,
+    /// The number of living organisms of each kind, labelled `unit:<id>` or `structure:<id>`
+    pub census: BTreeMap<String, usize>,
+}
+
+/// The contents of a single tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileObservation {
+    /// The first axial coordinate of the tile
+    pub x: i32,
+    /// The second axial coordinate of the tile
+    pub y: i32,
+    /// The type of terrain
+    pub terrain: String,
+    /// The height of the tile
+    pub height: f32,
+    /// The structure built here, if any
+    pub structure: Option<String>,
+    /// The structure waiting to be built here, if any
+    pub ghost: Option<String>,
+    /// The number of units standing here
+    pub units: usize,
+}
+
+/// The strength of one type of signal on a single tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalObservation {
+    /// The first axial coordinate of the tile
+    pub x: i32,
+    /// The second axial coordinate of the tile
+    pub y: i32,
+    /// The type of signal, e.g. `Pull(leuco_chunk)`
+    pub signal_type: String,
+    /// The strength of the signal
+    pub strength: f32,
+}
+
+/// The data needed to make an [`Observation`] of the world.
+#[derive(SystemParam)]
+struct ObservationQuery<'w, 's> {
+    /// The current tick
+    simulation_tick: Res<'w, SimulationTick>,
+    /// The tiles, structures and ghosts on the map
+    map_geometry: Res<'w, MapGeometry>,
+    /// The signals on every tile
+    signals: Res<'w, Signals>,
+    /// The terrain of every tile
+    terrain_query: Query<'w, 's, (&'static TilePos, &'static Terrain)>,
+    /// Structures and ghosts
+    structure_query: Query<'w, 's, &'static Id<Structure>, Without<Preview>>,
+    /// Living structures, counted in the census
+    organism_query: Query<'w, 's, &'static Id<Structure>, (With<Organism>, Without<Ghost>)>,
+    /// Every unit
+    unit_query: Query<'w, 's, (&'static TilePos, &'static Id<Unit>)>,
+}
+
+impl<'w, 's> ObservationQuery<'w, 's> {
+    /// Observes the channels chosen in the `spec`.
+    fn observe_tensor(&self, spec: &ObservationSpec) -> TensorObservation {
+        let mut builder = TensorBuilder::new(spec);
+
+        for (&tile_pos, _) in self.terrain_query.iter() {
+            builder.add_tile(tile_pos);
+        }
+
+        for (channel_index, channel) in spec.channels.iter().enumerate() {
+            match channel {
+                Channel::Height => {
+                    for (&tile_pos, &height) in self.map_geometry.height_index.iter() {
+                        builder.add(channel_index, tile_pos, height);
+                    }
+                }
+                Channel::Terrain(terrain_type) => {
+                    for (&tile_pos, terrain) in self.terrain_query.iter() {
+                        if terrain == terrain_type {
+                            builder.add(channel_index, tile_pos, 1.);
+                        }
+                    }
+                }
+                Channel::Structures => {
+                    for &tile_pos in self.map_geometry.structure_index.keys() {
+                        builder.add(channel_index, tile_pos, 1.);
+                    }
+                }
+                Channel::Ghosts => {
+                    for &tile_pos in self.map_geometry.ghost_index.keys() {
+                        builder.add(channel_index, tile_pos, 1.);
+                    }
+                }
+                Channel::Units => {
+                    for (&tile_pos, _) in self.unit_query.iter() {
+                        builder.add(channel_index, tile_pos, 1.);
+                    }
+                }
+                Channel::Signal(signal_name) => {
+                    // Signals that have never been emitted are left at 0
+                    let maybe_signal_type = self
+                        .signals
+                        .signal_types()
+                        .into_iter()
+                        .find(|signal_type| signal_type.to_string() == *signal_name);
+
+                    if let Some(signal_type) = maybe_signal_type {
+                        for (tile_pos, strength) in self.signals.tiles_with(signal_type) {
+                            builder.add(channel_index, tile_pos, strength.value());
+                        }
+                    }
+                }
+            }
+        }
+
+        builder.finish(self.simulation_tick.get())
+    }
+
+    /// Summarizes the current state of the world.
+    fn observe(&self) -> Observation {
+        let structure_at = |index: &HashMap<TilePos, Entity>, tile_pos: &TilePos| {
+            index
+                .get(tile_pos)
+                .and_then(|&entity| self.structure_query.get(entity).ok())
+                .map(ToString::to_string)
+        };
+
+        let mut units_per_tile: BTreeMap<(i32, i32), usize> = BTreeMap::new();
+        for (tile_pos, _) in self.unit_query.iter() {
+            *units_per_tile.entry((tile_pos.x, tile_pos.y)).or_default() += 1;
+        }
+
+        let mut tiles: Vec<TileObservation> = self
+            .terrain_query
+            .iter()
+            .map(|(tile_pos, terrain)| TileObservation {
+                x: tile_pos.x,
+                y: tile_pos.y,
+                terrain: terrain.to_string(),
+                height: self
+                    .map_geometry
+                    .height_index
+                    .get(tile_pos)
+                    .copied()
+                    .unwrap_or_default(),
+                structure: structure_at(&self.map_geometry.structure_index, tile_pos),
+                ghost: structure_at(&self.map_geometry.ghost_index, tile_pos),
+                units: units_per_tile
+                    .get(&(tile_pos.x, tile_pos.y))
+                    .copied()
+                    .unwrap_or_default(),
+            })
+            .collect();
+        tiles.sort_by_key(|tile| (tile.x, tile.y));
+
+        let mut signals: Vec<SignalObservation> = self
+            .signals
+            .iter_strengths()
+            .map(|(signal_type, tile_pos, strength)| SignalObservation {
+                x: tile_pos.x,
+                y: tile_pos.y,
+                signal_type: signal_type.to_string(),
+                strength: strength.value(),
+            })
+            .collect();
+        signals.sort_by(|a, b| (a.x, a.y, &a.signal_type).cmp(&(b.x, b.y, &b.signal_type)));
+
+        let census = PopulationCensus::count(
+            self.unit_query.iter().map(|(_, &unit_id)| unit_id),
+            self.organism_query.iter().copied(),
+        )
+        .by_kind();
+
+        Observation {
+            tick: self.simulation_tick.get(),
+            tiles,
+            signals,
+            census,
+        }
+    }
+}
+
+/// Where and how to export a [`TensorObservation`] each tick.
+#[derive(Resource, Debug, Clone)]
+struct ObservationExport {
+    /// The parts of the world to observe
+    spec: ObservationSpec,
+    /// The file that observations are written to
+    path: PathBuf,
+}
+
+/// Writes a [`TensorObservation`] to disk after every tick, if requested.
+fn export_observations(
+    maybe_export: Option<Res<ObservationExport>>,
+    simulation_tick: Res<SimulationTick>,
+    observation_query: ObservationQuery,
+) {
+    let export = match maybe_export {
+        Some(export) => export,
+        None => return,
+    };
+
+    if !simulation_tick.is_changed() && !export.is_changed() {
+        return;
+    }
+
+    // Observations are written synchronously, so that the file is ready as soon as the frame ends
+    let bytes = observation_query.observe_tensor(&export.spec).to_bytes();
+    let staging_path = export.path.with_extension("partial");
+    let result = std::fs::write(&staging_path, bytes)
+        .and_then(|_| std::fs::rename(&staging_path, &export.path));
+
+    if let Err(error) = result {
+        error!(
+            "Could not export observation to {}: {error}",
+            export.path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_target_their_tile() {
+        let build = Order::Build {
+            structure: "ant_hive".to_string(),
+            x: 2,
+            y: -1,
+        };
+
+        assert_eq!(build.tile_pos(), TilePos::new(2, -1));
+        assert_eq!(
+            Order::Demolish { x: 0, y: 3 }.tile_pos(),
+            TilePos::new(0, 3)
+        );
+        assert_eq!(Order::Clear { x: -4, y: 0 }.tile_pos(), TilePos::new(-4, 0));
+    }
+}
@@ -0,0 +1,274 @@
+//! Dense, tensor-friendly observations of the world, for machine learning.
+//!
+//! An [`ObservationSpec`] chooses which [`Channel`]s to observe, how much of the map to cover and at what resolution.
+//! Only the requested channels are computed, so training loops don't pay for data they don't use.
+//!
+//! Observations are laid out channel-major, as `[channel][row][column]`,
+//! where rows follow the second axial coordinate and columns the first.
+//! Each cell holds the mean value of the on-map tiles that it covers, and cells entirely off the map are 0.
+
+use core::fmt::Display;
+use std::str::FromStr;
+
+use crate::{enum_iter::IterableEnum, simulation::geometry::TilePos, terrain::Terrain};
+
+/// A single layer of a [`TensorObservation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Channel {
+    /// The height of each tile.
+    Height,
+    /// 1 for tiles of this terrain type, and 0 otherwise.
+    Terrain(Terrain),
+    /// 1 for tiles with a structure, and 0 otherwise.
+    Structures,
+    /// 1 for tiles with a structure waiting to be built, and 0 otherwise.
+    Ghosts,
+    /// The number of units on each tile.
+    Units,
+    /// The strength of the signal whose type is displayed as this string, e.g. `Pull(leuco_chunk)`.
+    Signal(String),
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Channel::Height => write!(f, "height"),
+            Channel::Terrain(terrain) => write!(f, "terrain:{terrain}"),
+            Channel::Structures => write!(f, "structures"),
+            Channel::Ghosts => write!(f, "ghosts"),
+            Channel::Units => write!(f, "units"),
+            Channel::Signal(signal_type) => write!(f, "signal:{signal_type}"),
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = ChannelParseError;
+
+    /// Parses a channel from the same format that it is displayed in, e.g. `terrain:Rocky` or `signal:Alarm`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let channel = match s.split_once(':') {
+            None => match s {
+                "height" => Channel::Height,
+                "structures" => Channel::Structures,
+                "ghosts" => Channel::Ghosts,
+                "units" => Channel::Units,
+                _ => return Err(ChannelParseError(s.to_string())),
+            },
+            Some(("terrain", terrain_name)) => Terrain::variants()
+                .find(|terrain| terrain.to_string() == terrain_name)
+                .map(Channel::Terrain)
+                .ok_or_else(|| ChannelParseError(s.to_string()))?,
+            Some(("signal", signal_type)) if !signal_type.is_empty() => {
+                Channel::Signal(signal_type.to_string())
+            }
+            Some(_) => return Err(ChannelParseError(s.to_string())),
+        };
+
+        Ok(channel)
+    }
+}
+
+/// A string that does not describe any [`Channel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelParseError(pub String);
+
+impl Display for ChannelParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not an observation channel", self.0)
+    }
+}
+
+impl std::error::Error for ChannelParseError {}
+
+/// Describes which parts of the world are recorded in a [`TensorObservation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservationSpec {
+    /// The layers to observe, in order
+    pub channels: Vec<Channel>,
+    /// The number of tiles from the origin covered in each direction
+    pub radius: u32,
+    /// The width of the square of tiles averaged into each cell
+    ///
+    /// A value of 1 observes every tile individually.
+    pub cell_size: u32,
+}
+
+impl ObservationSpec {
+    /// Observes `channels` for every tile within `radius` of the origin.
+    pub fn new(channels: Vec<Channel>, radius: u32) -> Self {
+        ObservationSpec {
+            channels,
+            radius,
+            cell_size: 1,
+        }
+    }
+
+    /// Averages each square of `cell_size` by `cell_size` tiles into a single cell.
+    ///
+    /// A `cell_size` of 0 is treated as 1.
+    pub fn with_cell_size(mut self, cell_size: u32) -> Self {
+        self.cell_size = cell_size.max(1);
+        self
+    }
+
+    /// The number of cells along each side of the observed area.
+    fn side(&self) -> usize {
+        let cell_size = self.cell_size.max(1);
+        ((2 * self.radius + 1) + cell_size - 1) as usize / cell_size as usize
+    }
+
+    /// The shape of the observation, as `[channels, rows, columns]`.
+    pub fn shape(&self) -> [usize; 3] {
+        [self.channels.len(), self.side(), self.side()]
+    }
+
+    /// The index within a single channel of the cell covering `tile_pos`, if it is observed.
+    fn cell_index(&self, tile_pos: TilePos) -> Option<usize> {
+        let radius = self.radius as i32;
+        let cell_size = self.cell_size.max(1) as usize;
+
+        if tile_pos.x.abs() > radius || tile_pos.y.abs() > radius {
+            return None;
+        }
+
+        let column = (tile_pos.x + radius) as usize / cell_size;
+        let row = (tile_pos.y + radius) as usize / cell_size;
+        Some(row * self.side() + column)
+    }
+}
+
+/// Accumulates the values of each tile into a [`TensorObservation`].
+#[derive(Debug)]
+pub(super) struct TensorBuilder<'a> {
+    /// The layout of the observation
+    spec: &'a ObservationSpec,
+    /// The sum of the tile values in each cell, laid out like [`TensorObservation::data`]
+    sums: Vec<f32>,
+    /// The number of on-map tiles covered by each cell
+    tile_counts: Vec<u32>,
+}
+
+impl<'a> TensorBuilder<'a> {
+    /// Starts an empty observation, laid out according to `spec`.
+    pub(super) fn new(spec: &'a ObservationSpec) -> Self {
+        let [n_channels, rows, columns] = spec.shape();
+
+        TensorBuilder {
+            spec,
+            sums: vec![0.; n_channels * rows * columns],
+            tile_counts: vec![0; rows * columns],
+        }
+    }
+
+    /// Records that `tile_pos` is on the map, so that it is included when averaging its cell.
+    pub(super) fn add_tile(&mut self, tile_pos: TilePos) {
+        if let Some(cell_index) = self.spec.cell_index(tile_pos) {
+            self.tile_counts[cell_index] += 1;
+        }
+    }
+
+    /// Adds `value` to the `channel_index`th channel at `tile_pos`.
+    pub(super) fn add(&mut self, channel_index: usize, tile_pos: TilePos, value: f32) {
+        if let Some(cell_index) = self.spec.cell_index(tile_pos) {
+            self.sums[channel_index * self.tile_counts.len() + cell_index] += value;
+        }
+    }
+
+    /// Averages each cell, completing the observation made on `tick`.
+    pub(super) fn finish(self, tick: u64) -> TensorObservation {
+        let cells_per_channel = self.tile_counts.len();
+        let data = self
+            .sums
+            .iter()
+            .enumerate()
+            .map(|(i, &sum)| match self.tile_counts[i % cells_per_channel] {
+                0 => 0.,
+                tile_count => sum / tile_count as f32,
+            })
+            .collect();
+
+        TensorObservation {
+            tick,
+            shape: self.spec.shape(),
+            data,
+        }
+    }
+}
+
+/// A dense observation of the world, described by an [`ObservationSpec`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorObservation {
+    /// The tick on which the observation was made
+    pub tick: u64,
+    /// The shape of the observation, as `[channels, rows, columns]`
+    pub shape: [usize; 3],
+    /// The value of each cell, in channel-major order
+    pub data: Vec<f32>,
+}
+
+impl TensorObservation {
+    /// Encodes this observation as little-endian bytes.
+    ///
+    /// The tick is written as a `u64`, followed by each dimension of the shape as a `u32`,
+    /// followed by the data as `f32`s.
+    /// The data begins 20 bytes in, so it can be read directly with e.g. `numpy.frombuffer(buffer, "<f4", offset=20)`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(20 + 4 * self.data.len());
+        bytes.extend(self.tick.to_le_bytes());
+        for dimension in self.shape {
+            bytes.extend((dimension as u32).to_le_bytes());
+        }
+        for value in &self.data {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_round_trip_through_strings() {
+        let channels = [
+            Channel::Height,
+            Channel::Terrain(Terrain::Rocky),
+            Channel::Structures,
+            Channel::Ghosts,
+            Channel::Units,
+            Channel::Signal("Pull(leuco_chunk)".to_string()),
+        ];
+
+        for channel in channels {
+            assert_eq!(channel.to_string().parse(), Ok(channel));
+        }
+
+        assert!("terrain:Lava".parse::<Channel>().is_err());
+        assert!("signal:".parse::<Channel>().is_err());
+    }
+
+    #[test]
+    fn cells_average_the_tiles_they_cover() {
+        let spec = ObservationSpec::new(vec![Channel::Units, Channel::Height], 1).with_cell_size(2);
+        assert_eq!(spec.shape(), [2, 2, 2]);
+
+        let mut builder = TensorBuilder::new(&spec);
+        builder.add_tile(TilePos::new(-1, -1));
+        builder.add_tile(TilePos::new(0, -1));
+        builder.add(0, TilePos::new(-1, -1), 3.);
+        // Outside of the observed area
+        builder.add(0, TilePos::new(2, 0), 100.);
+        builder.add_tile(TilePos::new(1, 1));
+        builder.add(1, TilePos::new(1, 1), 0.5);
+
+        let observation = builder.finish(7);
+        assert_eq!(observation.data, vec![1.5, 0., 0., 0., 0., 0., 0., 0.5]);
+
+        let bytes = observation.to_bytes();
+        assert_eq!(bytes.len(), 20 + 4 * 8);
+        assert_eq!(bytes[..8], 7u64.to_le_bytes());
+    }
+}
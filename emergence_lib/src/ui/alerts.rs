@@ -0,0 +1,174 @@
+//! Displays the current day, the weather and the colony's alert status, and any recent alerts.
+//!
+//! Alerts with a location can be focused and activated to select the tile where they happened.
+
+use bevy::prelude::*;
+
+use crate::{
+    player_interaction::selection::{CurrentSelection, SelectedTiles},
+    simulation::{
+        alerts::AlertLog,
+        geometry::TilePos,
+        time::{InGameTime, Season, TimeOfDay},
+        weather::CurrentWeather,
+    },
+    units::alarm::ColonyAlertStatus,
+};
+
+use super::{
+    focus::{FocusActivated, Focusable},
+    FiraSansFontFamily, LeftPanel,
+};
+
+/// Initializes and updates the alerts panel.
+pub(super) struct AlertsPanelPlugin;
+
+impl Plugin for AlertsPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_alerts_panel)
+            .add_system(update_alerts_header)
+            .add_system(update_alert_entries)
+            .add_system(select_activated_alert);
+    }
+}
+
+/// The UI node that contains the header and all alert entries.
+#[derive(Component)]
+struct AlertsPanel;
+
+/// The UI node that displays the day and weather.
+#[derive(Component)]
+struct AlertsHeader;
+
+/// A UI node that displays a single alert.
+#[derive(Component)]
+struct AlertEntry {
+    /// Where the alert happened, if it has a location
+    tile_pos: Option<TilePos>,
+}
+
+/// Alerts are visited after any open menu when cycling focus.
+const FIRST_ALERT_FOCUS_ORDER: i32 = 1000;
+
+/// The style used for all text in the alerts panel.
+fn alerts_text_style(font_family: &FiraSansFontFamily) -> TextStyle {
+    TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    }
+}
+
+/// Creates the UI elements for the alerts panel.
+fn populate_alerts_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let left_panel = parent_query.single();
+
+    let alerts_panel = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                ..default()
+            },
+            AlertsPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", alerts_text_style(&font_family)),
+                AlertsHeader,
+            ));
+        })
+        .id();
+
+    commands.entity(left_panel).add_child(alerts_panel);
+}
+
+/// Shows the current day, season and time of day, the weather and whether the colony is on alert.
+fn update_alerts_header(
+    in_game_time: Res<InGameTime>,
+    season: Res<Season>,
+    time_of_day: Res<TimeOfDay>,
+    current_weather: Res<CurrentWeather>,
+    colony_alert_status: Res<ColonyAlertStatus>,
+    mut text_query: Query<&mut Text, With<AlertsHeader>>,
+) {
+    let mut text = text_query.single_mut();
+
+    text.sections[0].value = format!(
+        "{}, {} - {}\nWeather: {}\nColony: {}",
+        *in_game_time,
+        *season,
+        *time_of_day,
+        current_weather.get(),
+        *colony_alert_status
+    );
+}
+
+/// Rebuilds the list of alerts whenever a new one is raised.
+fn update_alert_entries(
+    alert_log: Res<AlertLog>,
+    font_family: Res<FiraSansFontFamily>,
+    panel_query: Query<Entity, With<AlertsPanel>>,
+    entry_query: Query<Entity, With<AlertEntry>>,
+    mut commands: Commands,
+) {
+    if !alert_log.is_changed() {
+        return;
+    }
+
+    for entity in entry_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let alerts_panel = panel_query.single();
+    let text_style = alerts_text_style(&font_family);
+
+    for (index, (day, alert)) in alert_log.recent().enumerate() {
+        let mut entry = commands.spawn((
+            TextBundle {
+                text: Text::from_section(format!("[Day {day}] {alert}"), text_style.clone()),
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            AlertEntry {
+                tile_pos: alert.tile_pos,
+            },
+        ));
+
+        // Only alerts with a location have anything to jump to
+        if alert.tile_pos.is_some() {
+            entry.insert(Focusable {
+                order: FIRST_ALERT_FOCUS_ORDER + index as i32,
+            });
+        }
+
+        let entry_entity = entry.id();
+        commands.entity(alerts_panel).add_child(entry_entity);
+    }
+}
+
+/// Selects the tile where an activated alert happened.
+fn select_activated_alert(
+    mut activation_events: EventReader<FocusActivated>,
+    entry_query: Query<&AlertEntry>,
+    mut current_selection: ResMut<CurrentSelection>,
+) {
+    for event in activation_events.iter() {
+        if let Ok(AlertEntry {
+            tile_pos: Some(tile_pos),
+        }) = entry_query.get(event.entity)
+        {
+            let mut selected_tiles = SelectedTiles::default();
+            selected_tiles.add_tile(*tile_pos);
+            *current_selection = CurrentSelection::Terrain(selected_tiles);
+        }
+    }
+}
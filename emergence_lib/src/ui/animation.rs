@@ -0,0 +1,317 @@
+//! Fades, slide-ins and toast notifications for the UI.
+//!
+//! These are driven by the [`UiClock`], which keeps running while the simulation is paused,
+//! so that the interface remains responsive.
+
+use bevy::prelude::*;
+
+use crate::{player_interaction::pause::UiClock, simulation::alerts::Alert};
+
+use super::FiraSansFontFamily;
+
+/// Eases animations in and out, so that they start and stop smoothly.
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// Fades the background and text of a UI node between two opacities.
+#[derive(Component, Debug, Clone)]
+pub(super) struct Fade {
+    /// Tracks the progress of the fade
+    timer: Timer,
+    /// The opacity at the start of the fade
+    from: f32,
+    /// The opacity at the end of the fade
+    to: f32,
+    /// The opacity of the node's background when fully faded in
+    background_alpha: f32,
+}
+
+impl Fade {
+    /// Fades a node in from fully transparent.
+    pub(super) fn fade_in(seconds: f32) -> Self {
+        Fade {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            from: 0.,
+            to: 1.,
+            background_alpha: 1.,
+        }
+    }
+
+    /// Fades a node out to fully transparent.
+    pub(super) fn fade_out(seconds: f32) -> Self {
+        Fade {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            from: 1.,
+            to: 0.,
+            background_alpha: 1.,
+        }
+    }
+
+    /// Sets the opacity of the node's background when fully faded in, for translucent nodes.
+    pub(super) fn with_background_alpha(mut self, background_alpha: f32) -> Self {
+        self.background_alpha = background_alpha;
+        self
+    }
+
+    /// The current opacity of the node.
+    fn alpha(&self) -> f32 {
+        self.from + (self.to - self.from) * smoothstep(self.timer.percent())
+    }
+}
+
+/// Slides a UI node in from one side.
+#[derive(Component, Debug, Clone)]
+pub(super) struct SlideIn {
+    /// Tracks the progress of the slide
+    timer: Timer,
+    /// How far to the left the node starts, in logical pixels
+    offset: f32,
+}
+
+impl SlideIn {
+    /// Slides a node in from `offset` pixels to its left over `seconds`.
+    pub(super) fn new(offset: f32, seconds: f32) -> Self {
+        SlideIn {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            offset,
+        }
+    }
+}
+
+/// Advances all fades, scaling the opacity of each node's background and text.
+fn animate_fades(
+    ui_clock: Res<UiClock>,
+    mut fade_query: Query<(
+        Entity,
+        &mut Fade,
+        Option<&mut BackgroundColor>,
+        Option<&mut Text>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut fade, maybe_background, maybe_text) in fade_query.iter_mut() {
+        fade.timer.tick(ui_clock.delta());
+        let alpha = fade.alpha();
+
+        if let Some(mut background) = maybe_background {
+            background.0.set_a(alpha * fade.background_alpha);
+        }
+
+        if let Some(mut text) = maybe_text {
+            for section in text.sections.iter_mut() {
+                section.style.color.set_a(alpha);
+            }
+        }
+
+        if fade.timer.finished() {
+            commands.entity(entity).remove::<Fade>();
+        }
+    }
+}
+
+/// Advances all slide-ins, moving each node towards its resting position.
+fn animate_slide_ins(
+    ui_clock: Res<UiClock>,
+    mut slide_query: Query<(Entity, &mut SlideIn, &mut Style)>,
+    mut commands: Commands,
+) {
+    for (entity, mut slide_in, mut style) in slide_query.iter_mut() {
+        slide_in.timer.tick(ui_clock.delta());
+        let remaining = 1. - smoothstep(slide_in.timer.percent());
+        style.position.left = Val::Px(-slide_in.offset * remaining);
+
+        if slide_in.timer.finished() {
+            commands.entity(entity).remove::<SlideIn>();
+        }
+    }
+}
+
+/// A short-lived notification, which fades away on its own.
+#[derive(Component, Debug)]
+struct Toast {
+    /// Counts down until the toast is removed
+    lifetime: Timer,
+}
+
+/// The UI node that holds all toasts.
+#[derive(Component)]
+struct ToastContainer;
+
+/// How long each toast is shown for, in seconds.
+const TOAST_SECONDS: f32 = 5.;
+
+/// How long toasts take to fade in and out, in seconds.
+const TOAST_FADE_SECONDS: f32 = 0.5;
+
+/// How far toasts slide in from, in logical pixels.
+const TOAST_SLIDE_DISTANCE: f32 = 40.;
+
+/// The opacity of the background of toasts, when fully faded in.
+const TOAST_BACKGROUND_ALPHA: f32 = 0.8;
+
+/// The maximum number of toasts shown at once.
+const MAX_TOASTS: usize = 4;
+
+/// Creates the container that toasts are placed in, at the top of the screen.
+fn spawn_toast_container(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.),
+                    left: Val::Percent(35.),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(30.), Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        ToastContainer,
+    ));
+}
+
+/// Shows each [`Alert`] as a toast.
+fn spawn_toasts(
+    mut alert_events: EventReader<Alert>,
+    font_family: Res<FiraSansFontFamily>,
+    container_query: Query<Entity, With<ToastContainer>>,
+    mut commands: Commands,
+) {
+    let container = container_query.single();
+
+    for alert in alert_events.iter() {
+        spawn_toast(&mut commands, container, &font_family, alert.to_string());
+    }
+}
+
+/// Announces when the simulation is paused or resumed.
+fn announce_pause(
+    time: Res<Time>,
+    mut was_paused: Local<bool>,
+    font_family: Res<FiraSansFontFamily>,
+    container_query: Query<Entity, With<ToastContainer>>,
+    mut commands: Commands,
+) {
+    if time.is_paused() == *was_paused {
+        return;
+    }
+
+    *was_paused = time.is_paused();
+    let message = if time.is_paused() {
+        "Paused"
+    } else {
+        "Resumed"
+    };
+
+    let container = container_query.single();
+    spawn_toast(&mut commands, container, &font_family, message.to_string());
+}
+
+/// Spawns a toast with the provided `message` as a child of the `container`.
+fn spawn_toast(
+    commands: &mut Commands,
+    container: Entity,
+    font_family: &FiraSansFontFamily,
+    message: String,
+) {
+    let text_style = TextStyle {
+        // Starts invisible, and is faded in
+        color: Color::rgba(0.9, 0.9, 0.9, 0.),
+        font: font_family.regular.clone_weak(),
+        font_size: 18.,
+    };
+
+    let toast = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(message, text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(8.)),
+                    margin: UiRect::bottom(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.).into(),
+                ..default()
+            },
+            Toast {
+                lifetime: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+            },
+            Fade::fade_in(TOAST_FADE_SECONDS).with_background_alpha(TOAST_BACKGROUND_ALPHA),
+            SlideIn::new(TOAST_SLIDE_DISTANCE, TOAST_FADE_SECONDS),
+        ))
+        .id();
+
+    commands.entity(container).add_child(toast);
+}
+
+/// Fades out and removes toasts once they have been shown for long enough.
+fn expire_toasts(
+    ui_clock: Res<UiClock>,
+    mut toast_query: Query<(Entity, &mut Toast, Option<&Fade>)>,
+    mut commands: Commands,
+) {
+    let mut toasts: Vec<_> = toast_query.iter_mut().collect();
+    // Newest first
+    toasts.sort_by_key(|a| a.1.lifetime.elapsed());
+
+    for (index, (entity, toast, maybe_fade)) in toasts.iter_mut().enumerate() {
+        toast.lifetime.tick(ui_clock.delta());
+
+        // Old toasts are removed early when too many pile up
+        if toast.lifetime.finished() || index >= MAX_TOASTS {
+            commands.entity(*entity).despawn_recursive();
+        } else if toast.lifetime.remaining_secs() <= TOAST_FADE_SECONDS && maybe_fade.is_none() {
+            let fade = Fade::fade_out(toast.lifetime.remaining_secs())
+                .with_background_alpha(TOAST_BACKGROUND_ALPHA);
+            commands.entity(*entity).insert(fade);
+        }
+    }
+}
+
+/// Animates the UI and shows toast notifications, independently of the simulation's clock.
+pub(super) struct UiAnimationPlugin;
+
+impl Plugin for UiAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_toast_container)
+            .add_system(spawn_toasts)
+            .add_system(announce_pause)
+            .add_system(expire_toasts)
+            .add_system(animate_fades.after(expire_toasts))
+            .add_system(animate_slide_ins);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn smoothstep_is_clamped() {
+        assert_eq!(smoothstep(-1.), 0.);
+        assert_eq!(smoothstep(0.), 0.);
+        assert_eq!(smoothstep(0.5), 0.5);
+        assert_eq!(smoothstep(1.), 1.);
+        assert_eq!(smoothstep(2.), 1.);
+    }
+
+    #[test]
+    fn fades_reach_their_target() {
+        let mut fade_in = Fade::fade_in(1.);
+        assert_eq!(fade_in.alpha(), 0.);
+        fade_in.timer.tick(Duration::from_secs(2));
+        assert_eq!(fade_in.alpha(), 1.);
+
+        let mut fade_out = Fade::fade_out(1.);
+        assert_eq!(fade_out.alpha(), 1.);
+        fade_out.timer.tick(Duration::from_secs(2));
+        assert_eq!(fade_out.alpha(), 0.);
+    }
+}
@@ -0,0 +1,149 @@
+//! Displays the colony's idle units and unclaimed jobs, to help answer "why is nothing getting done?"
+//!
+//! Each entry can be focused and activated to select an example of that problem.
+
+use bevy::prelude::*;
+
+use crate::{
+    player_interaction::selection::{CurrentSelection, SelectedTiles},
+    simulation::{backlog::JobBacklog, geometry::TilePos},
+};
+
+use super::{
+    focus::{FocusActivated, Focusable},
+    FiraSansFontFamily, LeftPanel,
+};
+
+/// Initializes and updates the job backlog panel.
+pub(super) struct BacklogPanelPlugin;
+
+impl Plugin for BacklogPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_backlog_panel)
+            .add_system(update_backlog_entries)
+            .add_system(select_activated_backlog_entry);
+    }
+}
+
+/// The UI node that contains all backlog entries.
+#[derive(Component)]
+struct BacklogPanel;
+
+/// A UI node that displays a single line of the backlog.
+#[derive(Component)]
+struct BacklogEntry {
+    /// Where an example of this entry can be found, if there is one
+    example: Option<TilePos>,
+}
+
+/// Backlog entries are visited after the alerts when cycling focus.
+const FIRST_BACKLOG_FOCUS_ORDER: i32 = 2000;
+
+/// The style used for all text in the backlog panel.
+fn backlog_text_style(font_family: &FiraSansFontFamily) -> TextStyle {
+    TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    }
+}
+
+/// Creates the UI elements for the backlog panel.
+fn populate_backlog_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let left_panel = parent_query.single();
+
+    let backlog_panel = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                ..default()
+            },
+            BacklogPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Colony backlog",
+                backlog_text_style(&font_family),
+            ));
+        })
+        .id();
+
+    commands.entity(left_panel).add_child(backlog_panel);
+}
+
+/// Rebuilds the list of backlog entries whenever the [`JobBacklog`] changes.
+fn update_backlog_entries(
+    backlog: Res<JobBacklog>,
+    font_family: Res<FiraSansFontFamily>,
+    panel_query: Query<Entity, With<BacklogPanel>>,
+    entry_query: Query<Entity, With<BacklogEntry>>,
+    mut commands: Commands,
+) {
+    if !backlog.is_changed() {
+        return;
+    }
+
+    for entity in entry_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let backlog_panel = panel_query.single();
+    let text_style = backlog_text_style(&font_family);
+
+    let idle_line = (
+        format!("Idle units: {}", backlog.idle_units()),
+        backlog.idle_example(),
+    );
+    let job_lines = backlog
+        .jobs()
+        .iter()
+        .map(|summary| (summary.to_string(), Some(summary.example)));
+
+    for (index, (text, example)) in std::iter::once(idle_line).chain(job_lines).enumerate() {
+        let mut entry = commands.spawn((
+            TextBundle {
+                text: Text::from_section(text, text_style.clone()),
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            BacklogEntry { example },
+        ));
+
+        // Only entries with an example have anything to jump to
+        if example.is_some() {
+            entry.insert(Focusable {
+                order: FIRST_BACKLOG_FOCUS_ORDER + index as i32,
+            });
+        }
+
+        let entry_entity = entry.id();
+        commands.entity(backlog_panel).add_child(entry_entity);
+    }
+}
+
+/// Selects the example tile of an activated backlog entry.
+fn select_activated_backlog_entry(
+    mut activation_events: EventReader<FocusActivated>,
+    entry_query: Query<&BacklogEntry>,
+    mut current_selection: ResMut<CurrentSelection>,
+) {
+    for event in activation_events.iter() {
+        if let Ok(BacklogEntry {
+            example: Some(tile_pos),
+        }) = entry_query.get(event.entity)
+        {
+            let mut selected_tiles = SelectedTiles::default();
+            selected_tiles.add_tile(*tile_pos);
+            *current_selection = CurrentSelection::Terrain(selected_tiles);
+        }
+    }
+}
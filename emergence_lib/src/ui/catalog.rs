@@ -0,0 +1,326 @@
+//! Searchable catalogs of structures and items.
+//!
+//! A single search box filters both the structures offered in the build menu and the items shown in the stock list.
+
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_input_manager::plugin::ToggleActions;
+
+use crate::{
+    asset_management::manifest::{Id, Item, ItemManifest},
+    player_interaction::PlayerAction,
+    structures::crafting::OutputInventory,
+};
+
+use super::{
+    focus::{FocusActivated, Focusable},
+    FiraSansFontFamily, LeftPanel,
+};
+
+/// The text that the catalogs are currently filtered by.
+#[derive(Resource, Debug, Default)]
+pub(super) struct CatalogSearch {
+    /// The text typed by the player
+    query: String,
+    /// Is the player currently typing into the search box?
+    editing: bool,
+}
+
+impl CatalogSearch {
+    /// Scores how well an entry with the given `name` and `tags` matches the current search.
+    ///
+    /// Returns `None` if the entry should be hidden.
+    pub(super) fn score(&self, name: &str, tags: &[&str]) -> Option<u32> {
+        fuzzy_score(&self.query, name, tags)
+    }
+}
+
+/// The score bonus for each matched character that directly follows the previous match.
+const CONSECUTIVE_BONUS: u32 = 2;
+
+/// The score bonus for each matched character at the start of a word.
+const WORD_START_BONUS: u32 = 3;
+
+/// The score given to entries with a tag that starts with the query.
+const TAG_SCORE: u32 = 1;
+
+/// Scores how well `query` matches an entry with the provided `name` and `tags`, ignoring case.
+///
+/// The name matches if the characters of the query appear in it in order,
+/// with contiguous runs and matches at the start of words scoring higher.
+/// Tags only match if they start with the query.
+/// Empty queries match everything.
+fn fuzzy_score(query: &str, name: &str, tags: &[&str]) -> Option<u32> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // String identifiers use underscores to separate words
+    let name: Vec<char> = name.to_lowercase().replace('_', " ").chars().collect();
+
+    let mut name_score = Some(0);
+    let mut next_position = 0;
+    let mut previous_match: Option<usize> = None;
+    for query_char in query.chars() {
+        match (next_position..name.len()).find(|&i| name[i] == query_char) {
+            Some(found) => {
+                let mut char_score = 1;
+                if found > 0 && previous_match == Some(found - 1) {
+                    char_score += CONSECUTIVE_BONUS;
+                }
+                if found == 0 || name[found - 1] == ' ' {
+                    char_score += WORD_START_BONUS;
+                }
+
+                name_score = name_score.map(|score| score + char_score);
+                previous_match = Some(found);
+                next_position = found + 1;
+            }
+            None => {
+                name_score = None;
+                break;
+            }
+        }
+    }
+
+    let tag_score = tags
+        .iter()
+        .any(|tag| tag.to_lowercase().starts_with(&query))
+        .then_some(TAG_SCORE);
+
+    name_score.max(tag_score)
+}
+
+/// The UI node that displays the search box.
+#[derive(Component)]
+struct SearchBox;
+
+/// The UI node that lists the items in storage.
+#[derive(Component)]
+struct StockList;
+
+/// The focus order of the search box, which comes after menus and alerts.
+const SEARCH_BOX_FOCUS_ORDER: i32 = 2000;
+
+/// Creates the search box and stock list.
+fn populate_catalog_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let left_panel = parent_query.single();
+
+    let catalog_panel = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.9).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style.clone()),
+                    background_color: Color::rgba(0.2, 0.2, 0.2, 1.).into(),
+                    ..default()
+                },
+                Interaction::default(),
+                Focusable {
+                    order: SEARCH_BOX_FOCUS_ORDER,
+                },
+                SearchBox,
+            ));
+
+            parent.spawn((TextBundle::from_section("", text_style), StockList));
+        })
+        .id();
+
+    commands.entity(left_panel).add_child(catalog_panel);
+}
+
+/// Starts typing into the search box when it is clicked or activated.
+///
+/// Other player actions are disabled while typing, so that keystrokes are not treated as commands.
+fn start_search(
+    mut activation_events: EventReader<FocusActivated>,
+    search_box_query: Query<(Entity, &Interaction), With<SearchBox>>,
+    mut catalog_search: ResMut<CatalogSearch>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    let (search_box, interaction) = search_box_query.single();
+    let activated = activation_events
+        .iter()
+        .any(|event| event.entity == search_box);
+
+    if !catalog_search.editing && (activated || *interaction == Interaction::Clicked) {
+        catalog_search.editing = true;
+        toggle_actions.enabled = false;
+    }
+}
+
+/// Edits the search query from typed characters.
+///
+/// Enter finishes the search, while Escape clears it.
+fn type_search(
+    mut character_events: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut catalog_search: ResMut<CatalogSearch>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    if !catalog_search.editing {
+        // Drop any characters typed while not searching
+        character_events.clear();
+        return;
+    }
+
+    for event in character_events.iter() {
+        if !event.char.is_control() {
+            catalog_search.query.push(event.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        catalog_search.query.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        catalog_search.query.clear();
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+        catalog_search.editing = false;
+        toggle_actions.enabled = true;
+    }
+}
+
+/// Shows the current search query.
+fn update_search_box(
+    catalog_search: Res<CatalogSearch>,
+    mut text_query: Query<&mut Text, With<SearchBox>>,
+) {
+    if !catalog_search.is_changed() {
+        return;
+    }
+
+    let mut text = text_query.single_mut();
+    let query = &catalog_search.query;
+    text.sections[0].value = if catalog_search.editing {
+        format!("Search: {query}_")
+    } else if query.is_empty() {
+        "Search...".to_string()
+    } else {
+        format!("Search: {query}")
+    };
+}
+
+/// Lists the stored items that match the current search, along with how many of each are in storage.
+fn update_stock_list(
+    catalog_search: Res<CatalogSearch>,
+    item_manifest: Res<ItemManifest>,
+    storage_query: Query<&OutputInventory>,
+    mut text_query: Query<&mut Text, With<StockList>>,
+) {
+    let mut stock: HashMap<Id<Item>, usize> = HashMap::new();
+    for storage in storage_query.iter() {
+        for slot in storage.iter() {
+            *stock.entry(slot.item_id()).or_default() += slot.count();
+        }
+    }
+
+    let mut entries: Vec<(u32, &str, usize)> = item_manifest
+        .variants()
+        .into_iter()
+        .filter_map(|item_id| {
+            let name = item_id.name().unwrap_or_default();
+            catalog_search.score(name, &[]).map(|score| {
+                (
+                    score,
+                    name,
+                    stock.get(&item_id).copied().unwrap_or_default(),
+                )
+            })
+        })
+        .collect();
+
+    // Best matches first, then alphabetically
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+
+    let mut string = "Stock:".to_string();
+    for (_score, name, count) in entries {
+        string += &format!("\n{name}: {count}");
+    }
+
+    let mut text = text_query.single_mut();
+    if text.sections[0].value != string {
+        text.sections[0].value = string;
+    }
+}
+
+/// Search boxes for the build menu and stock list.
+pub(super) struct CatalogPlugin;
+
+impl Plugin for CatalogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CatalogSearch>()
+            .add_startup_system(populate_catalog_panel)
+            .add_system(start_search)
+            .add_system(type_search.after(start_search))
+            .add_system(update_search_box.after(type_search))
+            .add_system(update_stock_list.after(type_search));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "leuco", &[]), Some(0));
+        assert_eq!(fuzzy_score("  ", "acacia", &[]), Some(0));
+    }
+
+    #[test]
+    fn query_must_be_a_subsequence() {
+        assert!(fuzzy_score("lco", "leuco", &[]).is_some());
+        assert!(fuzzy_score("ocl", "leuco", &[]).is_none());
+        assert!(fuzzy_score("leucos", "leuco", &[]).is_none());
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        assert_eq!(
+            fuzzy_score("LEU", "leuco", &[]),
+            fuzzy_score("leu", "leuco", &[])
+        );
+    }
+
+    #[test]
+    fn contiguous_prefixes_score_higher() {
+        let prefix = fuzzy_score("ac", "acacia_leaf", &[]).unwrap();
+        let scattered = fuzzy_score("af", "acacia_leaf", &[]).unwrap();
+        assert!(prefix > scattered);
+    }
+
+    #[test]
+    fn word_starts_score_higher() {
+        let word_start = fuzzy_score("l", "acacia_leaf", &[]).unwrap();
+        let mid_word = fuzzy_score("c", "acacia_leaf", &[]).unwrap();
+        assert!(word_start > mid_word);
+    }
+
+    #[test]
+    fn tags_match_by_prefix() {
+        assert!(fuzzy_score("wal", "fence", &["wall"]).is_some());
+        assert!(fuzzy_score("all", "fence", &["wall"]).is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! Displays the [`Chronicle`] as a timeline that can be browsed one season at a time.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{player_interaction::PlayerAction, simulation::chronicle::Chronicle};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Initializes and updates the chronicle panel.
+pub(super) struct ChroniclePanelPlugin;
+
+impl Plugin for ChroniclePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChronicleView>()
+            .add_startup_system(populate_chronicle_panel)
+            .add_system(browse_chronicle)
+            .add_system(update_chronicle_panel.after(browse_chronicle));
+    }
+}
+
+/// Which part of the chronicle the player is reading.
+#[derive(Resource, Debug, Default)]
+struct ChronicleView {
+    /// Is the chronicle panel shown?
+    open: bool,
+    /// The number of seasons back from the most recent one
+    seasons_ago: usize,
+}
+
+/// The UI node that displays the chronicle.
+#[derive(Component)]
+struct ChroniclePanel;
+
+/// Creates the UI elements for the chronicle panel, which start hidden.
+fn populate_chronicle_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<RightPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let right_panel = parent_query.single();
+
+    let chronicle_panel = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ChroniclePanel,
+        ))
+        .id();
+
+    commands.entity(right_panel).add_child(chronicle_panel);
+}
+
+/// Opens and closes the chronicle, and moves between seasons.
+fn browse_chronicle(
+    actions: Res<ActionState<PlayerAction>>,
+    chronicle: Res<Chronicle>,
+    mut chronicle_view: ResMut<ChronicleView>,
+) {
+    if actions.just_pressed(PlayerAction::ToggleChronicle) {
+        chronicle_view.open = !chronicle_view.open;
+        // Always open on the most recent season
+        chronicle_view.seasons_ago = 0;
+    }
+
+    if !chronicle_view.open {
+        return;
+    }
+
+    let n_seasons = chronicle.seasons().len();
+    if actions.just_pressed(PlayerAction::ChronicleEarlier)
+        && chronicle_view.seasons_ago + 1 < n_seasons
+    {
+        chronicle_view.seasons_ago += 1;
+    }
+
+    if actions.just_pressed(PlayerAction::ChronicleLater) && chronicle_view.seasons_ago > 0 {
+        chronicle_view.seasons_ago -= 1;
+    }
+}
+
+/// Shows the entries of the season being read.
+fn update_chronicle_panel(
+    chronicle: Res<Chronicle>,
+    chronicle_view: Res<ChronicleView>,
+    mut panel_query: Query<(&mut Text, &mut Visibility), With<ChroniclePanel>>,
+) {
+    if !chronicle.is_changed() && !chronicle_view.is_changed() {
+        return;
+    }
+
+    let (mut text, mut visibility) = panel_query.single_mut();
+
+    if !chronicle_view.open {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+
+    let seasons = chronicle.seasons();
+    let n_seasons = seasons.len();
+    let index = n_seasons.saturating_sub(chronicle_view.seasons_ago + 1);
+
+    text.sections[0].value = match seasons.get(index) {
+        Some(entries) => {
+            let first = &entries[0];
+            let mut string = format!(
+                "Chronicle: {} of year {} ({}/{n_seasons})",
+                first.season,
+                first.year,
+                index + 1
+            );
+            for entry in entries.iter() {
+                string += &format!("\n- {}", entry.text);
+            }
+            string
+        }
+        None => "Chronicle\nNothing has been written yet".to_string(),
+    };
+}
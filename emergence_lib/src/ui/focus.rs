@@ -0,0 +1,148 @@
+//! Moves keyboard and gamepad focus between interactive UI elements.
+//!
+//! Any UI node can opt in to focus navigation by adding a [`Focusable`] component.
+//! Screens then read [`UiFocus`] to find the focused element, and listen for [`FocusActivated`] events to respond to it.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::player_interaction::PlayerAction;
+
+/// A UI element that can be focused with the keyboard or a gamepad.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Focusable {
+    /// Elements are visited in ascending order
+    pub(super) order: i32,
+}
+
+/// The UI element that currently has focus, if any.
+#[derive(Resource, Debug, Default)]
+pub(super) struct UiFocus {
+    /// The focused entity, along with its color before it was highlighted
+    focused: Option<(Entity, BackgroundColor)>,
+}
+
+impl UiFocus {
+    /// The entity that currently has focus, if any.
+    pub(super) fn get(&self) -> Option<Entity> {
+        self.focused.map(|(entity, _)| entity)
+    }
+}
+
+/// Sent when the player activates the focused UI element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct FocusActivated {
+    /// The element that was activated
+    pub(super) entity: Entity,
+}
+
+/// The color used to highlight the focused element.
+const FOCUS_COLOR: Color = Color::rgb(0.3, 0.5, 0.8);
+
+/// Moves focus forwards or backwards through the visible [`Focusable`] elements.
+fn move_focus(
+    actions: Res<ActionState<PlayerAction>>,
+    focusable_query: Query<(Entity, &Focusable, &ComputedVisibility)>,
+    mut color_query: Query<&mut BackgroundColor>,
+    mut ui_focus: ResMut<UiFocus>,
+) {
+    // Forget about elements that have been hidden or despawned
+    if let Some((entity, original_color)) = ui_focus.focused {
+        match focusable_query.get(entity) {
+            Ok((.., visibility)) if visibility.is_visible() => (),
+            _ => {
+                if let Ok(mut color) = color_query.get_mut(entity) {
+                    *color = original_color;
+                }
+                ui_focus.focused = None;
+            }
+        }
+    }
+
+    let step: isize = if actions.just_pressed(PlayerAction::FocusNext) {
+        1
+    } else if actions.just_pressed(PlayerAction::FocusPrevious) {
+        -1
+    } else {
+        return;
+    };
+
+    let mut candidates: Vec<(i32, Entity)> = focusable_query
+        .iter()
+        .filter(|(.., visibility)| visibility.is_visible())
+        .map(|(entity, focusable, _)| (focusable.order, entity))
+        .collect();
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    // Sort by entity as well, so that elements with the same order are visited stably
+    candidates.sort();
+
+    let n = candidates.len() as isize;
+    let next_index = match ui_focus.get() {
+        Some(current) => {
+            let current_index = candidates
+                .iter()
+                .position(|&(_, entity)| entity == current)
+                .unwrap() as isize;
+            (current_index + step).rem_euclid(n)
+        }
+        None if step > 0 => 0,
+        None => n - 1,
+    };
+    let next_entity = candidates[next_index as usize].1;
+
+    if let Some((previous_entity, original_color)) = ui_focus.focused {
+        if let Ok(mut color) = color_query.get_mut(previous_entity) {
+            *color = original_color;
+        }
+    }
+
+    let original_color = match color_query.get(next_entity) {
+        Ok(color) => *color,
+        Err(_) => BackgroundColor::default(),
+    };
+    ui_focus.focused = Some((next_entity, original_color));
+}
+
+/// Highlights the focused element.
+///
+/// This runs every frame, as screens may recolor their elements.
+fn highlight_focus(ui_focus: Res<UiFocus>, mut color_query: Query<&mut BackgroundColor>) {
+    if let Some(entity) = ui_focus.get() {
+        if let Ok(mut color) = color_query.get_mut(entity) {
+            if color.0 != FOCUS_COLOR {
+                *color = BackgroundColor(FOCUS_COLOR);
+            }
+        }
+    }
+}
+
+/// Sends a [`FocusActivated`] event when the player activates the focused element.
+fn activate_focus(
+    actions: Res<ActionState<PlayerAction>>,
+    ui_focus: Res<UiFocus>,
+    mut activation_events: EventWriter<FocusActivated>,
+) {
+    if actions.just_pressed(PlayerAction::ActivateFocus) {
+        if let Some(entity) = ui_focus.get() {
+            activation_events.send(FocusActivated { entity });
+        }
+    }
+}
+
+/// Shared keyboard and gamepad navigation for all menus and panels.
+pub(super) struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFocus>()
+            .add_event::<FocusActivated>()
+            .add_system(move_focus)
+            .add_system(activate_focus.after(move_focus))
+            // Screens recolor their elements during the main update
+            .add_system(highlight_focus.in_base_set(CoreSet::PostUpdate));
+    }
+}
@@ -0,0 +1,161 @@
+//! Displays the [`FamilyTree`] of the colony, centered on the selected organism.
+//!
+//! Select a different member of the colony to browse to their branch of the tree.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    organisms::genealogy::{FamilyTree, LineageId, LineageRecord},
+    player_interaction::{selection::CurrentSelection, PlayerAction},
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// The maximum number of children listed by name.
+const MAX_LISTED_CHILDREN: usize = 5;
+
+/// Initializes and updates the genealogy panel.
+pub(super) struct GenealogyPanelPlugin;
+
+impl Plugin for GenealogyPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_genealogy_panel)
+            .add_system(update_genealogy_panel);
+    }
+}
+
+/// The UI node that displays the family tree.
+#[derive(Component)]
+struct GenealogyPanel;
+
+/// Creates the UI elements for the genealogy panel, which start hidden.
+fn populate_genealogy_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<RightPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let right_panel = parent_query.single();
+
+    let genealogy_panel = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GenealogyPanel,
+        ))
+        .id();
+
+    commands.entity(right_panel).add_child(genealogy_panel);
+}
+
+/// Describes a single member of the family tree, e.g. "Kari (generation 2, days 3-10)".
+fn describe_member(record: &LineageRecord) -> String {
+    let name = record.display_name();
+    let generation = record.generation;
+    let born_on = record.born_on;
+
+    match record.died_on {
+        Some(died_on) => format!("{name} (generation {generation}, days {born_on}-{died_on})"),
+        None => format!("{name} (generation {generation}, born day {born_on})"),
+    }
+}
+
+/// Describes the branch of the tree around `id`: its ancestors and its children.
+fn describe_branch(family_tree: &FamilyTree, id: LineageId) -> String {
+    let record = match family_tree.get(id) {
+        Some(record) => record,
+        None => return "This organism has been forgotten".to_string(),
+    };
+
+    let mut string = describe_member(record);
+
+    let ancestors = family_tree.ancestors(id);
+    if ancestors.is_empty() {
+        string += "\nFounder of its line";
+    }
+    for ancestor in ancestors {
+        // Ancestors are always remembered, as they were just found in the tree
+        let ancestor_record = family_tree.get(ancestor).unwrap();
+        string += &format!("\n  hatched from {}", describe_member(ancestor_record));
+    }
+
+    let children = family_tree.children(id);
+    if !children.is_empty() {
+        let n_alive = children
+            .iter()
+            .filter_map(|&child| family_tree.get(child))
+            .filter(|child| child.died_on.is_none())
+            .count();
+        string += &format!("\nChildren: {} ({n_alive} alive)", children.len());
+
+        for &child in children.iter().rev().take(MAX_LISTED_CHILDREN) {
+            if let Some(child_record) = family_tree.get(child) {
+                string += &format!("\n  {}", describe_member(child_record));
+            }
+        }
+    }
+
+    string
+}
+
+/// Shows or hides the family tree, and keeps it focused on the selected organism.
+fn update_genealogy_panel(
+    actions: Res<ActionState<PlayerAction>>,
+    family_tree: Res<FamilyTree>,
+    current_selection: Res<CurrentSelection>,
+    mut panel_query: Query<(&mut Text, &mut Visibility), With<GenealogyPanel>>,
+) {
+    let (mut text, mut visibility) = panel_query.single_mut();
+
+    if actions.just_pressed(PlayerAction::ToggleGenealogy) {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    } else if !family_tree.is_changed() && !current_selection.is_changed() {
+        return;
+    }
+
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let generations: Vec<String> = family_tree
+        .living_per_generation()
+        .iter()
+        .enumerate()
+        .map(|(generation, n_living)| format!("{generation}: {n_living}"))
+        .collect();
+    let mut string = format!(
+        "Genealogy: {} remembered\nLiving by generation: {}",
+        family_tree.n_records(),
+        generations.join(", ")
+    );
+
+    let selected = match *current_selection {
+        CurrentSelection::Unit(entity) | CurrentSelection::Structure(entity) => {
+            family_tree.living(entity)
+        }
+        _ => None,
+    };
+    string += &match selected {
+        Some(id) => format!("\n\n{}", describe_branch(&family_tree, id)),
+        None => "\n\nSelect a member of the colony to see their family".to_string(),
+    };
+
+    text.sections[0].value = string;
+}
@@ -0,0 +1,228 @@
+//! A row of structures along the bottom of the screen, for quickly picking what to place.
+//!
+//! Picking a structure puts it on the [`Clipboard`], which previews it as a ghost under the cursor.
+//! Previews that cannot be placed are tinted, and placed ghosts are built by units answering their work signals.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::{Id, Structure, StructureManifest},
+    player_interaction::{
+        clipboard::{Clipboard, ClipboardData},
+        PlayerAction,
+    },
+    simulation::geometry::Facing,
+};
+
+use super::{
+    focus::{FocusActivated, Focusable},
+    FiraSansFontFamily,
+};
+
+/// The maximum number of structures shown on the hotbar.
+const HOTBAR_SLOTS: usize = 9;
+
+/// Hotbar slots are visited after the backlog when cycling focus.
+const FIRST_HOTBAR_FOCUS_ORDER: i32 = 3000;
+
+/// The color of the text of the structure on the clipboard.
+const PICKED_TEXT_COLOR: Color = Color::ANTIQUE_WHITE;
+
+/// The color of the text of every other structure.
+const UNPICKED_TEXT_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+/// Initializes and updates the structure hotbar.
+pub(super) struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_hotbar)
+            .add_system(update_hotbar_slots)
+            .add_system(pick_hotbar_slot.after(update_hotbar_slots))
+            .add_system(highlight_picked_slot.after(pick_hotbar_slot));
+    }
+}
+
+/// The UI node that contains every hotbar slot.
+#[derive(Component)]
+struct Hotbar;
+
+/// A button on the hotbar that picks a structure to place.
+#[derive(Component)]
+struct HotbarSlot {
+    /// The structure picked by this slot
+    structure_id: Id<Structure>,
+}
+
+/// Creates the empty hotbar, centered along the bottom of the screen.
+fn populate_hotbar(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.),
+                    left: Val::Percent(25.),
+                    right: Val::Percent(25.),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        Hotbar,
+    ));
+}
+
+/// Fills the hotbar with the first structures in the manifest, in a stable order so muscle memory works.
+fn update_hotbar_slots(
+    structure_manifest: Res<StructureManifest>,
+    font_family: Res<FiraSansFontFamily>,
+    hotbar_query: Query<Entity, With<Hotbar>>,
+    slot_query: Query<Entity, With<HotbarSlot>>,
+    mut commands: Commands,
+) {
+    if !structure_manifest.is_changed() {
+        return;
+    }
+
+    for entity in slot_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mut variants: Vec<Id<Structure>> = structure_manifest.variants().into_iter().collect();
+    variants.sort();
+
+    let hotbar = hotbar_query.single();
+    let text_style = TextStyle {
+        color: UNPICKED_TEXT_COLOR,
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    for (index, structure_id) in variants.into_iter().take(HOTBAR_SLOTS).enumerate() {
+        let slot = commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.)),
+                        margin: UiRect::horizontal(Val::Px(2.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                    ..default()
+                },
+                HotbarSlot { structure_id },
+                Focusable {
+                    order: FIRST_HOTBAR_FOCUS_ORDER + index as i32,
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    structure_id.to_string(),
+                    text_style.clone(),
+                ));
+            })
+            .id();
+
+        commands.entity(hotbar).add_child(slot);
+    }
+}
+
+/// Puts the structure of a clicked or activated slot on the [`Clipboard`].
+///
+/// The player can also step through the slots in order.
+fn pick_hotbar_slot(
+    actions: Res<ActionState<PlayerAction>>,
+    mut activation_events: EventReader<FocusActivated>,
+    slot_query: Query<(Entity, &HotbarSlot, &Interaction)>,
+    structure_manifest: Res<StructureManifest>,
+    mut clipboard: ResMut<Clipboard>,
+) {
+    let activated: Vec<Entity> = activation_events.iter().map(|event| event.entity).collect();
+    let mut picked = slot_query
+        .iter()
+        .find(|(entity, _, interaction)| {
+            activated.contains(entity) || **interaction == Interaction::Clicked
+        })
+        .map(|(_, slot, _)| slot.structure_id);
+
+    let step: isize = if actions.just_pressed(PlayerAction::NextHotbarSlot) {
+        1
+    } else if actions.just_pressed(PlayerAction::PreviousHotbarSlot) {
+        -1
+    } else {
+        0
+    };
+
+    if step != 0 {
+        let mut slots: Vec<Id<Structure>> = slot_query
+            .iter()
+            .map(|(_, slot, _)| slot.structure_id)
+            .collect();
+        slots.sort();
+
+        if !slots.is_empty() {
+            let n = slots.len() as isize;
+            let current_index = clipboard
+                .values()
+                .next()
+                .and_then(|data| slots.iter().position(|&id| id == data.structure_id));
+            let next_index = match current_index {
+                Some(index) => (index as isize + step).rem_euclid(n),
+                None if step > 0 => 0,
+                None => n - 1,
+            };
+            picked = Some(slots[next_index as usize]);
+        }
+    }
+
+    if let Some(structure_id) = picked {
+        let already_picked = clipboard.len() == 1
+            && clipboard.values().next().map(|data| data.structure_id) == Some(structure_id);
+
+        // Picking the same structure again would throw away its facing and recipe
+        if !already_picked {
+            clipboard.set(Some(ClipboardData {
+                structure_id,
+                facing: Facing::default(),
+                active_recipe: structure_manifest
+                    .get(structure_id)
+                    .starting_recipe()
+                    .clone(),
+            }));
+        }
+    }
+}
+
+/// Brightens the label of the slot whose structure is on the [`Clipboard`].
+fn highlight_picked_slot(
+    clipboard: Res<Clipboard>,
+    slot_query: Query<(&HotbarSlot, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !clipboard.is_changed() {
+        return;
+    }
+
+    // Only a single copied structure corresponds to a slot
+    let picked = match clipboard.len() {
+        1 => clipboard.values().next().map(|data| data.structure_id),
+        _ => None,
+    };
+
+    for (slot, children) in slot_query.iter() {
+        let color = match picked == Some(slot.structure_id) {
+            true => PICKED_TEXT_COLOR,
+            false => UNPICKED_TEXT_COLOR,
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
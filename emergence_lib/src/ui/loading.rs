@@ -0,0 +1,127 @@
+//! Shows a progress bar while the game's assets are loading.
+
+use bevy::prelude::*;
+
+use crate::asset_management::{AssetState, LoadingProgress};
+
+use super::FiraSansFontFamily;
+
+/// Displays the loading screen until every asset is ready.
+pub(super) struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_loading_screen.in_schedule(OnEnter(AssetState::Loading)))
+            .add_system(update_loading_screen.run_if(in_state(AssetState::Loading)))
+            .add_system(despawn_loading_screen.in_schedule(OnExit(AssetState::Loading)));
+    }
+}
+
+/// The root node of the loading screen.
+#[derive(Component)]
+struct LoadingScreen;
+
+/// The filled portion of the progress bar.
+#[derive(Component)]
+struct LoadingBar;
+
+/// The text describing the progress of each collection of assets.
+#[derive(Component)]
+struct LoadingText;
+
+/// Creates a full-screen overlay containing the progress bar.
+fn spawn_loading_screen(mut commands: Commands, font_family: Res<FiraSansFontFamily>) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 20.,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.05, 0.05, 0.08).into(),
+                // Draw over the rest of the UI
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(400.), Val::Px(20.)),
+                        margin: UiRect::all(Val::Px(10.)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::rgb(0.4, 0.7, 0.3).into(),
+                            ..default()
+                        },
+                        LoadingBar,
+                    ));
+                });
+
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("Loading...", text_style)
+                        .with_alignment(TextAlignment::Center),
+                    ..default()
+                },
+                LoadingText,
+            ));
+        });
+}
+
+/// Fills the progress bar, and lists how far along each collection of assets is.
+fn update_loading_screen(
+    loading_progress: Res<LoadingProgress>,
+    mut bar_query: Query<&mut Style, With<LoadingBar>>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+) {
+    if !loading_progress.is_changed() {
+        return;
+    }
+
+    let total = loading_progress.total();
+    for mut style in bar_query.iter_mut() {
+        style.size.width = Val::Percent(total.fraction() * 100.);
+    }
+
+    let mut string = format!("Loading... {total}");
+    for (name, progress) in loading_progress.layers() {
+        string += &format!("\n{name}: {progress}");
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = string.clone();
+    }
+}
+
+/// Removes the loading screen once the game is ready.
+fn despawn_loading_screen(
+    mut commands: Commands,
+    loading_screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in loading_screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,10 +1,39 @@
 //! Creates the UI from all modules.
 //!
-use crate::ui::{select_structure::SelectStructurePlugin, selection_panel::HoverDetailsPlugin};
+use crate::ui::{
+    alerts::AlertsPanelPlugin,
+    animation::UiAnimationPlugin,
+    backlog::BacklogPanelPlugin,
+    catalog::CatalogPlugin,
+    chronicle::ChroniclePanelPlugin,
+    focus::FocusPlugin,
+    genealogy::GenealogyPanelPlugin,
+    hotbar::HotbarPlugin,
+    loading::LoadingScreenPlugin,
+    overlay::OverlayLegendPlugin,
+    population::PopulationPanelPlugin,
+    ruler::RulerPanelPlugin,
+    scaling::{ResponsivePanel, UiScalingPlugin},
+    select_structure::SelectStructurePlugin,
+    selection_panel::HoverDetailsPlugin,
+};
 use bevy::prelude::*;
 use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
 
+mod alerts;
+mod animation;
+mod backlog;
+mod catalog;
+mod chronicle;
+mod focus;
+mod genealogy;
+mod hotbar;
 mod intent;
+mod loading;
+mod overlay;
+mod population;
+mod ruler;
+pub mod scaling;
 mod select_structure;
 mod selection_panel;
 
@@ -30,10 +59,23 @@ impl Plugin for UiPlugin {
             regular: asset_server.load("fonts/FiraSans-Medium.ttf"),
         })
         .add_startup_system(setup_ui.in_base_set(StartupSet::PreStartup))
+        .add_plugin(UiScalingPlugin)
+        .add_plugin(FocusPlugin)
         .add_plugin(ScreenDiagnosticsPlugin::default())
         .add_plugin(ScreenFrameDiagnosticsPlugin)
         .add_plugin(HoverDetailsPlugin)
-        .add_plugin(SelectStructurePlugin);
+        .add_plugin(SelectStructurePlugin)
+        .add_plugin(AlertsPanelPlugin)
+        .add_plugin(BacklogPanelPlugin)
+        .add_plugin(CatalogPlugin)
+        .add_plugin(ChroniclePanelPlugin)
+        .add_plugin(HotbarPlugin)
+        .add_plugin(GenealogyPanelPlugin)
+        .add_plugin(PopulationPanelPlugin)
+        .add_plugin(RulerPanelPlugin)
+        .add_plugin(OverlayLegendPlugin)
+        .add_plugin(UiAnimationPlugin)
+        .add_plugin(LoadingScreenPlugin);
     }
 }
 
@@ -69,6 +111,7 @@ fn setup_ui(mut commands: Commands) {
                     ..default()
                 },
                 LeftPanel,
+                ResponsivePanel { base_width: 200. },
             ));
 
             // UI panel on the right side
@@ -82,6 +125,7 @@ fn setup_ui(mut commands: Commands) {
                     ..default()
                 },
                 RightPanel,
+                ResponsivePanel { base_width: 400. },
             ));
         });
 }
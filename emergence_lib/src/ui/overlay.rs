@@ -0,0 +1,75 @@
+//! Labels the signal overlay, so players know which signal they are looking at.
+
+use bevy::prelude::*;
+
+use crate::player_interaction::overlay::SignalOverlay;
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Initializes and updates the signal overlay legend.
+pub(super) struct OverlayLegendPlugin;
+
+impl Plugin for OverlayLegendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_overlay_legend)
+            .add_system(update_overlay_legend);
+    }
+}
+
+/// The UI node that names the overlaid signal.
+#[derive(Component)]
+struct OverlayLegend;
+
+/// Creates the UI elements for the overlay legend, which start hidden.
+fn populate_overlay_legend(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let left_panel = parent_query.single();
+
+    let overlay_legend = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            OverlayLegend,
+        ))
+        .id();
+
+    commands.entity(left_panel).add_child(overlay_legend);
+}
+
+/// Shows the name of the overlaid signal, and hides the legend when there is no overlay.
+fn update_overlay_legend(
+    signal_overlay: Res<SignalOverlay>,
+    mut legend_query: Query<(&mut Text, &mut Visibility), With<OverlayLegend>>,
+) {
+    if !signal_overlay.is_changed() {
+        return;
+    }
+
+    let (mut text, mut visibility) = legend_query.single_mut();
+
+    match signal_overlay.signal_type() {
+        Some(signal_type) => {
+            *visibility = Visibility::Inherited;
+            text.sections[0].value =
+                format!("Signal overlay: {signal_type}\nBlue is weak, red is strong");
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
@@ -0,0 +1,71 @@
+//! Displays the [`PopulationCensus`], so players can see how many of each organism are alive.
+
+use bevy::prelude::*;
+
+use crate::organisms::population::PopulationCensus;
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Initializes and updates the population panel.
+pub(super) struct PopulationPanelPlugin;
+
+impl Plugin for PopulationPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_population_panel)
+            .add_system(update_population_panel);
+    }
+}
+
+/// The UI node that displays the population.
+#[derive(Component)]
+struct PopulationPanel;
+
+/// Creates the UI elements for the population panel.
+fn populate_population_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let left_panel = parent_query.single();
+
+    let population_panel = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                ..default()
+            },
+            PopulationPanel,
+        ))
+        .id();
+
+    commands.entity(left_panel).add_child(population_panel);
+}
+
+/// Lists the most numerous organisms first whenever the [`PopulationCensus`] changes.
+fn update_population_panel(
+    census: Res<PopulationCensus>,
+    mut panel_query: Query<&mut Text, With<PopulationPanel>>,
+) {
+    if !census.is_changed() {
+        return;
+    }
+
+    let mut text = panel_query.single_mut();
+    let mut value = format!("Population: {}", census.total());
+    for (name, count) in census.most_numerous() {
+        value += &format!("\n{name}: {count}");
+    }
+
+    text.sections[0].value = value;
+}
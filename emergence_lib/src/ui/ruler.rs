@@ -0,0 +1,112 @@
+//! Displays the distances measured by the ruler.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::{Id, Unit},
+    player_interaction::{ruler::Ruler, selection::CurrentSelection},
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Initializes and updates the ruler panel.
+pub(super) struct RulerPanelPlugin;
+
+impl Plugin for RulerPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(populate_ruler_panel)
+            .add_system(update_ruler_panel);
+    }
+}
+
+/// The UI node that displays the measurement.
+#[derive(Component)]
+struct RulerPanel;
+
+/// Creates the UI elements for the ruler panel, which start hidden.
+fn populate_ruler_panel(
+    mut commands: Commands,
+    font_family: Res<FiraSansFontFamily>,
+    parent_query: Query<Entity, With<LeftPanel>>,
+) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.9, 0.9, 0.9),
+        font: font_family.regular.clone_weak(),
+        font_size: 16.,
+    };
+
+    let left_panel = parent_query.single();
+
+    let ruler_panel = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style),
+                style: Style {
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            RulerPanel,
+        ))
+        .id();
+
+    commands.entity(left_panel).add_child(ruler_panel);
+}
+
+/// Shows the hex distance, path distance and travel times of the current measurement.
+///
+/// If a unit is selected, only the travel time for its species is shown.
+fn update_ruler_panel(
+    ruler: Res<Ruler>,
+    current_selection: Res<CurrentSelection>,
+    unit_query: Query<&Id<Unit>>,
+    mut panel_query: Query<(&mut Text, &mut Visibility), With<RulerPanel>>,
+) {
+    if !ruler.is_changed() && !current_selection.is_changed() {
+        return;
+    }
+
+    let (mut text, mut visibility) = panel_query.single_mut();
+
+    let start = match ruler.start() {
+        Some(start) => start,
+        None => {
+            *visibility = Visibility::Hidden;
+            return;
+        }
+    };
+
+    *visibility = Visibility::Inherited;
+
+    let mut string = format!("Ruler\nFrom: {start}");
+    match (ruler.end(), ruler.measurement()) {
+        (Some(end), Some(measurement)) => {
+            string += &format!("\nTo: {end}\nHex distance: {}", measurement.hex_distance);
+
+            match &measurement.path {
+                Some(path) => {
+                    string += &format!("\nPath distance: {}", path.steps());
+
+                    let selected_species = match *current_selection {
+                        CurrentSelection::Unit(entity) => unit_query.get(entity).ok().copied(),
+                        _ => None,
+                    };
+
+                    for (unit_id, seconds) in &measurement.travel_times {
+                        if selected_species.is_none_or(|species| species == *unit_id) {
+                            let name = unit_id.name().unwrap_or_default();
+                            string += &format!("\n{name}: {seconds:.1} s");
+                        }
+                    }
+                }
+                None => string += "\nNo walkable path",
+            }
+        }
+        _ => string += "\nPress again to place the end",
+    }
+
+    text.sections[0].value = string;
+}
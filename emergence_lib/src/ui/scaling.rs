@@ -0,0 +1,176 @@
+//! Scales the UI so that it stays legible across screen resolutions and pixel densities.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+/// Player-controlled settings for the size of the UI.
+///
+/// Insert this resource before adding the [`UiPlugin`](super::UiPlugin) to customize it for a game.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct UiSettings {
+    /// A multiplier applied on top of the automatically chosen scale.
+    pub scale: f32,
+    /// Enlarges the UI so that its smallest text stays readable on small, dense screens such as handhelds.
+    pub minimum_readability: bool,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        UiSettings {
+            scale: 1.0,
+            minimum_readability: false,
+        }
+    }
+}
+
+impl UiSettings {
+    /// The window height, in logical pixels, that the UI was laid out for.
+    const REFERENCE_HEIGHT: f32 = 1080.;
+
+    /// The smallest automatic scale, used for very small windows.
+    const MIN_AUTOMATIC_SCALE: f32 = 0.75;
+
+    /// The largest automatic scale, used for very large windows.
+    const MAX_AUTOMATIC_SCALE: f32 = 2.0;
+
+    /// The smallest font size used anywhere in the UI, in logical pixels.
+    const SMALLEST_FONT_SIZE: f32 = 16.;
+
+    /// The smallest size, in physical pixels, that text is drawn at in minimum readability mode.
+    const MIN_READABLE_FONT_SIZE: f32 = 28.;
+
+    /// Computes the scale of the UI for a window of the given logical height and scale factor.
+    ///
+    /// The operating system's scale factor already converts logical pixels to physical ones,
+    /// so this only corrects for the size of the window itself.
+    pub(crate) fn effective_scale(&self, window_height: f32, window_scale_factor: f32) -> f32 {
+        let automatic_scale = (window_height / UiSettings::REFERENCE_HEIGHT).clamp(
+            UiSettings::MIN_AUTOMATIC_SCALE,
+            UiSettings::MAX_AUTOMATIC_SCALE,
+        );
+        let scale = automatic_scale * self.scale;
+
+        if self.minimum_readability {
+            let readable_scale = UiSettings::MIN_READABLE_FONT_SIZE
+                / (UiSettings::SMALLEST_FONT_SIZE * window_scale_factor);
+            scale.max(readable_scale)
+        } else {
+            scale
+        }
+    }
+}
+
+/// The largest fraction of the window's width that the side panels can take up, together.
+const MAX_PANEL_WIDTH_FRACTION: f32 = 0.6;
+
+/// A side panel whose width should shrink to fit on narrow screens.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub(super) struct ResponsivePanel {
+    /// The width of this panel at a scale of 1, in logical pixels
+    pub(super) base_width: f32,
+}
+
+/// Sets the [`UiScale`] from the [`UiSettings`] and the size of the primary window.
+fn update_ui_scale(
+    ui_settings: Res<UiSettings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if let Ok(window) = window_query.get_single() {
+        let scale =
+            ui_settings.effective_scale(window.height(), window.scale_factor() as f32) as f64;
+
+        // Avoid triggering change detection every frame
+        if ui_scale.scale != scale {
+            ui_scale.scale = scale;
+        }
+    }
+}
+
+/// Narrows the side panels when they would otherwise cover too much of the window.
+fn fit_panels_to_window(
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut panel_query: Query<(&ResponsivePanel, &mut Style)>,
+) {
+    if let Ok(window) = window_query.get_single() {
+        let scale = ui_scale.scale as f32;
+        let total_width: f32 = panel_query
+            .iter()
+            .map(|(panel, _)| panel.base_width * scale)
+            .sum();
+        let max_width = window.width() * MAX_PANEL_WIDTH_FRACTION;
+        let shrink = if total_width > max_width {
+            max_width / total_width
+        } else {
+            1.
+        };
+
+        for (panel, mut style) in panel_query.iter_mut() {
+            // Widths in pixels are multiplied by the UI scale when laid out
+            let width = Val::Px(panel.base_width * shrink);
+            if style.size.width != width {
+                style.size.width = width;
+            }
+        }
+    }
+}
+
+/// Keeps the UI at a readable size.
+pub(super) struct UiScalingPlugin;
+
+impl Plugin for UiScalingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiSettings>()
+            .add_system(update_ui_scale)
+            .add_system(fit_panels_to_window.after(update_ui_scale));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UiSettings;
+
+    #[test]
+    fn reference_window_is_unscaled() {
+        let settings = UiSettings::default();
+        assert_eq!(
+            settings.effective_scale(UiSettings::REFERENCE_HEIGHT, 1.),
+            1.
+        );
+    }
+
+    #[test]
+    fn user_scale_multiplies_automatic_scale() {
+        let settings = UiSettings {
+            scale: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.effective_scale(UiSettings::REFERENCE_HEIGHT * 2., 1.),
+            3.
+        );
+    }
+
+    #[test]
+    fn automatic_scale_is_clamped() {
+        let settings = UiSettings::default();
+        assert_eq!(
+            settings.effective_scale(1., 1.),
+            UiSettings::MIN_AUTOMATIC_SCALE
+        );
+        assert_eq!(
+            settings.effective_scale(100_000., 1.),
+            UiSettings::MAX_AUTOMATIC_SCALE
+        );
+    }
+
+    #[test]
+    fn minimum_readability_enlarges_small_text() {
+        let settings = UiSettings {
+            minimum_readability: true,
+            ..Default::default()
+        };
+        let scale = settings.effective_scale(400., 1.);
+        assert!(scale * UiSettings::SMALLEST_FONT_SIZE >= UiSettings::MIN_READABLE_FONT_SIZE);
+    }
+}
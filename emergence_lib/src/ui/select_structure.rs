@@ -5,13 +5,18 @@ use hexx::{Hex, HexLayout, HexOrientation};
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
-    asset_management::manifest::{Id, Structure, StructureManifest},
-    player_interaction::{
-        clipboard::{Clipboard, ClipboardData},
-        cursor::CursorPos,
-        PlayerAction,
+    asset_management::{
+        manifest::{Id, Structure, StructureManifest},
+        structures::StructureHandles,
     },
+    player_interaction::{clipboard::Clipboard, cursor::CursorPos, PlayerAction},
     simulation::geometry::Facing,
+    structures::ClipboardData,
+};
+
+use super::{
+    catalog::CatalogSearch,
+    focus::{Focusable, UiFocus},
 };
 
 /// Hex menu and selection modifying logic.
@@ -89,10 +94,15 @@ fn spawn_hex_menu(
     actions: Res<ActionState<PlayerAction>>,
     cursor_pos: Res<CursorPos>,
     structure_manifest: Res<StructureManifest>,
+    structure_handles: Res<StructureHandles>,
+    catalog_search: Res<CatalogSearch>,
+    ui_scale: Res<UiScale>,
 ) {
-    /// The size of the hexes used in this menu.
+    /// The size of the hexes used in this menu, before UI scaling.
     const HEX_SIZE: f32 = 64.0;
 
+    let ui_scale = ui_scale.scale as f32;
+
     if actions.just_pressed(PlayerAction::SelectStructure) {
         if let Some(cursor_pos) = cursor_pos.maybe_screen_pos() {
             let mut arrangement = HexMenuArrangement {
@@ -101,9 +111,10 @@ fn spawn_hex_menu(
                 layout: HexLayout {
                     orientation: HexOrientation::pointy(),
                     origin: cursor_pos,
+                    // The layout is in screen space, so must be scaled to match the icons
                     hex_size: Vec2 {
-                        x: HEX_SIZE,
-                        y: HEX_SIZE,
+                        x: HEX_SIZE * ui_scale,
+                        y: HEX_SIZE * ui_scale,
                     },
                 },
             };
@@ -115,12 +126,20 @@ fn spawn_hex_menu(
             let mut hexes =
                 Hex::ZERO.custom_spiral_range(1..range, hexx::Direction::BottomRight, true);
 
-            let mut variants: Vec<Id<Structure>> =
-                Vec::from_iter(structure_manifest.variants().into_iter());
+            // Only offer the structures that match the player's search
+            let mut variants: Vec<Id<Structure>> = structure_manifest
+                .variants()
+                .into_iter()
+                .filter(|structure_id| {
+                    let name = structure_id.name().unwrap_or_default();
+                    let tags = structure_manifest.get(*structure_id).tags();
+                    catalog_search.score(name, &tags).is_some()
+                })
+                .collect();
             // We want a stable order so muscle memory works effectively
             variants.sort();
 
-            for structure_id in variants {
+            for (index, structure_id) in variants.into_iter().enumerate() {
                 // Just give up rather than panic if too many entities are found
                 if let Some(hex) = hexes.next() {
                     arrangement.content_map.insert(hex, structure_id);
@@ -128,8 +147,10 @@ fn spawn_hex_menu(
                         .spawn(HexMenuIconBundle::new(
                             structure_id,
                             hex,
-                            &structure_manifest,
+                            index as i32,
+                            &structure_handles,
                             &arrangement.layout,
+                            ui_scale,
                         ))
                         .id();
                     arrangement.icon_map.insert(hex, icon_entity);
@@ -152,23 +173,30 @@ struct HexMenuIconBundle {
     image_bundle: ImageBundle,
     /// The corresponding `Id<Structure>`
     structure_id: Id<Structure>,
+    /// Allows the icon to be chosen without a cursor
+    focusable: Focusable,
 }
 
 impl HexMenuIconBundle {
     /// Create a new icon with the appropriate positioning and appearance.
+    ///
+    /// UI nodes are multiplied by the `ui_scale` when laid out, so screen space positions must be divided by it.
     fn new(
         structure_id: Id<Structure>,
         hex: Hex,
-        structure_manifest: &StructureManifest,
+        order: i32,
+        structure_handles: &StructureHandles,
         layout: &HexLayout,
+        ui_scale: f32,
     ) -> Self {
-        let color = structure_manifest.get(structure_id).color;
+        let color = structure_handles.icon_color(structure_id);
         // Correct for center vs corner positioning
         let half_cell = Vec2 {
             x: layout.hex_size.x / 2.,
             y: layout.hex_size.y / 2.,
         };
-        let screen_pos: Vec2 = layout.hex_to_world_pos(hex) - half_cell;
+        let screen_pos: Vec2 = (layout.hex_to_world_pos(hex) - half_cell) / ui_scale;
+        let size: Vec2 = layout.hex_size / ui_scale;
 
         let image_bundle = ImageBundle {
             background_color: BackgroundColor(color),
@@ -179,7 +207,7 @@ impl HexMenuIconBundle {
                     ..Default::default()
                 },
                 position_type: PositionType::Absolute,
-                size: Size::new(Val::Px(layout.hex_size.x), Val::Px(layout.hex_size.y)),
+                size: Size::new(Val::Px(size.x), Val::Px(size.y)),
                 ..Default::default()
             },
             ..Default::default()
@@ -189,19 +217,35 @@ impl HexMenuIconBundle {
             hex_menu: HexMenu,
             image_bundle,
             structure_id,
+            focusable: Focusable { order },
         }
     }
 }
 
 /// Select a hexagon from the hex menu.
+///
+/// The icon under the cursor is preferred, falling back to the focused icon.
 fn select_hex(
     cursor_pos: Res<CursorPos>,
     hex_menu_arrangement: Option<Res<HexMenuArrangement>>,
     actions: Res<ActionState<PlayerAction>>,
+    ui_focus: Res<UiFocus>,
+    icon_query: Query<&Id<Structure>, With<HexMenu>>,
 ) -> Result<HexMenuData, HexMenuError> {
     if let Some(arrangement) = hex_menu_arrangement {
         let complete = actions.released(PlayerAction::SelectStructure);
 
+        let maybe_focused = ui_focus.get().and_then(|icon_entity| {
+            icon_query
+                .get(icon_entity)
+                .ok()
+                .map(|&structure_id| HexMenuData {
+                    structure_id,
+                    icon_entity,
+                    complete,
+                })
+        });
+
         if let Some(cursor_pos) = cursor_pos.maybe_screen_pos() {
             let maybe_item = arrangement.get_item(cursor_pos);
             let maybe_icon_entity = arrangement.get_icon(cursor_pos);
@@ -214,11 +258,11 @@ fn select_hex(
                 })
             } else {
                 // Nothing found on lookup
-                Err(HexMenuError::NoSelection { complete })
+                maybe_focused.ok_or(HexMenuError::NoSelection { complete })
             }
         } else {
             // No cursor
-            Err(HexMenuError::NoSelection { complete })
+            maybe_focused.ok_or(HexMenuError::NoSelection { complete })
         }
     } else {
         // No menu exists
@@ -233,6 +277,7 @@ fn handle_selection(
     menu_query: Query<Entity, With<HexMenu>>,
     mut icon_query: Query<(Entity, &Id<Structure>, &mut BackgroundColor), With<HexMenu>>,
     structure_manifest: Res<StructureManifest>,
+    structure_handles: Res<StructureHandles>,
     commands: Commands,
 ) {
     /// Clean up the menu when we are done with it
@@ -263,7 +308,7 @@ fn handle_selection(
                     if icon_entity == data.icon_entity {
                         *icon_color = BackgroundColor(Color::ANTIQUE_WHITE);
                     } else {
-                        *icon_color = BackgroundColor(structure_manifest.get(structure_id).color);
+                        *icon_color = BackgroundColor(structure_handles.icon_color(structure_id));
                     }
                 }
             }
@@ -275,7 +320,7 @@ fn handle_selection(
                 cleanup(commands, menu_query);
             } else {
                 for (_icon_entity, &structure_id, mut icon_color) in icon_query.iter_mut() {
-                    *icon_color = BackgroundColor(structure_manifest.get(structure_id).color);
+                    *icon_color = BackgroundColor(structure_handles.icon_color(structure_id));
                 }
             }
         }
@@ -2,7 +2,9 @@
 
 use bevy::prelude::*;
 
-use crate::player_interaction::{selection::SelectionDetails, InteractionSystem};
+use crate::player_interaction::{
+    naming::NameEditor, selection::SelectionDetails, InteractionSystem,
+};
 
 use super::{FiraSansFontFamily, RightPanel};
 
@@ -85,6 +87,7 @@ fn populate_hover_panel(
 /// Updates UI elements for hover details based on new information.
 fn update_hover_details(
     selection_details: Res<SelectionDetails>,
+    name_editor: Res<NameEditor>,
     mut hover_panel_query: Query<&mut Visibility, With<HoverPanel>>,
     mut ghost_details_query: Query<
         (&mut Style, &mut Text),
@@ -164,18 +167,24 @@ fn update_hover_details(
         }
     }
 
+    // Show the name as it is typed, so the player can see what they are doing
+    let renaming = match name_editor.editing() {
+        Some(name) => format!("\nRenaming: {name}_"),
+        None => String::new(),
+    };
+
     match &*selection_details {
         SelectionDetails::Ghost(details) => {
             ghost_text.sections[0].value = format!("{details}");
         }
         SelectionDetails::Structure(details) => {
-            structure_text.sections[0].value = format!("{details}");
+            structure_text.sections[0].value = format!("{details}{renaming}");
         }
         SelectionDetails::Terrain(details) => {
             terrain_text.sections[0].value = format!("{details}");
         }
         SelectionDetails::Unit(details) => {
-            unit_text.sections[0].value = format!("{details}");
+            unit_text.sections[0].value = format!("{details}{renaming}");
         }
         SelectionDetails::None => (),
     };
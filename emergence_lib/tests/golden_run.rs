@@ -0,0 +1,71 @@
+//! Runs a fixed scenario headlessly and compares the result against a committed golden checksum.
+//!
+//! This catches accidental changes to the behavior of the simulation, e.g. from refactors.
+//! When the rules of the simulation are changed deliberately, regenerate the golden file by running
+//! `EMERGENCE_UPDATE_GOLDEN=1 cargo test --test golden_run` and commit the result.
+//!
+//! Time is advanced by a fixed amount each tick, and all randomness is seeded from the world's [`GenerationConfig`],
+//! so every run of the scenario should end in exactly the same state.
+
+use emergence_lib::simulation::generation::GenerationConfig;
+use emergence_lib::simulation::snapshot::Snapshot;
+use emergence_lib::testing::{run_ticks, simulation_app};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The number of ticks that the scenario is run for.
+const GOLDEN_TICKS: usize = 240;
+
+/// The length of each tick, so that the result does not depend on the speed of the machine.
+const TICK_DURATION: Duration = Duration::from_millis(16);
+
+/// Set this environment variable to overwrite the golden file with the current result.
+const UPDATE_GOLDEN_VAR: &str = "EMERGENCE_UPDATE_GOLDEN";
+
+/// The file that stores the expected result of the scenario.
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/golden_run.txt")
+}
+
+/// Runs the golden scenario, returning a snapshot of the final state.
+fn run_scenario() -> Snapshot {
+    let mut app = simulation_app(GenerationConfig::default());
+    run_ticks(&mut app, GOLDEN_TICKS, TICK_DURATION);
+
+    Snapshot::capture(&mut app.world)
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let first = run_scenario();
+    let second = run_scenario();
+
+    assert_eq!(first.checksum(), second.checksum());
+}
+
+#[test]
+fn golden_run_matches() {
+    let snapshot = run_scenario();
+    let actual = format!("ticks {GOLDEN_TICKS}\nchecksum {}\n", snapshot.checksum());
+    let path = golden_path();
+
+    if std::env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "No golden file found at {}. Run with {UPDATE_GOLDEN_VAR}=1 to create it.",
+            path.display()
+        )
+    });
+
+    assert_eq!(
+        expected, actual,
+        "The simulation no longer matches the golden run.\n\
+        If this change was deliberate, rerun with {UPDATE_GOLDEN_VAR}=1 and commit the new golden file.\n\
+        Final state:\n{snapshot}"
+    );
+}
@@ -0,0 +1,170 @@
+//! Saves a running simulation, loads it into a fresh one, and checks that the world survived the trip.
+//! Also rewinds a simulation to an in-memory checkpoint.
+
+use emergence_lib::asset_management::manifest::{Id, Recipe, Structure};
+use emergence_lib::simulation::generation::GenerationConfig;
+use emergence_lib::simulation::geometry::{MapGeometry, TilePos};
+use emergence_lib::simulation::save::{
+    restore_snapshot, save_snapshot, Checkpoint, LoadGame, SaveGame, SavedRemains, SavedWorld,
+};
+use emergence_lib::terrain::Terrain;
+use emergence_lib::testing::{run_ticks, simulation_app};
+use hexx::Direction;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// The number of ticks to run before saving.
+const TICKS_BEFORE_SAVE: usize = 60;
+
+/// The length of each tick, so that saves are taken at the same moment on every machine.
+const TICK_DURATION: Duration = Duration::from_millis(16);
+
+/// The parts of a save that should not change within a single tick: the seed, the terrain, and what stands where.
+///
+/// Any other fields in the save are skipped when reading it.
+#[derive(Debug, PartialEq, Deserialize)]
+struct StaticRecords {
+    /// The seed of the world
+    seed: u64,
+    /// Every tile of terrain
+    tiles: Vec<StaticTile>,
+    /// Every structure, without its crafting progress or energy, as those change every tick
+    structures: Vec<StaticStructure>,
+}
+
+/// A tile of terrain in [`StaticRecords`].
+#[derive(Debug, PartialEq, Deserialize)]
+struct StaticTile {
+    /// The position of the tile
+    tile_pos: TilePos,
+    /// The type of terrain
+    terrain: Terrain,
+    /// The height of the terrain
+    height: f32,
+}
+
+/// A structure in [`StaticRecords`].
+#[derive(Debug, PartialEq, Deserialize)]
+struct StaticStructure {
+    /// The position of the structure
+    tile_pos: TilePos,
+    /// The type of structure
+    structure_id: Id<Structure>,
+    /// The direction the structure faces
+    facing: Direction,
+    /// The recipe being crafted, if any
+    active_recipe: Option<Id<Recipe>>,
+}
+
+/// A save file, read as [`StaticRecords`].
+#[derive(Debug, PartialEq, Deserialize)]
+struct StaticSaveFile {
+    /// The static parts of the saved world
+    world: StaticRecords,
+}
+
+/// Reads the [`StaticRecords`] of a `saved_world` back out of its save file.
+fn static_records(saved_world: &SavedWorld) -> StaticRecords {
+    let save_file: StaticSaveFile = ron::from_str(&saved_world.to_string()).unwrap();
+    save_file.world
+}
+
+#[test]
+fn saves_round_trip_exactly() {
+    let mut original = simulation_app(GenerationConfig::default());
+    run_ticks(&mut original, TICKS_BEFORE_SAVE, TICK_DURATION);
+    let saved = SavedWorld::capture(&mut original.world);
+    let text = saved.to_string();
+
+    // A different seed, so that restoring the saved seed is checked too
+    let mut loaded = simulation_app(GenerationConfig::default().with_seed(1));
+    run_ticks(&mut loaded, 1, TICK_DURATION);
+    let parsed: SavedWorld = text.parse().unwrap();
+    restore_snapshot(&mut loaded.world, &parsed.into());
+    let restored = SavedWorld::capture(&mut loaded.world);
+
+    assert_eq!(restored.to_string(), text);
+}
+
+#[test]
+fn saved_games_can_be_loaded() {
+    let path = std::env::temp_dir().join("emergence_save_load_test.ron");
+
+    let mut original = simulation_app(GenerationConfig::default());
+    run_ticks(&mut original, TICKS_BEFORE_SAVE, TICK_DURATION);
+    original.world.send_event(SaveGame { path: path.clone() });
+    run_ticks(&mut original, 1, TICK_DURATION);
+    let saved = SavedWorld::capture(&mut original.world);
+
+    // Saving happens in the background, so wait until the whole file has been written
+    let mut attempts = 0;
+    while std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| contents.parse::<SavedWorld>().ok())
+        .is_none()
+        && attempts < 100
+    {
+        std::thread::sleep(Duration::from_millis(10));
+        attempts += 1;
+    }
+
+    let mut loaded = simulation_app(GenerationConfig::default());
+    run_ticks(&mut loaded, 1, TICK_DURATION);
+    loaded.world.send_event(LoadGame { path: path.clone() });
+    run_ticks(&mut loaded, 1, TICK_DURATION);
+    let restored = SavedWorld::capture(&mut loaded.world);
+
+    assert_eq!(static_records(&restored), static_records(&saved));
+    // The tick and the calendar advance once more after loading
+    assert_eq!(restored.tick(), saved.tick() + 1);
+    let day_length_in_ticks = 60. / TICK_DURATION.as_secs_f32();
+    assert!(
+        (restored.elapsed_days() - saved.elapsed_days() - 1. / day_length_in_ticks).abs() < 1e-4
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn snapshots_rewind_the_simulation() {
+    let mut app = simulation_app(GenerationConfig::default());
+    run_ticks(&mut app, TICKS_BEFORE_SAVE, TICK_DURATION);
+    let checkpoint = save_snapshot(&mut app.world);
+
+    run_ticks(&mut app, TICKS_BEFORE_SAVE, TICK_DURATION);
+    let moved_on = SavedWorld::capture(&mut app.world);
+    assert!(moved_on.elapsed_days() > checkpoint.saved_world().elapsed_days());
+
+    restore_snapshot(&mut app.world, &checkpoint);
+    let restored = SavedWorld::capture(&mut app.world);
+
+    assert_eq!(restored.tick(), checkpoint.tick());
+    assert_eq!(
+        restored.elapsed_days(),
+        checkpoint.saved_world().elapsed_days()
+    );
+    // Including the weather, crafting progress and the energy of every organism
+    assert_eq!(restored.to_string(), checkpoint.saved_world().to_string());
+}
+
+#[test]
+fn remains_survive_restoring() {
+    let mut app = simulation_app(GenerationConfig::default());
+    run_ticks(&mut app, 1, TICK_DURATION);
+    let mut saved = SavedWorld::capture(&mut app.world);
+    saved.remains = vec![SavedRemains {
+        tile_pos: TilePos::ORIGIN,
+        corpses: 2,
+    }];
+    let checkpoint = Checkpoint::from(saved);
+
+    // Restoring twice checks that the remains restored the first time are cleared away again
+    restore_snapshot(&mut app.world, &checkpoint);
+    restore_snapshot(&mut app.world, &checkpoint);
+    let restored = SavedWorld::capture(&mut app.world);
+
+    assert_eq!(restored.remains, checkpoint.saved_world().remains);
+    let remains_index = &app.world.resource::<MapGeometry>().remains_index;
+    assert_eq!(remains_index.len(), 1);
+    assert!(remains_index.contains_key(&TilePos::ORIGIN));
+}
@@ -0,0 +1,44 @@
+//! Inspects the signal field using only the public API, as external tools and debug UIs do.
+
+use emergence_lib::signals::{SignalStrength, SignalType, Signals};
+use emergence_lib::simulation::geometry::TilePos;
+
+#[test]
+fn signals_can_be_read_from_outside_the_crate() {
+    let mut signals = Signals::default();
+    let home = TilePos::new(0, 0);
+    let away = TilePos::new(3, -2);
+
+    signals.add_signal(SignalType::Alarm, home, SignalStrength::new(2.));
+    signals.add_signal(SignalType::Alarm, away, SignalStrength::new(5.));
+    signals.add_signal(SignalType::Danger, home, SignalStrength::new(1.));
+
+    assert_eq!(signals.get(SignalType::Alarm, away).value(), 5.);
+    assert_eq!(signals.max_strength(SignalType::Alarm).value(), 5.);
+    assert_eq!(
+        signals.signal_types(),
+        vec![SignalType::Alarm, SignalType::Danger]
+    );
+
+    let mut alarm_tiles: Vec<TilePos> = signals
+        .tiles_with(SignalType::Alarm)
+        .map(|(tile_pos, _strength)| tile_pos)
+        .collect();
+    alarm_tiles.sort_by_key(|tile_pos| (tile_pos.x, tile_pos.y));
+    assert_eq!(alarm_tiles, vec![home, away]);
+
+    let local_signals = signals.all_signals_at_position(home);
+    assert_eq!(local_signals.get(SignalType::Danger).value(), 1.);
+    assert_eq!(local_signals.iter().count(), 2);
+}
+
+#[test]
+fn missing_signals_are_empty() {
+    let signals = Signals::default();
+
+    assert_eq!(signals.tiles_with(SignalType::Alarm).count(), 0);
+    assert_eq!(
+        signals.get(SignalType::Alarm, TilePos::new(1, 1)),
+        SignalStrength::ZERO
+    );
+}
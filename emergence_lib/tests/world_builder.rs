@@ -0,0 +1,42 @@
+//! Builds small hand-made worlds, and checks that they contain exactly what was asked for.
+
+use bevy::prelude::*;
+use emergence_lib::asset_management::manifest::{Id, Structure, Unit};
+use emergence_lib::simulation::geometry::TilePos;
+use emergence_lib::terrain::Terrain;
+use emergence_lib::testing::{minimal_app, WorldBuilder};
+
+#[test]
+fn world_builder_spawns_requested_organisms() {
+    let mut app = minimal_app();
+    WorldBuilder::hex_radius(10)
+        .with_terrain_fill(Terrain::Plain)
+        .spawn_unit("ant", (0, 0))
+        .spawn_structure("ant_hive", (1, 0))
+        .build(&mut app);
+    app.update();
+
+    let terrain: Vec<Terrain> = app
+        .world
+        .query::<&Terrain>()
+        .iter(&app.world)
+        .copied()
+        .collect();
+    assert!(terrain.iter().all(|&terrain| terrain == Terrain::Plain));
+
+    let units: Vec<TilePos> = app
+        .world
+        .query_filtered::<&TilePos, With<Id<Unit>>>()
+        .iter(&app.world)
+        .copied()
+        .collect();
+    assert_eq!(units, vec![TilePos::new(0, 0)]);
+
+    let structures: Vec<TilePos> = app
+        .world
+        .query_filtered::<&TilePos, With<Id<Structure>>>()
+        .iter(&app.world)
+        .copied()
+        .collect();
+    assert_eq!(structures, vec![TilePos::new(1, 0)]);
+}
@@ -7,16 +7,15 @@ use syn::{Data, DeriveInput, Ident};
 /// This approach and implementation is inspired by the `strum` crate,
 /// Copyright (c) 2019 Peter Glotfelty
 /// available under the MIT License at <https://github.com/Peternator7/strum>
-
 pub(crate) fn iterable_enum_inner(ast: &DeriveInput) -> TokenStream {
     // Splitting the abstract syntax tree
     let enum_name = &ast.ident;
     let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
 
-    let crate_path = if let Ok(found_crate) = crate_name("emergence_lib") {
+    let crate_path = if let Ok(found_crate) = crate_name("emergence_core") {
         // The crate was found in the Cargo.toml
         match found_crate {
-            FoundCrate::Itself => quote!(emergence_lib),
+            FoundCrate::Itself => quote!(emergence_core),
             FoundCrate::Name(name) => {
                 let ident = Ident::new(&name, Span::call_site());
                 quote!(#ident)
@@ -27,11 +26,11 @@ pub(crate) fn iterable_enum_inner(ast: &DeriveInput) -> TokenStream {
         // so we assume that we are in the owning_crate itself
         //
         // In order for this to play nicely with unit tests within the crate itself,
-        // `use crate as emergence_lib` at the top of each test module
+        // `use crate as emergence_core` at the top of each test module
         //
         // Note that doc tests, integration tests and examples want the full standard import,
         // as they are evaluated as if they were external
-        quote!(emergence_lib)
+        quote!(emergence_core)
     };
 
     let variants = match &ast.data {
@@ -52,8 +51,8 @@ pub(crate) fn iterable_enum_inner(ast: &DeriveInput) -> TokenStream {
             syn::Fields::Unit => quote! {},
             // Use the default values for tuple-like fields
             syn::Fields::Unnamed(fields) => {
-                let defaults = ::std::iter::repeat(quote!(::core::default::Default::default()))
-                    .take(fields.unnamed.len());
+                let defaults =
+                    (0..fields.unnamed.len()).map(|_| quote!(::core::default::Default::default()));
                 quote! { (#(#defaults),*) }
             }
             // Use the default values for tuple-like fields
@@ -71,7 +70,7 @@ pub(crate) fn iterable_enum_inner(ast: &DeriveInput) -> TokenStream {
             syn::Fields::Unit => quote! {},
             // Use the default values for tuple-like fields
             syn::Fields::Unnamed(fields) => {
-                let underscores = ::std::iter::repeat(quote!(_)).take(fields.unnamed.len());
+                let underscores = (0..fields.unnamed.len()).map(|_| quote!(_));
                 quote! { (#(#underscores),*) }
             }
             // Use the default values for tuple-like fields
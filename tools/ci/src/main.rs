@@ -79,6 +79,14 @@ fn main() {
         cmd!(sh, "cargo test --workspace --lib --bins --tests --benches")
             .run()
             .expect("Please fix failing tests in output above.");
+
+        // Mods are not enabled by default, but their loading is still tested
+        cmd!(
+            sh,
+            "cargo test --package emergence_core --lib --features mods"
+        )
+        .run()
+        .expect("Please fix failing tests with mods enabled in output above.");
     }
 
     if what_to_run.contains(Check::DOC_TEST) {
@@ -103,5 +111,15 @@ fn main() {
         cmd!(sh, "cargo check --workspace")
             .run()
             .expect("Please fix compiler errors in above output.");
+
+        // Make sure that the slimmest configuration still compiles
+        cmd!(sh, "cargo check --workspace --no-default-features")
+            .run()
+            .expect("Please fix compiler errors without default features in above output.");
+
+        // The examples are not run in CI, but should at least keep up with the public API
+        cmd!(sh, "cargo check --workspace --examples")
+            .run()
+            .expect("Please fix compiler errors in the examples in above output.");
     }
 }
@@ -0,0 +1,30 @@
+//! Python bindings for stepping and observing a headless Emergence simulation.
+//!
+//! The bindings are only compiled when the `python` feature is enabled,
+//! so that the rest of the workspace can be built without a Python toolchain.
+//! Build and install them into the active Python environment with `maturin develop`, then:
+//!
+//! ```python
+//! import emergence_py
+//! import numpy
+//!
+//! simulation = emergence_py.Simulation(map_radius=10, seed=42)
+//! simulation.step(100)
+//! observation = simulation.observe()
+//! checkpoint = simulation.save_snapshot()
+//! simulation.build("ant_hive", 2, -1)
+//! simulation.step(100)
+//! simulation.restore_snapshot(checkpoint)
+//!
+//! tick, shape, data = simulation.observe_tensor(["height", "units", "signal:Alarm"], radius=10)
+//! tensor = numpy.frombuffer(data, "<f4").reshape(shape)
+//! ```
+//!
+//! The underlying Rust interface is [`emergence_lib::research`].
+#![deny(missing_docs)]
+#![deny(clippy::missing_docs_in_private_items)]
+// The code generated by pyo3 relies on unsafe code
+#![cfg_attr(not(feature = "python"), forbid(unsafe_code))]
+
+#[cfg(feature = "python")]
+mod python;
@@ -0,0 +1,224 @@
+//! The Python module, generated with pyo3.
+
+use emergence_lib::{
+    research::{
+        tensor::{Channel, ObservationSpec},
+        Observation, Order, ResearchEnvironment,
+    },
+    simulation::{generation::GenerationConfig, save::Checkpoint},
+};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+use std::path::PathBuf;
+
+/// A saved state of a simulation, which it can be rewound to.
+#[pyclass(name = "Checkpoint")]
+struct PyCheckpoint {
+    /// The saved state, shared with every other copy of this checkpoint
+    checkpoint: Checkpoint,
+}
+
+#[pymethods]
+impl PyCheckpoint {
+    /// The tick on which the checkpoint was taken.
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.checkpoint.tick()
+    }
+}
+
+/// A headless simulation, which only advances when stepped.
+///
+/// Simulations cannot be shared between Python threads.
+#[pyclass(name = "Simulation", unsendable)]
+struct PySimulation {
+    /// The simulation being driven from Python
+    environment: ResearchEnvironment,
+}
+
+#[pymethods]
+impl PySimulation {
+    /// Creates a new simulation, optionally with a fixed `map_radius` and world generation `seed`.
+    #[new]
+    #[pyo3(signature = (map_radius = None, seed = None))]
+    fn new(map_radius: Option<u32>, seed: Option<u64>) -> Self {
+        let mut gen_config = GenerationConfig::default();
+        if let Some(map_radius) = map_radius {
+            gen_config = gen_config.with_map_radius(map_radius);
+        }
+        if let Some(seed) = seed {
+            gen_config = gen_config.with_seed(seed);
+        }
+
+        PySimulation {
+            environment: ResearchEnvironment::new(gen_config),
+        }
+    }
+
+    /// Advances the simulation by `frames` frames.
+    #[pyo3(signature = (frames = 1))]
+    fn step(&mut self, frames: u32) {
+        self.environment.step(frames);
+    }
+
+    /// The number of frames that the simulation has advanced.
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.environment.tick()
+    }
+
+    /// Records the current state of the simulation in memory, returning a `Checkpoint`.
+    fn save_snapshot(&mut self) -> PyCheckpoint {
+        PyCheckpoint {
+            checkpoint: self.environment.save_snapshot(),
+        }
+    }
+
+    /// Rewinds the simulation to the state recorded in the `checkpoint`.
+    fn restore_snapshot(&mut self, checkpoint: &PyCheckpoint) {
+        self.environment.restore_snapshot(&checkpoint.checkpoint);
+    }
+
+    /// Reports the current state of the world as a dictionary of `tick`, `tiles`, `signals` and `census`.
+    fn observe(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        observation_to_dict(py, &self.environment.observe())
+    }
+
+    /// Observes only the `channels` within `radius` tiles of the origin, averaging squares of `cell_size` tiles.
+    ///
+    /// Returns a tuple of `(tick, shape, data)`, where `data` holds little-endian `f32`s in channel-major order:
+    /// `numpy.frombuffer(data, "<f4").reshape(shape)`.
+    #[pyo3(signature = (channels, radius, cell_size = 1))]
+    fn observe_tensor<'py>(
+        &mut self,
+        py: Python<'py>,
+        channels: Vec<String>,
+        radius: u32,
+        cell_size: u32,
+    ) -> PyResult<(u64, (usize, usize, usize), &'py PyBytes)> {
+        let spec = observation_spec(channels, radius, cell_size)?;
+        let observation = self.environment.observe_tensor(&spec);
+        let [n_channels, rows, columns] = observation.shape;
+        let data: Vec<u8> = observation
+            .data
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+
+        Ok((
+            observation.tick,
+            (n_channels, rows, columns),
+            PyBytes::new(py, &data),
+        ))
+    }
+
+    /// Writes the `channels` within `radius` tiles of the origin to the file at `path` after every tick.
+    ///
+    /// The data begins after a 20 byte header: `numpy.fromfile(path, "<f4", offset=20)`.
+    #[pyo3(signature = (channels, radius, path, cell_size = 1))]
+    fn export_observations(
+        &mut self,
+        channels: Vec<String>,
+        radius: u32,
+        path: PathBuf,
+        cell_size: u32,
+    ) -> PyResult<()> {
+        let spec = observation_spec(channels, radius, cell_size)?;
+        self.environment.export_observations(Some((spec, path)));
+        Ok(())
+    }
+
+    /// Stops writing observations started by `export_observations`.
+    fn stop_exporting(&mut self) {
+        self.environment.export_observations(None);
+    }
+
+    /// Orders the colony to build `structure` on the tile at (`x`, `y`).
+    fn build(&mut self, structure: String, x: i32, y: i32) -> PyResult<()> {
+        self.submit(Order::Build { structure, x, y })
+    }
+
+    /// Orders the colony to demolish anything on the tile at (`x`, `y`), and keep it clear.
+    fn demolish(&mut self, x: i32, y: i32) -> PyResult<()> {
+        self.submit(Order::Demolish { x, y })
+    }
+
+    /// Cancels any previous order for the tile at (`x`, `y`).
+    fn clear(&mut self, x: i32, y: i32) -> PyResult<()> {
+        self.submit(Order::Clear { x, y })
+    }
+}
+
+impl PySimulation {
+    /// Submits the `order`, raising a `ValueError` if it is refused.
+    fn submit(&mut self, order: Order) -> PyResult<()> {
+        self.environment
+            .submit(&order)
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+}
+
+/// Builds an [`ObservationSpec`] from the arguments given in Python.
+fn observation_spec(
+    channels: Vec<String>,
+    radius: u32,
+    cell_size: u32,
+) -> PyResult<ObservationSpec> {
+    let channels = channels
+        .iter()
+        .map(|channel| channel.parse::<Channel>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| PyValueError::new_err(error.to_string()))?;
+
+    Ok(ObservationSpec::new(channels, radius).with_cell_size(cell_size))
+}
+
+/// Converts an [`Observation`] into nested Python dictionaries and lists.
+fn observation_to_dict(py: Python<'_>, observation: &Observation) -> PyResult<PyObject> {
+    let tiles = observation
+        .tiles
+        .iter()
+        .map(|tile| {
+            let dict = PyDict::new(py);
+            dict.set_item("x", tile.x)?;
+            dict.set_item("y", tile.y)?;
+            dict.set_item("terrain", &tile.terrain)?;
+            dict.set_item("height", tile.height)?;
+            dict.set_item("structure", &tile.structure)?;
+            dict.set_item("ghost", &tile.ghost)?;
+            dict.set_item("units", tile.units)?;
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let signals = observation
+        .signals
+        .iter()
+        .map(|signal| {
+            let dict = PyDict::new(py);
+            dict.set_item("x", signal.x)?;
+            dict.set_item("y", signal.y)?;
+            dict.set_item("signal_type", &signal.signal_type)?;
+            dict.set_item("strength", signal.strength)?;
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("tick", observation.tick)?;
+    dict.set_item("tiles", tiles)?;
+    dict.set_item("signals", signals)?;
+    dict.set_item("census", observation.census.clone())?;
+    Ok(dict.into())
+}
+
+/// Steps and observes headless Emergence simulations.
+#[pymodule]
+fn emergence_py(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PySimulation>()?;
+    module.add_class::<PyCheckpoint>()?;
+    Ok(())
+}
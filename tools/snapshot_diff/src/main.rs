@@ -0,0 +1,32 @@
+//! Prints the differences between two simulation snapshots.
+//!
+//! Snapshots are saved periodically when `SnapshotConfig::interval` is set.
+//!
+//! Usage: `cargo run -p snapshot_diff -- <before> <after>`
+
+use emergence_core::simulation::snapshot::{Snapshot, SnapshotDiff};
+use std::{env, error::Error, fs, process};
+
+/// Reads and parses the snapshot saved at `path`.
+fn read_snapshot(path: &str) -> Result<Snapshot, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents.parse()?)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("Usage: snapshot_diff <before> <after>");
+        process::exit(2);
+    }
+
+    let snapshots =
+        read_snapshot(&args[0]).and_then(|before| Ok((before, read_snapshot(&args[1])?)));
+    match snapshots {
+        Ok((before, after)) => print!("{}", SnapshotDiff::between(&before, &after)),
+        Err(error) => {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+    }
+}